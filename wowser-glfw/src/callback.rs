@@ -1,5 +1,6 @@
-use crate::{get_glfw_result, GlfwResult, Window};
+use crate::{get_glfw_result, GlfwResult, InputEvent, Window};
 use libc::c_void;
+use std::os::raw::c_int;
 use wowser_glfw_sys::*;
 
 fn get_window_from_glfw_user_pointer(window: *mut GLFWwindow) -> Option<&'static mut Window> {
@@ -37,10 +38,73 @@ fn on_window_move_callback(window: *mut GLFWwindow, x: i32, y: i32) {
     }
 }
 
+unsafe extern "C" fn unsafe_on_cursor_pos_callback(window: *mut GLFWwindow, x: f64, y: f64) {
+    on_cursor_pos_callback(window, x, y);
+}
+
+fn on_cursor_pos_callback(window: *mut GLFWwindow, x: f64, y: f64) {
+    if let Some(window) = get_window_from_glfw_user_pointer(window) {
+        window.cursor_pos = (x, y);
+        window.input_events.push(InputEvent::CursorMove { x, y });
+    }
+}
+
+unsafe extern "C" fn unsafe_on_mouse_button_callback(
+    window: *mut GLFWwindow,
+    button: c_int,
+    action: c_int,
+    mods: c_int,
+) {
+    on_mouse_button_callback(window, button, action, mods);
+}
+
+fn on_mouse_button_callback(window: *mut GLFWwindow, button: c_int, action: c_int, mods: c_int) {
+    if let Some(window) = get_window_from_glfw_user_pointer(window) {
+        let (x, y) = window.cursor_pos;
+        window.input_events.push(InputEvent::MouseButton {
+            button,
+            action: action.into(),
+            modifiers: mods.into(),
+            x,
+            y,
+        });
+    }
+}
+
+unsafe extern "C" fn unsafe_on_key_callback(
+    window: *mut GLFWwindow,
+    key: c_int,
+    scancode: c_int,
+    action: c_int,
+    mods: c_int,
+) {
+    on_key_callback(window, key, scancode, action, mods);
+}
+
+fn on_key_callback(
+    window: *mut GLFWwindow,
+    key: c_int,
+    scancode: c_int,
+    action: c_int,
+    mods: c_int,
+) {
+    if let Some(window) = get_window_from_glfw_user_pointer(window) {
+        window.input_events.push(InputEvent::Key {
+            key,
+            scancode,
+            action: action.into(),
+            modifiers: mods.into(),
+        });
+    }
+}
+
 pub(super) fn initialize_glfw_callbacks(window: &mut Window) -> GlfwResult {
     let glfw_window_ptr = window.get_glfw_window_ptr();
     unsafe { glfwSetWindowSizeCallback(glfw_window_ptr, Some(unsafe_on_window_resize_callback)) };
     unsafe { glfwSetWindowPosCallback(glfw_window_ptr, Some(unsafe_on_window_move_callback)) };
+    unsafe { glfwSetCursorPosCallback(glfw_window_ptr, Some(unsafe_on_cursor_pos_callback)) };
+    unsafe { glfwSetMouseButtonCallback(glfw_window_ptr, Some(unsafe_on_mouse_button_callback)) };
+    unsafe { glfwSetKeyCallback(glfw_window_ptr, Some(unsafe_on_key_callback)) };
 
     get_glfw_result()
 }
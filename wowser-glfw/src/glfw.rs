@@ -1,9 +1,9 @@
 use super::{
     get_error, get_result_if_not_error, initialize_glfw_callbacks, GlfwError, GlfwResult,
-    WindowHint,
+    InputEvent, WindowHint,
 };
 use libc::c_void;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_int;
 use std::ptr::{self, NonNull};
 use wowser_glfw_sys::*;
@@ -42,6 +42,9 @@ pub struct Window {
     is_alive: bool,
     pub(crate) window_resize_event: Option<(i32, i32)>,
     pub(crate) window_move_event: Option<(i32, i32)>,
+    pub(crate) input_events: Vec<InputEvent>,
+    /// The last position of the cursor, which mouse button events happen at
+    pub(crate) cursor_pos: (f64, f64),
 }
 
 impl Window {
@@ -74,6 +77,11 @@ impl Window {
         self.window_move_event.take()
     }
 
+    /// The mouse and keyboard input received since the last call, in the order received
+    pub fn take_input_events(&mut self) -> Vec<InputEvent> {
+        std::mem::take(&mut self.input_events)
+    }
+
     pub fn set_window_size(&self, width: i32, height: i32) -> GlfwResult {
         set_window_size(self, width, height)
     }
@@ -148,6 +156,8 @@ pub fn create_window(
             is_alive: true,
             window_resize_event: None,
             window_move_event: None,
+            input_events: vec![],
+            cursor_pos: (0.0, 0.0),
         }))
     } else {
         Err(get_error())
@@ -240,6 +250,17 @@ pub fn wait_events() -> GlfwResult {
     get_glfw_result()
 }
 
+/// The layout specific name of a printable key, such as `a` for `GLFW_KEY_A` on a QWERTY keyboard.
+/// Keys that are not printable have no name.
+pub fn get_key_name(key: c_int, scancode: c_int) -> Option<String> {
+    let name = unsafe { glfwGetKeyName(key, scancode) };
+    if name.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
 pub fn get_glfw_result() -> GlfwResult {
     match get_error() {
         GlfwError::NoError => Ok(()),
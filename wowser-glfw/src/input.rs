@@ -0,0 +1,83 @@
+use std::os::raw::c_int;
+use wowser_glfw_sys::*;
+
+/// Input received by a window since its events were last taken
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// The cursor moved to a position relative to the top left of the content area
+    CursorMove { x: f64, y: f64 },
+    /// A mouse button changed state with the cursor at a position relative to the top left of the
+    /// content area. Buttons are numbered from `GLFW_MOUSE_BUTTON_1`, the left button.
+    MouseButton {
+        button: c_int,
+        action: InputAction,
+        modifiers: Modifiers,
+        x: f64,
+        y: f64,
+    },
+    /// A key changed state, identified by its `GLFW_KEY_*` constant and its platform specific
+    /// scancode
+    Key {
+        key: c_int,
+        scancode: c_int,
+        action: InputAction,
+        modifiers: Modifiers,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputAction {
+    Press,
+    Release,
+    Repeat,
+}
+
+impl From<c_int> for InputAction {
+    fn from(action: c_int) -> InputAction {
+        match action as u32 {
+            GLFW_PRESS => InputAction::Press,
+            GLFW_REPEAT => InputAction::Repeat,
+            _ => InputAction::Release,
+        }
+    }
+}
+
+/// The modifier keys held during input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub super_key: bool,
+}
+
+impl From<c_int> for Modifiers {
+    fn from(mods: c_int) -> Modifiers {
+        let mods = mods as u32;
+        Modifiers {
+            shift: mods & GLFW_MOD_SHIFT != 0,
+            control: mods & GLFW_MOD_CONTROL != 0,
+            alt: mods & GLFW_MOD_ALT != 0,
+            super_key: mods & GLFW_MOD_SUPER != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers_from_mods() {
+        assert_eq!(Modifiers::from(0), Modifiers::default());
+        assert_eq!(
+            Modifiers::from((GLFW_MOD_SHIFT | GLFW_MOD_ALT) as c_int),
+            Modifiers {
+                shift: true,
+                control: false,
+                alt: true,
+                super_key: false,
+            }
+        );
+    }
+}
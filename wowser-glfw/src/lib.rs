@@ -1,12 +1,14 @@
 mod callback;
 mod error;
 mod glfw;
+mod input;
 mod ptr_holder;
 mod window_hint;
 
 use callback::*;
 pub use error::*;
 pub use glfw::*;
+pub use input::*;
 pub use window_hint::*;
 
 /// Used to convert a callback for usage with this module's set_error_callback
@@ -0,0 +1,359 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    meta::ParseNestedMeta, parse::Parser, spanned::Spanned, Attribute, Error, FnArg, Ident,
+    ImplItem, ItemFn, ItemImpl, LitStr, Meta, Result, ReturnType, Signature, Type,
+};
+
+const JS_METHOD_ATTRIBUTE: &str = "js_method";
+
+/// How a function marked with `#[js_method]` is exposed
+#[derive(Clone, Copy, PartialEq)]
+enum MethodKind {
+    Method,
+    Getter,
+    Setter,
+}
+
+struct MethodOptions {
+    kind: MethodKind,
+    name: Option<String>,
+}
+
+impl MethodOptions {
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("getter") {
+            self.kind = MethodKind::Getter;
+        } else if meta.path.is_ident("setter") {
+            self.kind = MethodKind::Setter;
+        } else {
+            return Err(meta.error("expected `name`, `getter`, or `setter`"));
+        }
+        Ok(())
+    }
+
+    fn from_attribute(attr: &Attribute) -> Result<Self> {
+        let mut options = Self {
+            kind: MethodKind::Method,
+            name: None,
+        };
+        if let Meta::List(_) = attr.meta {
+            attr.parse_nested_meta(|meta| options.parse_meta(meta))?;
+        }
+        Ok(options)
+    }
+
+    /// Methods and getters are named after the function in camel case, while setters drop the
+    /// `set_` prefix
+    fn js_name(&self, ident: &Ident) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let name = ident.to_string();
+        let name = match self.kind {
+            MethodKind::Setter => name.strip_prefix("set_").unwrap_or(&name),
+            MethodKind::Method | MethodKind::Getter => &name,
+        };
+        to_camel_case(name)
+    }
+}
+
+fn to_camel_case(name: &str) -> String {
+    let mut words = name.split('_').filter(|word| !word.is_empty());
+    let mut camel_case = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel_case.extend(first.to_uppercase());
+            camel_case.push_str(chars.as_str());
+        }
+    }
+    camel_case
+}
+
+pub fn js_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `name`"))
+        }
+    });
+    if let Err(error) = parser.parse(attr) {
+        return error.to_compile_error().into();
+    }
+
+    let item_impl = match syn::parse::<ItemImpl>(item) {
+        Ok(item_impl) => item_impl,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    match impl_js_class(item_impl, name) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn impl_js_class(mut item_impl: ItemImpl, name: Option<String>) -> Result<TokenStream2> {
+    let self_ty = item_impl.self_ty.clone();
+    let name = match name {
+        Some(name) => name,
+        None => match self_ty.as_ref() {
+            Type::Path(path) => path.path.segments.last().unwrap().ident.to_string(),
+            ty => return Err(Error::new(ty.span(), "expected `name` for this type")),
+        },
+    };
+
+    let mut methods = vec![];
+    // (Name, Getter, Setter)
+    let mut accessors: Vec<(String, Option<TokenStream2>, Option<TokenStream2>)> = vec![];
+    for item in &mut item_impl.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let Some(index) = method
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident(JS_METHOD_ATTRIBUTE))
+        else {
+            continue;
+        };
+        let attr = method.attrs.remove(index);
+        let options = MethodOptions::from_attribute(&attr)?;
+        let js_name = options.js_name(&method.sig.ident);
+        let binding = build_method_binding(&method.sig, &self_ty)?;
+
+        match options.kind {
+            MethodKind::Method => methods.push(quote! { (#js_name, #binding) }),
+            MethodKind::Getter | MethodKind::Setter => {
+                let index = match accessors.iter().position(|(name, ..)| *name == js_name) {
+                    Some(index) => index,
+                    None => {
+                        accessors.push((js_name.clone(), None, None));
+                        accessors.len() - 1
+                    }
+                };
+                let (_name, get, set) = &mut accessors[index];
+                let accessor = if options.kind == MethodKind::Getter {
+                    get
+                } else {
+                    set
+                };
+                if accessor.is_some() {
+                    return Err(Error::new(attr.span(), "accessor is already defined"));
+                }
+                *accessor = Some(binding);
+            }
+        }
+    }
+
+    let accessors = accessors.into_iter().map(|(name, get, set)| {
+        let get = option_tokens(get);
+        let set = option_tokens(set);
+        quote! { (#name, #get, #set) }
+    });
+    Ok(quote! {
+        #item_impl
+
+        impl crate::js::JsHostClass for #self_ty {
+            const NAME: &'static str = #name;
+
+            fn build_prototype(parent: crate::js::JsValueNode) -> crate::js::JsValueNode {
+                crate::js::build_host_prototype(
+                    parent,
+                    vec![#(#methods),*],
+                    vec![#(#accessors),*],
+                )
+            }
+        }
+    })
+}
+
+fn option_tokens(tokens: Option<TokenStream2>) -> TokenStream2 {
+    match tokens {
+        Some(tokens) => quote! { Some(#tokens) },
+        None => quote! { None },
+    }
+}
+
+pub fn js_method(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(
+            Span::call_site(),
+            "options are only supported on methods of a `#[js_class]`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let item_fn = match syn::parse::<ItemFn>(item) {
+        Ok(item_fn) => item_fn,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    match impl_js_function(item_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Keeps the function, adding a native function named after it with a `js_` prefix
+fn impl_js_function(item_fn: ItemFn) -> Result<TokenStream2> {
+    let vis = &item_fn.vis;
+    let ident = &item_fn.sig.ident;
+    let binding_ident = format_ident!("js_{}", ident);
+    let Arguments {
+        conversions,
+        call_args,
+        has_receiver,
+        ..
+    } = Arguments::from_signature(&item_fn.sig);
+    if has_receiver {
+        return Err(Error::new(
+            item_fn.sig.span(),
+            "`#[js_method]` functions outside a `#[js_class]` cannot take `self`",
+        ));
+    }
+    let unwrap_result = build_unwrap_result(&item_fn.sig);
+    Ok(quote! {
+        #item_fn
+
+        #vis fn #binding_ident(
+            closure_context: &mut crate::js::JsClosureContext,
+            _this: crate::js::JsValueNode,
+            args: &[crate::js::JsValueNode],
+        ) -> crate::js::JsFunctionResult {
+            #(#conversions)*
+            let result = #ident(#(#call_args),*);
+            #unwrap_result
+            Ok(crate::js::IntoJsValue::into_js_value(result, closure_context))
+        }
+    })
+}
+
+/// A native function calling the method on the host object `this` refers to
+fn build_method_binding(sig: &Signature, self_ty: &Type) -> Result<TokenStream2> {
+    let ident = &sig.ident;
+    let Arguments {
+        conversions,
+        call_args,
+        has_receiver,
+        uses_context,
+    } = Arguments::from_signature(sig);
+    if !has_receiver {
+        return Err(Error::new(
+            sig.span(),
+            "`#[js_method]` methods must take `&self` or `&mut self`",
+        ));
+    }
+    let context_ident = if uses_context {
+        quote! { closure_context }
+    } else {
+        quote! { _closure_context }
+    };
+    let unwrap_result = build_unwrap_result(sig);
+    Ok(quote! {
+        |closure_context: &mut crate::js::JsClosureContext,
+         this: crate::js::JsValueNode,
+         args: &[crate::js::JsValueNode]|
+         -> crate::js::JsFunctionResult {
+            #(#conversions)*
+            let result = crate::js::with_host_object(
+                closure_context,
+                &this,
+                |object: &mut #self_ty, #context_ident| object.#ident(#(#call_args),*),
+            )?;
+            #unwrap_result
+            Ok(crate::js::IntoJsValue::into_js_value(result, closure_context))
+        }
+    })
+}
+
+/// How the JS arguments are passed to a function. Parameters of type `&mut JsClosureContext` are
+/// passed the closure context, while the others are converted from the JS arguments in order.
+struct Arguments {
+    conversions: Vec<TokenStream2>,
+    call_args: Vec<TokenStream2>,
+    has_receiver: bool,
+    uses_context: bool,
+}
+
+impl Arguments {
+    fn from_signature(sig: &Signature) -> Self {
+        let mut arguments = Self {
+            conversions: vec![],
+            call_args: vec![],
+            has_receiver: false,
+            uses_context: false,
+        };
+        for input in &sig.inputs {
+            let pat_type = match input {
+                FnArg::Receiver(_) => {
+                    arguments.has_receiver = true;
+                    continue;
+                }
+                FnArg::Typed(pat_type) => pat_type,
+            };
+            if is_closure_context(&pat_type.ty) {
+                arguments.uses_context = true;
+                arguments.call_args.push(quote! { closure_context });
+                continue;
+            }
+            let index = arguments.conversions.len();
+            let arg_ident = format_ident!("arg{}", index);
+            let ty = &pat_type.ty;
+            arguments.conversions.push(quote! {
+                let #arg_ident = <#ty as crate::js::FromJsValue>::from_js_argument(
+                    args,
+                    #index,
+                    closure_context,
+                )?;
+            });
+            arguments.call_args.push(quote! { #arg_ident });
+        }
+        arguments
+    }
+}
+
+fn is_closure_context(ty: &Type) -> bool {
+    let Type::Reference(reference) = ty else {
+        return false;
+    };
+    let Type::Path(path) = reference.elem.as_ref() else {
+        return false;
+    };
+    reference.mutability.is_some()
+        && path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "JsClosureContext")
+}
+
+/// Functions returning a `Result` throw their errors
+fn build_unwrap_result(sig: &Signature) -> TokenStream2 {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return quote! {};
+    };
+    let Type::Path(path) = ty.as_ref() else {
+        return quote! {};
+    };
+    match path.path.segments.last() {
+        Some(segment) if segment.ident == "Result" => quote! { let result = result?; },
+        _ => quote! {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_camel_case() {
+        assert_eq!(to_camel_case("get_item"), "getItem");
+        assert_eq!(to_camel_case("length"), "length");
+        assert_eq!(to_camel_case("to_iso_string"), "toIsoString");
+    }
+}
@@ -1,6 +1,7 @@
 extern crate proc_macro;
 extern crate syn;
 
+mod js_bindings;
 mod macros;
 
 use proc_macro::TokenStream;
@@ -9,3 +10,23 @@ use proc_macro::TokenStream;
 pub fn display_from_debug_derive(input: TokenStream) -> TokenStream {
     macros::display_from_debug_derive(input)
 }
+
+/// Exposes the methods of an impl block marked with `#[js_method]` to JS by implementing
+/// `JsHostClass` for its type. The class is named after the type unless given a `name`.
+///
+/// Methods take `&self` or `&mut self` and are named after the function in camel case, unless given
+/// a `name`. `#[js_method(getter)]` and `#[js_method(setter)]` define accessors instead, where
+/// setters drop the `set_` prefix of their name.
+#[proc_macro_attribute]
+pub fn js_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+    js_bindings::js_class(attr, item)
+}
+
+/// Adds a native function calling the function, named after it with a `js_` prefix. Parameters
+/// are converted from the JS arguments with `FromJsValue`, other than a `&mut JsClosureContext`,
+/// and the result is converted with `IntoJsValue`. Errors of functions returning a `Result` are
+/// thrown.
+#[proc_macro_attribute]
+pub fn js_method(attr: TokenStream, item: TokenStream) -> TokenStream {
+    js_bindings::js_method(attr, item)
+}
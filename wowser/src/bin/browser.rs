@@ -1,9 +1,10 @@
-use std::env;
+use std::{cell::RefCell, env, path::PathBuf, rc::Rc, thread};
 
 use wowser::browser::Tab;
-use wowser::net::{HttpRequest, Url};
-use wowser::ui::Window;
-use wowser::{log, startup};
+use wowser::js::api::storage::StorageProfile;
+use wowser::net::Url;
+use wowser::startup;
+use wowser::ui::{UiEventProcessor, Window};
 use wowser_glfw as glfw;
 
 fn main() {
@@ -14,24 +15,19 @@ fn main() {
 
 fn run(url: &str) {
     let url = Url::parse(url).expect("Invalid URL provided");
-    let request = HttpRequest::new(url);
-    let response = futures::executor::block_on(request.get()).expect("Failed to load HTML page");
-    if !response.status.contains_success_content() {
-        log!(ERROR: "Invalid response", String::from_utf8_lossy(&response.body));
-        return;
-    }
-
-    let html = std::str::from_utf8(&response.body).expect("Invalid HTML Encoding");
 
     startup::start();
     {
         let window_rc = Window::new().unwrap();
         let mut window = window_rc.borrow_mut();
 
-        let mut tab = Tab::load(&mut window, html);
+        let profile = Rc::new(RefCell::new(StorageProfile::new(&get_profile_directory())));
+        let mut tab = Tab::new(&mut window, Some(profile));
+        tab.navigate(&url);
 
         loop {
             glfw::poll_events().unwrap();
+            tab.process_events().expect("Failed to process events");
             tab.window
                 .check_for_updates()
                 .expect("Error while checking UI updates");
@@ -39,6 +35,21 @@ fn run(url: &str) {
                 break;
             }
             tab.render();
+            // Rendering only does work when the page changed, so wait for the next frame rather
+            // than polling again right away
+            thread::sleep(tab.get_time_until_next_frame());
         }
     }
 }
+
+/// Where the profile keeps local storage, which is `WOWSER_PROFILE` when set and `~/.wowser`
+/// otherwise
+fn get_profile_directory() -> PathBuf {
+    if let Ok(directory) = env::var("WOWSER_PROFILE") {
+        return PathBuf::from(directory);
+    }
+    env::var("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| env::temp_dir())
+        .join(".wowser")
+}
@@ -0,0 +1,187 @@
+use wowser::js::{parse_js_statements, JsDocument, JsStatementResult};
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+/// Runs the file passed in, or reads lines to run interactively when no file is passed in. Files
+/// ending in `.mjs`, or passed in after `--module`, are run as modules.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("--module"), Some(path)) => run_module(path),
+        (Some(path), None) if path.ends_with(".mjs") => run_module(path),
+        (Some(path), None) => run_file(path),
+        (_, _) => run_repl(),
+    }
+}
+
+/// Runs the file as a classic script, which stops at the first exception it does not catch
+fn run_file(path: &str) {
+    let script = fs::read_to_string(path).expect("Unable to read file");
+    let mut document = JsDocument::new(vec![]);
+    match document.run_script(&script) {
+        Ok(value) => println!("{}", value.get_ref().to_string()),
+        Err(value) => {
+            eprintln!("Uncaught {}", value.get_ref().to_string());
+            process::exit(1);
+        }
+    }
+}
+
+/// Runs a module along with the modules it imports from the filesystem, relative to its path
+fn run_module(path: &str) {
+    let path = fs::canonicalize(path).expect("Unable to read file");
+    let url = format!("file://{}", path.display());
+    let mut document = JsDocument::new(vec![]);
+    document.global_closure_context.modules.base_url = Some(url.clone());
+    if let Err(value) = document.run_module(&url) {
+        eprintln!("Uncaught {}", value.get_ref().to_string());
+        process::exit(1);
+    }
+    let results = &document.global_closure_context.expression_results;
+    println!("{}", format_result(results.last()));
+}
+
+fn run_repl() {
+    let mut document = JsDocument::new(vec![]);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut source = String::new();
+
+    loop {
+        print!("{}", if source.is_empty() { "> " } else { "... " });
+        io::stdout().flush().expect("Unable to write prompt");
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        if source.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ".exit" => break,
+                ".gc" => {
+                    document.collect_garbage();
+                    print_heap_size(&document);
+                    continue;
+                }
+                ".heap" => {
+                    print_heap_size(&document);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        source.push_str(&line);
+        source.push('\n');
+        let node_graph = &document.global_closure_context.nodes_graph;
+        match parse_js_statements(&source, node_graph) {
+            Ok(_) => {
+                // Results of earlier lines are dropped so they can be garbage collected
+                document.global_closure_context.expression_results.clear();
+                match document.run_script(&source) {
+                    Ok(value) => println!("{}", value.get_ref().to_string()),
+                    Err(value) => eprintln!("Uncaught {}", value.get_ref().to_string()),
+                }
+            }
+            Err(_) if is_incomplete(&source) => continue,
+            Err(error) => eprintln!("SyntaxError: {error}"),
+        }
+        source.clear();
+    }
+}
+
+fn format_result(result: Option<&JsStatementResult>) -> String {
+    match result {
+        Some(
+            JsStatementResult::Value(value)
+            | JsStatementResult::ReturnValue(value)
+            | JsStatementResult::ThrowValue(value),
+        ) => value.get_ref().to_string(),
+        _ => "undefined".to_string(),
+    }
+}
+
+fn print_heap_size(document: &JsDocument) {
+    let size = document.global_closure_context.nodes_graph.borrow().size();
+    println!("{size} nodes");
+}
+
+/// Checks if the source ends inside a string or comment or before its brackets are closed, in
+/// which case more lines are read before it is parsed again. Brackets in comments and regular
+/// expression literals are not counted.
+fn is_incomplete(source: &str) -> bool {
+    let mut depth = 0;
+    let mut chars = source.chars().peekable();
+    // The last character outside of whitespace, strings and comments, which tells whether a `/`
+    // divides or starts a regular expression
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                let mut escaped = false;
+                let is_closed = chars.by_ref().any(|next| {
+                    let is_quote = next == c && !escaped;
+                    escaped = next == '\\' && !escaped;
+                    is_quote
+                });
+                if !is_closed {
+                    return true;
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&next| next == '\n');
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut star = false;
+                let is_closed = chars.by_ref().any(|next| {
+                    let is_end = star && next == '/';
+                    star = next == '*';
+                    is_end
+                });
+                if !is_closed {
+                    return true;
+                }
+                continue;
+            }
+            '/' if can_start_regex(previous) => {
+                skip_regex_literal(&mut chars);
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = Some(c);
+        }
+    }
+    depth > 0
+}
+
+/// Whether a `/` after the character starts a regular expression rather than dividing, which it
+/// does at the start of the source and after operators and opening brackets
+fn can_start_regex(previous: Option<char>) -> bool {
+    previous.map_or(true, |previous| "(,=:[!&|?{};+-*%<>~^".contains(previous))
+}
+
+/// Skips past the closing `/` of a regular expression literal, which cannot span lines. A `/` in
+/// a character class does not close it.
+fn skip_regex_literal(chars: &mut impl Iterator<Item = char>) {
+    let mut escaped = false;
+    let mut in_class = false;
+    for c in chars {
+        match c {
+            '\n' => return,
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{rc::Rc, time::Duration};
+
+use crate::js::api::date::{Clock, Date};
+
+/// The least time between frames in milliseconds, which is how often most displays refresh
+pub const FRAME_INTERVAL: f64 = 1000.0 / 60.0;
+
+/// What a frame of the tab is drawn from, so it is only drawn again when one of them changed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameInputs {
+    /// The version of the document, which changes with the DOM
+    pub document_version: u64,
+    pub width: i32,
+    pub height: i32,
+    pub scroll_y: f32,
+}
+
+/// Decides when the tab starts a frame, which is when what it shows changed or scripts asked for
+/// an animation frame, and no more often than the display refreshes. Times are read from the
+/// clock, which tests replace to control when frames are due.
+#[derive(Debug)]
+pub struct FrameScheduler {
+    clock: Rc<dyn Clock>,
+    /// When the last frame started
+    last_frame_time: Option<Date>,
+    /// What the last frame drew, which there is none of until the document is drawn
+    drawn: Option<FrameInputs>,
+    /// Set when the document has to be drawn again for a reason its inputs do not show, such as
+    /// a stylesheet or image that was still loading
+    invalidated: bool,
+}
+
+impl FrameScheduler {
+    pub fn new(clock: Rc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            last_frame_time: None,
+            drawn: None,
+            invalidated: false,
+        }
+    }
+
+    pub fn get_clock(&self) -> Rc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Forgets what was drawn, as a new document has not been drawn yet
+    pub fn reset(&mut self) {
+        self.drawn = None;
+        self.invalidated = false;
+    }
+
+    /// Has the document drawn again, even if its inputs do not change
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    /// Whether the document was drawn since it was last reset
+    pub fn has_drawn(&self) -> bool {
+        self.drawn.is_some()
+    }
+
+    /// Whether the document has to be drawn again to show the inputs
+    pub fn needs_draw(&self, inputs: &FrameInputs) -> bool {
+        self.invalidated || self.drawn.as_ref() != Some(inputs)
+    }
+
+    /// Starts a frame when the document has to be drawn or there are animation frame callbacks to
+    /// run, and the frame interval has passed since the last frame started. Results in the time
+    /// the frame starts at.
+    pub fn start_frame(
+        &mut self,
+        inputs: &FrameInputs,
+        has_animation_frame_callbacks: bool,
+    ) -> Option<Date> {
+        if !has_animation_frame_callbacks && !self.needs_draw(inputs) {
+            return None;
+        }
+        let now = self.clock.now();
+        // A clock set back does not hold off frames until it catches up
+        let is_too_soon = self.last_frame_time.is_some_and(|last_frame_time| {
+            (0.0..FRAME_INTERVAL).contains(&(now - last_frame_time))
+        });
+        if is_too_soon {
+            return None;
+        }
+        self.last_frame_time = Some(now);
+        Some(now)
+    }
+
+    /// Records that the document was drawn from the inputs
+    pub fn finish_draw(&mut self, inputs: FrameInputs) {
+        self.drawn = Some(inputs);
+        self.invalidated = false;
+    }
+
+    /// How long until the next frame could start at the rate the display refreshes, which the
+    /// tab waits between renders so it does not spin while nothing changes
+    pub fn get_time_until_next_frame(&self) -> Duration {
+        let Some(last_frame_time) = self.last_frame_time else {
+            return Duration::ZERO;
+        };
+        let since_frame = (self.clock.now() - last_frame_time).rem_euclid(FRAME_INTERVAL);
+        Duration::from_secs_f64((FRAME_INTERVAL - since_frame) / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::js::api::date::FixedClock;
+
+    use super::*;
+
+    fn inputs(document_version: u64) -> FrameInputs {
+        FrameInputs {
+            document_version,
+            width: 100,
+            height: 100,
+            scroll_y: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_start_frame() {
+        let clock = Rc::new(FixedClock::new(1000.0));
+        let mut frames = FrameScheduler::new(clock.clone());
+        assert!(!frames.has_drawn());
+        assert_eq!(frames.start_frame(&inputs(0), false), Some(1000.0));
+        frames.finish_draw(inputs(0));
+        assert!(frames.has_drawn());
+
+        // Nothing changed
+        clock.set(1100.0);
+        assert!(!frames.needs_draw(&inputs(0)));
+        assert_eq!(frames.start_frame(&inputs(0), false), None);
+
+        // The document changed, but only right after the last frame
+        assert_eq!(frames.start_frame(&inputs(1), false), Some(1100.0));
+        frames.finish_draw(inputs(1));
+        clock.set(1110.0);
+        assert!(frames.needs_draw(&inputs(2)));
+        assert_eq!(frames.start_frame(&inputs(2), false), None);
+        clock.set(1120.0);
+        assert_eq!(frames.start_frame(&inputs(2), false), Some(1120.0));
+        frames.finish_draw(inputs(2));
+
+        // Animation frame callbacks start frames that draw nothing
+        clock.set(1200.0);
+        assert_eq!(frames.start_frame(&inputs(2), true), Some(1200.0));
+        assert!(!frames.needs_draw(&inputs(2)));
+
+        // A clock set back does not hold off frames
+        clock.set(500.0);
+        assert_eq!(frames.start_frame(&inputs(2), true), Some(500.0));
+    }
+
+    #[test]
+    fn test_invalidate_and_reset() {
+        let clock = Rc::new(FixedClock::new(0.0));
+        let mut frames = FrameScheduler::new(clock);
+        frames.finish_draw(inputs(0));
+        frames.invalidate();
+        assert!(frames.needs_draw(&inputs(0)));
+        frames.finish_draw(inputs(0));
+        assert!(!frames.needs_draw(&inputs(0)));
+
+        frames.reset();
+        assert!(!frames.has_drawn());
+        assert!(frames.needs_draw(&inputs(0)));
+    }
+
+    #[test]
+    fn test_get_time_until_next_frame() {
+        let clock = Rc::new(FixedClock::new(1000.0));
+        let mut frames = FrameScheduler::new(clock.clone());
+        assert_eq!(frames.get_time_until_next_frame(), Duration::ZERO);
+        frames.start_frame(&inputs(0), false);
+        assert_eq!(
+            frames.get_time_until_next_frame(),
+            Duration::from_secs_f64(FRAME_INTERVAL / 1000.0)
+        );
+        clock.set(1010.0);
+        assert_eq!(
+            frames.get_time_until_next_frame(),
+            Duration::from_secs_f64((FRAME_INTERVAL - 10.0) / 1000.0)
+        );
+        // Waits for the next time a frame would start while idle
+        clock.set(1000.0 + FRAME_INTERVAL * 10.0 + 5.0);
+        assert!(
+            frames.get_time_until_next_frame() < Duration::from_secs_f64(FRAME_INTERVAL / 1000.0)
+        );
+    }
+}
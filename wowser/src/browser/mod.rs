@@ -1,3 +1,5 @@
+mod frame_scheduler;
 mod tab;
 
+pub use frame_scheduler::*;
 pub use tab::*;
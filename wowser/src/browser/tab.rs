@@ -1,68 +1,733 @@
-use std::borrow::Borrow;
+use std::{borrow::Borrow, cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
 
 use crate::{
     css::{parse_css, CssDocument},
     font::{BDFFont, CachingFont},
-    html::{parse_html, HtmlDocument},
+    html::{parse_html, ElementContents, ElementContentsId, ElementHtmlNode, HtmlDocument},
+    js::{
+        api::{
+            date::{Clock, HighResolutionClock},
+            history::{NavigationRequest, SessionHistory},
+            module::{ModuleLoader, UrlModuleLoader},
+            storage::{InMemoryStorage, StorageArea, StorageProfile},
+        },
+        JsDocument, JsEvent, JsEventDetail, JsEventModifiers, JsEventTargetId, JsFunctionResult,
+        JsInterruptHandle, JsLimits,
+    },
+    net::{percent_decode, Url, UrlProtocol, UrlRecord, NETWORK_RESOURCE_MANAGER},
     render::{
-        self, html_css_to_styles, normalize_style_nodes, style_html, AsyncRenderContext, Color,
-        RectangleSceneNode, SceneNode,
+        self, hit_test, html_css_to_styles, load_scripts, normalize_style_nodes, queue_scripts,
+        style_html, AsyncRenderContext, Color, ReadyScript, RectangleSceneNode, SceneNode,
     },
-    ui::{UiEventProcessor, UiResult, Window},
+    ui::{Modifiers, UiEventProcessor, UiInputEvent, UiResult, Window},
     util::{Point, Rect},
 };
 
+use super::{FrameInputs, FrameScheduler};
+
 const DEFAULT_FONT_BYTES: &[u8] = include_bytes!("../../data/gohufont-11.bdf");
 
 const USERAGENT_CSS: &[u8] = include_bytes!("../assets/useragent_stylesheet.css");
 
+/// The steps a script of a page may take in one run before it is terminated, so a page cannot hang
+/// the tab
+const MAX_SCRIPT_STEPS: u64 = 10_000_000;
+
+/// The nodes the scripts of a page may hold before growing further throws a `RangeError`
+const MAX_SCRIPT_HEAP_SIZE: usize = 10_000_000;
+
 fn get_useragent_css() -> CssDocument {
     parse_css(std::str::from_utf8(USERAGENT_CSS).unwrap()).unwrap()
 }
 
 pub struct Tab<'w> {
     pub window: &'w mut Window,
-    html: HtmlDocument,
+    /// The document, which is shared with the scripts that change it
+    html: Rc<RefCell<HtmlDocument>>,
     async_render_context: AsyncRenderContext,
+    /// The scripts and modules run in the tab
+    js: JsDocument,
+    /// The layout of the last render, including nodes that draw nothing, to find what input is
+    /// over
+    scene: Vec<SceneNode>,
+    /// Whether `DOMContentLoaded` was dispatched, once the scripts that block loading have run
+    dom_content_loaded: bool,
+    /// Whether `load` was dispatched, once all scripts have run
+    loaded: bool,
+    /// The element the main mouse button was pressed on, which is clicked when it is released over
+    /// the same element
+    mouse_down_target: Option<JsEventTargetId>,
+    /// The pages the tab has been on, which is shared with the scripts that navigate through it
+    history: Rc<RefCell<SessionHistory>>,
+    /// Loads the modules the scripts of its pages import
+    loader: Rc<dyn ModuleLoader>,
+    /// The URL of the page of the current entry of the session history while it is loading, which
+    /// replaces the document once it loads
+    pending_page: Option<Url>,
+    /// Where the local storage of the origins navigated to is kept
+    profile: Option<Rc<RefCell<StorageProfile>>>,
+    /// The session storage of each origin the tab has been on, which its documents share.
+    /// Documents without an origin share the storage of `None`.
+    session_storage: HashMap<Option<String>, Rc<RefCell<StorageArea>>>,
+    /// How far down the document is scrolled
+    scroll_y: f32,
+    /// The fragment of the URL to scroll to once the element it identifies is laid out
+    pending_fragment: Option<String>,
+    /// Decides when the document is drawn again and animation frame callbacks run
+    frames: FrameScheduler,
+    /// Stops the scripts of the documents shown, which stay stopped until it is cleared
+    interrupt: JsInterruptHandle,
+    /// The limits the scripts of the documents shown run with
+    limits: JsLimits,
 }
 
 impl<'w> Tab<'w> {
+    /// A tab showing an empty page, which keeps the local storage of the pages it navigates to in
+    /// the profile
+    pub fn new(window: &'w mut Window, profile: Option<Rc<RefCell<StorageProfile>>>) -> Tab<'w> {
+        let mut tab = Tab {
+            window,
+            html: Rc::new(RefCell::new(parse_html("").unwrap())),
+            async_render_context: AsyncRenderContext::default(),
+            js: JsDocument::new(vec![]),
+            scene: vec![],
+            dom_content_loaded: false,
+            loaded: false,
+            mouse_down_target: None,
+            history: Rc::new(RefCell::new(SessionHistory::new())),
+            loader: Rc::new(UrlModuleLoader::default()),
+            pending_page: None,
+            profile,
+            session_storage: HashMap::new(),
+            scroll_y: 0.0,
+            pending_fragment: None,
+            frames: FrameScheduler::new(Rc::new(HighResolutionClock)),
+            interrupt: JsInterruptHandle::new(),
+            limits: JsLimits {
+                max_steps: Some(MAX_SCRIPT_STEPS),
+                max_heap_size: Some(MAX_SCRIPT_HEAP_SIZE),
+                ..JsLimits::default()
+            },
+        };
+        tab.show_document("", None);
+        tab
+    }
+
     pub fn load(window: &'w mut Window, html_contents: &str) -> Tab<'w> {
-        // Parse the documents
-        let html = parse_html(html_contents).unwrap();
+        let mut tab = Tab::new(window, None);
+        tab.show_document(html_contents, None);
+        tab
+    }
+
+    /// Loads the HTML of the page at the URL, which its scripts fetch relative to. The scripts use
+    /// the local storage of its origin in the profile, which is shared with the other tabs of the
+    /// profile.
+    pub fn load_from_url(
+        window: &'w mut Window,
+        url: &Url,
+        html_contents: &str,
+        profile: &Rc<RefCell<StorageProfile>>,
+    ) -> Tab<'w> {
+        let mut tab = Tab::new(window, Some(profile.clone()));
+        tab.history
+            .borrow_mut()
+            .push_document(url.to_record(), false);
+        tab.show_document(html_contents, Some(url));
+        tab
+    }
+
+    /// Changes how the modules scripts import are loaded, which is over the network or from the
+    /// filesystem by default
+    pub fn set_loader(&mut self, loader: Rc<dyn ModuleLoader>) {
+        self.js.global_closure_context.modules.loader = loader.clone();
+        self.loader = loader;
+    }
+
+    /// Changes the limits the scripts of the document and the pages navigated to run with
+    pub fn set_limits(&mut self, limits: JsLimits) {
+        self.js.global_closure_context.limits = limits.clone();
+        self.limits = limits;
+    }
+
+    /// Changes the clock frames are timed by, which scripts read high resolution time from too
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.js.set_clock(clock.clone());
+        self.frames = FrameScheduler::new(clock);
+    }
+
+    /// Navigates to the page at the URL, adding it to the session history. Only the fragment
+    /// changing scrolls to it on the page instead of loading it again. The page is shown by a
+    /// later render once it loads.
+    pub fn navigate(&mut self, url: &Url) {
+        self.navigate_to(url.to_record(), false);
+    }
+
+    /// Goes back to the previous entry of the session history, if there is one
+    pub fn back(&mut self) {
+        self.traverse(-1);
+    }
+
+    /// Goes forward to the next entry of the session history, if there is one
+    pub fn forward(&mut self) {
+        self.traverse(1);
+    }
+
+    /// The URL of the entry of the session history the tab is on, which there is none of before it
+    /// navigates
+    pub fn get_url(&self) -> Option<UrlRecord> {
+        RefCell::borrow(&self.history)
+            .current()
+            .map(|entry| entry.url.clone())
+    }
+
+    /// Runs the module at the URL in the tab, loading the modules it imports over the network or
+    /// from the filesystem
+    pub fn run_module(&mut self, url: &str) -> JsFunctionResult {
+        self.js.run_module(url)
+    }
+
+    /// A handle the UI can use to stop the scripts running in the tab from another thread. Scripts
+    /// stay stopped, including those of the pages navigated to, until the UI clears it.
+    pub fn get_interrupt_handle(&self) -> JsInterruptHandle {
+        self.interrupt.clone()
+    }
 
-        // Get useragent stylesheet
-        let css = get_useragent_css();
+    /// Runs the scripts of the document that have loaded and the handlers of fetches that
+    /// finished, then carries out the navigations they asked for. Then renders a frame when one is
+    /// due, which runs the animation frame callbacks and draws the document when the DOM, styles,
+    /// window size or scroll position changed since it was last drawn.
+    pub fn render(&mut self) {
+        self.show_pending_page();
+        self.run_scripts();
+        self.dispatch_load_events();
+        self.js.dispatch_storage_events();
+        self.js.process_fetches();
+        self.process_navigation_requests();
+        self.render_frame();
+        self.scroll_to_pending_fragment();
+    }
+
+    /// How long until the next frame could start, which is how long to wait before rendering
+    /// again when there is no input
+    pub fn get_time_until_next_frame(&self) -> Duration {
+        self.frames.get_time_until_next_frame()
+    }
+
+    /// Runs the animation frame callbacks with the time the frame starts at, then draws the
+    /// document if it changed, when a frame is due
+    fn render_frame(&mut self) {
+        let has_callbacks = self.js.has_animation_frame_callbacks();
+        let inputs = self.get_frame_inputs();
+        let Some(frame_time) = self.frames.start_frame(&inputs, has_callbacks) else {
+            return;
+        };
+        if has_callbacks {
+            self.js.run_animation_frame_callbacks(frame_time);
+        }
+        if self.frames.needs_draw(&self.get_frame_inputs()) {
+            self.draw();
+        }
+    }
+
+    fn get_frame_inputs(&self) -> FrameInputs {
+        let bounds = self.window.get_bounds();
+        FrameInputs {
+            document_version: RefCell::borrow(&self.html).get_version(),
+            width: bounds.width,
+            height: bounds.height,
+            scroll_y: self.scroll_y,
+        }
+    }
+
+    /// Lays out and draws the document where it is scrolled to. Stylesheets are only found while
+    /// laying out, so it is laid out again right away when they changed. Resources that are still
+    /// loading have it drawn again in a later frame.
+    fn draw(&mut self) {
+        self.async_render_context.reset_changes();
+        self.scene = render_once(
+            self.window,
+            &RefCell::borrow(&self.html),
+            &mut self.async_render_context,
+            self.scroll_y,
+        );
+        if self.async_render_context.styles_changed {
+            self.async_render_context.reset_changes();
+            self.scene = render_once(
+                self.window,
+                &RefCell::borrow(&self.html),
+                &mut self.async_render_context,
+                self.scroll_y,
+            );
+        }
+        self.frames.finish_draw(self.get_frame_inputs());
+        if self.async_render_context.styles_changed || self.async_render_context.loading_resources {
+            self.frames.invalidate();
+        }
+    }
+
+    /// Replaces the document with one parsed from the HTML, which is at the URL of the current
+    /// entry of the session history when it has one. The document is scrolled to where the entry
+    /// was left, or to the fragment of its URL.
+    fn show_document(&mut self, html_contents: &str, url: Option<&Url>) {
+        let html = parse_html(html_contents).unwrap_or_else(|err| {
+            log!(ERROR: "Unable to parse HTML", err);
+            parse_html("").unwrap()
+        });
+        self.html = Rc::new(RefCell::new(html));
 
         let mut async_render_context = AsyncRenderContext::default();
         async_render_context
             .css_documents
-            .insert("USER-AGENT".to_string(), (0, css));
+            .insert("USER-AGENT".to_string(), (0, get_useragent_css()));
+        async_render_context.base_url = url.cloned();
+        queue_scripts(
+            &RefCell::borrow(&self.html).html,
+            false,
+            &mut async_render_context,
+        );
+        self.async_render_context = async_render_context;
 
-        Tab {
-            window,
-            html,
-            async_render_context,
+        let mut js = JsDocument::new(vec![]);
+        js.set_clock(self.frames.get_clock());
+        js.global_closure_context.modules.loader = self.loader.clone();
+        js.global_closure_context.interrupt = self.interrupt.clone();
+        js.global_closure_context.limits = self.limits.clone();
+        js.set_session_history(self.history.clone());
+        js.set_html_document(self.html.clone());
+        if let Some(url) = url {
+            js.set_url(url);
+            let origin = url.origin();
+            let local_storage = origin.as_ref().and_then(|origin| {
+                self.profile.as_ref().and_then(|profile| {
+                    profile
+                        .borrow_mut()
+                        .get_local_storage(origin)
+                        .map_err(|err| {
+                            log!(ERROR: "Unable to load local storage of", origin, err);
+                        })
+                        .ok()
+                })
+            });
+            let session_storage = self
+                .session_storage
+                .entry(origin)
+                .or_insert_with(|| {
+                    let storage = Box::<InMemoryStorage>::default();
+                    Rc::new(RefCell::new(StorageArea::new(storage)))
+                })
+                .clone();
+            js.set_storage(&url.to_record().to_string(), local_storage, session_storage);
         }
+        self.js = js;
+
+        self.scene = vec![];
+        self.frames.reset();
+        self.dom_content_loaded = false;
+        self.loaded = false;
+        self.mouse_down_target = None;
+        self.scroll_y = RefCell::borrow(&self.history)
+            .current()
+            .map_or(0.0, |entry| entry.scroll_y);
+        self.pending_fragment = url
+            .filter(|url| self.scroll_y == 0.0 && !url.fragment.is_empty())
+            .map(|url| url.fragment.clone());
     }
 
-    pub fn render(&mut self) {
-        render_once(self.window, &self.html, &mut self.async_render_context);
+    /// Navigates to the URL, adding an entry to the session history or replacing the current one.
+    /// URLs that only differ from the current one by their fragment scroll to it on the document.
+    fn navigate_to(&mut self, url: UrlRecord, replace: bool) {
+        let current_url = self.get_url();
+        if let Some(current_url) = current_url {
+            if url.fragment.is_some() && url.equals_except_fragment(&current_url) {
+                self.history
+                    .borrow_mut()
+                    .push_same_document(url.clone(), None, replace);
+                self.js.update_url_from_history();
+                self.finish_fragment_navigation(&current_url, &url);
+                return;
+            }
+        }
+        let Some(page_url) = Url::from_record(&url) else {
+            log!(ERROR: "Unable to navigate to", url.to_string());
+            return;
+        };
+        self.history.borrow_mut().push_document(url, replace);
+        self.load_page(&page_url, false);
+    }
+
+    /// Loads the page at the URL of the current entry, which a later render shows once it loads.
+    /// Pages are loaded from the cache unless bypassing it, while `file:` URLs are read and shown
+    /// right away. Pages that fail to load show an empty page.
+    fn load_page(&mut self, url: &Url, bypass_cache: bool) {
+        let request_url = get_request_url(url);
+        if url.protocol == UrlProtocol::File {
+            let html = UrlModuleLoader::default()
+                .load(&request_url.to_record().to_string())
+                .unwrap_or_else(|err| {
+                    log!(ERROR: "Unable to load", url.to_string(), err);
+                    String::new()
+                });
+            self.pending_page = None;
+            self.show_document(&html, Some(url));
+            return;
+        }
+        if bypass_cache {
+            match NETWORK_RESOURCE_MANAGER.lock() {
+                Ok(mut network_resource_manager) => network_resource_manager.remove(&request_url),
+                Err(_) => {
+                    log!(ERROR: "Unable to bypass the cache for", url.to_string());
+                }
+            }
+        }
+        self.pending_page = Some(url.clone());
+    }
+
+    /// Requests the page that is loading, then shows it once its response arrived
+    fn show_pending_page(&mut self) {
+        let Some(url) = self.pending_page.clone() else {
+            return;
+        };
+        let Some(result) = self.async_render_context.get_resource(&get_request_url(&url)) else {
+            return;
+        };
+        self.pending_page = None;
+        let html = match result.as_ref() {
+            Ok(response) => String::from_utf8_lossy(&response.body).to_string(),
+            Err(err) => {
+                log!(ERROR: "Unable to load", url.to_string(), err);
+                String::new()
+            }
+        };
+        self.show_document(&html, Some(&url));
+    }
+
+    /// Loads the page of the current entry of the session history again, from the network when
+    /// bypassing the cache
+    fn reload(&mut self, bypass_cache: bool) {
+        let url = self.get_url();
+        match url.as_ref().and_then(Url::from_record) {
+            Some(url) => self.load_page(&url, bypass_cache),
+            None => {
+                log!(ERROR: "Unable to reload", url.map(|url| url.to_string()));
+            }
+        }
+    }
+
+    /// Moves back or forward through the session history. Moving to an entry of the same
+    /// document fires `popstate`, and `hashchange` when the fragment changed, while moving to
+    /// another document loads it again.
+    fn traverse(&mut self, delta: isize) {
+        let old_entry = RefCell::borrow(&self.history).current().cloned();
+        if !self.history.borrow_mut().traverse(delta) {
+            return;
+        }
+        let new_entry = RefCell::borrow(&self.history).current().cloned();
+        let (Some(old_entry), Some(new_entry)) = (old_entry, new_entry) else {
+            return;
+        };
+        if old_entry.document_id != new_entry.document_id {
+            self.reload(false);
+            return;
+        }
+        self.js.update_url_from_history();
+        self.scroll_y = new_entry.scroll_y;
+        let event = JsEvent::new("popstate", false, false, JsEventDetail::PopState);
+        self.js.dispatch_event(JsEventTargetId::Window, event);
+        if old_entry.url.fragment != new_entry.url.fragment {
+            self.dispatch_hash_change(&old_entry.url, &new_entry.url);
+        }
+    }
+
+    /// Scrolls to the fragment navigated to, then fires `hashchange` when it changed
+    fn finish_fragment_navigation(&mut self, old_url: &UrlRecord, new_url: &UrlRecord) {
+        self.pending_fragment = new_url.fragment.clone();
+        if old_url.fragment != new_url.fragment {
+            self.dispatch_hash_change(old_url, new_url);
+        }
+    }
+
+    fn dispatch_hash_change(&mut self, old_url: &UrlRecord, new_url: &UrlRecord) {
+        let detail = JsEventDetail::HashChange {
+            old_url: old_url.to_string(),
+            new_url: new_url.to_string(),
+        };
+        let event = JsEvent::new("hashchange", false, false, detail);
+        self.js.dispatch_event(JsEventTargetId::Window, event);
+    }
+
+    /// Carries out the navigations the scripts asked for in order. Once the document is replaced,
+    /// the rest are dropped along with the document that asked for them.
+    fn process_navigation_requests(&mut self) {
+        let requests = self.history.borrow_mut().take_requests();
+        let html = self.html.clone();
+        for request in &requests {
+            if !Rc::ptr_eq(&html, &self.html) {
+                break;
+            }
+            match request {
+                NavigationRequest::Navigate { url, replace } => {
+                    self.navigate_to(url.clone(), *replace)
+                }
+                NavigationRequest::Reload => self.reload(true),
+                NavigationRequest::Traverse(delta) => self.traverse(*delta),
+                NavigationRequest::FragmentNavigated { old_url, new_url } => {
+                    self.finish_fragment_navigation(old_url, new_url)
+                }
+            }
+        }
+    }
+
+    /// Scrolls to the element the fragment of the URL identifies once it is laid out, or stops
+    /// looking for it once the page has loaded. The document is drawn scrolled in the next frame.
+    fn scroll_to_pending_fragment(&mut self) {
+        let Some(fragment) = self.pending_fragment.clone() else {
+            return;
+        };
+        let scroll_y = self.get_fragment_scroll_y(&fragment);
+        if scroll_y.is_some() || self.loaded {
+            self.pending_fragment = None;
+        }
+        let Some(scroll_y) = scroll_y else {
+            return;
+        };
+        self.scroll_y = scroll_y;
+        if let Some(entry) = self.history.borrow_mut().current_mut() {
+            entry.scroll_y = scroll_y;
+        }
+    }
+
+    /// How far to scroll to show the element the fragment identifies at the top, which is the
+    /// element with it as its `id` or the `<a>` with it as its `name`. An empty fragment or `top`
+    /// identify the top of the document.
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#find-a-potential-indicated-element>
+    fn get_fragment_scroll_y(&self, fragment: &str) -> Option<f32> {
+        if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") {
+            return Some(0.0);
+        }
+        let decoded = String::from_utf8_lossy(&percent_decode(fragment.as_bytes())).to_string();
+        let html = RefCell::borrow(&self.html);
+        let element = [fragment, decoded.as_str()].iter().find_map(|name| {
+            html.html.find_first(|node| match node {
+                ElementContents::Element(element) => {
+                    element.get_attribute("id") == Some(name)
+                        || (element.tag_name == "a" && element.get_attribute("name") == Some(name))
+                }
+                ElementContents::Text(_) => false,
+            })
+        })?;
+        let id = element.get_id();
+        let top = self
+            .scene
+            .iter()
+            .filter(|node| node.element_id() == Some(id))
+            .map(|node| node.bounds().y)
+            .reduce(f32::min)?;
+        Some((top + self.scroll_y).max(0.0))
+    }
+
+    /// Dispatches `DOMContentLoaded` to the document once the scripts blocking it have run, and
+    /// `load` to the window once every script has
+    fn dispatch_load_events(&mut self) {
+        let scripts = &self.async_render_context.scripts;
+        if !self.dom_content_loaded && scripts.has_run_document_scripts() {
+            self.dom_content_loaded = true;
+            let event = JsEvent::new("DOMContentLoaded", true, false, JsEventDetail::None);
+            self.js.dispatch_event(JsEventTargetId::Document, event);
+        }
+        let scripts = &self.async_render_context.scripts;
+        if self.dom_content_loaded && !self.loaded && scripts.has_run_all() {
+            self.loaded = true;
+            let event = JsEvent::new("load", false, false, JsEventDetail::None);
+            self.js.dispatch_event(JsEventTargetId::Window, event);
+        }
+    }
+
+    /// The element drawn at the point, or the document when there is none. Text is part of the
+    /// element containing it.
+    fn get_target_at(&self, x: f32, y: f32) -> JsEventTargetId {
+        let html = RefCell::borrow(&self.html);
+        let id = hit_test(&self.scene, x, y).and_then(|id| match html.get_node(id)? {
+            ElementContents::Text(_) => html.get_parent(id).map(|parent| parent.get_id()),
+            ElementContents::Element(_) => Some(id),
+        });
+        id.map_or(JsEventTargetId::Document, JsEventTargetId::Node)
+    }
+
+    /// Keyboard input goes to the body, as no element can be focused
+    fn get_keyboard_target(&self) -> JsEventTargetId {
+        let html = RefCell::borrow(&self.html);
+        let body = html.html.find_first(
+            |node| matches!(node, ElementContents::Element(element) if element.tag_name == "body"),
+        );
+        body.map(ElementContents::get_id)
+            .map_or(JsEventTargetId::Document, JsEventTargetId::Node)
+    }
+
+    /// Dispatches the DOM events for input to the window
+    fn dispatch_input_event(&mut self, input: UiInputEvent) {
+        match input {
+            UiInputEvent::MouseMove { x, y } => {
+                let target = self.get_target_at(x, y);
+                let detail = get_mouse_detail(x, y, 0, Modifiers::default());
+                let event = JsEvent::new("mousemove", true, true, detail);
+                self.js.dispatch_event(target, event);
+            }
+            UiInputEvent::MouseButton {
+                button,
+                pressed,
+                x,
+                y,
+                modifiers,
+            } => {
+                let target = self.get_target_at(x, y);
+                let event_type = if pressed { "mousedown" } else { "mouseup" };
+                let detail = get_mouse_detail(x, y, button, modifiers);
+                let event = JsEvent::new(event_type, true, true, detail.clone());
+                self.js.dispatch_event(target, event);
+                if button != 0 {
+                    return;
+                }
+                if pressed {
+                    self.mouse_down_target = Some(target);
+                } else if self.mouse_down_target.take() == Some(target) {
+                    let event = JsEvent::new("click", true, true, detail);
+                    if self.js.dispatch_event(target, event) {
+                        self.follow_link(target);
+                    }
+                }
+            }
+            UiInputEvent::Key {
+                key,
+                code,
+                pressed,
+                repeat,
+                modifiers,
+            } => {
+                let target = self.get_keyboard_target();
+                let event_type = if pressed { "keydown" } else { "keyup" };
+                let detail = JsEventDetail::Keyboard {
+                    key,
+                    code: code.clone(),
+                    repeat,
+                    modifiers: get_event_modifiers(modifiers),
+                };
+                let event = JsEvent::new(event_type, true, true, detail);
+                let not_canceled = self.js.dispatch_event(target, event);
+                // Alt and the left and right arrows go back and forward, as in other browsers
+                if not_canceled && pressed && modifiers.alt {
+                    match code.as_str() {
+                        "ArrowLeft" => self.back(),
+                        "ArrowRight" => self.forward(),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Navigates to the `href` of the link the target is in, as clicking it does
+    fn follow_link(&mut self, target: JsEventTargetId) {
+        let JsEventTargetId::Node(id) = target else {
+            return;
+        };
+        let Some(href) = self.get_link_href(id) else {
+            return;
+        };
+        match UrlRecord::parse(&href, self.get_url().as_ref()) {
+            Some(url) => self.navigate_to(url, false),
+            None => {
+                log!(ERROR: "Invalid link", href);
+            }
+        }
+    }
+
+    /// The `href` of the nearest `<a>` containing the node, including itself
+    fn get_link_href(&self, id: ElementContentsId) -> Option<String> {
+        let html = RefCell::borrow(&self.html);
+        let mut id = Some(id);
+        while let Some(current) = id {
+            if let Some(ElementContents::Element(element)) = html.get_node(current) {
+                if element.tag_name == "a" {
+                    if let Some(href) = element.get_attribute("href") {
+                        return Some(href.to_string());
+                    }
+                }
+            }
+            id = html.get_parent(current).map(ElementHtmlNode::get_id);
+        }
+        None
+    }
+
+    /// Runs the scripts of the document that are ready in order, logging uncaught exceptions
+    /// instead of stopping the load. The scripts that other scripts inserted into the document
+    /// are queued first.
+    fn run_scripts(&mut self) {
+        let inserted = self.html.borrow_mut().take_inserted();
+        let html = RefCell::borrow(&self.html);
+        for id in inserted {
+            if let Some(node) = html.get_node(id).filter(|_| html.is_connected(id)) {
+                queue_scripts(node, true, &mut self.async_render_context);
+            }
+        }
+        drop(html);
+
+        load_scripts(&mut self.async_render_context);
+        let scripts = self.async_render_context.scripts.take_ready();
+        for (name, script) in &scripts {
+            let result = match script {
+                ReadyScript::Classic(source) => self.js.run_script(source),
+                ReadyScript::Module(url) => self.js.run_module(&url.to_record().to_string()),
+            };
+            if let Err(value) = result {
+                log!(ERROR["JS"]: "Uncaught exception in", name, value.get_ref().to_string());
+            }
+        }
     }
 }
 
 impl UiEventProcessor for Tab<'_> {
     fn process_events(&mut self) -> UiResult {
         self.window.process_events()?;
+        for input in self.window.take_input_events() {
+            self.dispatch_input_event(input);
+        }
         Ok(())
     }
 }
 
+/// The URL the page at the URL is requested with, which leaves out the fragment
+fn get_request_url(url: &Url) -> Url {
+    Url {
+        fragment: String::new(),
+        ..url.clone()
+    }
+}
+
+fn get_mouse_detail(x: f32, y: f32, button: u16, modifiers: Modifiers) -> JsEventDetail {
+    JsEventDetail::Mouse {
+        client_x: f64::from(x),
+        client_y: f64::from(y),
+        button,
+        modifiers: get_event_modifiers(modifiers),
+    }
+}
+
+fn get_event_modifiers(modifiers: Modifiers) -> JsEventModifiers {
+    JsEventModifiers {
+        alt: modifiers.alt,
+        control: modifiers.control,
+        meta: modifiers.super_key,
+        shift: modifiers.shift,
+    }
+}
+
+/// Draws the document scrolled down by the offset, resulting in its layout
 fn render_once(
     window: &mut Window,
     html: &HtmlDocument,
     async_render_context: &mut AsyncRenderContext,
-) {
+    scroll_y: f32,
+) -> Vec<SceneNode> {
     let mut sorted_css_documents = async_render_context
         .css_documents
         .iter()
@@ -85,9 +750,17 @@ fn render_once(
     normalize_style_nodes(&mut style_root);
 
     // Flatten the hierarchy on nodes to a scene, which incorporates layout, sizing, text wrapping, etc. The
-    // output should be pretty much ready to draw at this point
-    let mut scene_nodes =
-        render::style_to_scene(&style_root, 0_f32, window.get_bounds().width as f32);
+    // output should be pretty much ready to draw at this point. Nodes that draw nothing are kept
+    // in the layout to find what is under the cursor.
+    let mut layout = render::layout_scene(&style_root, 0_f32, window.get_bounds().width as f32);
+    for node in &mut layout {
+        node.mut_bounds().y -= scroll_y;
+    }
+    let mut scene_nodes: Vec<SceneNode> = layout
+        .iter()
+        .filter(|node| node.is_visible())
+        .cloned()
+        .collect();
 
     // Expands the HTML scene node background to be the real background of the window instead of
     // constrained to the proportions of the actual element.
@@ -108,6 +781,7 @@ fn render_once(
                 text,
                 font_size,
                 text_color,
+                ..
             }) => {
                 let mut offset = Point {
                     x: bounds.x,
@@ -133,6 +807,7 @@ fn render_once(
                 fill_pixels,
                 border_color,
                 border_width,
+                ..
             }) => {
                 window_mutator
                     .draw_rect(
@@ -162,6 +837,8 @@ fn render_once(
             }
         }
     }
+
+    layout
 }
 
 /// Insert a synthetic scene node at the beginning to force the html background color across the
@@ -188,6 +865,7 @@ fn force_html_background(scene_nodes: &mut Vec<SceneNode>) {
             fill_pixels: first_node.fill_pixels.clone(),
             border_color: Color::TRANSPARENT,
             border_width: 0_f32,
+            element_id: None,
         });
         scene_nodes.insert(0, root);
     }
@@ -196,12 +874,18 @@ fn force_html_background(scene_nodes: &mut Vec<SceneNode>) {
 #[cfg(test)]
 mod tests {
     use crate::image::Bitmap;
+    use crate::js::api::{date::FixedClock, storage::StorageListener};
     use crate::ui::tests::lock_for_ui_threads;
     use crate::util::get_bool_env;
     use crate::{function_name, startup};
     use std::{
         env, fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
         panic::{catch_unwind, UnwindSafe},
+        sync::{Arc, Mutex},
+        thread,
+        time::Instant,
     };
 
     use super::*;
@@ -343,4 +1027,346 @@ mod tests {
             tab.render();
         });
     }
+
+    /// Serves the pages by path from a loopback port until the tests end, counting the requests
+    /// made for each. Results in the URL of the server.
+    fn serve_pages(pages: &[(&str, &str)]) -> (String, Arc<Mutex<HashMap<String, usize>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        let pages: HashMap<String, String> = pages
+            .iter()
+            .map(|(path, contents)| (path.to_string(), contents.to_string()))
+            .collect();
+        let requests = Arc::new(Mutex::new(HashMap::new()));
+        let counts = requests.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                // Requests made for pages have no body, so they end with the headers
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                *counts.lock().unwrap().entry(path.to_string()).or_insert(0) += 1;
+                let response = match pages.get(path) {
+                    Some(contents) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{contents}",
+                        contents.len()
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (server, requests)
+    }
+
+    /// Runs the test with a tab that loads the pages by path from a server, which is passed to
+    /// the test along with the requests made for each path
+    fn navigation_test<F>(pages: &[(&str, &str)], test: F)
+    where
+        F: FnOnce(&mut Tab, &str, &Mutex<HashMap<String, usize>>) + UnwindSafe,
+    {
+        let (server, requests) = serve_pages(pages);
+        lock_for_ui_threads(|| {
+            let result = catch_unwind(|| {
+                startup::start();
+                let window_rc = Window::new().unwrap();
+                let mut window = window_rc.borrow_mut();
+                let mut tab = Tab::new(&mut window, None);
+                test(&mut tab, &server, &requests);
+            });
+            startup::stop();
+            result.unwrap();
+        });
+    }
+
+    /// Renders until the page that is loading is shown
+    fn wait_for_response(tab: &mut Tab) {
+        let start = Instant::now();
+        tab.render();
+        while tab.pending_page.is_some() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Timed out loading the page"
+            );
+            thread::sleep(Duration::from_millis(1));
+            tab.render();
+        }
+    }
+
+    /// Renders until the page that is loading is shown and drawn
+    fn wait_for_page(tab: &mut Tab) {
+        wait_for_response(tab);
+        while !tab.frames.has_drawn() {
+            thread::sleep(tab.get_time_until_next_frame());
+            tab.render();
+        }
+    }
+
+    fn navigate(tab: &mut Tab, url: &str) {
+        tab.navigate(&Url::parse(url).unwrap());
+        wait_for_page(tab);
+    }
+
+    fn run_script(tab: &mut Tab, source: &str) -> String {
+        tab.js.run_script(source).unwrap().get_ref().to_string()
+    }
+
+    fn get_url(tab: &Tab) -> String {
+        tab.get_url().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_navigation() {
+        let pages = [
+            ("/", r#"<html><body><a href="/b">first</a></body></html>"#),
+            ("/b", "<html><body>second</body></html>"),
+        ];
+        navigation_test(&pages, |tab, server, requests| {
+            navigate(tab, &format!("{server}/"));
+            assert_eq!(run_script(tab, "document.body.textContent"), "first");
+
+            // Clicking the link navigates to it
+            let (x, y) = tab
+                .scene
+                .iter()
+                .filter(|node| {
+                    node.element_id()
+                        .is_some_and(|id| tab.get_link_href(id).is_some())
+                })
+                .map(|node| (node.bounds().x + 1.0, node.bounds().y + 1.0))
+                .next()
+                .unwrap();
+            for pressed in [true, false] {
+                tab.dispatch_input_event(UiInputEvent::MouseButton {
+                    button: 0,
+                    pressed,
+                    x,
+                    y,
+                    modifiers: Modifiers::default(),
+                });
+            }
+            wait_for_page(tab);
+            assert_eq!(get_url(tab), format!("{server}/b"));
+            assert_eq!(run_script(tab, "document.body.textContent"), "second");
+
+            // Going back and forward loads the pages again, from the cache
+            tab.back();
+            wait_for_page(tab);
+            assert_eq!(get_url(tab), format!("{server}/"));
+            assert_eq!(run_script(tab, "document.body.textContent"), "first");
+            tab.forward();
+            wait_for_page(tab);
+            assert_eq!(get_url(tab), format!("{server}/b"));
+            assert_eq!(requests.lock().unwrap()["/"], 1);
+
+            // Entries of the same document stay on it
+            run_script(
+                tab,
+                "var log = ''; \
+                function onHashChange(e) { log = log + e.newURL + ';'; } \
+                function onPopState(e) { log = log + e.type + ';'; } \
+                addEventListener('hashchange', onHashChange); \
+                addEventListener('popstate', onPopState); \
+                location.hash = 'x'; history.pushState(null, '', '/c');",
+            );
+            tab.render();
+            assert_eq!(get_url(tab), format!("{server}/c"));
+            assert_eq!(RefCell::borrow(&tab.history).len(), 4);
+            tab.back();
+            assert_eq!(get_url(tab), format!("{server}/b#x"));
+            assert_eq!(
+                run_script(tab, "log"),
+                format!("{server}/b#x;popstate;{server}/b#x;")
+            );
+
+            run_script(tab, "history.go(-2)");
+            tab.render();
+            wait_for_page(tab);
+            assert_eq!(get_url(tab), format!("{server}/"));
+            assert_eq!(run_script(tab, "document.body.textContent"), "first");
+        });
+    }
+
+    #[test]
+    fn test_reload() {
+        let pages = [("/", "<html><body><script>var a = 1;</script></body></html>")];
+        navigation_test(&pages, |tab, server, requests| {
+            navigate(tab, &format!("{server}/#x"));
+            // The page stays until the one reloaded in its place loads
+            run_script(tab, "location.reload()");
+            tab.render();
+            assert_eq!(run_script(tab, "a"), "1");
+            run_script(tab, "a = 2");
+            wait_for_page(tab);
+            assert_eq!(run_script(tab, "a"), "1");
+            assert_eq!(get_url(tab), format!("{server}/#x"));
+            // Reloading requests the page again instead of using the cache
+            assert_eq!(requests.lock().unwrap()["/"], 2);
+        });
+    }
+
+    #[test]
+    fn test_inserted_scripts() {
+        let pages = [(
+            "/",
+            "<html><body><script>var log = 'a';</script></body></html>",
+        )];
+        navigation_test(&pages, |tab, server, _requests| {
+            navigate(tab, &format!("{server}/"));
+            assert_eq!(run_script(tab, "log"), "a");
+
+            // Scripts set through innerHTML never run, while inserted ones run once connected
+            run_script(
+                tab,
+                "document.body.innerHTML = '<script>log = log + \"b\";</script>'; \
+                var div = document.createElement('div'); \
+                var script = document.createElement('script'); \
+                script.textContent = 'log = log + \"c\"'; \
+                div.appendChild(script);",
+            );
+            tab.render();
+            assert_eq!(run_script(tab, "log"), "a");
+            run_script(tab, "document.body.appendChild(div)");
+            tab.render();
+            assert_eq!(run_script(tab, "log"), "ac");
+            run_script(tab, "document.body.appendChild(script)");
+            tab.render();
+            assert_eq!(run_script(tab, "log"), "ac");
+        });
+    }
+
+    #[test]
+    fn test_module_scripts() {
+        let pages = [
+            (
+                "/",
+                "<html><body>\
+                    <script type=\"module\" src=\"/main.js\"></script>\
+                    <script>var log = 'classic';</script>\
+                    <script type=\"module\">log = 'inline';</script>\
+                </body></html>",
+            ),
+            (
+                "/main.js",
+                "import { name } from './name.js'; log = log + ',' + name;",
+            ),
+            ("/name.js", "export const name = 'module';"),
+        ];
+        navigation_test(&pages, |tab, server, _requests| {
+            navigate(tab, &format!("{server}/"));
+            // Modules run after the classic scripts of the document, and inline ones are skipped
+            assert_eq!(run_script(tab, "log"), "classic,module");
+        });
+    }
+
+    #[test]
+    fn test_storage_event_url() {
+        let pages = [("/a?b", "<html></html>")];
+        navigation_test(&pages, |tab, server, _requests| {
+            let url = Url::parse(&format!("{server}/a?b")).unwrap();
+            tab.navigate(&url);
+            wait_for_page(tab);
+            let listener = StorageListener::new(&format!("{server}/c"));
+            tab.session_storage[&url.origin()]
+                .borrow_mut()
+                .listen(&listener);
+            run_script(tab, "sessionStorage.setItem('a', '1')");
+            let changes = listener.take_changes();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].url, format!("{server}/a?b"));
+        });
+    }
+
+    #[test]
+    fn test_script_limits() {
+        let pages = [(
+            "/",
+            "<html><body><script>\
+                var log = 'a'; \
+                function f(n) { return n == 0 ? 0 : f(n + -1); } \
+                function onClick(e) { f(200); log = 'click'; } \
+                function step(time) { f(200); log = 'frame'; } \
+                addEventListener('click', onClick); \
+                requestAnimationFrame(step); \
+                f(200); \
+                log = 'script'; \
+            </script></body></html>",
+        )];
+        navigation_test(&pages, |tab, server, _requests| {
+            let clock = Rc::new(FixedClock::new(1000.0));
+            tab.set_clock(clock.clone());
+            tab.set_limits(JsLimits {
+                max_steps: Some(500),
+                ..JsLimits::default()
+            });
+            tab.render();
+
+            // Scripts, event listeners and animation frame callbacks are each terminated once
+            // they run out of steps
+            tab.navigate(&Url::parse(&format!("{server}/")).unwrap());
+            wait_for_response(tab);
+            assert_eq!(run_script(tab, "log"), "a");
+            let event = JsEvent::new("click", false, false, JsEventDetail::None);
+            tab.js.dispatch_event(JsEventTargetId::Window, event);
+            assert_eq!(run_script(tab, "log"), "a");
+            clock.set(1020.0);
+            tab.render();
+            assert!(!tab.js.has_animation_frame_callbacks());
+            assert_eq!(run_script(tab, "log"), "a");
+        });
+    }
+
+    #[test]
+    fn test_animation_frames() {
+        let pages = [(
+            "/",
+            "<html><body><script>\
+                var times = ''; \
+                var count = 0; \
+                function step(time) { \
+                    count = count + 1; \
+                    times = times ? times + ',' + time : '' + time; \
+                    document.body.setAttribute('data-frame', count); \
+                    if (count != 3) requestAnimationFrame(step); \
+                } \
+                requestAnimationFrame(step); \
+                function cancel() { times = times + 'canceled'; } \
+                var canceled = requestAnimationFrame(cancel); \
+                cancelAnimationFrame(canceled); \
+            </script></body></html>",
+        )];
+        navigation_test(&pages, |tab, server, _requests| {
+            let clock = Rc::new(FixedClock::new(1000.0));
+            tab.set_clock(clock.clone());
+            // The empty page is drawn first
+            tab.render();
+
+            // The script runs once the page loads, but the next frame is not due yet
+            tab.navigate(&Url::parse(&format!("{server}/")).unwrap());
+            wait_for_response(tab);
+            tab.render();
+            assert_eq!(run_script(tab, "count"), "0");
+
+            clock.set(1020.0);
+            tab.render();
+            assert_eq!(run_script(tab, "times"), "20");
+            // The callback changed the document, which was drawn after it ran
+            assert!(!tab.frames.needs_draw(&tab.get_frame_inputs()));
+            tab.render();
+            assert_eq!(run_script(tab, "times"), "20");
+
+            for time in [1040.0, 1060.0, 1080.0] {
+                clock.set(time);
+                tab.render();
+            }
+            assert_eq!(run_script(tab, "times"), "20,40,60");
+            assert_eq!(run_script(tab, "performance.now()"), "80");
+            assert_eq!(run_script(tab, "performance.timeOrigin"), "1000");
+        });
+    }
 }
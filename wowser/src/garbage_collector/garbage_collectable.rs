@@ -5,4 +5,13 @@ use super::GcNode;
 /// An element that can be garbage collected or reference other garbage collectable nodes.
 pub trait GarbageCollectable: Sized + PartialEq + Debug {
     fn get_referenced_nodes(&self) -> Vec<GcNode<Self>>;
+
+    /// Entries held weakly by this element as (key, value) pairs. A value is only kept alive
+    /// through this element while its key is reachable some other way.
+    fn get_weak_entries(&self) -> Vec<(GcNode<Self>, GcNode<Self>)> {
+        vec![]
+    }
+
+    /// Called after a collection so entries whose keys were collected can be dropped.
+    fn retain_weak_entries(&mut self) {}
 }
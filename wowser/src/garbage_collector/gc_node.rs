@@ -4,7 +4,7 @@ use std::{
     rc::{Rc, Weak},
 };
 
-use super::{inner_node::InnerNode, GarbageCollectable, GcNodeGraph};
+use super::{gc_sweep_id::GcSweepId, inner_node::InnerNode, GarbageCollectable, GcNodeGraph};
 
 /// Represents an opaque node that can be garbage collected
 pub struct GcNode<T: GarbageCollectable> {
@@ -52,6 +52,12 @@ impl<T: GarbageCollectable> GcNode<T> {
         self.node.strong_count() > 0
     }
 
+    /// Identifies the node. Identifiers are not reused while any reference to the node, including
+    /// this one, is held.
+    pub fn id(&self) -> usize {
+        self.node.as_ptr() as usize
+    }
+
     pub fn get_node_graph(&self) -> Rc<RefCell<GcNodeGraph<T>>> {
         self.node_graph.upgrade().expect("Graph not present")
     }
@@ -145,6 +151,12 @@ impl<T: GarbageCollectable> GcNode<T> {
         Self { node, node_graph }
     }
 
+    pub(super) fn is_marked(&self, gc_sweep_id: GcSweepId) -> bool {
+        self.node
+            .upgrade()
+            .is_some_and(|rc| rc.borrow().gc_sweep_id == gc_sweep_id)
+    }
+
     pub(super) fn with_mut_node<F>(&mut self, func: F)
     where
         F: FnOnce(&mut InnerNode<T>),
@@ -3,7 +3,11 @@ use std::{
     rc::{Rc, Weak},
 };
 
-use super::{gc_sweep_id::GcSweepId, inner_node::InnerNode, GarbageCollectable, GcNode};
+use super::{
+    gc_sweep_id::{next_gc_sweep_id, GcSweepId},
+    inner_node::InnerNode,
+    GarbageCollectable, GcNode,
+};
 
 /// A graph of nodes that can be garbage collected
 pub struct GcNodeGraph<T: GarbageCollectable> {
@@ -40,15 +44,25 @@ impl<T: GarbageCollectable> GcNodeGraph<T> {
     /// Runs the garbage collector, freeing up non-root nodes using a mark-and-sweep algorithm.
     pub fn gc(node_graph: &Rc<RefCell<GcNodeGraph<T>>>) {
         let mut graph = node_graph.borrow_mut();
-        graph.gc_sweep_id += 1;
+        graph.gc_sweep_id = next_gc_sweep_id(graph.gc_sweep_id);
         let gc_sweep_id = graph.gc_sweep_id;
-        Self::mark_nodes(&graph.root, gc_sweep_id);
+        Self::mark_nodes(
+            vec![GcNode::new(Rc::downgrade(&graph.root), Weak::new())],
+            gc_sweep_id,
+        );
+        graph.mark_weak_entries(gc_sweep_id);
         graph.nodes = graph
             .nodes
             .iter()
             .filter(|node| node.borrow().gc_sweep_id == gc_sweep_id)
             .map(Rc::clone)
             .collect();
+
+        // Swept nodes no longer exist, so weak entries can now check their keys
+        graph.root.borrow_mut().value.retain_weak_entries();
+        for node in &graph.nodes {
+            node.borrow_mut().value.retain_weak_entries();
+        }
     }
 
     /// Returns how many nodes are stored in the graph.
@@ -57,11 +71,9 @@ impl<T: GarbageCollectable> GcNodeGraph<T> {
         self.nodes.len() + 1
     }
 
-    /// Recurses through the nodes, finding everything reachable from the root node and updating the
-    /// sweep id.
-    fn mark_nodes(root_node: &Rc<RefCell<InnerNode<T>>>, new_gc_sweep_id: GcSweepId) {
-        let mut queue: Vec<GcNode<T>> = vec![GcNode::new(Rc::downgrade(root_node), Weak::new())];
-
+    /// Recurses through the nodes, finding everything reachable from the given nodes and updating
+    /// the sweep id.
+    fn mark_nodes(mut queue: Vec<GcNode<T>>, new_gc_sweep_id: GcSweepId) {
         while let Some(mut node) = queue.pop() {
             node.with_mut_node(|node| {
                 // Return early if this node was already visited to avoid cycles
@@ -77,6 +89,28 @@ impl<T: GarbageCollectable> GcNodeGraph<T> {
     }
 }
 
+impl<T: GarbageCollectable> GcNodeGraph<T> {
+    /// Marks the values of weak entries whose keys were reached. Marking a value may reach the
+    /// keys of other weak entries, so this repeats until nothing new is marked.
+    fn mark_weak_entries(&self, gc_sweep_id: GcSweepId) {
+        loop {
+            let newly_reachable: Vec<_> = self
+                .nodes
+                .iter()
+                .filter(|node| node.borrow().gc_sweep_id == gc_sweep_id)
+                .flat_map(|node| node.borrow().value.get_weak_entries())
+                .filter(|(key, value)| key.is_marked(gc_sweep_id) && !value.is_marked(gc_sweep_id))
+                .map(|(_key, value)| value)
+                .collect();
+
+            if newly_reachable.is_empty() {
+                return;
+            }
+            Self::mark_nodes(newly_reachable, gc_sweep_id);
+        }
+    }
+}
+
 impl<T: GarbageCollectable> std::fmt::Debug for GcNodeGraph<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GcNodeGraph")
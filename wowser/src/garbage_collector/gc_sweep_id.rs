@@ -2,3 +2,12 @@
 pub(super) type GcSweepId = u8;
 
 pub(super) const DEFAULT_GC_SWEEP_ID: GcSweepId = 0;
+
+/// The identifier of the sweep after the given one. Identifiers wrap around, skipping the default
+/// so that nodes created since the last sweep never look like they were already marked.
+pub(super) fn next_gc_sweep_id(gc_sweep_id: GcSweepId) -> GcSweepId {
+    match gc_sweep_id.wrapping_add(1) {
+        DEFAULT_GC_SWEEP_ID => DEFAULT_GC_SWEEP_ID + 1,
+        next => next,
+    }
+}
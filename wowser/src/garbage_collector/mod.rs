@@ -17,6 +17,7 @@ mod tests {
         value: u8,
         left: Option<GcNode<TestTree>>,
         right: Option<GcNode<TestTree>>,
+        weak_entries: Vec<(GcNode<TestTree>, GcNode<TestTree>)>,
     }
 
     impl GarbageCollectable for TestTree {
@@ -27,6 +28,14 @@ mod tests {
                 .map(Clone::clone)
                 .collect()
         }
+
+        fn get_weak_entries(&self) -> Vec<(GcNode<TestTree>, GcNode<TestTree>)> {
+            self.weak_entries.clone()
+        }
+
+        fn retain_weak_entries(&mut self) {
+            self.weak_entries.retain(|(key, _value)| key.exists());
+        }
     }
 
     impl TestTree {
@@ -35,6 +44,7 @@ mod tests {
                 value,
                 left: None,
                 right: None,
+                weak_entries: vec![],
             }
         }
 
@@ -201,6 +211,22 @@ mod tests {
         assert_eq!(graph.borrow().size(), 2);
     }
 
+    #[test]
+    fn test_many_garbage_collections() {
+        let (graph, mut root) = GcNodeGraph::<TestTree>::new(TestTree::new(1));
+        let kept = GcNodeGraph::create_node(&graph, TestTree::new(2));
+        // Enough collections for the sweep ids to wrap around, each reaching the old node through
+        // a new one
+        for _ in 0..600 {
+            let mut parent = GcNodeGraph::create_node(&graph, TestTree::new(3));
+            parent.with_mut(|parent| parent.left = Some(kept.clone()));
+            root.with_mut(|root| root.left = Some(parent));
+            GcNodeGraph::gc(&graph);
+            assert!(kept.exists());
+            assert_eq!(graph.borrow().size(), 3);
+        }
+    }
+
     #[test]
     fn test_drop_node_graph() {
         let node = {
@@ -248,4 +274,51 @@ mod tests {
         let other = root.create_new_node(TestTree::new(100));
         assert!(!root.is_same_ref(&other));
     }
+
+    #[test]
+    #[allow(clippy::redundant_clone)]
+    fn test_id() {
+        let (_graph, root) = GcNodeGraph::<TestTree>::new(TestTree::new(100));
+        assert_eq!(root.id(), root.clone().id());
+        let other = root.create_new_node(TestTree::new(100));
+        assert_ne!(root.id(), other.id());
+    }
+
+    #[test]
+    fn test_weak_entries() {
+        let (graph, mut root) = GcNodeGraph::<TestTree>::new(TestTree::new(1));
+        let mut weak = root.create_new_node(TestTree::new(2));
+        let key = root.create_new_node(TestTree::new(3));
+        let value = root.create_new_node(TestTree::new(4));
+        let chained_value = root.create_new_node(TestTree::new(5));
+        let unreachable_key = root.create_new_node(TestTree::new(6));
+        let unreachable_value = root.create_new_node(TestTree::new(7));
+
+        root.with_mut(|root| {
+            root.left = Some(weak.clone());
+            root.right = Some(key.clone());
+        });
+        weak.with_mut(|weak| {
+            weak.weak_entries = vec![
+                (unreachable_key.clone(), unreachable_value.clone()),
+                // Reachable only once `value` is found reachable through `key`
+                (value.clone(), chained_value.clone()),
+                (key.clone(), value.clone()),
+            ]
+        });
+        assert_eq!(graph.borrow().size(), 7);
+
+        GcNodeGraph::gc(&graph);
+        assert_eq!(graph.borrow().size(), 5);
+        assert!(value.exists());
+        assert!(chained_value.exists());
+        assert!(!unreachable_key.exists());
+        assert!(!unreachable_value.exists());
+        assert_eq!(weak.map_value(|weak| weak.weak_entries.len()), 2);
+
+        root.with_mut(|root| root.right = None);
+        GcNodeGraph::gc(&graph);
+        assert_eq!(graph.borrow().size(), 2);
+        assert!(weak.map_value(|weak| weak.weak_entries.is_empty()));
+    }
 }
@@ -9,6 +9,13 @@ use crate::parse::ParsedTokenOffset;
 pub struct HtmlDocument {
     pub doctype: DoctypeHtmlNode,
     pub html: ElementContents,
+    /// Nodes created by scripts or removed from the document, which are not rendered but may be
+    /// inserted into the document again
+    pub detached: Vec<ElementContents>,
+    /// The nodes moved into an element since they were last taken, in the order they were moved
+    inserted: Vec<ElementContentsId>,
+    /// Counts the changes made through the document, so renders can tell when it changed
+    version: u64,
 }
 
 impl HtmlDocument {
@@ -31,6 +38,154 @@ impl HtmlDocument {
         HtmlDocument {
             doctype: document_node.doctype,
             html: ElementContents::Element(html),
+            detached: vec![],
+            inserted: vec![],
+            version: 0,
+        }
+    }
+
+    /// Changes each time the document may have been changed, which is whenever a node is borrowed
+    /// mutably or moved
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Finds a node in the document or among the detached nodes
+    pub fn get_node(&self, id: ElementContentsId) -> Option<&ElementContents> {
+        std::iter::once(&self.html)
+            .chain(&self.detached)
+            .find_map(|root| root.find_first(|node| node.get_id() == id))
+    }
+
+    pub fn get_node_mut(&mut self, id: ElementContentsId) -> Option<&mut ElementContents> {
+        self.version += 1;
+        std::iter::once(&mut self.html)
+            .chain(&mut self.detached)
+            .find_map(|root| root.find_by_id_mut(id))
+    }
+
+    /// The element the node is a child of, if it has one
+    pub fn get_parent(&self, id: ElementContentsId) -> Option<&ElementHtmlNode> {
+        let parent = std::iter::once(&self.html)
+            .chain(&self.detached)
+            .find_map(|root| {
+                root.find_first(|node| match node {
+                    ElementContents::Element(element) => {
+                        element.children.iter().any(|child| child.get_id() == id)
+                    }
+                    ElementContents::Text(_) => false,
+                })
+            });
+        match parent {
+            Some(ElementContents::Element(element)) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Adds a node that is not part of the document yet, returning its identifier
+    pub fn add_detached(&mut self, node: ElementContents) -> ElementContentsId {
+        let id = node.get_id();
+        self.version += 1;
+        self.detached.push(node);
+        id
+    }
+
+    /// Removes the node from its parent, leaving it detached. Results in `false` when it has no
+    /// parent.
+    pub fn detach(&mut self, id: ElementContentsId) -> bool {
+        self.version += 1;
+        let node = std::iter::once(&mut self.html)
+            .chain(&mut self.detached)
+            .find_map(|root| root.take_descendant(id));
+        match node {
+            Some(node) => {
+                self.detached.push(node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves a node into the children of an element, before the child identified by `before` or
+    /// after the other children. Results in `false`, leaving the document unchanged, when the
+    /// parent is not an element, the node is the root or contains the parent, or `before` is not
+    /// a child of the parent.
+    pub fn insert_child(
+        &mut self,
+        parent: ElementContentsId,
+        id: ElementContentsId,
+        before: Option<ElementContentsId>,
+    ) -> bool {
+        let is_valid = match (self.get_node(parent), self.get_node(id)) {
+            (Some(ElementContents::Element(element)), Some(node)) => {
+                node.find_first(|node| node.get_id() == parent).is_none()
+                    && before.map_or(true, |before| {
+                        before != id
+                            && element
+                                .children
+                                .iter()
+                                .any(|child| child.get_id() == before)
+                    })
+            }
+            _ => false,
+        };
+        if !is_valid || id == self.html.get_id() {
+            return false;
+        }
+
+        let Some(node) = self.take_child(id) else {
+            return false;
+        };
+        if let Some(ElementContents::Element(element)) = self.get_node_mut(parent) {
+            let index = before
+                .and_then(|before| {
+                    element
+                        .children
+                        .iter()
+                        .position(|child| child.get_id() == before)
+                })
+                .unwrap_or(element.children.len());
+            element.children.insert(index, node);
+        }
+        self.inserted.push(id);
+        true
+    }
+
+    /// The nodes moved into an element since the last time they were taken. Children replaced all
+    /// at once are not included.
+    pub fn take_inserted(&mut self) -> Vec<ElementContentsId> {
+        std::mem::take(&mut self.inserted)
+    }
+
+    /// Whether the node is part of the document rather than detached
+    pub fn is_connected(&self, id: ElementContentsId) -> bool {
+        self.html.find_first(|node| node.get_id() == id).is_some()
+    }
+
+    /// Takes a node out of its parent or the detached nodes
+    fn take_child(&mut self, id: ElementContentsId) -> Option<ElementContents> {
+        self.version += 1;
+        if let Some(index) = self.detached.iter().position(|node| node.get_id() == id) {
+            return Some(self.detached.remove(index));
+        }
+        std::iter::once(&mut self.html)
+            .chain(&mut self.detached)
+            .find_map(|root| root.take_descendant(id))
+    }
+
+    /// Drops the detached nodes that have no node the predicate holds for among themselves and
+    /// their descendants
+    pub fn retain_detached(&mut self, predicate: impl Fn(ElementContentsId) -> bool) {
+        self.detached
+            .retain(|root| root.find_first(|node| predicate(node.get_id())).is_some());
+    }
+
+    /// Replaces the children of an element, detaching its previous children. Does nothing when
+    /// the node is not an element.
+    pub fn replace_children(&mut self, id: ElementContentsId, children: Vec<ElementContents>) {
+        if let Some(ElementContents::Element(element)) = self.get_node_mut(id) {
+            let previous_children = std::mem::replace(&mut element.children, children);
+            self.detached.extend(previous_children);
         }
     }
 }
@@ -101,6 +256,76 @@ impl ElementContents {
             Self::Element(node) => node.id,
         }
     }
+
+    /// The text of the node and its descendants
+    pub fn text_content(&self) -> String {
+        match self {
+            Self::Text(node) => node.text.clone(),
+            Self::Element(node) => node
+                .children
+                .iter()
+                .map(|child| child.text_content())
+                .collect(),
+        }
+    }
+
+    /// The descendants matching the predicate in document order, not including this node
+    pub fn find_descendants<P>(&self, mut predicate: P) -> Vec<&ElementContents>
+    where
+        P: FnMut(&ElementContents) -> bool,
+    {
+        let mut descendants = vec![];
+        self.collect_descendants(&mut predicate, &mut descendants);
+        descendants
+    }
+
+    fn collect_descendants<'a, P>(
+        &'a self,
+        predicate: &mut P,
+        descendants: &mut Vec<&'a ElementContents>,
+    ) where
+        P: FnMut(&ElementContents) -> bool,
+    {
+        if let Self::Element(element) = self {
+            for child in &element.children {
+                if predicate(child) {
+                    descendants.push(child);
+                }
+                child.collect_descendants(predicate, descendants);
+            }
+        }
+    }
+
+    pub fn find_by_id_mut(&mut self, id: ElementContentsId) -> Option<&mut ElementContents> {
+        if self.get_id() == id {
+            return Some(self);
+        }
+        match self {
+            Self::Element(element) => element
+                .children
+                .iter_mut()
+                .find_map(|child| child.find_by_id_mut(id)),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// Removes a descendant from its parent
+    fn take_descendant(&mut self, id: ElementContentsId) -> Option<ElementContents> {
+        let Self::Element(element) = self else {
+            return None;
+        };
+        match element
+            .children
+            .iter()
+            .position(|child| child.get_id() == id)
+        {
+            Some(index) => Some(element.children.remove(index)),
+            None => element
+                .children
+                .iter_mut()
+                .find_map(|child| child.take_descendant(id)),
+        }
+    }
     pub fn find_first<P>(&self, mut predicate: P) -> Option<&ElementContents>
     where
         P: FnMut(&ElementContents) -> bool,
@@ -197,6 +422,28 @@ impl ElementHtmlNode {
             .iter()
             .any(|attribute| attribute.name == lowercase_name)
     }
+
+    /// Sets the value of an attribute, adding it when missing
+    pub fn set_attribute(&mut self, name: &str, value: String) {
+        let lowercase_name = name.to_ascii_lowercase();
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name == lowercase_name)
+        {
+            Some(attribute) => attribute.value = Some(value),
+            None => self.attributes.push(TagAttributeHtmlNode {
+                name: lowercase_name,
+                value: Some(value),
+            }),
+        }
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        let lowercase_name = name.to_ascii_lowercase();
+        self.attributes
+            .retain(|attribute| attribute.name != lowercase_name);
+    }
 }
 
 impl Display for ElementHtmlNode {
@@ -393,6 +640,77 @@ mod tests {
         assert_eq!("<tag />", node.to_string());
     }
 
+    fn element(tag_name: &str, children: Vec<ElementContents>) -> ElementContents {
+        ElementContents::Element(ElementHtmlNode::new(0, tag_name.into(), vec![], children))
+    }
+
+    #[test]
+    fn html_document_mutation() {
+        let mut document = HtmlDocument::from(DocumentHtmlNode {
+            doctype: DoctypeHtmlNode::default(),
+            contents: vec![element("a", vec![]), element("b", vec![])],
+        });
+        let root = document.html.get_id();
+        let ids = document
+            .html
+            .find_descendants(|_| true)
+            .iter()
+            .map(|node| node.get_id())
+            .collect::<Vec<_>>();
+        let (a, b) = (ids[0], ids[1]);
+        assert_eq!(root, document.get_parent(a).unwrap().get_id());
+        assert_eq!(document.get_version(), 0);
+
+        let text = document.add_detached(ElementContents::Text(TextHtmlNode::new("text".into())));
+        assert!(document.get_parent(text).is_none());
+        assert!(document.insert_child(a, text, None));
+        assert_eq!("<html><a>text</a><b /></html>", document.html.to_string());
+        assert_eq!(a, document.get_parent(text).unwrap().get_id());
+
+        assert!(document.insert_child(root, b, Some(a)));
+        assert_eq!("<html><b /><a>text</a></html>", document.html.to_string());
+        assert_eq!("text", document.html.text_content());
+        assert_eq!(document.take_inserted(), vec![text, b]);
+        assert!(document.take_inserted().is_empty());
+
+        // Nodes cannot be moved into themselves or next to something that is not a sibling
+        assert!(!document.insert_child(a, a, None));
+        assert!(!document.insert_child(text, b, None));
+        assert!(!document.insert_child(a, b, Some(a)));
+        assert!(!document.insert_child(a, root, None));
+        assert_eq!("<html><b /><a>text</a></html>", document.html.to_string());
+
+        assert!(document.is_connected(text));
+        assert!(document.detach(a));
+        assert!(!document.detach(a));
+        assert!(!document.is_connected(text));
+        assert_eq!("<html><b /></html>", document.html.to_string());
+        assert_eq!("text", document.get_node(text).unwrap().to_string());
+
+        let version = document.get_version();
+        document.replace_children(a, vec![]);
+        assert_eq!("<a />", document.get_node(a).unwrap().to_string());
+        assert!(document.get_version() > version);
+        assert!(document.get_node(text).is_some());
+
+        document.retain_detached(|id| id == text);
+        assert!(document.get_node(a).is_none());
+        assert!(document.get_node(text).is_some());
+        document.retain_detached(|_| false);
+        assert!(document.detached.is_empty());
+    }
+
+    #[test]
+    fn set_remove_attribute() {
+        let mut element = ElementHtmlNode::new(0, "tag".into(), vec![], vec![]);
+        element.set_attribute("A", "1".into());
+        element.set_attribute("b", "2".into());
+        element.set_attribute("a", "3".into());
+        assert_eq!("<tag a=\"3\" b=\"2\" />", element.to_string());
+        element.remove_attribute("A");
+        assert_eq!("<tag b=\"2\" />", element.to_string());
+    }
+
     #[test]
     fn get_has_attribute() {
         let element = ElementHtmlNode::new(
@@ -0,0 +1,214 @@
+use super::Date;
+
+pub const MS_PER_SECOND: f64 = 1000.0;
+pub const MS_PER_MINUTE: f64 = 60.0 * MS_PER_SECOND;
+pub const MS_PER_HOUR: f64 = 60.0 * MS_PER_MINUTE;
+pub const MS_PER_DAY: f64 = 24.0 * MS_PER_HOUR;
+/// Time values are at most 100,000,000 days from the epoch
+pub const MAX_TIME: f64 = 8.64e15;
+
+/// Days per era of 400 years, after which the Gregorian calendar repeats
+const DAYS_PER_ERA: i64 = 146_097;
+/// Days from 0000-03-01 to 1970-01-01
+const EPOCH_DAYS: i64 = 719_468;
+
+/// The number of days from 1970-01-01 to a date in the proleptic Gregorian calendar. Months count
+/// from 0, and days from 1.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Years start in March so leap days fall at the end of the year
+    let year = if month < 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (i64::from(month) + 10) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * DAYS_PER_ERA + day_of_era - EPOCH_DAYS
+}
+
+/// The (Year, Month, Day) of a number of days from 1970-01-01, the inverse of [days_from_civil]
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + EPOCH_DAYS;
+    let era = days.div_euclid(DAYS_PER_ERA);
+    let day_of_era = days.rem_euclid(DAYS_PER_ERA);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = ((month_from_march + 2) % 12) as u32;
+    let year = year_of_era + era * 400 + i64::from(month < 2);
+    (year, month, day)
+}
+
+/// The parts of a time value, as shown on a calendar and clock
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DateFields {
+    pub year: i64,
+    /// From 0 for January
+    pub month: u32,
+    /// From 1
+    pub day: u32,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub milliseconds: u32,
+    /// From 0 for Sunday
+    pub weekday: u32,
+}
+
+impl DateFields {
+    /// Splits a time value into its parts, or `None` when it is `NaN`
+    pub fn from_time(time: Date) -> Option<Self> {
+        if !time.is_finite() {
+            return None;
+        }
+        let days = (time / MS_PER_DAY).floor();
+        let time_in_day = (time - days * MS_PER_DAY) as u32;
+        let days = days as i64;
+        let (year, month, day) = civil_from_days(days);
+        Some(Self {
+            year,
+            month,
+            day,
+            hours: time_in_day / MS_PER_HOUR as u32,
+            minutes: time_in_day / MS_PER_MINUTE as u32 % 60,
+            seconds: time_in_day / MS_PER_SECOND as u32 % 60,
+            milliseconds: time_in_day % MS_PER_SECOND as u32,
+            weekday: (days + 4).rem_euclid(7) as u32,
+        })
+    }
+}
+
+/// The milliseconds into a day of a time, where each part may overflow into the next.
+/// <https://tc39.es/ecma262/#sec-maketime>
+pub fn make_time(hours: f64, minutes: f64, seconds: f64, milliseconds: f64) -> f64 {
+    if ![hours, minutes, seconds, milliseconds]
+        .iter()
+        .all(|part| part.is_finite())
+    {
+        return f64::NAN;
+    }
+    hours.trunc() * MS_PER_HOUR
+        + minutes.trunc() * MS_PER_MINUTE
+        + seconds.trunc() * MS_PER_SECOND
+        + milliseconds.trunc()
+}
+
+/// The days from 1970-01-01 to a date, where months overflow into years and days overflow into
+/// months. <https://tc39.es/ecma262/#sec-makeday>
+pub fn make_day(year: f64, month: f64, day: f64) -> f64 {
+    if ![year, month, day].iter().all(|part| part.is_finite()) {
+        return f64::NAN;
+    }
+    let year = year.trunc() + (month.trunc() / 12.0).floor();
+    // Years this far out are past the range of valid time values
+    if year.abs() > 400_000.0 {
+        return f64::NAN;
+    }
+    let month = month.trunc().rem_euclid(12.0) as u32;
+    days_from_civil(year as i64, month, 1) as f64 + day.trunc() - 1.0
+}
+
+/// <https://tc39.es/ecma262/#sec-makedate>
+pub fn make_date(day: f64, time: f64) -> Date {
+    if !day.is_finite() || !time.is_finite() {
+        return f64::NAN;
+    }
+    day * MS_PER_DAY + time
+}
+
+/// Rounds a time value towards zero, or gives `NaN` when it is out of range.
+/// <https://tc39.es/ecma262/#sec-timeclip>
+pub fn time_clip(time: Date) -> Date {
+    if !time.is_finite() || time.abs() > MAX_TIME {
+        return f64::NAN;
+    }
+    // Adding zero turns negative zero into positive zero
+    time.trunc() + 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 0, 1), 0);
+        assert_eq!(days_from_civil(1969, 11, 31), -1);
+        assert_eq!(days_from_civil(2000, 2, 1), 11017);
+        assert_eq!(days_from_civil(0, 0, 1), -719_528);
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 0, 1));
+        assert_eq!(civil_from_days(-1), (1969, 11, 31));
+        assert_eq!(civil_from_days(11016), (2000, 1, 29));
+        assert_eq!(civil_from_days(-719_529), (-1, 11, 31));
+        for days in (-800_000..800_000).step_by(997) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn test_date_fields() {
+        assert_eq!(DateFields::from_time(f64::NAN), None);
+        assert_eq!(
+            DateFields::from_time(-1.0),
+            Some(DateFields {
+                year: 1969,
+                month: 11,
+                day: 31,
+                hours: 23,
+                minutes: 59,
+                seconds: 59,
+                milliseconds: 999,
+                weekday: 3,
+            })
+        );
+        assert_eq!(
+            DateFields::from_time(1_000_000_000_123.0),
+            Some(DateFields {
+                year: 2001,
+                month: 8,
+                day: 9,
+                hours: 1,
+                minutes: 46,
+                seconds: 40,
+                milliseconds: 123,
+                weekday: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_make_day() {
+        assert_eq!(make_day(1970.0, 0.0, 1.0), 0.0);
+        assert_eq!(make_day(1969.0, 12.0, 1.0), 0.0);
+        assert_eq!(make_day(1970.0, -1.0, 32.0), 0.0);
+        assert_eq!(make_day(2000.0, 1.0, 30.0), 11017.0);
+        assert!(make_day(f64::INFINITY, 0.0, 1.0).is_nan());
+        assert!(make_day(1e9, 0.0, 1.0).is_nan());
+    }
+
+    #[test]
+    fn test_make_date() {
+        let time = make_time(1.0, 46.0, 40.0, 123.0);
+        assert_eq!(
+            make_date(make_day(2001.0, 8.0, 9.0), time),
+            1_000_000_000_123.0
+        );
+        assert_eq!(make_time(0.0, 0.0, -1.5, 0.0), -1000.0);
+        assert!(make_time(0.0, f64::NAN, 0.0, 0.0).is_nan());
+    }
+
+    #[test]
+    fn test_time_clip() {
+        assert_eq!(time_clip(1.9), 1.0);
+        assert!(time_clip(-0.5).is_sign_positive());
+        assert_eq!(time_clip(MAX_TIME), MAX_TIME);
+        assert!(time_clip(MAX_TIME + 1.0).is_nan());
+        assert!(time_clip(f64::NAN).is_nan());
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::Date;
+
+/// The source of the current time, which can be replaced so results do not depend on when they
+/// are run
+pub trait Clock: Debug {
+    /// The current time in milliseconds since the epoch
+    fn now(&self) -> Date;
+}
+
+/// Reads the time from the system
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Date {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_millis() as Date,
+            Err(error) => -(error.duration().as_millis() as Date),
+        }
+    }
+}
+
+/// Reads the time from the system with sub-millisecond precision, for timing rather than dates,
+/// which are in whole milliseconds
+#[derive(Debug, Default)]
+pub struct HighResolutionClock;
+
+impl Clock for HighResolutionClock {
+    fn now(&self) -> Date {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs_f64() * 1000.0,
+            Err(error) => -error.duration().as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Stays at a time until it is moved
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    now: Cell<Date>,
+}
+
+impl FixedClock {
+    pub fn new(now: Date) -> Self {
+        Self {
+            now: Cell::new(now),
+        }
+    }
+
+    pub fn set(&self, now: Date) {
+        self.now.set(now);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Date {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock() {
+        // 2020-01-01T00:00:00Z
+        assert!(SystemClock.now() > 1_577_836_800_000.0);
+        assert_eq!(SystemClock.now().fract(), 0.0);
+    }
+
+    #[test]
+    fn test_high_resolution_clock() {
+        let before = SystemClock.now();
+        let now = HighResolutionClock.now();
+        assert!(now >= before);
+        assert!(now < before + 1000.0);
+    }
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::new(123.0);
+        assert_eq!(clock.now(), 123.0);
+        clock.set(456.0);
+        assert_eq!(clock.now(), 456.0);
+    }
+}
@@ -0,0 +1,140 @@
+use super::{Date, DateFields, TimeZone, MS_PER_MINUTE};
+
+pub const INVALID_DATE: &str = "Invalid Date";
+pub const WEEKDAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+pub const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// `YYYY-MM-DDTHH:mm:ss.sssZ`, with six digit signed years outside 0 to 9999, or `None` for an
+/// invalid time
+pub fn to_iso_string(time: Date) -> Option<String> {
+    let fields = DateFields::from_time(time)?;
+    let year = match fields.year {
+        0..=9999 => format!("{:04}", fields.year),
+        year if year < 0 => format!("-{:06}", -year),
+        year => format!("+{year:06}"),
+    };
+    Some(format!(
+        "{year}-{:02}-{:02}T{}.{:03}Z",
+        fields.month + 1,
+        fields.day,
+        format_clock(&fields),
+        fields.milliseconds
+    ))
+}
+
+/// `Tue, 18 Oct 2026 12:00:00 GMT`
+pub fn to_utc_string(time: Date) -> String {
+    match DateFields::from_time(time) {
+        Some(fields) => format!(
+            "{}, {:02} {} {} {} GMT",
+            WEEKDAY_NAMES[fields.weekday as usize],
+            fields.day,
+            MONTH_NAMES[fields.month as usize],
+            format_year(fields.year),
+            format_clock(&fields)
+        ),
+        None => INVALID_DATE.to_string(),
+    }
+}
+
+/// `Tue Oct 18 2026 12:00:00 GMT+0000` in local time
+pub fn to_local_string(time: Date, time_zone: &dyn TimeZone) -> String {
+    match (
+        to_local_date_string(time, time_zone),
+        to_local_time_string(time, time_zone),
+    ) {
+        (Some(date), Some(time)) => format!("{date} {time}"),
+        _ => INVALID_DATE.to_string(),
+    }
+}
+
+/// `Tue Oct 18 2026` in local time
+pub fn to_local_date_string(time: Date, time_zone: &dyn TimeZone) -> Option<String> {
+    let fields = DateFields::from_time(time_zone.to_local(time))?;
+    Some(format!(
+        "{} {} {:02} {}",
+        WEEKDAY_NAMES[fields.weekday as usize],
+        MONTH_NAMES[fields.month as usize],
+        fields.day,
+        format_year(fields.year)
+    ))
+}
+
+/// `12:00:00 GMT+0000` in local time
+pub fn to_local_time_string(time: Date, time_zone: &dyn TimeZone) -> Option<String> {
+    let fields = DateFields::from_time(time_zone.to_local(time))?;
+    let offset = (time_zone.offset_at(time) / MS_PER_MINUTE) as i64;
+    let sign = if offset < 0 { '-' } else { '+' };
+    Some(format!(
+        "{} GMT{sign}{:02}{:02}",
+        format_clock(&fields),
+        offset.abs() / 60,
+        offset.abs() % 60
+    ))
+}
+
+/// `HH:mm:ss`
+fn format_clock(fields: &DateFields) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        fields.hours, fields.minutes, fields.seconds
+    )
+}
+
+/// Years are at least four digits, after the sign of negative years
+fn format_year(year: i64) -> String {
+    if year < 0 {
+        format!("-{:04}", -year)
+    } else {
+        format!("{year:04}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js::api::date::FixedOffset;
+
+    #[test]
+    fn test_to_iso_string() {
+        assert_eq!(to_iso_string(0.0).unwrap(), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            to_iso_string(1_000_000_000_123.0).unwrap(),
+            "2001-09-09T01:46:40.123Z"
+        );
+        assert_eq!(
+            to_iso_string(-62_198_755_200_000.0).unwrap(),
+            "-000001-01-01T00:00:00.000Z"
+        );
+        assert_eq!(
+            to_iso_string(8.64e15).unwrap(),
+            "+275760-09-13T00:00:00.000Z"
+        );
+        assert_eq!(to_iso_string(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_to_utc_string() {
+        assert_eq!(
+            to_utc_string(1_000_000_000_123.0),
+            "Sun, 09 Sep 2001 01:46:40 GMT"
+        );
+        assert_eq!(to_utc_string(f64::NAN), "Invalid Date");
+    }
+
+    #[test]
+    fn test_to_local_string() {
+        let time_zone = FixedOffset::new(-330);
+        assert_eq!(
+            to_local_string(0.0, &time_zone),
+            "Wed Dec 31 1969 18:30:00 GMT-0530"
+        );
+        assert_eq!(
+            to_local_string(0.0, &FixedOffset::UTC),
+            "Thu Jan 01 1970 00:00:00 GMT+0000"
+        );
+        assert_eq!(to_local_string(f64::NAN, &time_zone), "Invalid Date");
+    }
+}
@@ -1 +1,17 @@
+//! Dates as time values, with the calendar math, time zones, and formats used by the JS `Date`
+//! object
+
+mod civil;
+mod clock;
+mod format;
+mod parse;
+mod time_zone;
+
+pub use civil::*;
+pub use clock::*;
+pub use format::*;
+pub use parse::*;
+pub use time_zone::*;
+
+/// Milliseconds since 1970-01-01T00:00:00Z, or `NaN` for an invalid date
 pub type Date = f64;
@@ -0,0 +1,362 @@
+use super::{
+    days_from_civil, make_date, make_time, time_clip, Date, TimeZone, MONTH_NAMES, MS_PER_MINUTE,
+    WEEKDAY_NAMES,
+};
+
+/// Parses a date in the ISO-8601 format of `toISOString`, or in the RFC-2822 style of
+/// `toUTCString` and `toString`. Gives `NaN` for anything else.
+/// <https://tc39.es/ecma262/#sec-date.parse>
+pub fn parse_date(input: &str, time_zone: &dyn TimeZone) -> Date {
+    let input = input.trim();
+    let time = parse_iso(input, time_zone).or_else(|| parse_rfc_2822(input, time_zone));
+    time_clip(time.unwrap_or(f64::NAN))
+}
+
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl Cursor<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn eat_sign(&mut self) -> Option<i64> {
+        if self.eat('+') {
+            Some(1)
+        } else if self.eat('-') {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+
+    /// Reads exactly `count` digits
+    fn digits(&mut self, count: usize) -> Option<u32> {
+        let digits = self.rest.get(..count)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.rest = &self.rest[count..];
+        digits.parse().ok()
+    }
+
+    /// Reads one or more digits, as milliseconds when they follow a decimal point
+    fn fraction(&mut self) -> Option<u32> {
+        let length = self.rest.bytes().take_while(u8::is_ascii_digit).count();
+        if length == 0 {
+            return None;
+        }
+        let digits = format!("{:0<3}", &self.rest[..length.min(3)]);
+        self.rest = &self.rest[length..];
+        digits.parse().ok()
+    }
+}
+
+/// `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`, optionally followed by `THH:mm`, `:ss`, `.sss`, and
+/// `Z` or `±HH:mm`. Years may instead be six digits with a sign. Dates alone are in UTC, while
+/// times without an offset are local.
+fn parse_iso(input: &str, time_zone: &dyn TimeZone) -> Option<Date> {
+    let mut cursor = Cursor { rest: input };
+    let year = match cursor.eat_sign() {
+        Some(sign) => {
+            let year = cursor.digits(6)?;
+            if sign < 0 && year == 0 {
+                return None;
+            }
+            sign * i64::from(year)
+        }
+        None => i64::from(cursor.digits(4)?),
+    };
+    let mut day = 1;
+    let month = if cursor.eat('-') {
+        let month = cursor.digits(2)?;
+        if cursor.eat('-') {
+            day = cursor.digits(2)?;
+        }
+        month
+    } else {
+        1
+    };
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month - 1) {
+        return None;
+    }
+
+    let mut time = None;
+    if cursor.eat('T') {
+        let hours = cursor.digits(2)?;
+        cursor.eat(':').then_some(())?;
+        let minutes = cursor.digits(2)?;
+        let seconds = if cursor.eat(':') {
+            cursor.digits(2)?
+        } else {
+            0
+        };
+        let milliseconds = if cursor.eat('.') {
+            cursor.fraction()?
+        } else {
+            0
+        };
+        let is_end_of_day = hours == 24 && minutes == 0 && seconds == 0 && milliseconds == 0;
+        if (hours > 23 && !is_end_of_day) || minutes > 59 || seconds > 59 {
+            return None;
+        }
+        time = Some(make_time(
+            hours.into(),
+            minutes.into(),
+            seconds.into(),
+            milliseconds.into(),
+        ));
+    }
+
+    let offset = if cursor.eat('Z') {
+        Some(0.0)
+    } else if let Some(sign) = cursor.eat_sign() {
+        time?;
+        let hours = cursor.digits(2)?;
+        cursor.eat(':').then_some(())?;
+        let minutes = cursor.digits(2)?;
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        Some((sign * i64::from(hours * 60 + minutes)) as f64 * MS_PER_MINUTE)
+    } else {
+        None
+    };
+    if !cursor.rest.is_empty() {
+        return None;
+    }
+
+    let days = days_from_civil(year, month - 1, day) as f64;
+    let date = make_date(days, time.unwrap_or(0.0));
+    Some(match (offset, time) {
+        (Some(offset), _) => date - offset,
+        (None, Some(_)) => time_zone.to_utc(date),
+        (None, None) => date,
+    })
+}
+
+/// Dates such as `Tue, 18 Oct 2026 12:00:00 GMT`, `Tue Oct 18 2026 12:00:00 GMT+0000`, or
+/// `18 Oct 2026 12:00 -0500`. The parts may come in any order, and a missing time is midnight.
+/// Times without a zone are local.
+fn parse_rfc_2822(input: &str, time_zone: &dyn TimeZone) -> Option<Date> {
+    let mut year = None;
+    let mut month = None;
+    let mut day = None;
+    let mut time = None;
+    let mut offset = None;
+
+    let input = strip_comments(input);
+    let words = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty());
+    for word in words {
+        let lowercase = word.to_ascii_lowercase();
+        if let Some(zone_offset) = parse_zone(&lowercase) {
+            offset = Some(zone_offset?);
+        } else if word.contains(':') {
+            time = Some(parse_clock(word)?);
+        } else if word.bytes().all(|b| b.is_ascii_digit()) {
+            let value: u32 = word.parse().ok()?;
+            if day.is_none() && word.len() <= 2 && value <= 31 {
+                day = Some(value);
+            } else if year.is_none() {
+                year = Some(match (word.len(), value) {
+                    (2, 0..=49) => 2000 + i64::from(value),
+                    (2, _) => 1900 + i64::from(value),
+                    _ => i64::from(value),
+                });
+            } else {
+                return None;
+            }
+        } else if let Some(index) = find_name(&MONTH_NAMES, &lowercase) {
+            month = Some(index as u32);
+        } else if find_name(&WEEKDAY_NAMES, &lowercase).is_none() {
+            return None;
+        }
+    }
+
+    let (year, month, day) = (year?, month?, day?);
+    if day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day) as f64;
+    let date = make_date(days, time.unwrap_or(0.0));
+    Some(match offset {
+        Some(offset) => date - offset,
+        None => time_zone.to_utc(date),
+    })
+}
+
+/// Removes parenthesized comments, such as the name of the zone at the end of `toString`
+fn strip_comments(input: &str) -> String {
+    let mut depth = 0;
+    input
+        .chars()
+        .filter(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    return false;
+                }
+                _ => {}
+            }
+            depth == 0
+        })
+        .collect()
+}
+
+/// The offset of a zone such as `GMT`, `EST`, `+0530`, or `GMT-0800`. Gives `None` when the word
+/// is not a zone, and `Some(None)` when it is one with an invalid offset.
+fn parse_zone(word: &str) -> Option<Option<f64>> {
+    let hours = match word {
+        "z" | "ut" | "utc" | "gmt" => Some(0),
+        "edt" => Some(-4),
+        "est" | "cdt" => Some(-5),
+        "cst" | "mdt" => Some(-6),
+        "mst" | "pdt" => Some(-7),
+        "pst" => Some(-8),
+        _ => None,
+    };
+    if let Some(hours) = hours {
+        return Some(Some(f64::from(hours) * 60.0 * MS_PER_MINUTE));
+    }
+
+    let numeric = ["utc", "gmt"]
+        .iter()
+        .find_map(|prefix| word.strip_prefix(prefix))
+        .unwrap_or(word);
+    let mut cursor = Cursor { rest: numeric };
+    let sign = cursor.eat_sign()?;
+    let offset = (|| {
+        let hours = cursor.digits(2)?;
+        cursor.eat(':');
+        let minutes = cursor.digits(2)?;
+        (cursor.rest.is_empty() && minutes < 60).then_some(())?;
+        Some((sign * i64::from(hours * 60 + minutes)) as f64 * MS_PER_MINUTE)
+    })();
+    Some(offset)
+}
+
+/// `HH:mm` or `HH:mm:ss`
+fn parse_clock(word: &str) -> Option<f64> {
+    let parts = word
+        .split(':')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] => (hours, minutes, 0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(make_time(hours.into(), minutes.into(), seconds.into(), 0.0))
+}
+
+/// Finds a month or weekday by its abbreviation, or by a longer name starting with it
+fn find_name(names: &[&str], word: &str) -> Option<usize> {
+    names.iter().position(|name| {
+        let name = name.to_ascii_lowercase();
+        word.len() >= 3 && word.chars().all(char::is_alphabetic) && word.starts_with(&name)
+    })
+}
+
+/// The days in a month of a year, where months count from 0
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 11 {
+        (year + 1, 0)
+    } else {
+        (year, month + 1)
+    };
+    (days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js::api::date::{FixedOffset, MS_PER_DAY};
+
+    fn parse_utc(input: &str) -> Date {
+        parse_date(input, &FixedOffset::UTC)
+    }
+
+    #[test]
+    fn test_parse_iso_dates() {
+        assert_eq!(parse_utc("1970"), 0.0);
+        assert_eq!(parse_utc("1970-02"), 2_678_400_000.0);
+        assert_eq!(parse_utc("2001-09-09"), 999_993_600_000.0);
+        assert_eq!(parse_utc("-000001-01-01"), -62_198_755_200_000.0);
+        assert_eq!(parse_utc("+275760-09-13"), 8.64e15);
+        assert!(parse_utc("+275760-09-14").is_nan());
+        assert!(parse_utc("-000000-01-01").is_nan());
+        assert!(parse_utc("2001-13-01").is_nan());
+        assert!(parse_utc("2001-02-29").is_nan());
+        assert_eq!(parse_utc("2000-02-29"), 951_782_400_000.0);
+    }
+
+    #[test]
+    fn test_parse_iso_times() {
+        assert_eq!(parse_utc("2001-09-09T01:46:40.123Z"), 1_000_000_000_123.0);
+        assert_eq!(parse_utc("2001-09-09T01:46:40.1234Z"), 1_000_000_000_123.0);
+        assert_eq!(parse_utc("2001-09-09T01:46:40.1Z"), 1_000_000_000_100.0);
+        assert_eq!(
+            parse_utc("2001-09-09T01:46Z"),
+            1_000_000_000_000.0 - 40_000.0
+        );
+        assert_eq!(parse_utc("2001-09-09T03:16:40+01:30"), 1_000_000_000_000.0);
+        assert_eq!(parse_utc("1970-01-01T24:00Z"), MS_PER_DAY);
+        assert!(parse_utc("1970-01-01T24:01Z").is_nan());
+        assert!(parse_utc("1970-01-01T1:00Z").is_nan());
+        assert!(parse_utc("1970-01-01+01:00").is_nan());
+        assert!(parse_utc("1970-01-01T00:00Zjunk").is_nan());
+    }
+
+    #[test]
+    fn test_parse_iso_local_time() {
+        let time_zone = FixedOffset::new(120);
+        // Date-only forms are UTC, while date-time forms are local
+        assert_eq!(parse_date("1970-01-01", &time_zone), 0.0);
+        assert_eq!(parse_date("1970-01-01T02:00", &time_zone), 0.0);
+        assert_eq!(parse_date("1970-01-01T02:00Z", &time_zone), 7_200_000.0);
+    }
+
+    #[test]
+    fn test_parse_rfc_2822() {
+        assert_eq!(
+            parse_utc("Sun, 09 Sep 2001 01:46:40 GMT"),
+            1_000_000_000_000.0
+        );
+        assert_eq!(parse_utc("9 Sep 2001 03:46:40 +0200"), 1_000_000_000_000.0);
+        assert_eq!(
+            parse_utc("Sat Sep 08 2001 18:46:40 GMT-0700 (Pacific Daylight Time)"),
+            1_000_000_000_000.0
+        );
+        assert_eq!(parse_utc("Sep 8 2001 21:46:40 EDT"), 1_000_000_000_000.0);
+        assert_eq!(parse_utc("September 9, 2001"), 999_993_600_000.0);
+        assert_eq!(parse_utc("1 Jan 70 00:00 GMT"), 0.0);
+        assert_eq!(
+            parse_date("Thu Jan 01 1970 01:00:00", &FixedOffset::new(60)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_utc("").is_nan());
+        assert!(parse_utc("hello").is_nan());
+        assert!(parse_utc("Sep 2001").is_nan());
+        assert!(parse_utc("31 Feb 2001").is_nan());
+        assert!(parse_utc("1 Jan 2001 25:00").is_nan());
+        assert!(parse_utc("1 Jan 2001 00:00 +9999").is_nan());
+        assert!(parse_utc("1 Jan 2001 2002 2003").is_nan());
+    }
+}
@@ -0,0 +1,244 @@
+use std::{env, fmt::Debug, fs};
+
+use super::{Date, MS_PER_MINUTE, MS_PER_SECOND};
+
+const LOCAL_TIME_PATH: &str = "/etc/localtime";
+const ZONE_INFO_PATH: &str = "/usr/share/zoneinfo";
+
+/// Converts between UTC and the local time of a place
+pub trait TimeZone: Debug {
+    /// The milliseconds added to a time in UTC to get the local time
+    fn offset_at(&self, utc: Date) -> f64;
+
+    fn to_local(&self, utc: Date) -> Date {
+        utc + self.offset_at(utc)
+    }
+
+    /// Converts a local time to UTC. Local times that are skipped or repeated by a change of offset
+    /// use the offset from before the change.
+    fn to_utc(&self, local: Date) -> Date {
+        local - self.offset_at(local - self.offset_at(local))
+    }
+}
+
+/// A time zone whose offset never changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedOffset {
+    /// Minutes ahead of UTC
+    pub minutes: i32,
+}
+
+impl FixedOffset {
+    pub const UTC: Self = Self { minutes: 0 };
+
+    pub fn new(minutes: i32) -> Self {
+        Self { minutes }
+    }
+}
+
+impl TimeZone for FixedOffset {
+    fn offset_at(&self, _utc: Date) -> f64 {
+        f64::from(self.minutes) * MS_PER_MINUTE
+    }
+}
+
+/// The time zone of the system, as described by a TZif file.
+/// <https://www.rfc-editor.org/rfc/rfc8536>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LocalTimeZone {
+    /// (Time in UTC, Offset from then on) in ascending order of time
+    transitions: Vec<(Date, f64)>,
+    /// The offset before the first transition
+    initial_offset: f64,
+}
+
+impl LocalTimeZone {
+    /// Loads the zone named by the `TZ` environment variable, or else `/etc/localtime`. Zones that
+    /// cannot be loaded are treated as UTC.
+    pub fn load() -> Self {
+        let path = match env::var("TZ") {
+            Ok(name) => {
+                let name = name.trim_start_matches(':');
+                if name.starts_with('/') {
+                    name.to_string()
+                } else {
+                    format!("{ZONE_INFO_PATH}/{name}")
+                }
+            }
+            Err(_) => LOCAL_TIME_PATH.to_string(),
+        };
+        fs::read(path)
+            .ok()
+            .and_then(|data| Self::from_tzif(&data))
+            .unwrap_or_default()
+    }
+
+    /// Reads the transitions of a TZif file, preferring the 64-bit times of version 2 and later.
+    /// The rule for times after the last transition is ignored, so the last offset stays in effect.
+    pub fn from_tzif(data: &[u8]) -> Option<Self> {
+        let header = TzifHeader::parse(data)?;
+        if header.version == 0 {
+            return header.parse_block(data.get(TZIF_HEADER_LENGTH..)?, 4);
+        }
+        let v2_data = data.get(TZIF_HEADER_LENGTH + header.v1_block_length()..)?;
+        let v2_header = TzifHeader::parse(v2_data)?;
+        v2_header.parse_block(v2_data.get(TZIF_HEADER_LENGTH..)?, 8)
+    }
+}
+
+impl TimeZone for LocalTimeZone {
+    fn offset_at(&self, utc: Date) -> f64 {
+        let index = self
+            .transitions
+            .partition_point(|(transition, _offset)| *transition <= utc);
+        match index.checked_sub(1) {
+            Some(index) => self.transitions[index].1,
+            None => self.initial_offset,
+        }
+    }
+}
+
+const TZIF_HEADER_LENGTH: usize = 44;
+const TZIF_TYPE_LENGTH: usize = 6;
+
+struct TzifHeader {
+    version: u8,
+    is_ut_count: usize,
+    is_std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl TzifHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let version = match data.get(4)? {
+            0 => 0,
+            version => version.checked_sub(b'0')?,
+        };
+        let count = |index: usize| -> Option<usize> {
+            let start = 20 + index * 4;
+            let bytes = data.get(start..start + 4)?;
+            Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+        };
+        Some(Self {
+            version,
+            is_ut_count: count(0)?,
+            is_std_count: count(1)?,
+            leap_count: count(2)?,
+            time_count: count(3)?,
+            type_count: count(4)?,
+            char_count: count(5)?,
+        })
+    }
+
+    fn v1_block_length(&self) -> usize {
+        self.time_count * 5
+            + self.type_count * TZIF_TYPE_LENGTH
+            + self.char_count
+            + self.leap_count * 8
+            + self.is_std_count
+            + self.is_ut_count
+    }
+
+    fn parse_block(&self, data: &[u8], time_size: usize) -> Option<LocalTimeZone> {
+        let times_length = self.time_count * time_size;
+        let times = data.get(..times_length)?;
+        let type_indices = data.get(times_length..times_length + self.time_count)?;
+        let types_start = times_length + self.time_count;
+        let types = data.get(types_start..types_start + self.type_count * TZIF_TYPE_LENGTH)?;
+
+        let offsets: Vec<f64> = types
+            .chunks_exact(TZIF_TYPE_LENGTH)
+            .map(|local_type| {
+                let offset = i32::from_be_bytes(local_type[..4].try_into().unwrap());
+                f64::from(offset) * MS_PER_SECOND
+            })
+            .collect();
+        let transitions = times
+            .chunks_exact(time_size)
+            .zip(type_indices)
+            .map(|(time, type_index)| {
+                let seconds = match time_size {
+                    4 => i64::from(i32::from_be_bytes(time.try_into().ok()?)),
+                    _ => i64::from_be_bytes(time.try_into().ok()?),
+                };
+                let offset = *offsets.get(usize::from(*type_index))?;
+                Some((seconds as f64 * MS_PER_SECOND, offset))
+            })
+            .collect::<Option<_>>()?;
+        Some(LocalTimeZone {
+            transitions,
+            initial_offset: *offsets.first()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 1 TZif file for a zone one hour ahead of UTC that moves to two hours ahead at
+    /// 1,000,000 seconds after the epoch
+    fn build_tzif() -> Vec<u8> {
+        let mut data = b"TZif".to_vec();
+        data.push(0);
+        data.extend([0; 15]);
+        for count in [0u32, 0, 0, 1, 2, 4] {
+            data.extend(count.to_be_bytes());
+        }
+        data.extend(1_000_000i32.to_be_bytes());
+        data.push(1);
+        for (offset, designation) in [(3600i32, 0u8), (7200, 0)] {
+            data.extend(offset.to_be_bytes());
+            data.extend([0, designation]);
+        }
+        data.extend(b"ABC\0");
+        data
+    }
+
+    #[test]
+    fn test_fixed_offset() {
+        let time_zone = FixedOffset::new(-90);
+        assert_eq!(time_zone.offset_at(0.0), -5_400_000.0);
+        assert_eq!(time_zone.to_local(5_400_000.0), 0.0);
+        assert_eq!(time_zone.to_utc(0.0), 5_400_000.0);
+        assert_eq!(FixedOffset::UTC.to_utc(123.0), 123.0);
+    }
+
+    #[test]
+    fn test_from_tzif() {
+        let time_zone = LocalTimeZone::from_tzif(&build_tzif()).unwrap();
+        assert_eq!(time_zone.offset_at(-1e12), 3_600_000.0);
+        assert_eq!(time_zone.offset_at(999_999_999.0), 3_600_000.0);
+        assert_eq!(time_zone.offset_at(1e9), 7_200_000.0);
+        assert_eq!(time_zone.offset_at(1e15), 7_200_000.0);
+    }
+
+    #[test]
+    fn test_to_utc_across_transition() {
+        let time_zone = LocalTimeZone::from_tzif(&build_tzif()).unwrap();
+        assert_eq!(time_zone.to_utc(3_600_000.0), 0.0);
+        assert_eq!(time_zone.to_utc(1e9 + 7_200_000.0), 1e9);
+        // Skipped local times use the offset from before the change
+        assert_eq!(time_zone.to_utc(1e9 + 3_600_000.0), 1e9);
+    }
+
+    #[test]
+    fn test_from_invalid_tzif() {
+        assert_eq!(LocalTimeZone::from_tzif(b"TZif"), None);
+        assert_eq!(LocalTimeZone::from_tzif(b"not a time zone"), None);
+        let mut truncated = build_tzif();
+        truncated.truncate(50);
+        assert_eq!(LocalTimeZone::from_tzif(&truncated), None);
+    }
+
+    #[test]
+    fn test_default_is_utc() {
+        assert_eq!(LocalTimeZone::default().offset_at(1e12), 0.0);
+    }
+}
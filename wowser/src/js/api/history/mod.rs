@@ -0,0 +1,3 @@
+mod session_history;
+
+pub use session_history::*;
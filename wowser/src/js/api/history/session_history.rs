@@ -0,0 +1,220 @@
+use crate::{js::api::index_db::IDBValue, net::UrlRecord};
+
+/// A page the tab has been on, which is either a document it loaded or a state a script pushed
+/// onto the document
+/// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#session-history-entry>
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionHistoryEntry {
+    pub url: UrlRecord,
+    /// The state given to `pushState` or `replaceState`, serialized so it outlives the document
+    pub state: Option<IDBValue>,
+    /// Identifies the document the entry belongs to. The entries pushed by the scripts of a
+    /// document, and its fragment navigations, share it.
+    pub document_id: u64,
+    /// How far the page was scrolled when it was left, which is restored when coming back to it
+    pub scroll_y: f32,
+}
+
+/// The navigations scripts asked for, which the tab carries out once they finish running
+#[derive(Clone, Debug, PartialEq)]
+pub enum NavigationRequest {
+    /// Loads a new document, replacing the current entry rather than adding one when `replace`
+    Navigate { url: UrlRecord, replace: bool },
+    /// Loads the document of the current entry again
+    Reload,
+    /// Goes back or forward by the number of entries
+    Traverse(isize),
+    /// Only the fragment of the URL changed, which was done in place, so the tab scrolls to it and
+    /// fires `hashchange`
+    FragmentNavigated {
+        old_url: UrlRecord,
+        new_url: UrlRecord,
+    },
+}
+
+/// The entries the tab can go back and forward through, shared by the tab and its documents
+/// <https://html.spec.whatwg.org/multipage/document-sequences.html#tn-session-history-entries>
+#[derive(Debug, Default)]
+pub struct SessionHistory {
+    entries: Vec<SessionHistoryEntry>,
+    index: usize,
+    next_document_id: u64,
+    requests: Vec<NavigationRequest>,
+}
+
+impl SessionHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The entry the tab is on, which there is none of until it loads a document
+    pub fn current(&self) -> Option<&SessionHistoryEntry> {
+        self.entries.get(self.index)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut SessionHistoryEntry> {
+        self.entries.get_mut(self.index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Adds the entry of a new document after the current one, dropping the entries that were
+    /// forward of it, or replaces the current entry. Results in the identifier of the document.
+    pub fn push_document(&mut self, url: UrlRecord, replace: bool) -> u64 {
+        let document_id = self.next_document_id;
+        self.next_document_id += 1;
+        self.push_entry(
+            SessionHistoryEntry {
+                url,
+                state: None,
+                document_id,
+                scroll_y: 0.0,
+            },
+            replace,
+        );
+        document_id
+    }
+
+    /// Adds an entry for the current document after the current one, as `pushState` and fragment
+    /// navigations do, or replaces the current entry as `replaceState` does. The page stays where
+    /// it was scrolled to. The first entry is one of a new document.
+    pub fn push_same_document(&mut self, url: UrlRecord, state: Option<IDBValue>, replace: bool) {
+        let Some(current) = self.current() else {
+            self.push_document(url, replace);
+            if let Some(entry) = self.current_mut() {
+                entry.state = state;
+            }
+            return;
+        };
+        let entry = SessionHistoryEntry {
+            url,
+            state,
+            document_id: current.document_id,
+            scroll_y: current.scroll_y,
+        };
+        self.push_entry(entry, replace);
+    }
+
+    fn push_entry(&mut self, entry: SessionHistoryEntry, replace: bool) {
+        if replace && !self.entries.is_empty() {
+            self.entries[self.index] = entry;
+            return;
+        }
+        self.entries.truncate(self.index + 1);
+        self.entries.push(entry);
+        self.index = self.entries.len() - 1;
+    }
+
+    /// Moves back or forward by the number of entries, which does nothing when there is no entry
+    /// that far. Results in whether it moved.
+    pub fn traverse(&mut self, delta: isize) -> bool {
+        let Some(index) = self.index.checked_add_signed(delta) else {
+            return false;
+        };
+        if delta == 0 || index >= self.entries.len() {
+            return false;
+        }
+        self.index = index;
+        true
+    }
+
+    /// Asks the tab to navigate once the script running finishes
+    pub fn request(&mut self, request: NavigationRequest) {
+        self.requests.push(request);
+    }
+
+    /// The navigations asked for since they were last taken, in order
+    pub fn take_requests(&mut self) -> Vec<NavigationRequest> {
+        std::mem::take(&mut self.requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> UrlRecord {
+        UrlRecord::parse(url, None).unwrap()
+    }
+
+    fn current_url(history: &SessionHistory) -> String {
+        history.current().unwrap().url.to_string()
+    }
+
+    #[test]
+    fn test_push_document() {
+        let mut history = SessionHistory::new();
+        assert!(history.current().is_none());
+        let first = history.push_document(url("http://example.com/a"), false);
+        let second = history.push_document(url("http://example.com/b"), false);
+        assert_ne!(first, second);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.index(), 1);
+        assert_eq!(current_url(&history), "http://example.com/b");
+
+        let third = history.push_document(url("http://example.com/c"), true);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.current().unwrap().document_id, third);
+        assert_eq!(current_url(&history), "http://example.com/c");
+    }
+
+    #[test]
+    fn test_push_same_document() {
+        let mut history = SessionHistory::new();
+        let document_id = history.push_document(url("http://example.com/"), false);
+        history.push_same_document(url("http://example.com/#a"), None, false);
+        history.push_same_document(
+            url("http://example.com/b"),
+            Some(IDBValue::Number(1.0)),
+            true,
+        );
+        assert_eq!(history.len(), 2);
+        let current = history.current().unwrap();
+        assert_eq!(current.document_id, document_id);
+        assert_eq!(current.state, Some(IDBValue::Number(1.0)));
+        assert_eq!(current_url(&history), "http://example.com/b");
+    }
+
+    #[test]
+    fn test_traverse() {
+        let mut history = SessionHistory::new();
+        for path in ["a", "b", "c"] {
+            history.push_document(url(&format!("http://example.com/{path}")), false);
+        }
+        assert!(history.traverse(-2));
+        assert_eq!(current_url(&history), "http://example.com/a");
+        assert!(!history.traverse(-1));
+        assert!(!history.traverse(3));
+        assert!(!history.traverse(0));
+        assert!(history.traverse(1));
+        assert_eq!(current_url(&history), "http://example.com/b");
+
+        // Navigating drops the entries forward of the current one
+        history.push_document(url("http://example.com/d"), false);
+        assert_eq!(history.len(), 3);
+        assert!(!history.traverse(1));
+        assert_eq!(current_url(&history), "http://example.com/d");
+    }
+
+    #[test]
+    fn test_requests() {
+        let mut history = SessionHistory::new();
+        history.request(NavigationRequest::Traverse(-1));
+        history.request(NavigationRequest::Reload);
+        assert_eq!(
+            history.take_requests(),
+            vec![NavigationRequest::Traverse(-1), NavigationRequest::Reload]
+        );
+        assert!(history.take_requests().is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    ops::{Bound, RangeBounds},
+};
+
+use super::{
+    IDBError, IDBKey, IDBKeyRange, IDBObjectStore, IDBObjectStoreData, IDBResult, IDBValue,
+};
+
+/// <https://w3c.github.io/IndexedDB/#cursor-direction>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IDBCursorDirection {
+    Next,
+    /// Skips the records with the same key as the one before
+    NextUnique,
+    Prev,
+    /// Skips the records with the same key as the one before, going to the record with the lowest
+    /// primary key of each key
+    PrevUnique,
+}
+
+impl IDBCursorDirection {
+    fn is_forward(self) -> bool {
+        matches!(self, Self::Next | Self::NextUnique)
+    }
+
+    fn is_unique(self) -> bool {
+        matches!(self, Self::NextUnique | Self::PrevUnique)
+    }
+}
+
+/// Iterates over the records of an object store or index that are in a range. Moving the cursor
+/// consumes it, resulting in `None` once there are no more records.
+/// <https://w3c.github.io/IndexedDB/#cursor-interface>
+pub struct IDBCursor {
+    object_store: IDBObjectStore,
+    /// The index iterated over, or `None` for the object store
+    index: Option<String>,
+    range: IDBKeyRange,
+    direction: IDBCursorDirection,
+    key: IDBKey,
+    primary_key: IDBKey,
+    /// `None` for cursors over keys only
+    value: Option<IDBValue>,
+    key_only: bool,
+}
+
+impl IDBCursor {
+    pub(super) fn open(
+        object_store: IDBObjectStore,
+        index: Option<String>,
+        range: IDBKeyRange,
+        direction: IDBCursorDirection,
+        key_only: bool,
+    ) -> IDBResult<Option<Self>> {
+        let record = object_store.read(|data| {
+            find_record(
+                data,
+                index.as_deref(),
+                &range,
+                direction,
+                Start::Beginning,
+                key_only,
+            )
+        })?;
+        Ok(record.map(|(key, primary_key, value)| Self {
+            object_store,
+            index,
+            range,
+            direction,
+            key,
+            primary_key,
+            value,
+            key_only,
+        }))
+    }
+
+    pub fn direction(&self) -> IDBCursorDirection {
+        self.direction
+    }
+
+    /// The key of the record, which is the index key for cursors over indexes
+    pub fn key(&self) -> &IDBKey {
+        &self.key
+    }
+
+    /// The key of the record in the object store
+    pub fn primary_key(&self) -> &IDBKey {
+        &self.primary_key
+    }
+
+    /// `None` for cursors over keys only
+    pub fn value(&self) -> Option<&IDBValue> {
+        self.value.as_ref()
+    }
+
+    /// Moves past the count of records
+    pub fn advance(self, count: u32) -> IDBResult<Option<Self>> {
+        if count == 0 {
+            return Err(IDBError::TypeError);
+        }
+        let mut cursor = self;
+        for _ in 0..count {
+            match cursor.iterate(None, None)? {
+                Some(next) => cursor = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(cursor))
+    }
+
+    /// Moves to the next record, or to the first record at or past the key in the direction of the
+    /// cursor. Fails with a `DataError` when the key is not past the cursor.
+    pub fn continue_to(self, key: Option<IDBKey>) -> IDBResult<Option<Self>> {
+        if let Some(key) = &key {
+            if !self.is_before(key, None) {
+                return Err(IDBError::DataError);
+            }
+        }
+        self.iterate(key.as_ref(), None)
+    }
+
+    /// Moves to the first record at or past the key and primary key in the direction of the cursor,
+    /// which can only be done for cursors over indexes that do not skip duplicate keys
+    pub fn continue_primary_key(self, key: IDBKey, primary_key: IDBKey) -> IDBResult<Option<Self>> {
+        if self.index.is_none() || self.direction.is_unique() {
+            return Err(IDBError::InvalidAccessError);
+        }
+        if !self.is_before(&key, Some(&primary_key)) {
+            return Err(IDBError::DataError);
+        }
+        self.iterate(Some(&key), Some(&primary_key))
+    }
+
+    /// Replaces the value of the record, which must keep its key for object stores with in-line
+    /// keys
+    pub fn update(&self, value: IDBValue) -> IDBResult<IDBKey> {
+        if self.key_only {
+            return Err(IDBError::InvalidState);
+        }
+        match self.object_store.key_path()? {
+            Some(key_path) => {
+                if key_path.extract_key(&value).as_ref() != Some(&self.primary_key) {
+                    return Err(IDBError::DataError);
+                }
+                self.object_store.put(value, None)
+            }
+            None => self.object_store.put(value, Some(self.primary_key.clone())),
+        }
+    }
+
+    /// Removes the record, leaving the cursor where it is
+    pub fn delete(&self) -> IDBResult<()> {
+        if self.key_only {
+            return Err(IDBError::InvalidState);
+        }
+        self.object_store
+            .delete(IDBKeyRange::only(self.primary_key.clone()))
+    }
+
+    /// Whether the key, and then the primary key, is past the cursor in its direction
+    fn is_before(&self, key: &IDBKey, primary_key: Option<&IDBKey>) -> bool {
+        let ordering = match key.cmp(&self.key) {
+            Ordering::Equal => match primary_key {
+                Some(primary_key) => primary_key.cmp(&self.primary_key),
+                None => Ordering::Equal,
+            },
+            ordering => ordering,
+        };
+        match self.direction.is_forward() {
+            true => ordering == Ordering::Greater,
+            false => ordering == Ordering::Less,
+        }
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#iterate-a-cursor>
+    fn iterate(
+        mut self,
+        key: Option<&IDBKey>,
+        primary_key: Option<&IDBKey>,
+    ) -> IDBResult<Option<Self>> {
+        let start = match key {
+            Some(key) => Start::At(key, primary_key.map_or(Bound::Unbounded, Bound::Included)),
+            None if self.direction.is_unique() => Start::Past(&self.key),
+            None => Start::At(&self.key, Bound::Excluded(&self.primary_key)),
+        };
+        let record = self.object_store.read(|data| {
+            find_record(
+                data,
+                self.index.as_deref(),
+                &self.range,
+                self.direction,
+                start,
+                self.key_only,
+            )
+        })?;
+        let Some((key, primary_key, value)) = record else {
+            return Ok(None);
+        };
+        self.key = key;
+        self.primary_key = primary_key;
+        self.value = value;
+        Ok(Some(self))
+    }
+}
+
+/// Where a cursor looks for its next record
+#[derive(Clone, Copy)]
+enum Start<'a> {
+    /// At the first record of the range in the direction
+    Beginning,
+    /// At the key, with a primary key within the bound, or at the keys past it
+    At(&'a IDBKey, Bound<&'a IDBKey>),
+    /// At the keys past the key
+    Past(&'a IDBKey),
+}
+
+type Keys<'a> = Box<dyn DoubleEndedIterator<Item = &'a IDBKey> + 'a>;
+
+/// The records of an object store or index, as keys that each have primary keys
+#[derive(Clone, Copy)]
+enum CursorRecords<'a> {
+    ObjectStore(&'a BTreeMap<IDBKey, IDBValue>),
+    Index(&'a BTreeMap<IDBKey, BTreeSet<IDBKey>>),
+}
+
+impl<'a> CursorRecords<'a> {
+    fn keys(self, lower: Bound<&IDBKey>, upper: Bound<&IDBKey>) -> Keys<'a> {
+        if is_empty_range(lower, upper) {
+            return Box::new(iter::empty());
+        }
+        match self {
+            Self::ObjectStore(records) => Box::new(
+                records
+                    .range::<IDBKey, _>((lower, upper))
+                    .map(|(key, _)| key),
+            ),
+            Self::Index(records) => Box::new(
+                records
+                    .range::<IDBKey, _>((lower, upper))
+                    .map(|(key, _)| key),
+            ),
+        }
+    }
+
+    /// The primary keys of the key within the bounds, where records of object stores have their
+    /// key as their primary key
+    fn primary_keys(self, key: &IDBKey, lower: Bound<&IDBKey>, upper: Bound<&IDBKey>) -> Keys<'a> {
+        match self {
+            Self::ObjectStore(records) => Box::new(
+                records
+                    .get_key_value(key)
+                    .map(|(key, _)| key)
+                    .filter(|key| (lower, upper).contains(*key))
+                    .into_iter(),
+            ),
+            Self::Index(records) => match records.get(key) {
+                Some(primary_keys) if !is_empty_range(lower, upper) => {
+                    Box::new(primary_keys.range::<IDBKey, _>((lower, upper)))
+                }
+                _ => Box::new(iter::empty()),
+            },
+        }
+    }
+}
+
+/// Finds the record at the start or past it in the direction, resulting in its key, primary key,
+/// and value unless only keys are wanted
+fn find_record(
+    object_store: &IDBObjectStoreData,
+    index: Option<&str>,
+    range: &IDBKeyRange,
+    direction: IDBCursorDirection,
+    start: Start,
+    key_only: bool,
+) -> IDBResult<Option<(IDBKey, IDBKey, Option<IDBValue>)>> {
+    let records = match index {
+        Some(index) => CursorRecords::Index(&object_store.get_index(index)?.records),
+        None => CursorRecords::ObjectStore(&object_store.records),
+    };
+    let record = match direction.is_forward() {
+        true => find_next(records, range, start),
+        false => find_prev(records, range, start, direction.is_unique()),
+    };
+    Ok(record.map(|(key, primary_key)| {
+        let value = match key_only {
+            true => None,
+            false => object_store.records.get(&primary_key).cloned(),
+        };
+        (key, primary_key, value)
+    }))
+}
+
+/// Finds the first record from the start in key order, which is the one with the lowest primary
+/// key for each key
+fn find_next(
+    records: CursorRecords,
+    range: &IDBKeyRange,
+    start: Start,
+) -> Option<(IDBKey, IDBKey)> {
+    let lower = match start {
+        Start::Beginning => range.lower(),
+        Start::At(key, primary_lower) => {
+            if range.includes(key) {
+                let primary_key = records
+                    .primary_keys(key, primary_lower, Bound::Unbounded)
+                    .next();
+                if let Some(primary_key) = primary_key {
+                    return Some((key.clone(), primary_key.clone()));
+                }
+            }
+            max_lower(range.lower(), Bound::Excluded(key))
+        }
+        Start::Past(key) => max_lower(range.lower(), Bound::Excluded(key)),
+    };
+    let key = records.keys(lower, range.upper()).next()?;
+    let primary_key = records
+        .primary_keys(key, Bound::Unbounded, Bound::Unbounded)
+        .next()?;
+    Some((key.clone(), primary_key.clone()))
+}
+
+/// Finds the first record from the start in reverse key order, which is the one with the highest
+/// primary key for each key, or the lowest for unique cursors
+fn find_prev(
+    records: CursorRecords,
+    range: &IDBKeyRange,
+    start: Start,
+    unique: bool,
+) -> Option<(IDBKey, IDBKey)> {
+    let get_primary_key = |key: &IDBKey, upper: Bound<&IDBKey>| {
+        let mut primary_keys = records.primary_keys(key, Bound::Unbounded, upper);
+        match unique {
+            true => primary_keys.next(),
+            false => primary_keys.next_back(),
+        }
+    };
+    let upper = match start {
+        Start::Beginning => range.upper(),
+        Start::At(key, primary_upper) => {
+            if range.includes(key) {
+                let primary_key = get_primary_key(key, primary_upper);
+                if let Some(primary_key) = primary_key {
+                    return Some((key.clone(), primary_key.clone()));
+                }
+            }
+            min_upper(range.upper(), Bound::Excluded(key))
+        }
+        Start::Past(key) => min_upper(range.upper(), Bound::Excluded(key)),
+    };
+    let key = records.keys(range.lower(), upper).next_back()?;
+    let primary_key = get_primary_key(key, Bound::Unbounded)?;
+    Some((key.clone(), primary_key.clone()))
+}
+
+/// Whether no key is within the bounds, which ordered collections cannot make ranges of
+fn is_empty_range(lower: Bound<&IDBKey>, upper: Bound<&IDBKey>) -> bool {
+    match (lower, upper) {
+        (Bound::Included(lower), Bound::Included(upper)) => lower > upper,
+        (
+            Bound::Included(lower) | Bound::Excluded(lower),
+            Bound::Included(upper) | Bound::Excluded(upper),
+        ) => lower >= upper,
+        _ => false,
+    }
+}
+
+fn max_lower<'a>(a: Bound<&'a IDBKey>, b: Bound<&'a IDBKey>) -> Bound<&'a IDBKey> {
+    match (a, b) {
+        (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound,
+        (
+            Bound::Included(a_key) | Bound::Excluded(a_key),
+            Bound::Included(b_key) | Bound::Excluded(b_key),
+        ) => match a_key.cmp(b_key) {
+            Ordering::Greater => a,
+            Ordering::Less => b,
+            Ordering::Equal if matches!(a, Bound::Excluded(_)) => a,
+            Ordering::Equal => b,
+        },
+    }
+}
+
+fn min_upper<'a>(a: Bound<&'a IDBKey>, b: Bound<&'a IDBKey>) -> Bound<&'a IDBKey> {
+    match (a, b) {
+        (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound,
+        (
+            Bound::Included(a_key) | Bound::Excluded(a_key),
+            Bound::Included(b_key) | Bound::Excluded(b_key),
+        ) => match a_key.cmp(b_key) {
+            Ordering::Less => a,
+            Ordering::Greater => b,
+            Ordering::Equal if matches!(a, Bound::Excluded(_)) => a,
+            Ordering::Equal => b,
+        },
+    }
+}
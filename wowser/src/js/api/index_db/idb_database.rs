@@ -1,31 +1,227 @@
-use super::{IDBObjectStore, IDBResult, IndexDbVersion};
+use std::{cell::RefCell, collections::BTreeMap, path::PathBuf, rc::Rc};
 
-#[allow(dead_code)]
+use super::{
+    save_database, IDBError, IDBKeyPath, IDBObjectStore, IDBObjectStoreData, IDBResult,
+    IDBTransaction, IDBTransactionMode, IndexDbVersion,
+};
+
+/// The object stores of a database
+/// <https://w3c.github.io/IndexedDB/#database-construct>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IDBDatabaseData {
+    pub name: String,
+    /// 0 for databases that have not been created yet
+    pub version: IndexDbVersion,
+    pub object_stores: BTreeMap<String, IDBObjectStoreData>,
+}
+
+impl IDBDatabaseData {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: 0,
+            object_stores: BTreeMap::new(),
+        }
+    }
+}
+
+/// A database as shared by its connections and their transactions, which is saved to its file
+/// when transactions that change it commit
+#[derive(Debug)]
+pub struct IDBBackingStore {
+    pub data: IDBDatabaseData,
+    /// `None` for databases kept in memory
+    path: Option<PathBuf>,
+    /// The transactions that have not finished, with their modes and scopes
+    active_transactions: Vec<(u64, IDBTransactionMode, Vec<String>)>,
+    next_transaction_id: u64,
+    /// Set once the database is deleted, which closes its connections
+    pub deleted: bool,
+}
+
+impl IDBBackingStore {
+    pub fn new(data: IDBDatabaseData, path: Option<PathBuf>) -> Self {
+        Self {
+            data,
+            path,
+            active_transactions: vec![],
+            next_transaction_id: 0,
+            deleted: false,
+        }
+    }
+
+    pub fn has_active_transactions(&self) -> bool {
+        !self.active_transactions.is_empty()
+    }
+
+    /// Registers a transaction, which fails when another transaction would see its changes before
+    /// they are committed. Only read-only transactions run alongside others, since committing
+    /// saves the whole database, which must not include the changes of other transactions.
+    /// Transactions run as soon as they are created, so they cannot wait for each other.
+    /// <https://w3c.github.io/IndexedDB/#transaction-scheduling>
+    pub(super) fn start_transaction(
+        &mut self,
+        mode: IDBTransactionMode,
+        scope: &[String],
+    ) -> IDBResult<u64> {
+        let conflicts = self
+            .active_transactions
+            .iter()
+            .any(|(_, other_mode, other_scope)| match (mode, *other_mode) {
+                (IDBTransactionMode::ReadOnly, IDBTransactionMode::ReadOnly) => false,
+                (IDBTransactionMode::ReadOnly, IDBTransactionMode::ReadWrite) => {
+                    scope.iter().any(|name| other_scope.contains(name))
+                }
+                _ => true,
+            });
+        if conflicts {
+            return Err(IDBError::InvalidState);
+        }
+        let id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+        self.active_transactions.push((id, mode, scope.to_vec()));
+        Ok(id)
+    }
+
+    pub(super) fn finish_transaction(&mut self, id: u64) {
+        self.active_transactions
+            .retain(|(active_id, _, _)| *active_id != id);
+    }
+
+    /// Writes the database to its file
+    pub(super) fn save(&self) -> IDBResult<()> {
+        match &self.path {
+            Some(path) if !self.deleted => save_database(path, &self.data),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A connection to a database, which is made by [`super::IDBFactory::open`]
+/// <https://w3c.github.io/IndexedDB/#database-interface>
 pub struct IDBDatabase {
-    name: String,
-    version: IndexDbVersion,
-    object_store_names: Vec<String>,
+    backing_store: Rc<RefCell<IDBBackingStore>>,
+    /// The transaction upgrading the database while it is being opened
+    pub(super) upgrade_transaction: Option<IDBTransaction>,
+    closed: bool,
 }
 
 impl IDBDatabase {
-    pub fn new(name: &str, version: IndexDbVersion) -> Self {
+    pub(super) fn new(backing_store: Rc<RefCell<IDBBackingStore>>) -> Self {
         Self {
-            name: name.to_string(),
-            version,
-            object_store_names: vec![],
+            backing_store,
+            upgrade_transaction: None,
+            closed: false,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.backing_store.borrow().data.name.clone()
+    }
+
+    pub fn version(&self) -> IndexDbVersion {
+        self.backing_store.borrow().data.version
+    }
+
+    /// The names of the object stores in order
+    pub fn object_store_names(&self) -> Vec<String> {
+        self.backing_store
+            .borrow()
+            .data
+            .object_stores
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Starts a transaction over the object stores, which commits once it and everything using it
+    /// are dropped, or when committed explicitly
+    pub fn transaction(
+        &self,
+        store_names: &[&str],
+        mode: IDBTransactionMode,
+    ) -> IDBResult<IDBTransaction> {
+        if self.closed || self.backing_store.borrow().deleted {
+            return Err(IDBError::InvalidState);
+        }
+        if mode == IDBTransactionMode::VersionChange {
+            return Err(IDBError::TypeError);
+        }
+        if store_names.is_empty() {
+            return Err(IDBError::InvalidAccessError);
         }
+        let mut scope: Vec<String> = vec![];
+        for name in store_names {
+            if !self
+                .backing_store
+                .borrow()
+                .data
+                .object_stores
+                .contains_key(*name)
+            {
+                return Err(IDBError::NotFoundError);
+            }
+            if !scope.iter().any(|scope_name| scope_name == name) {
+                scope.push(name.to_string());
+            }
+        }
+        IDBTransaction::new(self.backing_store.clone(), mode, scope)
     }
 
+    /// Creates an object store, which can only be done while upgrading the database. Stores with
+    /// a key path read keys from their values, and stores that auto increment generate keys.
     pub fn create_object_store(
         &mut self,
         name: &str,
-        key_path: Option<&str>,
+        key_path: Option<IDBKeyPath>,
         auto_increment: Option<bool>,
     ) -> IDBResult<IDBObjectStore> {
-        Ok(IDBObjectStore::new(
-            name,
-            key_path,
-            auto_increment.unwrap_or(false),
-        ))
+        let transaction = self
+            .upgrade_transaction
+            .clone()
+            .ok_or(IDBError::InvalidState)?;
+        let auto_increment = auto_increment.unwrap_or(false);
+        transaction.upgrade(|database| {
+            if database.object_stores.contains_key(name) {
+                return Err(IDBError::ConstraintError);
+            }
+            match &key_path {
+                Some(key_path) if !key_path.is_valid() => return Err(IDBError::DataError),
+                Some(IDBKeyPath::Array(_)) if auto_increment => {
+                    return Err(IDBError::InvalidAccessError)
+                }
+                Some(IDBKeyPath::String(path)) if auto_increment && path.is_empty() => {
+                    return Err(IDBError::InvalidAccessError)
+                }
+                _ => {}
+            }
+            database.object_stores.insert(
+                name.to_string(),
+                IDBObjectStoreData::new(name, key_path, auto_increment),
+            );
+            Ok(())
+        })?;
+        Ok(IDBObjectStore::new(transaction, name))
+    }
+
+    /// Can only be done while upgrading the database
+    pub fn delete_object_store(&mut self, name: &str) -> IDBResult<()> {
+        let transaction = self
+            .upgrade_transaction
+            .clone()
+            .ok_or(IDBError::InvalidState)?;
+        transaction.upgrade(|database| {
+            database
+                .object_stores
+                .remove(name)
+                .map(|_| ())
+                .ok_or(IDBError::NotFoundError)
+        })
+    }
+
+    /// Stops new transactions from being made with the connection, leaving the ones already made
+    /// to finish
+    pub fn close(&mut self) {
+        self.closed = true;
     }
 }
@@ -1,11 +1,593 @@
-use super::{IDBDatabase, IndexDbVersion};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-pub struct IDBFactory {}
+use crate::js::api::storage::escape_file_name;
+
+use super::{
+    load_database, IDBBackingStore, IDBDatabase, IDBDatabaseData, IDBError, IDBResult,
+    IDBTransaction, IDBTransactionStatus, IndexDbVersion,
+};
+
+/// Opens the databases of an origin, which are saved to a file each in a directory, or kept in
+/// memory without one
+/// <https://w3c.github.io/IndexedDB/#factory-interface>
+#[derive(Default)]
+pub struct IDBFactory {
+    directory: Option<PathBuf>,
+    /// The databases that have been opened, which all their connections share
+    databases: HashMap<String, Rc<RefCell<IDBBackingStore>>>,
+}
 
-#[allow(dead_code)]
 impl IDBFactory {
-    async fn close(&self) {}
-    async fn open(name: &str, version: Option<IndexDbVersion>) -> Result<IDBDatabase, String> {
-        Ok(IDBDatabase::new(name, version.unwrap_or(1)))
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: Some(directory.to_path_buf()),
+            databases: HashMap::new(),
+        }
+    }
+
+    /// Connects to the database, creating it when it does not exist. When the version is higher
+    /// than the database's, `on_upgrade_needed` is called with the connection, the upgrade
+    /// transaction, and the old version to create the object stores and indexes of the new
+    /// version. The upgrade is undone if it fails or aborts. No version opens the current version,
+    /// or version 1 of a new database.
+    /// <https://w3c.github.io/IndexedDB/#open-a-database-connection>
+    pub fn open(
+        &mut self,
+        name: &str,
+        version: Option<IndexDbVersion>,
+        on_upgrade_needed: impl FnOnce(
+            &mut IDBDatabase,
+            &IDBTransaction,
+            IndexDbVersion,
+        ) -> IDBResult<()>,
+    ) -> IDBResult<IDBDatabase> {
+        if version == Some(0) {
+            return Err(IDBError::TypeError);
+        }
+        let backing_store = self.get_backing_store(name)?;
+        let old_version = backing_store.borrow().data.version;
+        let version = version.unwrap_or(old_version.max(1));
+        if version < old_version {
+            return Err(IDBError::VersionError);
+        }
+        let mut database = IDBDatabase::new(backing_store.clone());
+        if version > old_version {
+            let transaction = IDBTransaction::new_upgrade(backing_store, version)?;
+            database.upgrade_transaction = Some(transaction.clone());
+            let upgraded = on_upgrade_needed(&mut database, &transaction, old_version);
+            database.upgrade_transaction = None;
+            if let Err(error) = upgraded {
+                if transaction.status() == IDBTransactionStatus::Active {
+                    transaction.abort()?;
+                }
+                return Err(error);
+            }
+            if transaction.status() == IDBTransactionStatus::Aborted {
+                return Err(IDBError::AbortError);
+            }
+            transaction.commit()?;
+        }
+        Ok(database)
+    }
+
+    /// Deletes the database along with its file, closing its connections. Results in the version
+    /// it had, which is 0 when it did not exist.
+    /// <https://w3c.github.io/IndexedDB/#delete-a-database>
+    pub fn delete_database(&mut self, name: &str) -> IDBResult<IndexDbVersion> {
+        let backing_store = self.get_backing_store(name)?;
+        if backing_store.borrow().has_active_transactions() {
+            return Err(IDBError::InvalidState);
+        }
+        let version = backing_store.borrow().data.version;
+        backing_store.borrow_mut().deleted = true;
+        self.databases.remove(name);
+        if let Some(path) = self.get_path(name) {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(version)
+    }
+
+    fn get_path(&self, name: &str) -> Option<PathBuf> {
+        let file_name = escape_file_name(name) + ".indexeddb";
+        self.directory
+            .as_ref()
+            .map(|directory| directory.join(file_name))
+    }
+
+    /// The database, which is loaded from its file on first use
+    fn get_backing_store(&mut self, name: &str) -> IDBResult<Rc<RefCell<IDBBackingStore>>> {
+        if let Some(backing_store) = self.databases.get(name) {
+            return Ok(backing_store.clone());
+        }
+        let path = self.get_path(name);
+        let data = match &path {
+            Some(path) => {
+                if let Some(directory) = path.parent() {
+                    fs::create_dir_all(directory)?;
+                }
+                load_database(path)?
+            }
+            None => None,
+        };
+        let data = data.unwrap_or_else(|| IDBDatabaseData::new(name));
+        let backing_store = Rc::new(RefCell::new(IDBBackingStore::new(data, path)));
+        self.databases
+            .insert(name.to_string(), backing_store.clone());
+        Ok(backing_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::{
+        function_name,
+        js::api::index_db::{
+            IDBCursor, IDBCursorDirection, IDBKey, IDBKeyRange, IDBObjectStore, IDBTransactionMode,
+            IDBValue,
+        },
+    };
+
+    use super::*;
+
+    fn s(value: &str) -> IDBValue {
+        IDBValue::String(value.to_string())
+    }
+
+    fn key(value: &str) -> IDBKey {
+        IDBKey::String(value.to_string())
+    }
+
+    fn n(value: f64) -> IDBKey {
+        IDBKey::Number(value)
+    }
+
+    fn person(name: &str, city: &str, tags: &[&str]) -> IDBValue {
+        IDBValue::object([
+            ("name", s(name)),
+            ("city", s(city)),
+            (
+                "tags",
+                IDBValue::Array(tags.iter().map(|tag| s(tag)).collect()),
+            ),
+        ])
+    }
+
+    fn get_name(value: Option<&IDBValue>) -> String {
+        match value.and_then(|value| value.get_property("name")) {
+            Some(IDBValue::String(name)) => name,
+            _ => panic!("No name in {value:?}"),
+        }
+    }
+
+    /// Opens version 1 of a database of people with generated keys, indexed by unique names,
+    /// cities, and each of their tags
+    fn open_people(factory: &mut IDBFactory) -> IDBResult<IDBDatabase> {
+        factory.open("people", Some(1), |database, _, old_version| {
+            assert_eq!(old_version, 0);
+            let people = database.create_object_store("people", Some("id".into()), Some(true))?;
+            people.create_index("name", "name", Some(true), None)?;
+            people.create_index("city", "city", None, None)?;
+            people.create_index("tags", "tags", None, Some(true))?;
+            Ok(())
+        })
+    }
+
+    fn add_people(people: &IDBObjectStore) -> IDBResult<()> {
+        people.add(person("Ada", "London", &["math", "code"]), None)?;
+        people.add(person("Grace", "New York", &["code", "navy"]), None)?;
+        people.add(person("Alan", "London", &["math", "math"]), None)?;
+        people.add(person("Edsger", "Austin", &[]), None)?;
+        Ok(())
+    }
+
+    fn open_readwrite(database: &IDBDatabase) -> IDBResult<IDBObjectStore> {
+        database
+            .transaction(&["people"], IDBTransactionMode::ReadWrite)?
+            .object_store("people")
+    }
+
+    fn get_cursor_names(mut cursor: Option<IDBCursor>) -> IDBResult<Vec<String>> {
+        let mut names = vec![];
+        while let Some(current) = cursor {
+            names.push(get_name(current.value()));
+            cursor = current.continue_to(None)?;
+        }
+        Ok(names)
+    }
+
+    #[test]
+    fn test_upgrade() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        assert_eq!(database.name(), "people");
+        assert_eq!(database.version(), 1);
+        assert_eq!(database.object_store_names(), vec!["people"]);
+        let people = open_readwrite(&database)?;
+        assert_eq!(people.index_names()?, vec!["city", "name", "tags"]);
+        assert!(people.auto_increment()?);
+        drop(people);
+
+        // Opening without a version opens the current one, and lower versions are refused
+        let database = factory.open("people", None, |_, _, _| panic!("Not upgraded"))?;
+        assert_eq!(database.version(), 1);
+        assert_eq!(
+            factory.open("people", Some(0), |_, _, _| Ok(())).err(),
+            Some(IDBError::TypeError)
+        );
+
+        // Failed upgrades are undone
+        let result = factory.open("people", Some(2), |database, _, old_version| {
+            assert_eq!(old_version, 1);
+            database.create_object_store("pets", None, Some(true))?;
+            database.create_object_store("pets", None, None)?;
+            Ok(())
+        });
+        assert_eq!(result.err(), Some(IDBError::ConstraintError));
+        let result = factory.open("people", Some(2), |database, transaction, _| {
+            database.delete_object_store("people")?;
+            transaction.abort()
+        });
+        assert_eq!(result.err(), Some(IDBError::AbortError));
+        let database = factory.open("people", None, |_, _, _| Ok(()))?;
+        assert_eq!(database.version(), 1);
+        assert_eq!(database.object_store_names(), vec!["people"]);
+
+        // Object stores are only created while upgrading
+        let mut database = factory.open("people", Some(2), |database, transaction, _| {
+            database.create_object_store("pets", None, Some(true))?;
+            let people = transaction.object_store("people")?;
+            people.delete_index("tags")?;
+            Ok(())
+        })?;
+        assert_eq!(database.object_store_names(), vec!["people", "pets"]);
+        assert_eq!(
+            database.create_object_store("plants", None, None).err(),
+            Some(IDBError::InvalidState)
+        );
+        assert_eq!(
+            open_readwrite(&database)?.index_names()?,
+            vec!["city", "name"]
+        );
+        assert_eq!(
+            factory.open("people", Some(1), |_, _, _| Ok(())).err(),
+            Some(IDBError::VersionError)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_index_upgrade() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        add_people(&open_readwrite(&database)?)?;
+
+        let result = factory.open("people", Some(2), |_, transaction, _| {
+            let people = transaction.object_store("people")?;
+            people.create_index("city", "city", Some(true), None)?;
+            Ok(())
+        });
+        assert_eq!(result.err(), Some(IDBError::ConstraintError));
+        assert_eq!(database.version(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        let people = open_readwrite(&database)?;
+        add_people(&people)?;
+
+        assert_eq!(people.count(IDBKeyRange::unbound())?, 4);
+        assert_eq!(get_name(people.get(n(2.0))?.as_ref()), "Grace");
+        assert_eq!(people.get(n(5.0))?, None);
+        assert_eq!(
+            people.get_all_keys(IDBKeyRange::lower_bound(n(2.0), true), None)?,
+            vec![n(3.0), n(4.0)]
+        );
+        assert_eq!(people.get_all(IDBKeyRange::unbound(), Some(1))?.len(), 1);
+        assert_eq!(
+            people.get_key(IDBKeyRange::upper_bound(n(2.0), true))?,
+            Some(n(1.0))
+        );
+
+        // Putting replaces records, with the key read from the value
+        let mut ada = people.get(n(1.0))?.unwrap();
+        ada.set_property("city", s("Cambridge"));
+        assert_eq!(people.put(ada, None)?, n(1.0));
+        let city = people.index("city")?;
+        assert_eq!(city.count(key("Cambridge"))?, 1);
+        assert_eq!(city.count(key("London"))?, 1);
+
+        people.delete(IDBKeyRange::bound(Some(n(2.0)), Some(n(3.0)), None, None).unwrap())?;
+        assert_eq!(
+            people.get_all_keys(IDBKeyRange::unbound(), None)?,
+            vec![n(1.0), n(4.0)]
+        );
+        assert_eq!(city.count(IDBKeyRange::unbound())?, 2);
+        people.clear()?;
+        assert_eq!(people.count(IDBKeyRange::unbound())?, 0);
+        assert_eq!(city.count(IDBKeyRange::unbound())?, 0);
+
+        // Generated keys keep counting up after records are removed
+        assert_eq!(people.add(person("Barbara", "Boston", &[]), None)?, n(5.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        let people = open_readwrite(&database)?;
+        add_people(&people)?;
+
+        let name = people.index("name")?;
+        assert!(name.unique()?);
+        assert_eq!(name.get_key(key("Alan"))?, Some(n(3.0)));
+        assert_eq!(
+            get_name(
+                name.get(IDBKeyRange::lower_bound(key("B"), false))?
+                    .as_ref()
+            ),
+            "Edsger"
+        );
+
+        let city = people.index("city")?;
+        assert_eq!(
+            city.get_all_keys(key("London"), None)?,
+            vec![n(1.0), n(3.0)]
+        );
+        assert_eq!(get_name(city.get(key("London"))?.as_ref()), "Ada");
+
+        let tags = people.index("tags")?;
+        assert!(tags.multi_entry()?);
+        assert_eq!(tags.get_all_keys(key("math"), None)?, vec![n(1.0), n(3.0)]);
+        assert_eq!(tags.get_all_keys(key("code"), None)?, vec![n(1.0), n(2.0)]);
+        assert_eq!(tags.count(IDBKeyRange::unbound())?, 5);
+
+        assert_eq!(people.index("missing").err(), Some(IDBError::NotFoundError));
+        assert_eq!(
+            people.add(person("Ada", "Paris", &[]), None),
+            Err(IDBError::ConstraintError)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursors() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        let people = open_readwrite(&database)?;
+        add_people(&people)?;
+        let city = people.index("city")?;
+        let all = IDBKeyRange::unbound;
+
+        assert_eq!(
+            get_cursor_names(people.open_cursor(all(), IDBCursorDirection::Next)?)?,
+            vec!["Ada", "Grace", "Alan", "Edsger"]
+        );
+        assert_eq!(
+            get_cursor_names(people.open_cursor(
+                IDBKeyRange::bound(Some(n(2.0)), Some(n(4.0)), None, Some(true)).unwrap(),
+                IDBCursorDirection::Prev
+            )?)?,
+            vec!["Alan", "Grace"]
+        );
+        assert_eq!(
+            get_cursor_names(city.open_cursor(all(), IDBCursorDirection::Next)?)?,
+            vec!["Edsger", "Ada", "Alan", "Grace"]
+        );
+        assert_eq!(
+            get_cursor_names(city.open_cursor(all(), IDBCursorDirection::Prev)?)?,
+            vec!["Grace", "Alan", "Ada", "Edsger"]
+        );
+        assert_eq!(
+            get_cursor_names(city.open_cursor(all(), IDBCursorDirection::NextUnique)?)?,
+            vec!["Edsger", "Ada", "Grace"]
+        );
+        assert_eq!(
+            get_cursor_names(city.open_cursor(all(), IDBCursorDirection::PrevUnique)?)?,
+            vec!["Grace", "Ada", "Edsger"]
+        );
+        assert!(city
+            .open_cursor(key("Berlin"), IDBCursorDirection::Next)?
+            .is_none());
+
+        // Cursors can skip ahead by count, key, or primary key
+        let cursor = city.open_cursor(all(), IDBCursorDirection::Next)?.unwrap();
+        let cursor = cursor.advance(2)?.unwrap();
+        assert_eq!(
+            (cursor.key(), cursor.primary_key()),
+            (&key("London"), &n(3.0))
+        );
+        assert_eq!(
+            cursor.continue_to(Some(key("Austin"))).err(),
+            Some(IDBError::DataError)
+        );
+        let cursor = city.open_cursor(all(), IDBCursorDirection::Next)?.unwrap();
+        let cursor = cursor.continue_primary_key(key("London"), n(2.0))?.unwrap();
+        assert_eq!(cursor.primary_key(), &n(3.0));
+        let cursor = cursor.continue_to(Some(key("M")))?.unwrap();
+        assert_eq!(get_name(cursor.value()), "Grace");
+        assert!(cursor.continue_to(None)?.is_none());
+
+        let cursor = city
+            .open_key_cursor(all(), IDBCursorDirection::Prev)?
+            .unwrap();
+        assert_eq!(cursor.value(), None);
+        let cursor = cursor.continue_to(Some(key("London")))?.unwrap();
+        assert_eq!(
+            (cursor.key(), cursor.primary_key()),
+            (&key("London"), &n(3.0))
+        );
+        assert_eq!(cursor.delete(), Err(IDBError::InvalidState));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor_changes() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        let people = open_readwrite(&database)?;
+        add_people(&people)?;
+
+        let mut cursor = people.open_cursor(IDBKeyRange::unbound(), IDBCursorDirection::Next)?;
+        while let Some(current) = cursor {
+            let mut value = current.value().unwrap().clone();
+            if get_name(Some(&value)).starts_with('A') {
+                current.delete()?;
+            } else {
+                value.set_property("city", s("Paris"));
+                current.update(value)?;
+            }
+            cursor = current.continue_to(None)?;
+        }
+        let city = people.index("city")?;
+        assert_eq!(city.get_all_keys(key("Paris"), None)?, vec![n(2.0), n(4.0)]);
+        assert_eq!(people.count(IDBKeyRange::unbound())?, 2);
+
+        let cursor = people
+            .open_cursor(IDBKeyRange::unbound(), IDBCursorDirection::Next)?
+            .unwrap();
+        assert_eq!(
+            cursor.update(person("Grace", "Paris", &[])),
+            Err(IDBError::DataError)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_transactions() -> IDBResult<()> {
+        let mut factory = IDBFactory::default();
+        let database = open_people(&mut factory)?;
+        {
+            let transaction = database.transaction(&["people"], IDBTransactionMode::ReadWrite)?;
+            add_people(&transaction.object_store("people")?)?;
+            // Writes are kept from other transactions until they are committed
+            assert_eq!(
+                database
+                    .transaction(&["people"], IDBTransactionMode::ReadOnly)
+                    .err(),
+                Some(IDBError::InvalidState)
+            );
+            transaction.commit()?;
+            assert_eq!(transaction.status(), IDBTransactionStatus::Committed);
+            assert_eq!(
+                transaction.object_store("people").err(),
+                Some(IDBError::InvalidState)
+            );
+        }
+
+        let people = database
+            .transaction(&["people"], IDBTransactionMode::ReadOnly)?
+            .object_store("people")?;
+        let other_people = database
+            .transaction(&["people"], IDBTransactionMode::ReadOnly)?
+            .object_store("people")?;
+        assert_eq!(
+            people.add(person("Barbara", "Boston", &[]), None),
+            Err(IDBError::ReadOnlyError)
+        );
+        assert_eq!(other_people.count(IDBKeyRange::unbound())?, 4);
+        drop((people, other_people));
+
+        // Aborting undoes writes, deletes, clears, and generated keys
+        let people = open_readwrite(&database)?;
+        people.add(person("Barbara", "Boston", &[]), None)?;
+        assert_eq!(
+            people.put(person("Ada", "Paris", &[]), Some(n(1.0))),
+            Err(IDBError::DataError)
+        );
+        let mut ada = people.get(n(1.0))?.unwrap();
+        ada.set_property("city", s("Paris"));
+        people.put(ada, None)?;
+        people.delete(n(2.0))?;
+        people.clear()?;
+        people.add(person("Linus", "Portland", &[]), None)?;
+        people.transaction().abort()?;
+        assert_eq!(
+            people.count(IDBKeyRange::unbound()).err(),
+            Some(IDBError::TransactionInactiveError)
+        );
+        drop(people);
+
+        let people = open_readwrite(&database)?;
+        assert_eq!(
+            get_cursor_names(
+                people.open_cursor(IDBKeyRange::unbound(), IDBCursorDirection::Next)?
+            )?,
+            vec!["Ada", "Grace", "Alan", "Edsger"]
+        );
+        assert_eq!(
+            people.index("city")?.get_all_keys(key("London"), None)?,
+            vec![n(1.0), n(3.0)]
+        );
+        assert_eq!(people.add(person("Barbara", "Boston", &[]), None)?, n(5.0));
+
+        // Failed requests abort the transaction
+        assert_eq!(
+            people.add(person("Ada", "Paris", &[]), None),
+            Err(IDBError::ConstraintError)
+        );
+        assert_eq!(people.transaction().status(), IDBTransactionStatus::Aborted);
+        drop(people);
+        assert_eq!(open_readwrite(&database)?.count(IDBKeyRange::unbound())?, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_persistence() -> IDBResult<()> {
+        let directory = env::temp_dir().join(function_name!());
+        if directory.exists() {
+            fs::remove_dir_all(&directory)?;
+        }
+        {
+            let mut factory = IDBFactory::new(&directory);
+            let database = open_people(&mut factory)?;
+            add_people(&open_readwrite(&database)?)?;
+            let people = open_readwrite(&database)?;
+            people.add(person("Barbara", "Boston", &[]), None)?;
+            people.transaction().abort()?;
+        }
+        {
+            let mut factory = IDBFactory::new(&directory);
+            let database = factory.open("people", None, |_, _, _| panic!("Not upgraded"))?;
+            assert_eq!(database.version(), 1);
+            let people = open_readwrite(&database)?;
+            assert_eq!(people.count(IDBKeyRange::unbound())?, 4);
+            assert_eq!(
+                people.index("tags")?.get_all_keys(key("math"), None)?,
+                vec![n(1.0), n(3.0)]
+            );
+            assert_eq!(people.add(person("Barbara", "Boston", &[]), None)?, n(5.0));
+            drop(people);
+
+            let mut database = database;
+            database.close();
+            assert_eq!(
+                database
+                    .transaction(&["people"], IDBTransactionMode::ReadOnly)
+                    .err(),
+                Some(IDBError::InvalidState)
+            );
+            assert_eq!(factory.delete_database("people")?, 1);
+        }
+        let mut factory = IDBFactory::new(&directory);
+        let database = factory.open("people", None, |_, _, old_version| {
+            assert_eq!(old_version, 0);
+            Ok(())
+        })?;
+        assert!(database.object_store_names().is_empty());
+        Ok(())
     }
 }
@@ -1,42 +1,223 @@
-use super::{IDBObjectStoreItem, IDBResult, IndexDbCount};
+use std::collections::{BTreeMap, BTreeSet};
 
-#[allow(dead_code)]
+use super::{
+    IDBCursor, IDBCursorDirection, IDBKey, IDBKeyPath, IDBKeyRange, IDBObjectStore,
+    IDBObjectStoreData, IDBResult, IDBValue, IndexDbCount,
+};
+
+/// The records of an index, which map the keys found at its key path in the values of its object
+/// store to the primary keys of those values, both in key order
+/// <https://w3c.github.io/IndexedDB/#index-construct>
+#[derive(Clone, Debug, PartialEq)]
+pub struct IDBIndexData {
+    pub name: String,
+    pub key_path: IDBKeyPath,
+    /// No two records may have the same key
+    pub unique: bool,
+    /// Values with an array at the key path are indexed by each member instead of the array
+    pub multi_entry: bool,
+    pub records: BTreeMap<IDBKey, BTreeSet<IDBKey>>,
+}
+
+impl IDBIndexData {
+    pub fn new(name: &str, key_path: IDBKeyPath, unique: bool, multi_entry: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            key_path,
+            unique,
+            multi_entry,
+            records: BTreeMap::new(),
+        }
+    }
+
+    /// The keys the value is indexed by, which are none when it has no key at the key path
+    pub fn get_keys(&self, value: &IDBValue) -> Vec<IDBKey> {
+        match self.key_path.evaluate(value) {
+            Some(index_value) if self.multi_entry => IDBKey::from_multi_entry_value(&index_value),
+            Some(index_value) => IDBKey::from_value(&index_value).into_iter().collect(),
+            None => vec![],
+        }
+    }
+
+    /// Whether indexing the value would give it a key another record has, which unique indexes
+    /// do not allow
+    pub fn has_conflict(&self, value: &IDBValue, primary_key: &IDBKey) -> bool {
+        self.unique
+            && self.get_keys(value).iter().any(|key| {
+                self.records.get(key).is_some_and(|primary_keys| {
+                    primary_keys
+                        .iter()
+                        .any(|other_primary_key| other_primary_key != primary_key)
+                })
+            })
+    }
+
+    pub fn insert(&mut self, value: &IDBValue, primary_key: &IDBKey) {
+        for key in self.get_keys(value) {
+            self.records
+                .entry(key)
+                .or_default()
+                .insert(primary_key.clone());
+        }
+    }
+
+    pub fn remove(&mut self, value: &IDBValue, primary_key: &IDBKey) {
+        for key in self.get_keys(value) {
+            if let Some(primary_keys) = self.records.get_mut(&key) {
+                primary_keys.remove(primary_key);
+                if primary_keys.is_empty() {
+                    self.records.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// The primary keys of the records in the range, in key order
+    fn get_primary_keys<'a>(
+        &'a self,
+        range: &IDBKeyRange,
+    ) -> impl Iterator<Item = &'a IDBKey> + 'a {
+        self.records
+            .range::<IDBKey, _>((range.lower(), range.upper()))
+            .flat_map(|(_, primary_keys)| primary_keys.iter())
+    }
+}
+
+/// An index of an object store, as used within a transaction
+/// <https://w3c.github.io/IndexedDB/#index-interface>
+#[derive(Clone)]
 pub struct IDBIndex {
+    object_store: IDBObjectStore,
     name: String,
-    object_store: String,
-    key_path: Option<String>,
-    multi_entry: bool,
-    unique: bool,
-    entries: Vec<IDBObjectStoreItem>,
 }
 
 impl IDBIndex {
-    pub fn new(
-        name: &str,
-        object_store: &str,
-        key_path: Option<&str>,
-        multi_entry: bool,
-        unique: bool,
-    ) -> Self {
+    pub(super) fn new(object_store: IDBObjectStore, name: &str) -> Self {
         Self {
+            object_store,
             name: name.to_string(),
-            object_store: object_store.to_string(),
-            key_path: key_path.map(str::to_string),
-            multi_entry,
-            unique,
-            entries: vec![],
         }
     }
 
-    pub async fn count(&self, key: Option<&str>) -> IndexDbCount {
-        if key.is_none() {
-            self.entries.len().try_into().unwrap()
-        } else {
-            0
-        }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn object_store(&self) -> &IDBObjectStore {
+        &self.object_store
+    }
+
+    pub fn key_path(&self) -> IDBResult<IDBKeyPath> {
+        self.read(|index, _| Ok(index.key_path.clone()))
+    }
+
+    pub fn unique(&self) -> IDBResult<bool> {
+        self.read(|index, _| Ok(index.unique))
+    }
+
+    pub fn multi_entry(&self) -> IDBResult<bool> {
+        self.read(|index, _| Ok(index.multi_entry))
+    }
+
+    /// The value of the first record in the range
+    pub fn get(&self, query: impl Into<IDBKeyRange>) -> IDBResult<Option<IDBValue>> {
+        let range = query.into();
+        self.read(|index, object_store| {
+            Ok(index
+                .get_primary_keys(&range)
+                .next()
+                .and_then(|primary_key| object_store.records.get(primary_key))
+                .cloned())
+        })
+    }
+
+    /// The primary key of the first record in the range
+    pub fn get_key(&self, query: impl Into<IDBKeyRange>) -> IDBResult<Option<IDBKey>> {
+        let range = query.into();
+        self.read(|index, _| Ok(index.get_primary_keys(&range).next().cloned()))
     }
 
-    pub async fn get(&self, _key: Option<&str>) -> IDBResult<Option<IDBObjectStoreItem>> {
-        Ok(None)
+    /// The values of the records in the range, up to the count when given
+    pub fn get_all(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        count: Option<u32>,
+    ) -> IDBResult<Vec<IDBValue>> {
+        let range = query.into();
+        self.read(|index, object_store| {
+            Ok(index
+                .get_primary_keys(&range)
+                .take(get_limit(count))
+                .filter_map(|primary_key| object_store.records.get(primary_key))
+                .cloned()
+                .collect())
+        })
+    }
+
+    /// The primary keys of the records in the range, up to the count when given
+    pub fn get_all_keys(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        count: Option<u32>,
+    ) -> IDBResult<Vec<IDBKey>> {
+        let range = query.into();
+        self.read(|index, _| {
+            Ok(index
+                .get_primary_keys(&range)
+                .take(get_limit(count))
+                .cloned()
+                .collect())
+        })
+    }
+
+    pub fn count(&self, query: impl Into<IDBKeyRange>) -> IDBResult<IndexDbCount> {
+        let range = query.into();
+        self.read(|index, _| Ok(index.get_primary_keys(&range).count() as IndexDbCount))
+    }
+
+    /// A cursor at the first record in the range in the direction, or `None` when there is none
+    pub fn open_cursor(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        direction: IDBCursorDirection,
+    ) -> IDBResult<Option<IDBCursor>> {
+        IDBCursor::open(
+            self.object_store.clone(),
+            Some(self.name.clone()),
+            query.into(),
+            direction,
+            false,
+        )
+    }
+
+    /// A cursor like [`Self::open_cursor`] that does not read the values of records
+    pub fn open_key_cursor(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        direction: IDBCursorDirection,
+    ) -> IDBResult<Option<IDBCursor>> {
+        IDBCursor::open(
+            self.object_store.clone(),
+            Some(self.name.clone()),
+            query.into(),
+            direction,
+            true,
+        )
+    }
+
+    fn read<R>(
+        &self,
+        f: impl FnOnce(&IDBIndexData, &IDBObjectStoreData) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        self.object_store
+            .read(|object_store| f(object_store.get_index(&self.name)?, object_store))
+    }
+}
+
+/// How many records to read for an optional count, where 0 and no count mean all of them
+pub(super) fn get_limit(count: Option<u32>) -> usize {
+    match count {
+        Some(count) if count > 0 => count as usize,
+        _ => usize::MAX,
     }
 }
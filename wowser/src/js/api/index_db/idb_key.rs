@@ -2,6 +2,9 @@ use std::cmp::Ordering;
 
 use crate::js::{api::date::Date, JsNumberPrimitive};
 
+use super::IDBValue;
+
+/// <https://w3c.github.io/IndexedDB/#key-construct>
 #[derive(Debug, PartialEq, Clone)]
 pub enum IDBKey {
     Number(JsNumberPrimitive),
@@ -11,41 +14,103 @@ pub enum IDBKey {
     Array(Vec<IDBKey>),
 }
 
-impl PartialOrd for IDBKey {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+impl IDBKey {
+    /// The lowest key, which records can be looked up from
+    pub fn min() -> Self {
+        Self::Number(JsNumberPrimitive::NEG_INFINITY)
+    }
+
+    /// Numbers, valid dates, strings, binary data, and arrays of those are keys, but other values
+    /// and arrays containing them are not
+    /// <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-key>
+    pub fn from_value(value: &IDBValue) -> Option<Self> {
+        match value {
+            IDBValue::Number(number) if !number.is_nan() => Some(Self::Number(*number)),
+            IDBValue::Date(date) if !date.is_nan() => Some(Self::Date(*date)),
+            IDBValue::String(string) => Some(Self::String(string.clone())),
+            IDBValue::Binary(bytes) => Some(Self::Binary(bytes.clone())),
+            IDBValue::Array(values) => values
+                .iter()
+                .map(Self::from_value)
+                .collect::<Option<Vec<_>>>()
+                .map(Self::Array),
+            _ => None,
+        }
+    }
+
+    /// The keys of a multi-entry index for the value, which are each distinct key in an array
+    /// value, skipping the members that are not keys
+    /// <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-multientry-key>
+    pub fn from_multi_entry_value(value: &IDBValue) -> Vec<Self> {
+        let IDBValue::Array(values) = value else {
+            return Self::from_value(value).into_iter().collect();
+        };
+        let mut keys: Vec<Self> = vec![];
+        for key in values.iter().filter_map(Self::from_value) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    pub fn to_value(&self) -> IDBValue {
+        match self {
+            Self::Number(number) => IDBValue::Number(*number),
+            Self::Date(date) => IDBValue::Date(*date),
+            Self::String(string) => IDBValue::String(string.clone()),
+            Self::Binary(bytes) => IDBValue::Binary(bytes.clone()),
+            Self::Array(keys) => IDBValue::Array(keys.iter().map(Self::to_value).collect()),
+        }
+    }
+}
+
+/// Keys are never `NaN`, so they are totally ordered
+impl Eq for IDBKey {}
+
+impl Ord for IDBKey {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Self::Number(s), Self::Number(o)) | (Self::Date(s), Self::Date(o)) => s.partial_cmp(o),
-            (Self::String(s), Self::String(o)) => s.partial_cmp(o),
+            (Self::Number(s), Self::Number(o)) | (Self::Date(s), Self::Date(o)) => {
+                s.partial_cmp(o).unwrap_or(Ordering::Equal)
+            }
+            (Self::String(s), Self::String(o)) => s.cmp(o),
             (Self::Array(s), Self::Array(o)) => {
                 let len = s.len().min(o.len());
                 for i in 0..len {
-                    let cmp = s[i].partial_cmp(&o[i]);
-                    if !matches!(cmp, Some(Ordering::Equal)) {
+                    let cmp = s[i].cmp(&o[i]);
+                    if cmp != Ordering::Equal {
                         return cmp;
                     }
                 }
-                s.len().partial_cmp(&o.len())
+                s.len().cmp(&o.len())
             }
-            (Self::Binary(s), Self::Binary(o)) => s.partial_cmp(o),
-            (Self::Array(_), _) => Some(Ordering::Greater),
-            (_, Self::Array(_)) => Some(Ordering::Less),
-            (Self::Binary(_), _) => Some(Ordering::Greater),
-            (_, Self::Binary(_)) => Some(Ordering::Less),
-            (Self::String(_), _) => Some(Ordering::Greater),
-            (_, Self::String(_)) => Some(Ordering::Less),
-            (Self::Date(_), _) => Some(Ordering::Greater),
-            (_, Self::Date(_)) => Some(Ordering::Less),
+            (Self::Binary(s), Self::Binary(o)) => s.cmp(o),
+            (Self::Array(_), _) => Ordering::Greater,
+            (_, Self::Array(_)) => Ordering::Less,
+            (Self::Binary(_), _) => Ordering::Greater,
+            (_, Self::Binary(_)) => Ordering::Less,
+            (Self::String(_), _) => Ordering::Greater,
+            (_, Self::String(_)) => Ordering::Less,
+            (Self::Date(_), _) => Ordering::Greater,
+            (_, Self::Date(_)) => Ordering::Less,
         }
     }
 }
 
+impl PartialOrd for IDBKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
 
     use crate::js::{api::date::Date, JsNumberPrimitive};
 
-    use super::IDBKey;
+    use super::{IDBKey, IDBValue};
 
     #[track_caller]
     fn assert_greater(a: IDBKey, b: IDBKey) {
@@ -153,4 +218,46 @@ mod tests {
         assert_greater(d(-9), n(999));
         assert_less(n(999), d(-9));
     }
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(IDBKey::from_value(&IDBValue::Number(1.0)), Some(n(1)));
+        assert_eq!(IDBKey::from_value(&IDBValue::Number(f64::NAN)), None);
+        assert_eq!(IDBKey::from_value(&IDBValue::Date(f64::NAN)), None);
+        assert_eq!(IDBKey::from_value(&IDBValue::Null), None);
+        assert_eq!(
+            IDBKey::from_value(&IDBValue::Array(vec![
+                IDBValue::String("a".to_string()),
+                IDBValue::Binary(vec![1]),
+            ])),
+            Some(a([s("a"), b([1])]))
+        );
+        assert_eq!(
+            IDBKey::from_value(&IDBValue::Array(vec![IDBValue::Bool(true)])),
+            None
+        );
+        assert_eq!(
+            IDBKey::from_value(&a([d(1), a([])]).to_value()),
+            Some(a([d(1), a([])]))
+        );
+    }
+
+    #[test]
+    fn test_from_multi_entry_value() {
+        let value = IDBValue::Array(vec![
+            IDBValue::Number(1.0),
+            IDBValue::Null,
+            IDBValue::Number(1.0),
+            IDBValue::Array(vec![IDBValue::Number(2.0)]),
+        ]);
+        assert_eq!(
+            IDBKey::from_multi_entry_value(&value),
+            vec![n(1), a([n(2)])]
+        );
+        assert_eq!(
+            IDBKey::from_multi_entry_value(&IDBValue::String("a".to_string())),
+            vec![s("a")]
+        );
+        assert!(IDBKey::from_multi_entry_value(&IDBValue::Null).is_empty());
+    }
 }
@@ -0,0 +1,194 @@
+use super::{IDBKey, IDBValue};
+
+/// Where the key of a value is found: a path of property names separated by `.`, the value itself
+/// for an empty path, or an array of paths for array keys
+/// <https://w3c.github.io/IndexedDB/#key-path-construct>
+#[derive(Clone, Debug, PartialEq)]
+pub enum IDBKeyPath {
+    String(String),
+    Array(Vec<String>),
+}
+
+impl From<&str> for IDBKeyPath {
+    fn from(path: &str) -> Self {
+        Self::String(path.to_string())
+    }
+}
+
+impl IDBKeyPath {
+    /// Paths are empty or identifiers separated by `.`, and arrays need at least one path
+    /// <https://w3c.github.io/IndexedDB/#valid-key-path>
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::String(path) => is_valid_path(path),
+            Self::Array(paths) => !paths.is_empty() && paths.iter().all(|path| is_valid_path(path)),
+        }
+    }
+
+    /// The value at the path, or an array of the values at each path for arrays. `None` when a
+    /// property along the path is missing.
+    /// <https://w3c.github.io/IndexedDB/#evaluate-a-key-path-on-a-value>
+    pub fn evaluate(&self, value: &IDBValue) -> Option<IDBValue> {
+        match self {
+            Self::String(path) => evaluate_path(path, value),
+            Self::Array(paths) => paths
+                .iter()
+                .map(|path| evaluate_path(path, value))
+                .collect::<Option<Vec<_>>>()
+                .map(IDBValue::Array),
+        }
+    }
+
+    /// The key at the path, or `None` when the value there is missing or not a key
+    /// <https://w3c.github.io/IndexedDB/#extract-a-key-from-a-value-using-a-key-path>
+    pub fn extract_key(&self, value: &IDBValue) -> Option<IDBKey> {
+        IDBKey::from_value(&self.evaluate(value)?)
+    }
+
+    /// Whether a generated key can be stored at the path of the value, which needs each property
+    /// along the path to be an object or missing
+    /// <https://w3c.github.io/IndexedDB/#check-that-a-key-could-be-injected-into-a-value>
+    pub fn can_inject(&self, value: &IDBValue) -> bool {
+        let Self::String(path) = self else {
+            return false;
+        };
+        let identifiers: Vec<&str> = path.split('.').collect();
+        let mut value = value.clone();
+        for identifier in &identifiers[..identifiers.len() - 1] {
+            if !matches!(value, IDBValue::Object(_)) {
+                return false;
+            }
+            match value.get_property(identifier) {
+                Some(property) => value = property,
+                None => return true,
+            }
+        }
+        matches!(value, IDBValue::Object(_))
+    }
+
+    /// Stores a generated key at the path, creating the missing objects along it. The path must
+    /// be one [`Self::can_inject`] allows.
+    /// <https://w3c.github.io/IndexedDB/#inject-a-key-into-a-value-using-a-key-path>
+    pub fn inject(&self, value: &mut IDBValue, key: &IDBKey) {
+        let Self::String(path) = self else {
+            return;
+        };
+        inject_path(path, value, key);
+    }
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.is_empty() || path.split('.').all(is_identifier)
+}
+
+/// Approximates ECMAScript's `IdentifierName` with Unicode letters and digits
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_alphabetic() || first == '$' || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '$' || c == '_')
+}
+
+fn evaluate_path(path: &str, value: &IDBValue) -> Option<IDBValue> {
+    if path.is_empty() {
+        return Some(value.clone());
+    }
+    let mut value = value.clone();
+    for identifier in path.split('.') {
+        value = value.get_property(identifier)?;
+    }
+    Some(value)
+}
+
+fn inject_path(path: &str, value: &mut IDBValue, key: &IDBKey) {
+    let Some((identifier, rest)) = path.split_once('.') else {
+        value.set_property(path, key.to_value());
+        return;
+    };
+    if value.get_property(identifier).is_none() {
+        value.set_property(identifier, IDBValue::Object(vec![]));
+    }
+    if let IDBValue::Object(properties) = value {
+        if let Some((_, property)) = properties
+            .iter_mut()
+            .find(|(property_name, _)| property_name == identifier)
+        {
+            inject_path(rest, property, key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> IDBKeyPath {
+        IDBKeyPath::from(path)
+    }
+
+    fn number(number: f64) -> IDBValue {
+        IDBValue::Number(number)
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(path("").is_valid());
+        assert!(path("a").is_valid());
+        assert!(path("$a.b_1.été").is_valid());
+        assert!(!path("a.").is_valid());
+        assert!(!path("1a").is_valid());
+        assert!(!path("a b").is_valid());
+        assert!(IDBKeyPath::Array(vec!["a".to_string(), "b.c".to_string()]).is_valid());
+        assert!(!IDBKeyPath::Array(vec![]).is_valid());
+    }
+
+    #[test]
+    fn test_extract_key() {
+        let value = IDBValue::object([
+            ("id", number(1.0)),
+            ("name", IDBValue::String("abc".to_string())),
+            ("inner", IDBValue::object([("flag", IDBValue::Bool(true))])),
+        ]);
+        assert_eq!(path("id").extract_key(&value), Some(IDBKey::Number(1.0)));
+        assert_eq!(
+            path("name.length").extract_key(&value),
+            Some(IDBKey::Number(3.0))
+        );
+        assert_eq!(path("inner.flag").extract_key(&value), None);
+        assert_eq!(path("missing").extract_key(&value), None);
+        assert_eq!(
+            path("").extract_key(&number(2.0)),
+            Some(IDBKey::Number(2.0))
+        );
+        assert_eq!(
+            IDBKeyPath::Array(vec!["id".to_string(), "name".to_string()]).extract_key(&value),
+            Some(IDBKey::Array(vec![
+                IDBKey::Number(1.0),
+                IDBKey::String("abc".to_string())
+            ]))
+        );
+        assert_eq!(
+            IDBKeyPath::Array(vec!["id".to_string(), "missing".to_string()]).extract_key(&value),
+            None
+        );
+    }
+
+    #[test]
+    fn test_inject() {
+        let key = IDBKey::Number(7.0);
+        let mut value = IDBValue::object([("a", IDBValue::object([]))]);
+        assert!(path("a.b.c").can_inject(&value));
+        path("a.b.c").inject(&mut value, &key);
+        assert_eq!(path("a.b.c").extract_key(&value), Some(key.clone()));
+
+        let mut value = IDBValue::object([]);
+        assert!(path("id").can_inject(&value));
+        path("id").inject(&mut value, &key);
+        assert_eq!(value, IDBValue::object([("id", number(7.0))]));
+
+        assert!(!path("a.b").can_inject(&IDBValue::object([("a", number(1.0))])));
+        assert!(!path("id").can_inject(&number(1.0)));
+    }
+}
@@ -1,8 +1,9 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, ops::Bound};
 
 use super::IDBKey;
 
-#[derive(Debug, PartialEq)]
+/// <https://w3c.github.io/IndexedDB/#range-construct>
+#[derive(Clone, Debug, PartialEq)]
 pub struct IDBKeyRange {
     lower_key: Option<IDBKey>,
     upper_key: Option<IDBKey>,
@@ -33,6 +34,34 @@ impl IDBKeyRange {
         })
     }
 
+    /// The range of just the key
+    pub fn only(key: IDBKey) -> Self {
+        Self {
+            lower_key: Some(key.clone()),
+            upper_key: Some(key),
+            lower_open: false,
+            upper_open: false,
+        }
+    }
+
+    pub fn lower_bound(key: IDBKey, open: bool) -> Self {
+        Self {
+            lower_key: Some(key),
+            upper_key: None,
+            lower_open: open,
+            upper_open: false,
+        }
+    }
+
+    pub fn upper_bound(key: IDBKey, open: bool) -> Self {
+        Self {
+            lower_key: None,
+            upper_key: Some(key),
+            lower_open: false,
+            upper_open: open,
+        }
+    }
+
     pub fn unbound() -> Self {
         Self {
             lower_key: None,
@@ -63,6 +92,31 @@ impl IDBKeyRange {
         }
         true
     }
+
+    /// The lower bound, for looking up keys in ordered collections
+    pub fn lower(&self) -> Bound<&IDBKey> {
+        match &self.lower_key {
+            Some(key) if self.lower_open => Bound::Excluded(key),
+            Some(key) => Bound::Included(key),
+            None => Bound::Unbounded,
+        }
+    }
+
+    /// The upper bound, for looking up keys in ordered collections
+    pub fn upper(&self) -> Bound<&IDBKey> {
+        match &self.upper_key {
+            Some(key) if self.upper_open => Bound::Excluded(key),
+            Some(key) => Bound::Included(key),
+            None => Bound::Unbounded,
+        }
+    }
+}
+
+/// Queries for a key are queries for the range of just the key
+impl From<IDBKey> for IDBKeyRange {
+    fn from(key: IDBKey) -> Self {
+        Self::only(key)
+    }
 }
 
 #[cfg(test)]
@@ -1,44 +1,559 @@
-use super::{IDBIndex, IDBResult};
+use std::collections::BTreeMap;
 
-#[allow(dead_code)]
-pub struct IDBObjectStore {
-    name: String,
-    key_path: Option<String>,
-    auto_increment: bool,
-    indices: Vec<IDBIndex>,
+use crate::js::JsNumberPrimitive;
+
+use super::{
+    get_limit, IDBCursor, IDBCursorDirection, IDBError, IDBIndex, IDBIndexData, IDBKey, IDBKeyPath,
+    IDBKeyRange, IDBResult, IDBTransaction, IDBUndoEntry, IDBValue, IndexDbCount,
+};
+
+/// Key generators run out once they reach this number, past which numbers cannot all be told apart
+const MAX_GENERATED_KEY: JsNumberPrimitive = 9_007_199_254_740_992.0;
+
+/// The records of an object store in key order, along with the indexes kept up to date with them
+/// <https://w3c.github.io/IndexedDB/#object-store-construct>
+#[derive(Clone, Debug, PartialEq)]
+pub struct IDBObjectStoreData {
+    pub name: String,
+    /// Where keys are found in values, for stores with in-line keys
+    pub key_path: Option<IDBKeyPath>,
+    /// The current number of the key generator, for stores with one
+    /// <https://w3c.github.io/IndexedDB/#key-generator-construct>
+    pub key_generator: Option<JsNumberPrimitive>,
+    pub records: BTreeMap<IDBKey, IDBValue>,
+    pub indexes: BTreeMap<String, IDBIndexData>,
 }
 
-pub enum IDBObjectStoreItem {
-    String(String),
+impl IDBObjectStoreData {
+    pub fn new(name: &str, key_path: Option<IDBKeyPath>, auto_increment: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            key_path,
+            key_generator: auto_increment.then_some(1.0),
+            records: BTreeMap::new(),
+            indexes: BTreeMap::new(),
+        }
+    }
+
+    pub fn get_index(&self, name: &str) -> IDBResult<&IDBIndexData> {
+        self.indexes.get(name).ok_or(IDBError::InvalidState)
+    }
+
+    /// Adds the index of the records, which fails with a `ConstraintError` when a unique index
+    /// would have records with the same key
+    pub fn add_index(&mut self, mut index: IDBIndexData) -> IDBResult<()> {
+        for (primary_key, value) in &self.records {
+            if index.has_conflict(value, primary_key) {
+                return Err(IDBError::ConstraintError);
+            }
+            index.insert(value, primary_key);
+        }
+        self.indexes.insert(index.name.clone(), index);
+        Ok(())
+    }
+
+    /// Indexes the records again, such as after loading them
+    pub fn rebuild_indexes(&mut self) {
+        for index in self.indexes.values_mut() {
+            index.records.clear();
+            for (primary_key, value) in &self.records {
+                index.insert(value, primary_key);
+            }
+        }
+    }
+
+    /// Stores the value with its key, which is given for out-of-line keys, read from the value for
+    /// in-line keys, or generated. Results in the key and the value the record had before.
+    /// <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>
+    pub fn store_record(
+        &mut self,
+        mut value: IDBValue,
+        key: Option<IDBKey>,
+        no_overwrite: bool,
+    ) -> IDBResult<(IDBKey, Option<IDBValue>)> {
+        let key = match (self.key_path.clone(), key) {
+            (Some(_), Some(_)) => return Err(IDBError::DataError),
+            (None, Some(key)) => {
+                self.update_key_generator(&key);
+                key
+            }
+            (None, None) => self.generate_key()?,
+            (Some(key_path), None) => match key_path.evaluate(&value) {
+                Some(key_value) => {
+                    let key = IDBKey::from_value(&key_value).ok_or(IDBError::DataError)?;
+                    self.update_key_generator(&key);
+                    key
+                }
+                None if self.key_generator.is_some() && key_path.can_inject(&value) => {
+                    let key = self.generate_key()?;
+                    key_path.inject(&mut value, &key);
+                    key
+                }
+                None => return Err(IDBError::DataError),
+            },
+        };
+        if no_overwrite && self.records.contains_key(&key) {
+            return Err(IDBError::ConstraintError);
+        }
+        if self
+            .indexes
+            .values()
+            .any(|index| index.has_conflict(&value, &key))
+        {
+            return Err(IDBError::ConstraintError);
+        }
+        let old_value = self.set_record(&key, Some(value));
+        Ok((key, old_value))
+    }
+
+    /// Replaces or removes the record without any checks, keeping the indexes up to date.
+    /// Results in the value the record had before.
+    pub fn set_record(&mut self, key: &IDBKey, value: Option<IDBValue>) -> Option<IDBValue> {
+        let old_value = self.records.remove(key);
+        for index in self.indexes.values_mut() {
+            if let Some(old_value) = &old_value {
+                index.remove(old_value, key);
+            }
+            if let Some(value) = &value {
+                index.insert(value, key);
+            }
+        }
+        if let Some(value) = value {
+            self.records.insert(key.clone(), value);
+        }
+        old_value
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#generate-a-key>
+    fn generate_key(&mut self) -> IDBResult<IDBKey> {
+        let current_number = self.key_generator.ok_or(IDBError::DataError)?;
+        if current_number >= MAX_GENERATED_KEY {
+            return Err(IDBError::ConstraintError);
+        }
+        self.key_generator = Some(current_number + 1.0);
+        Ok(IDBKey::Number(current_number))
+    }
+
+    /// Explicit number keys move the key generator past them so generated keys do not collide
+    /// <https://w3c.github.io/IndexedDB/#possibly-update-the-key-generator>
+    fn update_key_generator(&mut self, key: &IDBKey) {
+        let (Some(current_number), IDBKey::Number(number)) = (self.key_generator, key) else {
+            return;
+        };
+        let value = number.floor().min(MAX_GENERATED_KEY);
+        if value >= current_number {
+            self.key_generator = Some(value + 1.0);
+        }
+    }
+}
+
+/// An object store as used within a transaction
+/// <https://w3c.github.io/IndexedDB/#object-store-interface>
+#[derive(Clone)]
+pub struct IDBObjectStore {
+    transaction: IDBTransaction,
+    name: String,
 }
 
 impl IDBObjectStore {
-    pub fn new(name: &str, key_path: Option<&str>, auto_increment: bool) -> Self {
+    pub(super) fn new(transaction: IDBTransaction, name: &str) -> Self {
         Self {
+            transaction,
             name: name.to_string(),
-            key_path: key_path.map(str::to_string),
-            auto_increment,
-            indices: vec![],
         }
     }
 
-    pub fn add(_item: &IDBObjectStoreItem, _key: Option<&str>) -> IDBResult<()> {
-        Ok(())
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn transaction(&self) -> &IDBTransaction {
+        &self.transaction
+    }
+
+    pub fn key_path(&self) -> IDBResult<Option<IDBKeyPath>> {
+        self.read(|object_store| Ok(object_store.key_path.clone()))
+    }
+
+    pub fn auto_increment(&self) -> IDBResult<bool> {
+        self.read(|object_store| Ok(object_store.key_generator.is_some()))
+    }
+
+    /// The names of the indexes in order
+    pub fn index_names(&self) -> IDBResult<Vec<String>> {
+        self.read(|object_store| Ok(object_store.indexes.keys().cloned().collect()))
+    }
+
+    /// Stores the value, replacing the record with its key. The key is only given for stores
+    /// without a key path, and may be left out when the store has a key generator.
+    pub fn put(&self, value: IDBValue, key: Option<IDBKey>) -> IDBResult<IDBKey> {
+        self.store_record(value, key, false)
+    }
+
+    /// Stores the value like [`Self::put`], but fails with a `ConstraintError` when there already
+    /// is a record with its key
+    pub fn add(&self, value: IDBValue, key: Option<IDBKey>) -> IDBResult<IDBKey> {
+        self.store_record(value, key, true)
+    }
+
+    /// The value of the first record in the range
+    pub fn get(&self, query: impl Into<IDBKeyRange>) -> IDBResult<Option<IDBValue>> {
+        let range = query.into();
+        self.read(|object_store| {
+            Ok(object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .next()
+                .map(|(_, value)| value.clone()))
+        })
+    }
+
+    /// The key of the first record in the range
+    pub fn get_key(&self, query: impl Into<IDBKeyRange>) -> IDBResult<Option<IDBKey>> {
+        let range = query.into();
+        self.read(|object_store| {
+            Ok(object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .next()
+                .map(|(key, _)| key.clone()))
+        })
     }
 
+    /// The values of the records in the range, up to the count when given
+    pub fn get_all(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        count: Option<u32>,
+    ) -> IDBResult<Vec<IDBValue>> {
+        let range = query.into();
+        self.read(|object_store| {
+            Ok(object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .take(get_limit(count))
+                .map(|(_, value)| value.clone())
+                .collect())
+        })
+    }
+
+    /// The keys of the records in the range, up to the count when given
+    pub fn get_all_keys(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        count: Option<u32>,
+    ) -> IDBResult<Vec<IDBKey>> {
+        let range = query.into();
+        self.read(|object_store| {
+            Ok(object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .take(get_limit(count))
+                .map(|(key, _)| key.clone())
+                .collect())
+        })
+    }
+
+    pub fn count(&self, query: impl Into<IDBKeyRange>) -> IDBResult<IndexDbCount> {
+        let range = query.into();
+        self.read(|object_store| {
+            Ok(object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .count() as IndexDbCount)
+        })
+    }
+
+    /// Removes the records in the range
+    pub fn delete(&self, query: impl Into<IDBKeyRange>) -> IDBResult<()> {
+        let range = query.into();
+        self.write(|object_store, undo_log| {
+            let keys: Vec<IDBKey> = object_store
+                .records
+                .range::<IDBKey, _>((range.lower(), range.upper()))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in keys {
+                let value = object_store.set_record(&key, None);
+                undo_log.push(IDBUndoEntry::Record {
+                    object_store: self.name.clone(),
+                    key,
+                    value,
+                });
+            }
+            Ok(())
+        })
+    }
+
+    pub fn clear(&self) -> IDBResult<()> {
+        self.write(|object_store, undo_log| {
+            let records = std::mem::take(&mut object_store.records);
+            object_store.rebuild_indexes();
+            undo_log.push(IDBUndoEntry::Records {
+                object_store: self.name.clone(),
+                records,
+            });
+            Ok(())
+        })
+    }
+
+    /// A cursor at the first record in the range in the direction, or `None` when there is none
+    pub fn open_cursor(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        direction: IDBCursorDirection,
+    ) -> IDBResult<Option<IDBCursor>> {
+        IDBCursor::open(self.clone(), None, query.into(), direction, false)
+    }
+
+    /// A cursor like [`Self::open_cursor`] that does not read the values of records
+    pub fn open_key_cursor(
+        &self,
+        query: impl Into<IDBKeyRange>,
+        direction: IDBCursorDirection,
+    ) -> IDBResult<Option<IDBCursor>> {
+        IDBCursor::open(self.clone(), None, query.into(), direction, true)
+    }
+
+    pub fn index(&self, name: &str) -> IDBResult<IDBIndex> {
+        self.read(
+            |object_store| match object_store.indexes.contains_key(name) {
+                true => Ok(IDBIndex::new(self.clone(), name)),
+                false => Err(IDBError::NotFoundError),
+            },
+        )
+    }
+
+    /// Indexes the records of the store by the keys at the key path, which can only be done while
+    /// upgrading the database. Fails with a `ConstraintError` when a unique index would have
+    /// records with the same key, which aborts the upgrade.
     pub fn create_index(
         &self,
         index_name: &str,
-        key_path: &str,
+        key_path: impl Into<IDBKeyPath>,
         unique: Option<bool>,
         multi_entry: Option<bool>,
     ) -> IDBResult<IDBIndex> {
-        Ok(IDBIndex::new(
-            index_name,
-            &self.name,
-            Some(key_path),
-            multi_entry.unwrap_or(false),
-            unique.unwrap_or(false),
-        ))
+        let key_path = key_path.into();
+        let multi_entry = multi_entry.unwrap_or(false);
+        let added = self.transaction.upgrade(|database| {
+            let object_store = database
+                .object_stores
+                .get_mut(&self.name)
+                .ok_or(IDBError::InvalidState)?;
+            if object_store.indexes.contains_key(index_name) {
+                return Err(IDBError::ConstraintError);
+            }
+            if !key_path.is_valid() {
+                return Err(IDBError::DataError);
+            }
+            if multi_entry && matches!(key_path, IDBKeyPath::Array(_)) {
+                return Err(IDBError::InvalidAccessError);
+            }
+            Ok(object_store.add_index(IDBIndexData::new(
+                index_name,
+                key_path,
+                unique.unwrap_or(false),
+                multi_entry,
+            )))
+        })?;
+        if let Err(error) = added {
+            self.transaction.abort()?;
+            return Err(error);
+        }
+        Ok(IDBIndex::new(self.clone(), index_name))
+    }
+
+    /// Can only be done while upgrading the database
+    pub fn delete_index(&self, index_name: &str) -> IDBResult<()> {
+        self.transaction.upgrade(|database| {
+            let object_store = database
+                .object_stores
+                .get_mut(&self.name)
+                .ok_or(IDBError::InvalidState)?;
+            object_store
+                .indexes
+                .remove(index_name)
+                .map(|_| ())
+                .ok_or(IDBError::NotFoundError)
+        })
+    }
+
+    fn store_record(
+        &self,
+        value: IDBValue,
+        key: Option<IDBKey>,
+        no_overwrite: bool,
+    ) -> IDBResult<IDBKey> {
+        self.write(|object_store, undo_log| {
+            let (key, value) = object_store.store_record(value, key, no_overwrite)?;
+            undo_log.push(IDBUndoEntry::Record {
+                object_store: self.name.clone(),
+                key: key.clone(),
+                value,
+            });
+            Ok(key)
+        })
+    }
+
+    pub(super) fn read<R>(
+        &self,
+        f: impl FnOnce(&IDBObjectStoreData) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        self.transaction.read(&self.name, f)
+    }
+
+    pub(super) fn write<R>(
+        &self,
+        f: impl FnOnce(&mut IDBObjectStoreData, &mut Vec<IDBUndoEntry>) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        self.transaction.write(&self.name, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(number: f64) -> IDBKey {
+        IDBKey::Number(number)
+    }
+
+    fn item(id: Option<f64>, name: &str) -> IDBValue {
+        let mut value = IDBValue::object([("name", IDBValue::String(name.to_string()))]);
+        if let Some(id) = id {
+            value.set_property("id", IDBValue::Number(id));
+        }
+        value
+    }
+
+    #[test]
+    fn test_out_of_line_keys() {
+        let mut store = IDBObjectStoreData::new("store", None, false);
+        let (key, old_value) = store
+            .store_record(item(None, "a"), Some(n(1.0)), true)
+            .unwrap();
+        assert_eq!((key, old_value), (n(1.0), None));
+        assert_eq!(
+            store.store_record(item(None, "b"), Some(n(1.0)), true),
+            Err(IDBError::ConstraintError)
+        );
+        let (_, old_value) = store
+            .store_record(item(None, "b"), Some(n(1.0)), false)
+            .unwrap();
+        assert_eq!(old_value, Some(item(None, "a")));
+        assert_eq!(
+            store.store_record(item(None, "c"), None, false),
+            Err(IDBError::DataError)
+        );
+    }
+
+    #[test]
+    fn test_key_generator() {
+        let mut store = IDBObjectStoreData::new("store", Some("id".into()), true);
+        let (key, _) = store.store_record(item(None, "a"), None, true).unwrap();
+        assert_eq!(key, n(1.0));
+        assert_eq!(store.records[&n(1.0)], item(Some(1.0), "a"));
+
+        // Explicit keys move the generator past them
+        store
+            .store_record(item(Some(10.5), "b"), None, true)
+            .unwrap();
+        let (key, _) = store.store_record(item(None, "c"), None, true).unwrap();
+        assert_eq!(key, n(11.0));
+
+        assert_eq!(
+            store.store_record(item(None, "d"), Some(n(20.0)), true),
+            Err(IDBError::DataError)
+        );
+        assert_eq!(
+            store.store_record(IDBValue::Number(1.0), None, true),
+            Err(IDBError::DataError)
+        );
+
+        store.key_generator = Some(MAX_GENERATED_KEY);
+        assert_eq!(
+            store.store_record(item(None, "e"), None, true),
+            Err(IDBError::ConstraintError)
+        );
+    }
+
+    #[test]
+    fn test_indexes() {
+        let mut store = IDBObjectStoreData::new("store", Some("id".into()), false);
+        store
+            .add_index(IDBIndexData::new("name", "name".into(), true, false))
+            .unwrap();
+        store
+            .store_record(item(Some(1.0), "a"), None, true)
+            .unwrap();
+        store
+            .store_record(item(Some(2.0), "b"), None, true)
+            .unwrap();
+        assert_eq!(
+            store.store_record(item(Some(3.0), "a"), None, true),
+            Err(IDBError::ConstraintError)
+        );
+        // A record keeps its own index key when replaced
+        store
+            .store_record(item(Some(1.0), "a"), None, false)
+            .unwrap();
+        store
+            .store_record(item(Some(2.0), "c"), None, false)
+            .unwrap();
+        let index = store.get_index("name").unwrap();
+        assert_eq!(
+            index.records.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                IDBKey::String("a".to_string()),
+                IDBKey::String("c".to_string())
+            ]
+        );
+
+        store.set_record(&n(1.0), None);
+        assert_eq!(store.get_index("name").unwrap().records.len(), 1);
+
+        store
+            .store_record(item(Some(3.0), "e"), None, true)
+            .unwrap();
+        assert_eq!(
+            store.add_index(IDBIndexData::new(
+                "length",
+                "name.length".into(),
+                true,
+                false
+            )),
+            Err(IDBError::ConstraintError)
+        );
+        assert!(store.indexes.get("length").is_none());
+    }
+
+    #[test]
+    fn test_multi_entry_index() {
+        let mut store = IDBObjectStoreData::new("store", None, true);
+        store
+            .add_index(IDBIndexData::new("tags", "tags".into(), false, true))
+            .unwrap();
+        let tags = |tags: &[&str]| {
+            IDBValue::object([(
+                "tags",
+                IDBValue::Array(
+                    tags.iter()
+                        .map(|tag| IDBValue::String(tag.to_string()))
+                        .collect(),
+                ),
+            )])
+        };
+        store.store_record(tags(&["a", "b"]), None, true).unwrap();
+        store.store_record(tags(&["b", "b"]), None, true).unwrap();
+        let index = store.get_index("tags").unwrap();
+        assert_eq!(
+            index.records[&IDBKey::String("b".to_string())]
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![n(1.0), n(2.0)]
+        );
+        assert_eq!(index.records.len(), 2);
     }
 }
@@ -0,0 +1,477 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    IDBDatabaseData, IDBError, IDBIndexData, IDBKey, IDBKeyPath, IDBObjectStoreData, IDBResult,
+    IDBValue,
+};
+
+/// Starts every database file, followed by the version of the format
+const MAGIC: &[u8; 8] = b"WOWSERDB";
+const FORMAT_VERSION: u32 = 1;
+
+impl From<io::Error> for IDBError {
+    fn from(error: io::Error) -> Self {
+        Self::UnknownError(error.to_string())
+    }
+}
+
+/// Saves the database so that the file has either the old or the new contents, even if the
+/// browser crashes while saving. The contents are written to a temporary file, which is synced to
+/// disk before it replaces the file.
+pub fn save_database(path: &Path, data: &IDBDatabaseData) -> IDBResult<()> {
+    let payload = encode_database(data);
+    let mut contents = Vec::with_capacity(payload.len() + 28);
+    contents.extend_from_slice(MAGIC);
+    contents.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+    write_bytes(&mut contents, &payload);
+    contents.extend_from_slice(&get_checksum(&payload).to_be_bytes());
+
+    let temporary_path = get_temporary_path(path);
+    let mut file = File::create(&temporary_path)?;
+    file.write_all(&contents)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temporary_path, path)?;
+    // The rename is only durable once the directory is synced too
+    if let Some(directory) = path.parent() {
+        File::open(directory)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Loads the database saved at the path, resulting in `None` when it has not been saved. Fails
+/// with an `UnknownError` when the file is corrupt.
+pub fn load_database(path: &Path) -> IDBResult<Option<IDBDatabaseData>> {
+    // Left behind by a save that did not finish, so the file still has the last saved contents
+    let temporary_path = get_temporary_path(path);
+    if temporary_path.exists() {
+        fs::remove_file(&temporary_path)?;
+    }
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    decode_file(&contents)
+        .map(Some)
+        .ok_or_else(|| IDBError::UnknownError(format!("Corrupt database file {path:?}")))
+}
+
+fn get_temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// 64-bit FNV-1a, which detects files that were cut short or changed
+fn get_checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn decode_file(contents: &[u8]) -> Option<IDBDatabaseData> {
+    let mut decoder = Decoder { bytes: contents };
+    if decoder.take(MAGIC.len())? != MAGIC
+        || u32::from_be_bytes(decoder.take(4)?.try_into().ok()?) != FORMAT_VERSION
+    {
+        return None;
+    }
+    let payload = decoder.bytes()?;
+    if decoder.u64()? != get_checksum(payload) || !decoder.bytes.is_empty() {
+        return None;
+    }
+    let mut decoder = Decoder { bytes: payload };
+    let data = decoder.database()?;
+    decoder.bytes.is_empty().then_some(data)
+}
+
+fn encode_database(data: &IDBDatabaseData) -> Vec<u8> {
+    let mut bytes = vec![];
+    write_string(&mut bytes, &data.name);
+    bytes.extend_from_slice(&data.version.to_be_bytes());
+    write_length(&mut bytes, data.object_stores.len());
+    for object_store in data.object_stores.values() {
+        write_string(&mut bytes, &object_store.name);
+        match &object_store.key_path {
+            Some(key_path) => {
+                bytes.push(1);
+                write_key_path(&mut bytes, key_path);
+            }
+            None => bytes.push(0),
+        }
+        match object_store.key_generator {
+            Some(current_number) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&current_number.to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        // Index records are built again from the values when loading
+        write_length(&mut bytes, object_store.indexes.len());
+        for index in object_store.indexes.values() {
+            write_string(&mut bytes, &index.name);
+            write_key_path(&mut bytes, &index.key_path);
+            bytes.push(u8::from(index.unique));
+            bytes.push(u8::from(index.multi_entry));
+        }
+        write_length(&mut bytes, object_store.records.len());
+        for (key, value) in &object_store.records {
+            write_key(&mut bytes, key);
+            write_value(&mut bytes, value);
+        }
+    }
+    bytes
+}
+
+fn write_length(bytes: &mut Vec<u8>, length: usize) {
+    bytes.extend_from_slice(&(length as u64).to_be_bytes());
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    write_length(bytes, value.len());
+    bytes.extend_from_slice(value);
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_bytes(bytes, value.as_bytes());
+}
+
+fn write_key_path(bytes: &mut Vec<u8>, key_path: &IDBKeyPath) {
+    match key_path {
+        IDBKeyPath::String(path) => {
+            bytes.push(0);
+            write_string(bytes, path);
+        }
+        IDBKeyPath::Array(paths) => {
+            bytes.push(1);
+            write_length(bytes, paths.len());
+            for path in paths {
+                write_string(bytes, path);
+            }
+        }
+    }
+}
+
+fn write_key(bytes: &mut Vec<u8>, key: &IDBKey) {
+    match key {
+        IDBKey::Number(number) => {
+            bytes.push(0);
+            bytes.extend_from_slice(&number.to_be_bytes());
+        }
+        IDBKey::Date(date) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&date.to_be_bytes());
+        }
+        IDBKey::String(string) => {
+            bytes.push(2);
+            write_string(bytes, string);
+        }
+        IDBKey::Binary(binary) => {
+            bytes.push(3);
+            write_bytes(bytes, binary);
+        }
+        IDBKey::Array(keys) => {
+            bytes.push(4);
+            write_length(bytes, keys.len());
+            for key in keys {
+                write_key(bytes, key);
+            }
+        }
+    }
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &IDBValue) {
+    match value {
+        IDBValue::Undefined => bytes.push(0),
+        IDBValue::Null => bytes.push(1),
+        IDBValue::Bool(bool) => {
+            bytes.push(2);
+            bytes.push(u8::from(*bool));
+        }
+        IDBValue::Number(number) => {
+            bytes.push(3);
+            bytes.extend_from_slice(&number.to_be_bytes());
+        }
+        IDBValue::String(string) => {
+            bytes.push(4);
+            write_string(bytes, string);
+        }
+        IDBValue::Date(date) => {
+            bytes.push(5);
+            bytes.extend_from_slice(&date.to_be_bytes());
+        }
+        IDBValue::Binary(binary) => {
+            bytes.push(6);
+            write_bytes(bytes, binary);
+        }
+        IDBValue::Array(values) => {
+            bytes.push(7);
+            write_length(bytes, values.len());
+            for value in values {
+                write_value(bytes, value);
+            }
+        }
+        IDBValue::Object(properties) => {
+            bytes.push(8);
+            write_length(bytes, properties.len());
+            for (name, value) in properties {
+                write_string(bytes, name);
+                write_value(bytes, value);
+            }
+        }
+    }
+}
+
+/// Reads what the `write_*` functions wrote, resulting in `None` for anything unexpected
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        if length > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        Some(f64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn length(&mut self) -> Option<usize> {
+        self.u64()?.try_into().ok()
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let length = self.length()?;
+        self.take(length)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.bytes()?.to_vec()).ok()
+    }
+
+    fn key_path(&mut self) -> Option<IDBKeyPath> {
+        match self.u8()? {
+            0 => Some(IDBKeyPath::String(self.string()?)),
+            1 => {
+                let length = self.length()?;
+                let paths = (0..length).map(|_| self.string()).collect::<Option<_>>()?;
+                Some(IDBKeyPath::Array(paths))
+            }
+            _ => None,
+        }
+    }
+
+    fn key(&mut self) -> Option<IDBKey> {
+        match self.u8()? {
+            0 => Some(IDBKey::Number(self.f64()?)),
+            1 => Some(IDBKey::Date(self.f64()?)),
+            2 => Some(IDBKey::String(self.string()?)),
+            3 => Some(IDBKey::Binary(self.bytes()?.to_vec())),
+            4 => {
+                let length = self.length()?;
+                let keys = (0..length).map(|_| self.key()).collect::<Option<_>>()?;
+                Some(IDBKey::Array(keys))
+            }
+            _ => None,
+        }
+    }
+
+    fn value(&mut self) -> Option<IDBValue> {
+        match self.u8()? {
+            0 => Some(IDBValue::Undefined),
+            1 => Some(IDBValue::Null),
+            2 => Some(IDBValue::Bool(self.bool()?)),
+            3 => Some(IDBValue::Number(self.f64()?)),
+            4 => Some(IDBValue::String(self.string()?)),
+            5 => Some(IDBValue::Date(self.f64()?)),
+            6 => Some(IDBValue::Binary(self.bytes()?.to_vec())),
+            7 => {
+                let length = self.length()?;
+                let values = (0..length).map(|_| self.value()).collect::<Option<_>>()?;
+                Some(IDBValue::Array(values))
+            }
+            8 => {
+                let length = self.length()?;
+                let properties = (0..length)
+                    .map(|_| Some((self.string()?, self.value()?)))
+                    .collect::<Option<_>>()?;
+                Some(IDBValue::Object(properties))
+            }
+            _ => None,
+        }
+    }
+
+    fn database(&mut self) -> Option<IDBDatabaseData> {
+        let mut data = IDBDatabaseData::new(&self.string()?);
+        data.version = self.u64()?;
+        for _ in 0..self.length()? {
+            let name = self.string()?;
+            let key_path = match self.bool()? {
+                true => Some(self.key_path()?),
+                false => None,
+            };
+            let mut object_store = IDBObjectStoreData::new(&name, key_path, false);
+            object_store.key_generator = match self.bool()? {
+                true => Some(self.f64()?),
+                false => None,
+            };
+            for _ in 0..self.length()? {
+                let index = IDBIndexData::new(
+                    &self.string()?,
+                    self.key_path()?,
+                    self.bool()?,
+                    self.bool()?,
+                );
+                object_store.indexes.insert(index.name.clone(), index);
+            }
+            for _ in 0..self.length()? {
+                let key = self.key()?;
+                let value = self.value()?;
+                object_store.records.insert(key, value);
+            }
+            object_store.rebuild_indexes();
+            data.object_stores.insert(name, object_store);
+        }
+        Some(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::function_name;
+
+    use super::*;
+
+    fn get_data() -> IDBDatabaseData {
+        let mut object_store = IDBObjectStoreData::new("store", Some("id".into()), true);
+        object_store.indexes.insert(
+            "tags".to_string(),
+            IDBIndexData::new("tags", "tags".into(), false, true),
+        );
+        object_store
+            .store_record(
+                IDBValue::object([
+                    (
+                        "tags",
+                        IDBValue::Array(vec![IDBValue::String("a".to_string())]),
+                    ),
+                    ("date", IDBValue::Date(1.0)),
+                    ("bytes", IDBValue::Binary(vec![1, 2])),
+                    ("flag", IDBValue::Bool(true)),
+                    ("missing", IDBValue::Undefined),
+                    ("empty", IDBValue::Null),
+                ]),
+                None,
+                true,
+            )
+            .unwrap();
+        object_store
+            .store_record(
+                IDBValue::object([(
+                    "id",
+                    IDBValue::Array(vec![
+                        IDBValue::Number(2.0),
+                        IDBValue::String("b".to_string()),
+                    ]),
+                )]),
+                None,
+                true,
+            )
+            .unwrap();
+        let mut data = IDBDatabaseData::new("database");
+        data.version = 3;
+        data.object_stores.insert("store".to_string(), object_store);
+        data.object_stores.insert(
+            "other".to_string(),
+            IDBObjectStoreData::new(
+                "other",
+                Some(IDBKeyPath::Array(vec!["a".to_string(), "b".to_string()])),
+                false,
+            ),
+        );
+        data
+    }
+
+    fn get_directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(name);
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_save_and_load() -> IDBResult<()> {
+        let path = get_directory(function_name!()).join("database.indexeddb");
+        assert_eq!(load_database(&path)?, None);
+
+        let data = get_data();
+        save_database(&path, &data)?;
+        assert_eq!(load_database(&path)?, Some(data));
+        assert!(!get_temporary_path(&path).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupted_save() -> IDBResult<()> {
+        let path = get_directory(function_name!()).join("database.indexeddb");
+        let data = get_data();
+        save_database(&path, &data)?;
+        fs::write(get_temporary_path(&path), b"WOWSER")?;
+        assert_eq!(load_database(&path)?, Some(data));
+        assert!(!get_temporary_path(&path).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_file() -> IDBResult<()> {
+        let path = get_directory(function_name!()).join("database.indexeddb");
+        save_database(&path, &get_data())?;
+        let mut contents = fs::read(&path)?;
+
+        let length = contents.len();
+        contents[length / 2] ^= 1;
+        fs::write(&path, &contents)?;
+        assert!(matches!(
+            load_database(&path),
+            Err(IDBError::UnknownError(_))
+        ));
+
+        fs::write(&path, &contents[..length - 1])?;
+        assert!(matches!(
+            load_database(&path),
+            Err(IDBError::UnknownError(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, thread};
+
+use crate::js::JsNumberPrimitive;
+
+use super::{
+    IDBBackingStore, IDBDatabaseData, IDBError, IDBKey, IDBObjectStore, IDBObjectStoreData,
+    IDBResult, IDBValue, IndexDbVersion,
+};
+
+/// <https://w3c.github.io/IndexedDB/#transaction-mode>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IDBTransactionMode {
+    ReadOnly,
+    ReadWrite,
+    /// Upgrades the database while it is opened, and is the only mode that changes object stores
+    /// and indexes
+    VersionChange,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IDBTransactionStatus {
+    Active,
+    Committed,
+    Aborted,
+}
+
+/// Undoes a change made by a transaction when it is aborted
+#[derive(Debug)]
+pub(super) enum IDBUndoEntry {
+    /// The value the record had, or `None` when there was no record
+    Record {
+        object_store: String,
+        key: IDBKey,
+        value: Option<IDBValue>,
+    },
+    /// The current number the key generator had
+    KeyGenerator {
+        object_store: String,
+        current_number: JsNumberPrimitive,
+    },
+    /// All the records the object store had before it was cleared
+    Records {
+        object_store: String,
+        records: BTreeMap<IDBKey, IDBValue>,
+    },
+}
+
+struct IDBTransactionInner {
+    backing_store: Rc<RefCell<IDBBackingStore>>,
+    id: u64,
+    mode: IDBTransactionMode,
+    scope: Vec<String>,
+    status: IDBTransactionStatus,
+    /// The changes to undo when aborting, in the order they were made
+    undo_log: Vec<IDBUndoEntry>,
+    /// The database before it was upgraded, which aborting an upgrade restores
+    snapshot: Option<IDBDatabaseData>,
+}
+
+/// A group of reads and writes to the object stores in its scope, which either all take effect
+/// when it commits or none do when it aborts. The transaction commits once it and the object
+/// stores, indexes, and cursors using it are dropped, like transactions in scripts commit once
+/// they have no more requests.
+/// <https://w3c.github.io/IndexedDB/#transaction-concept>
+#[derive(Clone)]
+pub struct IDBTransaction {
+    inner: Rc<RefCell<IDBTransactionInner>>,
+}
+
+impl IDBTransaction {
+    pub(super) fn new(
+        backing_store: Rc<RefCell<IDBBackingStore>>,
+        mode: IDBTransactionMode,
+        scope: Vec<String>,
+    ) -> IDBResult<Self> {
+        let id = backing_store.borrow_mut().start_transaction(mode, &scope)?;
+        Ok(Self {
+            inner: Rc::new(RefCell::new(IDBTransactionInner {
+                backing_store,
+                id,
+                mode,
+                scope,
+                status: IDBTransactionStatus::Active,
+                undo_log: vec![],
+                snapshot: None,
+            })),
+        })
+    }
+
+    /// Starts upgrading the database to the version, which is undone if the transaction aborts
+    /// <https://w3c.github.io/IndexedDB/#upgrade-transaction-steps>
+    pub(super) fn new_upgrade(
+        backing_store: Rc<RefCell<IDBBackingStore>>,
+        version: IndexDbVersion,
+    ) -> IDBResult<Self> {
+        let transaction = Self::new(
+            backing_store.clone(),
+            IDBTransactionMode::VersionChange,
+            vec![],
+        )?;
+        let mut backing_store = backing_store.borrow_mut();
+        transaction.inner.borrow_mut().snapshot = Some(backing_store.data.clone());
+        backing_store.data.version = version;
+        Ok(transaction)
+    }
+
+    pub fn mode(&self) -> IDBTransactionMode {
+        self.inner.borrow().mode
+    }
+
+    pub fn status(&self) -> IDBTransactionStatus {
+        self.inner.borrow().status
+    }
+
+    /// The names of the object stores the transaction can use, which are all of them for upgrades
+    pub fn object_store_names(&self) -> Vec<String> {
+        let inner = self.inner.borrow();
+        match inner.mode {
+            IDBTransactionMode::VersionChange => inner
+                .backing_store
+                .borrow()
+                .data
+                .object_stores
+                .keys()
+                .cloned()
+                .collect(),
+            _ => inner.scope.clone(),
+        }
+    }
+
+    pub fn object_store(&self, name: &str) -> IDBResult<IDBObjectStore> {
+        {
+            let inner = self.inner.borrow();
+            if inner.status != IDBTransactionStatus::Active {
+                return Err(IDBError::InvalidState);
+            }
+            if !inner.has_in_scope(name) {
+                return Err(IDBError::NotFoundError);
+            }
+        }
+        Ok(IDBObjectStore::new(self.clone(), name))
+    }
+
+    /// Makes the changes of the transaction durable, writing them to disk for databases that are
+    /// saved. Fails with an `UnknownError` when they cannot be written, which aborts the
+    /// transaction instead.
+    pub fn commit(&self) -> IDBResult<()> {
+        self.inner.borrow_mut().commit()
+    }
+
+    /// Undoes the changes of the transaction
+    pub fn abort(&self) -> IDBResult<()> {
+        self.inner.borrow_mut().abort()
+    }
+
+    pub(super) fn read<R>(
+        &self,
+        object_store: &str,
+        f: impl FnOnce(&IDBObjectStoreData) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        let inner = self.inner.borrow();
+        inner.check_usable(object_store)?;
+        let backing_store = inner.backing_store.borrow();
+        let object_store = backing_store
+            .data
+            .object_stores
+            .get(object_store)
+            .ok_or(IDBError::InvalidState)?;
+        f(object_store)
+    }
+
+    /// Changes the object store, logging how to undo the changes. A `ConstraintError` aborts the
+    /// transaction, like failed requests that scripts do not handle.
+    pub(super) fn write<R>(
+        &self,
+        object_store: &str,
+        f: impl FnOnce(&mut IDBObjectStoreData, &mut Vec<IDBUndoEntry>) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        let mut inner = self.inner.borrow_mut();
+        inner.check_usable(object_store)?;
+        if inner.mode == IDBTransactionMode::ReadOnly {
+            return Err(IDBError::ReadOnlyError);
+        }
+        let inner = &mut *inner;
+        let result = {
+            let mut backing_store = inner.backing_store.borrow_mut();
+            let data = backing_store
+                .data
+                .object_stores
+                .get_mut(object_store)
+                .ok_or(IDBError::InvalidState)?;
+            let key_generator = data.key_generator;
+            let result = f(data, &mut inner.undo_log);
+            if data.key_generator != key_generator {
+                if let Some(current_number) = key_generator {
+                    inner.undo_log.push(IDBUndoEntry::KeyGenerator {
+                        object_store: object_store.to_string(),
+                        current_number,
+                    });
+                }
+            }
+            result
+        };
+        if matches!(result, Err(IDBError::ConstraintError)) {
+            inner.abort()?;
+        }
+        result
+    }
+
+    /// Changes the object stores or indexes of the database, which only upgrades can do
+    pub(super) fn upgrade<R>(
+        &self,
+        f: impl FnOnce(&mut IDBDatabaseData) -> IDBResult<R>,
+    ) -> IDBResult<R> {
+        let inner = self.inner.borrow();
+        if inner.mode != IDBTransactionMode::VersionChange {
+            return Err(IDBError::InvalidState);
+        }
+        if inner.status != IDBTransactionStatus::Active {
+            return Err(IDBError::TransactionInactiveError);
+        }
+        let mut backing_store = inner.backing_store.borrow_mut();
+        f(&mut backing_store.data)
+    }
+}
+
+impl IDBTransactionInner {
+    fn has_in_scope(&self, object_store: &str) -> bool {
+        match self.mode {
+            IDBTransactionMode::VersionChange => self
+                .backing_store
+                .borrow()
+                .data
+                .object_stores
+                .contains_key(object_store),
+            _ => self.scope.iter().any(|name| name == object_store),
+        }
+    }
+
+    fn check_usable(&self, object_store: &str) -> IDBResult<()> {
+        if self.status != IDBTransactionStatus::Active {
+            return Err(IDBError::TransactionInactiveError);
+        }
+        if self.mode != IDBTransactionMode::VersionChange
+            && !self.scope.iter().any(|name| name == object_store)
+        {
+            return Err(IDBError::NotFoundError);
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#commit-a-transaction>
+    fn commit(&mut self) -> IDBResult<()> {
+        if self.status != IDBTransactionStatus::Active {
+            return Err(IDBError::InvalidState);
+        }
+        if self.snapshot.is_some() || !self.undo_log.is_empty() {
+            let saved = self.backing_store.borrow().save();
+            if let Err(error) = saved {
+                self.abort()?;
+                return Err(error);
+            }
+        }
+        self.status = IDBTransactionStatus::Committed;
+        self.undo_log.clear();
+        self.snapshot = None;
+        self.backing_store.borrow_mut().finish_transaction(self.id);
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#abort-a-transaction>
+    fn abort(&mut self) -> IDBResult<()> {
+        if self.status != IDBTransactionStatus::Active {
+            return Err(IDBError::InvalidState);
+        }
+        let mut backing_store = self.backing_store.borrow_mut();
+        if let Some(snapshot) = self.snapshot.take() {
+            backing_store.data = snapshot;
+        } else {
+            let object_stores = &mut backing_store.data.object_stores;
+            for entry in self.undo_log.drain(..).rev() {
+                match entry {
+                    IDBUndoEntry::Record {
+                        object_store,
+                        key,
+                        value,
+                    } => {
+                        if let Some(object_store) = object_stores.get_mut(&object_store) {
+                            object_store.set_record(&key, value);
+                        }
+                    }
+                    IDBUndoEntry::KeyGenerator {
+                        object_store,
+                        current_number,
+                    } => {
+                        if let Some(object_store) = object_stores.get_mut(&object_store) {
+                            object_store.key_generator = Some(current_number);
+                        }
+                    }
+                    IDBUndoEntry::Records {
+                        object_store,
+                        records,
+                    } => {
+                        if let Some(object_store) = object_stores.get_mut(&object_store) {
+                            object_store.records = records;
+                            object_store.rebuild_indexes();
+                        }
+                    }
+                }
+            }
+        }
+        self.undo_log.clear();
+        self.status = IDBTransactionStatus::Aborted;
+        backing_store.finish_transaction(self.id);
+        Ok(())
+    }
+}
+
+/// Transactions that are still active when dropped commit, unless they are dropped while
+/// panicking, when the half-done changes are undone instead
+impl Drop for IDBTransactionInner {
+    fn drop(&mut self) {
+        if self.status != IDBTransactionStatus::Active {
+            return;
+        }
+        let result = if thread::panicking() {
+            self.abort()
+        } else {
+            self.commit()
+        };
+        if let Err(err) = result {
+            log!(ERROR: "Unable to commit IndexedDB transaction:", err);
+        }
+    }
+}
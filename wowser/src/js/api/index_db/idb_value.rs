@@ -0,0 +1,89 @@
+use crate::js::{api::date::Date, JsNumberPrimitive};
+
+/// A value stored in an object store. These are the values the structured clone algorithm copies
+/// that IndexedDB can read keys from.
+/// <https://html.spec.whatwg.org/multipage/structured-data.html#serializable-objects>
+#[derive(Clone, Debug, PartialEq)]
+pub enum IDBValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(JsNumberPrimitive),
+    String(String),
+    Date(Date),
+    /// The bytes of an `ArrayBuffer` or of a view on one
+    Binary(Vec<u8>),
+    Array(Vec<IDBValue>),
+    /// The own properties of an object in order
+    Object(Vec<(String, IDBValue)>),
+}
+
+impl IDBValue {
+    /// An object with the properties, for building values to store
+    pub fn object<'a>(properties: impl IntoIterator<Item = (&'a str, IDBValue)>) -> Self {
+        Self::Object(
+            properties
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The property as read by key paths, which includes the `length` of strings and arrays
+    /// <https://w3c.github.io/IndexedDB/#evaluate-a-key-path-on-a-value>
+    pub fn get_property(&self, name: &str) -> Option<IDBValue> {
+        match self {
+            Self::String(string) if name == "length" => Some(Self::Number(
+                string.encode_utf16().count() as JsNumberPrimitive,
+            )),
+            Self::Array(values) if name == "length" => {
+                Some(Self::Number(values.len() as JsNumberPrimitive))
+            }
+            Self::Object(properties) => properties
+                .iter()
+                .find(|(property_name, _)| property_name == name)
+                .map(|(_, value)| value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Adds or replaces a property of an object, which other values do not have
+    pub fn set_property(&mut self, name: &str, value: IDBValue) {
+        if let Self::Object(properties) = self {
+            match properties
+                .iter_mut()
+                .find(|(property_name, _)| property_name == name)
+            {
+                Some((_, property)) => *property = value,
+                None => properties.push((name.to_string(), value)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() {
+        let mut value = IDBValue::object([("a", IDBValue::Number(1.0))]);
+        value.set_property("b", IDBValue::Null);
+        value.set_property("a", IDBValue::Bool(true));
+        assert_eq!(
+            value,
+            IDBValue::object([("a", IDBValue::Bool(true)), ("b", IDBValue::Null)])
+        );
+        assert_eq!(value.get_property("c"), None);
+
+        assert_eq!(
+            IDBValue::String("é😀".to_string()).get_property("length"),
+            Some(IDBValue::Number(3.0))
+        );
+        assert_eq!(
+            IDBValue::Array(vec![IDBValue::Null]).get_property("length"),
+            Some(IDBValue::Number(1.0))
+        );
+        assert_eq!(IDBValue::Number(1.0).get_property("length"), None);
+    }
+}
@@ -1,27 +1,53 @@
 mod event_handlers;
+mod idb_cursor;
 mod idb_database;
 mod idb_factory;
 mod idb_index;
 mod idb_key;
+mod idb_key_path;
 mod idb_key_range;
 mod idb_object_store;
+mod idb_persistence;
+mod idb_transaction;
+mod idb_value;
 
 pub use event_handlers::*;
+pub use idb_cursor::*;
 pub use idb_database::*;
 pub use idb_factory::*;
 pub use idb_index::*;
 pub use idb_key::*;
+pub use idb_key_path::*;
 pub use idb_key_range::*;
 pub use idb_object_store::*;
+pub use idb_persistence::*;
+pub use idb_transaction::*;
+pub use idb_value::*;
 
 pub type IndexDbVersion = u64;
 pub type IndexDbCount = u64;
 
+/// The `DOMException`s IndexedDB operations fail with
+/// <https://w3c.github.io/IndexedDB/#exceptions>
+#[derive(Debug, PartialEq)]
 pub enum IDBError {
     InvalidState,
     TransactionInactiveError,
     ConstraintError,
     InvalidAccessError,
+    /// A value is not a valid key, or a key is not allowed where it was given
+    DataError,
+    NotFoundError,
+    /// A write was made in a `readonly` transaction
+    ReadOnlyError,
+    /// A database was opened with a lower version than it has
+    VersionError,
+    /// The transaction was aborted before the request finished
+    AbortError,
+    /// An argument has the wrong type, such as a version of 0
+    TypeError,
+    /// The database could not be read or written
+    UnknownError(String),
 }
 
 pub type IDBResult<T> = Result<T, IDBError>;
@@ -1,3 +1,5 @@
 pub mod date;
+pub mod history;
 pub mod index_db;
+pub mod module;
 pub mod storage;
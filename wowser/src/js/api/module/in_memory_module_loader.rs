@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use super::ModuleLoader;
+
+/// Loads modules from sources it was given up front
+#[derive(Debug, Default)]
+pub struct InMemoryModuleLoader {
+    sources: HashMap<String, String>,
+}
+
+impl InMemoryModuleLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_module(mut self, url: &str, source: &str) -> Self {
+        self.sources.insert(url.to_string(), source.to_string());
+        self
+    }
+}
+
+impl ModuleLoader for InMemoryModuleLoader {
+    fn load(&self, url: &str) -> Result<String, String> {
+        self.sources
+            .get(url)
+            .cloned()
+            .ok_or_else(|| format!("No module at {url}"))
+    }
+}
@@ -0,0 +1,15 @@
+mod in_memory_module_loader;
+mod specifier;
+mod url_module_loader;
+
+use std::fmt::Debug;
+
+pub use in_memory_module_loader::*;
+pub use specifier::*;
+pub use url_module_loader::*;
+
+/// Fetches the source of modules by URL
+pub trait ModuleLoader: Debug {
+    /// The source of the module at the URL, or a description of why it could not be loaded
+    fn load(&self, url: &str) -> Result<String, String>;
+}
@@ -0,0 +1,117 @@
+/// Resolves a module specifier to the URL of the module. Specifiers are either URLs or paths
+/// starting with `/`, `./`, or `../`, which are resolved against the URL of the module or document
+/// importing them. Bare names such as `lodash` are not resolved.
+pub fn resolve_module_specifier(specifier: &str, base: Option<&str>) -> Option<String> {
+    if split_origin(specifier).is_some() {
+        return Some(specifier.to_string());
+    }
+    if !["/", "./", "../"]
+        .iter()
+        .any(|prefix| specifier.starts_with(prefix))
+    {
+        return None;
+    }
+
+    let (origin, base_path) = split_origin(base?)?;
+    let (path, suffix) = match specifier.find(['?', '#']) {
+        Some(index) => specifier.split_at(index),
+        None => (specifier, ""),
+    };
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{}{path}", &base_path[..=base_path.rfind('/')?])
+    };
+    Some(format!("{origin}{}{suffix}", remove_dot_segments(&path)))
+}
+
+/// Splits a URL into its scheme and authority, and the path after them without any query or
+/// fragment
+fn split_origin(url: &str) -> Option<(&str, &str)> {
+    let scheme_end = url.find("://")?;
+    let scheme = &url[..scheme_end];
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let authority_start = scheme_end + "://".len();
+    let path_start = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |index| authority_start + index);
+    let path = &url[path_start..];
+    let path = &path[..path.find(['?', '#']).unwrap_or(path.len())];
+    Some((&url[..path_start], if path.is_empty() { "/" } else { path }))
+}
+
+/// Resolves the `.` and `..` segments of an absolute path
+fn remove_dot_segments(path: &str) -> String {
+    let parts = path.split('/').skip(1).collect::<Vec<_>>();
+    let mut segments = vec![];
+    for (index, part) in parts.iter().enumerate() {
+        let is_last = index == parts.len() - 1;
+        match *part {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            part => {
+                segments.push(part);
+                continue;
+            }
+        }
+        // A path ending in a dot segment refers to a directory
+        if is_last {
+            segments.push("");
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Option<&str> = Some("http://example.com:8000/scripts/app/main.js?version=2");
+
+    #[test]
+    fn test_resolve_relative() {
+        assert_eq!(
+            resolve_module_specifier("./util.js", BASE).unwrap(),
+            "http://example.com:8000/scripts/app/util.js"
+        );
+        assert_eq!(
+            resolve_module_specifier("../lib/./math.js?x=1", BASE).unwrap(),
+            "http://example.com:8000/scripts/lib/math.js?x=1"
+        );
+        assert_eq!(
+            resolve_module_specifier("../../../../up.js", BASE).unwrap(),
+            "http://example.com:8000/up.js"
+        );
+        assert_eq!(
+            resolve_module_specifier("./", BASE).unwrap(),
+            "http://example.com:8000/scripts/app/"
+        );
+    }
+
+    #[test]
+    fn test_resolve_absolute() {
+        assert_eq!(
+            resolve_module_specifier("/root.js", BASE).unwrap(),
+            "http://example.com:8000/root.js"
+        );
+        assert_eq!(
+            resolve_module_specifier("/a/../b.js", Some("file:///home/user/main.js")).unwrap(),
+            "file:///b.js"
+        );
+        assert_eq!(
+            resolve_module_specifier("http://other.com/x.js", None).unwrap(),
+            "http://other.com/x.js"
+        );
+    }
+
+    #[test]
+    fn test_resolve_invalid() {
+        assert_eq!(resolve_module_specifier("lodash", BASE), None);
+        assert_eq!(resolve_module_specifier("./util.js", None), None);
+        assert_eq!(resolve_module_specifier("./util.js", Some("main.js")), None);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use crate::net::{percent_decode, Url, UrlProtocol, NETWORK_RESOURCE_MANAGER};
+
+use super::ModuleLoader;
+
+const FILE_URL_PREFIX: &str = "file://";
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Loads `file:` URLs from the filesystem, and `http:` URLs through the network resource manager,
+/// blocking until the response arrives
+#[derive(Debug)]
+pub struct UrlModuleLoader {
+    /// How long to wait for a response before giving up
+    pub timeout: Duration,
+}
+
+impl Default for UrlModuleLoader {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl UrlModuleLoader {
+    fn load_file(path: &str) -> Result<String, String> {
+        // The host of a file URL, such as `localhost`, is ignored
+        let path = &path[path.find('/').unwrap_or(path.len())..];
+        let path = String::from_utf8(percent_decode(path.as_bytes()))
+            .map_err(|_| format!("{path} is not a valid path"))?;
+        fs::read_to_string(&path).map_err(|error| format!("Failed to read {path}: {error}"))
+    }
+
+    fn load_http(&self, url: &Url) -> Result<String, String> {
+        let start = Instant::now();
+        loop {
+            let result = NETWORK_RESOURCE_MANAGER
+                .lock()
+                .map_err(|_| "Network resource manager is unavailable".to_string())?
+                .get_or_request(url)
+                .and_then(|result_weak| result_weak.upgrade());
+            if let Some(result) = result {
+                return match result.as_ref() {
+                    Ok(response) if response.status.contains_success_content() => {
+                        String::from_utf8(response.body.clone())
+                            .map_err(|_| format!("{url} is not valid UTF-8"))
+                    }
+                    Ok(response) => Err(format!(
+                        "Failed to load {url}: {} {}",
+                        response.status.status_code, response.status.reason_phrase
+                    )),
+                    Err(error) => Err(format!("Failed to load {url}: {error:?}")),
+                };
+            }
+            if start.elapsed() > self.timeout {
+                return Err(format!("Timed out loading {url}"));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl ModuleLoader for UrlModuleLoader {
+    fn load(&self, url: &str) -> Result<String, String> {
+        if let Some(path) = url.strip_prefix(FILE_URL_PREFIX) {
+            return Self::load_file(path);
+        }
+        match Url::parse(url) {
+            Some(parsed) if parsed.protocol == UrlProtocol::Http => self.load_http(&parsed),
+            _ => Err(format!("Unsupported module URL: {url}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_load_file() {
+        let path = env::temp_dir().join("wowser_url_module_loader_test.js");
+        fs::write(&path, "export const x = 1").unwrap();
+        let url = format!("file://{}", path.display());
+        assert_eq!(
+            UrlModuleLoader::default().load(&url).unwrap(),
+            "export const x = 1"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_percent_encoded_file() {
+        let path = env::temp_dir().join("wowser url module loader test.js");
+        fs::write(&path, "export const y = 2").unwrap();
+        let url = format!("file://{}", path.display()).replace(' ', "%20");
+        assert_eq!(
+            UrlModuleLoader::default().load(&url).unwrap(),
+            "export const y = 2"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(UrlModuleLoader::default()
+            .load("file:///does/not/exist.js")
+            .is_err());
+    }
+
+    #[test]
+    fn test_load_unsupported() {
+        assert!(UrlModuleLoader::default()
+            .load("ftp://example.com/a.js")
+            .is_err());
+    }
+}
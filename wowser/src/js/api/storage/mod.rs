@@ -1,10 +1,14 @@
 mod file_based_storage;
 mod in_memory_storage;
+mod storage_area;
 mod storage_error;
+mod storage_profile;
 
 pub use file_based_storage::*;
 pub use in_memory_storage::*;
+pub use storage_area::*;
 pub use storage_error::*;
+pub use storage_profile::*;
 
 /// <https://developer.mozilla.org/en-US/docs/Web/API/Storage>
 /// This should match the JS API in behavior
@@ -0,0 +1,235 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use super::{Storage, StorageError};
+
+/// The UTF-16 code units the keys and values of a storage area can add up to, which is the 5 MiB
+/// browsers commonly allow each origin
+pub const DEFAULT_STORAGE_QUOTA: usize = 5 * 1024 * 1024;
+
+/// A change made to a storage area, as reported by `storage` events
+/// <https://html.spec.whatwg.org/multipage/webstorage.html#the-storageevent-interface>
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageChange {
+    /// `None` when the storage was cleared
+    pub key: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// The URL of the document that made the change
+    pub url: String,
+}
+
+/// A document using storage areas, which is told about the changes other documents make to them
+#[derive(Clone, Debug, Default)]
+pub struct StorageListener {
+    /// The URL of the document, which its changes are reported with
+    pub url: String,
+    changes: Rc<RefCell<Vec<StorageChange>>>,
+}
+
+impl StorageListener {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            changes: Default::default(),
+        }
+    }
+
+    /// The changes made by other documents since they were last taken
+    pub fn take_changes(&self) -> Vec<StorageChange> {
+        self.changes.take()
+    }
+}
+
+/// Storage shared by the documents of an origin, or by the documents of a tab for session storage.
+/// The size of its items is limited by a quota, and changes are reported to the other documents
+/// listening to it.
+pub struct StorageArea {
+    storage: Box<dyn Storage>,
+    quota: usize,
+    listeners: Vec<Weak<RefCell<Vec<StorageChange>>>>,
+}
+
+impl StorageArea {
+    pub fn new(storage: Box<dyn Storage>) -> Self {
+        Self::with_quota(storage, DEFAULT_STORAGE_QUOTA)
+    }
+
+    pub fn with_quota(storage: Box<dyn Storage>, quota: usize) -> Self {
+        Self {
+            storage,
+            quota,
+            listeners: vec![],
+        }
+    }
+
+    pub fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
+    }
+
+    /// Reports the changes made by others to the listener, for as long as the listener is kept
+    pub fn listen(&mut self, listener: &StorageListener) {
+        self.listeners.push(Rc::downgrade(&listener.changes));
+    }
+
+    /// Stores the item unless the storage would then take more space than its quota allows
+    pub fn set_item(
+        &mut self,
+        key: &str,
+        value: &str,
+        source: &StorageListener,
+    ) -> Result<(), StorageError> {
+        let old_value = self.storage.get_item(key).map(str::to_string);
+        if old_value.as_deref() == Some(value) {
+            return Ok(());
+        }
+        let old_size = old_value
+            .as_deref()
+            .map_or(0, |old_value| get_item_size(key, old_value));
+        if self.get_size() - old_size + get_item_size(key, value) > self.quota {
+            return Err(StorageError::QuotaExceeded);
+        }
+        self.storage.set_item(key, value);
+        self.notify(
+            StorageChange {
+                key: Some(key.to_string()),
+                old_value,
+                new_value: Some(value.to_string()),
+                url: source.url.clone(),
+            },
+            source,
+        );
+        Ok(())
+    }
+
+    pub fn remove_item(&mut self, key: &str, source: &StorageListener) {
+        let Some(old_value) = self.storage.get_item(key).map(str::to_string) else {
+            return;
+        };
+        self.storage.remove_item(key);
+        self.notify(
+            StorageChange {
+                key: Some(key.to_string()),
+                old_value: Some(old_value),
+                new_value: None,
+                url: source.url.clone(),
+            },
+            source,
+        );
+    }
+
+    pub fn clear(&mut self, source: &StorageListener) {
+        if self.storage.length() == 0 {
+            return;
+        }
+        self.storage.clear();
+        self.notify(
+            StorageChange {
+                key: None,
+                old_value: None,
+                new_value: None,
+                url: source.url.clone(),
+            },
+            source,
+        );
+    }
+
+    /// The UTF-16 code units of all keys and values
+    fn get_size(&self) -> usize {
+        (0..self.storage.length())
+            .filter_map(|index| self.storage.key(index))
+            .map(|key| get_item_size(key, self.storage.get_item(key).unwrap_or_default()))
+            .sum()
+    }
+
+    /// Queues the change for every listener but the one that made it, forgetting listeners that
+    /// are gone
+    fn notify(&mut self, change: StorageChange, source: &StorageListener) {
+        self.listeners
+            .retain(|listener| listener.strong_count() > 0);
+        for listener in self.listeners.iter().filter_map(Weak::upgrade) {
+            if !Rc::ptr_eq(&listener, &source.changes) {
+                listener.borrow_mut().push(change.clone());
+            }
+        }
+    }
+}
+
+fn get_item_size(key: &str, value: &str) -> usize {
+    key.encode_utf16().count() + value.encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::js::api::storage::InMemoryStorage;
+
+    use super::*;
+
+    fn change(
+        key: Option<&str>,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> StorageChange {
+        StorageChange {
+            key: key.map(str::to_string),
+            old_value: old_value.map(str::to_string),
+            new_value: new_value.map(str::to_string),
+            url: "http://a.com/".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_changes_reported_to_others() {
+        let mut area = StorageArea::new(Box::<InMemoryStorage>::default());
+        let source = StorageListener::new("http://a.com/");
+        let other = StorageListener::new("http://a.com/other");
+        area.listen(&source);
+        area.listen(&other);
+        {
+            let gone = StorageListener::new("http://a.com/gone");
+            area.listen(&gone);
+        }
+
+        area.set_item("a", "1", &source).unwrap();
+        area.set_item("a", "1", &source).unwrap();
+        area.set_item("a", "2", &source).unwrap();
+        area.remove_item("a", &source);
+        area.remove_item("a", &source);
+        area.set_item("b", "3", &source).unwrap();
+        area.clear(&source);
+        area.clear(&source);
+
+        assert!(source.take_changes().is_empty());
+        assert_eq!(
+            other.take_changes(),
+            vec![
+                change(Some("a"), None, Some("1")),
+                change(Some("a"), Some("1"), Some("2")),
+                change(Some("a"), Some("2"), None),
+                change(Some("b"), None, Some("3")),
+                change(None, None, None),
+            ]
+        );
+        assert!(other.take_changes().is_empty());
+        assert_eq!(area.listeners.len(), 2);
+    }
+
+    #[test]
+    fn test_quota() {
+        let mut area = StorageArea::with_quota(Box::<InMemoryStorage>::default(), 6);
+        let source = StorageListener::default();
+        area.set_item("a", "bc", &source).unwrap();
+        area.set_item("é", "fg", &source).unwrap();
+        assert!(matches!(
+            area.set_item("h", "i", &source),
+            Err(StorageError::QuotaExceeded)
+        ));
+        // Replacing a value only needs room for the difference
+        area.set_item("a", "b", &source).unwrap();
+        area.set_item("h", "", &source).unwrap();
+        assert_eq!(area.storage().length(), 3);
+        assert_eq!(area.storage().get_item("a"), Some("b"));
+    }
+}
@@ -4,6 +4,8 @@ use std::{io, str::Utf8Error};
 pub enum StorageError {
     IoError(io::Error),
     Utf8Error(Utf8Error),
+    /// Storing the item would take more space than the storage is allowed
+    QuotaExceeded,
 }
 
 impl From<io::Error> for StorageError {
@@ -0,0 +1,105 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use super::{FileBasedStorage, InMemoryStorage, StorageArea, StorageError};
+
+/// The local storage of each origin, shared by all tabs showing documents of the origin. Storage
+/// is saved to a file for each origin in the profile directory, or kept in memory without one.
+#[derive(Default)]
+pub struct StorageProfile {
+    directory: Option<PathBuf>,
+    local_storage: HashMap<String, Rc<RefCell<StorageArea>>>,
+}
+
+impl StorageProfile {
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: Some(directory.to_path_buf()),
+            local_storage: HashMap::new(),
+        }
+    }
+
+    /// The local storage of the origin, such as `http://example.com`, which is loaded on first use
+    pub fn get_local_storage(
+        &mut self,
+        origin: &str,
+    ) -> Result<Rc<RefCell<StorageArea>>, StorageError> {
+        if let Some(area) = self.local_storage.get(origin) {
+            return Ok(area.clone());
+        }
+        let area = match &self.directory {
+            Some(directory) => {
+                fs::create_dir_all(directory)?;
+                let path = directory.join(get_file_name(origin));
+                StorageArea::new(Box::new(FileBasedStorage::new(&path)?))
+            }
+            None => StorageArea::new(Box::<InMemoryStorage>::default()),
+        };
+        let area = Rc::new(RefCell::new(area));
+        self.local_storage.insert(origin.to_string(), area.clone());
+        Ok(area)
+    }
+}
+
+/// Names the file of an origin
+fn get_file_name(origin: &str) -> String {
+    escape_file_name(origin) + ".localstorage"
+}
+
+/// Escapes the characters that cannot be in file names, so each name has its own file
+pub fn escape_file_name(name: &str) -> String {
+    let mut escaped = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+            escaped.push(c);
+        } else {
+            escaped += &format!("_{:x}_", u32::from(c));
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::{function_name, js::api::storage::StorageListener};
+
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            get_file_name("http://example.com:8080"),
+            "http_3a__2f__2f_example.com_3a_8080.localstorage"
+        );
+    }
+
+    #[test]
+    fn test_shared_by_origin() -> Result<(), StorageError> {
+        let directory = env::temp_dir().join(function_name!());
+        if directory.exists() {
+            fs::remove_dir_all(&directory)?;
+        }
+        let listener = StorageListener::default();
+        {
+            let mut profile = StorageProfile::new(&directory);
+            let area = profile.get_local_storage("http://a.com")?;
+            area.borrow_mut().set_item("a", "1", &listener)?;
+            let same_area = profile.get_local_storage("http://a.com")?;
+            assert_eq!(same_area.borrow().storage().get_item("a"), Some("1"));
+            let other_area = profile.get_local_storage("http://b.com")?;
+            assert_eq!(other_area.borrow().storage().get_item("a"), None);
+        }
+
+        let mut profile = StorageProfile::new(&directory);
+        let area = profile.get_local_storage("http://a.com")?;
+        assert_eq!(area.borrow().storage().get_item("a"), Some("1"));
+        Ok(())
+    }
+}
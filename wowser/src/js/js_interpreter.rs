@@ -1,4 +1,9 @@
-use super::{JsDocument, JsExpression, JsFunction, JsRule, JsStatement, JsValue, JsValueGraph};
+use super::{
+    JsBigInt, JsBinaryOperator, JsClass, JsDeclarationKind, JsDocument, JsExport, JsExpression,
+    JsFunction, JsImport, JsImportBinding, JsMethod, JsMethodKind, JsObjectMember, JsPattern,
+    JsProperties, JsPropertyName, JsRule, JsStatement, JsValue, JsValueGraph, ARGUMENTS_REFERENCE,
+    CONSTRUCTOR_MEMBER, DEFAULT_EXPORT_NAME,
+};
 use crate::{
     garbage_collector::GcNodeGraph,
     js::JsReference,
@@ -12,6 +17,17 @@ type JsASTNode<'a> = ASTNode<'a, JsRule>;
 
 pub struct JsInterpreter {}
 
+impl JsInterpreter {
+    /// Interprets the statements of a document, creating values in an existing node graph
+    pub fn interpret_statements(
+        node_graph: &JsValueGraph,
+        document: &JsASTNode,
+    ) -> Vec<JsStatement> {
+        let children = extract_interpreter_n_children(document, JsRule::Document, 2);
+        on_statements(node_graph, &children[0])
+    }
+}
+
 fn on_statements(node_graph: &JsValueGraph, statements: &JsASTNode) -> Vec<JsStatement> {
     let children = extract_interpreter_children(statements, JsRule::Statements);
 
@@ -26,29 +42,212 @@ fn on_statement(node_graph: &JsValueGraph, statement: &JsASTNode) -> JsStatement
 
     let first_child = &children[0];
     match first_child.rule {
-        JsRule::Semicolon => JsStatement::Empty,
-        JsRule::Expression => JsStatement::Expression(on_expression(first_child)),
+        JsRule::Semicolon | JsRule::AutomaticSemicolon => JsStatement::Empty,
+        JsRule::ImportDeclaration => on_import_declaration(first_child),
+        JsRule::ExportDeclaration => on_export_declaration(node_graph, first_child),
+        JsRule::Expression => JsStatement::Expression(on_expression(node_graph, first_child)),
         JsRule::VarDeclaration => on_var_declaration(node_graph, first_child),
-        JsRule::VariableAssignment => on_variable_assignment(node_graph, first_child),
         JsRule::FunctionDeclaration => on_function_declaration(node_graph, first_child),
-        JsRule::ReturnKeyword => JsStatement::Return(on_expression(&children[1])),
-        JsRule::ThrowKeyword => JsStatement::Throw(on_expression(&children[1])),
+        JsRule::ClassDeclaration => on_class_declaration(node_graph, first_child),
+        JsRule::ReturnKeyword => match children.len() {
+            3 => JsStatement::Return(on_expression(node_graph, &children[1])),
+            _ => JsStatement::Return(JsExpression::Undefined),
+        },
+        JsRule::ThrowKeyword => JsStatement::Throw(on_expression(node_graph, &children[1])),
+        JsRule::BreakKeyword => match children.len() {
+            3 => JsStatement::Break(Some(on_variable_name(&children[1]))),
+            _ => JsStatement::Break(None),
+        },
         JsRule::IfStatement => on_if_statement(node_graph, first_child),
+        JsRule::SwitchStatement => on_switch_statement(node_graph, first_child),
+        JsRule::VariableName => JsStatement::Labeled(
+            on_variable_name(first_child),
+            Box::new(on_statement(node_graph, &children[2])),
+        ),
+        JsRule::OpenCurlyBrace => JsStatement::Block(on_statements(node_graph, &children[1])),
         rule => panic!("Unexpected child of Statement: {rule}"),
     }
 }
 
+fn on_import_declaration(node: &JsASTNode) -> JsStatement {
+    let children = extract_interpreter_children(node, JsRule::ImportDeclaration);
+
+    let (clause, specifier) = match children.len() {
+        3 => (None, &children[1]),
+        5 => (Some(&children[1]), &children[3]),
+        num => panic!("Unexpected number of children for import declaration: {num}"),
+    };
+    let mut bindings = vec![];
+    if let Some(clause) = clause {
+        for child in extract_interpreter_children(clause, JsRule::ImportClause) {
+            match child.rule {
+                JsRule::VariableName => bindings.push(JsImportBinding::Named(
+                    DEFAULT_EXPORT_NAME.to_string(),
+                    on_variable_name(child),
+                )),
+                JsRule::NamespaceImport => {
+                    let namespace_children =
+                        extract_interpreter_n_children(child, JsRule::NamespaceImport, 3);
+                    bindings.push(JsImportBinding::Namespace(on_variable_name(
+                        &namespace_children[2],
+                    )));
+                }
+                JsRule::NamedBindings => bindings.extend(
+                    on_named_bindings(child)
+                        .into_iter()
+                        .map(|(imported, local)| JsImportBinding::Named(imported, local)),
+                ),
+                JsRule::Comma => {}
+                rule => panic!("Unexpected child of ImportClause: {rule}"),
+            }
+        }
+    }
+    JsStatement::Import(JsImport {
+        specifier: on_module_specifier(specifier),
+        bindings,
+    })
+}
+
+fn on_export_declaration(node_graph: &JsValueGraph, node: &JsASTNode) -> JsStatement {
+    let children = extract_interpreter_children(node, JsRule::ExportDeclaration);
+
+    let export = match (children[0].rule, children[1].rule, children.len()) {
+        (JsRule::ExportDefaultKeyword, JsRule::FunctionDeclaration, _) => {
+            JsExport::DefaultDeclaration(Box::new(on_function_declaration(
+                node_graph,
+                &children[1],
+            )))
+        }
+        (JsRule::ExportDefaultKeyword, JsRule::ClassDeclaration, _) => {
+            JsExport::DefaultDeclaration(Box::new(on_class_declaration(node_graph, &children[1])))
+        }
+        (JsRule::ExportDefaultKeyword, _, _) => {
+            JsExport::Default(on_expression_assignment(node_graph, &children[1]))
+        }
+        (_, JsRule::OperatorMultiply, 7) => JsExport::All(
+            on_module_specifier(&children[5]),
+            Some(on_module_export_name(&children[3])),
+        ),
+        (_, JsRule::OperatorMultiply, _) => JsExport::All(on_module_specifier(&children[3]), None),
+        (_, JsRule::NamedBindings, 5) => JsExport::From(
+            on_module_specifier(&children[3]),
+            on_named_bindings(&children[1]),
+        ),
+        (_, JsRule::NamedBindings, _) => JsExport::Names(on_named_bindings(&children[1])),
+        (_, JsRule::VarDeclaration, _) => {
+            JsExport::Declaration(Box::new(on_var_declaration(node_graph, &children[1])))
+        }
+        (_, JsRule::FunctionDeclaration, _) => {
+            JsExport::Declaration(Box::new(on_function_declaration(node_graph, &children[1])))
+        }
+        (_, JsRule::ClassDeclaration, _) => {
+            JsExport::Declaration(Box::new(on_class_declaration(node_graph, &children[1])))
+        }
+        (_, rule, _) => panic!("Unexpected child of ExportDeclaration: {rule}"),
+    };
+    JsStatement::Export(export)
+}
+
+fn on_module_specifier(node: &JsASTNode) -> String {
+    let child = extract_interpreter_single_child(node, JsRule::ModuleSpecifier);
+    on_string_literal(child)
+}
+
+/// (Name, Alias) pairs of the bindings, where the alias defaults to the name
+fn on_named_bindings(node: &JsASTNode) -> Vec<(String, String)> {
+    let children = extract_interpreter_n_children(node, JsRule::NamedBindings, 3);
+    let list_children = extract_interpreter_children(&children[1], JsRule::NamedBindingsList);
+
+    let mut ret = vec![];
+    if list_children.is_empty() {
+        return ret;
+    }
+    ret.push(on_named_binding(&list_children[0]));
+    let mut tail = &list_children[1];
+    loop {
+        let tail_children = extract_interpreter_children(tail, JsRule::NamedBindingsTail);
+        if tail_children.len() != 3 {
+            return ret;
+        }
+        ret.push(on_named_binding(&tail_children[1]));
+        tail = &tail_children[2];
+    }
+}
+
+fn on_named_binding(node: &JsASTNode) -> (String, String) {
+    let children = extract_interpreter_children(node, JsRule::NamedBinding);
+
+    let name = on_module_export_name(&children[0]);
+    match children.len() {
+        3 => (name, on_module_export_name(&children[2])),
+        _ => (name.clone(), name),
+    }
+}
+
+fn on_module_export_name(node: &JsASTNode) -> String {
+    let child = extract_interpreter_single_child(node, JsRule::ModuleExportName);
+
+    match child.rule {
+        JsRule::VariableName => on_variable_name(child),
+        JsRule::DefaultKeyword => DEFAULT_EXPORT_NAME.to_string(),
+        rule => panic!("Unexpected module export name: {rule}"),
+    }
+}
+
 fn on_var_declaration(node_graph: &JsValueGraph, var_declaration: &JsASTNode) -> JsStatement {
     let children = extract_interpreter_children(var_declaration, JsRule::VarDeclaration);
 
-    let reference = JsReference {
-        name: on_variable_name(&children[1]),
-        value: JsValue::undefined_rc(node_graph),
-    };
-    if children.len() == 4 {
-        JsStatement::VariableAssignment(reference, on_expression(&children[3]))
-    } else {
-        JsStatement::VarDeclaration(reference)
+    let kind = on_declaration_keyword(&children[0]);
+    let binding = &children[1];
+    match (kind, children.len()) {
+        (JsDeclarationKind::Var, 2) => JsStatement::VarDeclaration(JsReference {
+            name: on_variable_name(binding),
+            value: JsValue::undefined_rc(node_graph),
+            constant: false,
+        }),
+        (_, 2) => {
+            JsStatement::Declaration(kind, JsPattern::Identifier(on_variable_name(binding)), None)
+        }
+        (_, 4) => {
+            let pattern = on_binding_target(node_graph, binding);
+            let expression = on_expression_assignment(node_graph, &children[3]);
+            match (kind, pattern) {
+                (JsDeclarationKind::Var, JsPattern::Identifier(name)) => {
+                    JsStatement::VariableAssignment(
+                        JsReference {
+                            name,
+                            value: JsValue::undefined_rc(node_graph),
+                            constant: false,
+                        },
+                        expression,
+                    )
+                }
+                (kind, pattern) => JsStatement::Declaration(kind, pattern, Some(expression)),
+            }
+        }
+        (_, num) => panic!("Unexpected number of children in declaration: {num}"),
+    }
+}
+
+fn on_declaration_keyword(node: &JsASTNode) -> JsDeclarationKind {
+    let child = extract_interpreter_single_child(node, JsRule::DeclarationKeyword);
+
+    match child.rule {
+        JsRule::VarKeyword => JsDeclarationKind::Var,
+        JsRule::LetKeyword => JsDeclarationKind::Let,
+        JsRule::ConstKeyword => JsDeclarationKind::Const,
+        rule => panic!("Unexpected declaration keyword: {rule}"),
+    }
+}
+
+fn on_binding_target(node_graph: &JsValueGraph, node: &JsASTNode) -> JsPattern {
+    let child = extract_interpreter_single_child(node, JsRule::BindingTarget);
+
+    match child.rule {
+        JsRule::VariableName => JsPattern::Identifier(on_variable_name(child)),
+        JsRule::ArrayLiteral => JsPattern::from(on_array_literal(node_graph, child)),
+        JsRule::ObjectLiteral => JsPattern::from(on_object_literal(node_graph, child)),
+        rule => panic!("Unexpected binding target: {rule}"),
     }
 }
 
@@ -77,69 +276,233 @@ fn on_variable_name(variable_name: &JsASTNode) -> String {
     extract_interpreter_token(variable_name, JsRule::VariableName)
 }
 
-fn on_variable_assignment(
-    node_graph: &JsValueGraph,
-    variable_assignment: &JsASTNode,
-) -> JsStatement {
-    let children =
-        extract_interpreter_n_children(variable_assignment, JsRule::VariableAssignment, 3);
-
-    JsStatement::VariableAssignment(
-        JsReference {
-            name: on_variable_name(&children[0]),
-            value: JsValue::undefined_rc(node_graph),
-        },
-        on_expression(&children[2]),
-    )
-}
-
 fn on_function_declaration(node_graph: &JsValueGraph, node: &JsASTNode) -> JsStatement {
     let children = extract_interpreter_n_children(node, JsRule::FunctionDeclaration, 8);
     let function_name = on_variable_name(&children[1]);
-    let params = on_function_params(&children[3]);
-    let statements = on_statements(node_graph, &children[6]);
+    let (params, prologue) = on_function_params(node_graph, &children[3]);
+    let statements = prologue
+        .into_iter()
+        .chain(on_statements(node_graph, &children[6]))
+        .collect();
     let raw_text = node.rebuild_full_text().trim().to_string();
     JsStatement::FunctionDeclaration(JsReference {
         name: function_name.clone(),
         value: GcNodeGraph::create_node(
             node_graph,
-            JsValue::Function(JsFunction::UserDefined(
-                raw_text,
-                function_name,
-                params,
-                statements,
-            )),
+            JsValue::Function(
+                JsFunction::UserDefined(raw_text, function_name, params, statements),
+                JsProperties::new(),
+            ),
         ),
+        constant: false,
     })
 }
 
-fn on_function_params(node: &JsASTNode) -> Vec<String> {
-    let children = extract_interpreter_children(node, JsRule::FunctionParams);
+fn on_class_declaration(node_graph: &JsValueGraph, node: &JsASTNode) -> JsStatement {
+    let children = extract_interpreter_n_children(node, JsRule::ClassDeclaration, 5);
+    let name = on_variable_name(&children[1]);
+
+    let mut constructor = None;
+    let mut methods = vec![];
+    for member in extract_interpreter_children(&children[3], JsRule::ClassMembers) {
+        let member_children = extract_interpreter_children(member, JsRule::ClassMember);
+        match (member_children[0].rule, member_children.len()) {
+            (JsRule::Semicolon, _) => {}
+            (JsRule::StaticKeyword, 2) => {
+                methods.push((true, on_method_definition(node_graph, &member_children[1])));
+            }
+            (JsRule::MethodDefinition, 1) => {
+                let (kind, method_name, params, statements) =
+                    on_method_parts(node_graph, &member_children[0]);
+                match (kind, method_name) {
+                    (JsMethodKind::Method, JsPropertyName::Static(method_name))
+                        if method_name == CONSTRUCTOR_MEMBER =>
+                    {
+                        constructor = Some((params, statements));
+                    }
+                    (kind, method_name) => methods.push((
+                        false,
+                        build_method(
+                            node_graph,
+                            &member_children[0],
+                            kind,
+                            method_name,
+                            params,
+                            statements,
+                        ),
+                    )),
+                }
+            }
+            (rule, num) => panic!("Unexpected class member: {rule} with {num} children"),
+        }
+    }
 
-    if children.is_empty() {
-        return vec![];
+    // The constructor keeps the name of the class and the source of the whole declaration
+    let raw_text = node.rebuild_full_text().trim().to_string();
+    let (params, statements) = constructor.unwrap_or_default();
+    JsStatement::ClassDeclaration(JsClass {
+        name: name.clone(),
+        constructor: GcNodeGraph::create_node(
+            node_graph,
+            JsValue::Function(
+                JsFunction::UserDefined(raw_text, name, params, statements),
+                JsProperties::new(),
+            ),
+        ),
+        methods,
+    })
+}
+
+fn on_method_definition(node_graph: &JsValueGraph, node: &JsASTNode) -> JsMethod {
+    let (kind, name, params, statements) = on_method_parts(node_graph, node);
+    build_method(node_graph, node, kind, name, params, statements)
+}
+
+fn build_method(
+    node_graph: &JsValueGraph,
+    node: &JsASTNode,
+    kind: JsMethodKind,
+    name: JsPropertyName,
+    params: Vec<String>,
+    statements: Vec<JsStatement>,
+) -> JsMethod {
+    let function_name = match &name {
+        JsPropertyName::Static(name) => name.clone(),
+        JsPropertyName::Computed(_) => String::new(),
+    };
+    let raw_text = node.rebuild_full_text().trim().to_string();
+    JsMethod {
+        kind,
+        name,
+        function: GcNodeGraph::create_node(
+            node_graph,
+            JsValue::Function(
+                JsFunction::UserDefined(raw_text, function_name, params, statements),
+                JsProperties::new(),
+            ),
+        ),
     }
+}
 
-    let variable_name = on_variable_name(&children[0]);
-    let mut params = if children.len() == 3 {
-        on_function_params(&children[2])
-    } else {
-        vec![]
+/// (Kind, Name, Params, Implementation)
+fn on_method_parts(
+    node_graph: &JsValueGraph,
+    node: &JsASTNode,
+) -> (JsMethodKind, JsPropertyName, Vec<String>, Vec<JsStatement>) {
+    let children = extract_interpreter_children(node, JsRule::MethodDefinition);
+
+    let (kind, children) = match children.len() {
+        8 => {
+            let kind =
+                match extract_interpreter_token(&children[0], JsRule::AccessorKeyword).as_str() {
+                    "get" => JsMethodKind::Getter,
+                    "set" => JsMethodKind::Setter,
+                    keyword => panic!("Unexpected accessor keyword: {keyword}"),
+                };
+            (kind, &children[1..])
+        }
+        7 => (JsMethodKind::Method, &children[..]),
+        num => panic!("Unexpected number of children for method definition: {num}"),
     };
-    params.insert(0, variable_name);
-    params
+    let name = on_member_name(node_graph, &children[0]);
+    let (params, prologue) = on_function_params(node_graph, &children[2]);
+    let statements = prologue
+        .into_iter()
+        .chain(on_statements(node_graph, &children[5]))
+        .collect();
+    (kind, name, params, statements)
+}
+
+fn on_member_name(node_graph: &JsValueGraph, node: &JsASTNode) -> JsPropertyName {
+    let children = extract_interpreter_children(node, JsRule::MemberName);
+
+    match children.len() {
+        1 => JsPropertyName::Static(on_property_name(&children[0])),
+        3 => JsPropertyName::Computed(on_expression(node_graph, &children[1])),
+        num => panic!("Unexpected number of children for member name: {num}"),
+    }
+}
+
+/// Simple parameter lists are bound by name. Lists with defaults, patterns, or a rest element are
+/// instead destructured from the function's arguments by a statement run before the body.
+fn on_function_params(
+    node_graph: &JsValueGraph,
+    node: &JsASTNode,
+) -> (Vec<String>, Option<JsStatement>) {
+    let children = extract_interpreter_children(node, JsRule::FunctionParams);
+
+    let mut params = vec![];
+    let mut rest = None;
+    if !children.is_empty() {
+        params.push(on_function_param(node_graph, &children[0], &mut rest));
+        let mut tail = &children[1];
+        loop {
+            let tail_children = extract_interpreter_children(tail, JsRule::FunctionParamsTail);
+            if tail_children.len() != 3 {
+                break;
+            }
+            params.push(on_function_param(node_graph, &tail_children[1], &mut rest));
+            tail = &tail_children[2];
+        }
+    }
+
+    let params = params.into_iter().flatten().collect::<Vec<_>>();
+    let simple_names = params
+        .iter()
+        .map(|param| match param {
+            JsPattern::Identifier(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+
+    match simple_names {
+        Some(names) if rest.is_none() => (names, None),
+        _ => (
+            vec![],
+            Some(JsStatement::Declaration(
+                JsDeclarationKind::Let,
+                JsPattern::Array(params.into_iter().map(Some).collect(), rest),
+                Some(JsExpression::Reference(ARGUMENTS_REFERENCE.to_string())),
+            )),
+        ),
+    }
+}
+
+/// Returns the parameter's pattern, or records it as the rest element
+fn on_function_param(
+    node_graph: &JsValueGraph,
+    node: &JsASTNode,
+    rest: &mut Option<Box<JsPattern>>,
+) -> Option<JsPattern> {
+    let children = extract_interpreter_children(node, JsRule::FunctionParam);
+
+    match children.len() {
+        1 => Some(on_binding_target(node_graph, &children[0])),
+        2 => {
+            *rest = Some(Box::new(on_binding_target(node_graph, &children[1])));
+            None
+        }
+        3 => Some(JsPattern::Default(
+            Box::new(on_binding_target(node_graph, &children[0])),
+            Box::new(on_expression_assignment(node_graph, &children[2])),
+        )),
+        num => panic!("Unexpected number of children for function param: {num}"),
+    }
 }
 
 fn on_if_statement(node_graph: &JsValueGraph, node: &JsASTNode) -> JsStatement {
     let children = extract_interpreter_children(node, JsRule::IfStatement);
 
-    let conditional_expression = on_expression(&children[2]);
+    let conditional_expression = on_expression(node_graph, &children[2]);
 
     let execution_statements = match children.len() {
         6 => {
             let execution_node = &children[4];
             match execution_node.rule {
-                JsRule::Expression => vec![JsStatement::Expression(on_expression(execution_node))],
+                JsRule::Expression => vec![JsStatement::Expression(on_expression(
+                    node_graph,
+                    execution_node,
+                ))],
                 JsRule::Statement => vec![on_statement(node_graph, execution_node)],
                 _ => panic!(
                     "Unexpected if statement execution node: {}",
@@ -172,7 +535,10 @@ fn on_else_condition(node_graph: &JsValueGraph, node: &JsASTNode) -> Vec<JsState
         0 => vec![],
         2 => match children[1].rule {
             JsRule::Statement => vec![on_statement(node_graph, &children[1])],
-            JsRule::Expression => vec![JsStatement::Expression(on_expression(&children[1]))],
+            JsRule::Expression => vec![JsStatement::Expression(on_expression(
+                node_graph,
+                &children[1],
+            ))],
             _ => panic!("Unsupported rule type in else condition"),
         },
         4 => on_statements(node_graph, &children[2]),
@@ -180,91 +546,379 @@ fn on_else_condition(node_graph: &JsValueGraph, node: &JsASTNode) -> Vec<JsState
     }
 }
 
-fn on_expression(node: &JsASTNode) -> JsExpression {
-    let child = extract_interpreter_single_child(node, JsRule::Expression);
+fn on_switch_statement(node_graph: &JsValueGraph, node: &JsASTNode) -> JsStatement {
+    let children = extract_interpreter_n_children(node, JsRule::SwitchStatement, 7);
 
-    match child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(child),
-        JsRule::ExpressionEquality => on_expression_equality(child),
-        JsRule::ExpressionAdd => on_expression_add(child),
-        JsRule::ExpressionMultiply => on_expression_multiply(child),
-        JsRule::ExpressionConditional => on_expression_conditional(child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(child),
-        JsRule::LiteralValue => on_literal_value(child),
-        JsRule::DotAccess => on_dot_access(child),
-        rule => panic!("Unexpected rule: {rule}"),
+    let discriminant = on_expression(node_graph, &children[2]);
+    let cases = extract_interpreter_children(&children[5], JsRule::SwitchCases)
+        .iter()
+        .map(|case| {
+            let case_children = extract_interpreter_children(case, JsRule::SwitchCase);
+            match case_children.len() {
+                4 => (
+                    Some(on_expression(node_graph, &case_children[1])),
+                    on_statements(node_graph, &case_children[3]),
+                ),
+                3 => (None, on_statements(node_graph, &case_children[2])),
+                num => panic!("Unexpected number of children for switch case: {num}"),
+            }
+        })
+        .collect();
+    JsStatement::Switch(discriminant, cases)
+}
+
+fn on_expression(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::Expression, 2);
+
+    let first = on_expression_assignment(node_graph, &children[0]);
+    let operations = extract_interpreter_children(&children[1], JsRule::ExpressionCommaOperations);
+    if operations.is_empty() {
+        return first;
+    }
+
+    let mut expressions = vec![first];
+    for operation in operations {
+        let operation_children =
+            extract_interpreter_n_children(operation, JsRule::ExpressionCommaOperation, 2);
+        expressions.push(on_expression_assignment(node_graph, &operation_children[1]));
     }
+    JsExpression::Sequence(expressions)
 }
 
-fn on_expression_function_invoke(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_n_children(node, JsRule::ExpressionFunctionInvoke, 2);
+fn on_expression_assignment(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionAssignment, 2);
 
-    let first_child = &children[0];
-    let reference_to_invoke = match first_child.rule {
-        JsRule::DotAccess => on_dot_access(first_child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(first_child),
-        JsRule::LiteralValue => on_literal_value(first_child),
-        rule => panic!("Unexpected rule: {rule}"),
-    };
+    let target = on_expression_conditional(node_graph, &children[0]);
+    let tail_children = extract_interpreter_children(&children[1], JsRule::AssignmentTail);
+    match tail_children.len() {
+        0 => target,
+        2 => JsExpression::Assign(
+            Box::new(JsPattern::from(target)),
+            Box::new(on_expression_assignment(node_graph, &tail_children[1])),
+        ),
+        num => panic!("Unexpected number of children for assignment: {num}"),
+    }
+}
 
-    let arguments = on_function_invoke(&children[1]);
+fn on_expression_conditional(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionConditional, 2);
+
+    let condition = on_expression_bitwise_or(node_graph, &children[0]);
+    let tail_children = extract_interpreter_children(&children[1], JsRule::ConditionalTail);
+    match tail_children.len() {
+        0 => condition,
+        4 => JsExpression::Condition(
+            Box::new(condition),
+            Box::new(on_expression_assignment(node_graph, &tail_children[1])),
+            Box::new(on_expression_assignment(node_graph, &tail_children[3])),
+        ),
+        num => panic!("Unexpected number of children for conditional: {num}"),
+    }
+}
 
-    JsExpression::InvokeFunction(Box::new(reference_to_invoke), arguments)
+/// Folds left-associative operations, where each operation is an operator node followed by an
+/// operand
+fn on_binary_operations(
+    node_graph: &JsValueGraph,
+    node: &JsASTNode,
+    rules: (JsRule, JsRule, JsRule),
+    on_operand: fn(&JsValueGraph, &JsASTNode) -> JsExpression,
+) -> JsExpression {
+    let (rule, operations_rule, operation_rule) = rules;
+    let children = extract_interpreter_n_children(node, rule, 2);
+
+    let operations = extract_interpreter_children(&children[1], operations_rule);
+    operations
+        .iter()
+        .fold(on_operand(node_graph, &children[0]), |a, operation| {
+            let operation_children = extract_interpreter_n_children(operation, operation_rule, 2);
+            let operator = &operation_children[0];
+            let operator = extract_interpreter_token(operator, operator.rule);
+            let operator = JsBinaryOperator::from_token(&operator)
+                .unwrap_or_else(|| panic!("Invalid binary operator found: {operator}"));
+            let b = on_operand(node_graph, &operation_children[1]);
+            JsExpression::Binary(operator, Box::new(a), Box::new(b))
+        })
 }
 
-fn on_function_invoke(node: &JsASTNode) -> Vec<JsExpression> {
-    let children = extract_interpreter_n_children(node, JsRule::FunctionInvoke, 3);
+fn on_expression_bitwise_or(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionBitwiseOr,
+            JsRule::ExpressionBitwiseOrOperations,
+            JsRule::ExpressionBitwiseOrOperation,
+        ),
+        on_expression_bitwise_xor,
+    )
+}
 
-    on_function_arguments(&children[1])
+fn on_expression_bitwise_xor(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionBitwiseXor,
+            JsRule::ExpressionBitwiseXorOperations,
+            JsRule::ExpressionBitwiseXorOperation,
+        ),
+        on_expression_bitwise_and,
+    )
 }
 
-fn on_function_arguments(node: &JsASTNode) -> Vec<JsExpression> {
-    let children = extract_interpreter_children(node, JsRule::FunctionArguments);
+fn on_expression_bitwise_and(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionBitwiseAnd,
+            JsRule::ExpressionBitwiseAndOperations,
+            JsRule::ExpressionBitwiseAndOperation,
+        ),
+        on_expression_equality,
+    )
+}
 
-    let mut ret = vec![];
+fn on_expression_equality(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionEquality, 2);
+
+    let operations =
+        extract_interpreter_children(&children[1], JsRule::ExpressionEqualityOperations);
+    operations.iter().fold(
+        on_expression_relational(node_graph, &children[0]),
+        |a, operation| {
+            let operation_children =
+                extract_interpreter_n_children(operation, JsRule::ExpressionEqualityOperation, 2);
+            let a = Box::new(a);
+            let b = Box::new(on_expression_relational(node_graph, &operation_children[1]));
+            match on_equality_operator(&operation_children[0]).as_str() {
+                "==" => JsExpression::DoubleEquals(true, a, b),
+                "!=" => JsExpression::DoubleEquals(false, a, b),
+                "===" => JsExpression::TripleEquals(true, a, b),
+                "!==" => JsExpression::TripleEquals(false, a, b),
+                operator => panic!("Invalid equality operator found: {operator}"),
+            }
+        },
+    )
+}
 
-    if !children.is_empty() {
-        ret.push(on_expression(&children[0]));
+fn on_equality_operator(node: &JsASTNode) -> String {
+    extract_interpreter_token(node, JsRule::OperatorEquality)
+}
+
+fn on_expression_relational(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionRelational,
+            JsRule::ExpressionRelationalOperations,
+            JsRule::ExpressionRelationalOperation,
+        ),
+        on_expression_shift,
+    )
+}
+
+fn on_expression_shift(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionShift,
+            JsRule::ExpressionShiftOperations,
+            JsRule::ExpressionShiftOperation,
+        ),
+        on_expression_add,
+    )
+}
+
+fn on_expression_add(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionAdd, 2);
+
+    let operations = extract_interpreter_children(&children[1], JsRule::ExpressionAddOperations);
+    operations.iter().fold(
+        on_expression_multiply(node_graph, &children[0]),
+        |a, operation| {
+            let operation_children =
+                extract_interpreter_n_children(operation, JsRule::ExpressionAddOperation, 2);
+            let a = Box::new(a);
+            let b = Box::new(on_expression_multiply(node_graph, &operation_children[1]));
+            match on_add_operator(&operation_children[0]).as_str() {
+                "+" => JsExpression::Add(a, b),
+                "-" => JsExpression::Binary(JsBinaryOperator::Subtract, a, b),
+                operator => panic!("Invalid add operator found: {operator}"),
+            }
+        },
+    )
+}
+
+fn on_add_operator(node: &JsASTNode) -> String {
+    extract_interpreter_token(node, JsRule::OperatorAdd)
+}
+
+fn on_expression_multiply(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    on_binary_operations(
+        node_graph,
+        node,
+        (
+            JsRule::ExpressionMultiply,
+            JsRule::ExpressionMultiplyOperations,
+            JsRule::ExpressionMultiplyOperation,
+        ),
+        on_expression_exponent,
+    )
+}
+
+fn on_expression_exponent(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionExponent, 2);
+
+    let base = on_expression_unary(node_graph, &children[0]);
+    let tail_children = extract_interpreter_children(&children[1], JsRule::ExponentTail);
+    match tail_children.len() {
+        0 => base,
+        2 => JsExpression::Binary(
+            JsBinaryOperator::Exponent,
+            Box::new(base),
+            Box::new(on_expression_exponent(node_graph, &tail_children[1])),
+        ),
+        num => panic!("Unexpected number of children for exponentiation: {num}"),
     }
+}
 
-    if children.len() == 3 {
-        ret.extend(on_function_arguments(&children[2]))
+fn on_expression_unary(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_children(node, JsRule::ExpressionUnary);
+
+    match children.len() {
+        1 => on_expression_member(node_graph, &children[0]),
+        2 => {
+            let operand = Box::new(on_expression_unary(node_graph, &children[1]));
+            match children[0].rule {
+                JsRule::OperatorBitwiseNot => JsExpression::BitwiseNot(operand),
+                _ => match on_add_operator(&children[0]).as_str() {
+                    "-" => JsExpression::Negate(operand),
+                    _ => JsExpression::CastToNumber(operand),
+                },
+            }
+        }
+        num => panic!("Unexpected number of children for unary expression: {num}"),
     }
+}
 
-    ret
+fn on_expression_member(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ExpressionMember, 2);
+
+    let base = match children[0].rule {
+        JsRule::NewExpression => on_new_expression(node_graph, &children[0]),
+        _ => on_primary_expression(node_graph, &children[0]),
+    };
+    let accesses = extract_interpreter_children(&children[1], JsRule::MemberAccesses);
+    accesses.iter().fold(base, |base, access| {
+        let access = extract_interpreter_single_child(access, JsRule::MemberAccess);
+        on_member_access(node_graph, base, access)
+    })
 }
 
-fn on_expression_conditional(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_n_children(node, JsRule::ExpressionConditional, 5);
+fn on_new_expression(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_children(node, JsRule::NewExpression);
 
-    let case_sub_conditional = &children[0];
-    let true_condition_expression = &children[2];
-    let false_condition_expression_sub_conditional = &children[4];
+    let accesses = extract_interpreter_children(&children[2], JsRule::NewTargetAccesses);
+    let constructor = accesses.iter().fold(
+        on_primary_expression(node_graph, &children[1]),
+        |base, access| {
+            let access = extract_interpreter_single_child(access, JsRule::NewTargetAccess);
+            on_member_access(node_graph, base, access)
+        },
+    );
+    let args = match children.len() {
+        3 => vec![],
+        4 => on_function_invoke(node_graph, &children[3]),
+        num => panic!("Unexpected number of children for new expression: {num}"),
+    };
+    JsExpression::New(Box::new(constructor), args)
+}
 
-    JsExpression::Condition(
-        Box::new(on_expression_sub_conditional(case_sub_conditional)),
-        Box::new(on_expression(true_condition_expression)),
-        Box::new(on_expression_sub_conditional(
-            false_condition_expression_sub_conditional,
-        )),
-    )
+fn on_member_access(
+    node_graph: &JsValueGraph,
+    base: JsExpression,
+    access: &JsASTNode,
+) -> JsExpression {
+    match access.rule {
+        JsRule::DotAccess => {
+            let access_children = extract_interpreter_n_children(access, JsRule::DotAccess, 2);
+            JsExpression::AccessMember(Box::new(base), on_variable_name(&access_children[1]))
+        }
+        JsRule::ComputedMemberAccess => {
+            let access_children =
+                extract_interpreter_n_children(access, JsRule::ComputedMemberAccess, 3);
+            JsExpression::AccessComputedMember(
+                Box::new(base),
+                Box::new(on_expression(node_graph, &access_children[1])),
+            )
+        }
+        JsRule::FunctionInvoke => {
+            JsExpression::InvokeFunction(Box::new(base), on_function_invoke(node_graph, access))
+        }
+        rule => panic!("Unexpected member access: {rule}"),
+    }
 }
 
-fn on_expression_sub_conditional(node: &JsASTNode) -> JsExpression {
-    let child = extract_interpreter_single_child(node, JsRule::ExpressionSubConditional);
+fn on_primary_expression(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let child = extract_interpreter_single_child(node, JsRule::PrimaryExpression);
 
     match child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(child),
-        JsRule::ExpressionEquality => on_expression_equality(child),
-        JsRule::ExpressionAdd => on_expression_add(child),
-        JsRule::ExpressionMultiply => on_expression_multiply(child),
+        JsRule::ImportCall => {
+            let children = extract_interpreter_n_children(child, JsRule::ImportCall, 4);
+            JsExpression::Import(Box::new(on_expression_assignment(node_graph, &children[2])))
+        }
         JsRule::RightHandSideVariable => on_right_hand_side_variable(child),
-        JsRule::LiteralValue => on_literal_value(child),
+        JsRule::LiteralValue => on_literal_value(node_graph, child),
+        JsRule::ParenthesizedExpression => {
+            let children =
+                extract_interpreter_n_children(child, JsRule::ParenthesizedExpression, 3);
+            on_expression(node_graph, &children[1])
+        }
         rule => panic!("Unexpected rule: {rule}"),
     }
 }
 
-fn on_literal_value(node: &JsASTNode) -> JsExpression {
+fn on_function_invoke(node_graph: &JsValueGraph, node: &JsASTNode) -> Vec<JsExpression> {
+    let children = extract_interpreter_n_children(node, JsRule::FunctionInvoke, 3);
+
+    on_function_arguments(node_graph, &children[1])
+}
+
+fn on_function_arguments(node_graph: &JsValueGraph, node: &JsASTNode) -> Vec<JsExpression> {
+    let children = extract_interpreter_children(node, JsRule::FunctionArguments);
+
+    let mut ret = vec![];
+    if children.is_empty() {
+        return ret;
+    }
+
+    ret.push(on_function_argument(node_graph, &children[0]));
+    let mut tail = &children[1];
+    loop {
+        let tail_children = extract_interpreter_children(tail, JsRule::FunctionArgumentsTail);
+        if tail_children.len() != 3 {
+            return ret;
+        }
+        ret.push(on_function_argument(node_graph, &tail_children[1]));
+        tail = &tail_children[2];
+    }
+}
+
+fn on_function_argument(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_children(node, JsRule::FunctionArgument);
+
+    match children.len() {
+        1 => on_expression_assignment(node_graph, &children[0]),
+        2 => JsExpression::Spread(Box::new(on_expression_assignment(node_graph, &children[1]))),
+        num => panic!("Unexpected number of children for function argument: {num}"),
+    }
+}
+
+fn on_literal_value(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
     let child = extract_interpreter_single_child(node, JsRule::LiteralValue);
 
     match child.rule {
@@ -272,10 +926,13 @@ fn on_literal_value(node: &JsASTNode) -> JsExpression {
         JsRule::FalseKeyword => JsExpression::Boolean(false),
         JsRule::NullKeyword => JsExpression::Null,
         JsRule::Number => on_number(child),
+        JsRule::BigInt => on_bigint(child),
         JsRule::String => on_string(child),
+        JsRule::RegExp => on_regexp(child),
         JsRule::Undefined => JsExpression::Undefined,
         JsRule::NaNKeyword => JsExpression::Number(f64::NAN),
-        JsRule::ObjectLiteral => on_object_literal(child),
+        JsRule::ObjectLiteral => on_object_literal(node_graph, child),
+        JsRule::ArrayLiteral => on_array_literal(node_graph, child),
         rule => panic!("Unexpected rule: {rule}"),
     }
 }
@@ -287,196 +944,133 @@ fn on_number(node: &JsASTNode) -> JsExpression {
     JsExpression::Number(number_value)
 }
 
+/// BigInt literals are decimal integers suffixed with `n`
+fn on_bigint(node: &JsASTNode) -> JsExpression {
+    let token = extract_interpreter_token(node, JsRule::BigInt);
+    let digits = token.trim_end_matches('n').replace('_', "");
+    JsExpression::BigInt(JsBigInt::parse(&digits).unwrap())
+}
+
 fn on_string(node: &JsASTNode) -> JsExpression {
     JsExpression::String(on_string_literal(node))
 }
 
+/// Splits a regular expression literal into its pattern and flags
+fn on_regexp(node: &JsASTNode) -> JsExpression {
+    let token = extract_interpreter_token(node, JsRule::RegExp);
+    let end = token.rfind('/').unwrap();
+    JsExpression::RegExp(token[1..end].to_string(), token[end + 1..].to_string())
+}
+
 fn on_string_literal(node: &JsASTNode) -> String {
     let token = extract_interpreter_token(node, JsRule::String);
     token[1..token.len() - 1].to_string()
 }
 
-fn on_object_literal(node: &JsASTNode) -> JsExpression {
+fn on_object_literal(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
     let children = extract_interpreter_n_children(node, JsRule::ObjectLiteral, 3);
-    let object_members = on_object_members(&children[1]);
+    let object_members = on_object_members(node_graph, &children[1]);
     JsExpression::Object(object_members)
 }
 
-fn on_object_members(node: &JsASTNode) -> Vec<(String, JsExpression)> {
+fn on_object_members(node_graph: &JsValueGraph, node: &JsASTNode) -> Vec<JsObjectMember> {
     let children = extract_interpreter_children(node, JsRule::ObjectMembers);
+
+    let mut ret = vec![];
     if children.is_empty() {
-        return vec![];
+        return ret;
     }
 
-    let key = on_string_literal(&children[0]);
-    let value = on_expression(&children[2]);
-
-    let mut ret = vec![(key, value)];
-
-    if children.len() == 5 {
-        ret.extend(on_object_members(&children[4]));
+    ret.push(on_object_member(node_graph, &children[0]));
+    let mut tail = &children[1];
+    loop {
+        let tail_children = extract_interpreter_children(tail, JsRule::ObjectMembersTail);
+        if tail_children.len() != 3 {
+            return ret;
+        }
+        ret.push(on_object_member(node_graph, &tail_children[1]));
+        tail = &tail_children[2];
     }
-
-    ret
 }
 
-fn on_expression_add(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_children(node, JsRule::ExpressionAdd);
+fn on_object_member(node_graph: &JsValueGraph, node: &JsASTNode) -> JsObjectMember {
+    let children = extract_interpreter_children(node, JsRule::ObjectMember);
 
     let first_child = &children[0];
-
     match first_child.rule {
-        JsRule::ExpressionFunctionInvoke => {
-            let a = on_expression_function_invoke(&children[0]);
-            let b = on_expression_sub_add(&children[2]);
-            JsExpression::Add(Box::new(a), Box::new(b))
-        }
-        JsRule::ExpressionMultiply => {
-            let a = on_expression_multiply(&children[0]);
-            let b = on_expression_sub_add(&children[2]);
-            JsExpression::Add(Box::new(a), Box::new(b))
+        JsRule::Spread => {
+            JsObjectMember::Spread(on_expression_assignment(node_graph, &children[1]))
         }
-        JsRule::RightHandSideVariable => {
-            let a = on_right_hand_side_variable(&children[0]);
-            let b = on_expression_sub_add(&children[2]);
-            JsExpression::Add(Box::new(a), Box::new(b))
-        }
-        JsRule::LiteralValue => {
-            let a = on_literal_value(&children[0]);
-            let b = on_expression_sub_add(&children[2]);
-            JsExpression::Add(Box::new(a), Box::new(b))
+        JsRule::PropertyName => JsObjectMember::Property(
+            JsPropertyName::Static(on_property_name(first_child)),
+            on_expression_assignment(node_graph, &children[2]),
+        ),
+        JsRule::MethodDefinition => {
+            JsObjectMember::Method(on_method_definition(node_graph, first_child))
         }
-        JsRule::OperatorAdd => {
-            let literal_value_expression = on_literal_value(&children[1]);
-            JsExpression::CastToNumber(Box::new(literal_value_expression))
+        JsRule::OpenBracket => JsObjectMember::Property(
+            JsPropertyName::Computed(on_expression(node_graph, &children[1])),
+            on_expression_assignment(node_graph, &children[4]),
+        ),
+        JsRule::VariableName => {
+            let name = on_variable_name(first_child);
+            let value = if children.len() == 3 {
+                JsExpression::Assign(
+                    Box::new(JsPattern::Identifier(name.clone())),
+                    Box::new(on_expression_assignment(node_graph, &children[2])),
+                )
+            } else {
+                JsExpression::Reference(name.clone())
+            };
+            JsObjectMember::Property(JsPropertyName::Static(name), value)
         }
-        _ => panic!("Invalid first type type"),
-    }
-}
-
-fn on_expression_sub_add(node: &JsASTNode) -> JsExpression {
-    let child = extract_interpreter_single_child(node, JsRule::ExpressionSubAdd);
-
-    match child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(child),
-        JsRule::ExpressionMultiply => on_expression_multiply(child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(child),
-        JsRule::LiteralValue => on_literal_value(child),
-        JsRule::ExpressionAdd => on_expression_add(child),
-        rule => panic!("Invalid first child rule: {rule}"),
-    }
-}
-
-fn on_expression_multiply(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_n_children(node, JsRule::ExpressionMultiply, 3);
-
-    let first_child = &children[0];
-    let a = match first_child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(first_child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(first_child),
-        JsRule::LiteralValue => on_literal_value(first_child),
-        rule => panic!("Invalid first child rule: {rule}"),
-    };
-    let b = on_expression_sub_multiply(&children[2]);
-    JsExpression::Multiply(Box::new(a), Box::new(b))
-}
-
-fn on_expression_sub_multiply(node: &JsASTNode) -> JsExpression {
-    let child = extract_interpreter_single_child(node, JsRule::ExpressionSubMultiply);
-
-    match child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(child),
-        JsRule::ExpressionMultiply => on_expression_multiply(child),
-        JsRule::LiteralValue => on_literal_value(child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(child),
-        rule => panic!("Invalid child type {rule}"),
-    }
-}
-
-fn on_expression_equality(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_n_children(node, JsRule::ExpressionEquality, 3);
-
-    let first_child = &children[0];
-    let a = Box::new(match first_child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(first_child),
-        JsRule::ExpressionAdd => on_expression_add(first_child),
-        JsRule::ExpressionMultiply => on_expression_multiply(first_child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(first_child),
-        JsRule::LiteralValue => on_literal_value(first_child),
-        rule => panic!("Invalid first child rule: {rule}"),
-    });
-    let b = Box::new(on_expression_sub_equality(&children[2]));
-
-    match on_equality_operator(&children[1]).as_str() {
-        "==" => JsExpression::DoubleEquals(true, a, b),
-        "!=" => JsExpression::DoubleEquals(false, a, b),
-        "===" => JsExpression::TripleEquals(true, a, b),
-        "!==" => JsExpression::TripleEquals(false, a, b),
-        operator => panic!("Invalid equality operator found: {operator}"),
+        rule => panic!("Unexpected object member: {rule}"),
     }
 }
 
-fn on_expression_sub_equality(node: &JsASTNode) -> JsExpression {
-    let child = extract_interpreter_single_child(node, JsRule::ExpressionSubEquality);
+fn on_property_name(node: &JsASTNode) -> String {
+    let child = extract_interpreter_single_child(node, JsRule::PropertyName);
 
     match child.rule {
-        JsRule::ExpressionFunctionInvoke => on_expression_function_invoke(child),
-        JsRule::ExpressionEquality => on_expression_equality(child),
-        JsRule::ExpressionAdd => on_expression_add(child),
-        JsRule::ExpressionMultiply => on_expression_multiply(child),
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(child),
-        JsRule::LiteralValue => on_literal_value(child),
-        rule => panic!("Invalid child type {rule}"),
+        JsRule::String => on_string_literal(child),
+        JsRule::VariableName => on_variable_name(child),
+        rule => panic!("Unexpected property name: {rule}"),
     }
 }
 
-fn on_equality_operator(node: &JsASTNode) -> String {
-    extract_interpreter_token(node, JsRule::OperatorEquality)
-}
+fn on_array_literal(node_graph: &JsValueGraph, node: &JsASTNode) -> JsExpression {
+    let children = extract_interpreter_n_children(node, JsRule::ArrayLiteral, 3);
+    let element_children = extract_interpreter_n_children(&children[1], JsRule::ArrayElements, 2);
 
-fn on_dot_access(node: &JsASTNode) -> JsExpression {
-    let children = extract_interpreter_n_children(node, JsRule::DotAccess, 3);
-
-    let first_child = &children[0];
-    let base_value = match first_child.rule {
-        JsRule::RightHandSideVariable => on_right_hand_side_variable(first_child),
-        JsRule::LiteralValue => on_literal_value(first_child),
-        rule => panic!("Invalid child type {rule}"),
-    };
-
-    let access_child = &children[2];
-    match access_child.rule {
-        JsRule::VariableName => {
-            JsExpression::AccessMember(Box::new(base_value), on_variable_name(access_child))
+    let mut elements = vec![on_optional_array_element(node_graph, &element_children[0])];
+    let mut tail = &element_children[1];
+    loop {
+        let tail_children = extract_interpreter_children(tail, JsRule::ArrayElementsTail);
+        if tail_children.is_empty() {
+            break;
         }
-        JsRule::DotAccess => {
-            let reversed_access_chain = recurse_dot_access(node);
+        elements.push(on_optional_array_element(node_graph, &tail_children[1]));
+        tail = &tail_children[2];
+    }
 
-            let mut prev = base_value;
-            for link in reversed_access_chain.iter().rev() {
-                prev = JsExpression::AccessMember(Box::new(prev), link.to_string());
-            }
-            prev
-        }
-        rule => panic!("Invalid child type {rule}"),
+    // A trailing comma does not add a hole
+    if let Some(None) = elements.last() {
+        elements.pop();
     }
+    JsExpression::Array(elements)
 }
 
-fn recurse_dot_access(node: &JsASTNode) -> Vec<String> {
-    let children = extract_interpreter_n_children(node, JsRule::DotAccess, 3);
-
-    let access_child = &children[2];
-    match access_child.rule {
-        JsRule::VariableName => vec![on_variable_name(access_child)],
-        JsRule::DotAccess => {
-            let mut ret = recurse_dot_access(access_child);
+fn on_optional_array_element(node_graph: &JsValueGraph, node: &JsASTNode) -> Option<JsExpression> {
+    let children = extract_interpreter_children(node, JsRule::OptionalArrayElement);
 
-            let first_child = &access_child.children[0];
-            assert_eq!(first_child.children[0].rule, JsRule::VariableName);
-            ret.push(on_variable_name(&first_child.children[0]));
-            ret
-        }
-        rule => panic!("Invalid child type {rule}"),
+    match children.len() {
+        0 => None,
+        1 => Some(on_expression_assignment(node_graph, &children[0])),
+        2 => Some(JsExpression::Spread(Box::new(on_expression_assignment(
+            node_graph,
+            &children[1],
+        )))),
+        num => panic!("Unexpected number of children for array element: {num}"),
     }
 }
 
@@ -486,39 +1080,7 @@ impl Interpreter<'_, JsRule> for JsInterpreter {
     fn on_node(&self, document: &JsASTNode) -> Option<JsDocument> {
         let mut js_document = JsDocument::new(vec![]);
         let node_graph = &js_document.global_closure_context.nodes_graph;
-
-        let children = extract_interpreter_children(document, JsRule::Document);
-
-        let first_child = &children[0];
-
-        let statements = match first_child.rule {
-            JsRule::Terminator => vec![],
-            JsRule::Expression => vec![JsStatement::Expression(on_expression(first_child))],
-            JsRule::VarDeclaration => {
-                vec![on_var_declaration(node_graph, first_child)]
-            }
-            JsRule::VariableAssignment => vec![on_variable_assignment(node_graph, first_child)],
-            JsRule::Statements => {
-                let mut statements = on_statements(node_graph, first_child);
-                let second_child = &children[1];
-                match second_child.rule {
-                    JsRule::Expression => {
-                        statements.push(JsStatement::Expression(on_expression(second_child)))
-                    }
-                    JsRule::VarDeclaration => {
-                        statements.push(on_var_declaration(node_graph, second_child))
-                    }
-                    JsRule::VariableAssignment => {
-                        statements.push(on_variable_assignment(node_graph, second_child))
-                    }
-                    _ => {}
-                };
-                statements
-            }
-            rule => panic!("Unspported first rule: {rule}"),
-        };
-
-        js_document.statements = statements;
+        js_document.statements = Self::interpret_statements(node_graph, document);
 
         Some(js_document)
     }
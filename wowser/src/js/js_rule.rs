@@ -4,58 +4,152 @@ use wowser_macros::DisplayFromDebug;
 #[derive(Clone, Copy, Debug, DisplayFromDebug, PartialEq, Eq, Hash)]
 pub enum JsRule {
     Document,
+    DocumentEnd,
     Statements,
     Statement,
+    StatementEnd,
     IfKeyword,
     IfStatement,
     ElseKeyword,
     ElseStatement,
+    SwitchStatement,
+    SwitchKeyword,
+    SwitchCases,
+    SwitchCase,
+    CaseKeyword,
+    DefaultKeyword,
+    BreakKeyword,
     VarDeclaration,
+    DeclarationKeyword,
     VarKeyword,
+    LetKeyword,
+    ConstKeyword,
+    BindingTarget,
     ThisKeyword,
     FunctionDeclaration,
     FunctionKeyword,
+    NewKeyword,
+    ClassDeclaration,
+    ClassKeyword,
+    ClassMembers,
+    ClassMember,
+    StaticKeyword,
+    MethodDefinition,
+    AccessorKeyword,
+    MemberName,
     FunctionParams,
+    FunctionParamsTail,
+    FunctionParam,
     ReturnKeyword,
     ThrowKeyword,
+    ImportDeclaration,
+    ImportKeyword,
+    ImportClause,
+    NamespaceImport,
+    ExportDeclaration,
+    ExportKeyword,
+    ExportDefaultKeyword,
+    ModuleSpecifier,
+    NamedBindings,
+    NamedBindingsList,
+    NamedBindingsTail,
+    NamedBinding,
+    ModuleExportName,
+    FromKeyword,
+    AsKeyword,
+    ImportCall,
     ObjectLiteral,
     ObjectMembers,
+    ObjectMembersTail,
+    ObjectMember,
+    PropertyName,
+    ArrayLiteral,
+    ArrayElements,
+    ArrayElementsTail,
+    OptionalArrayElement,
     TrueKeyword,
     FalseKeyword,
     NullKeyword,
     RightHandSideVariable,
     VariableName,
-    VariableAssignment,
     Expression,
+    ExpressionCommaOperations,
+    ExpressionCommaOperation,
+    ExpressionAssignment,
+    AssignmentTail,
+    ExpressionConditional,
+    ConditionalTail,
+    ExpressionBitwiseOr,
+    ExpressionBitwiseOrOperations,
+    ExpressionBitwiseOrOperation,
+    ExpressionBitwiseXor,
+    ExpressionBitwiseXorOperations,
+    ExpressionBitwiseXorOperation,
+    ExpressionBitwiseAnd,
+    ExpressionBitwiseAndOperations,
+    ExpressionBitwiseAndOperation,
+    ExpressionEquality,
+    ExpressionEqualityOperations,
+    ExpressionEqualityOperation,
+    ExpressionRelational,
+    ExpressionRelationalOperations,
+    ExpressionRelationalOperation,
+    ExpressionShift,
+    ExpressionShiftOperations,
+    ExpressionShiftOperation,
     ExpressionAdd,
-    ExpressionSubAdd,
+    ExpressionAddOperations,
+    ExpressionAddOperation,
     ExpressionMultiply,
-    ExpressionSubMultiply,
-    ExpressionEquality,
-    ExpressionSubEquality,
-    ExpressionFunctionInvoke,
+    ExpressionMultiplyOperations,
+    ExpressionMultiplyOperation,
+    ExpressionExponent,
+    ExponentTail,
+    ExpressionUnary,
+    ExpressionMember,
+    NewExpression,
+    NewTargetAccesses,
+    NewTargetAccess,
+    MemberAccesses,
+    MemberAccess,
+    DotAccess,
+    ComputedMemberAccess,
     FunctionInvoke,
     FunctionArguments,
-    ExpressionConditional,
-    ExpressionSubConditional,
+    FunctionArgumentsTail,
+    FunctionArgument,
+    PrimaryExpression,
+    ParenthesizedExpression,
     OperatorAdd,
     OperatorMultiply,
+    OperatorExponent,
+    OperatorShift,
+    OperatorRelational,
+    OperatorBitwiseAnd,
+    OperatorBitwiseXor,
+    OperatorBitwiseOr,
+    OperatorBitwiseNot,
     OperatorEquals,
     OperatorEquality,
-    DotAccess,
     OpenParen,
     CloseParen,
     OpenCurlyBrace,
     CloseCurlyBrace,
+    OpenBracket,
+    CloseBracket,
+    Spread,
     Dot,
     Comma,
     LiteralValue,
     Number,
+    BigInt,
     String,
+    RegExp,
     Undefined,
     NaNKeyword,
     Colon,
     Semicolon,
+    AutomaticSemicolon,
     QuestionMark,
     Terminator,
 }
@@ -69,32 +163,66 @@ impl Rule for JsRule {
     fn children(&self) -> Vec<RuleType<Self>> {
         match self {
             Self::Document => vec![
-                RuleType::Sequence(vec![Self::Statements, Self::VarDeclaration, Self::Terminator]),
-                RuleType::Sequence(vec![Self::Statements, Self::Expression, Self::Terminator]),
-                RuleType::Sequence(vec![Self::Statements, Self::VariableAssignment, Self::Terminator]),
-                RuleType::Sequence(vec![Self::Statements, Self::Terminator]),
+                RuleType::Sequence(vec![Self::Statements, Self::DocumentEnd]),
+            ],
+            Self::DocumentEnd => vec![
                 RuleType::Rule(Self::Terminator),
             ],
             Self::Statements => vec![
                 RuleType::RepeatableRule(Self::Statement),
             ],
             Self::Statement => vec![
+                RuleType::Rule(Self::ImportDeclaration),
+                RuleType::Rule(Self::ExportDeclaration),
                 RuleType::Rule(Self::FunctionDeclaration),
+                RuleType::Rule(Self::ClassDeclaration),
                 RuleType::Rule(Self::IfStatement),
-                RuleType::Sequence(vec![Self::ReturnKeyword, Self::Expression, Self::Semicolon]),
-                RuleType::Sequence(vec![Self::ThrowKeyword, Self::Expression, Self::Semicolon]),
-                RuleType::Sequence(vec![Self::VarDeclaration, Self::Semicolon]),
-                RuleType::Sequence(vec![Self::Expression, Self::Semicolon]),
-                RuleType::Sequence(vec![Self::VariableAssignment, Self::Semicolon]),
+                RuleType::Rule(Self::SwitchStatement),
+                RuleType::Sequence(vec![Self::ReturnKeyword, Self::Expression, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::ReturnKeyword, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::ThrowKeyword, Self::Expression, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::BreakKeyword, Self::VariableName, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::BreakKeyword, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::VarDeclaration, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::VariableName, Self::Colon, Self::Statement]),
+                RuleType::Sequence(vec![Self::Expression, Self::StatementEnd]),
+                // Tried after expressions, so braces starting a statement are an object literal
+                // when they can be one
+                RuleType::Sequence(vec![Self::OpenCurlyBrace, Self::Statements, Self::CloseCurlyBrace]),
                 RuleType::Sequence(vec![Self::Semicolon]),
+                RuleType::Sequence(vec![Self::AutomaticSemicolon]),
+            ],
+            Self::StatementEnd => vec![
+                RuleType::Rule(Self::Semicolon),
+                RuleType::Rule(Self::AutomaticSemicolon),
+                // Semicolons may be left out before a closing brace or the end of the script. That
+                // isn't checked here, but the lexer rejects most other tokens that could follow on
+                // the same line.
+                RuleType::Sequence(vec![]),
             ],
             Self::VarDeclaration => vec![
-                RuleType::Sequence(vec![Self::VarKeyword, Self::VariableName, Self::OperatorEquals, Self::Expression]),
-                RuleType::Sequence(vec![Self::VarKeyword, Self::VariableName]),
+                RuleType::Sequence(vec![Self::DeclarationKeyword, Self::BindingTarget, Self::OperatorEquals, Self::ExpressionAssignment]),
+                RuleType::Sequence(vec![Self::DeclarationKeyword, Self::VariableName]),
+            ],
+            Self::DeclarationKeyword => vec![
+                RuleType::Rule(Self::VarKeyword),
+                RuleType::Rule(Self::LetKeyword),
+                RuleType::Rule(Self::ConstKeyword),
             ],
             Self::VarKeyword => vec![
                 RuleType::Token(JsToken::VarKeyword),
             ],
+            Self::LetKeyword => vec![
+                RuleType::Token(JsToken::LetKeyword),
+            ],
+            Self::ConstKeyword => vec![
+                RuleType::Token(JsToken::ConstKeyword),
+            ],
+            Self::BindingTarget => vec![
+                RuleType::Rule(Self::VariableName),
+                RuleType::Rule(Self::ArrayLiteral),
+                RuleType::Rule(Self::ObjectLiteral),
+            ],
             Self::ThisKeyword => vec![
                 RuleType::Token(JsToken::ThisKeyword),
             ],
@@ -113,25 +241,218 @@ impl Rule for JsRule {
             Self::FunctionKeyword => vec![
                 RuleType::Token(JsToken::FunctionKeyword),
             ],
+            Self::NewKeyword => vec![
+                RuleType::Token(JsToken::NewKeyword),
+            ],
+            Self::ClassDeclaration => vec![
+                RuleType::Sequence(vec![
+                    Self::ClassKeyword,
+                    Self::VariableName,
+                    Self::OpenCurlyBrace,
+                    Self::ClassMembers,
+                    Self::CloseCurlyBrace,
+                ]),
+            ],
+            Self::ClassKeyword => vec![
+                RuleType::Token(JsToken::ClassKeyword),
+            ],
+            Self::ClassMembers => vec![
+                RuleType::RepeatableRule(Self::ClassMember),
+            ],
+            Self::ClassMember => vec![
+                RuleType::Sequence(vec![Self::StaticKeyword, Self::MethodDefinition]),
+                RuleType::Rule(Self::MethodDefinition),
+                RuleType::Rule(Self::Semicolon),
+            ],
+            Self::StaticKeyword => vec![
+                RuleType::Token(JsToken::StaticKeyword),
+            ],
+            Self::MethodDefinition => vec![
+                RuleType::Sequence(vec![
+                    Self::AccessorKeyword,
+                    Self::MemberName,
+                    Self::OpenParen,
+                    Self::FunctionParams,
+                    Self::CloseParen,
+                    Self::OpenCurlyBrace,
+                    Self::Statements,
+                    Self::CloseCurlyBrace,
+                ]),
+                RuleType::Sequence(vec![
+                    Self::MemberName,
+                    Self::OpenParen,
+                    Self::FunctionParams,
+                    Self::CloseParen,
+                    Self::OpenCurlyBrace,
+                    Self::Statements,
+                    Self::CloseCurlyBrace,
+                ]),
+            ],
+            Self::AccessorKeyword => vec![
+                RuleType::Token(JsToken::AccessorKeyword),
+            ],
+            Self::MemberName => vec![
+                RuleType::Rule(Self::PropertyName),
+                RuleType::Sequence(vec![Self::OpenBracket, Self::Expression, Self::CloseBracket]),
+            ],
             Self::FunctionParams => vec![
-                RuleType::Sequence(vec![Self::VariableName, Self::Comma, Self::FunctionParams]),
-                RuleType::Sequence(vec![Self::VariableName, Self::Comma]),
-                RuleType::Rule(Self::VariableName),
+                RuleType::Sequence(vec![Self::FunctionParam, Self::FunctionParamsTail]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::FunctionParamsTail => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::FunctionParam, Self::FunctionParamsTail]),
+                RuleType::Sequence(vec![Self::Comma]),
                 RuleType::Sequence(vec![]),
             ],
+            Self::FunctionParam => vec![
+                RuleType::Sequence(vec![Self::Spread, Self::BindingTarget]),
+                RuleType::Sequence(vec![Self::BindingTarget, Self::OperatorEquals, Self::ExpressionAssignment]),
+                RuleType::Rule(Self::BindingTarget),
+            ],
             Self::ReturnKeyword => vec![
                 RuleType::Token(JsToken::ReturnKeyword),
             ],
             Self::ThrowKeyword => vec![
                 RuleType::Token(JsToken::ThrowKeyword),
             ],
+            // Only valid at the top level of a module, which is checked when the module is run
+            Self::ImportDeclaration => vec![
+                RuleType::Sequence(vec![Self::ImportKeyword, Self::ModuleSpecifier, Self::StatementEnd]),
+                RuleType::Sequence(vec![
+                    Self::ImportKeyword,
+                    Self::ImportClause,
+                    Self::FromKeyword,
+                    Self::ModuleSpecifier,
+                    Self::StatementEnd,
+                ]),
+            ],
+            Self::ImportKeyword => vec![
+                RuleType::Token(JsToken::ImportKeyword),
+            ],
+            Self::ImportClause => vec![
+                RuleType::Sequence(vec![Self::VariableName, Self::Comma, Self::NamespaceImport]),
+                RuleType::Sequence(vec![Self::VariableName, Self::Comma, Self::NamedBindings]),
+                RuleType::Rule(Self::VariableName),
+                RuleType::Rule(Self::NamespaceImport),
+                RuleType::Rule(Self::NamedBindings),
+            ],
+            Self::NamespaceImport => vec![
+                RuleType::Sequence(vec![Self::OperatorMultiply, Self::AsKeyword, Self::VariableName]),
+            ],
+            Self::ExportDeclaration => vec![
+                RuleType::Sequence(vec![Self::ExportDefaultKeyword, Self::FunctionDeclaration]),
+                RuleType::Sequence(vec![Self::ExportDefaultKeyword, Self::ClassDeclaration]),
+                RuleType::Sequence(vec![Self::ExportDefaultKeyword, Self::ExpressionAssignment, Self::StatementEnd]),
+                RuleType::Sequence(vec![
+                    Self::ExportKeyword,
+                    Self::OperatorMultiply,
+                    Self::AsKeyword,
+                    Self::ModuleExportName,
+                    Self::FromKeyword,
+                    Self::ModuleSpecifier,
+                    Self::StatementEnd,
+                ]),
+                RuleType::Sequence(vec![
+                    Self::ExportKeyword,
+                    Self::OperatorMultiply,
+                    Self::FromKeyword,
+                    Self::ModuleSpecifier,
+                    Self::StatementEnd,
+                ]),
+                RuleType::Sequence(vec![
+                    Self::ExportKeyword,
+                    Self::NamedBindings,
+                    Self::FromKeyword,
+                    Self::ModuleSpecifier,
+                    Self::StatementEnd,
+                ]),
+                RuleType::Sequence(vec![Self::ExportKeyword, Self::NamedBindings, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::ExportKeyword, Self::VarDeclaration, Self::StatementEnd]),
+                RuleType::Sequence(vec![Self::ExportKeyword, Self::FunctionDeclaration]),
+                RuleType::Sequence(vec![Self::ExportKeyword, Self::ClassDeclaration]),
+            ],
+            Self::ExportKeyword => vec![
+                RuleType::Token(JsToken::ExportKeyword),
+            ],
+            Self::ExportDefaultKeyword => vec![
+                RuleType::Token(JsToken::ExportDefaultKeyword),
+            ],
+            Self::ModuleSpecifier => vec![
+                RuleType::Rule(Self::String),
+            ],
+            Self::NamedBindings => vec![
+                RuleType::Sequence(vec![Self::OpenCurlyBrace, Self::NamedBindingsList, Self::CloseCurlyBrace]),
+            ],
+            Self::NamedBindingsList => vec![
+                RuleType::Sequence(vec![Self::NamedBinding, Self::NamedBindingsTail]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::NamedBindingsTail => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::NamedBinding, Self::NamedBindingsTail]),
+                RuleType::Sequence(vec![Self::Comma]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::NamedBinding => vec![
+                RuleType::Sequence(vec![Self::ModuleExportName, Self::AsKeyword, Self::ModuleExportName]),
+                RuleType::Rule(Self::ModuleExportName),
+            ],
+            Self::ModuleExportName => vec![
+                RuleType::Rule(Self::VariableName),
+                RuleType::Rule(Self::DefaultKeyword),
+            ],
+            Self::FromKeyword => vec![
+                RuleType::Token(JsToken::FromKeyword),
+            ],
+            Self::AsKeyword => vec![
+                RuleType::Token(JsToken::AsKeyword),
+            ],
+            Self::ImportCall => vec![
+                RuleType::Sequence(vec![Self::ImportKeyword, Self::OpenParen, Self::ExpressionAssignment, Self::CloseParen]),
+            ],
             Self::ObjectLiteral => vec![
                 RuleType::Sequence(vec![Self::OpenCurlyBrace, Self::ObjectMembers, Self::CloseCurlyBrace]),
             ],
             Self::ObjectMembers => vec![
-                RuleType::Sequence(vec![Self::String, Self::Colon, Self::Expression, Self::Comma, Self::ObjectMembers]),
-                RuleType::Sequence(vec![Self::String, Self::Colon, Self::Expression, Self::Comma]),
-                RuleType::Sequence(vec![Self::String, Self::Colon, Self::Expression]),
+                RuleType::Sequence(vec![Self::ObjectMember, Self::ObjectMembersTail]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::ObjectMembersTail => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::ObjectMember, Self::ObjectMembersTail]),
+                RuleType::Sequence(vec![Self::Comma]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::ObjectMember => vec![
+                RuleType::Sequence(vec![Self::Spread, Self::ExpressionAssignment]),
+                RuleType::Sequence(vec![Self::PropertyName, Self::Colon, Self::ExpressionAssignment]),
+                RuleType::Sequence(vec![
+                    Self::OpenBracket,
+                    Self::Expression,
+                    Self::CloseBracket,
+                    Self::Colon,
+                    Self::ExpressionAssignment,
+                ]),
+                RuleType::Rule(Self::MethodDefinition),
+                // Only valid when the object literal is reinterpreted as a destructuring pattern
+                RuleType::Sequence(vec![Self::VariableName, Self::OperatorEquals, Self::ExpressionAssignment]),
+                RuleType::Rule(Self::VariableName),
+            ],
+            Self::PropertyName => vec![
+                RuleType::Rule(Self::String),
+                RuleType::Rule(Self::VariableName),
+            ],
+            Self::ArrayLiteral => vec![
+                RuleType::Sequence(vec![Self::OpenBracket, Self::ArrayElements, Self::CloseBracket]),
+            ],
+            Self::ArrayElements => vec![
+                RuleType::Sequence(vec![Self::OptionalArrayElement, Self::ArrayElementsTail]),
+            ],
+            Self::ArrayElementsTail => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::OptionalArrayElement, Self::ArrayElementsTail]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::OptionalArrayElement => vec![
+                RuleType::Sequence(vec![Self::Spread, Self::ExpressionAssignment]),
+                RuleType::Rule(Self::ExpressionAssignment),
                 RuleType::Sequence(vec![]),
             ],
             Self::IfStatement => vec![
@@ -179,6 +500,36 @@ impl Rule for JsRule {
                 ]),
                 RuleType::Sequence(vec![]),
             ],
+            Self::SwitchStatement => vec![
+                RuleType::Sequence(vec![
+                    Self::SwitchKeyword,
+                    Self::OpenParen,
+                    Self::Expression,
+                    Self::CloseParen,
+                    Self::OpenCurlyBrace,
+                    Self::SwitchCases,
+                    Self::CloseCurlyBrace,
+                ]),
+            ],
+            Self::SwitchKeyword => vec![
+                RuleType::Token(JsToken::SwitchKeyword),
+            ],
+            Self::SwitchCases => vec![
+                RuleType::RepeatableRule(Self::SwitchCase),
+            ],
+            Self::SwitchCase => vec![
+                RuleType::Sequence(vec![Self::CaseKeyword, Self::Expression, Self::Colon, Self::Statements]),
+                RuleType::Sequence(vec![Self::DefaultKeyword, Self::Colon, Self::Statements]),
+            ],
+            Self::CaseKeyword => vec![
+                RuleType::Token(JsToken::CaseKeyword),
+            ],
+            Self::DefaultKeyword => vec![
+                RuleType::Token(JsToken::DefaultKeyword),
+            ],
+            Self::BreakKeyword => vec![
+                RuleType::Token(JsToken::BreakKeyword),
+            ],
             Self::IfKeyword => vec![
                 RuleType::Token(JsToken::IfKeyword),
             ],
@@ -201,100 +552,198 @@ impl Rule for JsRule {
             Self::VariableName => vec![
                 RuleType::Token(JsToken::VariableName),
             ],
-            Self::VariableAssignment => vec![
-                RuleType::Sequence(vec![Self::VariableName, Self::OperatorEquals, Self::Expression]),
-            ],
+            // Each precedence level below is parsed as its first operand followed by a flat list
+            // of the remaining operations. This keeps parsing linear rather than re-parsing the
+            // left-hand side for every alternative.
             Self::Expression => vec![
-                RuleType::Rule(Self::ExpressionConditional),
-                RuleType::Rule(Self::ExpressionEquality),
-                RuleType::Rule(Self::ExpressionAdd),
-                RuleType::Rule(Self::ExpressionMultiply),
-                RuleType::Rule(Self::ExpressionFunctionInvoke),
-                RuleType::Rule(Self::DotAccess),
-                RuleType::Rule(Self::RightHandSideVariable),
-                RuleType::Rule(Self::LiteralValue),
+                RuleType::Sequence(vec![Self::ExpressionAssignment, Self::ExpressionCommaOperations]),
+            ],
+            Self::ExpressionCommaOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionCommaOperation),
+            ],
+            Self::ExpressionCommaOperation => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::ExpressionAssignment]),
+            ],
+            // The left-hand side is parsed as a regular expression and then reinterpreted as an
+            // assignment target, which allows literals to double as destructuring patterns.
+            Self::ExpressionAssignment => vec![
+                RuleType::Sequence(vec![Self::ExpressionConditional, Self::AssignmentTail]),
+            ],
+            Self::AssignmentTail => vec![
+                RuleType::Sequence(vec![Self::OperatorEquals, Self::ExpressionAssignment]),
+                RuleType::Sequence(vec![]),
             ],
             Self::ExpressionConditional => vec![
+                RuleType::Sequence(vec![Self::ExpressionBitwiseOr, Self::ConditionalTail]),
+            ],
+            Self::ConditionalTail => vec![
                 RuleType::Sequence(vec![
-                    JsRule::ExpressionSubConditional,
-                    JsRule::QuestionMark,
-                    JsRule::Expression,
-                    JsRule::Colon,
-                    JsRule::ExpressionSubConditional,
+                    Self::QuestionMark,
+                    Self::ExpressionAssignment,
+                    Self::Colon,
+                    Self::ExpressionAssignment,
                 ]),
+                RuleType::Sequence(vec![]),
             ],
-            Self::ExpressionSubConditional => vec![
-                RuleType::Rule(Self::ExpressionFunctionInvoke),
-                RuleType::Rule(Self::ExpressionEquality),
-                RuleType::Rule(Self::ExpressionAdd),
-                RuleType::Rule(Self::ExpressionMultiply),
-                RuleType::Rule(Self::RightHandSideVariable),
-                RuleType::Rule(Self::LiteralValue),
+            Self::ExpressionBitwiseOr => vec![
+                RuleType::Sequence(vec![Self::ExpressionBitwiseXor, Self::ExpressionBitwiseOrOperations]),
+            ],
+            Self::ExpressionBitwiseOrOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionBitwiseOrOperation),
+            ],
+            Self::ExpressionBitwiseOrOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorBitwiseOr, Self::ExpressionBitwiseXor]),
+            ],
+            Self::ExpressionBitwiseXor => vec![
+                RuleType::Sequence(vec![Self::ExpressionBitwiseAnd, Self::ExpressionBitwiseXorOperations]),
+            ],
+            Self::ExpressionBitwiseXorOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionBitwiseXorOperation),
+            ],
+            Self::ExpressionBitwiseXorOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorBitwiseXor, Self::ExpressionBitwiseAnd]),
+            ],
+            Self::ExpressionBitwiseAnd => vec![
+                RuleType::Sequence(vec![Self::ExpressionEquality, Self::ExpressionBitwiseAndOperations]),
+            ],
+            Self::ExpressionBitwiseAndOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionBitwiseAndOperation),
+            ],
+            Self::ExpressionBitwiseAndOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorBitwiseAnd, Self::ExpressionEquality]),
             ],
             Self::ExpressionEquality => vec![
-                RuleType::Sequence(vec![Self::ExpressionFunctionInvoke, Self::OperatorEquality, Self::ExpressionSubEquality]),
-                RuleType::Sequence(vec![Self::ExpressionAdd, Self::OperatorEquality, Self::ExpressionSubEquality]),
-                RuleType::Sequence(vec![Self::ExpressionMultiply, Self::OperatorEquality, Self::ExpressionSubEquality]),
-                RuleType::Sequence(vec![Self::RightHandSideVariable, Self::OperatorEquality, Self::ExpressionSubEquality]),
-                RuleType::Sequence(vec![Self::LiteralValue, Self::OperatorEquality, Self::ExpressionSubEquality]),
-            ],
-            Self::ExpressionSubEquality => vec![
-                RuleType::Rule(Self::ExpressionFunctionInvoke),
-                RuleType::Rule(Self::ExpressionEquality),
-                RuleType::Rule(Self::ExpressionAdd),
-                RuleType::Rule(Self::ExpressionMultiply),
-                RuleType::Rule(Self::RightHandSideVariable),
-                RuleType::Rule(Self::LiteralValue),
+                RuleType::Sequence(vec![Self::ExpressionRelational, Self::ExpressionEqualityOperations]),
+            ],
+            Self::ExpressionEqualityOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionEqualityOperation),
+            ],
+            Self::ExpressionEqualityOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorEquality, Self::ExpressionRelational]),
+            ],
+            Self::ExpressionRelational => vec![
+                RuleType::Sequence(vec![Self::ExpressionShift, Self::ExpressionRelationalOperations]),
+            ],
+            Self::ExpressionRelationalOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionRelationalOperation),
+            ],
+            Self::ExpressionRelationalOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorRelational, Self::ExpressionShift]),
+            ],
+            Self::ExpressionShift => vec![
+                RuleType::Sequence(vec![Self::ExpressionAdd, Self::ExpressionShiftOperations]),
+            ],
+            Self::ExpressionShiftOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionShiftOperation),
+            ],
+            Self::ExpressionShiftOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorShift, Self::ExpressionAdd]),
             ],
             Self::ExpressionAdd => vec![
-                RuleType::Sequence(vec![Self::ExpressionFunctionInvoke, Self::OperatorAdd, Self::ExpressionSubAdd]),
-                RuleType::Sequence(vec![Self::ExpressionMultiply, Self::OperatorAdd, Self::ExpressionSubAdd]),
-                RuleType::Sequence(vec![Self::RightHandSideVariable, Self::OperatorAdd, Self::ExpressionSubAdd]),
-                RuleType::Sequence(vec![Self::LiteralValue, Self::OperatorAdd, Self::ExpressionSubAdd]),
-                RuleType::Sequence(vec![Self::OperatorAdd, Self::LiteralValue]),
-            ],
-            Self::ExpressionSubAdd => vec![
-                RuleType::Rule(Self::ExpressionFunctionInvoke),
-                RuleType::Rule(Self::ExpressionAdd),
-                RuleType::Rule(Self::ExpressionMultiply),
-                RuleType::Rule(Self::RightHandSideVariable),
-                RuleType::Rule(Self::ExpressionAdd),
-                RuleType::Rule(Self::LiteralValue),
+                RuleType::Sequence(vec![Self::ExpressionMultiply, Self::ExpressionAddOperations]),
+            ],
+            Self::ExpressionAddOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionAddOperation),
+            ],
+            Self::ExpressionAddOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorAdd, Self::ExpressionMultiply]),
             ],
             Self::ExpressionMultiply => vec![
-                RuleType::Sequence(vec![Self::ExpressionFunctionInvoke, Self::OperatorMultiply, Self::ExpressionSubMultiply]),
-                RuleType::Sequence(vec![Self::RightHandSideVariable, Self::OperatorMultiply, Self::ExpressionSubMultiply]),
-                RuleType::Sequence(vec![Self::LiteralValue, Self::OperatorMultiply, Self::ExpressionSubMultiply]),
+                RuleType::Sequence(vec![Self::ExpressionExponent, Self::ExpressionMultiplyOperations]),
             ],
-            Self::ExpressionSubMultiply => vec![
-                RuleType::Rule(Self::ExpressionFunctionInvoke),
-                RuleType::Rule(Self::ExpressionMultiply),
-                RuleType::Rule(Self::RightHandSideVariable),
-                RuleType::Rule(Self::LiteralValue),
+            Self::ExpressionMultiplyOperations => vec![
+                RuleType::RepeatableRule(Self::ExpressionMultiplyOperation),
+            ],
+            Self::ExpressionMultiplyOperation => vec![
+                RuleType::Sequence(vec![Self::OperatorMultiply, Self::ExpressionExponent]),
+            ],
+            // Exponentiation groups from the right, so the right-hand side is itself an
+            // exponentiation
+            Self::ExpressionExponent => vec![
+                RuleType::Sequence(vec![Self::ExpressionUnary, Self::ExponentTail]),
+            ],
+            Self::ExponentTail => vec![
+                RuleType::Sequence(vec![Self::OperatorExponent, Self::ExpressionExponent]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::ExpressionUnary => vec![
+                RuleType::Sequence(vec![Self::OperatorAdd, Self::ExpressionUnary]),
+                RuleType::Sequence(vec![Self::OperatorBitwiseNot, Self::ExpressionUnary]),
+                RuleType::Rule(Self::ExpressionMember),
+            ],
+            Self::ExpressionMember => vec![
+                RuleType::Sequence(vec![Self::NewExpression, Self::MemberAccesses]),
+                RuleType::Sequence(vec![Self::PrimaryExpression, Self::MemberAccesses]),
+            ],
+            // The constructor is only followed by member accesses so that the first argument list
+            // belongs to `new` rather than to a call of the constructor.
+            Self::NewExpression => vec![
+                RuleType::Sequence(vec![
+                    Self::NewKeyword,
+                    Self::PrimaryExpression,
+                    Self::NewTargetAccesses,
+                    Self::FunctionInvoke,
+                ]),
+                RuleType::Sequence(vec![Self::NewKeyword, Self::PrimaryExpression, Self::NewTargetAccesses]),
+            ],
+            Self::NewTargetAccesses => vec![
+                RuleType::RepeatableRule(Self::NewTargetAccess),
+            ],
+            Self::NewTargetAccess => vec![
+                RuleType::Rule(Self::DotAccess),
+                RuleType::Rule(Self::ComputedMemberAccess),
+            ],
+            Self::MemberAccesses => vec![
+                RuleType::RepeatableRule(Self::MemberAccess),
+            ],
+            Self::MemberAccess => vec![
+                RuleType::Rule(Self::DotAccess),
+                RuleType::Rule(Self::ComputedMemberAccess),
+                RuleType::Rule(Self::FunctionInvoke),
+            ],
+            Self::DotAccess => vec![
+                RuleType::Sequence(vec![Self::Dot, Self::VariableName]),
             ],
-            Self::ExpressionFunctionInvoke => vec![
-                RuleType::Sequence(vec![Self::DotAccess, Self::FunctionInvoke]),
-                RuleType::Sequence(vec![Self::RightHandSideVariable, Self::FunctionInvoke]),
-                RuleType::Sequence(vec![Self::LiteralValue, Self::FunctionInvoke]),
+            Self::ComputedMemberAccess => vec![
+                RuleType::Sequence(vec![Self::OpenBracket, Self::Expression, Self::CloseBracket]),
             ],
             Self::FunctionInvoke => vec![
                 RuleType::Sequence(vec![Self::OpenParen, Self::FunctionArguments, Self::CloseParen]),
             ],
             Self::FunctionArguments => vec![
-                RuleType::Sequence(vec![Self::Expression, Self::Comma, Self::FunctionArguments]),
-                RuleType::Sequence(vec![Self::Expression, Self::Comma]),
-                RuleType::Sequence(vec![Self::Expression]),
+                RuleType::Sequence(vec![Self::FunctionArgument, Self::FunctionArgumentsTail]),
                 RuleType::Sequence(vec![]),
             ],
+            Self::FunctionArgumentsTail => vec![
+                RuleType::Sequence(vec![Self::Comma, Self::FunctionArgument, Self::FunctionArgumentsTail]),
+                RuleType::Sequence(vec![Self::Comma]),
+                RuleType::Sequence(vec![]),
+            ],
+            Self::FunctionArgument => vec![
+                RuleType::Sequence(vec![Self::Spread, Self::ExpressionAssignment]),
+                RuleType::Rule(Self::ExpressionAssignment),
+            ],
+            Self::PrimaryExpression => vec![
+                RuleType::Rule(Self::ImportCall),
+                RuleType::Rule(Self::RightHandSideVariable),
+                RuleType::Rule(Self::LiteralValue),
+                RuleType::Rule(Self::ParenthesizedExpression),
+            ],
+            Self::ParenthesizedExpression => vec![
+                RuleType::Sequence(vec![Self::OpenParen, Self::Expression, Self::CloseParen]),
+            ],
             Self::LiteralValue => vec![
                 RuleType::Rule(Self::TrueKeyword),
                 RuleType::Rule(Self::FalseKeyword),
                 RuleType::Rule(Self::Number),
+                RuleType::Rule(Self::BigInt),
                 RuleType::Rule(Self::String),
+                RuleType::Rule(Self::RegExp),
                 RuleType::Rule(Self::Undefined),
                 RuleType::Rule(Self::NullKeyword),
                 RuleType::Rule(Self::NaNKeyword),
                 RuleType::Rule(Self::ObjectLiteral),
+                RuleType::Rule(Self::ArrayLiteral),
             ],
             Self::OperatorAdd => vec![
                 RuleType::Token(JsToken::OperatorAdd),
@@ -302,18 +751,33 @@ impl Rule for JsRule {
             Self::OperatorMultiply => vec![
                 RuleType::Token(JsToken::OperatorMultiply),
             ],
+            Self::OperatorExponent => vec![
+                RuleType::Token(JsToken::OperatorExponent),
+            ],
+            Self::OperatorShift => vec![
+                RuleType::Token(JsToken::OperatorShift),
+            ],
+            Self::OperatorRelational => vec![
+                RuleType::Token(JsToken::OperatorRelational),
+            ],
+            Self::OperatorBitwiseAnd => vec![
+                RuleType::Token(JsToken::OperatorBitwiseAnd),
+            ],
+            Self::OperatorBitwiseXor => vec![
+                RuleType::Token(JsToken::OperatorBitwiseXor),
+            ],
+            Self::OperatorBitwiseOr => vec![
+                RuleType::Token(JsToken::OperatorBitwiseOr),
+            ],
+            Self::OperatorBitwiseNot => vec![
+                RuleType::Token(JsToken::OperatorBitwiseNot),
+            ],
             Self::OperatorEquals => vec![
                 RuleType::Token(JsToken::OperatorEquals),
             ],
             Self::OperatorEquality => vec![
                 RuleType::Token(JsToken::OperatorEquality),
             ],
-            Self::DotAccess => vec![
-                RuleType::Sequence(vec![JsRule::RightHandSideVariable, JsRule::Dot, JsRule::DotAccess]),
-                RuleType::Sequence(vec![JsRule::RightHandSideVariable, JsRule::Dot, JsRule::VariableName]),
-                RuleType::Sequence(vec![JsRule::LiteralValue, JsRule::Dot, JsRule::DotAccess]),
-                RuleType::Sequence(vec![JsRule::LiteralValue, JsRule::Dot, JsRule::VariableName]),
-            ],
             Self::OpenParen => vec! [
                 RuleType::Token(JsToken::OpenParen),
             ],
@@ -326,6 +790,15 @@ impl Rule for JsRule {
             Self::CloseCurlyBrace => vec![
                 RuleType::Token(JsToken::CloseCurlyBrace),
             ],
+            Self::OpenBracket => vec![
+                RuleType::Token(JsToken::OpenBracket),
+            ],
+            Self::CloseBracket => vec![
+                RuleType::Token(JsToken::CloseBracket),
+            ],
+            Self::Spread => vec![
+                RuleType::Token(JsToken::Spread),
+            ],
             Self::Dot => vec![
                 RuleType::Token(JsToken::Dot),
             ],
@@ -335,9 +808,15 @@ impl Rule for JsRule {
             Self::Number => vec![
                 RuleType::Token(JsToken::Number),
             ],
+            Self::BigInt => vec![
+                RuleType::Token(JsToken::BigInt),
+            ],
             Self::String => vec![
                 RuleType::Token(JsToken::String),
             ],
+            Self::RegExp => vec![
+                RuleType::Token(JsToken::RegExp),
+            ],
             Self::Undefined => vec![
                 RuleType::Token(JsToken::Undefined),
             ],
@@ -350,6 +829,9 @@ impl Rule for JsRule {
             Self::Semicolon => vec![
                 RuleType::Token(JsToken::Semicolon),
             ],
+            Self::AutomaticSemicolon => vec![
+                RuleType::Token(JsToken::AutomaticSemicolon),
+            ],
             Self::QuestionMark => vec![
                 RuleType::Token(JsToken::QuestionMark),
             ],
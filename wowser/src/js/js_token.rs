@@ -7,30 +7,58 @@ pub enum JsToken {
     IfKeyword,
     ElseKeyword,
     VarKeyword,
+    LetKeyword,
+    ConstKeyword,
     FunctionKeyword,
+    NewKeyword,
+    ClassKeyword,
+    SwitchKeyword,
+    CaseKeyword,
+    DefaultKeyword,
+    BreakKeyword,
+    StaticKeyword,
+    AccessorKeyword,
     ThisKeyword,
     ReturnKeyword,
     ThrowKeyword,
+    ImportKeyword,
+    ExportKeyword,
+    ExportDefaultKeyword,
+    FromKeyword,
+    AsKeyword,
     TrueKeyword,
     FalseKeyword,
     NullKeyword,
     NaNKeyword,
     VariableName,
     Number,
+    BigInt,
     String,
+    RegExp,
     Undefined,
     OperatorAdd,
     OperatorMultiply,
+    OperatorExponent,
+    OperatorShift,
+    OperatorRelational,
+    OperatorBitwiseAnd,
+    OperatorBitwiseXor,
+    OperatorBitwiseOr,
+    OperatorBitwiseNot,
     OperatorEquals,
     OperatorEquality,
     OpenParen,
     CloseParen,
     OpenCurlyBrace,
     CloseCurlyBrace,
+    OpenBracket,
+    CloseBracket,
+    Spread,
     Dot,
     Comma,
     Colon,
     Semicolon,
+    AutomaticSemicolon,
     QuestionMark,
     InlineComment,
     MultilineComment,
@@ -40,9 +68,16 @@ pub enum JsToken {
 const STATEMENT_START: &[JsToken] = &[
     JsToken::IfKeyword,
     JsToken::VarKeyword,
+    JsToken::LetKeyword,
+    JsToken::ConstKeyword,
     JsToken::FunctionKeyword,
+    JsToken::ClassKeyword,
+    JsToken::SwitchKeyword,
+    JsToken::BreakKeyword,
     JsToken::ReturnKeyword,
     JsToken::ThrowKeyword,
+    JsToken::ExportDefaultKeyword,
+    JsToken::ExportKeyword,
     JsToken::Semicolon,
 ];
 
@@ -50,27 +85,73 @@ const EXPRESSION_START: &[JsToken] = &[
     JsToken::TrueKeyword,
     JsToken::FalseKeyword,
     JsToken::NullKeyword,
+    // Tried before numbers, which would otherwise match their digits
+    JsToken::BigInt,
     JsToken::Number,
     JsToken::String,
     JsToken::Undefined,
     JsToken::NaNKeyword,
     JsToken::OperatorAdd,
+    JsToken::OperatorBitwiseNot,
+    JsToken::NewKeyword,
     JsToken::VariableName,
     JsToken::OpenCurlyBrace,
+    JsToken::OpenBracket,
+    JsToken::OpenParen,
+    JsToken::RegExp,
+    JsToken::ImportKeyword,
 ];
 
+/// Tokens that may start a declaration's binding, either a plain name or a destructuring pattern
+const BINDING_START: &[JsToken] = &[
+    JsToken::VariableName,
+    JsToken::OpenCurlyBrace,
+    JsToken::OpenBracket,
+];
+
+/// Tokens that may start a method definition in an object literal or class body, which must be
+/// tried before the names they prefix
+const METHOD_START: &[JsToken] = &[JsToken::StaticKeyword, JsToken::AccessorKeyword];
+
+/// Tokens that start a clause of a switch statement
+const CASE_START: &[JsToken] = &[JsToken::CaseKeyword, JsToken::DefaultKeyword];
+
+/// Tokens that may follow `export`
+const EXPORT_START: &[JsToken] = &[
+    JsToken::OpenCurlyBrace,
+    JsToken::OperatorMultiply,
+    JsToken::VarKeyword,
+    JsToken::LetKeyword,
+    JsToken::ConstKeyword,
+    JsToken::FunctionKeyword,
+    JsToken::ClassKeyword,
+];
+
+/// Tokens that access a member of, or invoke, the preceding value
+const MEMBER_ACCESS: &[JsToken] = &[JsToken::Dot, JsToken::OpenBracket, JsToken::OpenParen];
+
 const POST_EXPRESSION: &[JsToken] = &[
     JsToken::OperatorAdd,
+    // Tried before the operators they start with
+    JsToken::OperatorExponent,
     JsToken::OperatorMultiply,
+    JsToken::OperatorShift,
+    JsToken::OperatorRelational,
+    JsToken::OperatorBitwiseAnd,
+    JsToken::OperatorBitwiseXor,
+    JsToken::OperatorBitwiseOr,
     JsToken::OperatorEquality,
     JsToken::CloseParen,
     JsToken::CloseCurlyBrace,
+    JsToken::CloseBracket,
     JsToken::Comma,
     JsToken::Semicolon,
     JsToken::ElseKeyword,
     JsToken::Colon,
     JsToken::QuestionMark,
     JsToken::Terminator,
+    // Tried last, so a line break only ends a statement that could not otherwise continue
+    JsToken::AutomaticSemicolon,
 ];
 
 impl Token for JsToken {
@@ -80,32 +161,72 @@ impl Token for JsToken {
             Self::IfKeyword => r"\s*(if)\s*",
             Self::ElseKeyword => r"\s*(else)\s*",
             Self::VarKeyword => r"\s*(var\s)\s*",
+            Self::LetKeyword => r"\s*(let\s)\s*",
+            Self::ConstKeyword => r"\s*(const\s)\s*",
             Self::FunctionKeyword => r"\s*(function\s)\s*",
-            Self::ThisKeyword => r"\s*(this)\s*",
-            Self::ReturnKeyword => r"\s*(return\s)\s*",
+            Self::NewKeyword => r"\s*(new\s)\s*",
+            Self::ClassKeyword => r"\s*(class\s)\s*",
+            Self::SwitchKeyword => r"\s*(switch)\s*",
+            Self::CaseKeyword => r"\s*(case)(?![\w$])\s*",
+            // Also names the default export in import and export lists
+            Self::DefaultKeyword => r"\s*(default)\s*(?=:|,|}|as\s)",
+            // Like `return`, a line break after `break` ends the statement
+            Self::BreakKeyword => r"\s*(break)(?![\w$])[^\S\n]*",
+            Self::StaticKeyword => r#"\s*(static)\s+(?=[\w$\['"])"#,
+            Self::AccessorKeyword => r#"\s*(get|set)\s+(?=[\w$\['"])"#,
+            Self::ThisKeyword => r"\s*(this)[^\S\n]*",
+            Self::ReturnKeyword => r"\s*(return)(?![\w$])[^\S\n]*",
             Self::ThrowKeyword => r"\s*(throw\s)\s*",
-            Self::TrueKeyword => r"\s*(true)\s*",
-            Self::FalseKeyword => r"\s*(false)\s*",
-            Self::NullKeyword => r"\s*(null)\s*",
+            Self::ImportKeyword => r"\s*(import)(?![\w$])\s*",
+            Self::ExportKeyword => r"\s*(export)(?![\w$])\s*",
+            Self::ExportDefaultKeyword => r"\s*(export\s+default)(?![\w$])\s*",
+            // `from` and `as` are only keywords where a name could not appear, so they are
+            // recognized by what follows them and tried after the tokens they could be mistaken for
+            Self::FromKeyword => r#"\s*(from)\s*(?=["'])"#,
+            Self::AsKeyword => r"\s*(as)\s+(?=[a-zA-Z_$])",
+            Self::TrueKeyword => r"\s*(true)[^\S\n]*",
+            Self::FalseKeyword => r"\s*(false)[^\S\n]*",
+            Self::NullKeyword => r"\s*(null)[^\S\n]*",
             Self::VariableName => {
-                r"\s*((?!((var|function|throw|return|undefined|true|false|null|if|NaN)[^a-zA-Z_$]))[a-zA-Z_][\w\d]*)\s*"
+                r"\s*((?!((var|let|const|function|class|switch|case|default|break|new|throw|return|undefined|true|false|null|if|else|NaN|import|export)(?![\w$])))[a-zA-Z_][\w\d]*)[^\S\n]*"
             }
-            Self::Number => r"\s*(-?\d[\d_]*(\.\d[\d_]*)?)\s*",
-            Self::String => r#"\s*(("[^"]*")|('[^']*'))\s*"#,
-            Self::Undefined => r"\s*(undefined)\s*",
-            Self::NaNKeyword => r"\s*(NaN)\s*",
-            Self::OperatorAdd => r"\s*(\+)\s*",
-            Self::OperatorMultiply => r"\s*(\*)\s*",
+            Self::Number => r"\s*(-?\d[\d_]*(\.\d[\d_]*)?)[^\S\n]*",
+            Self::BigInt => r"\s*(-?\d[\d_]*n)(?![\w$])[^\S\n]*",
+            Self::String => r#"\s*(("[^"]*")|('[^']*'))[^\S\n]*"#,
+            // A slash starting an expression begins a regular expression, while one following an
+            // expression is division. Slashes starting comments are left to the comment tokens.
+            Self::RegExp => {
+                r"\s*(/(?![*/])(?:\\.|\[(?:\\.|[^\]\\\n])*\]|[^/\\\n\[])+/[a-z]*)[^\S\n]*"
+            }
+            Self::Undefined => r"\s*(undefined)[^\S\n]*",
+            Self::NaNKeyword => r"\s*(NaN)[^\S\n]*",
+            // Also unary `+` and `-`, although a `-` starting an expression is tried as part of a
+            // number first
+            Self::OperatorAdd => r"\s*(\+|-)\s*",
+            Self::OperatorMultiply => r"\s*(\*|/(?![/*])|%)\s*",
+            Self::OperatorExponent => r"\s*(\*\*)\s*",
+            Self::OperatorShift => r"\s*(>>>|<<|>>)\s*",
+            Self::OperatorRelational => r"\s*(<=|>=|<|>)\s*",
+            Self::OperatorBitwiseAnd => r"\s*(&)\s*",
+            Self::OperatorBitwiseXor => r"\s*(\^)\s*",
+            Self::OperatorBitwiseOr => r"\s*(\|)\s*",
+            Self::OperatorBitwiseNot => r"\s*(~)\s*",
             Self::OperatorEquals => r"\s*(=)\s*",
             Self::OperatorEquality => r"\s*(!==|!=|===|==)\s*",
             Self::OpenParen => r"\s*(\()\s*",
-            Self::CloseParen => r"\s*(\))\s*",
+            Self::CloseParen => r"\s*(\))[^\S\n]*",
             Self::OpenCurlyBrace => r"\s*({)\s*",
-            Self::CloseCurlyBrace => r"\s*(})\s*",
+            Self::CloseCurlyBrace => r"\s*(})[^\S\n]*",
+            Self::OpenBracket => r"\s*(\[)\s*",
+            Self::CloseBracket => r"\s*(\])[^\S\n]*",
+            Self::Spread => r"\s*(\.\.\.)\s*",
             Self::Dot => r"\s*(\.)\s*",
             Self::Comma => r"\s*(,)\s*",
             Self::Colon => r"\s*(:)\s*",
             Self::Semicolon => r"\s*(;)\s*",
+            // Tokens that may end a statement stop before line breaks, so a line break they cannot
+            // otherwise be followed by ends the statement as if it were a semicolon
+            Self::AutomaticSemicolon => r"[^\S\n]*(\n)\s*",
             Self::QuestionMark => r"\s*(\?)\s*",
             Self::InlineComment => r"\s*(//.*)",
             Self::MultilineComment => r"\s*(/\*(.|\n)*?\*/)\s*",
@@ -135,77 +256,197 @@ impl Token for JsToken {
                 EXPRESSION_START,
                 STATEMENT_START,
             ].concat(),
-            Self::VarKeyword => vec![
+            Self::VarKeyword => Vec::from(BINDING_START),
+            Self::LetKeyword => Vec::from(BINDING_START),
+            Self::ConstKeyword => Vec::from(BINDING_START),
+            Self::FunctionKeyword => vec![
                 Self::VariableName,
             ],
-            Self::FunctionKeyword => vec![
+            Self::NewKeyword => vec![
+                Self::VariableName,
+                Self::OpenParen,
+            ],
+            Self::ClassKeyword => vec![
+                Self::VariableName,
+            ],
+            Self::SwitchKeyword => vec![
+                Self::OpenParen,
+            ],
+            Self::CaseKeyword => Vec::from(EXPRESSION_START),
+            Self::DefaultKeyword => vec![
+                Self::Colon,
+                Self::Comma,
+                Self::CloseCurlyBrace,
+                Self::AsKeyword,
+            ],
+            Self::BreakKeyword => vec![
+                Self::AutomaticSemicolon,
+                Self::Semicolon,
+                Self::CloseCurlyBrace,
+                Self::VariableName,
+                Self::Terminator,
+            ],
+            Self::StaticKeyword => vec![
+                Self::AccessorKeyword,
+                Self::VariableName,
+                Self::String,
+                Self::OpenBracket,
+            ],
+            Self::AccessorKeyword => vec![
                 Self::VariableName,
+                Self::String,
+                Self::OpenBracket,
             ],
             Self::ThisKeyword => [
                 &[
                     Self::OperatorEquals,
-                    Self::OpenParen,
-                    Self::Dot,
                 ],
+                MEMBER_ACCESS,
                 POST_EXPRESSION,
             ].concat(),
-            Self::ReturnKeyword => Vec::from(EXPRESSION_START),
+            // A line break after `return` always ends the statement
+            Self::ReturnKeyword => [
+                &[
+                    Self::AutomaticSemicolon,
+                    Self::Semicolon,
+                    Self::CloseCurlyBrace,
+                    Self::Terminator,
+                ],
+                EXPRESSION_START,
+            ].concat(),
             Self::ThrowKeyword => Vec::from(EXPRESSION_START),
+            Self::ImportKeyword => vec![
+                Self::OpenParen,
+                Self::String,
+                Self::OpenCurlyBrace,
+                Self::OperatorMultiply,
+                Self::VariableName,
+            ],
+            Self::ExportKeyword => Vec::from(EXPORT_START),
+            Self::ExportDefaultKeyword => [
+                &[
+                    Self::FunctionKeyword,
+                    Self::ClassKeyword,
+                ],
+                EXPRESSION_START,
+            ].concat(),
+            Self::FromKeyword => vec![
+                Self::String,
+            ],
+            Self::AsKeyword => vec![
+                Self::VariableName,
+                Self::DefaultKeyword,
+            ],
             Self::TrueKeyword => Vec::from(POST_EXPRESSION),
             Self::FalseKeyword => Vec::from(POST_EXPRESSION),
             Self::NullKeyword => Vec::from(POST_EXPRESSION),
             Self::VariableName => [
                 &[
                     Self::OperatorEquals,
-                    Self::OpenParen,
-                    Self::Dot,
+                ],
+                MEMBER_ACCESS,
+                &[
+                    Self::OpenCurlyBrace,
                 ],
                 POST_EXPRESSION,
+                &[
+                    Self::AsKeyword,
+                    Self::FromKeyword,
+                ],
             ].concat(),
             Self::Number => Vec::from(POST_EXPRESSION),
+            Self::BigInt => [
+                MEMBER_ACCESS,
+                POST_EXPRESSION,
+            ].concat(),
             Self::String => [
-                &[
-                    Self::Dot,
-                    Self::Colon,
-                ],
+                MEMBER_ACCESS,
+                POST_EXPRESSION,
+            ].concat(),
+            Self::RegExp => [
+                MEMBER_ACCESS,
                 POST_EXPRESSION,
             ].concat(),
             Self::Undefined => Vec::from(POST_EXPRESSION),
             Self::NaNKeyword => Vec::from(POST_EXPRESSION),
             Self::OperatorAdd => Vec::from(EXPRESSION_START),
-            Self::OperatorMultiply => Vec::from(EXPRESSION_START),
+            Self::OperatorMultiply => [
+                EXPRESSION_START,
+                &[
+                    Self::AsKeyword,
+                    Self::FromKeyword,
+                ],
+            ].concat(),
+            Self::OperatorExponent => Vec::from(EXPRESSION_START),
+            Self::OperatorShift => Vec::from(EXPRESSION_START),
+            Self::OperatorRelational => Vec::from(EXPRESSION_START),
+            Self::OperatorBitwiseAnd => Vec::from(EXPRESSION_START),
+            Self::OperatorBitwiseXor => Vec::from(EXPRESSION_START),
+            Self::OperatorBitwiseOr => Vec::from(EXPRESSION_START),
+            Self::OperatorBitwiseNot => Vec::from(EXPRESSION_START),
             Self::OperatorEquals => Vec::from(EXPRESSION_START),
             Self::OperatorEquality => Vec::from(EXPRESSION_START),
             Self::OpenParen => [
                 &[
                     Self::CloseParen,
+                    Self::Spread,
                 ],
                 EXPRESSION_START,
             ].concat(),
+            // Operators are tried before anything starting an expression, so a `-` or `/` after a
+            // parenthesized expression is subtraction or division rather than part of a number or
+            // regular expression
             Self::CloseParen => [
                 &[
-                    Self::CloseParen,
-                    Self::OpenCurlyBrace,
-                    Self::OperatorMultiply,
+                    Self::Dot,
                     Self::OperatorAdd,
-                    Self::Comma,
-                    Self::Terminator,
+                    Self::OperatorMultiply,
                 ],
                 EXPRESSION_START,
                 STATEMENT_START,
+                POST_EXPRESSION,
             ].concat(),
             Self::OpenCurlyBrace => [
                 &[
                     Self::CloseCurlyBrace,
+                    Self::Spread,
                 ],
+                CASE_START,
+                METHOD_START,
                 EXPRESSION_START,
                 STATEMENT_START,
             ].concat(),
             Self::CloseCurlyBrace => [
+                &[
+                    Self::OperatorEquals,
+                    Self::Dot,
+                ],
+                CASE_START,
+                METHOD_START,
                 EXPRESSION_START,
                 STATEMENT_START,
                 POST_EXPRESSION,
+                &[
+                    Self::FromKeyword,
+                ],
             ].concat(),
+            Self::OpenBracket => [
+                &[
+                    Self::CloseBracket,
+                    Self::Comma,
+                    Self::Spread,
+                ],
+                EXPRESSION_START,
+            ].concat(),
+            Self::CloseBracket => [
+                &[
+                    Self::OperatorEquals,
+                    Self::Colon,
+                ],
+                MEMBER_ACCESS,
+                POST_EXPRESSION,
+            ].concat(),
+            Self::Spread => Vec::from(EXPRESSION_START),
             Self::Dot => vec![
                 Self::VariableName,
             ],
@@ -213,19 +454,36 @@ impl Token for JsToken {
                 &[
                     Self::CloseParen,
                     Self::CloseCurlyBrace,
+                    Self::CloseBracket,
+                    Self::Comma,
+                    Self::Spread,
+                ],
+                METHOD_START,
+                EXPRESSION_START,
+                &[
+                    Self::OperatorMultiply,
+                    Self::DefaultKeyword,
                 ],
+            ].concat(),
+            Self::Colon => [
                 EXPRESSION_START,
+                STATEMENT_START,
+                CASE_START,
+                &[
+                    Self::CloseCurlyBrace,
+                ],
             ].concat(),
-            Self::Colon => Vec::from(EXPRESSION_START),
             Self::Semicolon => [
                 &[
                     Self::CloseCurlyBrace,
                     Self::ElseKeyword,
                     Self::Terminator,
                 ],
+                CASE_START,
                 EXPRESSION_START,
                 STATEMENT_START,
             ].concat(),
+            Self::AutomaticSemicolon => Self::Semicolon.next_tokens(),
             Self::QuestionMark => Vec::from(EXPRESSION_START),
             Self::InlineComment => vec![],
             Self::MultilineComment => vec![],
@@ -9,26 +9,75 @@ pub use js_rule::*;
 pub use js_token::*;
 pub use runtime::*;
 
-use crate::parse::{Interpreter, Lexer, Parser};
+use crate::parse::{Interpreter, Lexer, ParsedToken, ParsedTokens, Parser, ParserResult};
 
 pub fn parse_js(document: &str) -> Result<JsDocument, String> {
-    let lexer = Lexer::new(JsToken::Document);
-    let tokens = lexer.parse(document).ok_or("Failed to lex JS")?;
-    let ast = Parser {}.parse(&tokens, &JsRule::Document)?;
+    let tokens = lex_js(document)?;
+    let ast = parse_js_tokens(document, &tokens)?;
     let document = JsInterpreter {}
         .interpret(&ast)
         .ok_or("Failed to interpret JS")?;
     Ok(document)
 }
 
+/// Parses statements to run in an existing document, such as a line entered into a REPL
+pub fn parse_js_statements(
+    document: &str,
+    node_graph: &JsValueGraph,
+) -> Result<Vec<JsStatement>, String> {
+    let tokens = lex_js(document)?;
+    let ast = parse_js_tokens(document, &tokens)?;
+    Ok(JsInterpreter::interpret_statements(node_graph, &ast.node))
+}
+
+/// Errors describe where the document failed to lex
+fn lex_js(document: &str) -> Result<ParsedTokens<'_, JsToken>, String> {
+    Lexer::new(JsToken::Document)
+        .lex(document)
+        .map_err(|error| error.describe(document))
+}
+
+/// Errors describe where the document failed to parse
+fn parse_js_tokens<'a>(
+    document: &str,
+    tokens: &'a [ParsedToken<'a, JsToken>],
+) -> Result<ParserResult<'a, JsRule>, String> {
+    Parser {}
+        .parse_with_location(tokens, &JsRule::Document)
+        .map_err(|error| error.describe(document))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        cell::RefCell,
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        rc::Rc,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use wowser_macros::js_class;
 
-    use crate::garbage_collector::GcNodeGraph;
+    use crate::{
+        garbage_collector::GcNodeGraph,
+        html::{parse_html, ElementContents},
+        js::api::{
+            date::{FixedClock, FixedOffset},
+            history::{NavigationRequest, SessionHistory},
+            module::InMemoryModuleLoader,
+            storage::{InMemoryStorage, StorageArea, StorageListener},
+        },
+        net::{Url, UrlRecord},
+    };
 
     use super::{
-        parse_js, JsExpression, JsFunction, JsStatement, JsStatementResult, JsValue, JsValueGraph,
+        parse_js, parse_js_statements, JsClosureContext, JsDate, JsDocument, JsEngine, JsEvent,
+        JsEventDetail, JsEventModifiers, JsEventTargetId, JsExpression, JsFunction,
+        JsNativeFunctionImplementation, JsStatement, JsStatementResult, JsStorage, JsValue,
+        JsValueGraph, JsValueNode,
     };
 
     fn get_node_graph() -> JsValueGraph {
@@ -39,7 +88,19 @@ mod tests {
     /// This returns the value graph because when it goes out of scope, the results get cleared from memory
     #[track_caller]
     fn run_js(script: &str) -> (JsValueGraph, Vec<JsStatementResult>) {
+        run_js_with_engine(script, JsEngine::default())
+    }
+
+    #[track_caller]
+    fn run_js_with_engine(
+        script: &str,
+        engine: JsEngine,
+    ) -> (JsValueGraph, Vec<JsStatementResult>) {
         let mut js_document = parse_js(script).unwrap();
+        js_document.global_closure_context.engine = engine;
+        // Scripts run at 2001-09-09T01:46:40Z, an hour ahead of UTC, wherever and whenever
+        js_document.global_closure_context.clock = Rc::new(FixedClock::new(1e12));
+        js_document.global_closure_context.time_zone = Rc::new(FixedOffset::new(60));
         js_document.run();
         (
             js_document.global_closure_context.nodes_graph,
@@ -47,16 +108,97 @@ mod tests {
         )
     }
 
+    /// Runs the script with each engine, which must give the same results
     #[track_caller]
     fn run_test(script: &str, expected_results: Vec<JsStatementResult>) {
-        let (_node_graph, results) = run_js(script);
-        assert_eq!(results, expected_results);
+        for engine in JsEngine::ALL {
+            let (_node_graph, results) = run_js_with_engine(script, engine);
+            assert_eq!(results, expected_results, "{engine:?}");
+        }
     }
 
     #[track_caller]
     fn assert_last_value_equals(script: &str, expected_result: JsStatementResult) {
-        let (_node_graph, results) = run_js(script);
-        assert_eq!(results.last().unwrap(), &expected_result);
+        for engine in JsEngine::ALL {
+            let (_node_graph, results) = run_js_with_engine(script, engine);
+            assert_eq!(results.last().unwrap(), &expected_result, "{engine:?}");
+        }
+    }
+
+    /// Runs the script with each engine and `host` declared as the value created, which must give
+    /// the same last result
+    #[track_caller]
+    fn assert_host_result_equals(
+        script: &str,
+        create_host: impl Fn(&mut JsClosureContext) -> JsValueNode,
+        expected_result: JsStatementResult,
+    ) {
+        for engine in JsEngine::ALL {
+            let mut js_document = parse_js(script).unwrap();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            let host = create_host(closure_context);
+            closure_context.get_or_declare_reference_mut("host").value = host;
+            js_document.run();
+            let results = &js_document.global_closure_context.expression_results;
+            assert_eq!(results.last().unwrap(), &expected_result, "{engine:?}");
+        }
+    }
+
+    /// Runs the script with each engine against the HTML document, which must give the same last
+    /// result. Results in the document as the script left it.
+    #[track_caller]
+    fn assert_dom_result_equals(
+        html: &str,
+        script: &str,
+        expected_result: JsStatementResult,
+    ) -> String {
+        let mut html_results = vec![];
+        for engine in JsEngine::ALL {
+            let document = Rc::new(RefCell::new(parse_html(html).unwrap()));
+            let mut js_document = parse_js(script).unwrap();
+            js_document.global_closure_context.engine = engine;
+            js_document.set_html_document(document.clone());
+            js_document.run();
+            let results = &js_document.global_closure_context.expression_results;
+            assert_eq!(results.last().unwrap(), &expected_result, "{engine:?}");
+            html_results.push(document.borrow().html.to_string());
+        }
+        html_results.dedup();
+        assert_eq!(html_results.len(), 1, "{html_results:?}");
+        html_results.remove(0)
+    }
+
+    /// Loads modules from `https://example.com/` with the sources passed in by path
+    fn get_module_loader(modules: &[(&str, &str)]) -> Rc<InMemoryModuleLoader> {
+        let loader = modules
+            .iter()
+            .fold(InMemoryModuleLoader::new(), |loader, (path, source)| {
+                loader.with_module(&format!("https://example.com/{path}"), source)
+            });
+        Rc::new(loader)
+    }
+
+    /// Imports `main.js` from the modules with each engine, which must result in the same last
+    /// result recorded, or value thrown
+    #[track_caller]
+    fn assert_module_result_equals(modules: &[(&str, &str)], expected_result: JsStatementResult) {
+        for engine in JsEngine::ALL {
+            let mut js_document = JsDocument::new(vec![]);
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            closure_context.modules.loader = get_module_loader(modules);
+            closure_context.modules.base_url = Some("https://example.com/".to_string());
+            let result = match js_document.run_module("./main.js") {
+                Ok(_namespace) => js_document
+                    .global_closure_context
+                    .expression_results
+                    .pop()
+                    .unwrap(),
+                Err(value) => JsStatementResult::ThrowValue(value),
+            };
+            assert_eq!(result, expected_result, "{engine:?}");
+        }
     }
 
     fn result_as_number(result: &JsStatementResult) -> f64 {
@@ -296,331 +438,1738 @@ mod tests {
             "atob(btoa('a' + 'b' + 'c') + 'de')",
             vec![JsStatementResult::string(&node_graph, "abcu")],
         );
-    }
-
-    #[test]
-    pub fn test_function_declaration() {
-        let node_graph = get_node_graph();
-        run_test(
-            "function foo(){}",
-            vec![JsStatementResult::Value(GcNodeGraph::create_node(
-                &node_graph,
-                JsValue::Function(JsFunction::UserDefined(
-                    "function foo(){}".to_string(),
-                    "foo".to_string(),
-                    vec![],
-                    vec![],
-                )),
-            ))],
+        assert_last_value_equals("btoa(1)", JsStatementResult::string(&node_graph, "MQ=="));
+        assert_last_value_equals(
+            "atob('%')",
+            JsStatementResult::ThrowValue(JsValue::type_error_or_dom_exception_rc(&node_graph)),
         );
-        run_test(
-            "function foo(arg1, arg2) { arg1 + arg2; }",
-            vec![JsStatementResult::Value(GcNodeGraph::create_node(
-                &node_graph,
-                JsValue::Function(JsFunction::UserDefined(
-                    "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
-                    "foo".to_string(),
-                    vec!["arg1".to_string(), "arg2".to_string()],
-                    vec![JsStatement::Expression(JsExpression::Add(
-                        Box::new(JsExpression::Reference("arg1".to_string())),
-                        Box::new(JsExpression::Reference("arg2".to_string())),
-                    ))],
-                )),
-            ))],
+        assert_last_value_equals(
+            "atob()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
         );
-        run_test(
-            "function foo(arg1, arg2) { arg1 + arg2; };function bar(arg1, arg2) { arg1 + arg2; }",
-            vec![
-                JsStatementResult::Value(GcNodeGraph::create_node(
-                    &node_graph,
-                    JsValue::Function(JsFunction::UserDefined(
-                        "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
-                        "foo".to_string(),
-                        vec!["arg1".to_string(), "arg2".to_string()],
-                        vec![JsStatement::Expression(JsExpression::Add(
-                            Box::new(JsExpression::Reference("arg1".to_string())),
-                            Box::new(JsExpression::Reference("arg2".to_string())),
-                        ))],
-                    )),
-                )),
-                JsStatementResult::Void,
-                JsStatementResult::Value(GcNodeGraph::create_node(
-                    &node_graph,
-                    JsValue::Function(JsFunction::UserDefined(
-                        "function bar(arg1, arg2) { arg1 + arg2; }".to_string(),
-                        "bar".to_string(),
-                        vec!["arg1".to_string(), "arg2".to_string()],
-                        vec![JsStatement::Expression(JsExpression::Add(
-                            Box::new(JsExpression::Reference("arg1".to_string())),
-                            Box::new(JsExpression::Reference("arg2".to_string())),
-                        ))],
-                    )),
-                )),
-            ],
+        assert_last_value_equals(
+            "btoa(Symbol())",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
         );
     }
 
-    #[test]
-    pub fn test_user_function_invocations() {
-        let node_graph = get_node_graph();
-        run_test(
-            "function foo(arg1, arg2) { arg1 + arg2; } foo(1, 'abc')",
-            vec![
-                JsStatementResult::Value(GcNodeGraph::create_node(
-                    &node_graph,
-                    JsValue::Function(JsFunction::UserDefined(
-                        "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
-                        "foo".to_string(),
-                        vec!["arg1".to_string(), "arg2".to_string()],
-                        vec![JsStatement::Expression(JsExpression::Add(
-                            Box::new(JsExpression::Reference("arg1".to_string())),
-                            Box::new(JsExpression::Reference("arg2".to_string())),
-                        ))],
-                    )),
-                )),
-                JsStatementResult::string(&node_graph, "1abc"),
-                JsStatementResult::undefined(&node_graph),
-            ],
-        );
+    struct Counter {
+        count: f64,
+    }
 
-        run_test(
-            "function foo(arg1, arg2) { arg1 + arg2; return arg1; arg2; return arg2; } foo(1, 'abc')",
-            vec![
-                JsStatementResult::Value(GcNodeGraph::create_node(
-                    &node_graph,
-                    JsValue::Function(JsFunction::UserDefined(
-                    "function foo(arg1, arg2) { arg1 + arg2; return arg1; arg2; return arg2; }".to_string(),
-                    "foo".to_string(),
-                    vec!["arg1".to_string(), "arg2".to_string()],
-                    vec![
-                        JsStatement::Expression(JsExpression::Add(
-                            Box::new(JsExpression::Reference("arg1".to_string())),
-                            Box::new(JsExpression::Reference("arg2".to_string())),
-                        )),
-                        JsStatement::Return(JsExpression::Reference("arg1".to_string())),
-                        JsStatement::Expression(JsExpression::Reference("arg2".to_string())),
-                        JsStatement::Return(JsExpression::Reference("arg2".to_string())),
-                    ],
-                )))),
-                JsStatementResult::string(&node_graph, "1abc"),
-                JsStatementResult::number(&node_graph, 1),
-            ],
-        );
+    #[js_class]
+    impl Counter {
+        #[js_method(getter)]
+        fn count(&self) -> f64 {
+            self.count
+        }
+
+        #[js_method(setter)]
+        fn set_count(&mut self, count: f64) {
+            self.count = count;
+        }
+
+        #[js_method(name = "add")]
+        fn increment(&mut self, by: Option<f64>) -> f64 {
+            self.count += by.unwrap_or(1.0);
+            self.count
+        }
+
+        #[js_method]
+        fn is_positive(&self) -> bool {
+            self.count > 0.0
+        }
+
+        #[js_method]
+        fn count_to(
+            &self,
+            closure_context: &mut JsClosureContext,
+            limit: u32,
+        ) -> Result<JsValueNode, JsValueNode> {
+            if f64::from(limit) < self.count {
+                return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+            }
+            let numbers = (self.count as u32..=limit)
+                .map(|number| JsValue::number_rc(&closure_context.nodes_graph, number))
+                .collect();
+            Ok(JsValue::array_rc(&closure_context.nodes_graph, numbers))
+        }
     }
 
     #[test]
-    fn test_undefined() {
+    fn test_host_class() {
         let node_graph = get_node_graph();
-        run_test("undefined", vec![JsStatementResult::undefined(&node_graph)]);
-        run_test(
-            "undefined; undefined",
-            vec![
-                JsStatementResult::undefined(&node_graph),
-                JsStatementResult::undefined(&node_graph),
-            ],
+        let create_counter = |closure_context: &mut JsClosureContext| {
+            closure_context.create_host_object(Counter { count: 1.0 })
+        };
+        let assert_counter_result_equals = |script: &str, expected_result| {
+            assert_host_result_equals(script, create_counter, expected_result)
+        };
+
+        assert_counter_result_equals("host.count", JsStatementResult::number(&node_graph, 1));
+        assert_counter_result_equals(
+            "host.count = '5'; host.count",
+            JsStatementResult::number(&node_graph, 5),
         );
-        run_test(
-            "var a; a = undefined; a",
-            vec![
-                JsStatementResult::undefined(&node_graph),
-                JsStatementResult::undefined(&node_graph),
-                JsStatementResult::undefined(&node_graph),
-            ],
+        assert_counter_result_equals(
+            "host.add(); host.add('2')",
+            JsStatementResult::number(&node_graph, 4),
         );
-        assert!(run_js("undefined+undefined*undefined+undefined",).1[0].is_nan());
-        run_test(
-            "var undefined_var = 1; undefined_var",
-            vec![
-                JsStatementResult::number(&node_graph, 1),
-                JsStatementResult::number(&node_graph, 1),
-            ],
+        assert_counter_result_equals(
+            "host.count = -2; host.isPositive()",
+            JsStatementResult::bool(&node_graph, false),
+        );
+        assert_counter_result_equals(
+            "'' + host.countTo(3)",
+            JsStatementResult::string(&node_graph, "1,2,3"),
+        );
+        assert_counter_result_equals(
+            "host.count = 2; host.countTo(1)",
+            JsStatementResult::ThrowValue(JsValue::range_error_rc(&node_graph)),
+        );
+        assert_counter_result_equals(
+            "host.countTo()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_counter_result_equals(
+            "let other = {'add': host.add}; other.add()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_counter_result_equals(
+            "'' + host",
+            JsStatementResult::string(&node_graph, "[object Counter]"),
+        );
+        assert_counter_result_equals(
+            "Object.getPrototypeOf(host).hasOwnProperty('add')",
+            JsStatementResult::bool(&node_graph, true),
         );
     }
 
     #[test]
-    fn test_bool() {
+    fn test_storage() {
         let node_graph = get_node_graph();
-        run_test("true", vec![JsStatementResult::bool(&node_graph, true)]);
-        run_test("false", vec![JsStatementResult::bool(&node_graph, false)]);
-        run_test(
-            "false + true;",
-            vec![JsStatementResult::number(&node_graph, 1)],
+        let create_storage = |closure_context: &mut JsClosureContext| {
+            let area = StorageArea::with_quota(Box::<InMemoryStorage>::default(), 10);
+            let storage = JsStorage::new(Rc::new(RefCell::new(area)), StorageListener::default());
+            closure_context.create_host_object_with_named_properties(storage)
+        };
+        let assert_storage_result_equals = |script: &str, expected_result| {
+            assert_host_result_equals(script, create_storage, expected_result)
+        };
+
+        assert_storage_result_equals("host.length", JsStatementResult::number(&node_graph, 0));
+        assert_storage_result_equals(
+            "host.setItem('a', 1); host.setItem('b', true); host.length",
+            JsStatementResult::number(&node_graph, 2),
         );
-        run_test(
-            "true + false",
-            vec![JsStatementResult::number(&node_graph, 1)],
+        assert_storage_result_equals(
+            "host.setItem('a', 1); host.getItem('a')",
+            JsStatementResult::string(&node_graph, "1"),
         );
-        run_test(
-            "1 * false",
-            vec![JsStatementResult::number(&node_graph, 0.0)],
+        assert_storage_result_equals(
+            "host.setItem('a', 1); host.key(0)",
+            JsStatementResult::string(&node_graph, "a"),
         );
-        assert!(run_js("undefined * false").1[0].is_nan());
-        run_test(
-            "true + '2'",
-            vec![JsStatementResult::string(&node_graph, "true2")],
+        assert_storage_result_equals("host.getItem('a')", JsStatementResult::null(&node_graph));
+        assert_storage_result_equals(
+            "host.setItem('a', 1); host.removeItem('a'); host.getItem('a')",
+            JsStatementResult::null(&node_graph),
+        );
+        assert_storage_result_equals(
+            "host.setItem('a', 1); host.clear(); host.length",
+            JsStatementResult::number(&node_graph, 0),
+        );
+        assert_storage_result_equals(
+            "host.setItem('a')",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_storage_result_equals(
+            "'' + host",
+            JsStatementResult::string(&node_graph, "[object Storage]"),
+        );
+
+        let string_cases = [
+            ("host.a = 1; host.getItem('a')", "1"),
+            ("host.setItem('a', 'b'); host.a", "b"),
+            ("host['a b'] = 'c'; host['a b']", "c"),
+            (
+                "host.setItem('key', 'a'); '' + (host.key === Object.getPrototypeOf(host).key)",
+                "true",
+            ),
+            (
+                "host.key = 'a'; \
+                host.getItem('key') + (host.key === Object.getPrototypeOf(host).key)",
+                "atrue",
+            ),
+            (
+                "host.a = 1; Reflect.deleteProperty(host, 'a'); '' + host.getItem('a')",
+                "null",
+            ),
+            (
+                "Object.defineProperty(host, 'a', { value: 'd' }); host.a",
+                "d",
+            ),
+            (
+                "host.a = 1; '' + Reflect.has(host, 'a') + Reflect.has(host, 'b')",
+                "truefalse",
+            ),
+            ("host.a = '12345'; host.b = '123'", "123"),
+        ];
+        for (script, expected) in string_cases {
+            assert_storage_result_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+        assert_storage_result_equals(
+            "host.a = 1; host.b = 2; Object.keys(host).length",
+            JsStatementResult::number(&node_graph, 2),
+        );
+        assert_storage_result_equals(
+            "host.a = '1234567890'",
+            JsStatementResult::ThrowValue(JsValue::quota_exceeded_error_rc(&node_graph)),
+        );
+        assert_storage_result_equals(
+            "host.setItem('a', '12345'); host.setItem('b', '12345')",
+            JsStatementResult::ThrowValue(JsValue::quota_exceeded_error_rc(&node_graph)),
         );
     }
 
     #[test]
-    fn test_null() {
+    fn test_window_storage() {
         let node_graph = get_node_graph();
-        run_test("null", vec![JsStatementResult::null(&node_graph)]);
-        run_test("null;", vec![JsStatementResult::null(&node_graph)]);
-        run_test("1 + null;", vec![JsStatementResult::number(&node_graph, 1)]);
-        run_test("3 * null", vec![JsStatementResult::number(&node_graph, 0)]);
-        assert!(run_js("null + undefined").1[0].is_nan());
-        run_test(
-            "null + ''",
-            vec![JsStatementResult::string(&node_graph, "null")],
+        let html = "<body></body>";
+
+        let string_cases = [
+            (
+                "sessionStorage.a = 1; window.sessionStorage.getItem('a')",
+                "1",
+            ),
+            ("'' + (window.sessionStorage === sessionStorage)", "true"),
+        ];
+        for (script, expected) in string_cases {
+            assert_dom_result_equals(
+                html,
+                script,
+                JsStatementResult::string(&node_graph, expected),
+            );
+        }
+        // Documents without an origin have no local storage
+        assert_dom_result_equals(
+            html,
+            "window.localStorage",
+            JsStatementResult::ThrowValue(JsValue::security_error_rc(&node_graph)),
         );
-        run_test("null * ''", vec![JsStatementResult::number(&node_graph, 0)]);
     }
 
     #[test]
-    fn test_nan() {
-        let _node_graph = get_node_graph();
-        assert!(run_js("NaN").1[0].is_nan());
-        assert!(run_js("NaN * NaN + NaN").1[0].is_nan());
+    fn test_storage_events() {
+        for engine in JsEngine::ALL {
+            let local = Rc::new(RefCell::new(StorageArea::new(
+                Box::<InMemoryStorage>::default(),
+            )));
+            let create_document = |url: &str| {
+                let mut js_document = JsDocument::new(vec![]);
+                js_document.global_closure_context.engine = engine;
+                let session = StorageArea::new(Box::<InMemoryStorage>::default());
+                js_document.set_storage(url, Some(local.clone()), Rc::new(RefCell::new(session)));
+                js_document.set_html_document(Rc::new(RefCell::new(parse_html("").unwrap())));
+                js_document
+            };
+            let mut first = create_document("http://a.com/first");
+            let mut second = create_document("http://a.com/second");
+            second
+                .run_script(
+                    "var log = ''; \
+                    function record(e) { \
+                        log = log + e.key + ',' + e.oldValue + ',' + e.newValue + ',' + e.url + \
+                        ';' + (e.storageArea === localStorage) + ';'; } \
+                    addEventListener('storage', record);",
+                )
+                .unwrap();
+
+            first
+                .run_script(
+                    "localStorage.a = 1; localStorage.setItem('a', 2); sessionStorage.b = 3; \
+                    localStorage.clear();",
+                )
+                .unwrap();
+            assert!(!first.dispatch_storage_events());
+            assert!(second.dispatch_storage_events());
+            assert!(!second.dispatch_storage_events());
+
+            let result = second.run_script("log").unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "a,null,1,http://a.com/first;true;a,1,2,http://a.com/first;true;\
+                null,null,null,http://a.com/first;true;",
+                "{engine:?}"
+            );
+            let result = second.run_script("localStorage.length").unwrap();
+            assert_eq!(result.get_ref().to_string(), "0", "{engine:?}");
+        }
     }
 
     #[test]
-    fn test_if_statements() {
+    fn test_dom() {
         let node_graph = get_node_graph();
-        run_test(
-            "if(true){1;}",
-            vec![
-                JsStatementResult::number(&node_graph, 1),
-                JsStatementResult::Void,
-            ],
-        );
-        run_test(
-            "if(true)1",
-            vec![
-                JsStatementResult::number(&node_graph, 1),
-                JsStatementResult::Void,
-            ],
-        );
-        run_test(
-            "if(true)var i;2",
-            vec![
-                JsStatementResult::undefined(&node_graph),
-                JsStatementResult::Void,
-                JsStatementResult::number(&node_graph, 2),
-            ],
+        let html =
+            r#"<body><div id="a" class="one two">x</div><div class="two">y<b>z</b></div></body>"#;
+
+        let string_cases = [
+            ("document.getElementById('a').textContent", "x"),
+            ("document.body.textContent", "xyz"),
+            ("document.documentElement.tagName", "HTML"),
+            (
+                "document.body.childNodes[1].childNodes[0].nodeName",
+                "#text",
+            ),
+            (
+                "document.getElementsByClassName('two')[1].innerHTML",
+                "y<b>z</b>",
+            ),
+            (
+                "document.getElementById('a').getAttribute('class')",
+                "one two",
+            ),
+            (
+                "var p = document.createElement('P'); p.textContent = 'new'; \
+                document.body.appendChild(p); document.body.innerHTML",
+                r#"<div id="a" class="one two">x</div><div class="two">y<b>z</b></div><p>new</p>"#,
+            ),
+            (
+                "var a = document.getElementById('a'); \
+                a.insertBefore(document.createTextNode('w'), a.childNodes[0]); a.innerHTML",
+                "wx",
+            ),
+            (
+                "var b = document.getElementsByTagName('b')[0]; \
+                document.body.insertBefore(b, document.getElementById('a')); \
+                b.parentNode.tagName + document.body.childNodes.length",
+                "BODY3",
+            ),
+            (
+                "var a = document.getElementById('a'); a.innerHTML = '<i>1</i>2'; \
+                a.childNodes[0].tagName + a.childNodes[1].textContent",
+                "I2",
+            ),
+            (
+                "var list = document.getElementById('a').classList; list.add('three'); \
+                list.remove('one'); list.toggle('two'); list.value",
+                "three",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_dom_result_equals(
+                html,
+                script,
+                JsStatementResult::string(&node_graph, expected),
+            );
+        }
+
+        let bool_cases = [
+            (
+                "document.getElementById('a') === document.body.childNodes[0]",
+                true,
+            ),
+            (
+                "document.getElementById('a').parentNode === document.body",
+                true,
+            ),
+            (
+                "document.getElementById('a').classList.contains('two')",
+                true,
+            ),
+            ("document.getElementById('a').hasAttribute('id')", true),
+        ];
+        for (script, expected) in bool_cases {
+            assert_dom_result_equals(html, script, JsStatementResult::bool(&node_graph, expected));
+        }
+
+        let null_cases = [
+            "document.getElementById('missing')",
+            "document.getElementById('a').getAttribute('missing')",
+            "document.createElement('div').parentNode",
+            "document.head",
+            "document.body.childNodes[1].childNodes[0].tagName",
+        ];
+        for script in null_cases {
+            assert_dom_result_equals(html, script, JsStatementResult::null(&node_graph));
+        }
+
+        let error_cases = [
+            "document.body.appendChild(1)",
+            "document.getElementById('a').appendChild(document.body)",
+            "document.body.appendChild(document.body)",
+            "document.getElementById('a').removeChild(document.body)",
+            "document.body.childNodes[1].childNodes[0].setAttribute('a', 'b')",
+            "document.createTextNode('a').appendChild(document.createElement('b'))",
+            "document.body.insertBefore(document.createElement('b'), document.getElementsByTagName('b')[0])",
+        ];
+        for script in error_cases {
+            assert_dom_result_equals(
+                html,
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+
+        // Changes are made to the shared document
+        let document = assert_dom_result_equals(
+            html,
+            "var a = document.getElementById('a'); a.setAttribute('title', 'hi'); \
+            document.body.removeChild(document.body.childNodes[1]); a.nodeType",
+            JsStatementResult::number(&node_graph, 1),
         );
-        run_test(
-            "1; if (null * undefined) { 2; } 3",
-            vec![
-                JsStatementResult::number(&node_graph, 1),
-                JsStatementResult::Void,
-                JsStatementResult::number(&node_graph, 3),
-            ],
+        assert_eq!(
+            document,
+            r#"<html><body><div id="a" class="one two" title="hi">x</div></body></html>"#
         );
     }
 
     #[test]
-    fn test_else_statements() {
+    fn test_events() {
         let node_graph = get_node_graph();
-        run_test(
-            "if(true){1;} else {2;}",
-            vec![
-                JsStatementResult::number(&node_graph, 1),
-                JsStatementResult::Void,
-            ],
-        );
-        run_test(
-            "if(false){1;} else {2;}",
-            vec![
-                JsStatementResult::number(&node_graph, 2),
-                JsStatementResult::Void,
-            ],
-        );
-        run_test(
-            "if(false)1; else 2;",
-            vec![
-                JsStatementResult::number(&node_graph, 2),
-                JsStatementResult::Void,
-            ],
+        let html = r#"<body><div id="a"><b id="b">x</b></div></body>"#;
+        let setup = "var log = ''; var a = document.getElementById('a'); \
+            var b = document.getElementById('b'); \
+            function add(entry) { log = log ? log + ',' + entry : entry; } \
+            function record(name) { \
+                return { name: name, handleEvent(e) { add(this.name + e.eventPhase); } }; \
+            } ";
+
+        let string_cases = [
+            (
+                "window.addEventListener('go', record('w')); \
+                document.addEventListener('go', record('d'), true); \
+                a.addEventListener('go', record('a'), { capture: true }); \
+                a.addEventListener('go', record('A')); \
+                b.addEventListener('go', record('b')); \
+                b.dispatchEvent(new Event('go', { bubbles: true })); log",
+                "d1,a1,b2,A3,w3",
+            ),
+            (
+                "a.addEventListener('go', record('a')); b.addEventListener('go', record('b')); \
+                b.dispatchEvent(new Event('go')); log",
+                "b2",
+            ),
+            (
+                "function stop(e) { \
+                add(e.target.getAttribute('id') + e.currentTarget.getAttribute('id')); \
+                e.stopPropagation(); } \
+                a.addEventListener('go', stop); \
+                document.addEventListener('go', record('d')); \
+                b.dispatchEvent(new Event('go', { bubbles: true })); log",
+                "ba",
+            ),
+            (
+                "function stop(e) { e.stopImmediatePropagation(); } \
+                b.addEventListener('go', stop); \
+                b.addEventListener('go', record('b')); \
+                b.dispatchEvent(new Event('go')); log",
+                "",
+            ),
+            (
+                "b.addEventListener('go', record('b'), { once: true }); \
+                var listener = record('c'); b.addEventListener('go', listener); \
+                b.addEventListener('go', listener); b.dispatchEvent(new Event('go')); \
+                b.removeEventListener('go', listener); b.dispatchEvent(new Event('go')); \
+                log",
+                "b2,c2",
+            ),
+            (
+                "b.addEventListener('go', { handleEvent(e) { add(e.type); } }); \
+                addEventListener('go', record('w')); \
+                b.dispatchEvent(new Event('go', { bubbles: true })); log",
+                "go,w3",
+            ),
+            (
+                "function fail() { throw new Error('ignored'); } b.addEventListener('go', fail); \
+                b.addEventListener('go', record('b')); b.dispatchEvent(new Event('go')); \
+                log",
+                "b2",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_dom_result_equals(
+                html,
+                &format!("{setup}{script}"),
+                JsStatementResult::string(&node_graph, expected),
+            );
+        }
+
+        let bool_cases = [
+            (
+                "function cancel(e) { e.preventDefault(); } b.addEventListener('go', cancel); \
+                b.dispatchEvent(new Event('go', { cancelable: true }))",
+                false,
+            ),
+            (
+                "function cancel(e) { e.preventDefault(); } b.addEventListener('go', cancel); \
+                b.dispatchEvent(new Event('go'))",
+                true,
+            ),
+            ("new Event('go').isTrusted", false),
+            ("window.document === document", true),
+            ("window.window === window", true),
+        ];
+        for (script, expected) in bool_cases {
+            assert_dom_result_equals(
+                html,
+                &format!("{setup}{script}"),
+                JsStatementResult::bool(&node_graph, expected),
+            );
+        }
+
+        assert_dom_result_equals(
+            html,
+            "new Event()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
         );
-        run_test(
-            "if(false)var i;else 2",
-            vec![
-                JsStatementResult::number(&node_graph, 2),
+    }
+
+    #[test]
+    fn test_dispatch_browser_event() {
+        for engine in JsEngine::ALL {
+            let document = parse_html("<body>a</body>").unwrap();
+            let body = document.html.find_first(|node| {
+                matches!(node, ElementContents::Element(element) if element.tag_name == "body")
+            });
+            let body = body.unwrap().get_id();
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            js_document.set_html_document(Rc::new(RefCell::new(document)));
+
+            js_document
+                .run_script(
+                    "var log; \
+                    function record(e) { \
+                        log = e.isTrusted + ',' + e.clientX + ',' + e.button + ',' + e.shiftKey; } \
+                    function cancel(e) { e.preventDefault(); } \
+                    document.body.addEventListener('click', record); \
+                    document.addEventListener('click', cancel);",
+                )
+                .unwrap();
+            let event = JsEvent::new(
+                "click",
+                true,
+                true,
+                JsEventDetail::Mouse {
+                    client_x: 3.0,
+                    client_y: 4.0,
+                    button: 0,
+                    modifiers: JsEventModifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                },
+            );
+            assert!(!js_document.dispatch_event(JsEventTargetId::Node(body), event));
+            let result = js_document.run_script("log").unwrap();
+            assert_eq!(result.get_ref().to_string(), "true,3,0,true", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_run_script() {
+        for engine in JsEngine::ALL {
+            let document = Rc::new(RefCell::new(parse_html("<body>a</body>").unwrap()));
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            js_document.set_html_document(document.clone());
+
+            js_document.run_script("var count = 1").unwrap();
+            assert!(js_document.run_script("throw new TypeError()").is_err());
+            assert!(js_document.run_script("return 1").is_err());
+            let result = js_document
+                .run_script("count = count + 1; document.body.textContent = 'b' + count")
+                .unwrap();
+            assert_eq!(result.get_ref().to_string(), "b2", "{engine:?}");
+            assert_eq!(
+                document.borrow().html.to_string(),
+                "<html><body>b2</body></html>"
+            );
+        }
+    }
+
+    /// Runs the script with each engine, then results in `log` once the jobs it queued ran
+    #[track_caller]
+    fn assert_log_equals(script: &str, expected_log: &str) {
+        for engine in JsEngine::ALL {
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            js_document.run_script("var log = ''").unwrap();
+            js_document.run_script(script).unwrap();
+            let log = js_document.run_script("log").unwrap();
+            assert_eq!(log.get_ref().to_string(), expected_log, "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_promises() {
+        let setup = "function add(value) { log = log + value; } ";
+        let cases = [
+            (
+                "function next(v) { add(v); return v + 1; } \
+                Promise.resolve(1).then(next).then(add); add('sync');",
+                "sync12",
+            ),
+            ("Promise.reject('a').catch(add)", "a"),
+            (
+                "function settle(resolve) { resolve('a'); resolve('b'); } \
+                new Promise(settle).then(add)",
+                "a",
+            ),
+            (
+                "function fail() { throw 'a'; } new Promise(fail).catch(add)",
+                "a",
+            ),
+            (
+                "Promise.resolve({ then(resolve) { resolve('a'); } }).then(add)",
+                "a",
+            ),
+            (
+                "function fail() { throw 'a'; } Promise.resolve().then(fail).then(null, add)",
+                "a",
+            ),
+            (
+                "function inner() { return Promise.resolve('b'); } function first() { add('a'); } \
+                Promise.resolve().then(inner).then(add); Promise.resolve().then(first);",
+                "ab",
+            ),
+            (
+                "var p = Promise.resolve(1); add(Promise.resolve(p) === p);",
+                "true",
+            ),
+            (
+                "var resolveP; function capture(resolve) { resolveP = resolve; } \
+                var p = new Promise(capture); resolveP(p); p.catch(add)",
+                "TypeError",
+            ),
+        ];
+        for (script, expected_log) in cases {
+            assert_log_equals(&format!("{setup}{script}"), expected_log);
+        }
+
+        let mut js_document = JsDocument::new(vec![]);
+        assert!(js_document
+            .run_script("function executor() {} Promise(executor)")
+            .is_err());
+        assert!(js_document.run_script("new Promise(1)").is_err());
+    }
+
+    #[test]
+    fn test_headers() {
+        let node_graph = get_node_graph();
+        let string_cases = [
+            (
+                "var h = new Headers({ 'Content-Type': 'text/plain' }); h.append('X-A', '1'); \
+                h.append('x-a', ' 2 '); h.get('content-type') + ';' + h.get('X-A')",
+                "text/plain;1, 2",
+            ),
+            ("'' + new Headers([['a', 'b']]).has('A')", "true"),
+            (
+                "var h = new Headers(); h.set('a', '1'); h.set('a', '2'); h.delete('b'); \
+                h.get('a') + h.get('b')",
+                "2null",
+            ),
+            ("new Headers(new Headers({ a: '1' })).get('a')", "1"),
+            (
+                "var r = new Request('http://a.com/', { headers: { host: 'b', 'x-a': '1' } }); \
+                '' + r.headers.has('host') + r.headers.has('x-a')",
+                "falsetrue",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+
+        let type_error_cases = [
+            "new Headers().append('a b', 'c')",
+            "new Headers().set('', 'a')",
+            "new Headers([['a']])",
+            "new Headers(1)",
+        ];
+        for script in type_error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_response() {
+        let node_graph = get_node_graph();
+        let string_cases = [
+            (
+                "var r = new Response('a'); r.status + ' ' + r.ok + ' ' + r.type",
+                "200 true default",
+            ),
+            (
+                "var r = new Response(null, { status: 404, statusText: 'Not Found' }); \
+                r.status + ' ' + r.ok + ' ' + r.statusText",
+                "404 false Not Found",
+            ),
+            (
+                "new Response('a').headers.get('content-type')",
+                "text/plain;charset=UTF-8",
+            ),
+            (
+                "new Response('a', { headers: { 'Content-Type': 'text/html' } }) \
+                .headers.get('content-type')",
+                "text/html",
+            ),
+            (
+                "var r = new Request('http://a.com:8000/x?y', { method: 'post', body: 'b' }); \
+                r.method + ' ' + r.url + ' ' + r.bodyUsed",
+                "POST http://a.com:8000/x?y false",
+            ),
+            ("new Request('http://a.com/x').url", "http://a.com/x"),
+            (
+                "var r = new Request(new Request('http://a.com:8000/', { method: 'PUT' })); \
+                r.method",
+                "PUT",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+
+        assert_last_value_equals(
+            "new Response('a', { status: 100 })",
+            JsStatementResult::ThrowValue(JsValue::range_error_rc(&node_graph)),
+        );
+        let type_error_cases = [
+            "new Response('a', { status: 204 })",
+            "new Request('http://a.com/', { body: 'a' })",
+            "new Request('http://a.com/', { method: 'CONNECT' })",
+            "new Request('/a')",
+            "Response()",
+        ];
+        for script in type_error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+
+        let setup = "function add(value) { log = log + value; } ";
+        let log_cases = [
+            (
+                "var r = new Response('{\"a\": [1, 2]}'); \
+                function read(v) { add(v.a[1]); add(r.bodyUsed); } \
+                r.json().then(read); r.text().catch(add);",
+                "2trueTypeError",
+            ),
+            (
+                "function read(b) { add(new Uint8Array(b)[2]); } \
+                new Response('abc').arrayBuffer().then(read)",
+                "99",
+            ),
+            ("new Response('{').json().catch(add)", "SyntaxError"),
+            (
+                "new Request('http://a.com/', { method: 'POST', body: 'a' }).text().then(add)",
+                "a",
+            ),
+            ("fetch('file:///a').catch(add)", "TypeError"),
+        ];
+        for (script, expected_log) in log_cases {
+            assert_log_equals(&format!("{setup}{script}"), expected_log);
+        }
+    }
+
+    /// Serves the responses in order to the connections made to a loopback port, sending each
+    /// request it receives through the channel. Results in the URL of the server.
+    fn serve_http(responses: Vec<String>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _address) = listener.accept().unwrap();
+                let request = read_http_request(&mut stream);
+                stream.write_all(response.as_bytes()).unwrap();
+                let _ = sender.send(request);
+            }
+        });
+        (server, receiver)
+    }
+
+    /// Reads a request up to the end of its body, which is as long as its `Content-Length`
+    fn read_http_request(stream: &mut TcpStream) -> String {
+        let mut request = String::new();
+        let mut buffer = [0; 512];
+        loop {
+            if let Some(end) = request.find("\r\n\r\n") {
+                let length = request[..end]
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .map_or(0, |length| length.parse().unwrap());
+                if request.len() >= end + 4 + length {
+                    return request;
+                }
+            }
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                return request;
+            }
+            request += std::str::from_utf8(&buffer[..read]).unwrap();
+        }
+    }
+
+    fn http_response(status: &str, headers: &[&str], body: &str) -> String {
+        let mut response = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\n", body.len());
+        for header in headers {
+            response += &format!("{header}\r\n");
+        }
+        response + "\r\n" + body
+    }
+
+    /// Runs the script in a document at the URL with `server` declared as the URL of the server,
+    /// processing its fetches until none are left. Results in `log`.
+    fn run_fetch_script(
+        engine: JsEngine,
+        document_url: &str,
+        server: &str,
+        script: &str,
+    ) -> String {
+        let mut js_document = JsDocument::new(vec![]);
+        let closure_context = &mut js_document.global_closure_context;
+        closure_context.engine = engine;
+        let server = JsValue::string_rc(&closure_context.nodes_graph, server.to_string());
+        closure_context.get_or_declare_reference_mut("server").value = server;
+        js_document.set_url(&Url::parse(document_url).unwrap());
+        js_document.run_script("var log = ''").unwrap();
+        js_document.run_script(script).unwrap();
+
+        let start = Instant::now();
+        while js_document.global_closure_context.fetches.has_pending() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Fetches timed out"
+            );
+            js_document.process_fetches();
+            thread::sleep(Duration::from_millis(5));
+        }
+        js_document.run_script("log").unwrap().get_ref().to_string()
+    }
+
+    #[test]
+    fn test_fetch_same_origin() {
+        for engine in JsEngine::ALL {
+            let (server, requests) = serve_http(vec![http_response(
+                "200 OK",
+                &["Content-Type: application/json", "X-Secret: s"],
+                r#"{"a": 1}"#,
+            )]);
+            let log = run_fetch_script(
+                engine,
+                &format!("{server}/dir/page.html"),
+                &server,
+                "function read(r) { \
+                    log = log + r.status + r.ok + r.type + r.headers.get('x-secret') + r.url; \
+                    return r.json(); \
+                } \
+                function add(v) { log = log + v.a; } \
+                fetch('data.json?b', { headers: { 'X-Custom': 'c' } }).then(read).then(add);",
+            );
+            assert_eq!(
+                log,
+                format!("200truebasics{server}/dir/data.json?b1"),
+                "{engine:?}"
+            );
+
+            let request = requests.recv().unwrap();
+            assert!(request.starts_with("GET /dir/data.json?b HTTP/1.1\r\n"));
+            assert!(request.contains("\r\nx-custom: c\r\n"), "{request}");
+            assert!(!request.contains("origin"), "{request}");
+        }
+    }
+
+    #[test]
+    fn test_fetch_cors() {
+        for engine in JsEngine::ALL {
+            let (server, requests) = serve_http(vec![
+                http_response(
+                    "200 OK",
+                    &[
+                        "Access-Control-Allow-Origin: http://a.com",
+                        "Access-Control-Expose-Headers: X-Exposed",
+                        "Content-Type: text/plain",
+                        "X-Exposed: e",
+                        "X-Secret: s",
+                    ],
+                    "hi",
+                ),
+                http_response("200 OK", &["Access-Control-Allow-Origin: http://b.com"], ""),
+            ]);
+            let log = run_fetch_script(
+                engine,
+                "http://a.com/",
+                &server,
+                "function read(r) { \
+                    log = log + r.type + r.headers.get('x-secret') + r.headers.get('x-exposed') + \
+                        r.headers.get('content-type') + ';'; \
+                    return r.text(); \
+                } \
+                function next(v) { log = log + v + ';'; return fetch(server + '/denied'); } \
+                function fail(e) { log = log + e; } \
+                fetch(server + '/allowed', { method: 'POST', body: 'a' }) \
+                .then(read).then(next).catch(fail);",
+            );
+            assert_eq!(log, "corsnulletext/plain;hi;TypeError", "{engine:?}");
+
+            let request = requests.recv().unwrap();
+            assert!(request.starts_with("POST /allowed HTTP/1.1\r\n"));
+            assert!(
+                request.contains("\r\norigin: http://a.com\r\n"),
+                "{request}"
+            );
+            assert!(request.ends_with("\r\n\r\na"), "{request}");
+        }
+    }
+
+    #[test]
+    fn test_fetch_preflight() {
+        for engine in JsEngine::ALL {
+            let (server, requests) = serve_http(vec![
+                http_response(
+                    "204 No Content",
+                    &[
+                        "Access-Control-Allow-Origin: http://a.com",
+                        "Access-Control-Allow-Methods: PUT",
+                        "Access-Control-Allow-Headers: X-A",
+                    ],
+                    "",
+                ),
+                http_response("200 OK", &["Access-Control-Allow-Origin: *"], "done"),
+                http_response("204 No Content", &["Access-Control-Allow-Origin: *"], ""),
+            ]);
+            let log = run_fetch_script(
+                engine,
+                "http://a.com/",
+                &server,
+                "function read(r) { return r.text(); } \
+                function next(v) { \
+                    log = log + v + ';'; \
+                    return fetch(server + '/delete', { method: 'DELETE' }); \
+                } \
+                function fail(e) { log = log + e; } \
+                fetch(server + '/put', { method: 'PUT', headers: { 'X-A': '1' }, body: 'b' }) \
+                .then(read).then(next).catch(fail);",
+            );
+            assert_eq!(log, "done;TypeError", "{engine:?}");
+
+            let preflight = requests.recv().unwrap();
+            assert!(preflight.starts_with("OPTIONS /put HTTP/1.1\r\n"));
+            assert!(preflight.contains("\r\naccess-control-request-method: PUT\r\n"));
+            assert!(preflight.contains("\r\naccess-control-request-headers: x-a\r\n"));
+            let request = requests.recv().unwrap();
+            assert!(request.starts_with("PUT /put HTTP/1.1\r\n"));
+            assert!(request.contains("\r\nx-a: 1\r\n"), "{request}");
+            assert!(request.ends_with("\r\n\r\nb"), "{request}");
+            // The preflight of the DELETE request does not allow it, so it is not sent
+            let preflight = requests.recv().unwrap();
+            assert!(preflight.starts_with("OPTIONS /delete HTTP/1.1\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_fetch_network_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        for engine in JsEngine::ALL {
+            let log = run_fetch_script(
+                engine,
+                &format!("{server}/"),
+                &server,
+                "function fail(e) { log = log + e; } fetch('/a').catch(fail)",
+            );
+            assert_eq!(log, "TypeError", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_xml_http_request() {
+        for engine in JsEngine::ALL {
+            let (server, requests) =
+                serve_http(vec![http_response("200 OK", &["X-A: a"], r#"{"a": 1}"#)]);
+            let log = run_fetch_script(
+                engine,
+                &format!("{server}/dir/page.html"),
+                &server,
+                "var xhr = new XMLHttpRequest(); \
+                log = log + xhr.readyState + XMLHttpRequest.DONE + ';'; \
+                function onReadyStateChange() { log = log + this.readyState; } \
+                function onProgress(e) { \
+                    log = log + ';' + e.loaded + '/' + e.total + e.lengthComputable; \
+                } \
+                function onLoad() { \
+                    log = log + ';' + xhr.status + xhr.statusText + xhr.getResponseHeader('X-A') + \
+                        xhr.response.a + xhr.responseURL; \
+                } \
+                function onLoadEnd(e) { log = log + ';' + e.type; } \
+                xhr.onreadystatechange = onReadyStateChange; \
+                xhr.onprogress = onProgress; \
+                xhr.onload = onLoad; \
+                xhr.onloadend = onLoadEnd; \
+                xhr.open('POST', 'data.json'); \
+                xhr.setRequestHeader('X-Custom', 'c'); \
+                xhr.responseType = 'json'; \
+                xhr.send('body'); \
+                log = log + ';sent';",
+            );
+            assert_eq!(
+                log,
+                format!("04;1;sent23;8/8true4;200OKa1{server}/dir/data.json;loadend"),
+                "{engine:?}"
+            );
+
+            let request = requests.recv().unwrap();
+            assert!(request.starts_with("POST /dir/data.json HTTP/1.1\r\n"));
+            assert!(request.contains("\r\nx-custom: c\r\n"), "{request}");
+            assert!(
+                request.contains("\r\ncontent-type: text/plain;charset=UTF-8\r\n"),
+                "{request}"
+            );
+            assert!(request.ends_with("\r\n\r\nbody"), "{request}");
+        }
+    }
+
+    #[test]
+    fn test_xml_http_request_sync_and_abort() {
+        for engine in JsEngine::ALL {
+            // The aborted request and the last request can reach the server in either order
+            let (server, _requests) = serve_http(vec![
+                http_response("200 OK", &[], "hi"),
+                http_response("200 OK", &[], "abc"),
+                http_response("200 OK", &[], "abc"),
+            ]);
+            let log = run_fetch_script(
+                engine,
+                &format!("{server}/"),
+                &server,
+                "var xhr = new XMLHttpRequest(); \
+                function onReadyStateChange() { log = log + xhr.readyState; } \
+                function onLoad() { log = log + 'load'; } \
+                function onAbort(e) { log = log + e.type + aborted.readyState; } \
+                xhr.onreadystatechange = onReadyStateChange; \
+                xhr.onload = onLoad; \
+                xhr.open('GET', '/a', false); \
+                xhr.send(); \
+                log = log + ';' + xhr.responseText + ';'; \
+                var aborted = new XMLHttpRequest(); \
+                aborted.onabort = onAbort; \
+                aborted.onload = onLoad; \
+                aborted.open('GET', '/b'); \
+                aborted.send(); \
+                aborted.abort(); \
+                log = log + aborted.readyState + aborted.status + ';'; \
+                var buffer = new XMLHttpRequest(); \
+                buffer.responseType = 'arraybuffer'; \
+                buffer.open('GET', '/c', false); \
+                buffer.send(); \
+                log = log + new Uint8Array(buffer.response)[1] + (buffer.response === buffer.response);",
+            );
+            assert_eq!(log, "14load;hi;abort400;98true", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_xml_http_request_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        for engine in JsEngine::ALL {
+            let log = run_fetch_script(
+                engine,
+                &format!("{server}/"),
+                &server,
+                "var xhr = new XMLHttpRequest(); \
+                function onError(e) { log = log + e.type + xhr.readyState + xhr.status; } \
+                function onLoadEnd(e) { log = log + e.type; } \
+                function send() { sync.send(); } \
+                function fail(e) { log = log + e + sync.readyState + ';'; } \
+                xhr.onerror = onError; \
+                xhr.onloadend = onLoadEnd; \
+                xhr.open('GET', '/a'); \
+                xhr.send(); \
+                var sync = new XMLHttpRequest(); \
+                sync.open('GET', '/b', false); \
+                new Promise(send).catch(fail);",
+            );
+            assert_eq!(log, "NetworkError4;error40loadend", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        assert_log_equals(
+            "var u = new URL('../c?x=1#f', 'http://user:pw@Example.COM:8080/a/b'); \
+            log = u.href + '|' + u.origin + '|' + u.protocol + '|' + u.username + '|' + \
+                u.password + '|' + u.host + '|' + u.hostname + '|' + u.port + '|' + \
+                u.pathname + '|' + u.search + '|' + u.hash + ';'; \
+            u.protocol = 'https'; u.port = '443'; u.hostname = 'other.org'; \
+            u.pathname = '/a b/./d/..'; u.hash = ''; u.username = ''; u.password = ''; \
+            log = log + u.toString() + ';' + u.toJSON() + ';'; \
+            u.href = 'data:text/plain,hi'; \
+            log = log + u.origin + u.pathname + ';'; \
+            log = log + new URL('HTTP://[0:0::1]:80/%7e/../x y').href + ';'; \
+            log = log + new URL('file:///C|/a/../../b').href;",
+            "http://user:pw@example.com:8080/c?x=1#f|http://example.com:8080|http:|user|pw|\
+            example.com:8080|example.com|8080|/c|?x=1|#f;\
+            https://other.org/a%20b/?x=1;https://other.org/a%20b/?x=1;\
+            nulltext/plain,hi;\
+            http://[::1]/x%20y;\
+            file:///C:/b",
+        );
+        assert_log_equals(
+            "function add(e) { log = log + e; } \
+            function parse() { new URL('nope'); } \
+            function parseWithBase() { new URL('a', 'nope'); } \
+            function setHref() { new URL('http://a').href = 'b'; } \
+            new Promise(parse).catch(add); \
+            new Promise(parseWithBase).catch(add); \
+            new Promise(setHref).catch(add);",
+            "TypeErrorTypeErrorTypeError",
+        );
+    }
+
+    #[test]
+    fn test_url_search_params() {
+        assert_log_equals(
+            "var u = new URL('http://example.com/?b=1&a=2&b=3'); var p = u.searchParams; \
+            p.append('c', 'x y'); p.set('b', '4'); \
+            log = u.search + ';' + p.get('b') + p.get('z') + p.size + p.has('a') + \
+                p.has('a', '3') + ';'; \
+            p.sort(); log = log + u.href + ';'; \
+            u.search = '?d=1&d=2'; \
+            log = log + p.getAll('d').length + p.get('d') + p.has('b') + ';'; \
+            p.delete('d', '1'); p.delete('x'); log = log + p.toString() + ';'; \
+            p.delete('d'); log = log + u.href + ';'; \
+            log = log + new URLSearchParams('?a=%20&b=+').toString() + ';' + \
+                new URLSearchParams([['a', '1'], ['a', '2']]).toString() + ';' + \
+                new URLSearchParams({ k: 'v&w' }).toString() + ';' + \
+                new URLSearchParams(p).size;",
+            "?b=4&a=2&c=x+y;4null3truefalse;\
+            http://example.com/?a=2&b=4&c=x+y;\
+            21false;d=2;\
+            http://example.com/;\
+            a=+&b=+;a=1&a=2;k=v%26w;0",
+        );
+        assert_log_equals(
+            "function add(e) { log = log + e; } \
+            function create() { new URLSearchParams([['a']]); } \
+            new Promise(create).catch(add);",
+            "TypeError",
+        );
+    }
+
+    /// Creates a document at the URL with a window, which shares the session history
+    fn create_navigation_document(
+        engine: JsEngine,
+        url: &str,
+        history: &Rc<RefCell<SessionHistory>>,
+    ) -> JsDocument {
+        let mut js_document = JsDocument::new(vec![]);
+        js_document.global_closure_context.engine = engine;
+        js_document.set_session_history(history.clone());
+        js_document.set_html_document(Rc::new(RefCell::new(parse_html("").unwrap())));
+        js_document.set_url(&Url::parse(url).unwrap());
+        js_document
+    }
+
+    fn url_record(url: &str) -> UrlRecord {
+        UrlRecord::parse(url, None).unwrap()
+    }
+
+    #[test]
+    fn test_location() {
+        for engine in JsEngine::ALL {
+            let history = Rc::new(RefCell::new(SessionHistory::new()));
+            let mut js_document =
+                create_navigation_document(engine, "http://example.com:8080/a/b?c=d#e", &history);
+            let result = js_document
+                .run_script(
+                    "location.href + '|' + location.origin + '|' + location.protocol + '|' + \
+                    location.host + '|' + location.hostname + '|' + location.port + '|' + \
+                    location.pathname + '|' + location.search + '|' + location.hash + '|' + \
+                    (window.location === location) + '|' + location.toString()",
+                )
+                .unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "http://example.com:8080/a/b?c=d#e|http://example.com:8080|http:|\
+                example.com:8080|example.com|8080|/a/b|?c=d|#e|true|\
+                http://example.com:8080/a/b?c=d#e",
+                "{engine:?}"
+            );
+
+            // Changing only the fragment moves to a new entry of the document right away
+            js_document.run_script("location.hash = 'f'").unwrap();
+            let result = js_document.run_script("location.href").unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "http://example.com:8080/a/b?c=d#f",
+                "{engine:?}"
+            );
+            assert_eq!(history.borrow().len(), 2, "{engine:?}");
+            assert_eq!(
+                history.borrow_mut().take_requests(),
+                vec![NavigationRequest::FragmentNavigated {
+                    old_url: url_record("http://example.com:8080/a/b?c=d#e"),
+                    new_url: url_record("http://example.com:8080/a/b?c=d#f"),
+                }],
+                "{engine:?}"
+            );
+
+            // Other navigations are left to the tab
+            js_document
+                .run_script(
+                    "location.assign('../x'); location.replace('http://other.com/'); \
+                    location.reload(); location.search = 'q'; window.location = '/y';",
+                )
+                .unwrap();
+            assert_eq!(
+                history.borrow_mut().take_requests(),
+                vec![
+                    NavigationRequest::Navigate {
+                        url: url_record("http://example.com:8080/x"),
+                        replace: false,
+                    },
+                    NavigationRequest::Navigate {
+                        url: url_record("http://other.com/"),
+                        replace: true,
+                    },
+                    NavigationRequest::Reload,
+                    NavigationRequest::Navigate {
+                        url: url_record("http://example.com:8080/a/b?q#f"),
+                        replace: false,
+                    },
+                    NavigationRequest::Navigate {
+                        url: url_record("http://example.com:8080/y"),
+                        replace: false,
+                    },
+                ],
+                "{engine:?}"
+            );
+            let result = js_document.run_script("location.pathname").unwrap();
+            assert_eq!(result.get_ref().to_string(), "/a/b", "{engine:?}");
+
+            let error = js_document
+                .run_script("location.href = 'http://[';")
+                .unwrap_err();
+            assert_eq!(error.get_ref().to_string(), "SyntaxError", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        for engine in JsEngine::ALL {
+            let history = Rc::new(RefCell::new(SessionHistory::new()));
+            let mut js_document =
+                create_navigation_document(engine, "http://example.com/", &history);
+            let result = js_document
+                .run_script(
+                    "var log = history.length + ',' + history.state + ';'; \
+                    history.pushState({ a: [1, 'x'], d: null }, '', '?p=1'); \
+                    log = log + history.length + ',' + location.href + ',' + \
+                        history.state.a[1] + history.state.d + \
+                        (history.state === history.state) + ';'; \
+                    history.replaceState(5, '', '#h'); \
+                    log + history.length + ',' + location.href + ',' + history.state",
+                )
+                .unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "1,null;2,http://example.com/?p=1,xnulltrue;2,http://example.com/?p=1#h,5",
+                "{engine:?}"
+            );
+            assert_eq!(
+                js_document
+                    .global_closure_context
+                    .fetches
+                    .document_url
+                    .as_ref()
+                    .map(|url| url.to_record().to_string())
+                    .as_deref(),
+                Some("http://example.com/?p=1#h"),
+                "{engine:?}"
+            );
+            assert!(history.borrow_mut().take_requests().is_empty());
+
+            js_document
+                .run_script("history.back(); history.forward(); history.go(); history.go(-2);")
+                .unwrap();
+            assert_eq!(
+                history.borrow_mut().take_requests(),
+                vec![
+                    NavigationRequest::Traverse(-1),
+                    NavigationRequest::Traverse(1),
+                    NavigationRequest::Reload,
+                    NavigationRequest::Traverse(-2),
+                ],
+                "{engine:?}"
+            );
+
+            for (script, expected) in [
+                ("function f() {} history.pushState(f, '')", "DataCloneError"),
+                (
+                    "var o = {}; o.o = o; history.pushState(o, '')",
+                    "DataCloneError",
+                ),
+                (
+                    "history.pushState(null, '', 'http://other.com/')",
+                    "SecurityError",
+                ),
+                ("history.pushState(null, '', 'http://[')", "SyntaxError"),
+            ] {
+                let error = js_document.run_script(script).unwrap_err();
+                assert_eq!(error.get_ref().to_string(), expected, "{engine:?} {script}");
+            }
+            assert_eq!(history.borrow().len(), 2, "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_popstate_and_hashchange_events() {
+        for engine in JsEngine::ALL {
+            let history = Rc::new(RefCell::new(SessionHistory::new()));
+            let mut js_document =
+                create_navigation_document(engine, "http://example.com/#a", &history);
+            js_document
+                .run_script(
+                    "var log = ''; \
+                    function onPopState(e) { \
+                        log = log + e.type + ',' + e.state.n + ',' + location.hash + ';'; } \
+                    function onHashChange(e) { log = log + e.oldURL + ',' + e.newURL + ';'; } \
+                    addEventListener('popstate', onPopState); \
+                    addEventListener('hashchange', onHashChange); \
+                    history.replaceState({ n: 1 }, ''); \
+                    history.pushState({ n: 2 }, '', '#b');",
+                )
+                .unwrap();
+
+            // The tab moves back to the first entry of the document
+            assert!(history.borrow_mut().traverse(-1));
+            js_document.update_url_from_history();
+            let event = JsEvent::new("popstate", false, false, JsEventDetail::PopState);
+            js_document.dispatch_event(JsEventTargetId::Window, event);
+            let detail = JsEventDetail::HashChange {
+                old_url: "http://example.com/#b".to_string(),
+                new_url: "http://example.com/#a".to_string(),
+            };
+            let event = JsEvent::new("hashchange", false, false, detail);
+            js_document.dispatch_event(JsEventTargetId::Window, event);
+
+            let result = js_document.run_script("log").unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "popstate,1,#a;http://example.com/#b,http://example.com/#a;",
+                "{engine:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_animation_frames() {
+        for engine in JsEngine::ALL {
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            js_document.set_html_document(Rc::new(RefCell::new(parse_html("").unwrap())));
+            let clock = Rc::new(FixedClock::new(1000.0));
+            js_document.set_clock(clock.clone());
+            assert!(!js_document.has_animation_frame_callbacks());
+            js_document
+                .run_script(
+                    "var log = ''; \
+                    function add(entry) { log = log ? log + ',' + entry : entry; } \
+                    function a(time) { \
+                        add('a' + time); \
+                        cancelAnimationFrame(third); \
+                        window.requestAnimationFrame(c); \
+                    } \
+                    function b(time) { add('b' + time); } \
+                    function c(time) { add('c' + time); } \
+                    function fail() { throw 'reported'; } \
+                    function canceled() { add('canceled'); } \
+                    var first = requestAnimationFrame(a); \
+                    requestAnimationFrame(fail); \
+                    var third = requestAnimationFrame(canceled); \
+                    requestAnimationFrame(b);",
+                )
+                .unwrap();
+            assert!(js_document.has_animation_frame_callbacks());
+
+            // Callbacks requested while running run in the next frame
+            assert!(js_document.run_animation_frame_callbacks(1016.5));
+            assert!(js_document.run_animation_frame_callbacks(1033.0));
+            assert!(!js_document.has_animation_frame_callbacks());
+            assert!(!js_document.run_animation_frame_callbacks(1050.0));
+            clock.set(1060.25);
+            let result = js_document
+                .run_script(
+                    "log + '|' + first + '|' + performance.now() + '|' + \
+                    (window.performance === performance)",
+                )
+                .unwrap();
+            assert_eq!(
+                result.get_ref().to_string(),
+                "a16.5,b16.5,c33|1|60.25|true",
+                "{engine:?}"
+            );
+
+            let error = js_document
+                .run_script("requestAnimationFrame(1)")
+                .unwrap_err();
+            assert_eq!(error.get_ref().to_string(), "TypeError", "{engine:?}");
+        }
+    }
+
+    #[test]
+    pub fn test_function_declaration() {
+        let node_graph = get_node_graph();
+        run_test(
+            "function foo(){}",
+            vec![JsStatementResult::Value(GcNodeGraph::create_node(
+                &node_graph,
+                JsValue::Function(
+                    JsFunction::UserDefined(
+                        "function foo(){}".to_string(),
+                        "foo".to_string(),
+                        vec![],
+                        vec![],
+                    ),
+                    Default::default(),
+                ),
+            ))],
+        );
+        run_test(
+            "function foo(arg1, arg2) { arg1 + arg2; }",
+            vec![JsStatementResult::Value(GcNodeGraph::create_node(
+                &node_graph,
+                JsValue::Function(
+                    JsFunction::UserDefined(
+                        "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
+                        "foo".to_string(),
+                        vec!["arg1".to_string(), "arg2".to_string()],
+                        vec![JsStatement::Expression(JsExpression::Add(
+                            Box::new(JsExpression::Reference("arg1".to_string())),
+                            Box::new(JsExpression::Reference("arg2".to_string())),
+                        ))],
+                    ),
+                    Default::default(),
+                ),
+            ))],
+        );
+        run_test(
+            "function foo(arg1, arg2) { arg1 + arg2; };function bar(arg1, arg2) { arg1 + arg2; }",
+            vec![
+                JsStatementResult::Value(GcNodeGraph::create_node(
+                    &node_graph,
+                    JsValue::Function(
+                        JsFunction::UserDefined(
+                            "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
+                            "foo".to_string(),
+                            vec!["arg1".to_string(), "arg2".to_string()],
+                            vec![JsStatement::Expression(JsExpression::Add(
+                                Box::new(JsExpression::Reference("arg1".to_string())),
+                                Box::new(JsExpression::Reference("arg2".to_string())),
+                            ))],
+                        ),
+                        Default::default(),
+                    ),
+                )),
                 JsStatementResult::Void,
+                JsStatementResult::Value(GcNodeGraph::create_node(
+                    &node_graph,
+                    JsValue::Function(
+                        JsFunction::UserDefined(
+                            "function bar(arg1, arg2) { arg1 + arg2; }".to_string(),
+                            "bar".to_string(),
+                            vec!["arg1".to_string(), "arg2".to_string()],
+                            vec![JsStatement::Expression(JsExpression::Add(
+                                Box::new(JsExpression::Reference("arg1".to_string())),
+                                Box::new(JsExpression::Reference("arg2".to_string())),
+                            ))],
+                        ),
+                        Default::default(),
+                    ),
+                )),
             ],
         );
+    }
+
+    #[test]
+    pub fn test_user_function_invocations() {
+        let node_graph = get_node_graph();
         run_test(
-            "if(false){2;}else 3",
+            "function foo(arg1, arg2) { arg1 + arg2; } foo(1, 'abc')",
             vec![
-                JsStatementResult::number(&node_graph, 3),
-                JsStatementResult::Void,
+                JsStatementResult::Value(GcNodeGraph::create_node(
+                    &node_graph,
+                    JsValue::Function(
+                        JsFunction::UserDefined(
+                            "function foo(arg1, arg2) { arg1 + arg2; }".to_string(),
+                            "foo".to_string(),
+                            vec!["arg1".to_string(), "arg2".to_string()],
+                            vec![JsStatement::Expression(JsExpression::Add(
+                                Box::new(JsExpression::Reference("arg1".to_string())),
+                                Box::new(JsExpression::Reference("arg2".to_string())),
+                            ))],
+                        ),
+                        Default::default(),
+                    ),
+                )),
+                JsStatementResult::string(&node_graph, "1abc"),
+                JsStatementResult::undefined(&node_graph),
             ],
         );
+
+        let source = "function foo(arg1, arg2) { arg1 + arg2; return arg1; arg2; return arg2; }";
         run_test(
-            "if (false) 1; else { 2; } 3",
+            &format!("{source} foo(1, 'abc')"),
             vec![
-                JsStatementResult::number(&node_graph, 2),
-                JsStatementResult::Void,
-                JsStatementResult::number(&node_graph, 3),
+                JsStatementResult::Value(GcNodeGraph::create_node(
+                    &node_graph,
+                    JsValue::Function(
+                        JsFunction::UserDefined(
+                            source.to_string(),
+                            "foo".to_string(),
+                            vec!["arg1".to_string(), "arg2".to_string()],
+                            vec![
+                                JsStatement::Expression(JsExpression::Add(
+                                    Box::new(JsExpression::Reference("arg1".to_string())),
+                                    Box::new(JsExpression::Reference("arg2".to_string())),
+                                )),
+                                JsStatement::Return(JsExpression::Reference("arg1".to_string())),
+                                JsStatement::Expression(JsExpression::Reference(
+                                    "arg2".to_string(),
+                                )),
+                                JsStatement::Return(JsExpression::Reference("arg2".to_string())),
+                            ],
+                        ),
+                        Default::default(),
+                    ),
+                )),
+                JsStatementResult::string(&node_graph, "1abc"),
+                JsStatementResult::number(&node_graph, 1),
             ],
         );
     }
 
     #[test]
-    fn test_triple_equality() {
+    fn test_undefined() {
         let node_graph = get_node_graph();
-        run_test("1 === 1", vec![JsStatementResult::bool(&node_graph, true)]);
-        // NaN inequality
+        run_test("undefined", vec![JsStatementResult::undefined(&node_graph)]);
         run_test(
-            "1*undefined === 1*undefined",
-            vec![JsStatementResult::bool(&node_graph, false)],
+            "undefined; undefined",
+            vec![
+                JsStatementResult::undefined(&node_graph),
+                JsStatementResult::undefined(&node_graph),
+            ],
         );
-        run_test("1 === 01", vec![JsStatementResult::bool(&node_graph, true)]);
         run_test(
-            "'abc' === \"abc\"",
-            vec![JsStatementResult::bool(&node_graph, true)],
-        );
-        assert_eq!(
-            run_js("function abc(){} var cba = abc; abc === cba").1[2],
-            JsStatementResult::bool(&node_graph, true),
+            "var a; a = undefined; a",
+            vec![
+                JsStatementResult::undefined(&node_graph),
+                JsStatementResult::undefined(&node_graph),
+                JsStatementResult::undefined(&node_graph),
+            ],
         );
+        assert!(run_js("undefined+undefined*undefined+undefined",).1[0].is_nan());
         run_test(
-            "true === true",
-            vec![JsStatementResult::bool(&node_graph, true)],
+            "var undefined_var = 1; undefined_var",
+            vec![
+                JsStatementResult::number(&node_graph, 1),
+                JsStatementResult::number(&node_graph, 1),
+            ],
         );
+    }
+
+    #[test]
+    fn test_bool() {
+        let node_graph = get_node_graph();
+        run_test("true", vec![JsStatementResult::bool(&node_graph, true)]);
+        run_test("false", vec![JsStatementResult::bool(&node_graph, false)]);
         run_test(
-            "true !== false",
-            vec![JsStatementResult::bool(&node_graph, true)],
+            "false + true;",
+            vec![JsStatementResult::number(&node_graph, 1)],
         );
         run_test(
-            "null === null",
-            vec![JsStatementResult::bool(&node_graph, true)],
+            "true + false",
+            vec![JsStatementResult::number(&node_graph, 1)],
         );
         run_test(
-            "undefined === undefined",
-            vec![JsStatementResult::bool(&node_graph, true)],
+            "1 * false",
+            vec![JsStatementResult::number(&node_graph, 0.0)],
         );
+        assert!(run_js("undefined * false").1[0].is_nan());
         run_test(
-            "undefined === null",
-            vec![JsStatementResult::bool(&node_graph, false)],
-        );
-        assert_last_value_equals("{} == {}", JsStatementResult::bool(&node_graph, false));
-        assert_last_value_equals(
-            "var a = {}; var b = a; a == b",
-            JsStatementResult::bool(&node_graph, true),
+            "true + '2'",
+            vec![JsStatementResult::string(&node_graph, "true2")],
         );
-
-        run_test("1 !== 1", vec![JsStatementResult::bool(&node_graph, false)]);
     }
 
     #[test]
-    fn test_double_equality() {
+    fn test_null() {
         let node_graph = get_node_graph();
-        run_test("1 == 1", vec![JsStatementResult::bool(&node_graph, true)]);
-        // NaN inequality
-        run_test(
-            "1*undefined == 1*undefined",
-            vec![JsStatementResult::bool(&node_graph, false)],
+        run_test("null", vec![JsStatementResult::null(&node_graph)]);
+        run_test("null;", vec![JsStatementResult::null(&node_graph)]);
+        run_test("1 + null;", vec![JsStatementResult::number(&node_graph, 1)]);
+        run_test("3 * null", vec![JsStatementResult::number(&node_graph, 0)]);
+        assert!(run_js("null + undefined").1[0].is_nan());
+        run_test(
+            "null + ''",
+            vec![JsStatementResult::string(&node_graph, "null")],
+        );
+        run_test("null * ''", vec![JsStatementResult::number(&node_graph, 0)]);
+    }
+
+    #[test]
+    fn test_nan() {
+        let _node_graph = get_node_graph();
+        assert!(run_js("NaN").1[0].is_nan());
+        assert!(run_js("NaN * NaN + NaN").1[0].is_nan());
+    }
+
+    #[test]
+    fn test_if_statements() {
+        let node_graph = get_node_graph();
+        run_test(
+            "if(true){1;}",
+            vec![
+                JsStatementResult::number(&node_graph, 1),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(true)1",
+            vec![
+                JsStatementResult::number(&node_graph, 1),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(true)var i;2",
+            vec![
+                JsStatementResult::undefined(&node_graph),
+                JsStatementResult::Void,
+                JsStatementResult::number(&node_graph, 2),
+            ],
+        );
+        run_test(
+            "1; if (null * undefined) { 2; } 3",
+            vec![
+                JsStatementResult::number(&node_graph, 1),
+                JsStatementResult::Void,
+                JsStatementResult::number(&node_graph, 3),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_else_statements() {
+        let node_graph = get_node_graph();
+        run_test(
+            "if(true){1;} else {2;}",
+            vec![
+                JsStatementResult::number(&node_graph, 1),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(false){1;} else {2;}",
+            vec![
+                JsStatementResult::number(&node_graph, 2),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(false)1; else 2;",
+            vec![
+                JsStatementResult::number(&node_graph, 2),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(false)var i;else 2",
+            vec![
+                JsStatementResult::number(&node_graph, 2),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if(false){2;}else 3",
+            vec![
+                JsStatementResult::number(&node_graph, 3),
+                JsStatementResult::Void,
+            ],
+        );
+        run_test(
+            "if (false) 1; else { 2; } 3",
+            vec![
+                JsStatementResult::number(&node_graph, 2),
+                JsStatementResult::Void,
+                JsStatementResult::number(&node_graph, 3),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_triple_equality() {
+        let node_graph = get_node_graph();
+        run_test("1 === 1", vec![JsStatementResult::bool(&node_graph, true)]);
+        // NaN inequality
+        run_test(
+            "1*undefined === 1*undefined",
+            vec![JsStatementResult::bool(&node_graph, false)],
+        );
+        run_test("1 === 01", vec![JsStatementResult::bool(&node_graph, true)]);
+        run_test(
+            "'abc' === \"abc\"",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        assert_eq!(
+            run_js("function abc(){} var cba = abc; abc === cba").1[2],
+            JsStatementResult::bool(&node_graph, true),
+        );
+        run_test(
+            "true === true",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        run_test(
+            "true !== false",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        run_test(
+            "null === null",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        run_test(
+            "undefined === undefined",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        run_test(
+            "undefined === null",
+            vec![JsStatementResult::bool(&node_graph, false)],
+        );
+        assert_last_value_equals("{} == {}", JsStatementResult::bool(&node_graph, false));
+        assert_last_value_equals(
+            "var a = {}; var b = a; a == b",
+            JsStatementResult::bool(&node_graph, true),
+        );
+
+        run_test("1 !== 1", vec![JsStatementResult::bool(&node_graph, false)]);
+    }
+
+    #[test]
+    fn test_double_equality() {
+        let node_graph = get_node_graph();
+        run_test("1 == 1", vec![JsStatementResult::bool(&node_graph, true)]);
+        // NaN inequality
+        run_test(
+            "1*undefined == 1*undefined",
+            vec![JsStatementResult::bool(&node_graph, false)],
         );
         run_test("1 == 01", vec![JsStatementResult::bool(&node_graph, true)]);
         run_test(
@@ -656,187 +2205,2518 @@ mod tests {
             vec![JsStatementResult::bool(&node_graph, false)],
         );
         assert_last_value_equals(
-            "var a = {}; var b = a; a == b",
+            "var a = {}; var b = a; a == b",
+            JsStatementResult::bool(&node_graph, true),
+        );
+        assert_last_value_equals(
+            "{} + ''",
+            JsStatementResult::string(&node_graph, "[object Object]"),
+        );
+
+        run_test("1 != 1", vec![JsStatementResult::bool(&node_graph, false)]);
+
+        run_test(
+            "1 == ' 01  '",
+            vec![JsStatementResult::bool(&node_graph, true)],
+        );
+        assert_eq!(
+            run_js("  function  abc ( ) { } abc == 'function  abc ( ) { }'").1[1],
+            JsStatementResult::bool(&node_graph, true),
+        );
+    }
+
+    #[test]
+    fn test_recursion() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "function factorial(num) {if (num == 1) { return 1; } return num * factorial(num + -1);} factorial(5)",
+            JsStatementResult::number(&node_graph, 120),
+        );
+        // Infinite recursion leads to undefined right now instead of a stack overflow exception
+        assert_last_value_equals(
+            "function recurse(num) {recurse(num + 1);} recurse(1)",
+            JsStatementResult::ThrowValue(JsValue::stack_overflow_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_object_literal() {
+        let node_graph = get_node_graph();
+        run_test("{}", vec![JsStatementResult::object(&node_graph, vec![])]);
+        run_test(
+            " { 'a' : 1 } ",
+            vec![JsStatementResult::object(
+                &node_graph,
+                vec![("a", JsValue::number_rc(&node_graph, 1))],
+            )],
+        );
+        run_test(
+            "{'a': 1, 'b': 2}",
+            vec![JsStatementResult::object(
+                &node_graph,
+                vec![
+                    ("a", JsValue::number_rc(&node_graph, 1)),
+                    ("b", JsValue::number_rc(&node_graph, 2)),
+                ],
+            )],
+        );
+        run_test(
+            "{'a': 1, 'b': 2,}",
+            vec![JsStatementResult::object(
+                &node_graph,
+                vec![
+                    ("a", JsValue::number_rc(&node_graph, 1)),
+                    ("b", JsValue::number_rc(&node_graph, 2)),
+                ],
+            )],
+        );
+        run_test(
+            "{'a': 1, 'a': 2}",
+            vec![JsStatementResult::object(
+                &node_graph,
+                vec![("a", JsValue::number_rc(&node_graph, 2))],
+            )],
+        );
+        run_test(
+            "{'a': 1 + 2, 'b': {}}",
+            vec![JsStatementResult::object(
+                &node_graph,
+                vec![
+                    ("a", JsValue::number_rc(&node_graph, 3)),
+                    ("b", JsValue::object_rc(&node_graph, Default::default())),
+                ],
+            )],
+        );
+    }
+
+    #[test]
+    fn test_object_operations() {
+        let node_graph = get_node_graph();
+        assert!(run_js("5 * {}").1.last().unwrap().is_nan());
+        run_test(
+            "5 + {}",
+            vec![JsStatementResult::string(&node_graph, "5[object Object]")],
+        );
+    }
+
+    #[test]
+    fn test_object_access() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a=true; a.foo",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "var a=123; a.foo",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "var a='abc'; a.foo",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals("var a={}; a.foo", JsStatementResult::undefined(&node_graph));
+        assert_last_value_equals(
+            "var a={'not foo': 'bar'}; a.foo",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "var a={'foo': 'bar'}; a.foo",
+            JsStatementResult::string(&node_graph, "bar"),
+        );
+        assert_last_value_equals(
+            "var a={'foo': {'bar': 123}}; a.foo.bar",
+            JsStatementResult::number(&node_graph, 123),
+        );
+    }
+
+    #[test]
+    fn test_prototype_to_string() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = true; a.toString()",
+            JsStatementResult::string(&node_graph, "true"),
+        );
+        assert_last_value_equals(
+            "var a = 5; a.toString()",
+            JsStatementResult::string(&node_graph, "5"),
+        );
+        assert_last_value_equals(
+            "var a = {}; a.toString()",
+            JsStatementResult::string(&node_graph, "[object Object]"),
+        );
+    }
+
+    #[test]
+    fn test_condition() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals("1 ? 2 : 3", JsStatementResult::number(&node_graph, 2));
+        assert_last_value_equals("false ? 2 : 3", JsStatementResult::number(&node_graph, 3));
+        assert_last_value_equals(
+            "1 ? 0 ? 3 : 4 : 5",
+            JsStatementResult::number(&node_graph, 4),
+        );
+        assert_last_value_equals(
+            "1 + 1 ? 2 + 2 : 3 + 3",
+            JsStatementResult::number(&node_graph, 4),
+        );
+    }
+
+    #[test]
+    fn test_throw() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "throw 1;",
+            JsStatementResult::ThrowValue(JsValue::number_rc(&node_graph, 1)),
+        );
+        assert_last_value_equals(
+            "function throws(val) { throw val; } throws('abc'); ",
+            JsStatementResult::ThrowValue(JsValue::str_rc(&node_graph, "abc")),
+        );
+        assert_last_value_equals(
+            "function throws(val) { if (val) { throw val; } } throws(''); ",
+            JsStatementResult::Value(JsValue::undefined_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_array_literal() {
+        let node_graph = get_node_graph();
+        run_test("[]", vec![JsStatementResult::array(&node_graph, vec![])]);
+        run_test(
+            "[1, 'a',]",
+            vec![JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::str_rc(&node_graph, "a"),
+                ],
+            )],
+        );
+        run_test(
+            "[1, , 2]",
+            vec![JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            )],
+        );
+        assert_last_value_equals(
+            "[1, [2, 3]] + ''",
+            JsStatementResult::string(&node_graph, "1,2,3"),
+        );
+        assert_last_value_equals(
+            "[1, 2, 3].length",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "var a = []; a === a",
+            JsStatementResult::bool(&node_graph, true),
+        );
+        assert_last_value_equals("[] == []", JsStatementResult::bool(&node_graph, false));
+    }
+
+    #[test]
+    fn test_computed_member_access() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals("[1, 2, 3][1]", JsStatementResult::number(&node_graph, 2));
+        assert_last_value_equals("[1, 2, 3][3]", JsStatementResult::undefined(&node_graph));
+        assert_last_value_equals("'abc'[2]", JsStatementResult::string(&node_graph, "c"));
+        assert_last_value_equals(
+            "var a = {'foo bar': 1}; a['foo' + ' bar']",
+            JsStatementResult::number(&node_graph, 1),
+        );
+        assert_last_value_equals(
+            "var a = {}; a.b = 2; a['c'] = 3; a.b * a.c",
+            JsStatementResult::number(&node_graph, 6),
+        );
+        assert_last_value_equals(
+            "var a = []; a[2] = 1; a",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::number_rc(&node_graph, 1),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var a; a.b = 1",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_spread() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = [2, 3]; [1, ...a, 4]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::number_rc(&node_graph, 4),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "[...'ab']",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "a"),
+                    JsValue::str_rc(&node_graph, "b"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "[...{}]",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "function add(a, b, c) { return a + b * c; } var args = [2, 3]; add(1, ...args)",
+            JsStatementResult::number(&node_graph, 7),
+        );
+        assert_last_value_equals(
+            "var a = {'a': 1, 'b': 2}; {...a, 'b': 3}",
+            JsStatementResult::object(
+                &node_graph,
+                vec![
+                    ("a", JsValue::number_rc(&node_graph, 1)),
+                    ("b", JsValue::number_rc(&node_graph, 3)),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "{...[5], ...null}",
+            JsStatementResult::object(&node_graph, vec![("0", JsValue::number_rc(&node_graph, 5))]),
+        );
+    }
+
+    #[test]
+    fn test_destructuring_declaration() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var [a, , b] = [1, 2, 3]; a + b",
+            JsStatementResult::number(&node_graph, 4),
+        );
+        assert_last_value_equals(
+            "let [a, ...rest] = [1, 2, 3]; rest",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 3),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "const {a, b: {c}, d = 4} = {'a': 1, 'b': {'c': 2}}; a + c + d",
+            JsStatementResult::number(&node_graph, 7),
+        );
+        assert_last_value_equals(
+            "let {a, ...rest} = {'a': 1, 'b': 2}; rest",
+            JsStatementResult::object(&node_graph, vec![("b", JsValue::number_rc(&node_graph, 2))]),
+        );
+        assert_last_value_equals(
+            "let [[a], {length}] = [[1], 'abc']; a + length",
+            JsStatementResult::number(&node_graph, 4),
+        );
+        assert_last_value_equals(
+            "let [a = 1, b = 2] = [undefined, null]; b",
+            JsStatementResult::null(&node_graph),
+        );
+        assert_last_value_equals(
+            "let {a} = null",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "let [a] = 1",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_destructuring_assignment() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = 1; var b = 2; [a, b] = [b, a]; a",
+            JsStatementResult::number(&node_graph, 2),
+        );
+        assert_last_value_equals(
+            "var a; var b; ({a, b = 5} = {'a': 1}); a + b",
+            JsStatementResult::number(&node_graph, 6),
+        );
+        assert_last_value_equals(
+            "var o = {}; [o.a, ...o['b']] = [1, 2]; o.b",
+            JsStatementResult::array(&node_graph, vec![JsValue::number_rc(&node_graph, 2)]),
+        );
+        assert_last_value_equals(
+            "var a = b = 3; a + b",
+            JsStatementResult::number(&node_graph, 6),
+        );
+    }
+
+    #[test]
+    fn test_function_param_patterns() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "function f(a, b = a + 1) { return a + b; } f(1)",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "function f(a, b = 2) { return b; } f(1, null)",
+            JsStatementResult::null(&node_graph),
+        );
+        assert_last_value_equals(
+            "function f(a, ...rest) { return rest; } f(1, 2, 3)",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 3),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "function f({a}, [b]) { return a + b; } f({'a': 1}, [2])",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "function f() { return arguments.length; } f(1, 2)",
+            JsStatementResult::number(&node_graph, 2),
+        );
+    }
+
+    #[test]
+    fn test_let_const() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "let a = 1; a = 2; a",
+            JsStatementResult::number(&node_graph, 2),
+        );
+        assert_last_value_equals("let a; a", JsStatementResult::undefined(&node_graph));
+        assert_last_value_equals(
+            "const a = 1; a = 2",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "const [a] = [1]; [a] = [2]",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "let a = 1; function f() { let a = 2; return a; } f() + a",
+            JsStatementResult::number(&node_graph, 3),
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "function Point(x, y) { this.x = x; this.y = y; } var p = new Point(1, 2); p.x + p.y",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "function Point() {} function norm() { return 5; } Point.prototype.norm = norm; new Point().norm()",
+            JsStatementResult::number(&node_graph, 5),
+        );
+        assert_last_value_equals(
+            "function Point() {} var p = new Point; p.constructor === Point",
+            JsStatementResult::bool(&node_graph, true),
+        );
+        assert_last_value_equals(
+            "function Box() { return [1]; } new Box()[0]",
+            JsStatementResult::number(&node_graph, 1),
+        );
+        assert_last_value_equals(
+            "var a = {}; new a()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_symbol() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "Symbol('a') === Symbol('a')",
+            JsStatementResult::bool(&node_graph, false),
+        );
+        assert_last_value_equals(
+            "var s = Symbol('a'); s === s",
+            JsStatementResult::bool(&node_graph, true),
+        );
+        assert_last_value_equals(
+            "Symbol.for('a') === Symbol.for('a')",
+            JsStatementResult::bool(&node_graph, true),
+        );
+        assert_last_value_equals(
+            "Symbol.keyFor(Symbol.for('a'))",
+            JsStatementResult::string(&node_graph, "a"),
+        );
+        assert_last_value_equals(
+            "Symbol.keyFor(Symbol('a'))",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "Symbol('a').description",
+            JsStatementResult::string(&node_graph, "a"),
+        );
+        assert_last_value_equals(
+            "Symbol('a').toString()",
+            JsStatementResult::string(&node_graph, "Symbol(a)"),
+        );
+        assert_last_value_equals(
+            "Symbol.iterator.description",
+            JsStatementResult::string(&node_graph, "Symbol.iterator"),
+        );
+        assert_last_value_equals(
+            "new Symbol()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "Symbol() + ''",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "+Symbol()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_symbol_keyed_properties() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var s = Symbol('a'); var o = {[s]: 1, 'a': 2}; o[s] + o.a",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "var s = Symbol('a'); var o = {}; o[s] = 1; o['Symbol(a)']",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "var key = 'a'; var o = {[key + 'b']: 1}; o.ab",
+            JsStatementResult::number(&node_graph, 1),
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var m = new Map(); m.set('a', 1).set('b', 2); m.get('a') + m.get('b') * 10",
+            JsStatementResult::number(&node_graph, 21),
+        );
+        assert_last_value_equals(
+            "var m = new Map([['a', 1], ['b', 2]]); m.delete('a'); [m.size, m.has('a'), m.has('b')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var m = new Map(); var k = {}; m.set(k, 1); m.set({}, 2); m.get(k)",
+            JsStatementResult::number(&node_graph, 1),
+        );
+        assert_last_value_equals(
+            "var m = new Map(); m.set(NaN, 1); m.set(0, 2); [m.get(NaN), m.get(-0), m.get('0')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::undefined_rc(&node_graph),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var m = new Map([['b', 1], ['a', 2]]); m.set('b', 3); [...m]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "b"),
+                            JsValue::number_rc(&node_graph, 3),
+                        ],
+                    ),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::number_rc(&node_graph, 2),
+                        ],
+                    ),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var m = new Map([['a', 1], ['b', 2]]); [...m.keys(), ...m.values()]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "a"),
+                    JsValue::str_rc(&node_graph, "b"),
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var total = 0; function add(value, key) { total = total + key + value; }
+            new Map([['a', 1], ['b', 2]]).forEach(add); total",
+            JsStatementResult::string(&node_graph, "0a1b2"),
+        );
+        assert_last_value_equals(
+            "var m = new Map([['a', 1]]); m.clear(); m.size",
+            JsStatementResult::number(&node_graph, 0),
+        );
+        assert_last_value_equals(
+            "'' + new Map()",
+            JsStatementResult::string(&node_graph, "[object Map]"),
+        );
+        assert_last_value_equals(
+            "Map()",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "new Map([1])",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var s = new Set([3, 1, 3, 2, 1]); s.add(4).add(1); [...s]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 4),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var s = new Set('abca'); s.delete('b'); [s.size, s.has('a'), s.has('b')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var [a, b] = new Set([1, 2]).entries(); a",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 1),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_weak_collections() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var w = new WeakMap(); var k = {}; w.set(k, 1); [w.get(k), w.has({}), w.delete(k), w.has(k)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var w = new WeakSet(); var k = {}; w.add(k); [w.has(k), w.has({})]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "new WeakMap().set('a', 1)",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "new WeakSet([1])",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "new WeakMap().size",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "[...new WeakSet()]",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_iteration_protocol() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var i = [1][Symbol.iterator](); var a = i.next(); var b = i.next(); [a.value, a.done, b.value, b.done]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var count = 0;
+            function next() { count = count + 1; return {'value': count, 'done': count === 3}; }
+            function iterator() { return {'next': next}; }
+            [...{[Symbol.iterator]: iterator}]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var [a, b] = new Map([['a', 1]]).keys(); [a, b]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "a"),
+                    JsValue::undefined_rc(&node_graph),
+                ],
+            ),
+        );
+    }
+
+    #[ignore = "Currently only has syntax parsing support"]
+    #[test]
+    fn test_this() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "this;",
+            JsStatementResult::Value(JsValue::object_rc(&node_graph, Default::default())),
+        );
+    }
+
+    #[test]
+    fn test_getters_setters() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var o = { a: 1, get double() { return this.a * 2; }, set double(v) { this.a = v * 0.5; } }; \
+            o.double = 10; [o.a, o.double]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 5),
+                    JsValue::number_rc(&node_graph, 10),
+                ],
+            ),
+        );
+        // Assignments to a property with only a getter are ignored
+        assert_last_value_equals(
+            "var o = { get a() { return 1; } }; o.a = 2; o.a",
+            JsStatementResult::number(&node_graph, 1),
+        );
+        assert_last_value_equals(
+            "var key = 'b'; var o = { [key]() { return 2; }, get: 3 }; o.b() + o.get",
+            JsStatementResult::number(&node_graph, 5),
+        );
+    }
+
+    #[test]
+    fn test_class() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "class Point { \
+                constructor(x, y) { this.x = x; this.y = y; } \
+                get sum() { return this.x + this.y; } \
+                scale(factor) { return new Point(this.x * factor, this.y * factor); } \
+                static origin() { return new Point(0, 0); } \
+            } \
+            var p = new Point(1, 2).scale(3); \
+            [p.x, p.sum, Point.origin().sum, p.constructor === Point, Object.keys(p)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::number_rc(&node_graph, 9),
+                    JsValue::number_rc(&node_graph, 0),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "x"),
+                            JsValue::str_rc(&node_graph, "y"),
+                        ],
+                    ),
+                ],
+            ),
+        );
+        // Methods are not enumerable, and classes without a constructor construct empty objects
+        assert_last_value_equals(
+            "class Empty { method() {} } [Object.keys(Empty.prototype), Object.keys(new Empty())]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::array_rc(&node_graph, vec![]),
+                    JsValue::array_rc(&node_graph, vec![]),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_object_define_property() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var o = {}; Object.defineProperty(o, 'a', { value: 1 }); o.a = 2; \
+            var d = Object.getOwnPropertyDescriptor(o, 'a'); \
+            [o.a, d.writable, d.enumerable, d.configurable, Object.keys(o)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::array_rc(&node_graph, vec![]),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var o = { b: 1 }; function getA() { return this.b + 1; } \
+            Object.defineProperty(o, 'a', { get: getA, enumerable: true }); \
+            [o.a, Object.keys(o)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "b"),
+                            JsValue::str_rc(&node_graph, "a"),
+                        ],
+                    ),
+                ],
+            ),
+        );
+        // Redefining a non-configurable property fails
+        assert_last_value_equals(
+            "var o = {}; Object.defineProperty(o, 'a', { value: 1 }); \
+            Object.defineProperty(o, 'a', { value: 2 })",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        // Descriptors cannot mix values and accessors
+        assert_last_value_equals(
+            "function getA() {} Object.defineProperty({}, 'a', { value: 1, get: getA })",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "Object.getOwnPropertyDescriptor({}, 'toString')",
+            JsStatementResult::undefined(&node_graph),
+        );
+    }
+
+    #[test]
+    fn test_object_keys_values_entries() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "Object.keys({ b: 1, '2': 2, a: 3, '1': 4, [Symbol()]: 5 })",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "1"),
+                    JsValue::str_rc(&node_graph, "2"),
+                    JsValue::str_rc(&node_graph, "b"),
+                    JsValue::str_rc(&node_graph, "a"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "Object.values({ a: 1, get b() { return 2; } })",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "Object.entries(['x'])",
+            JsStatementResult::array(
+                &node_graph,
+                vec![JsValue::array_rc(
+                    &node_graph,
+                    vec![
+                        JsValue::str_rc(&node_graph, "0"),
+                        JsValue::str_rc(&node_graph, "x"),
+                    ],
+                )],
+            ),
+        );
+        assert_last_value_equals(
+            "Object.keys(undefined)",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_object_assign() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var target = { a: 1, set b(v) { this.c = v; } }; \
+            var result = Object.assign(target, { a: 2, b: 3 }, null, { d: 4 }); \
+            [result === target, target.a, target.c, target.d]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::number_rc(&node_graph, 4),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "Object.assign(Object.freeze({ a: 1 }), { a: 2 })",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_object_freeze_seal() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var o = Object.freeze({ a: 1 }); o.a = 2; o.b = 3; \
+            [o.a, o.b, Object.isFrozen(o), Object.isSealed(o)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var o = Object.seal({ a: 1 }); o.a = 2; o.b = 3; \
+            [o.a, o.b, Object.isFrozen(o), Object.isSealed(o), Object.isExtensible(o)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::undefined_rc(&node_graph),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_has_own_property() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var o = { a: 1 }; \
+            [o.hasOwnProperty('a'), o.hasOwnProperty('toString'), [1].hasOwnProperty('length')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_regexp_literal() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var r = /a(b+)c/i; [r.test('xABBCx'), r.test('ac'), r.source, r.flags, r.global, r + '']",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::str_rc(&node_graph, "a(b+)c"),
+                    JsValue::str_rc(&node_graph, "i"),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::str_rc(&node_graph, "/a(b+)c/i"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "[/[/]x/.test('a/x'), /a\\/b/.test('a/b')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_regexp_exec() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var m = /(?<year>\\d{4})-(?<month>\\d\\d)/.exec('on 2024-05'); \
+            [m[0], m[1], m.index, m.input, m.groups.month, m.length]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "2024-05"),
+                    JsValue::str_rc(&node_graph, "2024"),
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::str_rc(&node_graph, "on 2024-05"),
+                    JsValue::str_rc(&node_graph, "05"),
+                    JsValue::number_rc(&node_graph, 3),
+                ],
+            ),
+        );
+        assert_last_value_equals("/x/.exec('abc')", JsStatementResult::null(&node_graph));
+    }
+
+    #[test]
+    fn test_regexp_last_index() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var r = /o/g; [r.test('foo'), r.lastIndex, r.test('foo'), r.lastIndex, \
+            r.test('foo'), r.lastIndex]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::number_rc(&node_graph, 3),
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::number_rc(&node_graph, 0),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var r = /b/y; var before = r.test('ab'); r.lastIndex = 1; \
+            [before, r.test('ab'), r.lastIndex, r.sticky]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::bool_rc(&node_graph, false),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_regexp_constructor() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var r = new RegExp('a+', 'gm'); var copy = RegExp(r); \
+            [r.flags, r.multiline, copy.source, copy.flags, RegExp('.', 's').test('\n')]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "gm"),
+                    JsValue::bool_rc(&node_graph, true),
+                    JsValue::str_rc(&node_graph, "a+"),
+                    JsValue::str_rc(&node_graph, "gm"),
+                    JsValue::bool_rc(&node_graph, true),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "RegExp('(')",
+            JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "new RegExp('a', 'gg')",
+            JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_string_regexp_methods() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var m = 'a1b22'.match(/\\d+/); \
+            [m[0], m.index, 'a1b22'.match(/\\d+/g), 'abc'.match(/\\d/g), 'abc'.search(/c/)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "1"),
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "1"),
+                            JsValue::str_rc(&node_graph, "22"),
+                        ],
+                    ),
+                    JsValue::null_rc(&node_graph),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var [first, second] = [...'a1b2'.matchAll(/[a-z](?<digit>\\d)/g)]; \
+            [first.groups.digit + first.index, second.groups.digit + second.index]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "10"),
+                    JsValue::str_rc(&node_graph, "22"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "'abc'.matchAll(/b/)",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_string_replace_split() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "function upper(m, c) { return '<' + c + '>'; } \
+            ['a-b-c'.replace('-', '+'), 'a-b-c'.replace(/-/g, '+'), \
+            'john smith'.replace(/(?<first>\\w+) (\\w+)/, '$2, $<first> $$'), \
+            'x1y2'.replace(/(\\d)/g, upper), 'abc'.replace(/b/, \"[$`$&$']\")]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "a+b-c"),
+                    JsValue::str_rc(&node_graph, "a+b+c"),
+                    JsValue::str_rc(&node_graph, "smith, john $"),
+                    JsValue::str_rc(&node_graph, "x<1>y<2>"),
+                    JsValue::str_rc(&node_graph, "a[abc]c"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "['a, b,c'.split(/\\s*,\\s*/), 'a1b'.split(/(\\d)/), 'ab'.split(''), 'a,b,c'.split(',', 2)]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::str_rc(&node_graph, "b"),
+                            JsValue::str_rc(&node_graph, "c"),
+                        ],
+                    ),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::str_rc(&node_graph, "1"),
+                            JsValue::str_rc(&node_graph, "b"),
+                        ],
+                    ),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::str_rc(&node_graph, "b"),
+                        ],
+                    ),
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::str_rc(&node_graph, "b"),
+                        ],
+                    ),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn test_switch() {
+        let node_graph = get_node_graph();
+        let script = "function describe(value) { \
+                var result = ''; \
+                switch (value) { \
+                    case 1: result = result + 'one '; \
+                    case 2: result = result + 'two'; break; \
+                    case '3': return 'string'; \
+                    default: result = 'other'; \
+                    case 4: result = result + ' four'; \
+                } \
+                return result; \
+            } ";
+        assert_last_value_equals(
+            &format!(
+                "{script} [describe(1), describe(2), describe('3'), describe(3), describe(4)]"
+            ),
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::str_rc(&node_graph, "one two"),
+                    JsValue::str_rc(&node_graph, "two"),
+                    JsValue::str_rc(&node_graph, "string"),
+                    JsValue::str_rc(&node_graph, "other four"),
+                    JsValue::str_rc(&node_graph, " four"),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "var tests = 0; switch (2) { case (tests = tests + 1): case (tests = tests + 1): } tests",
+            JsStatementResult::number(&node_graph, 2),
+        );
+    }
+
+    #[test]
+    fn test_labeled_block() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = 1; outer: { a = 2; { break outer; } a = 3; } a",
+            JsStatementResult::number(&node_graph, 2),
+        );
+        assert_last_value_equals(
+            "var a = 1; { let b = 2; a = a + b; } a",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "var a = 1; found: switch (a) { case 1: { break found; } default: a = 2; } a",
+            JsStatementResult::number(&node_graph, 1),
+        );
+    }
+
+    #[test]
+    fn test_comma_operator() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = 1; var b = (a = 2, a + 3); [a, b]",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 2),
+                    JsValue::number_rc(&node_graph, 5),
+                ],
+            ),
+        );
+        assert_last_value_equals("1, 2, 3", JsStatementResult::number(&node_graph, 3));
+        assert_last_value_equals(
+            "function f(a, b) { return b; } f((1, 2), 3)",
+            JsStatementResult::number(&node_graph, 3),
+        );
+    }
+
+    #[test]
+    fn test_automatic_semicolon_insertion() {
+        let node_graph = get_node_graph();
+        assert_last_value_equals(
+            "var a = 1\nvar b = a + 1\nlet c = [a, b]\nc",
+            JsStatementResult::array(
+                &node_graph,
+                vec![
+                    JsValue::number_rc(&node_graph, 1),
+                    JsValue::number_rc(&node_graph, 2),
+                ],
+            ),
+        );
+        assert_last_value_equals(
+            "function f() { return\n1 }\nf()",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "function f() { var a = 'x'\nreturn a }\nf()\n.length",
+            JsStatementResult::number(&node_graph, 1),
+        );
+        assert_last_value_equals(
+            "var a = 1\n+ 2\na",
+            JsStatementResult::number(&node_graph, 3),
+        );
+        assert_last_value_equals(
+            "var a = 0\nswitch (a) {\ncase 0:\na = 'zero'\nbreak\ndefault:\na = 'other'\n}\na",
+            JsStatementResult::string(&node_graph, "zero"),
+        );
+        assert!(parse_js("var a = 1 var b = 2").is_err());
+    }
+
+    #[test]
+    fn test_syntax_error_location() {
+        let cases = [
+            ("1 +* 2", "Invalid or unexpected token at line 1, column 4"),
+            (
+                "let a = 1;\nlet = 2",
+                "Invalid or unexpected token at line 2, column 5",
+            ),
+            (
+                "let a = 'b",
+                "Invalid or unexpected token at line 1, column 9",
+            ),
+            ("if (a) {\n}\n}", "Unexpected token at line 3, column 1"),
+        ];
+        for (script, expected_error) in cases {
+            assert_eq!(parse_js(script).unwrap_err(), expected_error, "{script}");
+        }
+    }
+
+    #[test]
+    fn test_eval() {
+        let node_graph = get_node_graph();
+        let cases = [
+            ("eval('1 + 2')", JsStatementResult::number(&node_graph, 3)),
+            (
+                "eval('1; 2; if (true) { 3 }')",
+                JsStatementResult::number(&node_graph, 3),
+            ),
+            ("eval('')", JsStatementResult::undefined(&node_graph)),
+            ("eval(5)", JsStatementResult::number(&node_graph, 5)),
+            (
+                "eval('var a = 1'); a",
+                JsStatementResult::number(&node_graph, 1),
+            ),
+            (
+                "function f(x) { return eval('x * 2') } f(4)",
+                JsStatementResult::number(&node_graph, 8),
+            ),
+            (
+                "function f() { eval('var y = 3'); return y } f()",
+                JsStatementResult::number(&node_graph, 3),
+            ),
+            (
+                "eval('throw 1')",
+                JsStatementResult::ThrowValue(JsValue::number_rc(&node_graph, 1)),
+            ),
+            (
+                "eval('1 +* 2')",
+                JsStatementResult::ThrowValue(JsValue::syntax_error_with_message_rc(
+                    &node_graph,
+                    "Invalid or unexpected token at line 1, column 4",
+                )),
+            ),
+            (
+                "eval('return 1')",
+                JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
+            ),
+        ];
+        for (script, expected_result) in cases {
+            assert_last_value_equals(script, expected_result);
+        }
+    }
+
+    #[test]
+    fn test_indirect_eval() {
+        let node_graph = get_node_graph();
+        let cases = [
+            "var x = 'global'; function f() { let x = 'local'; let e = eval; return e('x') } f()",
+            "var x = 'global'; function f() { let x = 'local'; return (0, eval)('x') } f()",
+        ];
+        for script in cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, "global"));
+        }
+        assert_last_value_equals(
+            "function f() { let e = eval; e('var z = 1') } f(); z",
+            JsStatementResult::number(&node_graph, 1),
+        );
+    }
+
+    #[test]
+    fn test_function_constructor() {
+        let node_graph = get_node_graph();
+        let cases = [
+            (
+                "let add = new Function('a', 'b', 'return a + b'); add(1, 2)",
+                JsStatementResult::number(&node_graph, 3),
+            ),
+            (
+                "let f = Function('a, b', 'return b'); f(1, 2)",
+                JsStatementResult::number(&node_graph, 2),
+            ),
+            (
+                "let f = Function('return 7'); f()",
+                JsStatementResult::number(&node_graph, 7),
+            ),
+            (
+                "let f = Function(); f()",
+                JsStatementResult::undefined(&node_graph),
+            ),
+            (
+                "Object.getPrototypeOf(Function('')) === Function.prototype",
+                JsStatementResult::bool(&node_graph, true),
+            ),
+            (
+                "Function('}')",
+                JsStatementResult::ThrowValue(JsValue::syntax_error_with_message_rc(
+                    &node_graph,
+                    "Unexpected token at line 4, column 1",
+                )),
+            ),
+            (
+                "Function('}; {')",
+                JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
+            ),
+        ];
+        for (script, expected_result) in cases {
+            assert_last_value_equals(script, expected_result);
+        }
+    }
+
+    #[test]
+    fn test_date_constructor() {
+        let node_graph = get_node_graph();
+        let cases = [
+            ("new Date(0).getTime()", 0.0),
+            ("new Date().getTime()", 1e12),
+            ("Date.now()", 1e12),
+            ("new Date(new Date(5)).getTime()", 5.0),
+            ("new Date(2001, 8, 9, 2, 46, 40).getTime()", 1e12),
+            ("new Date(2001, 8).getTime()", 999_298_800_000.0),
+            ("new Date('2001-09-09T01:46:40Z').valueOf()", 1e12),
+            ("Date.UTC(2001, 8, 9, 1, 46, 40, 123)", 1_000_000_000_123.0),
+            ("Date.UTC(99, 0)", 915_148_800_000.0),
+            ("new Date(5) * 2", 10.0),
+        ];
+        for (script, expected) in cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+        assert!(run_js("new Date(8640000000000001).getTime()")
+            .1
+            .last()
+            .unwrap()
+            .is_nan());
+        assert!(run_js("new Date('nonsense').getTime()")
+            .1
+            .last()
+            .unwrap()
+            .is_nan());
+        assert_last_value_equals(
+            "Date()",
+            JsStatementResult::string(&node_graph, "Sun Sep 09 2001 02:46:40 GMT+0100"),
+        );
+    }
+
+    #[test]
+    fn test_date_getters() {
+        let node_graph = get_node_graph();
+        let cases = [
+            ("getFullYear", 2001),
+            ("getMonth", 8),
+            ("getDate", 9),
+            ("getDay", 0),
+            ("getHours", 2),
+            ("getUTCHours", 1),
+            ("getMinutes", 46),
+            ("getSeconds", 40),
+            ("getMilliseconds", 123),
+            ("getTimezoneOffset", -60),
+        ];
+        for (getter, expected) in cases {
+            assert_last_value_equals(
+                &format!("new Date(1000000000123).{getter}()"),
+                JsStatementResult::number(&node_graph, expected),
+            );
+        }
+        assert_last_value_equals(
+            "new Date(-1).getUTCFullYear()",
+            JsStatementResult::number(&node_graph, 1969),
+        );
+        assert!(run_js("new Date(NaN).getDate()").1.last().unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_date_setters() {
+        let node_graph = get_node_graph();
+        run_test(
+            "var d = new Date(0);
+            d.setUTCFullYear(2000, 1, 29);
+            d.setMonth(12);
+            d.getDate();
+            d.setUTCHours(25, 1);
+            d.setMinutes(-1, 2, 3);
+            d.setTime(5);
+            d.getTime();",
+            vec![
+                // The date itself, as it is after the last setter
+                JsStatementResult::Value(JsValue::date_rc(
+                    &node_graph,
+                    JsDate::new(5.0, Rc::new(FixedOffset::UTC)),
+                )),
+                JsStatementResult::number(&node_graph, 951_782_400_000.0),
+                JsStatementResult::number(&node_graph, 980_726_400_000.0),
+                JsStatementResult::number(&node_graph, 29),
+                JsStatementResult::number(&node_graph, 980_816_460_000.0),
+                JsStatementResult::number(&node_graph, 980_816_342_003.0),
+                JsStatementResult::number(&node_graph, 5),
+                JsStatementResult::number(&node_graph, 5),
+            ],
+        );
+        assert!(run_js("new Date(NaN).setDate(1)")
+            .1
+            .last()
+            .unwrap()
+            .is_nan());
+        assert_last_value_equals(
+            "new Date(NaN).setFullYear(2000)",
+            JsStatementResult::number(&node_graph, 946_681_200_000.0),
+        );
+    }
+
+    #[test]
+    fn test_date_strings() {
+        let node_graph = get_node_graph();
+        let cases = [
+            ("d.toISOString()", "2001-09-09T01:46:40.123Z"),
+            ("d.toJSON()", "2001-09-09T01:46:40.123Z"),
+            ("d.toUTCString()", "Sun, 09 Sep 2001 01:46:40 GMT"),
+            ("d.toString()", "Sun Sep 09 2001 02:46:40 GMT+0100"),
+            ("d.toDateString()", "Sun Sep 09 2001"),
+            ("d.toTimeString()", "02:46:40 GMT+0100"),
+            ("'' + d", "Sun Sep 09 2001 02:46:40 GMT+0100"),
+            ("new Date(NaN).toString()", "Invalid Date"),
+        ];
+        for (script, expected) in cases {
+            assert_last_value_equals(
+                &format!("var d = new Date(1000000000123); {script}"),
+                JsStatementResult::string(&node_graph, expected),
+            );
+        }
+        assert_last_value_equals(
+            "new Date(NaN).toJSON()",
+            JsStatementResult::null(&node_graph),
+        );
+        assert_last_value_equals(
+            "new Date(NaN).toISOString()",
+            JsStatementResult::ThrowValue(JsValue::range_error_rc(&node_graph)),
+        );
+        assert_last_value_equals(
+            "Date.prototype.getTime.call({})",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_date_parse() {
+        let node_graph = get_node_graph();
+        let cases = [
+            (
+                "Date.parse('2001-09-09T01:46:40.123Z')",
+                1_000_000_000_123.0,
+            ),
+            ("Date.parse('2001-09-09T02:46:40')", 1e12),
+            ("Date.parse('2001-09-09')", 999_993_600_000.0),
+            ("Date.parse('Sun, 09 Sep 2001 01:46:40 GMT')", 1e12),
+            ("Date.parse(new Date(1000000000000).toString())", 1e12),
+            ("Date.parse(new Date(1000000000000).toUTCString())", 1e12),
+        ];
+        for (script, expected) in cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+        assert!(run_js("Date.parse('2001-02-29')")
+            .1
+            .last()
+            .unwrap()
+            .is_nan());
+    }
+
+    #[test]
+    fn test_typed_arrays() {
+        let node_graph = get_node_graph();
+        let number_cases = [
+            ("new Uint8Array(4).length", 4),
+            ("new Int16Array(3).byteLength", 6),
+            ("Float32Array.BYTES_PER_ELEMENT", 4),
+            ("new Uint32Array(1).BYTES_PER_ELEMENT", 4),
+            ("var a = new Uint8Array(2); a[0] = 257; a[1] = -1; a[0] + a[1]", 256),
+            ("var a = new Int8Array(1); a[5] = 1; a[0] + a.length", 1),
+            (
+                "var a = new Uint8Array([1, 2, 3, 4]); var s = a.subarray(1, 3); s[0] = 9;
+                a[1] + s.length * 10 + s.byteOffset * 100",
+                129,
+            ),
+            (
+                "var a = new Uint8Array([1, 2, 3, 4]); var c = a.slice(-2); c[0] = 7; a[2] * 10 + c[0]",
+                37,
+            ),
+            (
+                "var b = new ArrayBuffer(8); var f = new Float64Array(b); f[0] = 1;
+                new Uint8Array(b, 6)[1] + new Uint16Array(b, 2, 1).length",
+                64,
+            ),
+        ];
+        for (script, expected) in number_cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+
+        let string_cases = [
+            ("'' + new Uint8Array([1, 2, 3])", "1,2,3"),
+            ("'' + new Int8Array(new Uint8Array([255, 1]))", "-1,1"),
+            ("'' + new Uint8ClampedArray(3).fill(300, 1)", "0,255,255"),
+            ("'' + new Float32Array([0.5, NaN])", "0.5,NaN"),
+            (
+                "var a = new Uint8Array(4); a.set([5, 6], 2); '' + a",
+                "0,0,5,6",
+            ),
+            ("'' + [...new Int8Array([1, -129])]", "1,127"),
+            ("'' + [...new Uint8Array([7, 8]).entries()]", "0,7,1,8"),
+            ("'' + new ArrayBuffer(2)", "[object ArrayBuffer]"),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+
+        let error_cases = [
+            ("Uint8Array(2)", JsValue::type_error_rc(&node_graph)),
+            ("new ArrayBuffer(-1)", JsValue::range_error_rc(&node_graph)),
+            (
+                "new Uint16Array(new ArrayBuffer(3))",
+                JsValue::range_error_rc(&node_graph),
+            ),
+            (
+                "new Uint16Array(new ArrayBuffer(4), 1)",
+                JsValue::range_error_rc(&node_graph),
+            ),
+            (
+                "new Uint8Array(new ArrayBuffer(4), 2, 3)",
+                JsValue::range_error_rc(&node_graph),
+            ),
+            (
+                "new Uint8Array(2).set([1, 2, 3])",
+                JsValue::range_error_rc(&node_graph),
+            ),
+        ];
+        for (script, error) in error_cases {
+            assert_last_value_equals(script, JsStatementResult::ThrowValue(error));
+        }
+    }
+
+    #[test]
+    fn test_array_buffer() {
+        let node_graph = get_node_graph();
+        let cases = [
+            ("new ArrayBuffer(5).byteLength", 5),
+            ("new ArrayBuffer(5).slice(1, -1).byteLength", 3),
+            (
+                "var a = new Uint8Array([1, 2]); new Uint8Array(a.buffer.slice(1))[0]",
+                2,
+            ),
+        ];
+        for (script, expected) in cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+        assert_last_value_equals(
+            "new ArrayBuffer(2)[0]",
+            JsStatementResult::undefined(&node_graph),
+        );
+        assert_last_value_equals(
+            "var b = new ArrayBuffer(2); new Uint8Array(b).buffer === b",
             JsStatementResult::bool(&node_graph, true),
         );
         assert_last_value_equals(
-            "{} + ''",
-            JsStatementResult::string(&node_graph, "[object Object]"),
-        );
-
-        run_test("1 != 1", vec![JsStatementResult::bool(&node_graph, false)]);
-
-        run_test(
-            "1 == ' 01  '",
-            vec![JsStatementResult::bool(&node_graph, true)],
-        );
-        assert_eq!(
-            run_js("  function  abc ( ) { } abc == 'function  abc ( ) { }'").1[1],
-            JsStatementResult::bool(&node_graph, true),
+            "ArrayBuffer.isView(new DataView(new ArrayBuffer(1))) ? ArrayBuffer.isView(new ArrayBuffer(1)) : true",
+            JsStatementResult::bool(&node_graph, false),
         );
     }
 
     #[test]
-    fn test_recursion() {
+    fn test_data_view() {
         let node_graph = get_node_graph();
+        let cases = [
+            ("var v = new DataView(new ArrayBuffer(4)); v.setUint16(0, 258); v.getUint8(1)", 2),
+            ("var v = new DataView(new ArrayBuffer(4)); v.setUint16(0, 258, true); v.getUint8(1)", 1),
+            ("var v = new DataView(new ArrayBuffer(4)); v.setInt32(0, -2); v.getUint32(0)", 4294967294_u32),
+            (
+                "var v = new DataView(new ArrayBuffer(4)); v.setFloat32(0, 1.5); v.getUint16(0)",
+                0x3fc0,
+            ),
+            (
+                "var b = new ArrayBuffer(8); var v = new DataView(b, 2, 4); v.setInt8(0, -1);
+                new Uint8Array(b)[2] + v.byteOffset + v.byteLength",
+                261,
+            ),
+        ];
+        for (script, expected) in cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
         assert_last_value_equals(
-            "function factorial(num) {if (num == 1) { return 1; } return num * factorial(num + -1);} factorial(5)",
-            JsStatementResult::number(&node_graph, 120),
+            "new DataView(new ArrayBuffer(4), 1).getUint32(0)",
+            JsStatementResult::ThrowValue(JsValue::range_error_rc(&node_graph)),
         );
-        // Infinite recursion leads to undefined right now instead of a stack overflow exception
         assert_last_value_equals(
-            "function recurse(num) {recurse(num + 1);} recurse(1)",
-            JsStatementResult::ThrowValue(JsValue::stack_overflow_error_rc(&node_graph)),
+            "new DataView(new Uint8Array(4))",
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
         );
     }
 
     #[test]
-    fn test_object_literal() {
+    fn test_text_encoding() {
         let node_graph = get_node_graph();
-        run_test("{}", vec![JsStatementResult::object(&node_graph, vec![])]);
-        run_test(
-            " { 'a' : 1 } ",
-            vec![JsStatementResult::object(
-                &node_graph,
-                vec![("a", JsValue::number_rc(&node_graph, 1))],
-            )],
-        );
-        run_test(
-            "{'a': 1, 'b': 2}",
-            vec![JsStatementResult::object(
-                &node_graph,
-                vec![
-                    ("a", JsValue::number_rc(&node_graph, 1)),
-                    ("b", JsValue::number_rc(&node_graph, 2)),
-                ],
-            )],
-        );
-        run_test(
-            "{'a': 1, 'b': 2,}",
-            vec![JsStatementResult::object(
-                &node_graph,
-                vec![
-                    ("a", JsValue::number_rc(&node_graph, 1)),
-                    ("b", JsValue::number_rc(&node_graph, 2)),
-                ],
-            )],
-        );
-        run_test(
-            "{'a': 1, 'a': 2}",
-            vec![JsStatementResult::object(
-                &node_graph,
-                vec![("a", JsValue::number_rc(&node_graph, 2))],
-            )],
-        );
-        run_test(
-            "{'a': 1 + 2, 'b': {}}",
-            vec![JsStatementResult::object(
-                &node_graph,
-                vec![
-                    ("a", JsValue::number_rc(&node_graph, 3)),
-                    ("b", JsValue::object_rc(&node_graph, HashMap::new())),
-                ],
-            )],
+        let string_cases = [
+            ("'' + new TextEncoder().encode('aé€')", "97,195,169,226,130,172"),
+            ("new TextEncoder().encoding", "utf-8"),
+            (
+                "new TextDecoder().decode(new Uint8Array([239, 187, 191, 104, 105]))",
+                "hi",
+            ),
+            (
+                "new TextDecoder(' UTF8 ').decode(new TextEncoder().encode('aé€').buffer)",
+                "aé€",
+            ),
+            (
+                "new TextDecoder('utf-8', {'ignoreBOM': true}).decode(new Uint8Array([239, 187, 191]))",
+                "\u{feff}",
+            ),
+            (
+                "new TextDecoder().decode(new DataView(new Uint8Array([104, 255]).buffer))",
+                "h\u{fffd}",
+            ),
+            ("new TextDecoder().decode()", ""),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+        assert_last_value_equals(
+            "new TextDecoder('utf-8', {'fatal': true}).fatal",
+            JsStatementResult::bool(&node_graph, true),
         );
+
+        let error_cases = [
+            (
+                "new TextDecoder('latin1')",
+                JsValue::range_error_rc(&node_graph),
+            ),
+            (
+                "new TextDecoder('utf-8', {'fatal': true}).decode(new Uint8Array([255]))",
+                JsValue::type_error_rc(&node_graph),
+            ),
+            (
+                "new TextDecoder().decode('abc')",
+                JsValue::type_error_rc(&node_graph),
+            ),
+            ("TextEncoder()", JsValue::type_error_rc(&node_graph)),
+        ];
+        for (script, error) in error_cases {
+            assert_last_value_equals(script, JsStatementResult::ThrowValue(error));
+        }
     }
 
     #[test]
-    fn test_object_operations() {
+    fn test_proxy() {
         let node_graph = get_node_graph();
-        assert!(run_js("5 * {}").1.last().unwrap().is_nan());
-        run_test(
-            "5 + {}",
-            vec![JsStatementResult::string(&node_graph, "5[object Object]")],
-        );
+        let string_cases = [
+            (
+                "function trapGet(t, k, r) { return k + '!'; } new Proxy({}, { get: trapGet }).a",
+                "a!",
+            ),
+            (
+                "function trapGet(t, k, r) { return k + '!'; } var p = new Proxy({}, { get: trapGet }); \
+                var o = { __proto__: p }; o.b",
+                "b!",
+            ),
+            (
+                "function trapKeys(t) { return ['b', 'a']; } \
+                function trapDescriptor(t, k) { return { value: k, enumerable: true, configurable: true }; } \
+                '' + Object.keys(new Proxy({}, { ownKeys: trapKeys, getOwnPropertyDescriptor: trapDescriptor }))",
+                "b,a",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+
+        let number_cases = [
+            (
+                "var o = { a: 1 }; var p = new Proxy(o, {}); p.b = 2; o.b + p.a",
+                3,
+            ),
+            (
+                "function trapSet(t, k, v, r) { t[k] = v * 2; return true; } \
+                var p = new Proxy({}, { set: trapSet }); p.a = 2; p.a",
+                4,
+            ),
+            (
+                "function f(a, b) { return a + b; } \
+                function trapApply(t, thisArg, args) { return t(args[0], args[1]) * 10; } \
+                new Proxy(f, { apply: trapApply })(1, 2)",
+                30,
+            ),
+            ("function f(a) { return a + 1; } new Proxy(f, {})(1)", 2),
+            (
+                "function C(a) { this.a = a; } \
+                function trapConstruct(t, args, newTarget) { return { a: args[0] + 1 }; } \
+                var P = new Proxy(C, { construct: trapConstruct }); new P(1).a",
+                2,
+            ),
+            (
+                "function C(a) { this.a = a; } C.prototype.b = 2; var P = new Proxy(C, {}); \
+                var c = new P(5); c.a + c.b",
+                7,
+            ),
+            ("var r = Proxy.revocable({ a: 1 }, {}); r.proxy.a", 1),
+        ];
+        for (script, expected) in number_cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+
+        let bool_cases = [
+            (
+                "var proto = {}; function trapProto(t) { return proto; } \
+                Object.getPrototypeOf(new Proxy({}, { getPrototypeOf: trapProto })) === proto",
+                true,
+            ),
+            (
+                "var p = new Proxy({ get self() { return this; } }, {}); p.self === p",
+                true,
+            ),
+            (
+                "var t = { a: 1 }; var p = new Proxy(t, {}); Object.freeze(p); Object.isFrozen(t)",
+                true,
+            ),
+            (
+                "var t = {}; var p = new Proxy(t, {}); Object.defineProperty(p, 'a', { value: 1 }); \
+                Object.getOwnPropertyDescriptor(t, 'a').configurable",
+                false,
+            ),
+        ];
+        for (script, expected) in bool_cases {
+            assert_last_value_equals(script, JsStatementResult::bool(&node_graph, expected));
+        }
+
+        let error_cases = [
+            "Proxy({}, {})",
+            "new Proxy(1, {})",
+            "new Proxy({}, null)",
+            "new Proxy({}, { get: 1 }).a",
+            "new Proxy({}, {})()",
+            "var r = Proxy.revocable({}, {}); r.revoke(); r.proxy.a",
+        ];
+        for script in error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
     }
 
     #[test]
-    fn test_object_access() {
+    fn test_proxy_invariants() {
         let node_graph = get_node_graph();
+        let error_cases = [
+            "var t = {}; Object.defineProperty(t, 'a', { value: 1 }); \
+            function trapGet(t, k) { return 2; } new Proxy(t, { get: trapGet }).a",
+            "var t = {}; Object.defineProperty(t, 'a', { value: 1 }); \
+            function trapSet(t, k, v) { return true; } Reflect.set(new Proxy(t, { set: trapSet }), 'a', 2)",
+            "var t = {}; Object.defineProperty(t, 'a', { value: 1 }); \
+            function trapHas(t, k) { return false; } Reflect.has(new Proxy(t, { has: trapHas }), 'a')",
+            "var t = {}; Object.defineProperty(t, 'a', { value: 1 }); \
+            function trapDelete(t, k) { return true; } Reflect.deleteProperty(new Proxy(t, { deleteProperty: trapDelete }), 'a')",
+            "var t = {}; Object.defineProperty(t, 'a', { value: 1 }); \
+            function trapKeys(t) { return []; } Reflect.ownKeys(new Proxy(t, { ownKeys: trapKeys }))",
+            "function trapKeys(t) { return ['a', 'a']; } Reflect.ownKeys(new Proxy({}, { ownKeys: trapKeys }))",
+            "function trapKeys(t) { return [1]; } Reflect.ownKeys(new Proxy({}, { ownKeys: trapKeys }))",
+            "function trapKeys(t) { return ['a']; } \
+            Reflect.ownKeys(new Proxy(Object.preventExtensions({}), { ownKeys: trapKeys }))",
+            "function trapDescriptor(t, k) { return { value: 1 }; } \
+            Object.getOwnPropertyDescriptor(new Proxy({}, { getOwnPropertyDescriptor: trapDescriptor }), 'a')",
+            "function trapDefine(t, k, d) { return true; } \
+            Object.defineProperty(new Proxy({}, { defineProperty: trapDefine }), 'a', { configurable: false })",
+            "function trapExtensible(t) { return false; } \
+            Object.isExtensible(new Proxy({}, { isExtensible: trapExtensible }))",
+            "function trapPrevent(t) { return true; } \
+            Object.preventExtensions(new Proxy({}, { preventExtensions: trapPrevent }))",
+            "function trapProto(t) { return 1; } Object.getPrototypeOf(new Proxy({}, { getPrototypeOf: trapProto }))",
+            "function trapProto(t) { return {}; } \
+            Object.getPrototypeOf(new Proxy(Object.preventExtensions({}), { getPrototypeOf: trapProto }))",
+            "function C() {} function trapConstruct(t, args) { return 1; } \
+            var P = new Proxy(C, { construct: trapConstruct }); new P()",
+        ];
+        for script in error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+
+        // Traps may report anything about configurable properties of extensible targets
         assert_last_value_equals(
-            "var a=true; a.foo",
-            JsStatementResult::undefined(&node_graph),
+            "var t = { a: 1 }; function trapGet(t, k) { return 2; } \
+            function trapHas(t, k) { return false; } \
+            var p = new Proxy(t, { get: trapGet, has: trapHas }); p.a + Reflect.has(p, 'a')",
+            JsStatementResult::number(&node_graph, 2),
         );
+    }
+
+    #[test]
+    fn test_reflect() {
+        let node_graph = get_node_graph();
+        let number_cases = [
+            ("Reflect.get({ a: 1 }, 'a')", 1),
+            (
+                "Reflect.get({ get a() { return this.b; } }, 'a', { b: 2 })",
+                2,
+            ),
+            ("var o = {}; Reflect.set(o, 'a', 1); o.a", 1),
+            ("var a = [1]; Reflect.set(a, 1, 2); a.length", 2),
+            (
+                "var o = {}; var receiver = {}; Reflect.set(o, 'a', 1, receiver); receiver.a",
+                1,
+            ),
+            (
+                "function f(a, b) { return this.c + a + b; } Reflect.apply(f, { c: 1 }, [2, 3])",
+                6,
+            ),
+            (
+                "function C(a) { this.a = a; } Reflect.construct(C, [4]).a",
+                4,
+            ),
+            (
+                "function A() {} function B() {} B.prototype.x = 7; Reflect.construct(A, [], B).x",
+                7,
+            ),
+            ("var o = {}; Reflect.setPrototypeOf(o, { x: 3 }); o.x", 3),
+        ];
+        for (script, expected) in number_cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+
+        let bool_cases = [
+            ("Reflect.set(Object.freeze({ a: 1 }), 'a', 2)", false),
+            ("Reflect.has({}, 'toString')", true),
+            (
+                "var o = { a: 1 }; Reflect.deleteProperty(o, 'a'); Reflect.has(o, 'a')",
+                false,
+            ),
+            ("Reflect.deleteProperty([], 'length')", false),
+            (
+                "Reflect.defineProperty(Object.freeze({}), 'a', { value: 1 })",
+                false,
+            ),
+            (
+                "Reflect.getOwnPropertyDescriptor({ a: 1 }, 'a').writable",
+                true,
+            ),
+            (
+                "Reflect.getPrototypeOf(Reflect) === Object.getPrototypeOf({})",
+                true,
+            ),
+            (
+                "var a = {}; var b = { __proto__: a }; Reflect.setPrototypeOf(a, b)",
+                false,
+            ),
+            (
+                "Reflect.setPrototypeOf(Object.preventExtensions({}), {})",
+                false,
+            ),
+            ("Reflect.preventExtensions({})", true),
+            ("Reflect.isExtensible(Object.preventExtensions({}))", false),
+        ];
+        for (script, expected) in bool_cases {
+            assert_last_value_equals(script, JsStatementResult::bool(&node_graph, expected));
+        }
+
         assert_last_value_equals(
-            "var a=123; a.foo",
-            JsStatementResult::undefined(&node_graph),
+            "'' + Reflect.ownKeys([1, 2])",
+            JsStatementResult::string(&node_graph, "0,1,length"),
         );
         assert_last_value_equals(
-            "var a='abc'; a.foo",
+            "Reflect.getOwnPropertyDescriptor({}, 'a')",
             JsStatementResult::undefined(&node_graph),
         );
-        assert_last_value_equals("var a={}; a.foo", JsStatementResult::undefined(&node_graph));
+        for script in [
+            "Reflect.get(1, 'a')",
+            "Reflect.apply(1, null, [])",
+            "Reflect.setPrototypeOf({}, 1)",
+        ] {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_operators() {
+        let node_graph = get_node_graph();
+        let number_cases = [
+            ("7 - 2 - 1", 4.0),
+            ("var a = 3; -a", -3.0),
+            ("1 + 2 * 3 - 4 / 2", 5.0),
+            ("7 % 3", 1.0),
+            ("2 ** 3 ** 2", 512.0),
+            ("(1 + 1) / 4", 0.5),
+            ("1 << 31", -2147483648.0),
+            ("-8 >> 1", -4.0),
+            ("-1 >>> 28", 15.0),
+            ("6 & 3 | 8 ^ 1", 11.0),
+            ("~5", -6.0),
+        ];
+        for (script, expected) in number_cases {
+            assert_last_value_equals(script, JsStatementResult::number(&node_graph, expected));
+        }
+
+        let bool_cases = [
+            ("1 < 2", true),
+            ("2 <= 1", false),
+            ("'b' > 'a'", true),
+            ("'10' < '9'", true),
+            ("'10' < 9", false),
+            ("1 >= NaN", false),
+        ];
+        for (script, expected) in bool_cases {
+            assert_last_value_equals(script, JsStatementResult::bool(&node_graph, expected));
+        }
+    }
+
+    #[test]
+    fn test_bigint() {
+        let node_graph = get_node_graph();
+        let string_cases = [
+            ("(123n).toString()", "123"),
+            ("(1_000n * 1_000_000_000_000_000_000n).toString()", "1000000000000000000000"),
+            ("(2n ** 100n).toString()", "1267650600228229401496703205376"),
+            ("(-7n / 2n).toString()", "-3"),
+            ("(-7n % 2n).toString()", "-1"),
+            ("(1n - 3n).toString()", "-2"),
+            ("(-5n >> 1n).toString()", "-3"),
+            ("(1n << 70n).toString(16)", "400000000000000000"),
+            ("(~0n & 255n | 256n ^ 1n).toString()", "511"),
+            ("(255n).toString(2)", "11111111"),
+            ("BigInt('0x10') + ''", "16"),
+            ("BigInt(true) + 'n'", "1n"),
+            ("BigInt(2 ** 60) + ''", "1152921504606846976"),
+            ("BigInt.asIntN(8, 255n) + ''", "-1"),
+            ("BigInt.asUintN(64, -1n) + ''", "18446744073709551615"),
+            ("var a = new BigInt64Array([-1n, 2n]); a[0] + ',' + a[1]", "-1,2"),
+            ("0n ? 'truthy' : 'falsy'", "falsy"),
+            ("var a = new BigUint64Array(1); a[0] = -1n; a[0] + ''", "18446744073709551615"),
+            (
+                "var v = new DataView(new ArrayBuffer(8)); v.setBigInt64(0, -2n);                 v.getBigUint64(0) + ''",
+                "18446744073709551614",
+            ),
+        ];
+        for (script, expected) in string_cases {
+            assert_last_value_equals(script, JsStatementResult::string(&node_graph, expected));
+        }
+
+        let bool_cases = [
+            ("1n === 1n", true),
+            ("1n === 1", false),
+            ("1n == 1", true),
+            ("2n == '2'", true),
+            ("1n < 1.5", true),
+            ("10n > 9", true),
+            ("2n > '10'", false),
+            ("new Map([[1n, 'a']]).has(1n)", true),
+        ];
+        for (script, expected) in bool_cases {
+            assert_last_value_equals(script, JsStatementResult::bool(&node_graph, expected));
+        }
+
+        let type_error_cases = [
+            "1n + 1",
+            "1n * 2",
+            "+1n",
+            "1n >>> 0n",
+            "new BigInt(1)",
+            "BigInt(undefined)",
+            "new BigInt64Array([1])",
+            "new Float64Array([1n])",
+        ];
+        for script in type_error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+            );
+        }
+
+        let range_error_cases = ["1n / 0n", "2n ** -1n", "BigInt(1.5)", "(1n).toString(37)"];
+        for script in range_error_cases {
+            assert_last_value_equals(
+                script,
+                JsStatementResult::ThrowValue(JsValue::range_error_rc(&node_graph)),
+            );
+        }
+
         assert_last_value_equals(
-            "var a={'not foo': 'bar'}; a.foo",
-            JsStatementResult::undefined(&node_graph),
+            "BigInt('1.5')",
+            JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
         );
-        assert_last_value_equals(
-            "var a={'foo': 'bar'}; a.foo",
-            JsStatementResult::string(&node_graph, "bar"),
+    }
+
+    #[test]
+    fn test_module_named_exports() {
+        let node_graph = get_node_graph();
+        let lib = "export const a = 1;
+            export let b = 2;
+            export function add(x, y) { return x + y }
+            export class Point { constructor(x) { this.x = x } }
+            const d = 4;
+            export { d, d as e };";
+        assert_module_result_equals(
+            &[
+                ("lib.js", lib),
+                (
+                    "main.js",
+                    "import { a, b as two, add, Point, e, } from './lib.js';
+                    add(a, two) * new Point(e).x",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 12),
         );
-        assert_last_value_equals(
-            "var a={'foo': {'bar': 123}}; a.foo.bar",
-            JsStatementResult::number(&node_graph, 123),
+        assert_module_result_equals(
+            &[
+                ("lib.js", lib),
+                ("main.js", "import { a } from './lib.js'; a = 2"),
+            ],
+            JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
         );
     }
 
     #[test]
-    fn test_prototype_to_string() {
+    fn test_module_default_exports() {
         let node_graph = get_node_graph();
-        assert_last_value_equals(
-            "var a = true; a.toString()",
-            JsStatementResult::string(&node_graph, "true"),
+        assert_module_result_equals(
+            &[
+                ("six.js", "export default 2 * 3;"),
+                (
+                    "double.js",
+                    "export default function double(x) { return x * 2 }",
+                ),
+                (
+                    "main.js",
+                    "import six from './six.js';
+                    import double, * as lib from './double.js';
+                    import { default as twice } from './double.js';
+                    double(six) + twice(1) + lib['default'](1)",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 16),
         );
-        assert_last_value_equals(
-            "var a = 5; a.toString()",
-            JsStatementResult::string(&node_graph, "5"),
+    }
+
+    #[test]
+    fn test_module_namespace() {
+        let node_graph = get_node_graph();
+        let modules = [
+            ("lib.js", "export const b = 2; export const a = 1;"),
+            (
+                "main.js",
+                "import * as lib from './lib.js';
+                lib.b = 3;
+                [Object.keys(lib), lib.a + lib.b]",
+            ),
+        ];
+        assert_module_result_equals(
+            &modules,
+            JsStatementResult::Value(JsValue::array_rc(
+                &node_graph,
+                vec![
+                    JsValue::array_rc(
+                        &node_graph,
+                        vec![
+                            JsValue::str_rc(&node_graph, "a"),
+                            JsValue::str_rc(&node_graph, "b"),
+                        ],
+                    ),
+                    JsValue::number_rc(&node_graph, 3),
+                ],
+            )),
         );
-        assert_last_value_equals(
-            "var a = {}; a.toString()",
-            JsStatementResult::string(&node_graph, "[object Object]"),
+    }
+
+    #[test]
+    fn test_module_re_exports() {
+        let node_graph = get_node_graph();
+        assert_module_result_equals(
+            &[
+                ("a.js", "export const a = 1; export default 10;"),
+                ("b.js", "export const b = 2; export const a = 3;"),
+                ("c.js", "export const c = 4;"),
+                (
+                    "all.js",
+                    "export { a as x, default } from './a.js';
+                    export * from './b.js';
+                    export * from './c.js';
+                    export * as nested from './c.js';",
+                ),
+                (
+                    "main.js",
+                    "import { x, b, c, nested, default as ten } from './all.js';
+                    x + b * c + nested.c * ten",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 49),
+        );
+        // Names exported by more than one `export *` are ambiguous
+        assert_module_result_equals(
+            &[
+                ("a.js", "export const a = 1;"),
+                ("b.js", "export const a = 2;"),
+                ("all.js", "export * from './a.js'; export * from './b.js';"),
+                ("main.js", "import { a } from './all.js';"),
+            ],
+            JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph)),
         );
     }
 
     #[test]
-    fn test_condition() {
+    fn test_module_scope() {
         let node_graph = get_node_graph();
-        assert_last_value_equals("1 ? 2 : 3", JsStatementResult::number(&node_graph, 2));
-        assert_last_value_equals("false ? 2 : 3", JsStatementResult::number(&node_graph, 3));
-        assert_last_value_equals(
-            "1 ? 0 ? 3 : 4 : 5",
-            JsStatementResult::number(&node_graph, 4),
+        let lib = "let count = 1;
+            export let current = count;
+            export function next() {
+                count = count + 1;
+                current = count;
+                return count
+            }";
+        assert_module_result_equals(
+            &[
+                ("lib.js", lib),
+                (
+                    "main.js",
+                    "import { next, current } from './lib.js'; next(); next(); current",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 3),
         );
-        assert_last_value_equals(
-            "1 + 1 ? 2 + 2 : 3 + 3",
+        assert_module_result_equals(
+            &[
+                ("lib.js", lib),
+                ("main.js", "import { next } from './lib.js'; count"),
+            ],
+            JsStatementResult::ThrowValue(JsValue::reference_error_rc(&node_graph)),
+        );
+    }
+
+    #[test]
+    fn test_module_cycle() {
+        let node_graph = get_node_graph();
+        assert_module_result_equals(
+            &[
+                (
+                    "a.js",
+                    "import { b } from './b.js'; export function a() { return 1 } b()",
+                ),
+                (
+                    "b.js",
+                    "import { a } from './a.js'; export function b() { return a() + 1 }",
+                ),
+                (
+                    "main.js",
+                    "import { a } from './a.js'; import './b.js'; a()",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 1),
+        );
+        // Functions can be called before the module declaring them has run
+        assert_module_result_equals(
+            &[
+                (
+                    "a.js",
+                    "import { b } from './b.js';
+                    export function a() { return 1 }
+                    export const early = b();",
+                ),
+                (
+                    "b.js",
+                    "import { a } from './a.js'; export function b() { return a() + 1 }",
+                ),
+                (
+                    "main.js",
+                    "import { early } from './a.js'; import { b } from './b.js'; early + b()",
+                ),
+            ],
             JsStatementResult::number(&node_graph, 4),
         );
     }
 
     #[test]
-    fn test_throw() {
+    fn test_module_errors() {
         let node_graph = get_node_graph();
-        assert_last_value_equals(
-            "throw 1;",
+        let syntax_error = || JsStatementResult::ThrowValue(JsValue::syntax_error_rc(&node_graph));
+        let type_error = || JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph));
+        let cases = [
+            ("import { nope } from './lib.js';", syntax_error()),
+            ("export { nope } from './lib.js';", syntax_error()),
+            ("import './missing.js';", type_error()),
+            ("import 'lib.js';", type_error()),
+            (
+                "import './invalid.js';",
+                JsStatementResult::ThrowValue(JsValue::syntax_error_with_message_rc(
+                    &node_graph,
+                    "Invalid or unexpected token at line 1, column 14",
+                )),
+            ),
+            (
+                "import './throws.js';",
+                JsStatementResult::ThrowValue(JsValue::number_rc(&node_graph, 1)),
+            ),
+        ];
+        for (main, expected_result) in cases {
+            assert_module_result_equals(
+                &[
+                    ("lib.js", "export const a = 1;"),
+                    ("invalid.js", "export const = 1;"),
+                    ("throws.js", "throw 1;"),
+                    ("main.js", main),
+                ],
+                expected_result,
+            );
+        }
+        // Modules that fail are not run again
+        assert_module_result_equals(
+            &[
+                ("throws.js", "throw 1;"),
+                ("main.js", "import('./throws.js')"),
+            ],
             JsStatementResult::ThrowValue(JsValue::number_rc(&node_graph, 1)),
         );
-        assert_last_value_equals(
-            "function throws(val) { throw val; } throws('abc'); ",
-            JsStatementResult::ThrowValue(JsValue::str_rc(&node_graph, "abc")),
-        );
-        assert_last_value_equals(
-            "function throws(val) { if (val) { throw val; } } throws(''); ",
-            JsStatementResult::Value(JsValue::undefined_rc(&node_graph)),
+        run_test("import { a } from './lib.js'", vec![syntax_error()]);
+        run_test(
+            "1; export const a = 1",
+            vec![JsStatementResult::number(&node_graph, 1), syntax_error()],
         );
     }
 
-    #[ignore = "Currently only has syntax parsing support"]
     #[test]
-    fn test_this() {
+    fn test_dynamic_import() {
         let node_graph = get_node_graph();
-        assert_last_value_equals(
-            "this;",
-            JsStatementResult::Value(JsValue::object_rc(&node_graph, Default::default())),
+        assert_module_result_equals(
+            &[
+                ("dir/lib.js", "export const a = 1;"),
+                (
+                    "main.js",
+                    "const lib = import('./dir/' + 'lib.js');
+                    lib === import('https://example.com/dir/lib.js') ? lib.a : 0",
+                ),
+            ],
+            JsStatementResult::number(&node_graph, 1),
         );
+        for engine in JsEngine::ALL {
+            let mut js_document = parse_js("import('./lib.js').a; import('lib.js')").unwrap();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            closure_context.modules.loader =
+                get_module_loader(&[("lib.js", "export const a = 1;")]);
+            closure_context.modules.base_url = Some("https://example.com/index.html".to_string());
+            js_document.run();
+            assert_eq!(
+                js_document.global_closure_context.expression_results,
+                vec![
+                    // The result of the module's own statement
+                    JsStatementResult::number(&node_graph, 1),
+                    JsStatementResult::number(&node_graph, 1),
+                    JsStatementResult::ThrowValue(JsValue::type_error_rc(&node_graph)),
+                ],
+                "{engine:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_steps() {
+        let node_graph = get_node_graph();
+        for engine in JsEngine::ALL {
+            let mut js_document = parse_js("1; 2; 3; 4; 5; 6; 7; 8; 9; 10").unwrap();
+            js_document.global_closure_context.engine = engine;
+            js_document.global_closure_context.limits.max_steps = Some(8);
+            js_document.run();
+            let results = &js_document.global_closure_context.expression_results;
+            assert!(results.len() < 10, "{engine:?}");
+            assert_eq!(
+                results.last().unwrap(),
+                &JsStatementResult::ThrowValue(JsValue::termination_rc(&node_graph)),
+                "{engine:?}"
+            );
+
+            // Each run gets a budget of its own
+            let nodes_graph = &js_document.global_closure_context.nodes_graph;
+            js_document.statements = parse_js_statements("11", nodes_graph).unwrap();
+            js_document.run();
+            assert_eq!(
+                js_document.global_closure_context.expression_results.last(),
+                Some(&JsStatementResult::number(&node_graph, 11)),
+                "{engine:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let node_graph = get_node_graph();
+        for engine in JsEngine::ALL {
+            let mut js_document = parse_js("1; stop(); 2; 3").unwrap();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            // Interrupts from another thread while the script runs
+            let handle = closure_context.interrupt.clone();
+            let stop = JsValue::function_rc(
+                &closure_context.nodes_graph,
+                JsFunction::Native(
+                    "stop".to_string(),
+                    JsNativeFunctionImplementation {
+                        func: Rc::new(move |closure_context, _this, _args| {
+                            let handle = handle.clone();
+                            thread::spawn(move || handle.interrupt()).join().unwrap();
+                            Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+                        }),
+                        captures: vec![],
+                    },
+                ),
+            );
+            closure_context.get_or_declare_reference_mut("stop").value = stop;
+            js_document.run();
+
+            let results = &js_document.global_closure_context.expression_results;
+            assert_eq!(results[0], JsStatementResult::number(&node_graph, 1));
+            assert_eq!(
+                results.last().unwrap(),
+                &JsStatementResult::ThrowValue(JsValue::termination_rc(&node_graph)),
+                "{engine:?}"
+            );
+            assert!(results.len() <= 3, "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_interrupt_before_run() {
+        for engine in JsEngine::ALL {
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            let handle = js_document.global_closure_context.interrupt.clone();
+            handle.interrupt();
+            // Runs started after the interrupt are terminated until it is cleared
+            for _ in 0..2 {
+                let result = js_document.run_script("1").unwrap_err();
+                assert_eq!(result.get_ref().to_string(), "Terminated", "{engine:?}");
+            }
+            handle.clear();
+            let result = js_document.run_script("1").unwrap();
+            assert_eq!(result.get_ref().to_string(), "1", "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_max_call_depth() {
+        let node_graph = get_node_graph();
+        for engine in JsEngine::ALL {
+            let mut js_document =
+                parse_js("function f(n) { return n == 0 ? 0 : f(n + -1) } f(2); f(10)").unwrap();
+            js_document.global_closure_context.engine = engine;
+            js_document.global_closure_context.limits.max_call_depth = 4;
+            js_document.run();
+            assert_eq!(
+                js_document.global_closure_context.expression_results[1..],
+                [
+                    JsStatementResult::number(&node_graph, 0),
+                    JsStatementResult::ThrowValue(JsValue::stack_overflow_error_rc(&node_graph)),
+                ],
+                "{engine:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_heap_size() {
+        for engine in JsEngine::ALL {
+            // Garbage left by earlier statements is collected rather than counted
+            let mut js_document = parse_js(&"[1, 2, 3, 4, 5, 6].length; ".repeat(10)).unwrap();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            closure_context.limits.max_heap_size =
+                Some(closure_context.nodes_graph.borrow().size() + 40);
+            js_document.run();
+            // Collected nodes no longer compare equal to new ones, so only their values are
+            // compared
+            let results = &js_document.global_closure_context.expression_results;
+            assert_eq!(
+                results.iter().map(result_as_number).collect::<Vec<_>>(),
+                vec![6.0; 10],
+                "{engine:?}"
+            );
+
+            let elements = (0..500).map(|n| n.to_string()).collect::<Vec<_>>();
+            let script = format!("var a = [{}]; 1", elements.join(", "));
+            let mut js_document = parse_js(&script).unwrap();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.engine = engine;
+            closure_context.limits.max_heap_size =
+                Some(closure_context.nodes_graph.borrow().size() + 20);
+            js_document.run();
+            let results = &js_document.global_closure_context.expression_results;
+            assert_eq!(results.len(), 2, "{engine:?}");
+            assert!(
+                matches!(&results[0], JsStatementResult::ThrowValue(value)
+                    if value.get_ref().to_string() == "RangeError"),
+                "{engine:?}"
+            );
+            assert_eq!(result_as_number(&results[1]), 1.0, "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn test_max_heap_size_between_runs() {
+        for engine in JsEngine::ALL {
+            let mut js_document = JsDocument::new(vec![]);
+            js_document.global_closure_context.engine = engine;
+            js_document.set_html_document(Rc::new(RefCell::new(parse_html("").unwrap())));
+            js_document
+                .run_script(
+                    "var count = 0; \
+                    function onClick(e) { count = count + [1, 2, 3, 4, 5, 6].length; } \
+                    addEventListener('click', onClick);",
+                )
+                .unwrap();
+            js_document.collect_garbage();
+            let closure_context = &mut js_document.global_closure_context;
+            closure_context.limits.max_heap_size =
+                Some(closure_context.nodes_graph.borrow().size() + 100);
+
+            // The garbage each run leaves is collected before the next one grows the graph past
+            // its maximum size
+            for _ in 0..20 {
+                let result = js_document.run_script("[1, 2, 3, 4, 5, 6].length").unwrap();
+                assert_eq!(result.get_ref().to_string(), "6", "{engine:?}");
+                let event = JsEvent::new("click", false, false, JsEventDetail::None);
+                js_document.dispatch_event(JsEventTargetId::Window, event);
+            }
+            let count = js_document.run_script("count").unwrap();
+            assert_eq!(count.get_ref().to_string(), "120", "{engine:?}");
+        }
     }
 
     /// Note that this test should eventually fail
@@ -848,7 +4728,7 @@ mod tests {
         assert_eq!(actual_result, &expected_result);
 
         // This shrinks as all the globals get discarded and only the root node remains.
-        assert_eq!(node_graph.borrow().size(), 14);
+        assert_eq!(node_graph.borrow().size(), 449);
         GcNodeGraph::gc(&node_graph);
         assert_eq!(node_graph.borrow().size(), 1);
 
@@ -858,4 +4738,59 @@ mod tests {
             panic!("Invalid type of actual result");
         }
     }
+
+    #[test]
+    fn test_collect_garbage() {
+        let mut js_document = parse_js("var a = {'b': 1}; ({'c': 2}); a").unwrap();
+        js_document.run();
+        let node_graph = js_document.global_closure_context.nodes_graph.clone();
+        let size = node_graph.borrow().size();
+        js_document
+            .global_closure_context
+            .expression_results
+            .clear();
+        js_document.collect_garbage();
+        assert!(node_graph.borrow().size() < size);
+
+        js_document.statements = parse_js_statements("a.b + new Map().size", &node_graph).unwrap();
+        js_document.run();
+        assert_eq!(
+            js_document.global_closure_context.expression_results,
+            vec![JsStatementResult::number(&node_graph, 1)]
+        );
+    }
+
+    #[test]
+    fn test_collect_detached_nodes() {
+        let document = Rc::new(RefCell::new(parse_html("<body><p>a</p></body>").unwrap()));
+        let mut js_document = JsDocument::new(vec![]);
+        js_document.set_html_document(document.clone());
+        js_document
+            .run_script(
+                "var kept = document.createElement('div'); \
+                kept.appendChild(document.createTextNode('b')); \
+                document.createElement('span'); \
+                document.body.innerHTML = '<i>c</i>';",
+            )
+            .unwrap();
+        assert_eq!(document.borrow().detached.len(), 3);
+
+        // Only the nodes scripts can still reach are kept
+        js_document
+            .global_closure_context
+            .expression_results
+            .clear();
+        js_document.collect_garbage();
+        let detached = document
+            .borrow()
+            .detached
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(detached, vec!["<div>b</div>"]);
+        let result = js_document
+            .run_script("document.body.appendChild(kept); document.body.innerHTML")
+            .unwrap();
+        assert_eq!(result.get_ref().to_string(), "<i>c</i><div>b</div>");
+    }
 }
@@ -1,56 +1,232 @@
 use super::{
-    build_object_prototype, build_prototype, JsClosure, JsClosureContext, JsFunction,
-    JsFunctionResult, JsNativeFunctionImplementation, JsValue, JsValueGraph, JsValueNode,
+    build_array_buffer_constructor, build_array_buffer_prototype, build_array_prototype,
+    build_bigint_constructor, build_bigint_prototype, build_data_view_constructor,
+    build_data_view_prototype, build_date_constructor, build_date_prototype, build_eval_function,
+    build_event_target_prototype, build_function_constructor, build_function_entry,
+    build_host_constructor, build_host_constructor_with_references, build_iterator_prototype,
+    build_map_prototype, build_native_function, build_object_constructor, build_object_prototype,
+    build_promise_constructor, build_prototype, build_proxy_constructor, build_reflect_object,
+    build_regexp_constructor, build_regexp_prototype, build_set_prototype, build_string_prototype,
+    build_symbol_prototype, build_typed_array_constructor, build_typed_array_kind_prototype,
+    build_typed_array_prototype, build_weak_map_prototype, build_weak_set_prototype,
+    build_xml_http_request_constructor, collect_iterable, get_argument,
+    get_member_from_prototype_chain, js_fetch, JsClosure, JsClosureContext, JsCollectionKind,
+    JsEvent, JsFunctionResult, JsHeaders, JsHostClass, JsKeyedCollection, JsProperty,
+    JsPropertyKey, JsRequest, JsResponse, JsSymbol, JsTextDecoder, JsTextEncoder, JsTypedArrayKind,
+    JsUrl, JsUrlSearchParams, JsValue, JsValueGraph, JsValueNode, JsWellKnownSymbol,
+    CONSTRUCTOR_PROTOTYPE_MEMBER, EVAL_REFERENCE,
 };
-use crate::{garbage_collector::GcNodeGraph, util::Base64};
-use std::rc::Rc;
+use crate::util::Base64;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use wowser_macros::js_method;
 
 pub fn add_globals(closure_context: &mut JsClosureContext) {
+    let object = build_object_constructor(closure_context.global_prototypes.object.clone());
+    let symbol = build_symbol_constructor(&closure_context.nodes_graph);
+    let bigint = build_bigint_constructor(closure_context.global_prototypes.bigint.clone());
+    let regexp = build_regexp_constructor(closure_context.global_prototypes.regexp.clone());
+    let date = build_date_constructor(closure_context.global_prototypes.date.clone());
+    let function = build_function_constructor(closure_context.global_prototypes.function.clone());
+    let eval = closure_context.global_prototypes.eval.clone();
+    let array_buffer =
+        build_array_buffer_constructor(closure_context.global_prototypes.array_buffer.clone());
+    let typed_array_constructors = JsTypedArrayKind::ALL.map(|kind| {
+        let prototype = closure_context.global_prototypes.typed_arrays[&kind].clone();
+        (kind.name(), build_typed_array_constructor(kind, prototype))
+    });
+    let data_view =
+        build_data_view_constructor(closure_context.global_prototypes.data_view.clone());
+    let proxy = build_proxy_constructor(&closure_context.nodes_graph);
+    let reflect = build_reflect_object(closure_context.global_prototypes.object.clone());
+    let text_encoder = build_host_constructor(closure_context, JsTextEncoder::construct);
+    let text_decoder = build_host_constructor(closure_context, JsTextDecoder::construct);
+    let event = build_host_constructor(closure_context, JsEvent::construct);
+    let promise = build_promise_constructor(closure_context);
+    let headers = build_host_constructor(closure_context, JsHeaders::construct);
+    let request = build_host_constructor_with_references(closure_context, JsRequest::construct);
+    let response = build_host_constructor_with_references(closure_context, JsResponse::construct);
+    let xml_http_request = build_xml_http_request_constructor(closure_context);
+    let url = build_host_constructor_with_references(closure_context, JsUrl::construct);
+    let url_search_params = build_host_constructor(closure_context, JsUrlSearchParams::construct);
+    let collection_constructors = [
+        (
+            JsCollectionKind::Map,
+            closure_context.global_prototypes.map.clone(),
+        ),
+        (
+            JsCollectionKind::Set,
+            closure_context.global_prototypes.set.clone(),
+        ),
+        (
+            JsCollectionKind::WeakMap,
+            closure_context.global_prototypes.weak_map.clone(),
+        ),
+        (
+            JsCollectionKind::WeakSet,
+            closure_context.global_prototypes.weak_set.clone(),
+        ),
+    ]
+    .map(|(kind, prototype)| (kind.name(), build_collection_constructor(kind, prototype)));
+
     let global_closure = closure_context.get_lastest_closure();
     add_global_function(global_closure, "atob", js_atob);
     add_global_function(global_closure, "btoa", js_btoa);
+    add_global_function(global_closure, "fetch", js_fetch);
+    add_global_value(global_closure, "Object", object);
+    add_global_value(global_closure, "Symbol", symbol);
+    add_global_value(global_closure, "BigInt", bigint);
+    add_global_value(global_closure, "RegExp", regexp);
+    add_global_value(global_closure, "Date", date);
+    add_global_value(global_closure, "Function", function);
+    add_global_value(global_closure, EVAL_REFERENCE, eval);
+    add_global_value(global_closure, "ArrayBuffer", array_buffer);
+    add_global_value(global_closure, "DataView", data_view);
+    add_global_value(global_closure, "Proxy", proxy);
+    add_global_value(global_closure, "Reflect", reflect);
+    add_global_value(global_closure, "TextEncoder", text_encoder);
+    add_global_value(global_closure, "TextDecoder", text_decoder);
+    add_global_value(global_closure, "Event", event);
+    add_global_value(global_closure, "Promise", promise);
+    add_global_value(global_closure, "Headers", headers);
+    add_global_value(global_closure, "Request", request);
+    add_global_value(global_closure, "Response", response);
+    add_global_value(global_closure, "XMLHttpRequest", xml_http_request);
+    add_global_value(global_closure, "URL", url);
+    add_global_value(global_closure, "URLSearchParams", url_search_params);
+    for (name, constructor) in collection_constructors
+        .into_iter()
+        .chain(typed_array_constructors)
+    {
+        add_global_value(global_closure, name, constructor);
+    }
 }
 
 fn add_global_function(
     global_closure: &mut JsClosure,
     name: &str,
-    func: impl Fn(JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
+    func: impl Fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
 ) {
-    let node_graph = global_closure.node_graph.clone();
-    let reference = global_closure.get_or_declare_reference_mut(name);
-    let value = JsValue::Function(JsFunction::Native(
-        name.to_string(),
-        JsNativeFunctionImplementation {
-            func: Rc::new(func),
-        },
-    ));
-    reference.value = GcNodeGraph::create_node(&node_graph, value);
+    let value = build_native_function(&global_closure.node_graph, &name.into(), func);
+    add_global_value(global_closure, name, value);
 }
 
-fn js_atob(this: JsValueNode, args: &[JsValueNode]) -> JsFunctionResult {
-    let node_graph = this.get_node_graph();
-    match args.get(0) {
-        Some(value) => {
-            if let Some(decoded) = value.map_value(|v| v.to_string().base64_decode()) {
-                if let Ok(decoded_string) = std::str::from_utf8(&decoded) {
-                    return Ok(this.create_new_node(JsValue::str(decoded_string)));
-                }
-            }
-            Err(JsValue::type_error_or_dom_exception_rc(&node_graph))
+fn add_global_value(global_closure: &mut JsClosure, name: &str, value: JsValueNode) {
+    global_closure.get_or_declare_reference_mut(name).value = value;
+}
+
+/// `Symbol` creates unique symbols and cannot be used with `new`. `Symbol.for` returns the symbol
+/// registered under a key, creating it on first use.
+fn build_symbol_constructor(node_graph: &JsValueGraph) -> JsValueNode {
+    let registry: Rc<RefCell<HashMap<String, JsSymbol>>> = Default::default();
+    let key_for_registry = registry.clone();
+
+    let mut symbol = build_native_function(node_graph, &"Symbol".into(), |ctx, this, args| {
+        if !matches!(this.get_ref(), JsValue::Undefined) {
+            return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+        }
+        let description = match args.first().map(|arg| arg.get_ref()) {
+            None | Some(JsValue::Undefined) => None,
+            Some(description) => Some(description.to_string()),
+        };
+        Ok(JsValue::symbol_rc(
+            &ctx.nodes_graph,
+            JsSymbol::new(description),
+        ))
+    });
+
+    let for_entry = build_function_entry(node_graph, "for".into(), move |ctx, _this, args| {
+        let key = get_argument(ctx, args, 0).get_ref().to_string();
+        let symbol = registry
+            .borrow_mut()
+            .entry(key.clone())
+            .or_insert_with(|| JsSymbol::new(Some(key)))
+            .clone();
+        Ok(JsValue::symbol_rc(&ctx.nodes_graph, symbol))
+    });
+    let key_for_entry =
+        build_function_entry(node_graph, "keyFor".into(), move |ctx, _this, args| {
+            let symbol = get_argument(ctx, args, 0);
+            let JsValue::Symbol(symbol) = symbol.get_ref() else {
+                return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+            };
+            let registered = key_for_registry
+                .borrow()
+                .iter()
+                .find(|(_key, registered)| **registered == *symbol)
+                .map(|(key, _registered)| key.clone());
+            Ok(match registered {
+                Some(key) => JsValue::string_rc(&ctx.nodes_graph, key),
+                None => JsValue::undefined_rc(&ctx.nodes_graph),
+            })
+        });
+
+    if let JsValue::Function(_, properties) = symbol.get_mut() {
+        for (key, value) in [for_entry, key_for_entry] {
+            properties.insert(key, value);
+        }
+        for well_known in JsWellKnownSymbol::ALL {
+            properties.insert(
+                well_known.name().into(),
+                JsValue::symbol_rc(node_graph, JsSymbol::well_known(well_known)),
+            );
         }
-        _ => Err(JsValue::type_error_rc(&node_graph)),
     }
+    symbol
 }
 
-fn js_btoa(this: JsValueNode, args: &[JsValueNode]) -> JsFunctionResult {
-    let node_graph = this.get_node_graph();
-    match args.get(0) {
-        Some(value) => Ok(JsValue::string_rc(
-            &node_graph,
-            value.map_value(|v| v.to_string().base64_encode()),
-        )),
-        _ => Err(JsValue::type_error_rc(&node_graph)),
+/// Collections must be constructed with `new`, optionally from an iterable of initial entries.
+/// Entries of maps are themselves `[key, value]` objects.
+fn build_collection_constructor(kind: JsCollectionKind, prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &kind.name().into(), move |ctx, this, args| {
+            if matches!(this.get_ref(), JsValue::Undefined) {
+                return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+            }
+
+            let mut collection = JsKeyedCollection::new(kind);
+            let iterable = get_argument(ctx, args, 0);
+            if !matches!(iterable.get_ref(), JsValue::Undefined | JsValue::Null) {
+                for entry in collect_iterable(&iterable, ctx)? {
+                    let (key, value) = match kind {
+                        JsCollectionKind::Map | JsCollectionKind::WeakMap => {
+                            if !entry.get_ref().is_object() {
+                                return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+                            }
+                            let key = get_member_from_prototype_chain(&entry, &"0".into(), ctx)?;
+                            let value = get_member_from_prototype_chain(&entry, &"1".into(), ctx)?;
+                            (key, value)
+                        }
+                        JsCollectionKind::Set | JsCollectionKind::WeakSet => (entry.clone(), entry),
+                    };
+                    if kind.is_weak() && !key.get_ref().is_object() {
+                        return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+                    }
+                    collection.insert(key, value);
+                }
+            }
+            Ok(JsValue::collection_rc(&ctx.nodes_graph, collection))
+        });
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            JsPropertyKey::from(CONSTRUCTOR_PROTOTYPE_MEMBER),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
     }
+    constructor
+}
+
+#[js_method]
+fn atob(closure_context: &mut JsClosureContext, data: String) -> Result<String, JsValueNode> {
+    data.base64_decode()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or_else(|| JsValue::type_error_or_dom_exception_rc(&closure_context.nodes_graph))
+}
+
+#[js_method]
+fn btoa(data: String) -> String {
+    data.base64_encode()
 }
 
 #[derive(Debug)]
@@ -58,8 +234,30 @@ pub struct GlobalPrototypes {
     pub object: JsValueNode,
     pub boolean: JsValueNode,
     pub number: JsValueNode,
+    pub bigint: JsValueNode,
     pub string: JsValueNode,
     pub function: JsValueNode,
+    pub array: JsValueNode,
+    pub symbol: JsValueNode,
+    pub iterator: JsValueNode,
+    pub regexp: JsValueNode,
+    pub date: JsValueNode,
+    pub map: JsValueNode,
+    pub set: JsValueNode,
+    pub weak_map: JsValueNode,
+    pub weak_set: JsValueNode,
+    pub array_buffer: JsValueNode,
+    /// The prototype shared by the prototypes of each kind of typed array
+    pub typed_array: JsValueNode,
+    pub typed_arrays: HashMap<JsTypedArrayKind, JsValueNode>,
+    pub data_view: JsValueNode,
+    /// The intrinsic `eval`, which runs in the caller's scope when called directly by its name
+    pub eval: JsValueNode,
+    /// The prototype shared by the prototypes of the window, the document, and nodes
+    pub event_target: JsValueNode,
+    /// The prototypes of host classes by their name, registered when their first value is
+    /// created
+    pub host_classes: HashMap<&'static str, JsValueNode>,
 }
 
 impl GlobalPrototypes {
@@ -67,15 +265,98 @@ impl GlobalPrototypes {
         let object = build_object_prototype(node_graph);
         let boolean = build_prototype(object.clone(), []);
         let number = build_prototype(object.clone(), []);
-        let string = build_prototype(object.clone(), []);
+        let bigint = build_bigint_prototype(object.clone());
+        let string = build_string_prototype(object.clone());
         let function = build_prototype(object.clone(), []);
+        let array = build_array_prototype(object.clone());
+        let symbol = build_symbol_prototype(object.clone());
+        let iterator = build_iterator_prototype(object.clone());
+        let regexp = build_regexp_prototype(object.clone());
+        let date = build_date_prototype(object.clone());
+        let map = build_map_prototype(object.clone());
+        let set = build_set_prototype(object.clone());
+        let weak_map = build_weak_map_prototype(object.clone());
+        let weak_set = build_weak_set_prototype(object.clone());
+        let array_buffer = build_array_buffer_prototype(object.clone());
+        let typed_array = build_typed_array_prototype(object.clone());
+        let typed_arrays = JsTypedArrayKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let prototype = build_typed_array_kind_prototype(kind, typed_array.clone());
+                (kind, prototype)
+            })
+            .collect();
+        let data_view = build_data_view_prototype(object.clone());
+        let eval = build_eval_function(node_graph);
+        let event_target = build_event_target_prototype(object.clone());
 
         Self {
             object,
             boolean,
             number,
+            bigint,
             string,
             function,
+            array,
+            symbol,
+            iterator,
+            regexp,
+            date,
+            map,
+            set,
+            weak_map,
+            weak_set,
+            array_buffer,
+            typed_array,
+            typed_arrays,
+            data_view,
+            eval,
+            event_target,
+            host_classes: HashMap::new(),
         }
     }
+
+    /// Builds the prototype of a host class unless it is already registered
+    pub fn register_host_class<T: JsHostClass>(&mut self) {
+        self.register_host_class_extending::<T>(self.object.clone());
+    }
+
+    /// Builds the prototype of a host class inheriting from the parent prototype unless it is
+    /// already registered, such as the `EventTarget` prototype for classes events are dispatched
+    /// to
+    pub fn register_host_class_extending<T: JsHostClass>(&mut self, parent: JsValueNode) {
+        if !self.host_classes.contains_key(T::NAME) {
+            let prototype = T::build_prototype(parent);
+            self.host_classes.insert(T::NAME, prototype);
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![
+            self.object.clone(),
+            self.boolean.clone(),
+            self.number.clone(),
+            self.bigint.clone(),
+            self.string.clone(),
+            self.function.clone(),
+            self.array.clone(),
+            self.symbol.clone(),
+            self.iterator.clone(),
+            self.regexp.clone(),
+            self.date.clone(),
+            self.map.clone(),
+            self.set.clone(),
+            self.weak_map.clone(),
+            self.weak_set.clone(),
+            self.array_buffer.clone(),
+            self.typed_array.clone(),
+            self.data_view.clone(),
+            self.eval.clone(),
+            self.event_target.clone(),
+        ]
+        .into_iter()
+        .chain(self.typed_arrays.values().cloned())
+        .chain(self.host_classes.values().cloned())
+        .collect()
+    }
 }
@@ -0,0 +1,131 @@
+use crate::js::api::date::Date;
+
+use super::{
+    build_function_entry, invoke_function, FromJsValue, JsClosureContext, JsFunctionResult,
+    JsHostClass, JsProperty, JsValue, JsValueNode, JsWindow,
+};
+
+/// The callbacks scripts asked to run before the next frame with `requestAnimationFrame`
+/// <https://html.spec.whatwg.org/multipage/imagebitmap-and-animations.html#animation-frames>
+#[derive(Debug, Default)]
+pub struct JsAnimationFrames {
+    /// The callbacks by their handle, in the order they were requested
+    callbacks: Vec<(u32, JsValueNode)>,
+    /// The handle of the last callback requested, which are never reused by the document
+    last_handle: u32,
+}
+
+impl JsAnimationFrames {
+    /// Whether there are callbacks to run, which the next frame has to be drawn for
+    pub fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.callbacks
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect()
+    }
+}
+
+/// Declares `requestAnimationFrame` and `cancelAnimationFrame` as globals, which are also methods
+/// of the window. They are native functions rather than methods of [JsWindow] as the globals are
+/// called without it.
+pub fn add_animation_frame_globals(closure_context: &mut JsClosureContext) {
+    let node_graph = closure_context.nodes_graph.clone();
+    let methods = [
+        build_function_entry(
+            &node_graph,
+            "requestAnimationFrame".into(),
+            request_animation_frame,
+        ),
+        build_function_entry(
+            &node_graph,
+            "cancelAnimationFrame".into(),
+            cancel_animation_frame,
+        ),
+    ];
+    let mut prototype = closure_context.global_prototypes.host_classes[JsWindow::NAME].clone();
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, method) in &methods {
+            let property = JsProperty::with_attributes(method.clone(), true, false, true);
+            properties.insert_property(key.clone(), property);
+        }
+    }
+    let global_closure = closure_context.get_lastest_closure();
+    for (key, method) in methods {
+        global_closure
+            .get_or_declare_reference_mut(&key.to_string())
+            .value = method;
+    }
+}
+
+/// `requestAnimationFrame(callback)` runs the callback before the next frame is drawn with the
+/// time of the frame, resulting in a handle to cancel it with. Throws a `TypeError` when the
+/// callback cannot be called.
+fn request_animation_frame(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let Some(callback) = args.first().filter(|arg| arg.get_ref().is_callable()) else {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    };
+    let animation_frames = &mut closure_context.animation_frames;
+    animation_frames.last_handle += 1;
+    let handle = animation_frames.last_handle;
+    animation_frames.callbacks.push((handle, callback.clone()));
+    Ok(JsValue::number_rc(
+        &closure_context.nodes_graph,
+        f64::from(handle),
+    ))
+}
+
+/// `cancelAnimationFrame(handle)` keeps the callback from running, if it has not yet
+fn cancel_animation_frame(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let handle = u32::from_js_argument(args, 0, closure_context)?;
+    closure_context
+        .animation_frames
+        .callbacks
+        .retain(|(callback_handle, _)| *callback_handle != handle);
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// Runs the callbacks requested before the frame with the time it started at, relative to the
+/// time origin. Callbacks requested by these run before the next frame, and the ones canceled by
+/// these do not run. Exceptions are reported without stopping the other callbacks, and only
+/// termination is thrown. Results in whether any ran.
+/// <https://html.spec.whatwg.org/multipage/imagebitmap-and-animations.html#run-the-animation-frame-callbacks>
+pub fn run_animation_frame_callbacks(
+    closure_context: &mut JsClosureContext,
+    frame_time: Date,
+) -> Result<bool, JsValueNode> {
+    let last_handle = closure_context.animation_frames.last_handle;
+    let timestamp = closure_context.performance.to_relative(frame_time);
+    let mut ran = false;
+    loop {
+        let callbacks = &mut closure_context.animation_frames.callbacks;
+        let Some(index) = callbacks
+            .iter()
+            .position(|(handle, _)| *handle <= last_handle)
+        else {
+            return Ok(ran);
+        };
+        let (_, callback) = callbacks.remove(index);
+        ran = true;
+        let args = [JsValue::number_rc(&closure_context.nodes_graph, timestamp)];
+        let this = JsValue::undefined_rc(&closure_context.nodes_graph);
+        if let Err(value) = invoke_function(closure_context, &callback, this, &args) {
+            if closure_context.is_terminating() {
+                return Err(value);
+            }
+            let message = value.get_ref().to_string();
+            log!(ERROR["JS"]: "Uncaught exception in animation frame callback", message);
+        }
+    }
+}
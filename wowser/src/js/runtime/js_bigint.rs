@@ -0,0 +1,935 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::util::NumberUtils;
+
+use super::{
+    build_function_entry, build_native_function, build_prototype, get_argument, to_number,
+    JsClosureContext, JsFunctionResult, JsProperty, JsValue, JsValueGraph, JsValueNode,
+    CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+/// The most bits a BigInt may have. Arithmetic here is quadratic, so this keeps a single
+/// operation from hanging the page, and operations that would exceed it throw a `RangeError`.
+pub const BIGINT_MAX_BITS: u64 = 1 << 18;
+
+/// An arbitrary precision integer, stored as a sign and a magnitude of 32 bit limbs from least to
+/// most significant. The magnitude never has leading zero limbs, and zero is never negative.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JsBigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl JsBigInt {
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        trim(&mut limbs);
+        Self {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self::from_parts(false, vec![value as u32, (value >> 32) as u32])
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        let magnitude = Self::from_u64(value.unsigned_abs());
+        if value < 0 {
+            magnitude.neg()
+        } else {
+            magnitude
+        }
+    }
+
+    /// Converts an integral number, or `None` for fractions, `NaN`, and infinities
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        if value == 0.0 {
+            return Some(Self::default());
+        }
+
+        // Integral numbers are normal, so they are their mantissa with the implicit leading bit
+        // scaled by their exponent
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let limbs = [mantissa as u32, (mantissa >> 32) as u32];
+        let magnitude = if exponent >= 0 {
+            shift_left(&limbs, exponent as u64)
+        } else {
+            shift_right(&limbs, exponent.unsigned_abs())
+        };
+        Some(Self::from_parts(value < 0.0, magnitude))
+    }
+
+    /// Parses a string as `BigInt()` does, which allows surrounding whitespace, a sign on decimal
+    /// integers, and `0x`, `0o`, and `0b` prefixes. Strings of only whitespace are zero.
+    pub fn parse(string: &str) -> Option<Self> {
+        let string = string.trim();
+        if string.is_empty() {
+            return Some(Self::default());
+        }
+
+        let prefixed = |prefix: &str| {
+            string
+                .get(..2)
+                .filter(|start| start.eq_ignore_ascii_case(prefix))
+                .map(|_| &string[2..])
+        };
+        let (negative, radix, digits) = if let Some(digits) = prefixed("0x") {
+            (false, 16, digits)
+        } else if let Some(digits) = prefixed("0o") {
+            (false, 8, digits)
+        } else if let Some(digits) = prefixed("0b") {
+            (false, 2, digits)
+        } else if let Some(digits) = string.strip_prefix('-') {
+            (true, 10, digits)
+        } else {
+            (false, 10, string.strip_prefix('+').unwrap_or(string))
+        };
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut limbs = vec![];
+        for digit in digits.chars() {
+            multiply_add_small(&mut limbs, radix, digit.to_digit(radix)?);
+        }
+        Some(Self::from_parts(negative, limbs))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The number of bits in the magnitude
+    fn bit_length(&self) -> u64 {
+        match self.limbs.last() {
+            Some(last) => self.limbs.len() as u64 * 32 - u64::from(last.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    /// The nearest number, or an infinity when the value is too large to be represented
+    pub fn to_f64(&self) -> f64 {
+        let bit_length = self.bit_length();
+        let magnitude = if bit_length <= 64 {
+            to_u64(&self.limbs) as f64
+        } else {
+            // Only the top 64 bits fit, and the bits below them are folded into the lowest so
+            // that rounding to the 53 bits of the mantissa still rounds the right way
+            let shift = bit_length - 64;
+            let top = to_u64(&shift_right(&self.limbs, shift));
+            let truncated = shift_left(&shift_right(&self.limbs, shift), shift) != self.limbs;
+            (top | u64::from(truncated)) as f64 * 2f64.powi(shift as i32)
+        };
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Wraps the value to 64 bits, as storing it in a `BigUint64Array` does
+    pub fn to_u64_wrapping(&self) -> u64 {
+        let low = to_u64(&self.limbs);
+        if self.negative {
+            low.wrapping_neg()
+        } else {
+            low
+        }
+    }
+
+    /// Wraps the value to 64 bits, as storing it in a `BigInt64Array` does
+    pub fn to_i64_wrapping(&self) -> i64 {
+        self.to_u64_wrapping() as i64
+    }
+
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+
+        // Digits are split off in chunks of the most that fit in a limb
+        let mut chunk = radix;
+        let mut digits_per_chunk = 1;
+        while let Some(next) = chunk.checked_mul(radix) {
+            chunk = next;
+            digits_per_chunk += 1;
+        }
+
+        let mut chunks = vec![];
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            chunks.push(divide_small(&mut limbs, chunk));
+        }
+
+        let mut result = if self.negative {
+            "-".to_string()
+        } else {
+            String::new()
+        };
+        for (index, chunk) in chunks.iter().rev().enumerate() {
+            let digits = format_small(*chunk, radix);
+            if index > 0 {
+                result.push_str(&"0".repeat(digits_per_chunk - digits.len()));
+            }
+            result.push_str(&digits);
+        }
+        result
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::from_parts(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return Self::from_parts(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => Self::from_parts(
+                other.negative,
+                subtract_magnitudes(&other.limbs, &self.limbs),
+            ),
+            _ => Self::from_parts(
+                self.negative,
+                subtract_magnitudes(&self.limbs, &other.limbs),
+            ),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    /// The product, or `None` when it would have more than [BIGINT_MAX_BITS] bits
+    pub fn mul(&self, other: &Self) -> Option<Self> {
+        if self.bit_length() + other.bit_length() > BIGINT_MAX_BITS + 1 {
+            return None;
+        }
+        Some(Self::from_parts(
+            self.negative != other.negative,
+            multiply_magnitudes(&self.limbs, &other.limbs),
+        ))
+    }
+
+    /// The quotient rounded towards zero, or `None` when dividing by zero
+    pub fn div(&self, other: &Self) -> Option<Self> {
+        let (quotient, _remainder) = self.div_rem(other)?;
+        Some(quotient)
+    }
+
+    /// The remainder, which has the sign of the dividend, or `None` when dividing by zero
+    pub fn rem(&self, other: &Self) -> Option<Self> {
+        let (_quotient, remainder) = self.div_rem(other)?;
+        Some(remainder)
+    }
+
+    fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divide_magnitudes(&self.limbs, &other.limbs);
+        Some((
+            Self::from_parts(self.negative != other.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// The value raised to the exponent, or `None` when the exponent is negative or the result
+    /// would have more than [BIGINT_MAX_BITS] bits
+    pub fn pow(&self, exponent: &Self) -> Option<Self> {
+        if exponent.negative {
+            return None;
+        }
+        if exponent.is_zero() {
+            return Some(Self::from_u64(1));
+        }
+        // Zero and one keep their magnitude however large the exponent
+        if self.bit_length() <= 1 {
+            let odd = exponent.limbs[0] & 1 == 1;
+            return Some(Self::from_parts(self.negative && odd, self.limbs.clone()));
+        }
+        if exponent.bit_length() > 32
+            || (self.bit_length() - 1) * to_u64(&exponent.limbs) > BIGINT_MAX_BITS
+        {
+            return None;
+        }
+
+        let mut exponent = to_u64(&exponent.limbs);
+        let mut base = self.clone();
+        let mut result = Self::from_u64(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Shifts left by the amount, or right when it is negative. `None` when the result would have
+    /// more than [BIGINT_MAX_BITS] bits.
+    pub fn shl(&self, amount: &Self) -> Option<Self> {
+        if amount.negative {
+            return Some(self.shift_right_floor(&amount.limbs));
+        }
+        if self.is_zero() {
+            return Some(Self::default());
+        }
+        if amount.bit_length() > 32 || self.bit_length() + to_u64(&amount.limbs) > BIGINT_MAX_BITS {
+            return None;
+        }
+        Some(Self::from_parts(
+            self.negative,
+            shift_left(&self.limbs, to_u64(&amount.limbs)),
+        ))
+    }
+
+    /// Shifts right by the amount rounding towards negative infinity, or left when it is negative
+    pub fn shr(&self, amount: &Self) -> Option<Self> {
+        self.shl(&amount.neg())
+    }
+
+    fn shift_right_floor(&self, amount: &[u32]) -> Self {
+        let amount = if amount.len() > 2 {
+            u64::MAX
+        } else {
+            to_u64(amount)
+        };
+        if !self.negative {
+            return Self::from_parts(false, shift_right(&self.limbs, amount));
+        }
+        // Rounding the magnitude down would round the value up, so -a >> n is -((a - 1) >> n) - 1
+        let one = [1];
+        let shifted = shift_right(&subtract_magnitudes(&self.limbs, &one), amount);
+        Self::from_parts(true, add_magnitudes(&shifted, &one))
+    }
+
+    pub fn not(&self) -> Self {
+        self.neg().sub(&Self::from_u64(1))
+    }
+
+    pub fn bitand(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    /// Applies the operation to the limbs of the values in two's complement, which are long enough
+    /// to hold the sign bit of each
+    fn bitwise(&self, other: &Self, operation: impl Fn(u32, u32) -> u32) -> Self {
+        let length = self.limbs.len().max(other.limbs.len()) + 1;
+        let a = self.to_twos_complement(length);
+        let b = other.to_twos_complement(length);
+        let limbs = a.iter().zip(b).map(|(a, b)| operation(*a, b)).collect();
+        Self::from_twos_complement(limbs)
+    }
+
+    fn to_twos_complement(&self, length: usize) -> Vec<u32> {
+        if !self.negative {
+            let mut limbs = self.limbs.clone();
+            limbs.resize(length, 0);
+            return limbs;
+        }
+        // -a is !(a - 1)
+        let mut limbs = subtract_magnitudes(&self.limbs, &[1]);
+        limbs.resize(length, 0);
+        limbs.iter().map(|limb| !limb).collect()
+    }
+
+    fn from_twos_complement(limbs: Vec<u32>) -> Self {
+        if !limbs.last().is_some_and(|last| last >> 31 == 1) {
+            return Self::from_parts(false, limbs);
+        }
+        let inverted: Vec<u32> = limbs.iter().map(|limb| !limb).collect();
+        Self::from_parts(true, add_magnitudes(&inverted, &[1]))
+    }
+
+    /// The value wrapped to an unsigned integer of the bits, as `BigInt.asUintN` does. `None`
+    /// when the result would have more than [BIGINT_MAX_BITS] bits.
+    pub fn as_uint_n(&self, bits: u64) -> Option<Self> {
+        if !self.negative && self.bit_length() <= bits {
+            return Some(self.clone());
+        }
+        if bits > BIGINT_MAX_BITS {
+            return None;
+        }
+        let length = (bits as usize).div_ceiling(32);
+        let mut limbs = self.to_twos_complement(length.max(self.limbs.len() + 1));
+        limbs.truncate(length);
+        if bits % 32 != 0 {
+            if let Some(last) = limbs.last_mut() {
+                *last &= (1 << (bits % 32)) - 1;
+            }
+        }
+        Some(Self::from_parts(false, limbs))
+    }
+
+    /// The value wrapped to a signed integer of the bits, as `BigInt.asIntN` does
+    pub fn as_int_n(&self, bits: u64) -> Self {
+        if bits == 0 {
+            return Self::default();
+        }
+        if self.bit_length() < bits {
+            return self.clone();
+        }
+        // The value has more bits than the result, so the unsigned result is not too large
+        let unsigned = self.as_uint_n(bits).unwrap_or_default();
+        if unsigned.bit_length() == bits {
+            unsigned.sub(&Self::from_parts(false, shift_left(&[1], bits)))
+        } else {
+            unsigned
+        }
+    }
+
+    /// Compares the value with a number, or `None` when the number is `NaN`
+    pub fn compare_f64(&self, number: f64) -> Option<Ordering> {
+        if number.is_nan() {
+            return None;
+        }
+        if number.is_infinite() {
+            return Some(if number > 0.0 {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            });
+        }
+        let truncated = Self::from_f64(number.trunc())?;
+        Some(match self.cmp(&truncated) {
+            Ordering::Equal => 0f64.partial_cmp(&number.fract())?,
+            ordering => ordering,
+        })
+    }
+}
+
+impl Ord for JsBigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for JsBigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for JsBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_radix(10))
+    }
+}
+
+/// A number or a BigInt, which is what arithmetic operators and typed array elements work with
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsNumeric {
+    Number(f64),
+    BigInt(JsBigInt),
+}
+
+impl JsNumeric {
+    pub fn to_value_rc(&self, node_graph: &JsValueGraph) -> JsValueNode {
+        match self {
+            Self::Number(number) => JsValue::number_rc(node_graph, *number),
+            Self::BigInt(bigint) => JsValue::bigint_rc(node_graph, bigint.clone()),
+        }
+    }
+}
+
+impl fmt::Display for JsNumeric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::BigInt(bigint) => write!(f, "{bigint}"),
+        }
+    }
+}
+
+/// `BigInt` converts its argument to a BigInt and cannot be used with `new`. Numbers must be
+/// integral.
+pub fn build_bigint_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &"BigInt".into(), |ctx, this, args| {
+            if !matches!(this.get_ref(), JsValue::Undefined) {
+                return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+            }
+            let value = get_argument(ctx, args, 0);
+            let bigint = match value.get_ref() {
+                JsValue::Number(number) => JsBigInt::from_f64(*number)
+                    .ok_or_else(|| JsValue::range_error_rc(&ctx.nodes_graph))?,
+                _ => to_bigint(&value, ctx)?,
+            };
+            Ok(JsValue::bigint_rc(&ctx.nodes_graph, bigint))
+        });
+
+    let statics = [
+        build_function_entry(&node_graph, "asIntN".into(), |ctx, _this, args| {
+            let bits = to_bit_count(&get_argument(ctx, args, 0), ctx)?;
+            let bigint = to_bigint(&get_argument(ctx, args, 1), ctx)?;
+            Ok(JsValue::bigint_rc(&ctx.nodes_graph, bigint.as_int_n(bits)))
+        }),
+        build_function_entry(&node_graph, "asUintN".into(), |ctx, _this, args| {
+            let bits = to_bit_count(&get_argument(ctx, args, 0), ctx)?;
+            let bigint = to_bigint(&get_argument(ctx, args, 1), ctx)?
+                .as_uint_n(bits)
+                .ok_or_else(|| JsValue::range_error_rc(&ctx.nodes_graph))?;
+            Ok(JsValue::bigint_rc(&ctx.nodes_graph, bigint))
+        }),
+    ];
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        for (key, value) in statics {
+            properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+        }
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
+
+pub fn build_bigint_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "toString".into(), bigint_to_string),
+            build_function_entry(&node_graph, "toLocaleString".into(), bigint_to_string),
+            build_function_entry(&node_graph, "valueOf".into(), |ctx, this, _args| {
+                let bigint = this_bigint_value(&this, ctx)?;
+                Ok(JsValue::bigint_rc(&ctx.nodes_graph, bigint))
+            }),
+        ],
+    )
+}
+
+/// Formats the BigInt in a radix from 2 to 36, which defaults to 10
+fn bigint_to_string(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let bigint = this_bigint_value(&this, closure_context)?;
+    let radix = get_argument(closure_context, args, 0);
+    let radix = match radix.get_ref() {
+        JsValue::Undefined => 10.0,
+        _ => to_number(&radix, closure_context)?.trunc(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+    }
+    let string = bigint.to_string_radix(radix as u32);
+    Ok(JsValue::string_rc(&closure_context.nodes_graph, string))
+}
+
+fn this_bigint_value(
+    this: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<JsBigInt, JsValueNode> {
+    match this.get_ref() {
+        JsValue::BigInt(bigint) => Ok(bigint.clone()),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// The number of bits given to `BigInt.asIntN` and `BigInt.asUintN`, which must be a safe integer
+/// that is not negative
+fn to_bit_count(
+    value: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<u64, JsValueNode> {
+    let bits = match value.get_ref() {
+        JsValue::Undefined => 0.0,
+        _ => to_number(value, closure_context)?.trunc(),
+    };
+    let bits = if bits.is_nan() { 0.0 } else { bits };
+    if !(0.0..=9007199254740991.0).contains(&bits) {
+        return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(bits as u64)
+}
+
+/// Converts a value to a BigInt. Booleans are 0 or 1 and strings are parsed, throwing a
+/// `SyntaxError` when they are not integers. Numbers, `null`, `undefined`, and symbols throw a
+/// `TypeError`, as they are never implicitly converted.
+pub fn to_bigint(
+    value: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<JsBigInt, JsValueNode> {
+    let nodes_graph = &closure_context.nodes_graph;
+    match value.get_ref() {
+        JsValue::BigInt(bigint) => Ok(bigint.clone()),
+        JsValue::Boolean(b) => Ok(JsBigInt::from_u64(u64::from(*b))),
+        JsValue::Number(_) | JsValue::Undefined | JsValue::Null | JsValue::Symbol(_) => {
+            Err(JsValue::type_error_rc(nodes_graph))
+        }
+        value => {
+            JsBigInt::parse(&value.to_string()).ok_or_else(|| JsValue::syntax_error_rc(nodes_graph))
+        }
+    }
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+/// The lowest 64 bits of the magnitude
+fn to_u64(limbs: &[u32]) -> u64 {
+    let low = u64::from(limbs.first().copied().unwrap_or(0));
+    let high = u64::from(limbs.get(1).copied().unwrap_or(0));
+    low | (high << 32)
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (index, limb) in long.iter().enumerate() {
+        let sum = u64::from(*limb) + u64::from(short.get(index).copied().unwrap_or(0)) + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    result.push(carry as u32);
+    trim(&mut result);
+    result
+}
+
+/// Subtracts the smaller magnitude `b` from `a`
+fn subtract_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (index, limb) in a.iter().enumerate() {
+        let difference = i64::from(*limb) - i64::from(b.get(index).copied().unwrap_or(0)) - borrow;
+        result.push(difference as u32);
+        borrow = i64::from(difference < 0);
+    }
+    trim(&mut result);
+    result
+}
+
+fn multiply_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0; a.len() + b.len()];
+    for (i, a_limb) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, b_limb) in b.iter().enumerate() {
+            let product =
+                u64::from(*a_limb) * u64::from(*b_limb) + u64::from(result[i + j]) + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    result
+}
+
+fn multiply_add_small(limbs: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = u64::from(addend);
+    for limb in limbs.iter_mut() {
+        let product = u64::from(*limb) * u64::from(multiplier) + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+/// Divides the magnitude in place, returning the remainder
+fn divide_small(limbs: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0;
+    for limb in limbs.iter_mut().rev() {
+        let dividend = (remainder << 32) | u64::from(*limb);
+        *limb = (dividend / u64::from(divisor)) as u32;
+        remainder = dividend % u64::from(divisor);
+    }
+    trim(limbs);
+    remainder as u32
+}
+
+/// Long division of magnitudes, returning the quotient and remainder. This is Knuth's Algorithm D,
+/// which estimates each limb of the quotient from the top limbs of the remainder and divisor.
+fn divide_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitudes(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let mut quotient = a.to_vec();
+        let remainder = divide_small(&mut quotient, b[0]);
+        return (quotient, vec![remainder]);
+    }
+
+    // Shifting both so the top bit of the divisor is set keeps the estimates within 2 of the limb
+    let shift = u64::from(b[b.len() - 1].leading_zeros());
+    let divisor = shift_left(b, shift);
+    let mut remainder = shift_left(a, shift);
+    remainder.resize(a.len() + 1, 0);
+
+    let n = divisor.len();
+    let m = a.len() - n;
+    let base = 1u64 << 32;
+    let mut quotient = vec![0; m + 1];
+    for j in (0..=m).rev() {
+        let numerator = (u64::from(remainder[j + n]) << 32) | u64::from(remainder[j + n - 1]);
+        let mut estimate = numerator / u64::from(divisor[n - 1]);
+        let mut estimate_remainder = numerator % u64::from(divisor[n - 1]);
+        while estimate >= base
+            || estimate * u64::from(divisor[n - 2])
+                > (estimate_remainder << 32) | u64::from(remainder[j + n - 2])
+        {
+            estimate -= 1;
+            estimate_remainder += u64::from(divisor[n - 1]);
+            if estimate_remainder >= base {
+                break;
+            }
+        }
+
+        let mut borrow = 0;
+        let mut carry = 0;
+        for (i, limb) in divisor.iter().enumerate() {
+            let product = estimate * u64::from(*limb) + carry;
+            carry = product >> 32;
+            let difference = i64::from(remainder[i + j]) - borrow - (product & 0xffff_ffff) as i64;
+            remainder[i + j] = difference as u32;
+            borrow = i64::from(difference < 0);
+        }
+        let difference = i64::from(remainder[j + n]) - borrow - carry as i64;
+        remainder[j + n] = difference as u32;
+
+        // The estimate was one too large, so the divisor is added back
+        if difference < 0 {
+            estimate -= 1;
+            let mut carry = 0;
+            for (i, limb) in divisor.iter().enumerate() {
+                let sum = u64::from(remainder[i + j]) + u64::from(*limb) + carry;
+                remainder[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            remainder[j + n] = remainder[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    let remainder = shift_right(&remainder[..n], shift);
+    (quotient, remainder)
+}
+
+fn shift_left(limbs: &[u32], amount: u64) -> Vec<u32> {
+    let limb_shift = (amount / 32) as usize;
+    let bit_shift = amount % 32;
+    let mut result = vec![0; limb_shift];
+    let mut carry = 0;
+    for limb in limbs {
+        let shifted = u64::from(*limb) << bit_shift;
+        result.push(shifted as u32 | carry);
+        carry = (shifted >> 32) as u32;
+    }
+    result.push(carry);
+    trim(&mut result);
+    result
+}
+
+fn shift_right(limbs: &[u32], amount: u64) -> Vec<u32> {
+    let limb_shift = usize::try_from(amount / 32).unwrap_or(usize::MAX);
+    if limb_shift >= limbs.len() {
+        return vec![];
+    }
+    let bit_shift = amount % 32;
+    let mut result: Vec<u32> = limbs[limb_shift..]
+        .iter()
+        .enumerate()
+        .map(|(index, limb)| {
+            let high = limbs.get(limb_shift + index + 1).copied().unwrap_or(0);
+            (((u64::from(high) << 32) | u64::from(*limb)) >> bit_shift) as u32
+        })
+        .collect();
+    trim(&mut result);
+    result
+}
+
+fn format_small(mut value: u32, radix: u32) -> String {
+    let mut digits = vec![];
+    loop {
+        digits.push(std::char::from_digit(value % radix, radix).unwrap());
+        value /= radix;
+        if value == 0 {
+            return digits.iter().rev().collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bigint(string: &str) -> JsBigInt {
+        JsBigInt::parse(string).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(bigint("0").to_string(), "0");
+        assert_eq!(bigint("  -0 ").to_string(), "0");
+        assert_eq!(bigint("").to_string(), "0");
+        assert_eq!(bigint("+42").to_string(), "42");
+        assert_eq!(
+            bigint("-123456789012345678901234567890").to_string(),
+            "-123456789012345678901234567890"
+        );
+        assert_eq!(bigint("0xff").to_string(), "255");
+        assert_eq!(bigint("0o17").to_string(), "15");
+        assert_eq!(bigint("0B101").to_string(), "5");
+        assert_eq!(JsBigInt::parse("1.5"), None);
+        assert_eq!(JsBigInt::parse("-0x1"), None);
+        assert_eq!(JsBigInt::parse("0x"), None);
+        assert_eq!(JsBigInt::parse("12a"), None);
+
+        assert_eq!(bigint("255").to_string_radix(16), "ff");
+        assert_eq!(bigint("-255").to_string_radix(2), "-11111111");
+        assert_eq!(
+            bigint("18446744073709551616").to_string_radix(36),
+            "3w5e11264sgsg"
+        );
+    }
+
+    #[test]
+    fn test_f64_conversions() {
+        assert_eq!(JsBigInt::from_f64(1.5), None);
+        assert_eq!(JsBigInt::from_f64(f64::NAN), None);
+        assert_eq!(JsBigInt::from_f64(f64::INFINITY), None);
+        assert_eq!(JsBigInt::from_f64(-0.0), Some(JsBigInt::default()));
+        assert_eq!(JsBigInt::from_f64(-42.0), Some(JsBigInt::from_i64(-42)));
+        assert_eq!(
+            JsBigInt::from_f64(1e20).unwrap().to_string(),
+            "100000000000000000000"
+        );
+        assert_eq!(
+            JsBigInt::from_f64(2f64.powi(100)).unwrap().to_string(),
+            "1267650600228229401496703205376"
+        );
+
+        assert_eq!(bigint("-42").to_f64(), -42.0);
+        assert_eq!(bigint("100000000000000000000").to_f64(), 1e20);
+        // 2^53 + 1 is halfway between two numbers and rounds to even, while anything above it
+        // rounds up
+        assert_eq!(bigint("9007199254740993").to_f64(), 9007199254740992.0);
+        assert_eq!(
+            bigint("18446744073709553664").to_f64(),
+            18446744073709551616.0
+        );
+        assert_eq!(
+            bigint("18446744073709553665").to_f64(),
+            18446744073709555712.0
+        );
+        assert_eq!(
+            bigint("1").shl(&bigint("1024")).unwrap().to_f64(),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = bigint("123456789012345678901234567890");
+        let b = bigint("-987654321098765432109876543210");
+        assert_eq!(a.add(&b).to_string(), "-864197532086419753208641975320");
+        assert_eq!(a.sub(&b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            a.mul(&b).unwrap().to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(b.div(&a).unwrap().to_string(), "-8");
+        assert_eq!(b.rem(&a).unwrap().to_string(), "-9000000000900000000090");
+        assert_eq!(a.div(&JsBigInt::default()), None);
+        assert_eq!(
+            bigint("340282366920938463463374607431768211455")
+                .div(&bigint("18446744073709551617"))
+                .unwrap()
+                .to_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(
+            bigint("7").pow(&bigint("30")).unwrap().to_string(),
+            "22539340290692258087863249"
+        );
+        assert_eq!(
+            bigint("-1").pow(&bigint("99999999999")).unwrap(),
+            bigint("-1")
+        );
+        assert_eq!(bigint("2").pow(&bigint("-1")), None);
+        assert_eq!(bigint("2").pow(&bigint("1000000000")), None);
+    }
+
+    #[test]
+    fn test_bitwise() {
+        assert_eq!(bigint("-5").shr(&bigint("1")).unwrap(), bigint("-3"));
+        assert_eq!(bigint("5").shr(&bigint("1")).unwrap(), bigint("2"));
+        assert_eq!(bigint("-1").shr(&bigint("100")).unwrap(), bigint("-1"));
+        assert_eq!(
+            bigint("3").shl(&bigint("64")).unwrap().to_string(),
+            "55340232221128654848"
+        );
+        assert_eq!(bigint("12").bitand(&bigint("-4")), bigint("12"));
+        assert_eq!(bigint("-12").bitor(&bigint("3")), bigint("-9"));
+        assert_eq!(bigint("-12").bitxor(&bigint("-3")), bigint("9"));
+        assert_eq!(bigint("5").not(), bigint("-6"));
+
+        assert_eq!(
+            bigint("-1").as_uint_n(64).unwrap().to_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(bigint("255").as_int_n(8), bigint("-1"));
+        assert_eq!(bigint("127").as_int_n(8), bigint("127"));
+        assert_eq!(bigint("-129").as_int_n(8), bigint("127"));
+        assert_eq!(bigint("-1").as_uint_n(u64::MAX), None);
+        assert_eq!(bigint("-1").to_u64_wrapping(), u64::MAX);
+        assert_eq!(bigint("18446744073709551615").to_i64_wrapping(), -1);
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(bigint("-2") < bigint("-1"));
+        assert!(bigint("18446744073709551616") > bigint("18446744073709551615"));
+        assert_eq!(bigint("1").compare_f64(1.5), Some(Ordering::Less));
+        assert_eq!(bigint("-1").compare_f64(-1.5), Some(Ordering::Greater));
+        assert_eq!(bigint("2").compare_f64(2.0), Some(Ordering::Equal));
+        assert_eq!(bigint("2").compare_f64(f64::NAN), None);
+        assert_eq!(
+            bigint("2").compare_f64(f64::NEG_INFINITY),
+            Some(Ordering::Greater)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use super::{JsBigInt, JsBinaryOperator, JsDeclarationKind, JsMethodKind, JsValueNode};
+
+/// An instruction run by [super::JsVm]. Instructions operate on a stack of values, where the
+/// stack effect of each is noted as `[before] -> [after]` with the top of the stack last.
+/// Operands naming a `usize` index into the names or nodes of the [JsChunk] they belong to, unless
+/// they are a jump target.
+#[derive(Debug, PartialEq)]
+pub enum JsInstruction {
+    /// `[] -> [undefined]`
+    Undefined,
+    /// `[] -> [null]`
+    Null,
+    /// `[] -> [boolean]`
+    Boolean(bool),
+    /// `[] -> [number]`
+    Number(f64),
+    /// `[] -> [bigint]`
+    BigInt(Box<JsBigInt>),
+    /// `[] -> [string]`
+    String(usize),
+    /// (Source, Flags) `[] -> [regexp]`
+    RegExp(usize, usize),
+    /// `[] -> [node]`
+    Node(usize),
+
+    /// `[value] -> []`
+    Pop,
+    /// `[value] -> [value, value]`
+    Dup,
+    /// `[a, b] -> [a, b, a]`
+    Over,
+
+    /// Throws a `ReferenceError` when the name is not declared. `[] -> [value]`
+    GetReference(usize),
+    /// Declares the name if it is not visible. `[] -> [value]`
+    DeclareVar(usize),
+    /// Assigns the name, declaring it if it is not visible. `[value] -> [value]`
+    AssignVar(usize),
+    /// Binds the name, as a declaration of the kind when present. `[value] -> []`
+    Bind(usize, Option<JsDeclarationKind>),
+
+    /// `[a, b] -> [a + b]`
+    Add,
+    /// `[a, b] -> [a operator b]`
+    Binary(JsBinaryOperator),
+    /// `===` when `true`, or `!==`. `[a, b] -> [boolean]`
+    StrictEquals(bool),
+    /// `==` when `true`, or `!=`. `[a, b] -> [boolean]`
+    LooseEquals(bool),
+    /// `[value] -> [number]`
+    ToNumber,
+    /// `[value] -> [-value]`
+    Negate,
+    /// `[value] -> [~value]`
+    BitwiseNot,
+
+    /// `[] -> [object]`
+    NewObject,
+    /// `[object, key, value] -> [object]`
+    DefineProperty,
+    /// Copies the own enumerable properties of the source. `[object, source] -> [object]`
+    CopyProperties,
+    /// (Function, Kind, Is enumerable) `[target, key] -> []`
+    DefineMethod(usize, JsMethodKind, bool),
+    /// Links a class constructor with a new prototype. `[] -> [constructor, prototype]`
+    CreateClass(usize),
+    /// `[] -> [array]`
+    NewArray,
+    /// `[array, value] -> [array]`
+    ArrayPush,
+    /// Appends the values of an iterable. `[array, iterable] -> [array]`
+    ArraySpread,
+
+    /// Collects the values of an iterable into a new array. `[iterable] -> [array]`
+    CollectIterable,
+    /// Gets the element at the index, or `undefined`. `[array] -> [array, element]`
+    GetElement(usize),
+    /// Gets the elements from the index onwards. `[array] -> [array, array]`
+    GetRestElements(usize),
+    /// Throws a `TypeError` when the value is `null` or `undefined`. `[value] -> [value]`
+    RequireObjectCoercible,
+    /// Gets the own enumerable properties of the object other than those named. `[object] ->
+    /// [object, rest]`
+    GetRestProperties(Box<[usize]>),
+
+    /// `[base] -> [value]`
+    GetMember(usize),
+    /// `[base, key] -> [value]`
+    GetComputedMember,
+    /// `[value, base] -> []`
+    SetMember(usize),
+    /// `[value, base, key] -> []`
+    SetComputedMember,
+    /// Throws a `TypeError` when the member is not a function. `[base] -> [base, function]`
+    GetMethod(usize),
+    /// Throws a `TypeError` when the member is not a function. `[base, key] -> [base, function]`
+    GetComputedMethod,
+    /// Throws a `TypeError` when the value is not a function. `[value] -> [value]`
+    RequireFunction,
+
+    /// (Argument count) `[this, function, arguments..] -> [result]`
+    Call(usize),
+    /// `[this, function, arguments array] -> [result]`
+    CallSpread,
+    /// (Argument count) Like `Call`, but runs the intrinsic `eval` in the caller's scope. `[this,
+    /// function, arguments..] -> [result]`
+    CallEval(usize),
+    /// (Argument count) `[constructor, arguments..] -> [result]`
+    New(usize),
+    /// `[constructor, arguments array] -> [result]`
+    NewSpread,
+    /// Imports the module the specifier refers to. `[specifier] -> [namespace]`
+    Import,
+
+    Jump(usize),
+    /// `[condition] -> []`
+    JumpIfFalse(usize),
+    /// `[condition] -> []`
+    JumpIfTrue(usize),
+    /// `[value] -> [value]`
+    JumpIfNotUndefined(usize),
+    /// Returns from the function being run. `[value] -> []`
+    Return,
+    /// `[value] -> []`
+    Throw,
+    ThrowReferenceError,
+    ThrowSyntaxError,
+    /// Handles values thrown until the handler is removed by jumping to the target, with the
+    /// stack as it was when the handler was pushed and the thrown value on top
+    PushHandler(usize),
+    PopHandler,
+
+    /// Records the result of a statement. `[value] -> []`
+    RecordValue,
+    RecordVoid,
+    /// `[value] -> []`
+    RecordReturn,
+    /// `[value] -> []`
+    RecordThrow,
+    /// (Label)
+    RecordBreak(Option<usize>),
+}
+
+impl JsInstruction {
+    /// Replaces the target of a jump or handler
+    pub fn set_target(&mut self, target: usize) {
+        match self {
+            Self::Jump(jump_target)
+            | Self::JumpIfFalse(jump_target)
+            | Self::JumpIfTrue(jump_target)
+            | Self::JumpIfNotUndefined(jump_target)
+            | Self::PushHandler(jump_target) => *jump_target = target,
+            instruction => panic!("Instruction has no target: {instruction:?}"),
+        }
+    }
+}
+
+/// The compiled bytecode of a document or function
+#[derive(Debug, Default, PartialEq)]
+pub struct JsChunk {
+    pub instructions: Vec<JsInstruction>,
+    /// Names of variables and members, and string literals
+    pub names: Vec<String>,
+    /// Functions created when the source was interpreted
+    pub nodes: Vec<JsValueNode>,
+}
+
+impl JsChunk {
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.nodes.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_target() {
+        let mut instruction = JsInstruction::JumpIfFalse(0);
+        instruction.set_target(12);
+        assert_eq!(instruction, JsInstruction::JumpIfFalse(12));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_target_without_target() {
+        JsInstruction::Pop.set_target(12);
+    }
+
+    #[test]
+    fn test_instruction_size() {
+        // Instructions are kept small so chunks stay compact
+        assert!(std::mem::size_of::<JsInstruction>() <= 24);
+    }
+}
@@ -0,0 +1,139 @@
+use super::{
+    bind_identifier, JsClosureContext, JsDeclarationKind, JsFunctionResult, JsProperties,
+    JsProperty, JsPropertyDescriptor, JsPropertyKey, JsPropertyName, JsValue, JsValueNode,
+    CONSTRUCTOR_MEMBER, CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsMethodKind {
+    Method,
+    Getter,
+    Setter,
+}
+
+/// A function defined with method syntax in an object literal or class body
+#[derive(Debug, PartialEq)]
+pub struct JsMethod {
+    pub kind: JsMethodKind,
+    pub name: JsPropertyName,
+    pub function: JsValueNode,
+}
+
+impl JsMethod {
+    /// Evaluates the method's name and describes the property it defines
+    pub fn to_property(
+        &self,
+        enumerable: bool,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(JsPropertyKey, JsPropertyDescriptor), JsValueNode> {
+        let key = self.name.evaluate(closure_context)?;
+        Ok((
+            key,
+            method_descriptor(self.kind, self.function.clone(), enumerable),
+        ))
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        [
+            self.name.get_referenced_nodes(),
+            vec![self.function.clone()],
+        ]
+        .concat()
+    }
+}
+
+/// A class declaration. Classes are constructors whose methods are defined on their prototype, or
+/// on the constructor itself when static.
+#[derive(Debug, PartialEq)]
+pub struct JsClass {
+    pub name: String,
+    pub constructor: JsValueNode,
+    /// (Is static, Method)
+    pub methods: Vec<(bool, JsMethod)>,
+}
+
+impl JsClass {
+    /// Defines the class's members and binds it to its name in the current scope
+    pub fn run(&self, closure_context: &mut JsClosureContext) -> JsFunctionResult {
+        let nodes_graph = closure_context.nodes_graph.clone();
+        let constructor = self.constructor.clone();
+        let prototype = create_class_prototype(&constructor);
+
+        for (is_static, method) in &self.methods {
+            let (key, descriptor) = method.to_property(false, closure_context)?;
+            let mut target = if *is_static {
+                constructor.clone()
+            } else {
+                prototype.clone()
+            };
+            if let JsValue::Object(properties) | JsValue::Function(_, properties) = target.get_mut()
+            {
+                properties.define(&nodes_graph, key, descriptor);
+            }
+        }
+
+        bind_identifier(
+            closure_context,
+            &self.name,
+            constructor.clone(),
+            Some(JsDeclarationKind::Let),
+        )?;
+        Ok(constructor)
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.methods
+            .iter()
+            .flat_map(|(_is_static, method)| method.get_referenced_nodes())
+            .chain([self.constructor.clone()])
+            .collect()
+    }
+}
+
+/// Describes the property defined by a method, getter, or setter. A getter or setter only replaces
+/// its half of an existing accessor.
+pub fn method_descriptor(
+    kind: JsMethodKind,
+    function: JsValueNode,
+    enumerable: bool,
+) -> JsPropertyDescriptor {
+    let descriptor = match kind {
+        JsMethodKind::Method => JsPropertyDescriptor {
+            value: Some(function),
+            writable: Some(true),
+            ..Default::default()
+        },
+        JsMethodKind::Getter => JsPropertyDescriptor {
+            get: Some(Some(function)),
+            ..Default::default()
+        },
+        JsMethodKind::Setter => JsPropertyDescriptor {
+            set: Some(Some(function)),
+            ..Default::default()
+        },
+    };
+    JsPropertyDescriptor {
+        enumerable: Some(enumerable),
+        configurable: Some(true),
+        ..descriptor
+    }
+}
+
+/// Creates the prototype of a class and links it with the class's constructor
+pub fn create_class_prototype(constructor: &JsValueNode) -> JsValueNode {
+    let mut prototype_properties = JsProperties::new();
+    prototype_properties.insert_property(
+        CONSTRUCTOR_MEMBER.into(),
+        JsProperty::with_attributes(constructor.clone(), true, false, true),
+    );
+    let prototype = JsValue::object_rc(&constructor.get_node_graph(), prototype_properties);
+
+    let mut constructor = constructor.clone();
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype.clone(), false, false, false),
+        );
+    }
+    prototype
+}
@@ -1,11 +1,13 @@
 use crate::util::mut_vec_find_or_insert;
 
-use super::{JsReference, JsValue, JsValueGraph};
+use super::{JsReference, JsValue, JsValueGraph, JsValueNode};
 
 #[derive(Debug)]
 pub struct JsClosure {
     pub references: Vec<JsReference>,
     pub node_graph: JsValueGraph,
+    /// The URL of the module this is the top level scope of
+    pub module: Option<String>,
 }
 
 impl JsClosure {
@@ -13,6 +15,14 @@ impl JsClosure {
         Self {
             references: vec![],
             node_graph: node_graph.clone(),
+            module: None,
+        }
+    }
+
+    pub fn for_module(node_graph: &JsValueGraph, url: &str) -> Self {
+        Self {
+            module: Some(url.to_string()),
+            ..Self::new(node_graph)
         }
     }
 
@@ -41,8 +51,15 @@ impl JsClosure {
             || JsReference {
                 name: variable_name.into(),
                 value: JsValue::undefined_rc(&self.node_graph),
+                constant: false,
             },
-        );
-        self.references.last_mut().unwrap()
+        )
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.references
+            .iter()
+            .flat_map(JsReference::get_referenced_nodes)
+            .collect()
     }
 }
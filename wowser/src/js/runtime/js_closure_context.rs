@@ -1,4 +1,17 @@
-use super::{globals::GlobalPrototypes, JsClosure, JsReference, JsStatementResult, JsValueGraph};
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    garbage_collector::GcNodeGraph,
+    js::api::date::{Clock, LocalTimeZone, SystemClock, TimeZone},
+};
+
+use super::{
+    globals::GlobalPrototypes, JsAnimationFrames, JsChunk, JsClosure, JsCompiler, JsEngine,
+    JsEventTargets, JsFetches, JsFunction, JsHostClass, JsHostObject, JsHostReferences,
+    JsInterruptHandle, JsJobQueue, JsLimits, JsModuleMap, JsNamedProperties, JsNavigation,
+    JsPerformanceTimeline, JsReference, JsStatementResult, JsStorageAreas, JsValue, JsValueGraph,
+    JsValueNode,
+};
 
 #[derive(Debug)]
 pub struct JsClosureContext {
@@ -8,10 +21,47 @@ pub struct JsClosureContext {
     pub expression_results: Vec<JsStatementResult>,
     pub global_prototypes: GlobalPrototypes,
     pub nodes_graph: JsValueGraph,
+    pub engine: JsEngine,
+    /// The source of the current time for `Date`
+    pub clock: Rc<dyn Clock>,
+    /// The local time zone of new dates
+    pub time_zone: Rc<dyn TimeZone>,
+    /// The bytecode of user defined functions by the identifier of their node. The node is kept
+    /// so its identifier is not reused.
+    compiled_functions: HashMap<usize, (JsValueNode, Rc<JsChunk>)>,
+    /// The modules imported by the document, and how to load more
+    pub modules: JsModuleMap,
+    /// The event listeners of the document and its nodes
+    pub event_targets: JsEventTargets,
+    /// The local and session storage of the document
+    pub storage: JsStorageAreas,
+    /// The jobs to run once the script running finishes, such as the handlers of settled promises
+    pub jobs: JsJobQueue,
+    /// The requests made with `fetch` and `XMLHttpRequest` that are waiting on the network, and
+    /// the URL of the document they are made from
+    pub fetches: JsFetches,
+    /// The session history of the tab, which `location` and `history` navigate through
+    pub navigation: JsNavigation,
+    /// The time `performance.now()` and the timestamps of animation frames count from
+    pub performance: JsPerformanceTimeline,
+    /// The callbacks to run before the next frame is drawn
+    pub animation_frames: JsAnimationFrames,
+    pub limits: JsLimits,
+    /// Terminates the script being run when tripped
+    pub interrupt: JsInterruptHandle,
+    /// The steps taken since the run started
+    steps: u64,
+    /// Set once the run is terminated, so the termination is not handled like other thrown values
+    terminating: bool,
+    /// Set once growing past the maximum heap size threw a `RangeError`, until garbage is
+    /// collected
+    heap_exhausted: bool,
+    /// The root of the node graph, which only refers to other nodes while garbage is collected
+    root: JsValueNode,
 }
 
 impl JsClosureContext {
-    pub fn new(global: JsClosure) -> Self {
+    pub fn new(global: JsClosure, root: JsValueNode) -> Self {
         let global_prototypes = GlobalPrototypes::new(&global.node_graph);
         let nodes_graph = global.node_graph.clone();
         Self {
@@ -19,19 +69,169 @@ impl JsClosureContext {
             expression_results: vec![],
             global_prototypes,
             nodes_graph,
+            engine: JsEngine::default(),
+            clock: Rc::new(SystemClock),
+            time_zone: Rc::new(LocalTimeZone::load()),
+            compiled_functions: HashMap::new(),
+            modules: JsModuleMap::default(),
+            event_targets: JsEventTargets::default(),
+            storage: JsStorageAreas::default(),
+            jobs: JsJobQueue::default(),
+            fetches: JsFetches::default(),
+            navigation: JsNavigation::default(),
+            performance: JsPerformanceTimeline::default(),
+            animation_frames: JsAnimationFrames::default(),
+            limits: JsLimits::default(),
+            interrupt: JsInterruptHandle::new(),
+            steps: 0,
+            terminating: false,
+            heap_exhausted: false,
+            root,
+        }
+    }
+
+    /// Creates a value of a host class, registering its prototype on first use
+    pub fn create_host_object<T: JsHostClass>(&mut self, value: T) -> JsValueNode {
+        self.global_prototypes.register_host_class::<T>();
+        JsValue::host_object_rc(&self.nodes_graph, value)
+    }
+
+    /// Creates a value of a host class with named properties. See [JsNamedProperties].
+    pub fn create_host_object_with_named_properties<T: JsHostClass + JsNamedProperties>(
+        &mut self,
+        value: T,
+    ) -> JsValueNode {
+        self.global_prototypes.register_host_class::<T>();
+        let object = JsHostObject::with_named_properties(value);
+        GcNodeGraph::create_node(&self.nodes_graph, JsValue::HostObject(object))
+    }
+
+    /// Creates a value of a host class holding on to JS values. See [JsHostReferences].
+    pub fn create_host_object_with_references<T: JsHostClass + JsHostReferences>(
+        &mut self,
+        value: T,
+    ) -> JsValueNode {
+        self.global_prototypes.register_host_class::<T>();
+        let object = JsHostObject::with_references(value);
+        GcNodeGraph::create_node(&self.nodes_graph, JsValue::HostObject(object))
+    }
+
+    /// Starts counting the steps of a new run, which is not affected by the termination of the
+    /// last run. An interrupt terminates new runs too until its handle is cleared.
+    pub fn start_run(&mut self) {
+        self.steps = 0;
+        self.terminating = false;
+    }
+
+    /// Counts a step of the run, terminating it when it is interrupted or out of steps. Throws a
+    /// `RangeError` when the graph has grown past its maximum size.
+    pub fn step(&mut self) -> Result<(), JsValueNode> {
+        self.steps += 1;
+        let out_of_steps = self.limits.max_steps.is_some_and(|max| self.steps > max);
+        if self.terminating || out_of_steps || self.interrupt.is_interrupted() {
+            self.terminating = true;
+            return Err(JsValue::termination_rc(&self.nodes_graph));
+        }
+        if let Some(max_heap_size) = self.limits.max_heap_size {
+            if !self.heap_exhausted && self.nodes_graph.borrow().size() > max_heap_size {
+                self.heap_exhausted = true;
+                return Err(JsValue::range_error_rc(&self.nodes_graph));
+            }
         }
+        Ok(())
+    }
+
+    /// Whether the run was terminated, in which case thrown values are not handled until it ends
+    pub fn is_terminating(&self) -> bool {
+        self.terminating
+    }
+
+    /// Frees the nodes that can no longer be reached from the closure context or the roots. Values
+    /// held anywhere else are freed too, so this is only safe when nothing is being run, or
+    /// between the top level statements of a document. The detached HTML nodes whose objects were
+    /// freed are dropped as well.
+    pub fn collect_garbage(&mut self, roots: Vec<JsValueNode>) {
+        let live_nodes = roots
+            .into_iter()
+            .chain(self.get_referenced_nodes())
+            .collect();
+        *self.root.get_mut() = JsValue::Array(live_nodes, Default::default());
+        GcNodeGraph::gc(&self.nodes_graph);
+        *self.root.get_mut() = JsValue::Null;
+        self.heap_exhausted = false;
+        self.event_targets.prune_detached_nodes();
+    }
+
+    /// Collects garbage as [Self::collect_garbage] once the graph holds more than half of its
+    /// maximum size, leaving room for the statements run until the next collection
+    pub fn collect_garbage_near_limit<F>(&mut self, get_roots: F)
+    where
+        F: FnOnce() -> Vec<JsValueNode>,
+    {
+        let size = self.nodes_graph.borrow().size();
+        if self
+            .limits
+            .max_heap_size
+            .is_some_and(|max_heap_size| size > max_heap_size / 2)
+        {
+            self.collect_garbage(get_roots());
+        }
+        self.heap_exhausted = false;
     }
 
     pub fn with_new_context<T, F>(&mut self, func: F) -> T
     where
         F: Fn(&mut Self) -> T,
     {
-        self.closures.push(JsClosure::new(&self.nodes_graph));
+        self.push_closure();
         let ret = func(self);
-        self.closures.pop();
+        self.pop_closure();
         ret
     }
 
+    /// Runs a function with only the global scope visible, such as an indirect call to `eval`
+    pub fn with_global_scope<T>(&mut self, func: impl FnOnce(&mut Self) -> T) -> T {
+        let scopes = self.closures.split_off(1);
+        let ret = func(self);
+        self.closures.extend(scopes);
+        ret
+    }
+
+    /// Enters a new scope, which must be left with [Self::pop_closure]
+    pub fn push_closure(&mut self) {
+        self.closures.push(JsClosure::new(&self.nodes_graph));
+    }
+
+    pub fn pop_closure(&mut self) {
+        self.closures.pop();
+    }
+
+    /// Enters a scope that was left with [Self::take_closure], such as the scope of a module
+    pub fn push_existing_closure(&mut self, closure: JsClosure) {
+        self.closures.push(closure);
+    }
+
+    /// Leaves the latest scope, keeping it to be entered again
+    pub fn take_closure(&mut self) -> JsClosure {
+        assert!(self.closures.len() > 1, "The global scope cannot be left");
+        self.closures.pop().unwrap()
+    }
+
+    /// The top level scope of a module, if it has been entered
+    pub fn get_module_closure_mut(&mut self, url: &str) -> Option<&mut JsClosure> {
+        self.closures
+            .iter_mut()
+            .find(|closure| closure.module.as_deref() == Some(url))
+    }
+
+    /// The URL of the innermost module whose scope has been entered
+    pub fn get_current_module(&self) -> Option<&str> {
+        self.closures
+            .iter()
+            .rev()
+            .find_map(|closure| closure.module.as_deref())
+    }
+
     pub fn get_lastest_closure(&mut self) -> &mut JsClosure {
         self.closures.last_mut().unwrap()
     }
@@ -74,4 +274,54 @@ impl JsClosureContext {
     pub fn get_closure_depth(&self) -> usize {
         self.closures.len()
     }
+
+    /// Gets the bytecode of a user defined function, compiling it the first time it is run
+    pub fn get_compiled_function(&mut self, function: &JsValueNode) -> Rc<JsChunk> {
+        if let Some((_function, chunk)) = self.compiled_functions.get(&function.id()) {
+            return chunk.clone();
+        }
+        let chunk = match function.get_ref() {
+            JsValue::Function(JsFunction::UserDefined(_source, _name, _params, statements), _) => {
+                JsCompiler::compile_function(statements)
+            }
+            _ => JsChunk::default(),
+        };
+        let chunk = Rc::new(chunk);
+        self.compiled_functions
+            .insert(function.id(), (function.clone(), chunk.clone()));
+        chunk
+    }
+
+    /// Gets the nodes reachable from the closures, results, prototypes, compiled functions,
+    /// modules, event listeners, storage, jobs, fetches, navigation, `performance` and animation
+    /// frame callbacks
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        let closures = self
+            .closures
+            .iter()
+            .flat_map(JsClosure::get_referenced_nodes);
+        let results = self
+            .expression_results
+            .iter()
+            .flat_map(JsStatementResult::get_referenced_nodes);
+        let compiled_functions = self
+            .compiled_functions
+            .values()
+            .flat_map(|(function, chunk)| {
+                [vec![function.clone()], chunk.get_referenced_nodes()].concat()
+            });
+        closures
+            .chain(results)
+            .chain(self.global_prototypes.get_referenced_nodes())
+            .chain(compiled_functions)
+            .chain(self.modules.get_referenced_nodes())
+            .chain(self.event_targets.get_referenced_nodes())
+            .chain(self.storage.get_referenced_nodes())
+            .chain(self.jobs.get_referenced_nodes())
+            .chain(self.fetches.get_referenced_nodes())
+            .chain(self.navigation.get_referenced_nodes())
+            .chain(self.performance.get_referenced_nodes())
+            .chain(self.animation_frames.get_referenced_nodes())
+            .collect()
+    }
 }
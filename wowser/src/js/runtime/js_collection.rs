@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use super::{JsBigInt, JsSymbol, JsValue, JsValueNode};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsCollectionKind {
+    Map,
+    Set,
+    WeakMap,
+    WeakSet,
+}
+
+impl JsCollectionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Map => "Map",
+            Self::Set => "Set",
+            Self::WeakMap => "WeakMap",
+            Self::WeakSet => "WeakSet",
+        }
+    }
+
+    /// Weak collections only accept objects as keys and do not keep them alive
+    pub fn is_weak(&self) -> bool {
+        matches!(self, Self::WeakMap | Self::WeakSet)
+    }
+}
+
+/// Identifies a value by the SameValueZero algorithm, where `NaN` equals itself, `0` equals `-0`,
+/// and objects are compared by reference.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum SameValueZeroKey {
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(u64),
+    BigInt(JsBigInt),
+    String(String),
+    Symbol(JsSymbol),
+    Reference(usize),
+}
+
+impl From<&JsValueNode> for SameValueZeroKey {
+    fn from(node: &JsValueNode) -> Self {
+        let value = node.get_ref();
+        match value {
+            JsValue::Undefined => Self::Undefined,
+            JsValue::Null => Self::Null,
+            JsValue::Boolean(b) => Self::Boolean(*b),
+            JsValue::Number(n) if n.is_nan() => Self::Number(f64::NAN.to_bits()),
+            JsValue::Number(n) if *n == 0.0 => Self::Number(0.0f64.to_bits()),
+            JsValue::Number(n) => Self::Number(n.to_bits()),
+            JsValue::BigInt(bigint) => Self::BigInt(bigint.clone()),
+            JsValue::String(s) => Self::String(s.clone()),
+            JsValue::Symbol(symbol) => Self::Symbol(symbol.clone()),
+            value => Self::Reference(value as *const JsValue as usize),
+        }
+    }
+}
+
+/// The entries of a `Map`, `Set`, `WeakMap`, or `WeakSet` in insertion order. Sets store each
+/// value as both the key and value of its entry.
+#[derive(Debug)]
+pub struct JsKeyedCollection {
+    pub kind: JsCollectionKind,
+    /// Deleted entries leave holes so positions held by iterators remain valid
+    entries: Vec<Option<(JsValueNode, JsValueNode)>>,
+    index: HashMap<SameValueZeroKey, usize>,
+}
+
+impl JsKeyedCollection {
+    pub fn new(kind: JsCollectionKind) -> Self {
+        Self {
+            kind,
+            entries: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn get(&self, key: &JsValueNode) -> Option<&JsValueNode> {
+        self.index
+            .get(&SameValueZeroKey::from(key))
+            .and_then(|position| self.entries[*position].as_ref())
+            .map(|(_key, value)| value)
+    }
+
+    pub fn has(&self, key: &JsValueNode) -> bool {
+        self.index.contains_key(&SameValueZeroKey::from(key))
+    }
+
+    /// Updates the value of an existing entry in place, otherwise appends a new entry
+    pub fn insert(&mut self, key: JsValueNode, value: JsValueNode) {
+        let lookup_key = SameValueZeroKey::from(&key);
+        if let Some(position) = self.index.get(&lookup_key) {
+            if let Some(entry) = self.entries[*position].as_mut() {
+                entry.1 = value;
+            }
+            return;
+        }
+        self.index.insert(lookup_key, self.entries.len());
+        self.entries.push(Some((key, value)));
+    }
+
+    pub fn remove(&mut self, key: &JsValueNode) -> bool {
+        match self.index.remove(&SameValueZeroKey::from(key)) {
+            Some(position) => {
+                self.entries[position] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Finds the first entry at or after `position`, returning it with its position
+    pub fn next_entry(&self, position: usize) -> Option<(usize, &(JsValueNode, JsValueNode))> {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(position)
+            .find_map(|(position, entry)| entry.as_ref().map(|entry| (position, entry)))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &(JsValueNode, JsValueNode)> {
+        self.entries.iter().flatten()
+    }
+
+    /// Nodes that keep the entries alive. Weak collections hold nothing strongly.
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        if self.kind.is_weak() {
+            return vec![];
+        }
+        self.entries()
+            .flat_map(|(key, value)| [key.clone(), value.clone()])
+            .collect()
+    }
+
+    pub fn get_weak_entries(&self) -> Vec<(JsValueNode, JsValueNode)> {
+        match self.kind {
+            JsCollectionKind::WeakMap => self.entries().cloned().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Drops entries whose keys were garbage collected
+    pub fn retain_weak_entries(&mut self) {
+        if !self.kind.is_weak() {
+            return;
+        }
+        let entries = std::mem::take(&mut self.entries);
+        self.clear();
+        for (key, value) in entries.into_iter().flatten() {
+            if key.exists() {
+                self.insert(key, value);
+            }
+        }
+    }
+}
+
+impl PartialEq for JsKeyedCollection {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.entries().eq(other.entries())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::garbage_collector::GcNodeGraph;
+
+    use super::*;
+
+    #[test]
+    fn test_same_value_zero() {
+        let (node_graph, _root) = GcNodeGraph::new(JsValue::Undefined);
+        let mut collection = JsKeyedCollection::new(JsCollectionKind::Set);
+        let nan = JsValue::number_rc(&node_graph, f64::NAN);
+        collection.insert(nan.clone(), nan);
+        let zero = JsValue::number_rc(&node_graph, 0.0);
+        collection.insert(zero.clone(), zero);
+
+        assert!(collection.has(&JsValue::number_rc(&node_graph, f64::NAN)));
+        assert!(collection.has(&JsValue::number_rc(&node_graph, -0.0)));
+        assert!(!collection.has(&JsValue::str_rc(&node_graph, "0")));
+        assert!(!collection.has(&JsValue::object_rc(&node_graph, Default::default())));
+        assert_eq!(collection.len(), 2);
+    }
+
+    #[test]
+    fn test_weak_map_gc() {
+        let (node_graph, mut root) = GcNodeGraph::new(JsValue::Array(vec![], Default::default()));
+        let kept_key = JsValue::object_rc(&node_graph, Default::default());
+        let dropped_key = JsValue::object_rc(&node_graph, Default::default());
+        let mut collection = JsKeyedCollection::new(JsCollectionKind::WeakMap);
+        collection.insert(kept_key.clone(), JsValue::number_rc(&node_graph, 1.0));
+        collection.insert(dropped_key, JsValue::number_rc(&node_graph, 2.0));
+        let weak_map = JsValue::collection_rc(&node_graph, collection);
+        if let JsValue::Array(elements, _) = root.get_mut() {
+            elements.extend([weak_map.clone(), kept_key.clone()]);
+        }
+
+        assert_eq!(node_graph.borrow().size(), 6);
+        GcNodeGraph::gc(&node_graph);
+        assert_eq!(node_graph.borrow().size(), 4);
+        match weak_map.get_ref() {
+            JsValue::Collection(collection) => {
+                assert_eq!(collection.len(), 1);
+                assert!(collection.get(&kept_key).is_some());
+            }
+            value => panic!("Unexpected value: {value:?}"),
+        }
+    }
+}
@@ -0,0 +1,665 @@
+use std::collections::HashMap;
+
+use super::{
+    JsChunk, JsClass, JsDeclarationKind, JsExport, JsExpression, JsInstruction, JsObjectMember,
+    JsPattern, JsPropertyName, JsStatement, JsValueNode, DEFAULT_EXPORT_REFERENCE,
+};
+
+/// What a `break` may exit
+#[derive(Debug)]
+enum JsBreakTarget {
+    /// Exited by a `break` without a label
+    Switch,
+    /// Exited by a `break` with the label
+    Label(String),
+    /// A statement directly in a document or function body, where any `break` that reaches it is
+    /// recorded as its result
+    Statement,
+}
+
+#[derive(Debug)]
+struct JsBreakScope {
+    target: JsBreakTarget,
+    /// Jumps to patch to the end of the statement
+    jumps: Vec<usize>,
+}
+
+/// Compiles statements to the bytecode run by [super::JsVm]. Results are recorded as they are
+/// when walking the statements, so either engine may run a document.
+#[derive(Debug, Default)]
+pub struct JsCompiler {
+    chunk: JsChunk,
+    name_indices: HashMap<String, usize>,
+    break_scopes: Vec<JsBreakScope>,
+    in_function: bool,
+}
+
+impl JsCompiler {
+    /// Compiles a document. Statements that throw have the thrown value recorded, and the
+    /// statements after them still run.
+    pub fn compile_document(statements: &[JsStatement]) -> JsChunk {
+        let mut compiler = Self::default();
+        for statement in statements {
+            let handler = compiler.emit(JsInstruction::PushHandler(0));
+            compiler.compile_body_statement(statement);
+            compiler.emit(JsInstruction::PopHandler);
+            let end = compiler.emit(JsInstruction::Jump(0));
+            compiler.patch(handler);
+            compiler.emit(JsInstruction::RecordThrow);
+            compiler.patch(end);
+        }
+        compiler.chunk
+    }
+
+    /// Compiles the top level of a module, which is left by the first statement that throws.
+    /// Imports are bound before the module runs, so only declarations and default exports do
+    /// anything.
+    pub fn compile_module(statements: &[JsStatement]) -> JsChunk {
+        let mut compiler = Self::default();
+        for statement in statements {
+            match statement {
+                JsStatement::Export(
+                    JsExport::Declaration(declaration) | JsExport::DefaultDeclaration(declaration),
+                ) => compiler.compile_body_statement(declaration),
+                JsStatement::Export(JsExport::Default(expression)) => {
+                    compiler.compile_expression(expression);
+                    compiler.emit(JsInstruction::Dup);
+                    let name = compiler.name(DEFAULT_EXPORT_REFERENCE);
+                    compiler.emit(JsInstruction::Bind(name, Some(JsDeclarationKind::Const)));
+                    compiler.emit(JsInstruction::RecordValue);
+                }
+                JsStatement::Import(_) | JsStatement::Export(_) => {
+                    compiler.emit(JsInstruction::RecordVoid);
+                }
+                statement => compiler.compile_body_statement(statement),
+            }
+        }
+        compiler.chunk
+    }
+
+    /// Compiles the body of a user defined function, which returns `undefined` if it ends without
+    /// a `return`
+    pub fn compile_function(statements: &[JsStatement]) -> JsChunk {
+        let mut compiler = Self {
+            in_function: true,
+            ..Default::default()
+        };
+        for statement in statements {
+            compiler.compile_body_statement(statement);
+        }
+        compiler.emit(JsInstruction::Undefined);
+        compiler.emit(JsInstruction::Return);
+        compiler.chunk
+    }
+
+    fn compile_body_statement(&mut self, statement: &JsStatement) {
+        self.break_scopes.push(JsBreakScope {
+            target: JsBreakTarget::Statement,
+            jumps: vec![],
+        });
+        self.compile_statement(statement);
+        self.patch_break_scope();
+    }
+
+    fn compile_statements(&mut self, statements: &[JsStatement]) {
+        for statement in statements {
+            self.compile_statement(statement);
+        }
+    }
+
+    /// Compiles a statement, including recording its result
+    fn compile_statement(&mut self, statement: &JsStatement) {
+        match statement {
+            JsStatement::Empty => {
+                self.emit(JsInstruction::RecordVoid);
+            }
+            JsStatement::Expression(expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::VarDeclaration(reference) => {
+                let name = self.name(&reference.name);
+                self.emit(JsInstruction::DeclareVar(name));
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::VariableAssignment(reference, expression) => {
+                self.compile_expression(expression);
+                let name = self.name(&reference.name);
+                self.emit(JsInstruction::AssignVar(name));
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::Declaration(kind, pattern, expression) => {
+                match expression {
+                    Some(expression) => self.compile_expression(expression),
+                    None => {
+                        self.emit(JsInstruction::Undefined);
+                    }
+                }
+                self.emit(JsInstruction::Dup);
+                self.compile_pattern(pattern, Some(*kind));
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::FunctionDeclaration(reference) => {
+                let function = self.node(&reference.value);
+                self.emit(JsInstruction::Node(function));
+                let name = self.name(&reference.name);
+                self.emit(JsInstruction::AssignVar(name));
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::ClassDeclaration(class) => {
+                self.compile_class(class);
+                self.emit(JsInstruction::RecordValue);
+            }
+            JsStatement::Return(expression) => {
+                self.compile_expression(expression);
+                if self.in_function {
+                    self.emit(JsInstruction::Return);
+                } else {
+                    // Documents record the value and carry on with the next statement
+                    self.emit(JsInstruction::RecordReturn);
+                    let jump = self.emit(JsInstruction::Jump(0));
+                    self.break_scopes[0].jumps.push(jump);
+                }
+            }
+            JsStatement::Throw(expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::Throw);
+            }
+            JsStatement::If(condition, true_statements, false_statements) => {
+                self.compile_expression(condition);
+                let false_jump = self.emit(JsInstruction::JumpIfFalse(0));
+                self.compile_statements(true_statements);
+                let end = self.emit(JsInstruction::Jump(0));
+                self.patch(false_jump);
+                self.compile_statements(false_statements);
+                self.patch(end);
+                self.emit(JsInstruction::RecordVoid);
+            }
+            JsStatement::Switch(discriminant, cases) => self.compile_switch(discriminant, cases),
+            JsStatement::Block(statements) => {
+                self.compile_statements(statements);
+                self.emit(JsInstruction::RecordVoid);
+            }
+            JsStatement::Labeled(label, statement) => {
+                self.break_scopes.push(JsBreakScope {
+                    target: JsBreakTarget::Label(label.clone()),
+                    jumps: vec![],
+                });
+                self.compile_statement(statement);
+                let scope = self.break_scopes.pop().unwrap();
+                if !scope.jumps.is_empty() {
+                    let end = self.emit(JsInstruction::Jump(0));
+                    for jump in scope.jumps {
+                        self.patch(jump);
+                    }
+                    self.emit(JsInstruction::RecordVoid);
+                    self.patch(end);
+                }
+            }
+            JsStatement::Break(label) => self.compile_break(label.as_deref()),
+            // Modules compile their top level imports and exports themselves
+            JsStatement::Import(_) | JsStatement::Export(_) => {
+                self.emit(JsInstruction::ThrowSyntaxError);
+            }
+        }
+    }
+
+    /// Tests are compared with the discriminant in order until one matches, then the statements
+    /// run from the matching case, or from the default case, through to the end or a `break`
+    fn compile_switch(
+        &mut self,
+        discriminant: &JsExpression,
+        cases: &[(Option<JsExpression>, Vec<JsStatement>)],
+    ) {
+        self.compile_expression(discriminant);
+        let mut test_jumps = vec![];
+        for (index, (test, _statements)) in cases.iter().enumerate() {
+            if let Some(test) = test {
+                self.emit(JsInstruction::Dup);
+                self.compile_expression(test);
+                self.emit(JsInstruction::StrictEquals(true));
+                test_jumps.push((index, self.emit(JsInstruction::JumpIfTrue(0))));
+            }
+        }
+        self.emit(JsInstruction::Pop);
+        let mut default_jump = Some(self.emit(JsInstruction::Jump(0)));
+
+        // The discriminant is still on the stack when a test matches
+        let case_jumps: Vec<(usize, usize)> = test_jumps
+            .into_iter()
+            .map(|(index, jump)| {
+                self.patch(jump);
+                self.emit(JsInstruction::Pop);
+                (index, self.emit(JsInstruction::Jump(0)))
+            })
+            .collect();
+
+        self.break_scopes.push(JsBreakScope {
+            target: JsBreakTarget::Switch,
+            jumps: vec![],
+        });
+        for (index, (test, statements)) in cases.iter().enumerate() {
+            for (_index, jump) in case_jumps.iter().filter(|(case, _jump)| *case == index) {
+                self.patch(*jump);
+            }
+            if test.is_none() {
+                if let Some(jump) = default_jump.take() {
+                    self.patch(jump);
+                }
+            }
+            self.compile_statements(statements);
+        }
+        self.patch_break_scope();
+        if let Some(jump) = default_jump {
+            self.patch(jump);
+        }
+        self.emit(JsInstruction::RecordVoid);
+    }
+
+    /// Jumps to the end of the innermost statement the `break` exits
+    fn compile_break(&mut self, label: Option<&str>) {
+        let position = self
+            .break_scopes
+            .iter()
+            .rposition(|scope| match (&scope.target, label) {
+                (JsBreakTarget::Switch, None) => true,
+                (JsBreakTarget::Label(target), Some(label)) => target == label,
+                (JsBreakTarget::Statement, _) => true,
+                _ => false,
+            })
+            .expect("Statements are compiled within a body");
+        if let JsBreakTarget::Statement = self.break_scopes[position].target {
+            let label = label.map(|label| self.name(label));
+            self.emit(JsInstruction::RecordBreak(label));
+        }
+        let jump = self.emit(JsInstruction::Jump(0));
+        self.break_scopes[position].jumps.push(jump);
+    }
+
+    /// Compiles an expression, which pushes its value
+    fn compile_expression(&mut self, expression: &JsExpression) {
+        match expression {
+            JsExpression::Boolean(b) => {
+                self.emit(JsInstruction::Boolean(*b));
+            }
+            JsExpression::Number(num) => {
+                self.emit(JsInstruction::Number(*num));
+            }
+            JsExpression::BigInt(bigint) => {
+                self.emit(JsInstruction::BigInt(Box::new(bigint.clone())));
+            }
+            JsExpression::String(string) => {
+                let string = self.name(string);
+                self.emit(JsInstruction::String(string));
+            }
+            JsExpression::RegExp(source, flags) => {
+                let source = self.name(source);
+                let flags = self.name(flags);
+                self.emit(JsInstruction::RegExp(source, flags));
+            }
+            JsExpression::Undefined => {
+                self.emit(JsInstruction::Undefined);
+            }
+            JsExpression::Null => {
+                self.emit(JsInstruction::Null);
+            }
+            JsExpression::Object(members) => self.compile_object(members),
+            JsExpression::Array(elements) => {
+                self.emit(JsInstruction::NewArray);
+                self.compile_spreadable(elements.iter().map(Option::as_ref));
+            }
+            JsExpression::Spread(_) => {
+                self.emit(JsInstruction::ThrowSyntaxError);
+            }
+            JsExpression::TripleEquals(match_equality, a, b) => {
+                self.compile_expression(a);
+                self.compile_expression(b);
+                self.emit(JsInstruction::StrictEquals(*match_equality));
+            }
+            JsExpression::DoubleEquals(match_equality, a, b) => {
+                self.compile_expression(a);
+                self.compile_expression(b);
+                self.emit(JsInstruction::LooseEquals(*match_equality));
+            }
+            JsExpression::Add(a, b) => {
+                self.compile_expression(a);
+                self.compile_expression(b);
+                self.emit(JsInstruction::Add);
+            }
+            JsExpression::Binary(operator, a, b) => {
+                self.compile_expression(a);
+                self.compile_expression(b);
+                self.emit(JsInstruction::Binary(*operator));
+            }
+            JsExpression::Reference(variable_name) => {
+                let name = self.name(variable_name);
+                self.emit(JsInstruction::GetReference(name));
+            }
+            JsExpression::CastToNumber(expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::ToNumber);
+            }
+            JsExpression::Negate(expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::Negate);
+            }
+            JsExpression::BitwiseNot(expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::BitwiseNot);
+            }
+            JsExpression::InvokeFunction(function, args) => {
+                match function.as_ref() {
+                    JsExpression::AccessMember(base, member) => {
+                        self.compile_expression(base);
+                        let member = self.name(member);
+                        self.emit(JsInstruction::GetMethod(member));
+                    }
+                    JsExpression::AccessComputedMember(base, member) => {
+                        self.compile_expression(base);
+                        self.compile_expression(member);
+                        self.emit(JsInstruction::GetComputedMethod);
+                    }
+                    function => {
+                        self.emit(JsInstruction::Undefined);
+                        self.compile_expression(function);
+                        self.emit(JsInstruction::RequireFunction);
+                    }
+                }
+                let call = if function.is_direct_eval_callee() {
+                    JsInstruction::CallEval
+                } else {
+                    JsInstruction::Call
+                };
+                self.compile_arguments(args, call, JsInstruction::CallSpread);
+            }
+            JsExpression::New(constructor, args) => {
+                self.compile_expression(constructor);
+                self.compile_arguments(args, JsInstruction::New, JsInstruction::NewSpread);
+            }
+            JsExpression::AccessMember(base, member) => {
+                self.compile_expression(base);
+                let member = self.name(member);
+                self.emit(JsInstruction::GetMember(member));
+            }
+            JsExpression::AccessComputedMember(base, member) => {
+                self.compile_expression(base);
+                self.compile_expression(member);
+                self.emit(JsInstruction::GetComputedMember);
+            }
+            JsExpression::Assign(pattern, expression) => {
+                self.compile_expression(expression);
+                self.emit(JsInstruction::Dup);
+                self.compile_pattern(pattern, None);
+            }
+            JsExpression::Condition(condition, true_expression, false_expression) => {
+                self.compile_expression(condition);
+                let false_jump = self.emit(JsInstruction::JumpIfFalse(0));
+                self.compile_expression(true_expression);
+                let end = self.emit(JsInstruction::Jump(0));
+                self.patch(false_jump);
+                self.compile_expression(false_expression);
+                self.patch(end);
+            }
+            JsExpression::Sequence(expressions) => {
+                if expressions.is_empty() {
+                    self.emit(JsInstruction::Undefined);
+                }
+                for (index, expression) in expressions.iter().enumerate() {
+                    if index > 0 {
+                        self.emit(JsInstruction::Pop);
+                    }
+                    self.compile_expression(expression);
+                }
+            }
+            JsExpression::Import(specifier) => {
+                self.compile_expression(specifier);
+                self.emit(JsInstruction::Import);
+            }
+        }
+    }
+
+    /// Arguments are pushed individually, unless some are spread and are collected into an array
+    fn compile_arguments(
+        &mut self,
+        args: &[JsExpression],
+        invoke: fn(usize) -> JsInstruction,
+        invoke_spread: JsInstruction,
+    ) {
+        if args
+            .iter()
+            .any(|arg| matches!(arg, JsExpression::Spread(_)))
+        {
+            self.emit(JsInstruction::NewArray);
+            self.compile_spreadable(args.iter().map(Some));
+            self.emit(invoke_spread);
+        } else {
+            for arg in args {
+                self.compile_expression(arg);
+            }
+            self.emit(invoke(args.len()));
+        }
+    }
+
+    /// Appends values to the array on top of the stack, where holes are `undefined`
+    fn compile_spreadable<'a, I>(&mut self, expressions: I)
+    where
+        I: IntoIterator<Item = Option<&'a JsExpression>>,
+    {
+        for expression in expressions {
+            match expression {
+                Some(JsExpression::Spread(iterable)) => {
+                    self.compile_expression(iterable);
+                    self.emit(JsInstruction::ArraySpread);
+                }
+                Some(expression) => {
+                    self.compile_expression(expression);
+                    self.emit(JsInstruction::ArrayPush);
+                }
+                None => {
+                    self.emit(JsInstruction::Undefined);
+                    self.emit(JsInstruction::ArrayPush);
+                }
+            }
+        }
+    }
+
+    fn compile_object(&mut self, members: &[JsObjectMember]) {
+        self.emit(JsInstruction::NewObject);
+        for member in members {
+            match member {
+                JsObjectMember::Property(name, value) => {
+                    self.compile_property_name(name);
+                    self.compile_expression(value);
+                    self.emit(JsInstruction::DefineProperty);
+                }
+                JsObjectMember::Method(method) => {
+                    self.emit(JsInstruction::Dup);
+                    self.compile_property_name(&method.name);
+                    let function = self.node(&method.function);
+                    self.emit(JsInstruction::DefineMethod(function, method.kind, true));
+                }
+                JsObjectMember::Spread(expression) => {
+                    self.compile_expression(expression);
+                    self.emit(JsInstruction::CopyProperties);
+                }
+            }
+        }
+    }
+
+    /// Defines the class's members and binds it to its name, leaving the constructor on the stack
+    fn compile_class(&mut self, class: &JsClass) {
+        let constructor = self.node(&class.constructor);
+        self.emit(JsInstruction::CreateClass(constructor));
+        for (is_static, method) in &class.methods {
+            self.emit(if *is_static {
+                JsInstruction::Over
+            } else {
+                JsInstruction::Dup
+            });
+            self.compile_property_name(&method.name);
+            let function = self.node(&method.function);
+            self.emit(JsInstruction::DefineMethod(function, method.kind, false));
+        }
+        self.emit(JsInstruction::Pop);
+        self.emit(JsInstruction::Dup);
+        let name = self.name(&class.name);
+        self.emit(JsInstruction::Bind(name, Some(JsDeclarationKind::Let)));
+    }
+
+    fn compile_property_name(&mut self, name: &JsPropertyName) {
+        match name {
+            JsPropertyName::Static(name) => {
+                let name = self.name(name);
+                self.emit(JsInstruction::String(name));
+            }
+            JsPropertyName::Computed(expression) => self.compile_expression(expression),
+        }
+    }
+
+    /// Binds the value on top of the stack to the pattern, consuming it
+    fn compile_pattern(&mut self, pattern: &JsPattern, declaration: Option<JsDeclarationKind>) {
+        match pattern {
+            JsPattern::Identifier(name) => {
+                let name = self.name(name);
+                self.emit(JsInstruction::Bind(name, declaration));
+            }
+            JsPattern::Expression(expression) => match expression.as_ref() {
+                JsExpression::Reference(name) => {
+                    let name = self.name(name);
+                    self.emit(JsInstruction::Bind(name, declaration));
+                }
+                JsExpression::AccessMember(base, member) => {
+                    self.compile_expression(base);
+                    let member = self.name(member);
+                    self.emit(JsInstruction::SetMember(member));
+                }
+                JsExpression::AccessComputedMember(base, member) => {
+                    self.compile_expression(base);
+                    self.compile_expression(member);
+                    self.emit(JsInstruction::SetComputedMember);
+                }
+                _ => {
+                    self.emit(JsInstruction::ThrowReferenceError);
+                }
+            },
+            JsPattern::Array(elements, rest) => {
+                self.emit(JsInstruction::CollectIterable);
+                for (index, element) in elements.iter().enumerate() {
+                    if let Some(element) = element {
+                        self.emit(JsInstruction::GetElement(index));
+                        self.compile_pattern(element, declaration);
+                    }
+                }
+                if let Some(rest) = rest {
+                    self.emit(JsInstruction::GetRestElements(elements.len()));
+                    self.compile_pattern(rest, declaration);
+                }
+                self.emit(JsInstruction::Pop);
+            }
+            JsPattern::Object(properties, rest) => {
+                self.emit(JsInstruction::RequireObjectCoercible);
+                for (name, property) in properties {
+                    self.emit(JsInstruction::Dup);
+                    let name = self.name(name);
+                    self.emit(JsInstruction::GetMember(name));
+                    self.compile_pattern(property, declaration);
+                }
+                if let Some(rest) = rest {
+                    let names = properties
+                        .iter()
+                        .map(|(name, _property)| self.name(name))
+                        .collect();
+                    self.emit(JsInstruction::GetRestProperties(names));
+                    self.compile_pattern(rest, declaration);
+                }
+                self.emit(JsInstruction::Pop);
+            }
+            JsPattern::Default(pattern, default) => {
+                let defined_jump = self.emit(JsInstruction::JumpIfNotUndefined(0));
+                self.emit(JsInstruction::Pop);
+                self.compile_expression(default);
+                self.patch(defined_jump);
+                self.compile_pattern(pattern, declaration);
+            }
+        }
+    }
+
+    fn emit(&mut self, instruction: JsInstruction) -> usize {
+        self.chunk.instructions.push(instruction);
+        self.chunk.instructions.len() - 1
+    }
+
+    /// Points the jump at the index to the next instruction emitted
+    fn patch(&mut self, index: usize) {
+        let target = self.chunk.instructions.len();
+        self.chunk.instructions[index].set_target(target);
+    }
+
+    fn patch_break_scope(&mut self) {
+        let scope = self.break_scopes.pop().unwrap();
+        for jump in scope.jumps {
+            self.patch(jump);
+        }
+    }
+
+    fn name(&mut self, name: &str) -> usize {
+        if let Some(index) = self.name_indices.get(name) {
+            return *index;
+        }
+        self.chunk.names.push(name.to_string());
+        let index = self.chunk.names.len() - 1;
+        self.name_indices.insert(name.to_string(), index);
+        index
+    }
+
+    fn node(&mut self, node: &JsValueNode) -> usize {
+        self.chunk.nodes.push(node.clone());
+        self.chunk.nodes.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js::parse_js;
+
+    fn compile_function(script: &str) -> JsChunk {
+        let statements = parse_js(script).unwrap().statements;
+        JsCompiler::compile_function(&statements)
+    }
+
+    #[test]
+    fn test_compile_function() {
+        let chunk = compile_function("return a + 1");
+        assert_eq!(
+            chunk.instructions,
+            vec![
+                JsInstruction::GetReference(0),
+                JsInstruction::Number(1.0),
+                JsInstruction::Add,
+                JsInstruction::Return,
+                JsInstruction::Undefined,
+                JsInstruction::Return,
+            ]
+        );
+        assert_eq!(chunk.names, vec!["a"]);
+    }
+
+    #[test]
+    fn test_compile_break() {
+        let chunk = compile_function("a: { break a; } break");
+        assert_eq!(
+            chunk.instructions,
+            vec![
+                JsInstruction::Jump(3),
+                JsInstruction::RecordVoid,
+                JsInstruction::Jump(4),
+                JsInstruction::RecordVoid,
+                JsInstruction::RecordBreak(None),
+                JsInstruction::Jump(6),
+                JsInstruction::Undefined,
+                JsInstruction::Return,
+            ]
+        );
+    }
+}
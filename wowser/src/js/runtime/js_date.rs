@@ -0,0 +1,340 @@
+use std::{fmt, rc::Rc};
+
+use crate::js::api::date::{
+    make_date, make_day, make_time, parse_date, time_clip, to_iso_string, to_local_date_string,
+    to_local_string, to_local_time_string, to_utc_string, Date, DateFields, TimeZone, INVALID_DATE,
+    MS_PER_MINUTE,
+};
+
+use super::{
+    build_function_entry, build_native_function, build_prototype, get_argument, to_number,
+    JsClosureContext, JsFunctionResult, JsProperty, JsPropertyKey, JsValue, JsValueNode,
+    CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+/// A `Date` object. It keeps the time zone it was created in so it can be shown in local time
+/// wherever it is converted to a string.
+pub struct JsDate {
+    pub time: Date,
+    pub time_zone: Rc<dyn TimeZone>,
+}
+
+impl JsDate {
+    pub fn new(time: Date, time_zone: Rc<dyn TimeZone>) -> Self {
+        Self { time, time_zone }
+    }
+
+    /// The parts of the time, in local time or in UTC, or `None` for an invalid date
+    pub fn fields(&self, utc: bool) -> Option<DateFields> {
+        if utc {
+            DateFields::from_time(self.time)
+        } else {
+            DateFields::from_time(self.time_zone.to_local(self.time))
+        }
+    }
+
+    /// The minutes behind UTC of the local time
+    pub fn timezone_offset(&self) -> f64 {
+        if self.time.is_nan() {
+            return f64::NAN;
+        }
+        // Adding zero turns negative zero into positive zero
+        -self.time_zone.offset_at(self.time) / MS_PER_MINUTE + 0.0
+    }
+
+    pub fn to_date_string(&self) -> String {
+        to_local_date_string(self.time, &*self.time_zone)
+            .unwrap_or_else(|| INVALID_DATE.to_string())
+    }
+
+    pub fn to_time_string(&self) -> String {
+        to_local_time_string(self.time, &*self.time_zone)
+            .unwrap_or_else(|| INVALID_DATE.to_string())
+    }
+}
+
+impl fmt::Debug for JsDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsDate").field("time", &self.time).finish()
+    }
+}
+
+impl PartialEq for JsDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time || (self.time.is_nan() && other.time.is_nan())
+    }
+}
+
+impl fmt::Display for JsDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", to_local_string(self.time, &*self.time_zone))
+    }
+}
+
+/// `Date` called as a function gives the current time as a string. With `new`, it creates a date
+/// from nothing for the current time, from a time value, date, or string, or from the local year,
+/// month, and so on.
+pub fn build_date_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor = build_native_function(&node_graph, &"Date".into(), |ctx, this, args| {
+        let now = ctx.clock.now();
+        if matches!(this.get_ref(), JsValue::Undefined) {
+            let date = JsDate::new(now, ctx.time_zone.clone());
+            return Ok(JsValue::string_rc(&ctx.nodes_graph, date.to_string()));
+        }
+        let time = match args {
+            [] => now,
+            [value] => match value.get_ref() {
+                JsValue::Date(date) => date.time,
+                JsValue::String(string) => parse_date(string, &*ctx.time_zone),
+                _ => to_number(value, ctx)?,
+            },
+            _ => ctx.time_zone.to_utc(time_from_parts(ctx, args)?),
+        };
+        Ok(date_rc(ctx, time_clip(time)))
+    });
+
+    let statics = [
+        build_function_entry(&node_graph, "now".into(), |ctx, _this, _args| {
+            Ok(JsValue::number_rc(&ctx.nodes_graph, ctx.clock.now()))
+        }),
+        build_function_entry(&node_graph, "parse".into(), |ctx, _this, args| {
+            let input = get_argument(ctx, args, 0).get_ref().to_string();
+            let time = parse_date(&input, &*ctx.time_zone);
+            Ok(JsValue::number_rc(&ctx.nodes_graph, time))
+        }),
+        build_function_entry(&node_graph, "UTC".into(), |ctx, _this, args| {
+            let time = time_clip(time_from_parts(ctx, args)?);
+            Ok(JsValue::number_rc(&ctx.nodes_graph, time))
+        }),
+    ];
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        for (key, value) in statics {
+            properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+        }
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
+
+fn date_rc(closure_context: &JsClosureContext, time: Date) -> JsValueNode {
+    let date = JsDate::new(time, closure_context.time_zone.clone());
+    JsValue::date_rc(&closure_context.nodes_graph, date)
+}
+
+/// The time from the year, month, day, hours, minutes, seconds, and milliseconds arguments, where
+/// only the year is required. Years from 0 to 99 are in the 1900s.
+fn time_from_parts(
+    closure_context: &JsClosureContext,
+    args: &[JsValueNode],
+) -> Result<Date, JsValueNode> {
+    let mut parts = [f64::NAN, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+    for (part, arg) in parts.iter_mut().zip(args) {
+        *part = to_number(arg, closure_context)?;
+    }
+    let [year, month, day, hours, minutes, seconds, milliseconds] = parts;
+    let year = if (0.0..=99.0).contains(&year.trunc()) {
+        1900.0 + year.trunc()
+    } else {
+        year
+    };
+    Ok(make_date(
+        make_day(year, month, day),
+        make_time(hours, minutes, seconds, milliseconds),
+    ))
+}
+
+type DateField = fn(&DateFields) -> f64;
+type DateFormat = fn(&JsDate) -> String;
+
+/// (Name, Field) of each getter, which also has a UTC variant
+const GETTERS: [(&str, DateField); 8] = [
+    ("FullYear", |fields| fields.year as f64),
+    ("Month", |fields| fields.month.into()),
+    ("Date", |fields| fields.day.into()),
+    ("Day", |fields| fields.weekday.into()),
+    ("Hours", |fields| fields.hours.into()),
+    ("Minutes", |fields| fields.minutes.into()),
+    ("Seconds", |fields| fields.seconds.into()),
+    ("Milliseconds", |fields| fields.milliseconds.into()),
+];
+
+/// (Name, Index of the first part set, Most parts set) of each setter, which also has a UTC
+/// variant. Parts are indexed in the order year, month, day, hours, minutes, seconds, and
+/// milliseconds.
+const SETTERS: [(&str, usize, usize); 7] = [
+    ("FullYear", 0, 3),
+    ("Month", 1, 2),
+    ("Date", 2, 1),
+    ("Hours", 3, 4),
+    ("Minutes", 4, 3),
+    ("Seconds", 5, 2),
+    ("Milliseconds", 6, 1),
+];
+
+pub fn build_date_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let mut prototype = build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "getTime".into(), date_get_time),
+            build_function_entry(&node_graph, "valueOf".into(), date_get_time),
+            build_function_entry(&node_graph, "setTime".into(), date_set_time),
+            build_function_entry(&node_graph, "getTimezoneOffset".into(), |ctx, this, _| {
+                let offset = with_date(ctx, this, |date| date.timezone_offset())?;
+                Ok(JsValue::number_rc(&ctx.nodes_graph, offset))
+            }),
+            build_function_entry(&node_graph, "toISOString".into(), date_to_iso_string),
+            build_function_entry(&node_graph, "toJSON".into(), date_to_json),
+        ],
+    );
+
+    let string_methods: [(&str, DateFormat); 4] = [
+        ("toString", |date| date.to_string()),
+        ("toDateString", JsDate::to_date_string),
+        ("toTimeString", JsDate::to_time_string),
+        ("toUTCString", |date| to_utc_string(date.time)),
+    ];
+    let string_methods = string_methods.map(|(name, format)| {
+        build_function_entry(&node_graph, name.into(), move |ctx, this, _| {
+            let string = with_date(ctx, this, |date| format(date))?;
+            Ok(JsValue::string_rc(&ctx.nodes_graph, string))
+        })
+    });
+    let getters = GETTERS.into_iter().flat_map(|(name, field)| {
+        [false, true].map(|utc| {
+            let key = JsPropertyKey::from(accessor_name("get", name, utc));
+            build_function_entry(&node_graph, key, move |ctx, this, _| {
+                let fields = with_date(ctx, this, |date| date.fields(utc))?;
+                let value = fields.as_ref().map(field).unwrap_or(f64::NAN);
+                Ok(JsValue::number_rc(&ctx.nodes_graph, value))
+            })
+        })
+    });
+    let setters = SETTERS.into_iter().flat_map(|(name, start, count)| {
+        [false, true].map(|utc| {
+            let key = JsPropertyKey::from(accessor_name("set", name, utc));
+            build_function_entry(&node_graph, key, move |ctx, this, args| {
+                let mut values = vec![];
+                for index in 0..args.len().clamp(1, count) {
+                    values.push(to_number(&get_argument(ctx, args, index), ctx)?);
+                }
+                let time = with_date(ctx, this, |date| {
+                    let time = set_parts(date, utc, start, &values);
+                    date.time = time;
+                    time
+                })?;
+                Ok(JsValue::number_rc(&ctx.nodes_graph, time))
+            })
+        })
+    });
+    let methods: Vec<_> = string_methods
+        .into_iter()
+        .chain(getters)
+        .chain(setters)
+        .collect();
+
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, value) in methods {
+            properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+        }
+    }
+    prototype
+}
+
+/// Such as `getFullYear` or `getUTCFullYear`
+fn accessor_name(prefix: &str, name: &str, utc: bool) -> String {
+    let utc = if utc { "UTC" } else { "" };
+    format!("{prefix}{utc}{name}")
+}
+
+/// The time of a date after replacing some of its parts, starting at the part with the index.
+/// Setting the year of an invalid date starts from the epoch, while other setters leave it invalid.
+fn set_parts(date: &JsDate, utc: bool, start: usize, values: &[f64]) -> Date {
+    let fields = match date.fields(utc) {
+        Some(fields) => fields,
+        None if start == 0 => DateFields::from_time(0.0).unwrap(),
+        None => return f64::NAN,
+    };
+    let mut parts = [
+        fields.year as f64,
+        fields.month.into(),
+        fields.day.into(),
+        fields.hours.into(),
+        fields.minutes.into(),
+        fields.seconds.into(),
+        fields.milliseconds.into(),
+    ];
+    parts[start..start + values.len()].copy_from_slice(values);
+    let [year, month, day, hours, minutes, seconds, milliseconds] = parts;
+    let time = make_date(
+        make_day(year, month, day),
+        make_time(hours, minutes, seconds, milliseconds),
+    );
+    if utc {
+        time_clip(time)
+    } else {
+        time_clip(date.time_zone.to_utc(time))
+    }
+}
+
+fn date_get_time(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    _args: &[JsValueNode],
+) -> JsFunctionResult {
+    let time = with_date(closure_context, this, |date| date.time)?;
+    Ok(JsValue::number_rc(&closure_context.nodes_graph, time))
+}
+
+fn date_set_time(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let time = to_number(&get_argument(closure_context, args, 0), closure_context)?;
+    let time = time_clip(time);
+    with_date(closure_context, this, |date| date.time = time)?;
+    Ok(JsValue::number_rc(&closure_context.nodes_graph, time))
+}
+
+/// Throws a `RangeError` for invalid dates
+fn date_to_iso_string(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    _args: &[JsValueNode],
+) -> JsFunctionResult {
+    match with_date(closure_context, this, |date| to_iso_string(date.time))? {
+        Some(string) => Ok(JsValue::string_rc(&closure_context.nodes_graph, string)),
+        None => Err(JsValue::range_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// The same as `toISOString`, but gives `null` for invalid dates
+fn date_to_json(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    _args: &[JsValueNode],
+) -> JsFunctionResult {
+    let string = with_date(closure_context, this, |date| to_iso_string(date.time))?;
+    Ok(match string {
+        Some(string) => JsValue::string_rc(&closure_context.nodes_graph, string),
+        None => JsValue::null_rc(&closure_context.nodes_graph),
+    })
+}
+
+/// Runs `func` on the date `this`, throwing a `TypeError` if `this` is not one
+pub fn with_date<T>(
+    closure_context: &JsClosureContext,
+    mut this: JsValueNode,
+    func: impl FnOnce(&mut JsDate) -> T,
+) -> Result<T, JsValueNode> {
+    match this.get_mut() {
+        JsValue::Date(date) => Ok(func(date)),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
@@ -1,6 +1,23 @@
-use crate::garbage_collector::GcNodeGraph;
+use std::{cell::RefCell, rc::Rc};
 
-use super::{globals::add_globals, JsClosure, JsClosureContext, JsStatement, JsValue};
+use crate::{
+    garbage_collector::GcNodeGraph,
+    html::HtmlDocument,
+    js::api::{
+        date::{Clock, Date},
+        history::SessionHistory,
+        storage::StorageArea,
+    },
+    net::Url,
+};
+
+use super::{
+    add_document_global, add_storage_globals, add_window_global, dispatch_event, eval_source,
+    globals::add_globals, import_module, process_fetch_responses, run_animation_frame_callbacks,
+    run_jobs, take_storage_changes, update_document_url, JsClosure, JsClosureContext, JsCompiler,
+    JsEngine, JsEvent, JsEventDetail, JsEventTargetId, JsFunctionResult, JsStatement,
+    JsStorageAreas, JsValue, JsValueNode, JsVm,
+};
 
 #[derive(Debug)]
 pub struct JsDocument {
@@ -10,9 +27,9 @@ pub struct JsDocument {
 
 impl JsDocument {
     pub fn new(statements: Vec<JsStatement>) -> Self {
-        let (nodes_graph, _node) = GcNodeGraph::new(JsValue::Null);
+        let (nodes_graph, root) = GcNodeGraph::new(JsValue::Null);
         let global_closure = JsClosure::new(&nodes_graph);
-        let mut global_closure_context = JsClosureContext::new(global_closure);
+        let mut global_closure_context = JsClosureContext::new(global_closure, root);
         add_globals(&mut global_closure_context);
         Self {
             statements,
@@ -20,10 +37,179 @@ impl JsDocument {
         }
     }
 
+    /// Exposes the HTML document to scripts as `document`, along with the `window` showing it.
+    /// The document is shared, so changes scripts make are seen by whoever renders it.
+    pub fn set_html_document(&mut self, document: Rc<RefCell<HtmlDocument>>) {
+        add_window_global(&mut self.global_closure_context);
+        add_document_global(&mut self.global_closure_context, document);
+    }
+
+    /// Gives scripts the storage of the document at the URL. Documents without an origin have no
+    /// local storage, and session storage is shared by the documents of a tab.
+    pub fn set_storage(
+        &mut self,
+        url: &str,
+        local: Option<Rc<RefCell<StorageArea>>>,
+        session: Rc<RefCell<StorageArea>>,
+    ) {
+        let closure_context = &mut self.global_closure_context;
+        closure_context.storage = JsStorageAreas::new(url, local, session);
+        if closure_context.event_targets.window.is_some() {
+            add_storage_globals(closure_context);
+        }
+    }
+
+    /// Sets the URL of the document, which the requests scripts make with `fetch` are resolved
+    /// against and made from. It is the first entry of the session history when there are none.
+    pub fn set_url(&mut self, url: &Url) {
+        self.global_closure_context.fetches.document_url = Some(url.clone());
+        let mut history = self.global_closure_context.navigation.history.borrow_mut();
+        if history.is_empty() {
+            history.push_document(url.to_record(), false);
+        }
+    }
+
+    /// Gives scripts the session history of the tab, which `location` and `history` read from and
+    /// navigate through
+    pub fn set_session_history(&mut self, history: Rc<RefCell<SessionHistory>>) {
+        let navigation = &mut self.global_closure_context.navigation;
+        navigation.history = history;
+        navigation.clear_state();
+    }
+
+    /// Updates the URL of the document and `history.state` after the tab moved to another entry of
+    /// the document in the session history
+    pub fn update_url_from_history(&mut self) {
+        update_document_url(&mut self.global_closure_context);
+    }
+
+    /// Reads high resolution time from the clock, whose current time becomes the time origin of
+    /// the document
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.global_closure_context.performance.set_clock(clock);
+    }
+
+    /// Whether scripts asked for callbacks to run before the next frame
+    pub fn has_animation_frame_callbacks(&self) -> bool {
+        !self.global_closure_context.animation_frames.is_empty()
+    }
+
+    /// Runs the callbacks scripts asked to run before the frame starting at the time, which was
+    /// read from the clock of the document, then runs the jobs they queued. Results in whether
+    /// any ran.
+    pub fn run_animation_frame_callbacks(&mut self, frame_time: Date) -> bool {
+        self.collect_garbage_near_limit();
+        self.global_closure_context.start_run();
+        let ran = run_animation_frame_callbacks(&mut self.global_closure_context, frame_time)
+            .unwrap_or(true);
+        run_jobs(&mut self.global_closure_context);
+        ran
+    }
+
+    /// Settles the promises of the requests scripts made with `fetch` that finished, then runs the
+    /// jobs that queued. Results in whether any requests finished.
+    pub fn process_fetches(&mut self) -> bool {
+        self.collect_garbage_near_limit();
+        self.global_closure_context.start_run();
+        let processed = process_fetch_responses(&mut self.global_closure_context);
+        run_jobs(&mut self.global_closure_context);
+        processed
+    }
+
+    /// Dispatches a `storage` event to the window for each change other documents made to local
+    /// storage since the last time. Results in whether there were any.
+    pub fn dispatch_storage_events(&mut self) -> bool {
+        let changes = take_storage_changes(&self.global_closure_context);
+        let dispatched = !changes.is_empty();
+        for change in changes {
+            let event = JsEvent::new("storage", false, false, JsEventDetail::Storage(change));
+            self.dispatch_event(JsEventTargetId::Window, event);
+        }
+        dispatched
+    }
+
+    /// Dispatches an event from the browser, such as input, to the target, then runs the jobs the
+    /// listeners queued. Results in whether the event was not canceled, so its default action
+    /// should happen.
+    pub fn dispatch_event(&mut self, target: JsEventTargetId, event: JsEvent) -> bool {
+        self.collect_garbage_near_limit();
+        self.global_closure_context.start_run();
+        let event = self.global_closure_context.create_host_object(event);
+        let not_canceled =
+            dispatch_event(&mut self.global_closure_context, target, &event).unwrap_or(true);
+        run_jobs(&mut self.global_closure_context);
+        not_canceled
+    }
+
+    /// Runs the statements with the engine of the global closure context, recording the result of
+    /// each, then runs the jobs they queued. A terminated run records the termination and stops.
     pub fn run(&mut self) {
-        for statement in &self.statements {
-            let result = statement.run(&mut self.global_closure_context);
-            self.global_closure_context.record_new_result(result);
+        self.global_closure_context.start_run();
+        match self.global_closure_context.engine {
+            JsEngine::TreeWalker => {
+                for statement in &self.statements {
+                    self.global_closure_context
+                        .collect_garbage_near_limit(|| get_statement_nodes(&self.statements));
+                    let result = statement.run(&mut self.global_closure_context);
+                    self.global_closure_context.record_new_result(result);
+                    if self.global_closure_context.is_terminating() {
+                        break;
+                    }
+                }
+            }
+            JsEngine::Bytecode => {
+                let chunk = Rc::new(JsCompiler::compile_document(&self.statements));
+                JsVm::new().run_document(chunk, &mut self.global_closure_context);
+            }
         }
+        run_jobs(&mut self.global_closure_context);
+    }
+
+    /// Imports the module at the URL along with the modules it imports, resolving it against the
+    /// base URL of the modules when relative, then runs the jobs they queued. Results in the
+    /// module's namespace.
+    pub fn run_module(&mut self, url: &str) -> JsFunctionResult {
+        self.collect_garbage_near_limit();
+        self.global_closure_context.start_run();
+        let specifier =
+            JsValue::string_rc(&self.global_closure_context.nodes_graph, url.to_string());
+        let result = import_module(&mut self.global_closure_context, &specifier);
+        run_jobs(&mut self.global_closure_context);
+        result
+    }
+
+    /// Runs the source text of a classic script in the global scope, as a `<script>` element does,
+    /// then runs the jobs it queued. Declarations are shared with the other scripts of the
+    /// document. Results in the value of the last statement with one, or throws what the script
+    /// throws.
+    pub fn run_script(&mut self, source: &str) -> JsFunctionResult {
+        self.collect_garbage_near_limit();
+        self.global_closure_context.start_run();
+        let result = self
+            .global_closure_context
+            .with_global_scope(|closure_context| eval_source(closure_context, source));
+        run_jobs(&mut self.global_closure_context);
+        result
+    }
+
+    /// Frees the nodes that can no longer be reached from the statements or the global closure
+    /// context
+    pub fn collect_garbage(&mut self) {
+        let statement_nodes = get_statement_nodes(&self.statements);
+        self.global_closure_context.collect_garbage(statement_nodes);
     }
+
+    /// Collects garbage once the graph nears its maximum size, before a run starts so nothing is
+    /// being run
+    fn collect_garbage_near_limit(&mut self) {
+        self.global_closure_context
+            .collect_garbage_near_limit(|| get_statement_nodes(&self.statements));
+    }
+}
+
+fn get_statement_nodes(statements: &[JsStatement]) -> Vec<JsValueNode> {
+    statements
+        .iter()
+        .flat_map(JsStatement::get_referenced_nodes)
+        .collect()
 }
@@ -0,0 +1,639 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use wowser_macros::js_class;
+
+use crate::html::{
+    parse_html, ElementContents, ElementContentsId, ElementHtmlNode, HtmlDocument, TextHtmlNode,
+};
+
+use super::{with_host_object, FromJsValue, JsClosureContext, JsValue, JsValueNode};
+
+const NODE_TYPE_ELEMENT: u32 = 1;
+const NODE_TYPE_TEXT: u32 = 3;
+
+/// The HTML document scripts see, shared by the objects of its nodes
+#[derive(Clone)]
+pub struct JsDom {
+    pub document: Rc<RefCell<HtmlDocument>>,
+    /// The object of each node scripts have seen, so a node is always the same object
+    objects: Rc<RefCell<HashMap<ElementContentsId, JsValueNode>>>,
+}
+
+impl JsDom {
+    pub fn new(document: Rc<RefCell<HtmlDocument>>) -> Self {
+        Self {
+            document,
+            objects: Default::default(),
+        }
+    }
+
+    /// The object of a node, which is created again when its last one was garbage collected as
+    /// nothing could still refer to it
+    pub fn get_object(
+        &self,
+        closure_context: &mut JsClosureContext,
+        id: ElementContentsId,
+    ) -> JsValueNode {
+        if let Some(object) = self
+            .objects
+            .borrow()
+            .get(&id)
+            .filter(|object| object.exists())
+        {
+            return object.clone();
+        }
+        let object = closure_context.create_host_object(JsNode {
+            dom: self.clone(),
+            id,
+        });
+        self.objects.borrow_mut().insert(id, object.clone());
+        object
+    }
+
+    /// Drops the detached nodes scripts can no longer reach, which are those without a node whose
+    /// object is still alive
+    pub fn prune_detached(&self) {
+        let mut objects = self.objects.borrow_mut();
+        objects.retain(|_, object| object.exists());
+        self.document
+            .borrow_mut()
+            .retain_detached(|id| objects.contains_key(&id));
+    }
+
+    fn get_objects(
+        &self,
+        closure_context: &mut JsClosureContext,
+        ids: Vec<ElementContentsId>,
+    ) -> JsValueNode {
+        let objects = ids
+            .into_iter()
+            .map(|id| self.get_object(closure_context, id))
+            .collect();
+        JsValue::array_rc(&closure_context.nodes_graph, objects)
+    }
+
+    /// The elements under the node with the tag name, or all of them for `*`
+    fn get_elements_by_tag_name(
+        &self,
+        root: ElementContentsId,
+        tag_name: &str,
+    ) -> Vec<ElementContentsId> {
+        self.find_elements(root, |element| {
+            tag_name == "*" || element.tag_name.eq_ignore_ascii_case(tag_name)
+        })
+    }
+
+    /// The elements under the node with all the classes in the whitespace separated list
+    fn get_elements_by_class_name(
+        &self,
+        root: ElementContentsId,
+        class_names: &str,
+    ) -> Vec<ElementContentsId> {
+        let class_names = class_names.split_whitespace().collect::<Vec<_>>();
+        if class_names.is_empty() {
+            return vec![];
+        }
+        self.find_elements(root, |element| {
+            let classes = get_classes(element);
+            class_names
+                .iter()
+                .all(|class_name| classes.contains(class_name))
+        })
+    }
+
+    fn find_elements(
+        &self,
+        root: ElementContentsId,
+        predicate: impl Fn(&ElementHtmlNode) -> bool,
+    ) -> Vec<ElementContentsId> {
+        let document = self.document.borrow();
+        let Some(root) = document.get_node(root) else {
+            return vec![];
+        };
+        root.find_descendants(
+            |node| matches!(node, ElementContents::Element(element) if predicate(element)),
+        )
+        .into_iter()
+        .map(ElementContents::get_id)
+        .collect()
+    }
+
+    /// Runs a function with the element, throwing a `TypeError` when the node is text
+    fn with_element<R>(
+        &self,
+        closure_context: &JsClosureContext,
+        id: ElementContentsId,
+        func: impl FnOnce(&ElementHtmlNode) -> R,
+    ) -> Result<R, JsValueNode> {
+        match self.document.borrow().get_node(id) {
+            Some(ElementContents::Element(element)) => Ok(func(element)),
+            _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        }
+    }
+
+    /// Runs a function that changes the element, throwing a `TypeError` when the node is text
+    fn with_element_mut<R>(
+        &self,
+        closure_context: &JsClosureContext,
+        id: ElementContentsId,
+        func: impl FnOnce(&mut ElementHtmlNode) -> R,
+    ) -> Result<R, JsValueNode> {
+        match self.document.borrow_mut().get_node_mut(id) {
+            Some(ElementContents::Element(element)) => Ok(func(element)),
+            _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        }
+    }
+}
+
+/// Exposes the HTML document to scripts as `document`, whose nodes events can be dispatched to
+pub fn add_document_global(
+    closure_context: &mut JsClosureContext,
+    document: Rc<RefCell<HtmlDocument>>,
+) {
+    let event_target = closure_context.global_prototypes.event_target.clone();
+    let prototypes = &mut closure_context.global_prototypes;
+    prototypes.register_host_class_extending::<JsHtmlDocument>(event_target.clone());
+    prototypes.register_host_class_extending::<JsNode>(event_target);
+
+    let dom = JsDom::new(document);
+    let document = closure_context.create_host_object(JsHtmlDocument { dom: dom.clone() });
+    closure_context.event_targets.document = Some(document.clone());
+    closure_context.event_targets.dom = Some(dom);
+    closure_context
+        .get_or_declare_reference_mut("document")
+        .value = document;
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Document>
+pub struct JsHtmlDocument {
+    dom: JsDom,
+}
+
+#[js_class(name = "Document")]
+impl JsHtmlDocument {
+    fn root_id(&self) -> ElementContentsId {
+        self.dom.document.borrow().html.get_id()
+    }
+
+    /// The first element with the tag name
+    fn find_element(
+        &self,
+        closure_context: &mut JsClosureContext,
+        tag_name: &str,
+    ) -> Option<JsValueNode> {
+        let id = *self
+            .dom
+            .get_elements_by_tag_name(self.root_id(), tag_name)
+            .first()?;
+        Some(self.dom.get_object(closure_context, id))
+    }
+
+    #[js_method(getter)]
+    fn document_element(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.dom.get_object(closure_context, self.root_id())
+    }
+
+    #[js_method(getter)]
+    fn head(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        self.find_element(closure_context, "head")
+    }
+
+    #[js_method(getter)]
+    fn body(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        self.find_element(closure_context, "body")
+    }
+
+    #[js_method]
+    fn get_element_by_id(
+        &self,
+        closure_context: &mut JsClosureContext,
+        element_id: String,
+    ) -> Option<JsValueNode> {
+        let id = self
+            .dom
+            .find_elements(self.root_id(), |element| {
+                element.get_attribute("id") == Some(element_id.as_str())
+            })
+            .into_iter()
+            .next()?;
+        Some(self.dom.get_object(closure_context, id))
+    }
+
+    #[js_method]
+    fn get_elements_by_tag_name(
+        &self,
+        closure_context: &mut JsClosureContext,
+        tag_name: String,
+    ) -> JsValueNode {
+        let ids = self.dom.get_elements_by_tag_name(self.root_id(), &tag_name);
+        self.dom.get_objects(closure_context, ids)
+    }
+
+    #[js_method]
+    fn get_elements_by_class_name(
+        &self,
+        closure_context: &mut JsClosureContext,
+        class_names: String,
+    ) -> JsValueNode {
+        let ids = self
+            .dom
+            .get_elements_by_class_name(self.root_id(), &class_names);
+        self.dom.get_objects(closure_context, ids)
+    }
+
+    #[js_method]
+    fn create_element(
+        &self,
+        closure_context: &mut JsClosureContext,
+        tag_name: String,
+    ) -> JsValueNode {
+        let element = ElementHtmlNode::new(0, tag_name.to_ascii_lowercase(), vec![], vec![]);
+        let id = self
+            .dom
+            .document
+            .borrow_mut()
+            .add_detached(ElementContents::Element(element));
+        self.dom.get_object(closure_context, id)
+    }
+
+    #[js_method]
+    fn create_text_node(
+        &self,
+        closure_context: &mut JsClosureContext,
+        text: String,
+    ) -> JsValueNode {
+        let id = self
+            .dom
+            .document
+            .borrow_mut()
+            .add_detached(ElementContents::Text(TextHtmlNode::new(text)));
+        self.dom.get_object(closure_context, id)
+    }
+}
+
+/// An element or text in the document. Elements and text share this class, where the methods of
+/// elements throw a `TypeError` when called on text.
+///
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Node>
+#[derive(Clone)]
+pub struct JsNode {
+    dom: JsDom,
+    id: ElementContentsId,
+}
+
+impl JsNode {
+    pub fn get_id(&self) -> ElementContentsId {
+        self.id
+    }
+}
+
+#[js_class(name = "Node")]
+impl JsNode {
+    fn is_element(&self) -> bool {
+        matches!(
+            self.dom.document.borrow().get_node(self.id),
+            Some(ElementContents::Element(_))
+        )
+    }
+
+    #[js_method(getter)]
+    fn node_type(&self) -> u32 {
+        if self.is_element() {
+            NODE_TYPE_ELEMENT
+        } else {
+            NODE_TYPE_TEXT
+        }
+    }
+
+    #[js_method(getter)]
+    fn node_name(&self) -> String {
+        self.tag_name().unwrap_or_else(|| "#text".to_string())
+    }
+
+    /// The upper case name of elements, or `null` for text
+    #[js_method(getter)]
+    fn tag_name(&self) -> Option<String> {
+        match self.dom.document.borrow().get_node(self.id) {
+            Some(ElementContents::Element(element)) => Some(element.tag_name.to_ascii_uppercase()),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn parent_node(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        let parent = self.dom.document.borrow().get_parent(self.id)?.get_id();
+        Some(self.dom.get_object(closure_context, parent))
+    }
+
+    /// A snapshot of the children, which does not change as the children do
+    #[js_method(getter)]
+    fn child_nodes(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        let ids = match self.dom.document.borrow().get_node(self.id) {
+            Some(ElementContents::Element(element)) => element
+                .children
+                .iter()
+                .map(ElementContents::get_id)
+                .collect(),
+            _ => vec![],
+        };
+        self.dom.get_objects(closure_context, ids)
+    }
+
+    #[js_method(getter)]
+    fn text_content(&self) -> String {
+        self.dom
+            .document
+            .borrow()
+            .get_node(self.id)
+            .map(ElementContents::text_content)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the children of elements with the text, or the text of text nodes
+    #[js_method(setter)]
+    fn set_text_content(&mut self, text: String) {
+        let mut document = self.dom.document.borrow_mut();
+        match document.get_node_mut(self.id) {
+            Some(ElementContents::Text(node)) => node.text = text,
+            Some(ElementContents::Element(_)) => {
+                let children = if text.is_empty() {
+                    vec![]
+                } else {
+                    vec![ElementContents::Text(TextHtmlNode::new(text))]
+                };
+                document.replace_children(self.id, children);
+            }
+            None => {}
+        }
+    }
+
+    #[js_method(getter, name = "innerHTML")]
+    fn inner_html(&self, closure_context: &mut JsClosureContext) -> Result<String, JsValueNode> {
+        self.dom.with_element(closure_context, self.id, |element| {
+            element
+                .children
+                .iter()
+                .map(|child| child.to_string())
+                .collect()
+        })
+    }
+
+    /// Replaces the children of the element with the nodes parsed from the HTML, throwing a
+    /// `SyntaxError` when it cannot be parsed
+    #[js_method(setter, name = "innerHTML")]
+    fn set_inner_html(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        html: String,
+    ) -> Result<(), JsValueNode> {
+        self.dom.with_element(closure_context, self.id, |_| ())?;
+        let fragment = parse_html(&html)
+            .map_err(|_| JsValue::syntax_error_rc(&closure_context.nodes_graph))?;
+        let children = match fragment.html {
+            ElementContents::Element(html) => html.children,
+            ElementContents::Text(_) => vec![],
+        };
+        self.dom
+            .document
+            .borrow_mut()
+            .replace_children(self.id, children);
+        Ok(())
+    }
+
+    /// The value of the attribute, which is empty for attributes without one, or `null` when the
+    /// element does not have it
+    #[js_method]
+    fn get_attribute(
+        &self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<Option<String>, JsValueNode> {
+        self.dom.with_element(closure_context, self.id, |element| {
+            element
+                .has_attribute(&name)
+                .then(|| element.get_attribute(&name).unwrap_or_default().to_string())
+        })
+    }
+
+    #[js_method]
+    fn has_attribute(
+        &self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<bool, JsValueNode> {
+        self.dom.with_element(closure_context, self.id, |element| {
+            element.has_attribute(&name)
+        })
+    }
+
+    #[js_method]
+    fn set_attribute(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        self.dom
+            .with_element_mut(closure_context, self.id, |element| {
+                element.set_attribute(&name, value)
+            })
+    }
+
+    #[js_method]
+    fn remove_attribute(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<(), JsValueNode> {
+        self.dom
+            .with_element_mut(closure_context, self.id, |element| {
+                element.remove_attribute(&name)
+            })
+    }
+
+    #[js_method(getter)]
+    fn class_list(
+        &self,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<JsValueNode, JsValueNode> {
+        self.dom.with_element(closure_context, self.id, |_| ())?;
+        Ok(closure_context.create_host_object(JsDomTokenList {
+            dom: self.dom.clone(),
+            id: self.id,
+        }))
+    }
+
+    #[js_method]
+    fn get_elements_by_tag_name(
+        &self,
+        closure_context: &mut JsClosureContext,
+        tag_name: String,
+    ) -> JsValueNode {
+        let ids = self.dom.get_elements_by_tag_name(self.id, &tag_name);
+        self.dom.get_objects(closure_context, ids)
+    }
+
+    #[js_method]
+    fn get_elements_by_class_name(
+        &self,
+        closure_context: &mut JsClosureContext,
+        class_names: String,
+    ) -> JsValueNode {
+        let ids = self.dom.get_elements_by_class_name(self.id, &class_names);
+        self.dom.get_objects(closure_context, ids)
+    }
+
+    /// Moves the node to the end of the children, resulting in the node
+    #[js_method]
+    fn append_child(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        child: JsNode,
+    ) -> Result<JsValueNode, JsValueNode> {
+        self.insert(closure_context, child, None)
+    }
+
+    /// Moves the node before the reference child, or to the end when the reference is `null`,
+    /// resulting in the node
+    #[js_method]
+    fn insert_before(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        child: JsNode,
+        reference: JsValueNode,
+    ) -> Result<JsValueNode, JsValueNode> {
+        let reference = match reference.get_ref() {
+            JsValue::Null | JsValue::Undefined => None,
+            _ => Some(JsNode::from_js_value(&reference, closure_context)?),
+        };
+        self.insert(closure_context, child, reference)
+    }
+
+    /// Removes a child, throwing a `TypeError` when the node is not a child. Results in the
+    /// removed node.
+    #[js_method]
+    fn remove_child(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        child: JsNode,
+    ) -> Result<JsValueNode, JsValueNode> {
+        let mut document = self.dom.document.borrow_mut();
+        let is_child = document
+            .get_parent(child.id)
+            .is_some_and(|parent| parent.get_id() == self.id);
+        if !is_child || !document.detach(child.id) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        drop(document);
+        Ok(self.dom.get_object(closure_context, child.id))
+    }
+
+    /// Throws a `TypeError` when the node would be moved into itself or is text, or the
+    /// reference is not a child
+    fn insert(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        child: JsNode,
+        reference: Option<JsNode>,
+    ) -> Result<JsValueNode, JsValueNode> {
+        let is_inserted = self.dom.document.borrow_mut().insert_child(
+            self.id,
+            child.id,
+            reference.map(|reference| reference.id),
+        );
+        if !is_inserted {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        Ok(self.dom.get_object(closure_context, child.id))
+    }
+}
+
+/// Nodes are passed to host functions as their objects, throwing a `TypeError` for other values
+impl FromJsValue for JsNode {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        with_host_object(closure_context, value, |node: &mut JsNode, _| node.clone())
+    }
+}
+
+fn get_classes(element: &ElementHtmlNode) -> Vec<&str> {
+    element
+        .get_attribute("class")
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect()
+}
+
+/// The classes of an element, which reads and writes its `class` attribute
+///
+/// <https://developer.mozilla.org/en-US/docs/Web/API/DOMTokenList>
+pub struct JsDomTokenList {
+    dom: JsDom,
+    id: ElementContentsId,
+}
+
+#[js_class(name = "DOMTokenList")]
+impl JsDomTokenList {
+    fn get_classes(&self) -> Vec<String> {
+        match self.dom.document.borrow().get_node(self.id) {
+            Some(ElementContents::Element(element)) => get_classes(element)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn set_classes(&self, classes: Vec<String>) {
+        if let Some(ElementContents::Element(element)) =
+            self.dom.document.borrow_mut().get_node_mut(self.id)
+        {
+            element.set_attribute("class", classes.join(" "));
+        }
+    }
+
+    #[js_method(getter)]
+    fn length(&self) -> u32 {
+        self.get_classes().len() as u32
+    }
+
+    #[js_method(getter)]
+    fn value(&self) -> String {
+        self.get_classes().join(" ")
+    }
+
+    #[js_method]
+    fn contains(&self, token: String) -> bool {
+        self.get_classes().contains(&token)
+    }
+
+    #[js_method]
+    fn add(&mut self, token: String) {
+        let mut classes = self.get_classes();
+        if !classes.contains(&token) {
+            classes.push(token);
+            self.set_classes(classes);
+        }
+    }
+
+    #[js_method]
+    fn remove(&mut self, token: String) {
+        let mut classes = self.get_classes();
+        classes.retain(|class| *class != token);
+        self.set_classes(classes);
+    }
+
+    /// Adds the class when missing and removes it otherwise, resulting in whether it was added
+    #[js_method]
+    fn toggle(&mut self, token: String) -> bool {
+        let is_added = !self.contains(token.clone());
+        if is_added {
+            self.add(token);
+        } else {
+            self.remove(token);
+        }
+        is_added
+    }
+}
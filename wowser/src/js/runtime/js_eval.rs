@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use crate::js::parse_js_statements;
+
+use super::{
+    build_native_function, get_argument, FromJsValue, JsClosureContext, JsCompiler, JsEngine,
+    JsFunctionResult, JsProperty, JsStatement, JsStatementResult, JsValue, JsValueGraph,
+    JsValueNode, JsVm, CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+/// The name calls to `eval` must use to run in the caller's scope
+pub const EVAL_REFERENCE: &str = "eval";
+
+/// `eval` runs source text in the global scope, unless it is called directly by its name. See
+/// [run_eval].
+pub fn build_eval_function(node_graph: &JsValueGraph) -> JsValueNode {
+    build_native_function(
+        node_graph,
+        &EVAL_REFERENCE.into(),
+        |closure_context, _this, args| {
+            closure_context.with_global_scope(|closure_context| run_eval(closure_context, args))
+        },
+    )
+}
+
+/// Runs the source text passed to `eval` in the latest scope, as a direct call to `eval` does.
+/// Arguments other than strings are the result as they are.
+pub fn run_eval(closure_context: &mut JsClosureContext, args: &[JsValueNode]) -> JsFunctionResult {
+    let source = get_argument(closure_context, args, 0);
+    let JsValue::String(source) = source.get_ref() else {
+        return Ok(source);
+    };
+    eval_source(closure_context, source)
+}
+
+/// Runs source text with the engine of the closure context, resulting in the value of the last
+/// statement with one, or `undefined`. Declarations are made in the latest scope, as they are by
+/// `var`. Source text that cannot be parsed, or imports, exports, or returns outside a function,
+/// throw a `SyntaxError`.
+pub fn eval_source(closure_context: &mut JsClosureContext, source: &str) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let statements = parse_js_statements(source, &nodes_graph)
+        .map_err(|message| JsValue::syntax_error_with_message_rc(&nodes_graph, &message))?;
+    if statements.iter().any(|statement| {
+        matches!(
+            statement,
+            JsStatement::Import(_) | JsStatement::Export(_) | JsStatement::Return(_)
+        )
+    }) {
+        return Err(JsValue::syntax_error_rc(&nodes_graph));
+    }
+
+    let start = closure_context.expression_results.len();
+    match closure_context.engine {
+        JsEngine::TreeWalker => {
+            for statement in &statements {
+                match statement.run(closure_context) {
+                    JsStatementResult::ThrowValue(value) => return Err(value),
+                    result => closure_context.record_new_result(result),
+                }
+            }
+        }
+        JsEngine::Bytecode => {
+            // Modules run in the scope they are given, recording the result of each statement
+            let chunk = Rc::new(JsCompiler::compile_module(&statements));
+            JsVm::new().run_module(chunk, closure_context)?;
+        }
+    }
+    let completion = closure_context.expression_results[start..]
+        .iter()
+        .rev()
+        .find_map(|result| match result {
+            JsStatementResult::Value(value) => Some(value.clone()),
+            _ => None,
+        });
+    Ok(completion.unwrap_or_else(|| JsValue::undefined_rc(&nodes_graph)))
+}
+
+/// `Function` creates a function from the source text of its parameters followed by its body,
+/// whether or not it is called with `new`
+pub fn build_function_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &"Function".into(), |ctx, _this, args| {
+            let mut sources = vec![];
+            for arg in args {
+                sources.push(String::from_js_value(arg, ctx)?);
+            }
+            let body = sources.pop().unwrap_or_default();
+            let params = sources.join(",");
+            let source = format!("function anonymous({params}\n) {{\n{body}\n}}");
+
+            let nodes_graph = ctx.nodes_graph.clone();
+            let statements = parse_js_statements(&source, &nodes_graph)
+                .map_err(|message| JsValue::syntax_error_with_message_rc(&nodes_graph, &message))?;
+            // Parameters or bodies closing the function early leave other statements around it
+            match statements.as_slice() {
+                [JsStatement::FunctionDeclaration(reference)] => Ok(reference.value.clone()),
+                _ => Err(JsValue::syntax_error_rc(&nodes_graph)),
+            }
+        });
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
@@ -0,0 +1,776 @@
+use std::{collections::HashMap, fmt};
+
+use wowser_macros::js_class;
+
+use crate::{html::ElementContentsId, js::api::storage::StorageChange};
+
+use super::{
+    build_function_entry, build_prototype, get_history_state, get_local_storage_object,
+    internal_get, invoke_function, with_host_object, JsClosureContext, JsDom, JsFunctionResult,
+    JsHostClass, JsHtmlDocument, JsNode, JsPropertyKey, JsValue, JsValueGraph, JsValueNode,
+    JsWindow,
+};
+
+const PHASE_NONE: u32 = 0;
+const PHASE_CAPTURING: u32 = 1;
+const PHASE_AT_TARGET: u32 = 2;
+const PHASE_BUBBLING: u32 = 3;
+
+/// What events can be dispatched to, which are the window, the document, and its nodes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JsEventTargetId {
+    Window,
+    Document,
+    Node(ElementContentsId),
+}
+
+#[derive(Clone)]
+struct JsEventListener {
+    event_type: String,
+    /// A function, or an object with a `handleEvent` method
+    callback: JsValueNode,
+    capture: bool,
+    once: bool,
+}
+
+impl JsEventListener {
+    /// Listeners are the same when added for the same type, callback, and phase
+    fn is_same(&self, other: &JsEventListener) -> bool {
+        self.event_type == other.event_type
+            && self.callback.is_same_ref(&other.callback)
+            && self.capture == other.capture
+    }
+}
+
+/// The event listeners of the window and the document, along with the objects events are
+/// dispatched to. Listeners are kept by their target rather than its object, since the objects of
+/// nodes are created again after being garbage collected.
+#[derive(Default)]
+pub struct JsEventTargets {
+    listeners: HashMap<JsEventTargetId, Vec<JsEventListener>>,
+    pub window: Option<JsValueNode>,
+    pub document: Option<JsValueNode>,
+    pub dom: Option<JsDom>,
+}
+
+impl fmt::Debug for JsEventTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsEventTargets")
+            .field("listeners", &self.listeners.keys())
+            .finish()
+    }
+}
+
+impl JsEventTargets {
+    fn add_listener(&mut self, target: JsEventTargetId, listener: JsEventListener) {
+        let listeners = self.listeners.entry(target).or_default();
+        if !listeners.iter().any(|existing| existing.is_same(&listener)) {
+            listeners.push(listener);
+        }
+    }
+
+    /// Removes the listener, resulting in whether it was added
+    fn remove_listener(&mut self, target: JsEventTargetId, listener: &JsEventListener) -> bool {
+        let Some(listeners) = self.listeners.get_mut(&target) else {
+            return false;
+        };
+        let length = listeners.len();
+        listeners.retain(|existing| !existing.is_same(listener));
+        listeners.len() != length
+    }
+
+    fn has_listener(&self, target: JsEventTargetId, listener: &JsEventListener) -> bool {
+        self.listeners
+            .get(&target)
+            .is_some_and(|listeners| listeners.iter().any(|existing| existing.is_same(listener)))
+    }
+
+    fn get_listeners(
+        &self,
+        target: JsEventTargetId,
+        event_type: &str,
+        capture: bool,
+    ) -> Vec<JsEventListener> {
+        self.listeners
+            .get(&target)
+            .map(|listeners| {
+                listeners
+                    .iter()
+                    .filter(|listener| {
+                        listener.event_type == event_type && listener.capture == capture
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The targets an event dispatched to the target passes through, from the target up to the
+    /// window. Nodes outside the document only pass through their ancestors.
+    fn get_path(&self, target: JsEventTargetId) -> Vec<JsEventTargetId> {
+        let mut path = vec![target];
+        if let (JsEventTargetId::Node(id), Some(dom)) = (target, &self.dom) {
+            let document = dom.document.borrow();
+            let mut current = id;
+            while let Some(parent) = document.get_parent(current) {
+                current = parent.get_id();
+                path.push(JsEventTargetId::Node(current));
+            }
+            if current == document.html.get_id() {
+                path.push(JsEventTargetId::Document);
+            }
+        }
+        if path.last() == Some(&JsEventTargetId::Document) {
+            path.push(JsEventTargetId::Window);
+        }
+        path
+    }
+
+    /// Drops the detached nodes scripts can no longer reach, along with the listeners of nodes
+    /// that are gone
+    pub fn prune_detached_nodes(&mut self) {
+        let Some(dom) = &self.dom else {
+            return;
+        };
+        dom.prune_detached();
+        let document = dom.document.borrow();
+        self.listeners.retain(|target, _| match target {
+            JsEventTargetId::Node(id) => document.get_node(*id).is_some(),
+            JsEventTargetId::Window | JsEventTargetId::Document => true,
+        });
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.listeners
+            .values()
+            .flatten()
+            .map(|listener| listener.callback.clone())
+            .chain(self.window.clone())
+            .chain(self.document.clone())
+            .collect()
+    }
+}
+
+/// The object of an event target, or `null` when it has none
+fn get_target_object(
+    closure_context: &mut JsClosureContext,
+    target: JsEventTargetId,
+) -> JsValueNode {
+    let event_targets = &closure_context.event_targets;
+    let object = match target {
+        JsEventTargetId::Window => event_targets.window.clone(),
+        JsEventTargetId::Document => event_targets.document.clone(),
+        JsEventTargetId::Node(id) => event_targets
+            .dom
+            .clone()
+            .map(|dom| dom.get_object(closure_context, id)),
+    };
+    object.unwrap_or_else(|| JsValue::null_rc(&closure_context.nodes_graph))
+}
+
+/// The target `this` refers to. Functions called without `this`, such as `addEventListener` in the
+/// global scope, refer to the window.
+fn get_target_id(
+    closure_context: &mut JsClosureContext,
+    this: &JsValueNode,
+) -> Result<JsEventTargetId, JsValueNode> {
+    let class_name = match this.get_ref() {
+        JsValue::Undefined => return Ok(JsEventTargetId::Window),
+        JsValue::HostObject(object) => object.class_name,
+        _ => return Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    };
+    match class_name {
+        JsWindow::NAME => Ok(JsEventTargetId::Window),
+        JsHtmlDocument::NAME => Ok(JsEventTargetId::Document),
+        _ => with_host_object(closure_context, this, |node: &mut JsNode, _| {
+            JsEventTargetId::Node(node.get_id())
+        }),
+    }
+}
+
+/// Reads a boolean member of a dictionary argument, where missing dictionaries and members are
+/// `false`
+fn get_dictionary_flag(
+    closure_context: &mut JsClosureContext,
+    dictionary: Option<&JsValueNode>,
+    name: &str,
+) -> Result<bool, JsValueNode> {
+    match dictionary {
+        Some(dictionary) if dictionary.get_ref().is_object() => {
+            let value = internal_get(dictionary, &name.into(), dictionary, closure_context)?;
+            Ok(value.map_value(|value| value.into()))
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Reads the listener from the arguments of `addEventListener` or `removeEventListener`, which are
+/// the type, the callback, and whether to capture or a dictionary of options. A `null` callback
+/// is no listener.
+fn get_listener_argument(
+    closure_context: &mut JsClosureContext,
+    args: &[JsValueNode],
+) -> Result<Option<JsEventListener>, JsValueNode> {
+    let (Some(event_type), Some(callback)) = (args.first(), args.get(1)) else {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    };
+    if !callback.get_ref().is_object() {
+        return Ok(None);
+    }
+    let options = args.get(2);
+    let (capture, once) = match options.map(|options| options.get_ref().is_object()) {
+        Some(true) => (
+            get_dictionary_flag(closure_context, options, "capture")?,
+            get_dictionary_flag(closure_context, options, "once")?,
+        ),
+        Some(false) => (options.unwrap().map_value(|value| value.into()), false),
+        None => (false, false),
+    };
+    Ok(Some(JsEventListener {
+        event_type: event_type.get_ref().to_string(),
+        callback: callback.clone(),
+        capture,
+        once,
+    }))
+}
+
+/// The prototype of the window, the document, and nodes, which events can be dispatched to
+pub fn build_event_target_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(parent, build_event_target_methods(&node_graph))
+}
+
+/// `addEventListener`, `removeEventListener`, and `dispatchEvent`
+pub fn build_event_target_methods(node_graph: &JsValueGraph) -> [(JsPropertyKey, JsValueNode); 3] {
+    [
+        build_function_entry(
+            node_graph,
+            "addEventListener".into(),
+            event_target_add_event_listener,
+        ),
+        build_function_entry(
+            node_graph,
+            "removeEventListener".into(),
+            event_target_remove_event_listener,
+        ),
+        build_function_entry(
+            node_graph,
+            "dispatchEvent".into(),
+            event_target_dispatch_event,
+        ),
+    ]
+}
+
+fn event_target_add_event_listener(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_target_id(closure_context, &this)?;
+    if let Some(listener) = get_listener_argument(closure_context, args)? {
+        closure_context.event_targets.add_listener(target, listener);
+    }
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+fn event_target_remove_event_listener(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_target_id(closure_context, &this)?;
+    if let Some(listener) = get_listener_argument(closure_context, args)? {
+        closure_context
+            .event_targets
+            .remove_listener(target, &listener);
+    }
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// Dispatches a script's event, resulting in `false` when a listener canceled it
+fn event_target_dispatch_event(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_target_id(closure_context, &this)?;
+    let Some(event) = args.first() else {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    };
+    let not_canceled = dispatch_event(closure_context, target, event)?;
+    Ok(JsValue::bool_rc(&closure_context.nodes_graph, not_canceled))
+}
+
+/// Dispatches the event to the target. Listeners for capturing run from the window down to the
+/// target, then the other listeners run from the target back up when the event bubbles. Results
+/// in whether the event was not canceled, so its default action should happen. Exceptions thrown
+/// by listeners are reported without stopping the dispatch, and events already being dispatched
+/// throw.
+pub fn dispatch_event(
+    closure_context: &mut JsClosureContext,
+    target: JsEventTargetId,
+    event: &JsValueNode,
+) -> Result<bool, JsValueNode> {
+    let (event_type, bubbles) = with_host_object(
+        closure_context,
+        event,
+        |event: &mut JsEvent, closure_context| {
+            if event.dispatching {
+                return Err(JsValue::type_error_or_dom_exception_rc(
+                    &closure_context.nodes_graph,
+                ));
+            }
+            event.dispatching = true;
+            event.target = Some(target);
+            Ok((event.event_type.clone(), event.bubbles))
+        },
+    )??;
+
+    let path = closure_context.event_targets.get_path(target);
+    let mut result = Ok(());
+    for current in path.iter().rev() {
+        let phase = if *current == target {
+            PHASE_AT_TARGET
+        } else {
+            PHASE_CAPTURING
+        };
+        result = invoke_listeners(closure_context, event, &event_type, *current, phase, true);
+        if result.is_err() {
+            break;
+        }
+    }
+    if result.is_ok() {
+        for current in &path {
+            let phase = if *current == target {
+                PHASE_AT_TARGET
+            } else if bubbles {
+                PHASE_BUBBLING
+            } else {
+                continue;
+            };
+            result = invoke_listeners(closure_context, event, &event_type, *current, phase, false);
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    let default_prevented = with_host_object(closure_context, event, |event: &mut JsEvent, _| {
+        event.dispatching = false;
+        event.phase = PHASE_NONE;
+        event.current_target = None;
+        event.propagation_stopped = false;
+        event.immediate_propagation_stopped = false;
+        event.default_prevented
+    })?;
+    result?;
+    Ok(!default_prevented)
+}
+
+/// Runs the listeners of the current target for the phase, unless propagation was stopped. Only
+/// termination is thrown.
+fn invoke_listeners(
+    closure_context: &mut JsClosureContext,
+    event: &JsValueNode,
+    event_type: &str,
+    current: JsEventTargetId,
+    phase: u32,
+    capture: bool,
+) -> Result<(), JsValueNode> {
+    let stopped = with_host_object(closure_context, event, |event: &mut JsEvent, _| {
+        event.phase = phase;
+        event.current_target = Some(current);
+        event.propagation_stopped
+    })?;
+    if stopped {
+        return Ok(());
+    }
+
+    let listeners = closure_context
+        .event_targets
+        .get_listeners(current, event_type, capture);
+    if listeners.is_empty() {
+        return Ok(());
+    }
+    let this = get_target_object(closure_context, current);
+    for listener in listeners {
+        // Listeners removed by earlier listeners do not run
+        if !closure_context
+            .event_targets
+            .has_listener(current, &listener)
+        {
+            continue;
+        }
+        if listener.once {
+            closure_context
+                .event_targets
+                .remove_listener(current, &listener);
+        }
+
+        let result = if listener.callback.get_ref().is_callable() {
+            invoke_function(
+                closure_context,
+                &listener.callback,
+                this.clone(),
+                &[event.clone()],
+            )
+        } else {
+            internal_get(
+                &listener.callback,
+                &"handleEvent".into(),
+                &listener.callback,
+                closure_context,
+            )
+            .and_then(|handle_event| {
+                invoke_function(
+                    closure_context,
+                    &handle_event,
+                    listener.callback.clone(),
+                    &[event.clone()],
+                )
+            })
+        };
+        if let Err(value) = result {
+            if closure_context.is_terminating() {
+                return Err(value);
+            }
+            log!(ERROR["JS"]: "Uncaught exception in", event_type, "listener", value.get_ref().to_string());
+        }
+
+        let stopped = with_host_object(closure_context, event, |event: &mut JsEvent, _| {
+            event.immediate_propagation_stopped
+        })?;
+        if stopped {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The mouse and keyboard state of events from input, the change of `storage` events, how much of
+/// a response the events of `XMLHttpRequest` have received, or the URLs of `hashchange` events
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum JsEventDetail {
+    #[default]
+    None,
+    Mouse {
+        client_x: f64,
+        client_y: f64,
+        button: u16,
+        modifiers: JsEventModifiers,
+    },
+    Keyboard {
+        key: String,
+        code: String,
+        repeat: bool,
+        modifiers: JsEventModifiers,
+    },
+    Storage(StorageChange),
+    /// The bytes of the body received, out of the total when it is known
+    Progress {
+        loaded: u64,
+        total: Option<u64>,
+    },
+    /// `popstate`, whose state is that of the entry moved to
+    PopState,
+    HashChange {
+        old_url: String,
+        new_url: String,
+    },
+}
+
+/// The modifier keys held during input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsEventModifiers {
+    pub alt: bool,
+    pub control: bool,
+    pub meta: bool,
+    pub shift: bool,
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Event>
+///
+/// Events from mouse and keyboard input have the members of `MouseEvent` and `KeyboardEvent`,
+/// `storage` events have those of `StorageEvent`, the events of `XMLHttpRequest` have those of
+/// `ProgressEvent`, and `popstate` and `hashchange` events have those of `PopStateEvent` and
+/// `HashChangeEvent`, which are `null` on other events.
+#[derive(Debug, Default)]
+pub struct JsEvent {
+    event_type: String,
+    bubbles: bool,
+    cancelable: bool,
+    /// Whether the event comes from the browser rather than a script
+    is_trusted: bool,
+    detail: JsEventDetail,
+    default_prevented: bool,
+    propagation_stopped: bool,
+    immediate_propagation_stopped: bool,
+    dispatching: bool,
+    phase: u32,
+    target: Option<JsEventTargetId>,
+    current_target: Option<JsEventTargetId>,
+}
+
+impl JsEvent {
+    /// An event from the browser
+    pub fn new(event_type: &str, bubbles: bool, cancelable: bool, detail: JsEventDetail) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            bubbles,
+            cancelable,
+            is_trusted: true,
+            detail,
+            ..Default::default()
+        }
+    }
+
+    /// `new Event(type, { bubbles, cancelable })`
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let Some(event_type) = args.first() else {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        };
+        Ok(Self {
+            event_type: event_type.get_ref().to_string(),
+            bubbles: get_dictionary_flag(closure_context, args.get(1), "bubbles")?,
+            cancelable: get_dictionary_flag(closure_context, args.get(1), "cancelable")?,
+            ..Default::default()
+        })
+    }
+
+    fn get_modifiers(&self) -> Option<JsEventModifiers> {
+        match &self.detail {
+            JsEventDetail::None
+            | JsEventDetail::Storage(_)
+            | JsEventDetail::Progress { .. }
+            | JsEventDetail::PopState
+            | JsEventDetail::HashChange { .. } => None,
+            JsEventDetail::Mouse { modifiers, .. } | JsEventDetail::Keyboard { modifiers, .. } => {
+                Some(*modifiers)
+            }
+        }
+    }
+}
+
+#[js_class(name = "Event")]
+impl JsEvent {
+    #[js_method(getter, name = "type")]
+    fn event_type(&self) -> String {
+        self.event_type.clone()
+    }
+
+    #[js_method(getter)]
+    fn bubbles(&self) -> bool {
+        self.bubbles
+    }
+
+    #[js_method(getter)]
+    fn cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    #[js_method(getter)]
+    fn is_trusted(&self) -> bool {
+        self.is_trusted
+    }
+
+    #[js_method(getter)]
+    fn default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+
+    #[js_method(getter)]
+    fn event_phase(&self) -> u32 {
+        self.phase
+    }
+
+    #[js_method(getter)]
+    fn target(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        self.target
+            .map(|target| get_target_object(closure_context, target))
+    }
+
+    #[js_method(getter)]
+    fn current_target(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        self.current_target
+            .map(|target| get_target_object(closure_context, target))
+    }
+
+    /// Cancels the event, which only cancelable events can be
+    #[js_method]
+    fn prevent_default(&mut self) {
+        if self.cancelable {
+            self.default_prevented = true;
+        }
+    }
+
+    /// Stops the event from reaching the targets after the current one
+    #[js_method]
+    fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    /// Stops the event from reaching the listeners after the current one
+    #[js_method]
+    fn stop_immediate_propagation(&mut self) {
+        self.propagation_stopped = true;
+        self.immediate_propagation_stopped = true;
+    }
+
+    #[js_method(getter)]
+    fn client_x(&self) -> Option<f64> {
+        match &self.detail {
+            JsEventDetail::Mouse { client_x, .. } => Some(*client_x),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn client_y(&self) -> Option<f64> {
+        match &self.detail {
+            JsEventDetail::Mouse { client_y, .. } => Some(*client_y),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn button(&self) -> Option<u32> {
+        match &self.detail {
+            JsEventDetail::Mouse { button, .. } => Some(u32::from(*button)),
+            _ => None,
+        }
+    }
+
+    /// The key pressed, or the key of the storage item changed, which is `null` when the storage
+    /// was cleared
+    #[js_method(getter)]
+    fn key(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::Keyboard { key, .. } => Some(key.clone()),
+            JsEventDetail::Storage(change) => change.key.clone(),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn code(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::Keyboard { code, .. } => Some(code.clone()),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn repeat(&self) -> Option<bool> {
+        match &self.detail {
+            JsEventDetail::Keyboard { repeat, .. } => Some(*repeat),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn alt_key(&self) -> Option<bool> {
+        self.get_modifiers().map(|modifiers| modifiers.alt)
+    }
+
+    #[js_method(getter)]
+    fn ctrl_key(&self) -> Option<bool> {
+        self.get_modifiers().map(|modifiers| modifiers.control)
+    }
+
+    #[js_method(getter)]
+    fn meta_key(&self) -> Option<bool> {
+        self.get_modifiers().map(|modifiers| modifiers.meta)
+    }
+
+    #[js_method(getter)]
+    fn shift_key(&self) -> Option<bool> {
+        self.get_modifiers().map(|modifiers| modifiers.shift)
+    }
+
+    #[js_method(getter)]
+    fn old_value(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::Storage(change) => change.old_value.clone(),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn new_value(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::Storage(change) => change.new_value.clone(),
+            _ => None,
+        }
+    }
+
+    /// The URL of the document that changed the storage
+    #[js_method(getter)]
+    fn url(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::Storage(change) => Some(change.url.clone()),
+            _ => None,
+        }
+    }
+
+    /// The `localStorage` of the document the event is dispatched to, which is the storage changed
+    #[js_method(getter)]
+    fn storage_area(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        match &self.detail {
+            JsEventDetail::Storage(_) => get_local_storage_object(closure_context).ok(),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn length_computable(&self) -> Option<bool> {
+        match &self.detail {
+            JsEventDetail::Progress { total, .. } => Some(total.is_some()),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter)]
+    fn loaded(&self) -> Option<f64> {
+        match &self.detail {
+            JsEventDetail::Progress { loaded, .. } => Some(*loaded as f64),
+            _ => None,
+        }
+    }
+
+    /// The length of the body, which is 0 when it is not known
+    #[js_method(getter)]
+    fn total(&self) -> Option<f64> {
+        match &self.detail {
+            JsEventDetail::Progress { total, .. } => Some(total.unwrap_or_default() as f64),
+            _ => None,
+        }
+    }
+
+    /// The state of the entry of the session history moved to
+    #[js_method(getter)]
+    fn state(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        match &self.detail {
+            JsEventDetail::PopState => Some(get_history_state(closure_context)),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter, name = "oldURL")]
+    fn old_url(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::HashChange { old_url, .. } => Some(old_url.clone()),
+            _ => None,
+        }
+    }
+
+    #[js_method(getter, name = "newURL")]
+    fn new_url(&self) -> Option<String> {
+        match &self.detail {
+            JsEventDetail::HashChange { new_url, .. } => Some(new_url.clone()),
+            _ => None,
+        }
+    }
+}
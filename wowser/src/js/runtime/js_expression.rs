@@ -1,30 +1,139 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
 
 use super::{
-    get_member_from_prototype_chain, JsClosureContext, JsFunctionResult, JsValue, JsValueNode,
+    construct, evaluate_spreadable, get_member_from_prototype_chain,
+    get_or_create_constructor_prototype, get_own_enumerable_entries, import_module,
+    invoke_function, regexp_rc, run_eval, JsBigInt, JsClosureContext, JsFunctionResult, JsMethod,
+    JsNumeric, JsPattern, JsProperties, JsPropertyKey, JsValue, JsValueNode,
+    CONSTRUCTOR_PROTOTYPE_MEMBER, EVAL_REFERENCE,
 };
 
+/// The name of an object member or class method
+#[derive(Debug, PartialEq)]
+pub enum JsPropertyName {
+    Static(String),
+    /// Evaluated each time the member is defined
+    Computed(JsExpression),
+}
+
+impl JsPropertyName {
+    pub fn evaluate(
+        &self,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<JsPropertyKey, JsValueNode> {
+        match self {
+            Self::Static(name) => Ok(name.as_str().into()),
+            Self::Computed(expression) => Ok(JsPropertyKey::from(
+                expression.run(closure_context)?.get_ref(),
+            )),
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match self {
+            Self::Static(_) => vec![],
+            Self::Computed(expression) => expression.get_referenced_nodes(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsObjectMember {
+    Property(JsPropertyName, JsExpression),
+    /// Methods, getters, and setters
+    Method(JsMethod),
+    /// Copies the own enumerable properties of the value into the object
+    Spread(JsExpression),
+}
+
+/// The binary operators other than `+`, which also concatenates strings, and the equality
+/// operators
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsBinaryOperator {
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Exponent,
+    LeftShift,
+    SignedRightShift,
+    UnsignedRightShift,
+    BitwiseAnd,
+    BitwiseXor,
+    BitwiseOr,
+    LessThan,
+    GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+}
+
+impl JsBinaryOperator {
+    pub fn from_token(token: &str) -> Option<Self> {
+        Some(match token {
+            "-" => Self::Subtract,
+            "*" => Self::Multiply,
+            "/" => Self::Divide,
+            "%" => Self::Remainder,
+            "**" => Self::Exponent,
+            "<<" => Self::LeftShift,
+            ">>" => Self::SignedRightShift,
+            ">>>" => Self::UnsignedRightShift,
+            "&" => Self::BitwiseAnd,
+            "^" => Self::BitwiseXor,
+            "|" => Self::BitwiseOr,
+            "<" => Self::LessThan,
+            ">" => Self::GreaterThan,
+            "<=" => Self::LessThanOrEqual,
+            ">=" => Self::GreaterThanOrEqual,
+            _ => return None,
+        })
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum JsExpression {
     Boolean(bool),
     Number(f64),
+    BigInt(JsBigInt),
     String(String),
+    /// (Pattern, Flags)
+    RegExp(String, String),
     Undefined,
     Null,
-    Object(Vec<(String, JsExpression)>),
+    Object(Vec<JsObjectMember>),
+    /// Elements where `None` is a hole
+    Array(Vec<Option<JsExpression>>),
+    /// Only valid inside array literals, function arguments, and patterns
+    Spread(Box<JsExpression>),
     TripleEquals(bool, Box<JsExpression>, Box<JsExpression>),
     DoubleEquals(bool, Box<JsExpression>, Box<JsExpression>),
     Add(Box<JsExpression>, Box<JsExpression>),
-    Multiply(Box<JsExpression>, Box<JsExpression>),
+    Binary(JsBinaryOperator, Box<JsExpression>, Box<JsExpression>),
     Reference(String),
     CastToNumber(Box<JsExpression>),
+    Negate(Box<JsExpression>),
+    BitwiseNot(Box<JsExpression>),
     InvokeFunction(Box<JsExpression>, Vec<JsExpression>),
+    /// (Constructor, Arguments)
+    New(Box<JsExpression>, Vec<JsExpression>),
     AccessMember(Box<JsExpression>, String),
+    AccessComputedMember(Box<JsExpression>, Box<JsExpression>),
+    Assign(Box<JsPattern>, Box<JsExpression>),
     Condition(Box<JsExpression>, Box<JsExpression>, Box<JsExpression>),
+    /// Evaluates each expression in order, resulting in the last
+    Sequence(Vec<JsExpression>),
+    /// `import(specifier)`, which results in the namespace of the module
+    Import(Box<JsExpression>),
 }
 
 impl JsExpression {
+    /// Calls to a plain reference named `eval` run the intrinsic `eval` in the caller's scope
+    pub fn is_direct_eval_callee(&self) -> bool {
+        matches!(self, Self::Reference(name) if name == EVAL_REFERENCE)
+    }
+
     pub fn run(&self, closure_context: &mut JsClosureContext) -> JsFunctionResult {
+        closure_context.step()?;
         match self {
             Self::Reference(variable_name) => closure_context
                 .get_reference_mut(variable_name)
@@ -32,167 +141,116 @@ impl JsExpression {
                 .ok_or(JsValue::reference_error_rc(&closure_context.nodes_graph)),
             Self::Boolean(b) => Ok(JsValue::bool_rc(&closure_context.nodes_graph, *b)),
             Self::Number(num) => Ok(JsValue::number_rc(&closure_context.nodes_graph, *num)),
+            Self::BigInt(bigint) => Ok(JsValue::bigint_rc(
+                &closure_context.nodes_graph,
+                bigint.clone(),
+            )),
             Self::String(num) => Ok(JsValue::str_rc(&closure_context.nodes_graph, num)),
+            Self::RegExp(source, flags) => regexp_rc(&closure_context.nodes_graph, source, flags),
             Self::Undefined => Ok(JsValue::undefined_rc(&closure_context.nodes_graph)),
             Self::Null => Ok(JsValue::null_rc(&closure_context.nodes_graph)),
-            Self::Object(members) => {
-                let mut map = HashMap::with_capacity(members.len());
-                for (key, value) in members {
-                    map.insert(key.to_string(), value.run(closure_context)?);
-                }
-                Ok(JsValue::object_rc(&closure_context.nodes_graph, map))
+            Self::Object(members) => evaluate_object(members, closure_context),
+            Self::Array(elements) => {
+                let values =
+                    evaluate_spreadable(elements.iter().map(Option::as_ref), closure_context)?;
+                Ok(JsValue::array_rc(&closure_context.nodes_graph, values))
             }
+            Self::Spread(_) => Err(JsValue::syntax_error_rc(&closure_context.nodes_graph)),
             Self::TripleEquals(match_equality, a, b) => {
                 let a_value = a.run(closure_context)?;
                 let b_value = b.run(closure_context)?;
-                let result = match (a_value.get_ref(), b_value.get_ref()) {
-                    (JsValue::Boolean(a), JsValue::Boolean(b)) => a == b,
-                    (JsValue::Number(a), JsValue::Number(b)) => a == b,
-                    (JsValue::String(a), JsValue::String(b)) => a == b,
-                    (JsValue::Function(a), JsValue::Function(b)) => a == b,
-                    (JsValue::Null, JsValue::Null) => true,
-                    (JsValue::Undefined, JsValue::Undefined) => true,
-                    (JsValue::Object(_), JsValue::Object(_)) => a_value.is_same_ref(&b_value),
-                    (_, _) => false,
-                };
                 Ok(JsValue::bool_rc(
                     &closure_context.nodes_graph,
-                    *match_equality == result,
+                    *match_equality == is_strictly_equal(&a_value, &b_value),
                 ))
             }
             Self::DoubleEquals(match_equality, a, b) => {
                 let a_value = a.run(closure_context)?;
                 let b_value = b.run(closure_context)?;
-                let result = match (a_value.get_ref(), b_value.get_ref()) {
-                    (JsValue::String(a), JsValue::String(b)) => a == b,
-                    (
-                        a @ JsValue::Boolean(_) | a @ JsValue::Number(_) | a @ JsValue::String(_),
-                        b @ JsValue::Boolean(_) | b @ JsValue::Number(_) | b @ JsValue::String(_),
-                    ) => f64::from(a) == f64::from(b),
-                    (
-                        JsValue::Null | JsValue::Undefined,
-                        JsValue::Boolean(_)
-                        | JsValue::Number(_)
-                        | JsValue::String(_)
-                        | JsValue::Function(_),
-                    ) => false,
-                    (
-                        JsValue::Boolean(_)
-                        | JsValue::Number(_)
-                        | JsValue::String(_)
-                        | JsValue::Function(_),
-                        JsValue::Null | JsValue::Undefined,
-                    ) => false,
-                    (JsValue::Null | JsValue::Undefined, JsValue::Null | JsValue::Undefined) => {
-                        true
-                    }
-                    (JsValue::Boolean(_) | JsValue::Number(_), JsValue::Function(_)) => false,
-                    (JsValue::Function(_), JsValue::Boolean(_) | JsValue::Number(_)) => false,
-                    (a @ JsValue::String(_), b @ JsValue::Function(_)) => {
-                        a.to_string() == b.to_string()
-                    }
-                    (a @ JsValue::Function(_), b @ JsValue::String(_)) => {
-                        a.to_string() == b.to_string()
-                    }
-                    (JsValue::Object(_), JsValue::Object(_)) => a_value.is_same_ref(&b_value),
-                    (JsValue::Object(_), _) => false,
-                    (_, JsValue::Object(_)) => false,
-                    (JsValue::Function(a), JsValue::Function(b)) => a == b,
-                };
                 Ok(JsValue::bool_rc(
                     &closure_context.nodes_graph,
-                    *match_equality == result,
+                    *match_equality == is_loosely_equal(&a_value, &b_value),
                 ))
             }
             Self::Add(a, b) => {
                 let a_value = a.run(closure_context)?;
                 let b_value = b.run(closure_context)?;
-                match (a_value.get_ref(), b_value.get_ref()) {
-                    (
-                        a @ JsValue::Number(_)
-                        | a @ JsValue::Boolean(_)
-                        | a @ JsValue::Undefined
-                        | a @ JsValue::Null,
-                        b @ JsValue::Number(_)
-                        | b @ JsValue::Boolean(_)
-                        | b @ JsValue::Undefined
-                        | b @ JsValue::Null,
-                    ) => Ok(JsValue::number_rc(
-                        &closure_context.nodes_graph,
-                        f64::from(a) + f64::from(b),
-                    )),
-                    (a @ JsValue::Object(_), b) => Ok(JsValue::string_rc(
-                        &closure_context.nodes_graph,
-                        a.to_string() + &b.to_string(),
-                    )),
-                    (a, b @ JsValue::Object(_)) => Ok(JsValue::string_rc(
-                        &closure_context.nodes_graph,
-                        a.to_string() + &b.to_string(),
-                    )),
-                    (a @ JsValue::String(_) | a @ JsValue::Function(_), b) => {
-                        Ok(JsValue::string_rc(
-                            &closure_context.nodes_graph,
-                            a.to_string() + &b.to_string(),
-                        ))
-                    }
-                    (a, b @ JsValue::String(_) | b @ JsValue::Function(_)) => {
-                        Ok(JsValue::string_rc(
-                            &closure_context.nodes_graph,
-                            a.to_string() + &b.to_string(),
-                        ))
-                    }
-                }
+                add(&a_value, &b_value, closure_context)
             }
-            Self::Multiply(a, b) => {
+            Self::Binary(operator, a, b) => {
                 let a_value = a.run(closure_context)?;
                 let b_value = b.run(closure_context)?;
-                Ok(JsValue::number_rc(
-                    &closure_context.nodes_graph,
-                    f64::from(a_value.get_ref()) * f64::from(b_value.get_ref()),
-                ))
+                apply_binary_operator(*operator, &a_value, &b_value, closure_context)
             }
             Self::CastToNumber(expression) => {
                 let value = expression.run(closure_context)?;
-                Ok(JsValue::number_rc(
-                    &closure_context.nodes_graph,
-                    f64::from(value.get_ref()),
-                ))
+                let number = to_number(&value, closure_context)?;
+                Ok(JsValue::number_rc(&closure_context.nodes_graph, number))
+            }
+            Self::Negate(expression) => {
+                let value = expression.run(closure_context)?;
+                negate(&value, closure_context)
+            }
+            Self::BitwiseNot(expression) => {
+                let value = expression.run(closure_context)?;
+                bitwise_not(&value, closure_context)
             }
             Self::InvokeFunction(reference_to_invoke, arg_expressions) => {
-                let (this_value, value) =
-                    if let JsExpression::AccessMember(base, name) = reference_to_invoke.as_ref() {
+                let (this_value, value) = match reference_to_invoke.as_ref() {
+                    JsExpression::AccessMember(base, name) => {
                         let this_value = base.run(closure_context)?;
                         let value = get_member_from_prototype_chain(
-                            this_value.get_ref(),
-                            name,
+                            &this_value,
+                            &name.as_str().into(),
                             closure_context,
-                        );
+                        )?;
                         (this_value, value)
-                    } else {
-                        (
-                            JsValue::undefined_rc(&closure_context.nodes_graph),
-                            reference_to_invoke.run(closure_context)?,
-                        )
-                    };
-
-                match value.get_ref() {
-                    JsValue::Function(function) => {
-                        let mut evaluated_args: Vec<_> = Vec::with_capacity(arg_expressions.len());
-                        for expression in arg_expressions {
-                            evaluated_args.push(expression.run(closure_context)?)
-                        }
-                        function.run(closure_context, this_value, &evaluated_args)
                     }
-                    _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+                    JsExpression::AccessComputedMember(base, member) => {
+                        let this_value = base.run(closure_context)?;
+                        let name = JsPropertyKey::from(member.run(closure_context)?.get_ref());
+                        let value =
+                            get_member_from_prototype_chain(&this_value, &name, closure_context)?;
+                        (this_value, value)
+                    }
+                    _ => (
+                        JsValue::undefined_rc(&closure_context.nodes_graph),
+                        reference_to_invoke.run(closure_context)?,
+                    ),
+                };
+
+                if !value.get_ref().is_callable() {
+                    return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+                }
+                let evaluated_args =
+                    evaluate_spreadable(arg_expressions.iter().map(Some), closure_context)?;
+                if reference_to_invoke.is_direct_eval_callee()
+                    && value.is_same_ref(&closure_context.global_prototypes.eval)
+                {
+                    return run_eval(closure_context, &evaluated_args);
                 }
+                invoke_function(closure_context, &value, this_value, &evaluated_args)
+            }
+            Self::New(constructor, arg_expressions) => {
+                let constructor = constructor.run(closure_context)?;
+                let evaluated_args =
+                    evaluate_spreadable(arg_expressions.iter().map(Some), closure_context)?;
+                construct(closure_context, &constructor, &evaluated_args)
             }
             Self::AccessMember(reference, member_name) => {
                 let base_value = reference.run(closure_context)?;
-                Ok(get_member_from_prototype_chain(
-                    base_value.get_ref(),
-                    member_name,
-                    closure_context,
-                ))
+                access_member(&base_value, &member_name.as_str().into(), closure_context)
+            }
+            Self::AccessComputedMember(reference, member_expression) => {
+                let base_value = reference.run(closure_context)?;
+                let member_name =
+                    JsPropertyKey::from(member_expression.run(closure_context)?.get_ref());
+                access_member(&base_value, &member_name, closure_context)
+            }
+            Self::Assign(pattern, expression) => {
+                let value = expression.run(closure_context)?;
+                pattern.bind(closure_context, value.clone(), None)?;
+                Ok(value)
             }
             Self::Condition(conditional_expression, true_expression, false_expression) => {
                 let condition_result = conditional_expression.run(closure_context)?;
@@ -204,6 +262,17 @@ impl JsExpression {
                     false_expression.run(closure_context)
                 }
             }
+            Self::Sequence(expressions) => {
+                let mut result = JsValue::undefined_rc(&closure_context.nodes_graph);
+                for expression in expressions {
+                    result = expression.run(closure_context)?;
+                }
+                Ok(result)
+            }
+            Self::Import(specifier) => {
+                let specifier = specifier.run(closure_context)?;
+                import_module(closure_context, &specifier)
+            }
         }
     }
 
@@ -211,13 +280,29 @@ impl JsExpression {
         match self {
             Self::Boolean(_) => vec![],
             Self::Number(_) => vec![],
+            Self::BigInt(_) => vec![],
             Self::String(_) => vec![],
+            Self::RegExp(_, _) => vec![],
             Self::Undefined => vec![],
             Self::Null => vec![],
-            Self::Object(entries) => entries
+            Self::Object(members) => members
                 .iter()
-                .flat_map(|(_name, expression)| expression.get_referenced_nodes())
+                .flat_map(|member| match member {
+                    JsObjectMember::Property(name, expression) => [
+                        name.get_referenced_nodes(),
+                        expression.get_referenced_nodes(),
+                    ]
+                    .concat(),
+                    JsObjectMember::Method(method) => method.get_referenced_nodes(),
+                    JsObjectMember::Spread(expression) => expression.get_referenced_nodes(),
+                })
                 .collect(),
+            Self::Array(elements) => elements
+                .iter()
+                .flatten()
+                .flat_map(|expression| expression.get_referenced_nodes())
+                .collect(),
+            Self::Spread(expression) => expression.get_referenced_nodes(),
             Self::TripleEquals(_, a, b) => [a, b]
                 .iter()
                 .flat_map(|expression| expression.get_referenced_nodes())
@@ -230,12 +315,14 @@ impl JsExpression {
                 .iter()
                 .flat_map(|expression| expression.get_referenced_nodes())
                 .collect(),
-            Self::Multiply(a, b) => [a, b]
+            Self::Binary(_, a, b) => [a, b]
                 .iter()
                 .flat_map(|expression| expression.get_referenced_nodes())
                 .collect(),
             Self::Reference(_) => vec![],
             Self::CastToNumber(expression) => expression.get_referenced_nodes(),
+            Self::Negate(expression) => expression.get_referenced_nodes(),
+            Self::BitwiseNot(expression) => expression.get_referenced_nodes(),
             Self::InvokeFunction(this_expression, args_expressions) => [
                 this_expression.get_referenced_nodes(),
                 args_expressions
@@ -244,11 +331,371 @@ impl JsExpression {
                     .collect(),
             ]
             .concat(),
+            Self::New(constructor, args_expressions) => [
+                constructor.get_referenced_nodes(),
+                args_expressions
+                    .iter()
+                    .flat_map(|expression| expression.get_referenced_nodes())
+                    .collect(),
+            ]
+            .concat(),
             Self::AccessMember(expression, _) => expression.get_referenced_nodes(),
+            Self::AccessComputedMember(a, b) => [a, b]
+                .iter()
+                .flat_map(|expression| expression.get_referenced_nodes())
+                .collect(),
+            Self::Assign(pattern, expression) => [
+                pattern.get_referenced_nodes(),
+                expression.get_referenced_nodes(),
+            ]
+            .concat(),
             Self::Condition(a, b, c) => [a, b, c]
                 .iter()
                 .flat_map(|expression| expression.get_referenced_nodes())
                 .collect(),
+            Self::Sequence(expressions) => expressions
+                .iter()
+                .flat_map(|expression| expression.get_referenced_nodes())
+                .collect(),
+            Self::Import(specifier) => specifier.get_referenced_nodes(),
+        }
+    }
+}
+
+/// Compares values as `===` does, where objects are equal only to themselves and `NaN` is not
+/// equal to anything
+pub fn is_strictly_equal(a: &JsValueNode, b: &JsValueNode) -> bool {
+    match (a.get_ref(), b.get_ref()) {
+        (JsValue::Boolean(a), JsValue::Boolean(b)) => a == b,
+        (JsValue::Number(a), JsValue::Number(b)) => a == b,
+        (JsValue::BigInt(a), JsValue::BigInt(b)) => a == b,
+        (JsValue::String(a), JsValue::String(b)) => a == b,
+        (JsValue::Symbol(a), JsValue::Symbol(b)) => a == b,
+        (JsValue::Null, JsValue::Null) => true,
+        (JsValue::Undefined, JsValue::Undefined) => true,
+        (a_value, b_value) if a_value.is_object() && b_value.is_object() => a.is_same_ref(b),
+        (_, _) => false,
+    }
+}
+
+/// Compares values as `==` does, converting primitives of different types to numbers. BigInts
+/// are compared with numbers by their mathematical values.
+pub fn is_loosely_equal(a: &JsValueNode, b: &JsValueNode) -> bool {
+    match (a.get_ref(), b.get_ref()) {
+        (JsValue::String(a_value), JsValue::String(b_value)) => a_value == b_value,
+        (JsValue::Symbol(a_value), JsValue::Symbol(b_value)) => a_value == b_value,
+        (JsValue::Symbol(_), _) | (_, JsValue::Symbol(_)) => false,
+        (JsValue::BigInt(bigint), other) | (other, JsValue::BigInt(bigint)) => match other {
+            JsValue::BigInt(other) => bigint == other,
+            JsValue::Boolean(_) | JsValue::Number(_) => {
+                bigint.compare_f64(f64::from(other)) == Some(Ordering::Equal)
+            }
+            JsValue::String(string) => JsBigInt::parse(string).as_ref() == Some(bigint),
+            _ => false,
+        },
+        (
+            a_value @ JsValue::Boolean(_)
+            | a_value @ JsValue::Number(_)
+            | a_value @ JsValue::String(_),
+            b_value @ JsValue::Boolean(_)
+            | b_value @ JsValue::Number(_)
+            | b_value @ JsValue::String(_),
+        ) => f64::from(a_value) == f64::from(b_value),
+        (
+            JsValue::Null | JsValue::Undefined,
+            JsValue::Boolean(_) | JsValue::Number(_) | JsValue::String(_) | JsValue::Function(_, _),
+        ) => false,
+        (
+            JsValue::Boolean(_) | JsValue::Number(_) | JsValue::String(_) | JsValue::Function(_, _),
+            JsValue::Null | JsValue::Undefined,
+        ) => false,
+        (JsValue::Null | JsValue::Undefined, JsValue::Null | JsValue::Undefined) => true,
+        (JsValue::Boolean(_) | JsValue::Number(_), JsValue::Function(_, _)) => false,
+        (JsValue::Function(_, _), JsValue::Boolean(_) | JsValue::Number(_)) => false,
+        (a_value @ JsValue::String(_), b_value @ JsValue::Function(_, _)) => {
+            a_value.to_string() == b_value.to_string()
+        }
+        (a_value @ JsValue::Function(_, _), b_value @ JsValue::String(_)) => {
+            a_value.to_string() == b_value.to_string()
+        }
+        (a_value, b_value) if a_value.is_object() && b_value.is_object() => a.is_same_ref(b),
+        (
+            JsValue::Object(_)
+            | JsValue::Array(_, _)
+            | JsValue::Collection(_)
+            | JsValue::Iterator(_)
+            | JsValue::RegExp(_)
+            | JsValue::Date(_)
+            | JsValue::ArrayBuffer(_)
+            | JsValue::TypedArray(_)
+            | JsValue::DataView(_)
+            | JsValue::HostObject(_)
+            | JsValue::Proxy(_),
+            _,
+        ) => false,
+        (
+            _,
+            JsValue::Object(_)
+            | JsValue::Array(_, _)
+            | JsValue::Collection(_)
+            | JsValue::Iterator(_)
+            | JsValue::RegExp(_)
+            | JsValue::Date(_)
+            | JsValue::ArrayBuffer(_)
+            | JsValue::TypedArray(_)
+            | JsValue::DataView(_)
+            | JsValue::HostObject(_)
+            | JsValue::Proxy(_),
+        ) => false,
+        (JsValue::Function(_, _), JsValue::Function(_, _)) => a.is_same_ref(b),
+    }
+}
+
+/// Adds numbers or BigInts, or concatenates the values as strings when either is not a number,
+/// BigInt, boolean, `null`, or `undefined`. Adding a BigInt to any of the others throws a
+/// `TypeError`.
+pub fn add(
+    a: &JsValueNode,
+    b: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> JsFunctionResult {
+    match (a.get_ref(), b.get_ref()) {
+        (JsValue::Symbol(_), _) | (_, JsValue::Symbol(_)) => {
+            Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+        }
+        (JsValue::BigInt(a_value), JsValue::BigInt(b_value)) => Ok(JsValue::bigint_rc(
+            &closure_context.nodes_graph,
+            a_value.add(b_value),
+        )),
+        (
+            JsValue::BigInt(_),
+            JsValue::Number(_) | JsValue::Boolean(_) | JsValue::Undefined | JsValue::Null,
+        )
+        | (
+            JsValue::Number(_) | JsValue::Boolean(_) | JsValue::Undefined | JsValue::Null,
+            JsValue::BigInt(_),
+        ) => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        (
+            a_value @ JsValue::Number(_)
+            | a_value @ JsValue::Boolean(_)
+            | a_value @ JsValue::Undefined
+            | a_value @ JsValue::Null,
+            b_value @ JsValue::Number(_)
+            | b_value @ JsValue::Boolean(_)
+            | b_value @ JsValue::Undefined
+            | b_value @ JsValue::Null,
+        ) => Ok(JsValue::number_rc(
+            &closure_context.nodes_graph,
+            f64::from(a_value) + f64::from(b_value),
+        )),
+        (a_value, b_value) => Ok(JsValue::string_rc(
+            &closure_context.nodes_graph,
+            a_value.to_string() + &b_value.to_string(),
+        )),
+    }
+}
+
+/// Builds an object literal. Members are defined in order, so later members replace earlier ones.
+fn evaluate_object(
+    members: &[JsObjectMember],
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    let mut map = JsProperties::new();
+    for member in members {
+        match member {
+            JsObjectMember::Property(name, value) => {
+                let key = name.evaluate(closure_context)?;
+                map.insert(key, value.run(closure_context)?);
+            }
+            JsObjectMember::Method(method) => {
+                let (key, descriptor) = method.to_property(true, closure_context)?;
+                map.define(&closure_context.nodes_graph, key, descriptor);
+            }
+            JsObjectMember::Spread(expression) => {
+                let value = expression.run(closure_context)?;
+                for (key, value) in get_own_enumerable_entries(&value, closure_context)? {
+                    map.insert(key, value);
+                }
+            }
         }
     }
+    Ok(JsValue::object_rc(&closure_context.nodes_graph, map))
+}
+
+/// Gets a member of a value, creating the `prototype` of user defined functions on first access
+pub fn access_member(
+    base: &JsValueNode,
+    member: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    if member.as_str() == Some(CONSTRUCTOR_PROTOTYPE_MEMBER) {
+        if let Some(prototype) = get_or_create_constructor_prototype(closure_context, base) {
+            return Ok(prototype);
+        }
+    }
+    get_member_from_prototype_chain(base, member, closure_context)
+}
+
+/// Converts a value to a number, throwing a `TypeError` for symbols and BigInts which cannot be
+/// converted
+pub fn to_number(
+    value: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<f64, JsValueNode> {
+    match value.get_ref() {
+        JsValue::Symbol(_) | JsValue::BigInt(_) => {
+            Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+        }
+        value => Ok(f64::from(value)),
+    }
+}
+
+/// Converts a value to a number, unless it is a BigInt, throwing a `TypeError` for symbols
+pub fn to_numeric(
+    value: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<JsNumeric, JsValueNode> {
+    match value.get_ref() {
+        JsValue::BigInt(bigint) => Ok(JsNumeric::BigInt(bigint.clone())),
+        _ => Ok(JsNumeric::Number(to_number(value, closure_context)?)),
+    }
+}
+
+/// Wraps a number to a signed 32 bit integer, as bitwise operators do. `NaN` and infinities are
+/// zero.
+pub fn to_int32(number: f64) -> i32 {
+    to_uint32(number) as i32
+}
+
+/// Wraps a number to an unsigned 32 bit integer. `NaN` and infinities are zero.
+pub fn to_uint32(number: f64) -> u32 {
+    if number.is_finite() {
+        number.trunc().rem_euclid(2f64.powi(32)) as u32
+    } else {
+        0
+    }
+}
+
+/// Applies a binary operator. Relational operators compare two strings by their UTF-16 code units
+/// and anything else as numerics. The other operators convert their operands to numerics, which
+/// throws a `TypeError` unless both are numbers or both are BigInts.
+pub fn apply_binary_operator(
+    operator: JsBinaryOperator,
+    a: &JsValueNode,
+    b: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> JsFunctionResult {
+    let nodes_graph = &closure_context.nodes_graph;
+    let relation = match operator {
+        JsBinaryOperator::LessThan => Some(compare(a, b, closure_context)? == Some(Ordering::Less)),
+        JsBinaryOperator::GreaterThan => {
+            Some(compare(a, b, closure_context)? == Some(Ordering::Greater))
+        }
+        JsBinaryOperator::LessThanOrEqual => Some(matches!(
+            compare(a, b, closure_context)?,
+            Some(Ordering::Less | Ordering::Equal)
+        )),
+        JsBinaryOperator::GreaterThanOrEqual => Some(matches!(
+            compare(a, b, closure_context)?,
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+        _ => None,
+    };
+    if let Some(relation) = relation {
+        return Ok(JsValue::bool_rc(nodes_graph, relation));
+    }
+
+    let result = match (
+        to_numeric(a, closure_context)?,
+        to_numeric(b, closure_context)?,
+    ) {
+        (JsNumeric::Number(a), JsNumeric::Number(b)) => {
+            JsNumeric::Number(apply_number_operator(operator, a, b))
+        }
+        (JsNumeric::BigInt(a), JsNumeric::BigInt(b)) => {
+            let result = match operator {
+                JsBinaryOperator::Subtract => Some(a.sub(&b)),
+                JsBinaryOperator::Multiply => a.mul(&b),
+                JsBinaryOperator::Divide => a.div(&b),
+                JsBinaryOperator::Remainder => a.rem(&b),
+                JsBinaryOperator::Exponent => a.pow(&b),
+                JsBinaryOperator::LeftShift => a.shl(&b),
+                JsBinaryOperator::SignedRightShift => a.shr(&b),
+                JsBinaryOperator::BitwiseAnd => Some(a.bitand(&b)),
+                JsBinaryOperator::BitwiseXor => Some(a.bitxor(&b)),
+                JsBinaryOperator::BitwiseOr => Some(a.bitor(&b)),
+                // BigInts have no unsigned representation to shift
+                _ => return Err(JsValue::type_error_rc(nodes_graph)),
+            };
+            // Dividing by zero, negative exponents, and results that are too large
+            JsNumeric::BigInt(result.ok_or_else(|| JsValue::range_error_rc(nodes_graph))?)
+        }
+        _ => return Err(JsValue::type_error_rc(nodes_graph)),
+    };
+    Ok(result.to_value_rc(nodes_graph))
+}
+
+fn apply_number_operator(operator: JsBinaryOperator, a: f64, b: f64) -> f64 {
+    match operator {
+        JsBinaryOperator::Subtract => a - b,
+        JsBinaryOperator::Multiply => a * b,
+        JsBinaryOperator::Divide => a / b,
+        JsBinaryOperator::Remainder => a % b,
+        // Unlike `powf`, one to the power of an infinity or `NaN` is `NaN`
+        JsBinaryOperator::Exponent if b.is_nan() || (a.abs() == 1.0 && b.is_infinite()) => f64::NAN,
+        JsBinaryOperator::Exponent => a.powf(b),
+        // Shifts only use the low 5 bits of the amount, as the wrapping shifts do
+        JsBinaryOperator::LeftShift => to_int32(a).wrapping_shl(to_uint32(b)).into(),
+        JsBinaryOperator::SignedRightShift => to_int32(a).wrapping_shr(to_uint32(b)).into(),
+        JsBinaryOperator::UnsignedRightShift => to_uint32(a).wrapping_shr(to_uint32(b)).into(),
+        JsBinaryOperator::BitwiseAnd => (to_int32(a) & to_int32(b)).into(),
+        JsBinaryOperator::BitwiseXor => (to_int32(a) ^ to_int32(b)).into(),
+        JsBinaryOperator::BitwiseOr => (to_int32(a) | to_int32(b)).into(),
+        JsBinaryOperator::LessThan
+        | JsBinaryOperator::GreaterThan
+        | JsBinaryOperator::LessThanOrEqual
+        | JsBinaryOperator::GreaterThanOrEqual => f64::NAN,
+    }
+}
+
+/// Orders values as the relational operators do, or `None` when they are unordered, as `NaN` and
+/// strings that are not integers compared with BigInts are
+fn compare(
+    a: &JsValueNode,
+    b: &JsValueNode,
+    closure_context: &JsClosureContext,
+) -> Result<Option<Ordering>, JsValueNode> {
+    Ok(match (a.get_ref(), b.get_ref()) {
+        (JsValue::String(a), JsValue::String(b)) => Some(a.encode_utf16().cmp(b.encode_utf16())),
+        (JsValue::BigInt(a), JsValue::String(b)) => JsBigInt::parse(b).map(|b| a.cmp(&b)),
+        (JsValue::String(a), JsValue::BigInt(b)) => JsBigInt::parse(a).map(|a| a.cmp(b)),
+        _ => match (
+            to_numeric(a, closure_context)?,
+            to_numeric(b, closure_context)?,
+        ) {
+            (JsNumeric::Number(a), JsNumeric::Number(b)) => a.partial_cmp(&b),
+            (JsNumeric::BigInt(a), JsNumeric::BigInt(b)) => Some(a.cmp(&b)),
+            (JsNumeric::BigInt(a), JsNumeric::Number(b)) => a.compare_f64(b),
+            (JsNumeric::Number(a), JsNumeric::BigInt(b)) => b.compare_f64(a).map(Ordering::reverse),
+        },
+    })
+}
+
+/// Unary `-`, which negates numbers and BigInts
+pub fn negate(value: &JsValueNode, closure_context: &JsClosureContext) -> JsFunctionResult {
+    let result = match to_numeric(value, closure_context)? {
+        JsNumeric::Number(number) => JsNumeric::Number(-number),
+        JsNumeric::BigInt(bigint) => JsNumeric::BigInt(bigint.neg()),
+    };
+    Ok(result.to_value_rc(&closure_context.nodes_graph))
+}
+
+/// Unary `~`, which inverts the bits of numbers as 32 bit integers and of BigInts in two's
+/// complement
+pub fn bitwise_not(value: &JsValueNode, closure_context: &JsClosureContext) -> JsFunctionResult {
+    let result = match to_numeric(value, closure_context)? {
+        JsNumeric::Number(number) => JsNumeric::Number((!to_int32(number)).into()),
+        JsNumeric::BigInt(bigint) => JsNumeric::BigInt(bigint.not()),
+    };
+    Ok(result.to_value_rc(&closure_context.nodes_graph))
 }
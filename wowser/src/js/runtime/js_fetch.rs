@@ -0,0 +1,859 @@
+use futures::executor::block_on;
+use wowser_macros::{js_class, js_method};
+
+use crate::{
+    net::{HttpHeaderMap, HttpRequest, HttpResponse, HttpVerb, Url, UrlProtocol, NETWORK_EXECUTOR},
+    util::{parse_json, ExecutionError, Executor, JsonNumber, JsonValue, TaskToken},
+};
+
+use super::{
+    create_promise, create_settled_promise, finish_xml_http_request, get_argument, get_header_map,
+    get_member_from_prototype_chain, reject_promise, resolve_promise, with_host_object,
+    FromJsValue, JsClosureContext, JsFunctionResult, JsHeaders, JsHeadersGuard, JsHostReferences,
+    JsProperties, JsValue, JsValueNode,
+};
+
+/// Response headers scripts can read from cross-origin responses, along with the headers the
+/// server exposes with `Access-Control-Expose-Headers`
+/// <https://fetch.spec.whatwg.org/#cors-safelisted-response-header-name>
+const CORS_SAFELISTED_RESPONSE_HEADERS: [&str; 7] = [
+    "cache-control",
+    "content-language",
+    "content-length",
+    "content-type",
+    "expires",
+    "last-modified",
+    "pragma",
+];
+
+/// The requests scripts made with `fetch` and `XMLHttpRequest` that are waiting on the network,
+/// along with the URL of the document they are made from
+#[derive(Debug, Default)]
+pub struct JsFetches {
+    /// Relative URLs are resolved against the URL, and requests to other origins than its origin
+    /// are checked with CORS. Documents without a URL have an opaque origin.
+    pub document_url: Option<Url>,
+    pending: Vec<JsPendingFetch>,
+}
+
+impl JsFetches {
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.pending
+            .iter()
+            .map(|fetch| match &fetch.target {
+                JsFetchTarget::Promise(promise) => promise.clone(),
+                JsFetchTarget::XmlHttpRequest(request, _generation) => request.clone(),
+            })
+            .collect()
+    }
+
+    /// Whether any requests are waiting on the network
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// The origin requests are made from, which is `null` when it is opaque
+    fn get_origin(&self) -> String {
+        self.document_url
+            .as_ref()
+            .and_then(Url::origin)
+            .unwrap_or_else(|| "null".to_string())
+    }
+}
+
+/// What is told once a request sent on the network finishes
+#[derive(Debug)]
+pub enum JsFetchTarget {
+    /// The promise `fetch` returned, which settles with the response
+    Promise(JsValueNode),
+    /// An `XMLHttpRequest`, along with the number of the request it sent. See
+    /// [finish_xml_http_request].
+    XmlHttpRequest(JsValueNode, u64),
+}
+
+/// A request sent on the network
+#[derive(Debug)]
+struct JsPendingFetch {
+    target: JsFetchTarget,
+    request: JsFetchRequest,
+    /// Whether the request sent is the preflight request asking the server whether the request
+    /// can be sent, after which the request itself is sent
+    preflight: bool,
+    token: TaskToken,
+}
+
+/// What is sent for a request on the network
+#[derive(Debug)]
+pub struct JsFetchRequest {
+    pub url: Url,
+    pub method: HttpVerb,
+    pub headers: HttpHeaderMap,
+    pub body: Vec<u8>,
+    /// The origin of the document for requests to other origins, which are checked with CORS
+    cors_origin: Option<String>,
+}
+
+impl JsFetchRequest {
+    /// A request made by the document, which is checked with CORS when it is to another origin
+    pub fn new(
+        fetches: &JsFetches,
+        url: Url,
+        method: HttpVerb,
+        headers: HttpHeaderMap,
+        body: Vec<u8>,
+    ) -> Self {
+        let origin = fetches.get_origin();
+        let cors_origin = (url.origin().as_ref() != Some(&origin)).then_some(origin);
+        Self {
+            url,
+            method,
+            headers,
+            body,
+            cors_origin,
+        }
+    }
+
+    /// Whether the server must be asked whether the request can be sent before sending it
+    fn needs_preflight(&self) -> bool {
+        self.cors_origin.is_some() && !is_simple_request(self)
+    }
+
+    /// Builds the request, or the preflight request asking whether it can be sent, along with its
+    /// method. Requests to other origins say which origin they are from.
+    /// <https://fetch.spec.whatwg.org/#cors-preflight-fetch>
+    fn build(&self, preflight: bool) -> (HttpRequest, HttpVerb) {
+        let mut http_request = HttpRequest::new(self.url.clone());
+        if let Some(origin) = &self.cors_origin {
+            http_request = http_request.with_header("origin", origin);
+        }
+        if preflight {
+            let mut unsafe_headers = get_unsafe_header_names(&self.headers);
+            unsafe_headers.sort();
+            unsafe_headers.dedup();
+            http_request =
+                http_request.with_header("access-control-request-method", self.method.as_str());
+            if !unsafe_headers.is_empty() {
+                http_request = http_request
+                    .with_header("access-control-request-headers", &unsafe_headers.join(","));
+            }
+            return (http_request, HttpVerb::Options);
+        }
+        for header in self.headers.iter() {
+            http_request = http_request.with_header(&header.name, &header.value);
+        }
+        (http_request.with_body(self.body.clone()), self.method)
+    }
+
+    /// Sends the request, or its preflight request, on the network
+    fn send(&self, preflight: bool) -> Result<TaskToken, ExecutionError> {
+        let (http_request, verb) = self.build(preflight);
+        let mut executor = NETWORK_EXECUTOR
+            .lock()
+            .map_err(|err| ExecutionError(err.to_string()))?;
+        executor.run(http_request.send(verb))
+    }
+
+    /// Sends the request, along with its preflight request when it needs one, waiting for the
+    /// response. Results in `None` when the request fails or is not allowed with CORS.
+    pub fn send_blocking(&self) -> Option<HttpResponse> {
+        if self.needs_preflight() {
+            let (http_request, verb) = self.build(true);
+            let response = block_on(http_request.send(verb)).ok()?;
+            if !self.is_preflight_allowed(&response) {
+                return None;
+            }
+        }
+        let (http_request, verb) = self.build(false);
+        let response = block_on(http_request.send(verb)).ok()?;
+        self.receive(response)
+    }
+
+    /// Checks the response to a request to another origin allows the document to read it, keeping
+    /// only the safelisted headers and the headers the server exposes. Results in `None` when the
+    /// response is not allowed.
+    /// <https://fetch.spec.whatwg.org/#concept-filtered-response-cors>
+    fn receive(&self, response: HttpResponse) -> Option<HttpResponse> {
+        let Some(origin) = &self.cors_origin else {
+            return Some(response);
+        };
+        if !is_origin_allowed(origin, &response.headers) {
+            return None;
+        }
+        let mut headers = response.headers;
+        let exposed = headers
+            .get_combined("access-control-expose-headers")
+            .unwrap_or_default();
+        let exposed = parse_header_list(&exposed);
+        let expose_all = exposed.iter().any(|name| name == "*");
+        let hidden: Vec<String> = headers
+            .iter()
+            .map(|header| header.name.clone())
+            .filter(|name| {
+                !expose_all
+                    && !CORS_SAFELISTED_RESPONSE_HEADERS.contains(&name.as_str())
+                    && !exposed.contains(name)
+            })
+            .collect();
+        for name in hidden {
+            headers.remove(&name);
+        }
+        Some(HttpResponse {
+            headers,
+            ..response
+        })
+    }
+
+    /// Whether the response to the preflight request allows the document to send the request with
+    /// its method and headers
+    fn is_preflight_allowed(&self, response: &HttpResponse) -> bool {
+        let Some(origin) = &self.cors_origin else {
+            return true;
+        };
+        let headers = &response.headers;
+        let list = |name: &str| parse_header_list(&headers.get_combined(name).unwrap_or_default());
+        let methods = list("access-control-allow-methods");
+        let allowed_headers = list("access-control-allow-headers");
+        let method_allowed = matches!(self.method, HttpVerb::Get | HttpVerb::Head | HttpVerb::Post)
+            || methods
+                .iter()
+                .any(|method| method == "*" || method.eq_ignore_ascii_case(self.method.as_str()));
+        let headers_allowed = get_unsafe_header_names(&self.headers).iter().all(|name| {
+            allowed_headers
+                .iter()
+                .any(|allowed| allowed == "*" || allowed == name)
+        });
+        (200..=299).contains(&response.status.status_code)
+            && is_origin_allowed(origin, headers)
+            && method_allowed
+            && headers_allowed
+    }
+}
+
+/// The body of a request or response, which can only be read once
+#[derive(Debug, Default)]
+struct JsBody {
+    /// `None` for requests and responses without a body, which read as empty
+    bytes: Option<Vec<u8>>,
+    used: bool,
+}
+
+impl JsBody {
+    fn new(bytes: Option<Vec<u8>>) -> Self {
+        Self { bytes, used: false }
+    }
+
+    /// Takes the bytes of the body, throwing a `TypeError` when they were already read
+    fn consume(&mut self, closure_context: &JsClosureContext) -> Result<Vec<u8>, JsValueNode> {
+        if self.used {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        let bytes = self.bytes.take();
+        self.used = bytes.is_some();
+        Ok(bytes.unwrap_or_default())
+    }
+
+    /// <https://fetch.spec.whatwg.org/#dom-body-text>
+    fn text(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        let result = self.consume(closure_context).map(|bytes| {
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            JsValue::string_rc(&closure_context.nodes_graph, text)
+        });
+        create_settled_promise(closure_context, result)
+    }
+
+    /// Parses the body as JSON, rejecting with a `SyntaxError` when it is not valid
+    /// <https://fetch.spec.whatwg.org/#dom-body-json>
+    fn json(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        let result = self.consume(closure_context).and_then(|bytes| {
+            let text = String::from_utf8_lossy(&bytes);
+            let json = parse_json(&text)
+                .map_err(|_| JsValue::syntax_error_rc(&closure_context.nodes_graph))?;
+            Ok(json_to_js_value(closure_context, json))
+        });
+        create_settled_promise(closure_context, result)
+    }
+
+    /// <https://fetch.spec.whatwg.org/#dom-body-arraybuffer>
+    fn array_buffer(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        let result = self
+            .consume(closure_context)
+            .map(|bytes| JsValue::array_buffer_rc(&closure_context.nodes_graph, bytes));
+        create_settled_promise(closure_context, result)
+    }
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Request>
+#[derive(Debug)]
+pub struct JsRequest {
+    url: Url,
+    method: HttpVerb,
+    /// The `Headers` of the request, where forbidden request headers are ignored
+    headers: JsValueNode,
+    body: JsBody,
+}
+
+impl JsRequest {
+    /// `new Request(input, init)`, where the input is a URL resolved against the URL of the
+    /// document or a request to copy. The `method`, `headers`, and `body` of the init replace
+    /// those of the input, and requests with a `GET` or `HEAD` method cannot have a body.
+    /// <https://fetch.spec.whatwg.org/#dom-request>
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let input = get_argument(closure_context, args, 0);
+        let init = get_argument(closure_context, args, 1);
+        let input_request = match input.get_ref() {
+            JsValue::HostObject(object) => object.is::<JsRequest>(),
+            _ => false,
+        };
+        let (url, mut method, headers, mut body) = if input_request {
+            with_host_object(
+                closure_context,
+                &input,
+                |request: &mut JsRequest, ctx| -> Result<_, JsValueNode> {
+                    let headers = get_header_map(ctx, &request.headers)?;
+                    let body = request.body.consume(ctx)?;
+                    let body = (!body.is_empty()).then_some(body);
+                    Ok((request.url.clone(), request.method, headers, body))
+                },
+            )??
+        } else {
+            let url = String::from_js_value(&input, closure_context)?;
+            let url = resolve_url(closure_context, &url)?;
+            (url, HttpVerb::Get, HttpHeaderMap::default(), None)
+        };
+
+        let method_init = get_init_member(closure_context, &init, "method")?;
+        if !matches!(method_init.get_ref(), JsValue::Undefined) {
+            let name = String::from_js_value(&method_init, closure_context)?;
+            method = HttpVerb::parse(&name)
+                .ok_or_else(|| JsValue::type_error_rc(&closure_context.nodes_graph))?;
+        }
+        let mut headers_object = JsHeaders::new(HttpHeaderMap::default(), JsHeadersGuard::Request);
+        let headers_init = get_init_member(closure_context, &init, "headers")?;
+        if matches!(headers_init.get_ref(), JsValue::Undefined) {
+            headers_object.headers = headers;
+        } else {
+            headers_object.fill(closure_context, &headers_init)?;
+        }
+        let body_init = get_init_member(closure_context, &init, "body")?;
+        if !matches!(body_init.get_ref(), JsValue::Undefined) {
+            let (bytes, content_type) = extract_body(closure_context, &body_init)?;
+            if let Some(content_type) = content_type {
+                if !headers_object.headers.contains("content-type") {
+                    headers_object.headers.append("content-type", content_type);
+                }
+            }
+            body = bytes;
+        }
+        if body.is_some() && matches!(method, HttpVerb::Get | HttpVerb::Head) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+
+        Ok(Self {
+            url,
+            method,
+            headers: closure_context.create_host_object(headers_object),
+            body: JsBody::new(body),
+        })
+    }
+}
+
+impl JsHostReferences for JsRequest {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![self.headers.clone()]
+    }
+}
+
+#[js_class(name = "Request")]
+impl JsRequest {
+    #[js_method(getter)]
+    fn url(&self) -> String {
+        self.url.to_record().to_string()
+    }
+
+    #[js_method(getter)]
+    fn method(&self) -> &'static str {
+        self.method.as_str()
+    }
+
+    #[js_method(getter)]
+    fn headers(&self) -> JsValueNode {
+        self.headers.clone()
+    }
+
+    #[js_method(getter)]
+    fn body_used(&self) -> bool {
+        self.body.used
+    }
+
+    #[js_method]
+    fn text(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.text(closure_context)
+    }
+
+    #[js_method]
+    fn json(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.json(closure_context)
+    }
+
+    #[js_method]
+    fn array_buffer(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.array_buffer(closure_context)
+    }
+}
+
+/// How much of a response scripts can see
+/// <https://fetch.spec.whatwg.org/#concept-response-type>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JsResponseType {
+    /// A response from the same origin
+    Basic,
+    /// A response from another origin that allowed it with CORS, whose headers are filtered
+    Cors,
+    /// A response created by a script
+    Default,
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Response>
+#[derive(Debug)]
+pub struct JsResponse {
+    response_type: JsResponseType,
+    url: String,
+    status: u16,
+    status_text: String,
+    /// The `Headers` of the response, which cannot be changed for responses from the network
+    headers: JsValueNode,
+    body: JsBody,
+}
+
+impl JsResponse {
+    /// `new Response(body, init)`, where the init has the `status`, `statusText`, and `headers`
+    /// of the response. The status must be from 200 to 599, and responses whose status has no
+    /// content cannot have a body.
+    /// <https://fetch.spec.whatwg.org/#dom-response>
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let body_init = get_argument(closure_context, args, 0);
+        let init = get_argument(closure_context, args, 1);
+
+        let status = get_init_member(closure_context, &init, "status")?;
+        let status = match status.get_ref() {
+            JsValue::Undefined => 200,
+            _ => u32::from_js_value(&status, closure_context)?,
+        };
+        if !(200..=599).contains(&status) {
+            return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+        }
+        let status_text = get_init_member(closure_context, &init, "statusText")?;
+        let status_text = match status_text.get_ref() {
+            JsValue::Undefined => String::new(),
+            _ => String::from_js_value(&status_text, closure_context)?,
+        };
+
+        let mut headers = JsHeaders::new(HttpHeaderMap::default(), JsHeadersGuard::None);
+        let headers_init = get_init_member(closure_context, &init, "headers")?;
+        headers.fill(closure_context, &headers_init)?;
+        let (body, content_type) = extract_body(closure_context, &body_init)?;
+        if body.is_some() && matches!(status, 204 | 205 | 304) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        if let Some(content_type) = content_type {
+            if !headers.headers.contains("content-type") {
+                headers.headers.append("content-type", content_type);
+            }
+        }
+
+        Ok(Self {
+            response_type: JsResponseType::Default,
+            url: String::new(),
+            status: status as u16,
+            status_text,
+            headers: closure_context.create_host_object(headers),
+            body: JsBody::new(body),
+        })
+    }
+
+    /// A response from the network to a request, whose headers were already filtered when it is
+    /// from another origin
+    fn from_network(
+        closure_context: &mut JsClosureContext,
+        request: &JsFetchRequest,
+        response: HttpResponse,
+    ) -> Self {
+        let response_type = if request.cors_origin.is_some() {
+            JsResponseType::Cors
+        } else {
+            JsResponseType::Basic
+        };
+        let headers = JsHeaders::new(response.headers, JsHeadersGuard::Immutable);
+        Self {
+            response_type,
+            url: request.url.to_record().to_string(),
+            status: response.status.status_code,
+            status_text: response.status.reason_phrase,
+            headers: closure_context.create_host_object(headers),
+            body: JsBody::new(Some(response.body)),
+        }
+    }
+}
+
+impl JsHostReferences for JsResponse {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![self.headers.clone()]
+    }
+}
+
+#[js_class(name = "Response")]
+impl JsResponse {
+    #[js_method(getter, name = "type")]
+    fn response_type(&self) -> &'static str {
+        match self.response_type {
+            JsResponseType::Basic => "basic",
+            JsResponseType::Cors => "cors",
+            JsResponseType::Default => "default",
+        }
+    }
+
+    #[js_method(getter)]
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    #[js_method(getter)]
+    fn redirected(&self) -> bool {
+        false
+    }
+
+    #[js_method(getter)]
+    fn status(&self) -> u32 {
+        self.status.into()
+    }
+
+    /// Whether the status is successful, from 200 to 299
+    #[js_method(getter)]
+    fn ok(&self) -> bool {
+        (200..=299).contains(&self.status)
+    }
+
+    #[js_method(getter)]
+    fn status_text(&self) -> String {
+        self.status_text.clone()
+    }
+
+    #[js_method(getter)]
+    fn headers(&self) -> JsValueNode {
+        self.headers.clone()
+    }
+
+    #[js_method(getter)]
+    fn body_used(&self) -> bool {
+        self.body.used
+    }
+
+    #[js_method]
+    fn text(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.text(closure_context)
+    }
+
+    #[js_method]
+    fn json(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.json(closure_context)
+    }
+
+    #[js_method]
+    fn array_buffer(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        self.body.array_buffer(closure_context)
+    }
+}
+
+/// `fetch(input, init)` sends a request built as `new Request(input, init)` is, resulting in a
+/// promise of its response. The promise rejects with a `TypeError` when the request cannot be
+/// made, fails on the network, or is to another origin that does not allow it with CORS.
+/// Responses are processed by [process_fetch_responses].
+/// <https://fetch.spec.whatwg.org/#fetch-method>
+#[js_method]
+pub fn fetch(
+    closure_context: &mut JsClosureContext,
+    input: JsValueNode,
+    init: JsValueNode,
+) -> JsValueNode {
+    match start_fetch(closure_context, input, init) {
+        Ok(promise) => promise,
+        Err(error) => create_settled_promise(closure_context, Err(error)),
+    }
+}
+
+fn start_fetch(
+    closure_context: &mut JsClosureContext,
+    input: JsValueNode,
+    init: JsValueNode,
+) -> JsFunctionResult {
+    let mut request = JsRequest::construct(closure_context, &[input, init])?;
+    if request.url.protocol != UrlProtocol::Http {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let headers = get_header_map(closure_context, &request.headers)?;
+    let body = request.body.consume(closure_context)?;
+    let request = JsFetchRequest::new(
+        &closure_context.fetches,
+        request.url,
+        request.method,
+        headers,
+        body,
+    );
+    let promise = create_promise(closure_context);
+    send_fetch_request(
+        closure_context,
+        request,
+        JsFetchTarget::Promise(promise.clone()),
+    )
+    .map_err(|_| JsValue::type_error_rc(&closure_context.nodes_graph))?;
+    Ok(promise)
+}
+
+/// Sends a request on the network, starting with its preflight request when it needs one. The
+/// target is told once it finishes by [process_fetch_responses].
+pub fn send_fetch_request(
+    closure_context: &mut JsClosureContext,
+    request: JsFetchRequest,
+    target: JsFetchTarget,
+) -> Result<(), ExecutionError> {
+    let preflight = request.needs_preflight();
+    let token = request.send(preflight)?;
+    closure_context.fetches.pending.push(JsPendingFetch {
+        target,
+        request,
+        preflight,
+        token,
+    });
+    Ok(())
+}
+
+/// Tells the targets of the requests that finished, sending the requests whose preflight allowed
+/// them. Results in whether any requests finished.
+pub fn process_fetch_responses(closure_context: &mut JsClosureContext) -> bool {
+    let pending = std::mem::take(&mut closure_context.fetches.pending);
+    let mut finished = vec![];
+    match NETWORK_EXECUTOR.lock() {
+        Ok(executor) => {
+            for fetch in pending {
+                match executor.get_result(fetch.token) {
+                    Some(result) => finished.push((fetch, result)),
+                    None => closure_context.fetches.pending.push(fetch),
+                }
+            }
+        }
+        Err(_) => {
+            closure_context.fetches.pending = pending;
+            return false;
+        }
+    }
+
+    let processed = !finished.is_empty();
+    for (fetch, result) in finished {
+        finish_fetch(closure_context, fetch, result.ok().and_then(Result::ok));
+    }
+    processed
+}
+
+/// Handles the response to a request, which is the response to the preflight request when it
+/// was sent first. `None` is a network error.
+fn finish_fetch(
+    closure_context: &mut JsClosureContext,
+    fetch: JsPendingFetch,
+    response: Option<HttpResponse>,
+) {
+    let response = match response {
+        Some(response) if fetch.preflight => {
+            if fetch.request.is_preflight_allowed(&response) {
+                if let Ok(token) = fetch.request.send(false) {
+                    closure_context.fetches.pending.push(JsPendingFetch {
+                        preflight: false,
+                        token,
+                        ..fetch
+                    });
+                    return;
+                }
+            }
+            None
+        }
+        Some(response) => fetch.request.receive(response),
+        None => None,
+    };
+
+    match (fetch.target, response) {
+        (JsFetchTarget::Promise(promise), Some(response)) => {
+            let response = JsResponse::from_network(closure_context, &fetch.request, response);
+            let response = closure_context.create_host_object_with_references(response);
+            resolve_promise(closure_context, &promise, response);
+        }
+        (JsFetchTarget::Promise(promise), None) => {
+            let error = JsValue::type_error_rc(&closure_context.nodes_graph);
+            reject_promise(closure_context, &promise, error);
+        }
+        (JsFetchTarget::XmlHttpRequest(request, generation), response) => {
+            finish_xml_http_request(closure_context, &request, generation, response);
+        }
+    }
+}
+
+/// Whether a request to another origin can be sent without asking the server first, which is when
+/// it could be made by a form
+/// <https://fetch.spec.whatwg.org/#cors-safelisted-method>
+fn is_simple_request(request: &JsFetchRequest) -> bool {
+    matches!(
+        request.method,
+        HttpVerb::Get | HttpVerb::Head | HttpVerb::Post
+    ) && get_unsafe_header_names(&request.headers).is_empty()
+}
+
+/// The names of the headers that are not CORS-safelisted, which the server must allow
+/// <https://fetch.spec.whatwg.org/#cors-unsafe-request-header-names>
+fn get_unsafe_header_names(headers: &HttpHeaderMap) -> Vec<String> {
+    headers
+        .iter()
+        .filter(|header| match header.name.as_str() {
+            "accept" | "accept-language" | "content-language" => false,
+            "content-type" => {
+                let essence = header.value.split(';').next().unwrap_or_default();
+                !matches!(
+                    essence.trim().to_ascii_lowercase().as_str(),
+                    "application/x-www-form-urlencoded" | "multipart/form-data" | "text/plain"
+                )
+            }
+            _ => true,
+        })
+        .map(|header| header.name.clone())
+        .collect()
+}
+
+/// Whether the response to a request from the origin allows the origin to read it
+/// <https://fetch.spec.whatwg.org/#cors-check>
+fn is_origin_allowed(origin: &str, headers: &HttpHeaderMap) -> bool {
+    match headers.get_combined("access-control-allow-origin") {
+        Some(allowed) => allowed == "*" || allowed == origin,
+        None => false,
+    }
+}
+
+/// Splits a header listing names by commas, lower casing them as they are compared ignoring case
+fn parse_header_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Resolves a URL against the URL of the document, throwing a `TypeError` when it is not valid
+pub fn resolve_url(closure_context: &JsClosureContext, url: &str) -> Result<Url, JsValueNode> {
+    let resolved = match &closure_context.fetches.document_url {
+        Some(document_url) => document_url.join(url),
+        None => Url::parse(url),
+    };
+    resolved.ok_or_else(|| JsValue::type_error_rc(&closure_context.nodes_graph))
+}
+
+/// A member of the init of a request or response, where `undefined` and `null` inits have no
+/// members and other values that are not objects throw a `TypeError`
+fn get_init_member(
+    closure_context: &mut JsClosureContext,
+    init: &JsValueNode,
+    name: &str,
+) -> JsFunctionResult {
+    match init.get_ref() {
+        JsValue::Undefined | JsValue::Null => {
+            return Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+        }
+        value if !value.is_object() => {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+        }
+        _ => {}
+    }
+    get_member_from_prototype_chain(init, &name.into(), closure_context)
+}
+
+/// The bytes of a body, which is a buffer source or a string, along with the `Content-Type` it is
+/// sent with by default. `null` is no body.
+/// <https://fetch.spec.whatwg.org/#concept-bodyinit-extract>
+pub fn extract_body(
+    closure_context: &mut JsClosureContext,
+    body: &JsValueNode,
+) -> Result<(Option<Vec<u8>>, Option<&'static str>), JsValueNode> {
+    match body.get_ref() {
+        JsValue::Undefined | JsValue::Null => return Ok((None, None)),
+        JsValue::ArrayBuffer(_) | JsValue::TypedArray(_) | JsValue::DataView(_) => {}
+        _ => {
+            let text = String::from_js_value(body, closure_context)?;
+            return Ok((Some(text.into_bytes()), Some("text/plain;charset=UTF-8")));
+        }
+    }
+    let bytes = Vec::<u8>::from_js_value(body, closure_context)?;
+    Ok((Some(bytes), None))
+}
+
+/// Converts parsed JSON to the JS value it describes
+pub fn json_to_js_value(closure_context: &JsClosureContext, json: JsonValue) -> JsValueNode {
+    let nodes_graph = &closure_context.nodes_graph;
+    match json {
+        JsonValue::Object(members) => {
+            let mut properties = JsProperties::new();
+            for (key, value) in members {
+                properties.insert(key.into(), json_to_js_value(closure_context, value));
+            }
+            JsValue::object_rc(nodes_graph, properties)
+        }
+        JsonValue::Array(elements) => {
+            let elements = elements
+                .into_iter()
+                .map(|element| json_to_js_value(closure_context, element))
+                .collect();
+            JsValue::array_rc(nodes_graph, elements)
+        }
+        JsonValue::String(string) => JsValue::string_rc(nodes_graph, string),
+        JsonValue::Number(JsonNumber::F64(number)) => JsValue::number_rc(nodes_graph, number),
+        JsonValue::Number(JsonNumber::I64(number)) => {
+            JsValue::number_rc(nodes_graph, number as f64)
+        }
+        JsonValue::Number(JsonNumber::String(number)) => {
+            JsValue::number_rc(nodes_graph, number.parse::<f64>().unwrap_or(f64::NAN))
+        }
+        JsonValue::Boolean(boolean) => JsValue::bool_rc(nodes_graph, boolean),
+        JsonValue::Null => JsValue::null_rc(nodes_graph),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{js::JsDocument, net::HttpStatus};
+
+    use super::*;
+
+    #[test]
+    fn test_response_url_without_port() {
+        let mut js_document = JsDocument::new(vec![]);
+        let request = JsFetchRequest {
+            url: Url::parse("http://a.com/x?y").unwrap(),
+            method: HttpVerb::Get,
+            headers: HttpHeaderMap::default(),
+            body: vec![],
+            cors_origin: None,
+        };
+        let response = HttpResponse {
+            status: HttpStatus {
+                http_version: "HTTP/1.1".to_string(),
+                status_code: 200,
+                reason_phrase: "OK".to_string(),
+            },
+            headers: HttpHeaderMap::default(),
+            body: vec![],
+        };
+        let response =
+            JsResponse::from_network(&mut js_document.global_closure_context, &request, response);
+        assert_eq!(response.url, "http://a.com/x?y");
+    }
+}
@@ -1,17 +1,24 @@
 use std::rc::Rc;
 
-use super::{JsClosureContext, JsStatement, JsStatementResult, JsValue, JsValueNode};
+use super::{
+    enter_module_scope, get_member_from_prototype_chain, leave_module_scope, proxy_call,
+    proxy_construct, JsClosureContext, JsEngine, JsProperties, JsProperty, JsPropertyKey,
+    JsStatement, JsStatementResult, JsValue, JsValueNode, JsVm, PROTOTYPE_MEMBER,
+};
 
 #[derive(Clone)]
 #[allow(clippy::type_complexity)]
 pub struct JsNativeFunctionImplementation {
-    pub func: Rc<dyn Fn(JsValueNode, &[JsValueNode]) -> JsFunctionResult>,
+    pub func: Rc<dyn Fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult>,
+    /// The values `func` holds on to, which live as long as the function does
+    pub captures: Vec<JsValueNode>,
 }
 
 impl Default for JsNativeFunctionImplementation {
     fn default() -> Self {
         Self {
-            func: Rc::new(|this, _| Ok(JsValue::undefined_rc(&this.get_node_graph()))),
+            func: Rc::new(|_, this, _| Ok(JsValue::undefined_rc(&this.get_node_graph()))),
+            captures: vec![],
         }
     }
 }
@@ -46,6 +53,18 @@ pub enum JsFunction {
     ),
 }
 
+/// Bound in every user defined function to the list of arguments it was invoked with. Parameter
+/// lists with defaults, patterns, or rest elements are destructured from it.
+pub const ARGUMENTS_REFERENCE: &str = "arguments";
+
+/// Bound in every user defined function to the value the function was invoked on
+pub const THIS_REFERENCE: &str = "this";
+
+/// The member of a constructor holding the prototype of the objects it constructs
+pub const CONSTRUCTOR_PROTOTYPE_MEMBER: &str = "prototype";
+/// The member of a prototype referring back to its constructor
+pub const CONSTRUCTOR_MEMBER: &str = "constructor";
+
 pub type JsFunctionResult = Result<JsValueNode, JsValueNode>;
 
 impl JsFunction {
@@ -63,25 +82,18 @@ impl JsFunction {
         args: &[JsValueNode],
     ) -> JsFunctionResult {
         match self {
-            Self::Native(_, implementation) => implementation.func.as_ref()(this, args),
+            Self::Native(_, implementation) => {
+                implementation.func.as_ref()(closure_context, this, args)
+            }
             Self::UserDefined(_source, _name, params, implementation) => {
-                if closure_context.get_closure_depth() > 255 {
+                if closure_context.get_closure_depth() > closure_context.limits.max_call_depth {
                     return Err(JsValue::stack_overflow_error_rc(
                         &closure_context.nodes_graph,
                     ));
                 }
 
                 closure_context.with_new_context(|closure_context| {
-                    let closure = closure_context.get_lastest_closure();
-                    for (index, param_name) in params.iter().enumerate() {
-                        // Ensure all params are declared
-                        let reference = closure.get_or_declare_reference_mut(param_name);
-
-                        // Assign args that line up with parameters are assigned
-                        if let Some(arg) = args.get(index) {
-                            reference.value = arg.clone();
-                        }
-                    }
+                    declare_function_scope(closure_context, this.clone(), args, params);
                     for statement in implementation {
                         match statement.run(closure_context) {
                             JsStatementResult::ReturnValue(value) => return Ok(value),
@@ -97,7 +109,7 @@ impl JsFunction {
 
     pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
         match self {
-            Self::Native(_, _) => vec![],
+            Self::Native(_, implementation) => implementation.captures.clone(),
             Self::UserDefined(_source, _name, _params, statements) => statements
                 .iter()
                 .flat_map(|statement| statement.get_referenced_nodes())
@@ -106,6 +118,152 @@ impl JsFunction {
     }
 }
 
+/// Declares `this`, `arguments`, and the parameters of a user defined function in the latest
+/// scope. Parameters without a matching argument are `undefined`.
+pub fn declare_function_scope(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+    params: &[String],
+) {
+    let arguments = JsValue::array_rc(&closure_context.nodes_graph, args.to_vec());
+    let closure = closure_context.get_lastest_closure();
+    closure.get_or_declare_reference_mut(THIS_REFERENCE).value = this;
+    closure
+        .get_or_declare_reference_mut(ARGUMENTS_REFERENCE)
+        .value = arguments;
+    for (index, param_name) in params.iter().enumerate() {
+        // Ensure all params are declared
+        let reference = closure.get_or_declare_reference_mut(param_name);
+
+        // Assign args that line up with parameters are assigned
+        if let Some(arg) = args.get(index) {
+            reference.value = arg.clone();
+        }
+    }
+}
+
+/// Invokes a value as a function, throwing a `TypeError` if it is not callable. User defined
+/// functions are run by the engine of the closure context, with the scope of the module they were
+/// declared in visible to them.
+pub fn invoke_function(
+    closure_context: &mut JsClosureContext,
+    function: &JsValueNode,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    match function.get_ref() {
+        JsValue::Function(JsFunction::UserDefined(..), _properties)
+            if closure_context.engine == JsEngine::Bytecode =>
+        {
+            JsVm::new().call(closure_context, function, this, args)
+        }
+        JsValue::Function(implementation, _properties) => {
+            let module = enter_module_scope(closure_context, function);
+            let result = implementation.run(closure_context, this, args);
+            leave_module_scope(closure_context, module);
+            result
+        }
+        JsValue::Proxy(proxy) if proxy.callable => {
+            proxy_call(&proxy.clone(), this, args, closure_context)
+        }
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// Gets the `prototype` member of a constructor, which is created on first use for user defined
+/// functions. Returns `None` for values that are not functions and native functions without one.
+pub fn get_or_create_constructor_prototype(
+    closure_context: &JsClosureContext,
+    constructor: &JsValueNode,
+) -> Option<JsValueNode> {
+    let prototype_key = JsPropertyKey::from(CONSTRUCTOR_PROTOTYPE_MEMBER);
+    let mut constructor_node = constructor.clone();
+    match constructor_node.get_mut() {
+        JsValue::Function(function, properties) => {
+            if let Some(prototype) = properties.get_value(&prototype_key) {
+                return Some(prototype.clone());
+            }
+            if let JsFunction::Native(_, _) = function {
+                return None;
+            }
+            let mut prototype_properties = JsProperties::new();
+            prototype_properties.insert_property(
+                CONSTRUCTOR_MEMBER.into(),
+                JsProperty::with_attributes(constructor.clone(), true, false, true),
+            );
+            let prototype = JsValue::object_rc(&closure_context.nodes_graph, prototype_properties);
+            properties.insert_property(
+                prototype_key,
+                JsProperty::with_attributes(prototype.clone(), true, false, false),
+            );
+            Some(prototype)
+        }
+        _ => None,
+    }
+}
+
+/// Invokes a function with `new`. The function is invoked on a new object inheriting from its
+/// `prototype` member. The new object is the result unless the function returns some other object.
+pub fn construct(
+    closure_context: &mut JsClosureContext,
+    constructor: &JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    construct_with_new_target(closure_context, constructor, args, constructor)
+}
+
+/// Invokes a function with `new`, where the new object inherits from the `prototype` member of
+/// `new_target` rather than the function's own, as done by `Reflect.construct`
+pub fn construct_with_new_target(
+    closure_context: &mut JsClosureContext,
+    constructor: &JsValueNode,
+    args: &[JsValueNode],
+    new_target: &JsValueNode,
+) -> JsFunctionResult {
+    if let JsValue::Proxy(proxy) = constructor.get_ref() {
+        return proxy_construct(&proxy.clone(), args, new_target, closure_context);
+    }
+    if !constructor.get_ref().is_callable() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let this = create_constructed_object(closure_context, new_target)?;
+    let result = invoke_function(closure_context, constructor, this.clone(), args)?;
+    if result.get_ref().is_object() {
+        Ok(result)
+    } else {
+        Ok(this)
+    }
+}
+
+/// Creates the object a constructor is invoked on with `new`, throwing a `TypeError` if the
+/// constructor is not a function. The `prototype` of a proxy is read through its `get` trap.
+pub fn create_constructed_object(
+    closure_context: &mut JsClosureContext,
+    constructor: &JsValueNode,
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let prototype = match constructor.get_ref() {
+        JsValue::Function(_, _) => {
+            get_or_create_constructor_prototype(closure_context, constructor)
+        }
+        JsValue::Proxy(proxy) if proxy.callable => {
+            let prototype_key = JsPropertyKey::from(CONSTRUCTOR_PROTOTYPE_MEMBER);
+            let prototype =
+                get_member_from_prototype_chain(constructor, &prototype_key, closure_context)?;
+            Some(prototype).filter(|prototype| prototype.get_ref().is_object())
+        }
+        _ => return Err(JsValue::type_error_rc(&nodes_graph)),
+    };
+    let prototype =
+        prototype.unwrap_or_else(|| JsValue::object_rc(&nodes_graph, JsProperties::new()));
+
+    Ok(JsValue::object_rc(
+        &nodes_graph,
+        JsProperties::from([(PROTOTYPE_MEMBER.into(), prototype)]),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,242 @@
+use wowser_macros::js_class;
+
+use crate::net::HttpHeaderMap;
+
+use super::{
+    collect_iterable, get_argument, get_own_enumerable_entries, with_host_object, FromJsValue,
+    JsClosureContext, JsValue, JsValueNode,
+};
+
+/// Headers scripts cannot set on requests, which the browser controls
+/// <https://fetch.spec.whatwg.org/#forbidden-request-header>
+const FORBIDDEN_REQUEST_HEADERS: [&str; 21] = [
+    "accept-charset",
+    "accept-encoding",
+    "access-control-request-headers",
+    "access-control-request-method",
+    "connection",
+    "content-length",
+    "cookie",
+    "cookie2",
+    "date",
+    "dnt",
+    "expect",
+    "host",
+    "keep-alive",
+    "origin",
+    "referer",
+    "set-cookie",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "via",
+];
+
+/// What may be changed in a `Headers` object
+/// <https://fetch.spec.whatwg.org/#concept-headers-guard>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsHeadersGuard {
+    None,
+    /// The headers of a request, where forbidden request headers are ignored
+    Request,
+    /// The headers of a response from the network, which cannot be changed
+    Immutable,
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Headers>
+///
+/// Names are kept in lower case, as they are compared ignoring case.
+#[derive(Debug)]
+pub struct JsHeaders {
+    pub headers: HttpHeaderMap,
+    guard: JsHeadersGuard,
+}
+
+impl JsHeaders {
+    pub fn new(headers: HttpHeaderMap, guard: JsHeadersGuard) -> Self {
+        Self { headers, guard }
+    }
+
+    /// `new Headers(init)`, where the headers are copied from another `Headers` object, an
+    /// iterable of `[name, value]` pairs, or the properties of an object
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let mut headers = Self::new(HttpHeaderMap::default(), JsHeadersGuard::None);
+        let init = get_argument(closure_context, args, 0);
+        headers.fill(closure_context, &init)?;
+        Ok(headers)
+    }
+
+    /// Appends the headers of `init`, which are described in [Self::construct]. `undefined` has
+    /// none.
+    /// <https://fetch.spec.whatwg.org/#concept-headers-fill>
+    pub fn fill(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        init: &JsValueNode,
+    ) -> Result<(), JsValueNode> {
+        let (is_headers, is_array, is_object) = match init.get_ref() {
+            JsValue::Undefined => return Ok(()),
+            JsValue::HostObject(object) => (object.is::<JsHeaders>(), false, true),
+            value => (
+                false,
+                matches!(value, JsValue::Array(_, _)),
+                value.is_object(),
+            ),
+        };
+        let entries: Vec<(String, String)> = if is_headers {
+            get_header_map(closure_context, init)?
+                .iter()
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect()
+        } else if is_array {
+            let mut entries = vec![];
+            for pair in collect_iterable(init, closure_context)? {
+                let pair = collect_iterable(&pair, closure_context)?;
+                let [name, value] = &pair[..] else {
+                    return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+                };
+                entries.push((
+                    String::from_js_value(name, closure_context)?,
+                    String::from_js_value(value, closure_context)?,
+                ));
+            }
+            entries
+        } else if is_object {
+            let mut entries = vec![];
+            for (key, value) in get_own_enumerable_entries(init, closure_context)? {
+                entries.push((
+                    key.to_string(),
+                    String::from_js_value(&value, closure_context)?,
+                ));
+            }
+            entries
+        } else {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        };
+        for (name, value) in entries {
+            self.append(closure_context, name, value)?;
+        }
+        Ok(())
+    }
+
+    /// Validates and normalizes a header, throwing a `TypeError` when it is invalid or the headers
+    /// cannot be changed. Results in `None` when the header is ignored.
+    fn normalize(
+        &self,
+        closure_context: &JsClosureContext,
+        name: &str,
+        value: &str,
+    ) -> Result<Option<(String, String)>, JsValueNode> {
+        let value = value.trim_matches(is_http_whitespace);
+        if !is_token(name) || value.contains(['\0', '\r', '\n']) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        let name = name.to_ascii_lowercase();
+        match self.guard {
+            JsHeadersGuard::Immutable => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+            JsHeadersGuard::Request if is_forbidden_request_header(&name) => Ok(None),
+            JsHeadersGuard::Request | JsHeadersGuard::None => Ok(Some((name, value.to_string()))),
+        }
+    }
+}
+
+#[js_class(name = "Headers")]
+impl JsHeaders {
+    #[js_method]
+    pub fn append(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        if let Some((name, value)) = self.normalize(closure_context, &name, &value)? {
+            self.headers.append(&name, &value);
+        }
+        Ok(())
+    }
+
+    #[js_method]
+    fn delete(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<(), JsValueNode> {
+        if let Some((name, _value)) = self.normalize(closure_context, &name, "")? {
+            self.headers.remove(&name);
+        }
+        Ok(())
+    }
+
+    /// The values of the headers with the name joined by `, `, or `null` when there are none
+    #[js_method]
+    fn get(
+        &self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<Option<String>, JsValueNode> {
+        if !is_token(&name) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        Ok(self.headers.get_combined(&name.to_ascii_lowercase()))
+    }
+
+    #[js_method]
+    fn has(
+        &self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+    ) -> Result<bool, JsValueNode> {
+        if !is_token(&name) {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        Ok(self.headers.contains(&name.to_ascii_lowercase()))
+    }
+
+    /// Replaces the headers with the name
+    #[js_method]
+    fn set(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        if let Some((name, value)) = self.normalize(closure_context, &name, &value)? {
+            self.headers.set(&name, &value);
+        }
+        Ok(())
+    }
+}
+
+/// The headers of a `Headers` object, throwing a `TypeError` for other values
+pub fn get_header_map(
+    closure_context: &mut JsClosureContext,
+    headers: &JsValueNode,
+) -> Result<HttpHeaderMap, JsValueNode> {
+    with_host_object(closure_context, headers, |headers: &mut JsHeaders, _| {
+        headers.headers.clone()
+    })
+}
+
+/// <https://fetch.spec.whatwg.org/#forbidden-request-header>
+fn is_forbidden_request_header(name: &str) -> bool {
+    FORBIDDEN_REQUEST_HEADERS.contains(&name)
+        || name.starts_with("proxy-")
+        || name.starts_with("sec-")
+}
+
+/// Header names are tokens, which are visible ASCII without separators
+/// <https://httpwg.org/specs/rfc9110.html#rfc.section.5.6.2>
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+fn is_http_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
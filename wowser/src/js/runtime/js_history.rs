@@ -0,0 +1,268 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wowser_macros::js_class;
+
+use crate::{
+    js::api::history::{NavigationRequest, SessionHistory},
+    net::{Url, UrlRecord},
+};
+
+use super::{
+    build_function_entry, get_argument, structured_deserialize, structured_serialize, FromJsValue,
+    JsClosureContext, JsFunctionResult, JsHostClass, JsLocation, JsProperty, JsValue, JsValueNode,
+};
+
+/// The session history of the tab the document is shown in, which scripts use through `history`
+/// and `location`
+#[derive(Debug, Default)]
+pub struct JsNavigation {
+    /// Shared with the tab, which carries out the navigations scripts ask for
+    pub history: Rc<RefCell<SessionHistory>>,
+    /// `history.state`, deserialized when first read after the current entry changes, so scripts
+    /// get the same value each time
+    state: Option<JsValueNode>,
+    /// The `Location` and `History` objects, created when first read so scripts always get the
+    /// same ones
+    location_object: Option<JsValueNode>,
+    history_object: Option<JsValueNode>,
+}
+
+impl JsNavigation {
+    /// Forgets the state read from the current entry, after the tab moved to another one
+    pub fn clear_state(&mut self) {
+        self.state = None;
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.state
+            .iter()
+            .chain(self.location_object.iter())
+            .chain(self.history_object.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Declares `location` and `history` as globals
+pub fn add_navigation_globals(closure_context: &mut JsClosureContext) {
+    let location = get_location_object(closure_context);
+    let history = get_history_object(closure_context);
+    let global_closure = closure_context.get_lastest_closure();
+    global_closure
+        .get_or_declare_reference_mut("location")
+        .value = location;
+    global_closure.get_or_declare_reference_mut("history").value = history;
+}
+
+/// `location`
+pub fn get_location_object(closure_context: &mut JsClosureContext) -> JsValueNode {
+    if let Some(object) = &closure_context.navigation.location_object {
+        return object.clone();
+    }
+    let object = closure_context.create_host_object(JsLocation);
+    closure_context.navigation.location_object = Some(object.clone());
+    object
+}
+
+/// `history`, whose prototype has `pushState` and `replaceState` along with the methods of
+/// [JsHistory]. They are native functions rather than methods as serializing the state can run
+/// getters that use the history too.
+pub fn get_history_object(closure_context: &mut JsClosureContext) -> JsValueNode {
+    if let Some(object) = &closure_context.navigation.history_object {
+        return object.clone();
+    }
+    let object = closure_context.create_host_object(JsHistory);
+    let mut prototype = closure_context.global_prototypes.host_classes[JsHistory::NAME].clone();
+    let node_graph = closure_context.nodes_graph.clone();
+    let methods = [
+        build_function_entry(&node_graph, "pushState".into(), history_push_state),
+        build_function_entry(&node_graph, "replaceState".into(), history_replace_state),
+    ];
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, method) in methods {
+            properties.insert_property(key, JsProperty::with_attributes(method, true, false, true));
+        }
+    }
+    closure_context.navigation.history_object = Some(object.clone());
+    object
+}
+
+/// The URL of the current entry, which is `about:blank` before the tab loads a document
+pub fn get_current_url(closure_context: &JsClosureContext) -> UrlRecord {
+    let history = closure_context.navigation.history.borrow();
+    match history.current() {
+        Some(entry) => entry.url.clone(),
+        None => UrlRecord::parse("about:blank", None).expect("about:blank is a valid URL"),
+    }
+}
+
+/// Parses a URL relative to the URL of the current entry, throwing a `SyntaxError` when it is not
+/// valid
+pub fn resolve_navigation_url(
+    closure_context: &JsClosureContext,
+    url: &str,
+) -> Result<UrlRecord, JsValueNode> {
+    UrlRecord::parse(url, Some(&get_current_url(closure_context)))
+        .ok_or_else(|| JsValue::syntax_error_rc(&closure_context.nodes_graph))
+}
+
+/// Navigates the tab to the URL, adding an entry to the session history or replacing the current
+/// one. The document is left once the script running finishes, unless only the fragment of the
+/// URL changes, which moves to a new entry of the document right away.
+/// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#navigate>
+pub fn navigate(closure_context: &mut JsClosureContext, url: UrlRecord, replace: bool) {
+    let current_url = get_current_url(closure_context);
+    let history = closure_context.navigation.history.clone();
+    let is_fragment_navigation = url.fragment.is_some()
+        && !history.borrow().is_empty()
+        && url.equals_except_fragment(&current_url);
+    if !is_fragment_navigation {
+        let request = NavigationRequest::Navigate { url, replace };
+        history.borrow_mut().request(request);
+        return;
+    }
+    history
+        .borrow_mut()
+        .push_same_document(url.clone(), None, replace);
+    update_document_url(closure_context);
+    history
+        .borrow_mut()
+        .request(NavigationRequest::FragmentNavigated {
+            old_url: current_url,
+            new_url: url,
+        });
+}
+
+/// Updates what depends on the current entry after the document moved to another of its entries,
+/// which are the URL requests are made from and `history.state`
+pub fn update_document_url(closure_context: &mut JsClosureContext) {
+    let url = get_current_url(closure_context);
+    if let Some(url) = Url::from_record(&url) {
+        closure_context.fetches.document_url = Some(url);
+    }
+    closure_context.navigation.clear_state();
+}
+
+/// `history.state`, which is `null` for entries without any
+pub fn get_history_state(closure_context: &mut JsClosureContext) -> JsValueNode {
+    if let Some(state) = &closure_context.navigation.state {
+        return state.clone();
+    }
+    let history = closure_context.navigation.history.clone();
+    let history = history.borrow();
+    let Some(state) = history.current().and_then(|entry| entry.state.as_ref()) else {
+        return JsValue::null_rc(&closure_context.nodes_graph);
+    };
+    let state = structured_deserialize(closure_context, state);
+    closure_context.navigation.state = Some(state.clone());
+    state
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/History>
+///
+/// The session history is shared with the tab through the closure context, as it outlives the
+/// document.
+pub struct JsHistory;
+
+#[js_class(name = "History")]
+impl JsHistory {
+    /// The number of entries in the session history
+    #[js_method(getter)]
+    fn length(&self, closure_context: &mut JsClosureContext) -> u32 {
+        closure_context.navigation.history.borrow().len() as u32
+    }
+
+    #[js_method(getter)]
+    fn state(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        get_history_state(closure_context)
+    }
+
+    #[js_method]
+    fn back(&self, closure_context: &mut JsClosureContext) {
+        traverse_history(closure_context, -1);
+    }
+
+    #[js_method]
+    fn forward(&self, closure_context: &mut JsClosureContext) {
+        traverse_history(closure_context, 1);
+    }
+
+    /// Moves by the number of entries, or reloads the page when it is 0 or left out
+    #[js_method]
+    fn go(&self, closure_context: &mut JsClosureContext, delta: Option<i32>) {
+        traverse_history(closure_context, delta.unwrap_or_default() as isize);
+    }
+}
+
+/// Asks the tab to move through the session history once the script running finishes, reloading
+/// the page when the delta is 0
+pub fn traverse_history(closure_context: &mut JsClosureContext, delta: isize) {
+    let request = match delta {
+        0 => NavigationRequest::Reload,
+        delta => NavigationRequest::Traverse(delta),
+    };
+    closure_context
+        .navigation
+        .history
+        .borrow_mut()
+        .request(request);
+}
+
+/// `pushState(state, unused, url)` adds an entry with the state for the document
+fn history_push_state(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    push_state(closure_context, args, false)
+}
+
+/// `replaceState(state, unused, url)` changes the state and URL of the current entry
+fn history_replace_state(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    push_state(closure_context, args, true)
+}
+
+/// Serializes the state, throwing a `DataCloneError` when it cannot be, then changes the URL of
+/// the document without loading it. The URL is resolved against the current one, throwing a
+/// `SyntaxError` when it is not valid and a `SecurityError` when it is not one the document could
+/// have.
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#shared-history-push/replace-state-steps>
+fn push_state(
+    closure_context: &mut JsClosureContext,
+    args: &[JsValueNode],
+    replace: bool,
+) -> JsFunctionResult {
+    let state = get_argument(closure_context, args, 0);
+    let state = structured_serialize(closure_context, &state)?;
+    let current_url = get_current_url(closure_context);
+    let url = match Option::<String>::from_js_argument(args, 2, closure_context)? {
+        Some(url) => resolve_navigation_url(closure_context, &url)?,
+        None => current_url.clone(),
+    };
+    if !can_have_url_rewritten(&current_url, &url) {
+        return Err(JsValue::security_error_rc(&closure_context.nodes_graph));
+    }
+    closure_context
+        .navigation
+        .history
+        .borrow_mut()
+        .push_same_document(url, Some(state), replace);
+    update_document_url(closure_context);
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// Documents can only change their URL to another of the same origin, and file documents only the
+/// query and fragment
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#can-have-its-url-rewritten>
+fn can_have_url_rewritten(document_url: &UrlRecord, url: &UrlRecord) -> bool {
+    url.scheme == document_url.scheme
+        && url.username == document_url.username
+        && url.password == document_url.password
+        && url.host == document_url.host
+        && url.port == document_url.port
+        && (url.scheme != "file" || url.path == document_url.path)
+}
@@ -0,0 +1,526 @@
+use std::{any::Any, cell::RefCell, fmt, rc::Rc};
+
+use super::{
+    build_native_function, build_prototype, internal_get_prototype_of, internal_has_property,
+    to_number, JsClosureContext, JsFunctionResult, JsProperty, JsPropertyDescriptor, JsPropertyKey,
+    JsPropertyValue, JsValue, JsValueNode, CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+/// A Rust type exposed to JS, whose values are [JsHostObject]s inheriting from a prototype of its
+/// methods and accessors. Implemented by `#[wowser_macros::js_class]`.
+pub trait JsHostClass: Any {
+    /// The name of the class in JS, such as `Storage`
+    const NAME: &'static str;
+
+    fn build_prototype(parent: JsValueNode) -> JsValueNode;
+}
+
+/// A host class whose values have a property for each of their items, such as the items of
+/// `Storage`. The properties are strings, and are hidden by members of the prototype chain of the
+/// same name.
+/// <https://webidl.spec.whatwg.org/#idl-named-properties>
+pub trait JsNamedProperties {
+    fn get_supported_property_names(&self) -> Vec<String>;
+
+    fn get_named_property(&self, name: &str) -> Option<String>;
+
+    fn set_named_property(
+        &mut self,
+        name: &str,
+        value: String,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(), JsValueNode>;
+
+    fn delete_named_property(&mut self, name: &str);
+}
+
+/// A host class whose values hold on to JS values, such as the callbacks waiting on a `Promise`.
+/// The values are kept from being collected as garbage for as long as the host object is.
+pub trait JsHostReferences {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode>;
+}
+
+/// A value of a [JsHostClass], shared by the nodes it is copied into
+#[derive(Clone)]
+pub struct JsHostObject {
+    pub class_name: &'static str,
+    value: Rc<RefCell<dyn Any>>,
+    /// The same value, for classes with named properties
+    named_properties: Option<Rc<RefCell<dyn JsNamedProperties>>>,
+    /// The same value, for classes holding on to JS values
+    references: Option<Rc<RefCell<dyn JsHostReferences>>>,
+}
+
+impl JsHostObject {
+    pub fn new<T: JsHostClass>(value: T) -> Self {
+        Self {
+            class_name: T::NAME,
+            value: Rc::new(RefCell::new(value)),
+            named_properties: None,
+            references: None,
+        }
+    }
+
+    pub fn with_named_properties<T: JsHostClass + JsNamedProperties>(value: T) -> Self {
+        let value = Rc::new(RefCell::new(value));
+        Self {
+            class_name: T::NAME,
+            value: value.clone(),
+            named_properties: Some(value),
+            references: None,
+        }
+    }
+
+    pub fn with_references<T: JsHostClass + JsHostReferences>(value: T) -> Self {
+        let value = Rc::new(RefCell::new(value));
+        Self {
+            class_name: T::NAME,
+            value: value.clone(),
+            named_properties: None,
+            references: Some(value),
+        }
+    }
+
+    /// Whether the value is of the class
+    pub fn is<T: JsHostClass>(&self) -> bool {
+        self.class_name == T::NAME
+    }
+
+    /// The JS values the value holds on to, for classes that implement [JsHostReferences]
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.references
+            .as_ref()
+            .map(|references| references.borrow().get_referenced_nodes())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for JsHostObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsHostObject")
+            .field("class_name", &self.class_name)
+            .finish()
+    }
+}
+
+impl PartialEq for JsHostObject {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.value, &other.value)
+    }
+}
+
+/// Runs a function with the host object `this` refers to, throwing a `TypeError` when it is not a
+/// value of the class, such as when a method is called on another object
+pub fn with_host_object<T: JsHostClass, R>(
+    closure_context: &mut JsClosureContext,
+    this: &JsValueNode,
+    func: impl FnOnce(&mut T, &mut JsClosureContext) -> R,
+) -> Result<R, JsValueNode> {
+    let value = match this.get_ref() {
+        JsValue::HostObject(object) => object.value.clone(),
+        _ => return Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    };
+    let mut value = value.borrow_mut();
+    match value.downcast_mut::<T>() {
+        Some(value) => Ok(func(value, closure_context)),
+        None => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// The named properties of a value, for host objects of classes that have them
+pub fn get_named_properties(value: &JsValueNode) -> Option<Rc<RefCell<dyn JsNamedProperties>>> {
+    match value.get_ref() {
+        JsValue::HostObject(object) => object.named_properties.clone(),
+        _ => None,
+    }
+}
+
+/// Whether the key is a named property that is not hidden by a member of the prototype chain
+/// <https://webidl.spec.whatwg.org/#legacy-platform-object-property-visibility>
+fn is_named_property_visible(
+    value: &JsValueNode,
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(name) = key.as_str() else {
+        return Ok(false);
+    };
+    if named_properties.borrow().get_named_property(name).is_none() {
+        return Ok(false);
+    }
+    match internal_get_prototype_of(value, closure_context)? {
+        Some(prototype) => Ok(!internal_has_property(&prototype, key, closure_context)?),
+        None => Ok(true),
+    }
+}
+
+/// [[GetOwnProperty]] of host objects with named properties, which are writable, enumerable, and
+/// configurable
+pub fn host_get_own_property(
+    value: &JsValueNode,
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsProperty>, JsValueNode> {
+    if !is_named_property_visible(value, named_properties, key, closure_context)? {
+        return Ok(None);
+    }
+    let property = key
+        .as_str()
+        .and_then(|name| named_properties.borrow().get_named_property(name))
+        .map(|property| {
+            JsProperty::data(JsValue::string_rc(&closure_context.nodes_graph, property))
+        });
+    Ok(property)
+}
+
+/// [[Set]] of host objects with named properties, which assigns any string key as a named property
+/// when the object is the receiver, even those hidden by the prototype chain. Returns `None` when
+/// the ordinary assignment applies instead.
+pub fn host_set(
+    target: &JsValueNode,
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    key: &JsPropertyKey,
+    value: &JsValueNode,
+    receiver: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<bool>, JsValueNode> {
+    let Some(name) = key.as_str() else {
+        return Ok(None);
+    };
+    if !target.is_same_ref(receiver) {
+        return Ok(None);
+    }
+    // Converted before borrowing the value, in case converting runs a script using it
+    let value = String::from_js_value(value, closure_context)?;
+    named_properties
+        .borrow_mut()
+        .set_named_property(name, value, closure_context)?;
+    Ok(Some(true))
+}
+
+/// [[DefineOwnProperty]] of host objects with named properties, where defining a data property
+/// assigns the named property and accessors cannot be defined. Returns `None` for symbols, which
+/// are defined as they are on other host objects.
+pub fn host_define_own_property(
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    key: &JsPropertyKey,
+    descriptor: &JsPropertyDescriptor,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<bool>, JsValueNode> {
+    let Some(name) = key.as_str() else {
+        return Ok(None);
+    };
+    if descriptor.is_accessor() {
+        return Ok(Some(false));
+    }
+    let value = descriptor
+        .value
+        .clone()
+        .unwrap_or_else(|| JsValue::undefined_rc(&closure_context.nodes_graph));
+    let value = String::from_js_value(&value, closure_context)?;
+    named_properties
+        .borrow_mut()
+        .set_named_property(name, value, closure_context)?;
+    Ok(Some(true))
+}
+
+/// [[Delete]] of host objects with named properties, which deletes the named property when it is
+/// visible
+pub fn host_delete(
+    value: &JsValueNode,
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if is_named_property_visible(value, named_properties, key, closure_context)? {
+        if let Some(name) = key.as_str() {
+            named_properties.borrow_mut().delete_named_property(name);
+        }
+    }
+    Ok(true)
+}
+
+/// [[OwnPropertyKeys]] of host objects with named properties, which are the visible names
+pub fn host_own_keys(
+    value: &JsValueNode,
+    named_properties: &Rc<RefCell<dyn JsNamedProperties>>,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsPropertyKey>, JsValueNode> {
+    let names = named_properties.borrow().get_supported_property_names();
+    let mut keys = vec![];
+    for key in names.into_iter().map(JsPropertyKey::from) {
+        if is_named_property_visible(value, named_properties, &key, closure_context)? {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+/// Builds the prototype of a host class from its methods and its accessors, which are (Name,
+/// Getter, Setter)
+pub fn build_host_prototype(
+    parent: JsValueNode,
+    methods: Vec<(&str, JsHostFunction)>,
+    accessors: Vec<(&str, Option<JsHostFunction>, Option<JsHostFunction>)>,
+) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let mut prototype = build_prototype(parent, []);
+    let build_function =
+        |name: &str, func: JsHostFunction| build_native_function(&node_graph, &name.into(), func);
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (name, func) in methods {
+            let function = build_function(name, func);
+            properties.insert_property(
+                name.into(),
+                JsProperty::with_attributes(function, true, false, true),
+            );
+        }
+        for (name, get, set) in accessors {
+            let get = get.map(|func| build_function(&format!("get {name}"), func));
+            let set = set.map(|func| build_function(&format!("set {name}"), func));
+            properties.insert_property(
+                name.into(),
+                JsProperty {
+                    value: JsPropertyValue::Accessor { get, set },
+                    enumerable: false,
+                    configurable: true,
+                },
+            );
+        }
+    }
+    prototype
+}
+
+/// Builds the constructor of a host class, which creates its values from the arguments when called
+/// with `new` and throws a `TypeError` otherwise
+pub fn build_host_constructor<T: JsHostClass>(
+    closure_context: &mut JsClosureContext,
+    construct: fn(&mut JsClosureContext, &[JsValueNode]) -> Result<T, JsValueNode>,
+) -> JsValueNode {
+    build_constructor::<T>(closure_context, move |closure_context, args| {
+        let value = construct(closure_context, args)?;
+        Ok(closure_context.create_host_object(value))
+    })
+}
+
+/// Builds the constructor of a host class holding on to JS values. See [build_host_constructor].
+pub fn build_host_constructor_with_references<T: JsHostClass + JsHostReferences>(
+    closure_context: &mut JsClosureContext,
+    construct: fn(&mut JsClosureContext, &[JsValueNode]) -> Result<T, JsValueNode>,
+) -> JsValueNode {
+    build_constructor::<T>(closure_context, move |closure_context, args| {
+        let value = construct(closure_context, args)?;
+        Ok(closure_context.create_host_object_with_references(value))
+    })
+}
+
+fn build_constructor<T: JsHostClass>(
+    closure_context: &mut JsClosureContext,
+    create: impl Fn(&mut JsClosureContext, &[JsValueNode]) -> JsFunctionResult + 'static,
+) -> JsValueNode {
+    closure_context.global_prototypes.register_host_class::<T>();
+    let prototype = closure_context.global_prototypes.host_classes[T::NAME].clone();
+    let mut constructor = build_native_function(
+        &closure_context.nodes_graph,
+        &T::NAME.into(),
+        move |closure_context, this, args| {
+            if matches!(this.get_ref(), JsValue::Undefined) {
+                return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+            }
+            create(closure_context, args)
+        },
+    );
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
+
+pub type JsHostFunction =
+    fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult;
+
+/// Converts the arguments of host functions, throwing a `TypeError` when a required argument is
+/// missing or cannot be converted
+pub trait FromJsValue: Sized {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode>;
+
+    fn from_js_argument(
+        args: &[JsValueNode],
+        index: usize,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        match args.get(index) {
+            Some(value) => Self::from_js_value(value, closure_context),
+            None => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        }
+    }
+}
+
+impl FromJsValue for JsValueNode {
+    fn from_js_value(
+        value: &JsValueNode,
+        _closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        Ok(value.clone())
+    }
+
+    fn from_js_argument(
+        args: &[JsValueNode],
+        index: usize,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        Ok(args
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| JsValue::undefined_rc(&closure_context.nodes_graph)))
+    }
+}
+
+/// Missing and `undefined` arguments are `None`
+impl<T: FromJsValue> FromJsValue for Option<T> {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        match value.get_ref() {
+            JsValue::Undefined => Ok(None),
+            _ => T::from_js_value(value, closure_context).map(Some),
+        }
+    }
+
+    fn from_js_argument(
+        args: &[JsValueNode],
+        index: usize,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        match args.get(index) {
+            Some(value) => Self::from_js_value(value, closure_context),
+            None => Ok(None),
+        }
+    }
+}
+
+impl FromJsValue for String {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        match value.get_ref() {
+            JsValue::Symbol(_) => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+            value => Ok(value.to_string()),
+        }
+    }
+}
+
+impl FromJsValue for f64 {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        to_number(value, closure_context)
+    }
+}
+
+/// Numbers wrap around as `unsigned long` does in Web IDL
+impl FromJsValue for u32 {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        let number = to_number(value, closure_context)?;
+        if !number.is_finite() {
+            return Ok(0);
+        }
+        Ok(number.trunc().rem_euclid(2f64.powi(32)) as u32)
+    }
+}
+
+/// Numbers wrap around as `long` does in Web IDL
+impl FromJsValue for i32 {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        u32::from_js_value(value, closure_context).map(|number| number as i32)
+    }
+}
+
+impl FromJsValue for bool {
+    fn from_js_value(
+        value: &JsValueNode,
+        _closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        Ok(value.map_value(|value| value.into()))
+    }
+}
+
+/// Converts the results of host functions
+pub trait IntoJsValue {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode;
+}
+
+impl IntoJsValue for JsValueNode {
+    fn into_js_value(self, _closure_context: &JsClosureContext) -> JsValueNode {
+        self
+    }
+}
+
+/// `None` is `null`
+impl<T: IntoJsValue> IntoJsValue for Option<T> {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        match self {
+            Some(value) => value.into_js_value(closure_context),
+            None => JsValue::null_rc(&closure_context.nodes_graph),
+        }
+    }
+}
+
+impl IntoJsValue for () {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        JsValue::undefined_rc(&closure_context.nodes_graph)
+    }
+}
+
+impl IntoJsValue for String {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        JsValue::string_rc(&closure_context.nodes_graph, self)
+    }
+}
+
+impl IntoJsValue for &str {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        JsValue::str_rc(&closure_context.nodes_graph, self)
+    }
+}
+
+impl IntoJsValue for f64 {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        JsValue::number_rc(&closure_context.nodes_graph, self)
+    }
+}
+
+impl IntoJsValue for u32 {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        f64::from(self).into_js_value(closure_context)
+    }
+}
+
+impl IntoJsValue for i32 {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        f64::from(self).into_js_value(closure_context)
+    }
+}
+
+impl IntoJsValue for bool {
+    fn into_js_value(self, closure_context: &JsClosureContext) -> JsValueNode {
+        JsValue::bool_rc(&closure_context.nodes_graph, self)
+    }
+}
@@ -0,0 +1,318 @@
+//! The internal methods every object has, through which all property access goes. Proxies run
+//! their traps for these, and host objects with named properties expose them, while other values
+//! use the ordinary behavior of objects, arrays, and the other built-in values.
+//! <https://tc39.es/ecma262/#sec-object-internal-methods-and-internal-slots>
+
+use super::{
+    define_own_property, get_named_properties, get_own_property, get_prototype_of,
+    host_define_own_property, host_delete, host_get_own_property, host_own_keys, host_set,
+    invoke_function, proxy_define_own_property, proxy_delete, proxy_get, proxy_get_own_property,
+    proxy_get_prototype_of, proxy_has, proxy_is_extensible, proxy_own_keys,
+    proxy_prevent_extensions, proxy_set, proxy_set_prototype_of, JsClosureContext,
+    JsFunctionResult, JsProperty, JsPropertyDescriptor, JsPropertyKey, JsPropertyValue, JsValue,
+    JsValueNode, PROTOTYPE_MEMBER,
+};
+
+/// [[GetPrototypeOf]], the value a value inherits members from, or `None` at the end of the
+/// prototype chain
+pub fn internal_get_prototype_of(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsValueNode>, JsValueNode> {
+    match value.get_ref() {
+        JsValue::Proxy(proxy) => proxy_get_prototype_of(&proxy.clone(), closure_context),
+        value => Ok(get_prototype_of(value, closure_context)),
+    }
+}
+
+/// [[SetPrototypeOf]], where `None` makes the value inherit from nothing. Only plain objects can
+/// change their prototype, and only while extensible and without forming a cycle. Returns whether
+/// the value has the prototype afterwards.
+pub fn internal_set_prototype_of(
+    target: &JsValueNode,
+    prototype: Option<JsValueNode>,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if let JsValue::Proxy(proxy) = target.get_ref() {
+        return proxy_set_prototype_of(&proxy.clone(), prototype, closure_context);
+    }
+
+    let current = get_prototype_of(target.get_ref(), closure_context);
+    if is_same_prototype(&current, &prototype) {
+        return Ok(true);
+    }
+    // Proxies end the search for cycles, since their prototype is only known by running a trap
+    let mut ancestor = prototype.clone();
+    while let Some(node) = ancestor {
+        if node.is_same_ref(target) {
+            return Ok(false);
+        }
+        if let JsValue::Proxy(_) = node.get_ref() {
+            break;
+        }
+        ancestor = get_prototype_of(node.get_ref(), closure_context);
+    }
+
+    let parent = prototype.unwrap_or_else(|| JsValue::null_rc(&closure_context.nodes_graph));
+    let mut target = target.clone();
+    match target.get_mut() {
+        JsValue::Object(properties) if properties.is_extensible() => {
+            properties.insert_property(
+                PROTOTYPE_MEMBER.into(),
+                JsProperty::with_attributes(parent, true, false, true),
+            );
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Whether two prototypes, as returned by [internal_get_prototype_of], are the same
+pub fn is_same_prototype(a: &Option<JsValueNode>, b: &Option<JsValueNode>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.is_same_ref(b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// [[IsExtensible]]. Only objects and functions can be made non-extensible, so other objects are
+/// always extensible and primitives never are.
+pub fn internal_is_extensible(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    match value.get_ref() {
+        JsValue::Proxy(proxy) => proxy_is_extensible(&proxy.clone(), closure_context),
+        JsValue::Object(properties) | JsValue::Function(_, properties) => {
+            Ok(properties.is_extensible())
+        }
+        value => Ok(value.is_object()),
+    }
+}
+
+/// [[PreventExtensions]], returning whether the value is no longer extensible
+pub fn internal_prevent_extensions(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let mut value = value.clone();
+    match value.get_mut() {
+        JsValue::Proxy(proxy) => {
+            let proxy = proxy.clone();
+            proxy_prevent_extensions(&proxy, closure_context)
+        }
+        JsValue::Object(properties) | JsValue::Function(_, properties) => {
+            properties.prevent_extensions();
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// [[GetOwnProperty]]
+pub fn internal_get_own_property(
+    value: &JsValueNode,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsProperty>, JsValueNode> {
+    if let Some(named_properties) = get_named_properties(value) {
+        return host_get_own_property(value, &named_properties, key, closure_context);
+    }
+    match value.get_ref() {
+        JsValue::Proxy(proxy) => proxy_get_own_property(&proxy.clone(), key, closure_context),
+        value => Ok(get_own_property(value, key, &closure_context.nodes_graph)),
+    }
+}
+
+/// [[DefineOwnProperty]], returning whether the definition was allowed
+pub fn internal_define_own_property(
+    target: &mut JsValueNode,
+    key: JsPropertyKey,
+    descriptor: JsPropertyDescriptor,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if let Some(named_properties) = get_named_properties(target) {
+        let defined =
+            host_define_own_property(&named_properties, &key, &descriptor, closure_context)?;
+        if let Some(defined) = defined {
+            return Ok(defined);
+        }
+    }
+    match target.get_ref() {
+        JsValue::Proxy(proxy) => {
+            proxy_define_own_property(&proxy.clone(), key, descriptor, closure_context)
+        }
+        _ => define_own_property(target, key, descriptor, closure_context),
+    }
+}
+
+/// [[HasProperty]], whether the value or a value on its prototype chain has the property
+pub fn internal_has_property(
+    value: &JsValueNode,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if let JsValue::Proxy(proxy) = value.get_ref() {
+        return proxy_has(&proxy.clone(), key, closure_context);
+    }
+    if internal_get_own_property(value, key, closure_context)?.is_some() {
+        return Ok(true);
+    }
+    match get_prototype_of(value.get_ref(), closure_context) {
+        Some(parent) => internal_has_property(&parent, key, closure_context),
+        None => Ok(false),
+    }
+}
+
+/// [[Get]], reading a member found on the value or its prototype chain. Getters are invoked with
+/// the receiver as `this`, which is the value the member was read from.
+pub fn internal_get(
+    value: &JsValueNode,
+    key: &JsPropertyKey,
+    receiver: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    if let JsValue::Proxy(proxy) = value.get_ref() {
+        return proxy_get(&proxy.clone(), key, receiver, closure_context);
+    }
+    let property = internal_get_own_property(value, key, closure_context)?;
+    match property.map(|property| property.value) {
+        Some(JsPropertyValue::Data { value, .. }) => Ok(value),
+        Some(JsPropertyValue::Accessor { get: Some(get), .. }) => {
+            invoke_function(closure_context, &get, receiver.clone(), &[])
+        }
+        Some(JsPropertyValue::Accessor { get: None, .. }) => {
+            Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+        }
+        None => match get_prototype_of(value.get_ref(), closure_context) {
+            Some(parent) => internal_get(&parent, key, receiver, closure_context),
+            None => Ok(JsValue::undefined_rc(&closure_context.nodes_graph)),
+        },
+    }
+}
+
+/// [[Set]], assigning a member where setters found on the value or its prototype chain are invoked
+/// with the receiver as `this`. Otherwise the member is defined on the receiver, which is the
+/// value assigned to. Returns whether the assignment took effect, which it does not for read-only
+/// properties, non-extensible receivers, or primitive receivers.
+pub fn internal_set(
+    target: &JsValueNode,
+    key: &JsPropertyKey,
+    value: JsValueNode,
+    receiver: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if let JsValue::Proxy(proxy) = target.get_ref() {
+        return proxy_set(&proxy.clone(), key, value, receiver, closure_context);
+    }
+    if let Some(named_properties) = get_named_properties(target) {
+        let assigned = host_set(
+            target,
+            &named_properties,
+            key,
+            &value,
+            receiver,
+            closure_context,
+        )?;
+        if let Some(assigned) = assigned {
+            return Ok(assigned);
+        }
+    }
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let property = match get_own_property(target.get_ref(), key, &nodes_graph) {
+        Some(property) => property,
+        None => match get_prototype_of(target.get_ref(), closure_context) {
+            Some(parent) => {
+                return internal_set(&parent, key, value, receiver, closure_context);
+            }
+            None => JsProperty::data(JsValue::undefined_rc(&nodes_graph)),
+        },
+    };
+
+    match property.value {
+        JsPropertyValue::Accessor { set: Some(set), .. } => {
+            invoke_function(closure_context, &set, receiver.clone(), &[value])?;
+            Ok(true)
+        }
+        JsPropertyValue::Accessor { set: None, .. }
+        | JsPropertyValue::Data {
+            writable: false, ..
+        } => Ok(false),
+        JsPropertyValue::Data { writable: true, .. } => {
+            if !receiver.get_ref().is_object() {
+                return Ok(false);
+            }
+            let mut receiver = receiver.clone();
+            let descriptor = match internal_get_own_property(&receiver, key, closure_context)? {
+                Some(JsProperty {
+                    value: JsPropertyValue::Data { writable: true, .. },
+                    ..
+                }) => JsPropertyDescriptor {
+                    value: Some(value),
+                    ..Default::default()
+                },
+                Some(_) => return Ok(false),
+                None => JsProperty::data(value).into(),
+            };
+            internal_define_own_property(&mut receiver, key.clone(), descriptor, closure_context)
+        }
+    }
+}
+
+/// [[Delete]], returning whether the value no longer has the property. Non-configurable properties
+/// cannot be deleted. Arrays have no holes, so their deleted elements become `undefined`.
+pub fn internal_delete(
+    target: &JsValueNode,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if let JsValue::Proxy(proxy) = target.get_ref() {
+        return proxy_delete(&proxy.clone(), key, closure_context);
+    }
+    if let Some(named_properties) = get_named_properties(target) {
+        return host_delete(target, &named_properties, key, closure_context);
+    }
+    let nodes_graph = closure_context.nodes_graph.clone();
+    // The prototype is not an own property, even though objects store it as one
+    if key.as_str() == Some(PROTOTYPE_MEMBER) {
+        return Ok(true);
+    }
+    let Some(property) = get_own_property(target.get_ref(), key, &nodes_graph) else {
+        return Ok(true);
+    };
+    if !property.configurable {
+        return Ok(false);
+    }
+
+    let mut target = target.clone();
+    match target.get_mut() {
+        JsValue::Array(elements, _) if key.as_index().is_some() => {
+            if let Some(element) = key.as_index().and_then(|index| elements.get_mut(index)) {
+                *element = JsValue::undefined_rc(&nodes_graph);
+            }
+            Ok(true)
+        }
+        JsValue::Object(properties)
+        | JsValue::Function(_, properties)
+        | JsValue::Array(_, properties) => {
+            properties.remove(key);
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// [[OwnPropertyKeys]], the keys of all own properties of a value
+pub fn internal_own_property_keys(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsPropertyKey>, JsValueNode> {
+    if let Some(named_properties) = get_named_properties(value) {
+        return host_own_keys(value, &named_properties, closure_context);
+    }
+    match value.get_ref() {
+        JsValue::Proxy(proxy) => proxy_own_keys(&proxy.clone(), closure_context),
+        value => Ok(value.own_keys()),
+    }
+}
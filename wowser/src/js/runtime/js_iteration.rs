@@ -0,0 +1,206 @@
+use super::{
+    get_member_from_prototype_chain, invoke_function, to_number, JsClosureContext, JsExpression,
+    JsPropertyKey, JsSymbol, JsValue, JsValueGraph, JsValueNode, JsWellKnownSymbol, LENGTH_MEMBER,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsIterationKind {
+    Keys,
+    Values,
+    Entries,
+}
+
+/// The state of iterating over a built-in array, typed array, string, or keyed collection
+#[derive(Debug, PartialEq)]
+pub struct JsIterator {
+    source: JsValueNode,
+    kind: JsIterationKind,
+    position: usize,
+    done: bool,
+}
+
+impl JsIterator {
+    pub fn new(source: JsValueNode, kind: JsIterationKind) -> Self {
+        Self {
+            source,
+            kind,
+            position: 0,
+            done: false,
+        }
+    }
+
+    /// Advances the iterator. Once exhausted, it stays exhausted even if the source grows.
+    pub fn next(&mut self, node_graph: &JsValueGraph) -> Option<JsValueNode> {
+        if self.done {
+            return None;
+        }
+
+        let position = self.position;
+        let entry = match self.source.get_ref() {
+            JsValue::Array(elements, _) => elements.get(position).map(|element| {
+                self.position += 1;
+                (
+                    JsValue::number_rc(node_graph, position as f64),
+                    element.clone(),
+                )
+            }),
+            JsValue::TypedArray(array) => array.get(position).map(|element| {
+                self.position += 1;
+                (
+                    JsValue::number_rc(node_graph, position as f64),
+                    element.to_value_rc(node_graph),
+                )
+            }),
+            JsValue::String(string) => string.chars().nth(position).map(|c| {
+                self.position += 1;
+                let c = JsValue::string_rc(node_graph, c.to_string());
+                (c.clone(), c)
+            }),
+            JsValue::Collection(collection) => {
+                collection
+                    .next_entry(position)
+                    .map(|(position, (key, value))| {
+                        self.position = position + 1;
+                        (key.clone(), value.clone())
+                    })
+            }
+            _ => None,
+        };
+
+        let value = entry.map(|(key, value)| match self.kind {
+            JsIterationKind::Keys => key,
+            JsIterationKind::Values => value,
+            JsIterationKind::Entries => JsValue::array_rc(node_graph, vec![key, value]),
+        });
+        self.done = value.is_none();
+        value
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![self.source.clone()]
+    }
+}
+
+/// Builds the `{ value, done }` object returned by an iterator's `next` method
+pub fn iterator_result_rc(node_graph: &JsValueGraph, value: Option<JsValueNode>) -> JsValueNode {
+    let done = value.is_none();
+    let value = value.unwrap_or_else(|| JsValue::undefined_rc(node_graph));
+    JsValue::object_rc(
+        node_graph,
+        [
+            (JsPropertyKey::from("value"), value),
+            (
+                JsPropertyKey::from("done"),
+                JsValue::bool_rc(node_graph, done),
+            ),
+        ]
+        .into(),
+    )
+}
+
+/// Drains an iterable value into its elements, as done by spread syntax and array destructuring.
+/// Non-iterable values throw a `TypeError`.
+pub fn collect_iterable(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsValueNode>, JsValueNode> {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    match value.get_ref() {
+        JsValue::Array(elements, _) => return Ok(elements.clone()),
+        JsValue::TypedArray(array) => {
+            return Ok(array
+                .values()
+                .into_iter()
+                .map(|value| value.to_value_rc(&nodes_graph))
+                .collect())
+        }
+        JsValue::String(string) => {
+            return Ok(string
+                .chars()
+                .map(|c| JsValue::string_rc(&nodes_graph, c.to_string()))
+                .collect())
+        }
+        JsValue::Undefined | JsValue::Null => return Err(JsValue::type_error_rc(&nodes_graph)),
+        _ => {}
+    }
+
+    let iterator_key = JsSymbol::well_known(JsWellKnownSymbol::Iterator).into();
+    let iterator_method = get_member_from_prototype_chain(value, &iterator_key, closure_context)?;
+    let iterator = invoke_function(closure_context, &iterator_method, value.clone(), &[])?;
+    let next =
+        get_member_from_prototype_chain(&iterator, &JsPropertyKey::from("next"), closure_context)?;
+
+    let mut values = vec![];
+    loop {
+        let result = invoke_function(closure_context, &next, iterator.clone(), &[])?;
+        if !result.get_ref().is_object() {
+            return Err(JsValue::type_error_rc(&nodes_graph));
+        }
+        let done = get_member_from_prototype_chain(
+            &result,
+            &JsPropertyKey::from("done"),
+            closure_context,
+        )?;
+        if bool::from(done.get_ref()) {
+            return Ok(values);
+        }
+        values.push(get_member_from_prototype_chain(
+            &result,
+            &JsPropertyKey::from("value"),
+            closure_context,
+        )?);
+    }
+}
+
+/// Reads the elements of an array-like object, one with a `length` and indexed members, as done
+/// for the arguments list of `Reflect.apply`. Values that are not objects throw a `TypeError`.
+pub fn create_list_from_array_like(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsValueNode>, JsValueNode> {
+    match value.get_ref() {
+        JsValue::Array(elements, _) => return Ok(elements.clone()),
+        value if !value.is_object() => {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+        }
+        _ => {}
+    }
+
+    let length = get_member_from_prototype_chain(value, &LENGTH_MEMBER.into(), closure_context)?;
+    let length = to_number(&length, closure_context)?;
+    let length = if length > 0.0 { length as usize } else { 0 };
+    let mut elements = vec![];
+    for index in 0..length {
+        closure_context.step()?;
+        let key = JsPropertyKey::from(index.to_string());
+        elements.push(get_member_from_prototype_chain(
+            value,
+            &key,
+            closure_context,
+        )?);
+    }
+    Ok(elements)
+}
+
+/// Evaluates a list of expressions where entries may be spread, such as array elements and
+/// function arguments. Holes evaluate to `undefined`.
+pub fn evaluate_spreadable<'a, I>(
+    expressions: I,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsValueNode>, JsValueNode>
+where
+    I: IntoIterator<Item = Option<&'a JsExpression>>,
+{
+    let mut values = vec![];
+    for expression in expressions {
+        match expression {
+            Some(JsExpression::Spread(iterable)) => {
+                let iterable = iterable.run(closure_context)?;
+                values.extend(collect_iterable(&iterable, closure_context)?);
+            }
+            Some(expression) => values.push(expression.run(closure_context)?),
+            None => values.push(JsValue::undefined_rc(&closure_context.nodes_graph)),
+        }
+    }
+    Ok(values)
+}
@@ -0,0 +1,82 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The number of nested scopes, including the global scope, beyond which invoking a user defined
+/// function throws a stack overflow error by default
+pub const MAX_CLOSURE_DEPTH: usize = 255;
+
+/// Limits on the scripts run in a closure context, so untrusted scripts cannot hang or exhaust the
+/// browser
+#[derive(Clone, Debug)]
+pub struct JsLimits {
+    /// The number of steps a run may take before it is terminated. Each statement and expression
+    /// walked, or instruction run, is a step.
+    pub max_steps: Option<u64>,
+    /// The number of nodes the graph may hold. Garbage is collected between the top level
+    /// statements of a document, and before the scripts, event listeners and callbacks the browser
+    /// runs, once the graph holds more than half of it, as statements growing the graph past it
+    /// throw a `RangeError`.
+    pub max_heap_size: Option<usize>,
+    /// The number of nested scopes, including the global scope, beyond which invoking a user
+    /// defined function throws a stack overflow error
+    pub max_call_depth: usize,
+}
+
+impl Default for JsLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            max_heap_size: None,
+            max_call_depth: MAX_CLOSURE_DEPTH,
+        }
+    }
+}
+
+/// Terminates the script being run from any thread, such as when the user stops a page
+#[derive(Clone, Debug, Default)]
+pub struct JsInterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl JsInterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Terminates the script being run at its next step, along with the scripts run after it until
+    /// the handle is cleared
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// Lets scripts run again after they were interrupted
+    pub fn clear(&self) {
+        self.interrupted.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_interrupt_from_thread() {
+        let handle = JsInterruptHandle::new();
+        assert!(!handle.is_interrupted());
+        let other_handle = handle.clone();
+        thread::spawn(move || other_handle.interrupt())
+            .join()
+            .unwrap();
+        assert!(handle.is_interrupted());
+        handle.clear();
+        assert!(!handle.is_interrupted());
+    }
+}
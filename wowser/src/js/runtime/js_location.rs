@@ -0,0 +1,160 @@
+use wowser_macros::js_class;
+
+use crate::net::UrlRecord;
+
+use super::{
+    get_current_url, navigate, resolve_navigation_url, traverse_history, JsClosureContext,
+    JsValueNode,
+};
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Location>
+///
+/// Setting the URL or any part of it navigates to the new URL.
+pub struct JsLocation;
+
+impl JsLocation {
+    /// Navigates to the current URL with a part changed, as the part setters do
+    fn navigate_with(closure_context: &mut JsClosureContext, update: impl FnOnce(&mut UrlRecord)) {
+        let mut url = get_current_url(closure_context);
+        update(&mut url);
+        navigate(closure_context, url, false);
+    }
+}
+
+#[js_class(name = "Location")]
+impl JsLocation {
+    #[js_method(getter)]
+    fn href(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).to_string()
+    }
+
+    /// Navigates to the URL, resolved against the current one
+    #[js_method(setter)]
+    fn set_href(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        let url = resolve_navigation_url(closure_context, &value)?;
+        navigate(closure_context, url, false);
+        Ok(())
+    }
+
+    #[js_method(getter)]
+    fn origin(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context)
+            .origin()
+            .unwrap_or_else(|| "null".to_string())
+    }
+
+    #[js_method(getter)]
+    fn protocol(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).protocol()
+    }
+
+    #[js_method(setter)]
+    fn set_protocol(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_protocol(&value));
+    }
+
+    #[js_method(getter)]
+    fn host(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).host()
+    }
+
+    #[js_method(setter)]
+    fn set_host(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_host(&value));
+    }
+
+    #[js_method(getter)]
+    fn hostname(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).hostname()
+    }
+
+    #[js_method(setter)]
+    fn set_hostname(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_hostname(&value));
+    }
+
+    #[js_method(getter)]
+    fn port(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).port()
+    }
+
+    #[js_method(setter)]
+    fn set_port(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_port(&value));
+    }
+
+    #[js_method(getter)]
+    fn pathname(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).pathname()
+    }
+
+    #[js_method(setter)]
+    fn set_pathname(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_pathname(&value));
+    }
+
+    #[js_method(getter)]
+    fn search(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).search()
+    }
+
+    #[js_method(setter)]
+    fn set_search(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        Self::navigate_with(closure_context, |url| url.set_search(&value));
+    }
+
+    #[js_method(getter)]
+    fn hash(&self, closure_context: &mut JsClosureContext) -> String {
+        get_current_url(closure_context).hash()
+    }
+
+    /// Scrolls to the fragment without leaving the document, unless it is the current one. Unlike
+    /// `URL`, an empty hash is kept, which scrolls to the top.
+    #[js_method(setter)]
+    fn set_hash(&mut self, closure_context: &mut JsClosureContext, value: String) {
+        let current_url = get_current_url(closure_context);
+        let mut url = current_url.clone();
+        url.set_hash(&value);
+        url.fragment.get_or_insert_with(String::new);
+        if url.fragment != current_url.fragment {
+            navigate(closure_context, url, false);
+        }
+    }
+
+    #[js_method]
+    fn assign(
+        &self,
+        closure_context: &mut JsClosureContext,
+        url: String,
+    ) -> Result<(), JsValueNode> {
+        let url = resolve_navigation_url(closure_context, &url)?;
+        navigate(closure_context, url, false);
+        Ok(())
+    }
+
+    /// Navigates to the URL in place of the current entry, so going back skips the current page
+    #[js_method]
+    fn replace(
+        &self,
+        closure_context: &mut JsClosureContext,
+        url: String,
+    ) -> Result<(), JsValueNode> {
+        let url = resolve_navigation_url(closure_context, &url)?;
+        navigate(closure_context, url, true);
+        Ok(())
+    }
+
+    #[js_method]
+    fn reload(&self, closure_context: &mut JsClosureContext) {
+        traverse_history(closure_context, 0);
+    }
+
+    #[js_method(name = "toString")]
+    fn serialize(&self, closure_context: &mut JsClosureContext) -> String {
+        self.href(closure_context)
+    }
+}
@@ -0,0 +1,775 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::js::{
+    api::module::{resolve_module_specifier, ModuleLoader, UrlModuleLoader},
+    parse_js_statements,
+};
+
+use super::{
+    bind_identifier, JsClosure, JsClosureContext, JsCompiler, JsDeclarationKind, JsEngine,
+    JsExpression, JsFunction, JsFunctionResult, JsProperties, JsProperty, JsStatement,
+    JsStatementResult, JsValue, JsValueNode, JsVm,
+};
+
+/// The name a module's default export is imported by
+pub const DEFAULT_EXPORT_NAME: &str = "default";
+
+/// The reference a default export that is not a declaration is bound to in the module's scope,
+/// which scripts cannot name
+pub const DEFAULT_EXPORT_REFERENCE: &str = "*default*";
+
+/// A name bound by an `import` declaration
+#[derive(Debug, PartialEq)]
+pub enum JsImportBinding {
+    /// (Imported name, Local name)
+    Named(String, String),
+    /// (Local name) bound to the namespace of the module
+    Namespace(String),
+}
+
+/// `import ... from "specifier"`, or `import "specifier"` without any bindings
+#[derive(Debug, PartialEq)]
+pub struct JsImport {
+    pub specifier: String,
+    pub bindings: Vec<JsImportBinding>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsExport {
+    /// `export` of a declaration, under the names it declares
+    Declaration(Box<JsStatement>),
+    /// `export default` of a function or class declaration
+    DefaultDeclaration(Box<JsStatement>),
+    /// `export default` of an expression, bound to [DEFAULT_EXPORT_REFERENCE]
+    Default(JsExpression),
+    /// (Local name, Exported name) pairs
+    Names(Vec<(String, String)>),
+    /// (Specifier, (Imported name, Exported name) pairs)
+    From(String, Vec<(String, String)>),
+    /// (Specifier, Exported name of the namespace). Without a name, each name the module exports
+    /// other than its default export is exported.
+    All(String, Option<String>),
+}
+
+impl JsExport {
+    /// (Local name, Exported name) of the names exported from the module's own scope
+    fn get_local_exports(&self) -> Vec<(String, String)> {
+        match self {
+            Self::Declaration(declaration) => declaration
+                .get_declared_names()
+                .into_iter()
+                .map(|name| (name.clone(), name))
+                .collect(),
+            Self::DefaultDeclaration(declaration) => declaration
+                .get_declared_names()
+                .into_iter()
+                .map(|name| (name, DEFAULT_EXPORT_NAME.to_string()))
+                .collect(),
+            Self::Default(_expression) => vec![(
+                DEFAULT_EXPORT_REFERENCE.to_string(),
+                DEFAULT_EXPORT_NAME.to_string(),
+            )],
+            Self::Names(names) => names.clone(),
+            Self::From(..) | Self::All(..) => vec![],
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match self {
+            Self::Declaration(declaration) | Self::DefaultDeclaration(declaration) => {
+                declaration.get_referenced_nodes()
+            }
+            Self::Default(expression) => expression.get_referenced_nodes(),
+            Self::Names(_) | Self::From(..) | Self::All(..) => vec![],
+        }
+    }
+}
+
+/// The specifier of the module an `import` or `export ... from` requests
+fn get_requested_specifier(statement: &JsStatement) -> Option<&str> {
+    match statement {
+        JsStatement::Import(import) => Some(&import.specifier),
+        JsStatement::Export(JsExport::From(specifier, _) | JsExport::All(specifier, _)) => {
+            Some(specifier)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+enum JsModuleStatus {
+    /// Loaded, with the names it imports not yet checked
+    Linking,
+    /// Waiting to be run
+    Linked,
+    Evaluating,
+    Evaluated,
+    /// Linking or running threw the value, which is thrown again whenever the module is imported
+    Failed(JsValueNode),
+}
+
+/// Where the value of an exported name comes from
+#[derive(Clone, Debug, PartialEq)]
+enum JsExportBinding {
+    /// (Module URL, Name in the module's scope)
+    Local(String, String),
+    /// The namespace of the module with the URL
+    Namespace(String),
+}
+
+#[derive(Debug)]
+struct JsModule {
+    statements: Rc<Vec<JsStatement>>,
+    /// (Specifier, URL) of each module requested, in the order they are first requested
+    requests: Vec<(String, String)>,
+    status: JsModuleStatus,
+    /// The top level scope, which the closure context holds instead while it is entered
+    scope: Option<JsClosure>,
+    namespace: Option<JsValueNode>,
+}
+
+impl JsModule {
+    fn get_request(&self, specifier: &str) -> Option<&str> {
+        self.requests
+            .iter()
+            .find(|(requested, _url)| requested == specifier)
+            .map(|(_specifier, url)| url.as_str())
+    }
+
+    fn imports(&self) -> impl Iterator<Item = &JsImport> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                JsStatement::Import(import) => Some(import),
+                _ => None,
+            })
+    }
+
+    fn exports(&self) -> impl Iterator<Item = &JsExport> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                JsStatement::Export(export) => Some(export),
+                _ => None,
+            })
+    }
+}
+
+/// The modules of a document by URL. Modules are loaded with everything they import, linked, and
+/// then run after the modules they import. Modules importing each other in a cycle run in the
+/// order they are first imported, and can call each other's function declarations before they
+/// run.
+///
+/// Imported names are copies of the exported values rather than live bindings. They are updated
+/// whenever a module finishes running, or a function declared in one returns.
+#[derive(Debug)]
+pub struct JsModuleMap {
+    /// Fetches the source of modules
+    pub loader: Rc<dyn ModuleLoader>,
+    /// The URL that specifiers imported outside of modules are resolved against
+    pub base_url: Option<String>,
+    modules: HashMap<String, JsModule>,
+    /// The URL of the module each user defined function was declared in by the identifier of its
+    /// node. The node is kept so its identifier is not reused.
+    functions: HashMap<usize, (JsValueNode, String)>,
+}
+
+impl Default for JsModuleMap {
+    fn default() -> Self {
+        Self {
+            loader: Rc::new(UrlModuleLoader::default()),
+            base_url: None,
+            modules: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+impl JsModuleMap {
+    pub fn contains(&self, url: &str) -> bool {
+        self.modules.contains_key(url)
+    }
+
+    /// Follows an exported name through re-exports to the module it is declared in. Names that
+    /// are not exported, are exported by more than one `export *`, or are re-exported in a cycle
+    /// do not resolve.
+    fn resolve_export(
+        &self,
+        url: &str,
+        name: &str,
+        visited: &mut Vec<(String, String)>,
+    ) -> Option<JsExportBinding> {
+        let key = (url.to_string(), name.to_string());
+        if visited.contains(&key) {
+            return None;
+        }
+        visited.push(key);
+
+        let module = self.modules.get(url)?;
+        for export in module.exports() {
+            for (local, exported) in export.get_local_exports() {
+                if exported == name {
+                    return self.resolve_local(module, url, &local, visited);
+                }
+            }
+            match export {
+                JsExport::From(specifier, names) => {
+                    if let Some((imported, _exported)) =
+                        names.iter().find(|(_imported, exported)| exported == name)
+                    {
+                        return self.resolve_export(
+                            module.get_request(specifier)?,
+                            imported,
+                            visited,
+                        );
+                    }
+                }
+                JsExport::All(specifier, Some(exported)) if exported == name => {
+                    return Some(JsExportBinding::Namespace(
+                        module.get_request(specifier)?.to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        if name == DEFAULT_EXPORT_NAME {
+            return None;
+        }
+        let mut resolution = None;
+        for export in module.exports() {
+            if let JsExport::All(specifier, None) = export {
+                let binding = self.resolve_export(module.get_request(specifier)?, name, visited);
+                match (&resolution, binding) {
+                    (_, None) => {}
+                    (None, binding) => resolution = binding,
+                    (Some(existing), Some(binding)) if *existing != binding => return None,
+                    _ => {}
+                }
+            }
+        }
+        resolution
+    }
+
+    /// Resolves a name in a module's scope, which refers to another module when it was imported
+    fn resolve_local(
+        &self,
+        module: &JsModule,
+        url: &str,
+        local: &str,
+        visited: &mut Vec<(String, String)>,
+    ) -> Option<JsExportBinding> {
+        for import in module.imports() {
+            for binding in &import.bindings {
+                match binding {
+                    JsImportBinding::Named(imported, name) if name == local => {
+                        let request = module.get_request(&import.specifier)?;
+                        return self.resolve_export(request, imported, visited);
+                    }
+                    JsImportBinding::Namespace(name) if name == local => {
+                        let request = module.get_request(&import.specifier)?;
+                        return Some(JsExportBinding::Namespace(request.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some(JsExportBinding::Local(url.to_string(), local.to_string()))
+    }
+
+    /// The names a module exports, including those that may not resolve
+    fn get_exported_names(&self, url: &str, visited: &mut Vec<String>) -> Vec<String> {
+        if visited.iter().any(|visited_url| visited_url == url) {
+            return vec![];
+        }
+        visited.push(url.to_string());
+        let Some(module) = self.modules.get(url) else {
+            return vec![];
+        };
+
+        let mut names = vec![];
+        for export in module.exports() {
+            names.extend(
+                export
+                    .get_local_exports()
+                    .into_iter()
+                    .map(|(_local, exported)| exported),
+            );
+            match export {
+                JsExport::From(_specifier, pairs) => {
+                    names.extend(pairs.iter().map(|(_imported, exported)| exported.clone()));
+                }
+                JsExport::All(_specifier, Some(exported)) => names.push(exported.clone()),
+                _ => {}
+            }
+        }
+        for export in module.exports() {
+            let JsExport::All(specifier, None) = export else {
+                continue;
+            };
+            let Some(request) = module.get_request(specifier) else {
+                continue;
+            };
+            for name in self.get_exported_names(request, visited) {
+                if name != DEFAULT_EXPORT_NAME && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// (Local name, What it refers to) of each name a module imports
+    fn get_import_bindings(&self, url: &str) -> Vec<(String, Option<JsExportBinding>)> {
+        let Some(module) = self.modules.get(url) else {
+            return vec![];
+        };
+        module
+            .imports()
+            .flat_map(|import| {
+                let request = module.get_request(&import.specifier);
+                import.bindings.iter().map(move |binding| match binding {
+                    JsImportBinding::Named(imported, local) => (
+                        local.clone(),
+                        request.and_then(|request| {
+                            self.resolve_export(request, imported, &mut vec![])
+                        }),
+                    ),
+                    JsImportBinding::Namespace(local) => (
+                        local.clone(),
+                        request.map(|request| JsExportBinding::Namespace(request.to_string())),
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every name a module imports or re-exports from another module is exported by it
+    fn is_linkable(&self, url: &str) -> bool {
+        let Some(module) = self.modules.get(url) else {
+            return false;
+        };
+        let re_exports_resolve = module.exports().all(|export| match export {
+            JsExport::From(specifier, names) => names.iter().all(|(imported, _exported)| {
+                module
+                    .get_request(specifier)
+                    .and_then(|request| self.resolve_export(request, imported, &mut vec![]))
+                    .is_some()
+            }),
+            _ => true,
+        });
+        re_exports_resolve
+            && self
+                .get_import_bindings(url)
+                .iter()
+                .all(|(_local, binding)| binding.is_some())
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        let modules = self.modules.values().flat_map(|module| {
+            let failure = match &module.status {
+                JsModuleStatus::Failed(value) => Some(value.clone()),
+                _ => None,
+            };
+            module
+                .statements
+                .iter()
+                .flat_map(JsStatement::get_referenced_nodes)
+                .chain(
+                    module
+                        .scope
+                        .iter()
+                        .flat_map(JsClosure::get_referenced_nodes),
+                )
+                .chain(module.namespace.clone())
+                .chain(failure)
+        });
+        let functions = self
+            .functions
+            .values()
+            .map(|(function, _url)| function.clone());
+        modules.chain(functions).collect()
+    }
+}
+
+/// Imports the module a specifier refers to, loading, linking, and running it along with the
+/// modules it imports the first time. Specifiers are resolved against the module being run, or
+/// the base URL outside of modules. Results in the namespace of the module, rather than a promise
+/// of it as there are no promises.
+pub fn import_module(
+    closure_context: &mut JsClosureContext,
+    specifier: &JsValueNode,
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let referrer = closure_context
+        .get_current_module()
+        .map(str::to_string)
+        .or_else(|| closure_context.modules.base_url.clone());
+    let url = resolve_module_specifier(&specifier.get_ref().to_string(), referrer.as_deref())
+        .ok_or_else(|| JsValue::type_error_rc(&nodes_graph))?;
+    load_module_graph(closure_context, &url)?;
+    evaluate_module(closure_context, &url)?;
+    Ok(get_namespace(closure_context, &url))
+}
+
+/// Loads the module and the modules it imports that have not been loaded yet, then links them.
+/// Nothing is kept when any of them fails to load.
+fn load_module_graph(closure_context: &mut JsClosureContext, url: &str) -> Result<(), JsValueNode> {
+    let mut loaded = vec![];
+    let mut pending = vec![url.to_string()];
+    while let Some(url) = pending.pop() {
+        if closure_context.modules.contains(&url) {
+            continue;
+        }
+        match load_module(closure_context, &url) {
+            Ok(module) => {
+                pending.extend(
+                    module
+                        .requests
+                        .iter()
+                        .rev()
+                        .map(|(_, request)| request.clone()),
+                );
+                closure_context.modules.modules.insert(url.clone(), module);
+                loaded.push(url);
+            }
+            Err(value) => {
+                for url in loaded {
+                    closure_context.modules.modules.remove(&url);
+                }
+                return Err(value);
+            }
+        }
+    }
+    link_modules(closure_context, &loaded)
+}
+
+/// Fetches and parses a module, resolving the specifiers it requests. Modules that cannot be
+/// fetched throw a `TypeError`, and those that cannot be parsed a `SyntaxError`.
+fn load_module(closure_context: &JsClosureContext, url: &str) -> Result<JsModule, JsValueNode> {
+    let nodes_graph = &closure_context.nodes_graph;
+    let source = closure_context
+        .modules
+        .loader
+        .load(url)
+        .map_err(|_error| JsValue::type_error_rc(nodes_graph))?;
+    let statements = parse_js_statements(&source, nodes_graph)
+        .map_err(|message| JsValue::syntax_error_with_message_rc(nodes_graph, &message))?;
+
+    let mut requests: Vec<(String, String)> = vec![];
+    for specifier in statements.iter().filter_map(get_requested_specifier) {
+        if requests
+            .iter()
+            .any(|(requested, _url)| requested == specifier)
+        {
+            continue;
+        }
+        let request = resolve_module_specifier(specifier, Some(url))
+            .ok_or_else(|| JsValue::type_error_rc(nodes_graph))?;
+        requests.push((specifier.to_string(), request));
+    }
+    Ok(JsModule {
+        statements: Rc::new(statements),
+        requests,
+        status: JsModuleStatus::Linking,
+        scope: None,
+        namespace: None,
+    })
+}
+
+/// Creates the scopes of newly loaded modules with their function declarations hoisted, then
+/// binds the names they import. Modules importing a name that is not exported throw a
+/// `SyntaxError`.
+fn link_modules(
+    closure_context: &mut JsClosureContext,
+    urls: &[String],
+) -> Result<(), JsValueNode> {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    for url in urls {
+        let module = closure_context.modules.modules.get_mut(url).unwrap();
+        let mut scope = JsClosure::for_module(&nodes_graph, url);
+        for statement in module.statements.iter() {
+            let declaration = match statement {
+                JsStatement::Export(
+                    JsExport::Declaration(declaration) | JsExport::DefaultDeclaration(declaration),
+                ) => declaration,
+                statement => statement,
+            };
+            if let JsStatement::FunctionDeclaration(reference) = declaration {
+                scope.get_or_declare_reference_mut(&reference.name).value = reference.value.clone();
+            }
+        }
+        module.scope = Some(scope);
+
+        let nodes = module
+            .statements
+            .iter()
+            .flat_map(JsStatement::get_referenced_nodes)
+            .collect();
+        for function in collect_user_defined_functions(nodes) {
+            closure_context
+                .modules
+                .functions
+                .insert(function.id(), (function, url.clone()));
+        }
+    }
+
+    let is_linked = urls
+        .iter()
+        .all(|url| closure_context.modules.is_linkable(url));
+    let error = JsValue::syntax_error_rc(&nodes_graph);
+    for url in urls {
+        let module = closure_context.modules.modules.get_mut(url).unwrap();
+        module.status = match is_linked {
+            true => JsModuleStatus::Linked,
+            false => JsModuleStatus::Failed(error.clone()),
+        };
+    }
+    update_module_bindings(closure_context);
+    match is_linked {
+        true => Ok(()),
+        false => Err(error),
+    }
+}
+
+/// The user defined functions among the nodes, and those declared within them
+fn collect_user_defined_functions(nodes: Vec<JsValueNode>) -> Vec<JsValueNode> {
+    let mut functions: Vec<JsValueNode> = vec![];
+    let mut pending = nodes;
+    while let Some(node) = pending.pop() {
+        if let JsValue::Function(function @ JsFunction::UserDefined(..), _properties) =
+            node.get_ref()
+        {
+            if functions.iter().all(|function| function.id() != node.id()) {
+                pending.extend(function.get_referenced_nodes());
+                functions.push(node.clone());
+            }
+        }
+    }
+    functions
+}
+
+/// Runs a linked module after the modules it imports. Modules already running are part of a cycle
+/// and are left to finish.
+fn evaluate_module(closure_context: &mut JsClosureContext, url: &str) -> Result<(), JsValueNode> {
+    let module = closure_context
+        .modules
+        .modules
+        .get_mut(url)
+        .expect("Modules are loaded before they are run");
+    match &module.status {
+        JsModuleStatus::Failed(value) => return Err(value.clone()),
+        JsModuleStatus::Linked => module.status = JsModuleStatus::Evaluating,
+        _ => return Ok(()),
+    }
+
+    let requests = module
+        .requests
+        .iter()
+        .map(|(_specifier, request)| request.clone())
+        .collect::<Vec<_>>();
+    let result = requests
+        .iter()
+        .try_for_each(|request| evaluate_module(closure_context, request))
+        .and_then(|()| {
+            update_module_bindings(closure_context);
+            run_module_statements(closure_context, url)
+        });
+
+    let module = closure_context.modules.modules.get_mut(url).unwrap();
+    module.status = match &result {
+        Ok(()) => JsModuleStatus::Evaluated,
+        Err(value) => JsModuleStatus::Failed(value.clone()),
+    };
+    update_module_bindings(closure_context);
+    result
+}
+
+/// Runs the top level of a module in its scope with the engine of the closure context, recording
+/// the result of each statement until one throws
+fn run_module_statements(
+    closure_context: &mut JsClosureContext,
+    url: &str,
+) -> Result<(), JsValueNode> {
+    let module = closure_context.modules.modules.get_mut(url).unwrap();
+    let statements = module.statements.clone();
+    let scope = module.scope.take().expect("Modules run in their own scope");
+    closure_context.push_existing_closure(scope);
+
+    let result = match closure_context.engine {
+        JsEngine::TreeWalker => statements.iter().try_for_each(|statement| {
+            match run_module_statement(statement, closure_context) {
+                JsStatementResult::ThrowValue(value) => Err(value),
+                result => {
+                    closure_context.record_new_result(result);
+                    Ok(())
+                }
+            }
+        }),
+        JsEngine::Bytecode => {
+            let chunk = Rc::new(JsCompiler::compile_module(&statements));
+            JsVm::new().run_module(chunk, closure_context)
+        }
+    };
+
+    let scope = closure_context.take_closure();
+    closure_context.modules.modules.get_mut(url).unwrap().scope = Some(scope);
+    result
+}
+
+/// Runs a statement at the top level of a module, where imports were already bound
+fn run_module_statement(
+    statement: &JsStatement,
+    closure_context: &mut JsClosureContext,
+) -> JsStatementResult {
+    match statement {
+        JsStatement::Export(
+            JsExport::Declaration(declaration) | JsExport::DefaultDeclaration(declaration),
+        ) => declaration.run(closure_context),
+        JsStatement::Export(JsExport::Default(expression)) => {
+            let bound = expression.run(closure_context).and_then(|value| {
+                bind_identifier(
+                    closure_context,
+                    DEFAULT_EXPORT_REFERENCE,
+                    value.clone(),
+                    Some(JsDeclarationKind::Const),
+                )
+                .map(|()| value)
+            });
+            match bound {
+                Ok(value) => JsStatementResult::Value(value),
+                Err(value) => JsStatementResult::ThrowValue(value),
+            }
+        }
+        JsStatement::Import(_) | JsStatement::Export(_) => JsStatementResult::Void,
+        statement => statement.run(closure_context),
+    }
+}
+
+/// The top level scope of a module, whether or not it has been entered
+fn get_module_scope<'a>(
+    closure_context: &'a mut JsClosureContext,
+    url: &str,
+) -> Option<&'a mut JsClosure> {
+    if closure_context.modules.modules.get(url)?.scope.is_some() {
+        return closure_context.modules.modules.get_mut(url)?.scope.as_mut();
+    }
+    closure_context.get_module_closure_mut(url)
+}
+
+fn get_binding_value(
+    closure_context: &mut JsClosureContext,
+    binding: &JsExportBinding,
+) -> JsValueNode {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    match binding {
+        JsExportBinding::Local(url, name) => get_module_scope(closure_context, url)
+            .and_then(|scope| scope.get_reference(name))
+            .map(|reference| reference.value.clone())
+            .unwrap_or_else(|| JsValue::undefined_rc(&nodes_graph)),
+        JsExportBinding::Namespace(url) => get_namespace(closure_context, url),
+    }
+}
+
+/// The object whose properties are the names a module exports, created the first time it is
+/// requested
+fn get_namespace(closure_context: &mut JsClosureContext, url: &str) -> JsValueNode {
+    let module = closure_context.modules.modules.get_mut(url).unwrap();
+    if let Some(namespace) = &module.namespace {
+        return namespace.clone();
+    }
+    // The namespace is kept before it is filled in, as it may export itself
+    let namespace = JsValue::object_rc(&closure_context.nodes_graph, JsProperties::new());
+    module.namespace = Some(namespace.clone());
+    update_namespace(closure_context, url);
+    namespace
+}
+
+/// Copies the values of the names a module exports into its namespace, in the order of their names
+fn update_namespace(closure_context: &mut JsClosureContext, url: &str) {
+    let Some(mut namespace) = closure_context
+        .modules
+        .modules
+        .get(url)
+        .and_then(|module| module.namespace.clone())
+    else {
+        return;
+    };
+    let mut names = closure_context.modules.get_exported_names(url, &mut vec![]);
+    names.sort();
+    for name in names {
+        let Some(binding) = closure_context
+            .modules
+            .resolve_export(url, &name, &mut vec![])
+        else {
+            continue;
+        };
+        let value = get_binding_value(closure_context, &binding);
+        if let JsValue::Object(properties) = namespace.get_mut() {
+            properties.insert_property(
+                name.as_str().into(),
+                JsProperty::with_attributes(value, false, true, false),
+            );
+        }
+    }
+}
+
+/// Copies the values of exported names into the scopes of the modules importing them, and into
+/// namespaces
+fn update_module_bindings(closure_context: &mut JsClosureContext) {
+    let urls = closure_context
+        .modules
+        .modules
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    for url in &urls {
+        for (local, binding) in closure_context.modules.get_import_bindings(url) {
+            let Some(binding) = binding else {
+                continue;
+            };
+            let value = get_binding_value(closure_context, &binding);
+            if let Some(scope) = get_module_scope(closure_context, url) {
+                let reference = scope.get_or_declare_reference_mut(&local);
+                reference.value = value;
+                reference.constant = true;
+            }
+        }
+        update_namespace(closure_context, url);
+    }
+}
+
+/// Enters the scope of the module a user defined function was declared in, unless it has already
+/// been entered, so the function can see the module's names. Results in the module entered, which
+/// is left by [leave_module_scope] once the function returns.
+pub fn enter_module_scope(
+    closure_context: &mut JsClosureContext,
+    function: &JsValueNode,
+) -> Option<String> {
+    let (_function, url) = closure_context.modules.functions.get(&function.id())?;
+    let url = url.clone();
+    if closure_context.get_module_closure_mut(&url).is_some() {
+        return None;
+    }
+    let scope = closure_context
+        .modules
+        .modules
+        .get_mut(&url)?
+        .scope
+        .take()?;
+    closure_context.push_existing_closure(scope);
+    Some(url)
+}
+
+/// Leaves the scope entered by [enter_module_scope]. The function may have changed names other
+/// modules import, so they are updated.
+pub fn leave_module_scope(closure_context: &mut JsClosureContext, module: Option<String>) {
+    let Some(url) = module else {
+        return;
+    };
+    let scope = closure_context.take_closure();
+    if let Some(module) = closure_context.modules.modules.get_mut(&url) {
+        module.scope = Some(scope);
+    }
+    update_module_bindings(closure_context);
+}
@@ -0,0 +1,391 @@
+use super::{
+    build_function_entry, build_native_function, get_argument, get_member_from_prototype_chain,
+    get_own_enumerable_entries, get_own_enumerable_keys, internal_define_own_property,
+    internal_get_own_property, internal_get_prototype_of, internal_has_property,
+    internal_is_extensible, internal_own_property_keys, internal_prevent_extensions, set_member,
+    JsClosureContext, JsFunctionResult, JsProperties, JsProperty, JsPropertyDescriptor,
+    JsPropertyKey, JsPropertyValue, JsValue, JsValueGraph, JsValueNode,
+    CONSTRUCTOR_PROTOTYPE_MEMBER, PROTOTYPE_MEMBER,
+};
+
+/// How far `Object.seal` and `Object.freeze` restrict changes to an object
+#[derive(Clone, Copy, PartialEq)]
+enum JsIntegrityLevel {
+    /// No properties can be added, removed, or reconfigured
+    Sealed,
+    /// Sealed, and data properties are read-only
+    Frozen,
+}
+
+/// `Object` converts its argument to an object, and holds the functions for inspecting and
+/// defining the properties of objects.
+pub fn build_object_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor = build_native_function(&node_graph, &"Object".into(), |ctx, _, args| {
+        let value = get_argument(ctx, args, 0);
+        if value.get_ref().is_object() {
+            Ok(value)
+        } else {
+            Ok(JsValue::object_rc(&ctx.nodes_graph, JsProperties::new()))
+        }
+    });
+
+    let members = [
+        build_function_entry(&node_graph, "defineProperty".into(), object_define_property),
+        build_function_entry(
+            &node_graph,
+            "getOwnPropertyDescriptor".into(),
+            object_get_own_property_descriptor,
+        ),
+        build_function_entry(
+            &node_graph,
+            "getPrototypeOf".into(),
+            object_get_prototype_of,
+        ),
+        build_function_entry(&node_graph, "keys".into(), object_keys),
+        build_function_entry(&node_graph, "values".into(), |ctx, _, args| {
+            object_enumerate(ctx, args, |_key, value, _ctx| value)
+        }),
+        build_function_entry(&node_graph, "entries".into(), |ctx, _, args| {
+            object_enumerate(ctx, args, |key, value, ctx| {
+                JsValue::array_rc(&ctx.nodes_graph, vec![key, value])
+            })
+        }),
+        build_function_entry(&node_graph, "assign".into(), object_assign),
+        build_function_entry(&node_graph, "freeze".into(), |ctx, _, args| {
+            object_restrict(ctx, args, JsIntegrityLevel::Frozen)
+        }),
+        build_function_entry(&node_graph, "seal".into(), |ctx, _, args| {
+            object_restrict(ctx, args, JsIntegrityLevel::Sealed)
+        }),
+        build_function_entry(
+            &node_graph,
+            "preventExtensions".into(),
+            object_prevent_extensions,
+        ),
+        build_function_entry(&node_graph, "isFrozen".into(), |ctx, _, args| {
+            object_test(ctx, args, JsIntegrityLevel::Frozen)
+        }),
+        build_function_entry(&node_graph, "isSealed".into(), |ctx, _, args| {
+            object_test(ctx, args, JsIntegrityLevel::Sealed)
+        }),
+        build_function_entry(&node_graph, "isExtensible".into(), |ctx, _, args| {
+            let target = get_argument(ctx, args, 0);
+            let extensible = internal_is_extensible(&target, ctx)?;
+            Ok(JsValue::bool_rc(&ctx.nodes_graph, extensible))
+        }),
+    ];
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        for (key, value) in members {
+            properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+        }
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
+
+fn object_define_property(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let mut target = get_argument(closure_context, args, 0);
+    if !target.get_ref().is_object() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let key = JsPropertyKey::from(get_argument(closure_context, args, 1).get_ref());
+    let descriptor =
+        to_property_descriptor(&get_argument(closure_context, args, 2), closure_context)?;
+    if internal_define_own_property(&mut target, key, descriptor, closure_context)? {
+        Ok(target)
+    } else {
+        Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+    }
+}
+
+fn object_get_own_property_descriptor(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let target = get_argument(closure_context, args, 0);
+    if matches!(target.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&nodes_graph));
+    }
+    let key = JsPropertyKey::from(get_argument(closure_context, args, 1).get_ref());
+    let property = match internal_get_own_property(&target, &key, closure_context)? {
+        Some(property) if key.as_str() != Some(PROTOTYPE_MEMBER) => property,
+        _ => return Ok(JsValue::undefined_rc(&nodes_graph)),
+    };
+    Ok(from_property_descriptor(&property.into(), &nodes_graph))
+}
+
+fn object_get_prototype_of(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    if matches!(target.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(internal_get_prototype_of(&target, closure_context)?
+        .unwrap_or_else(|| JsValue::null_rc(&closure_context.nodes_graph)))
+}
+
+fn object_keys(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    if matches!(target.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let keys = get_own_enumerable_keys(&target, closure_context)?
+        .iter()
+        .filter_map(|key| Some(JsValue::str_rc(&closure_context.nodes_graph, key.as_str()?)))
+        .collect();
+    Ok(JsValue::array_rc(&closure_context.nodes_graph, keys))
+}
+
+/// Lists the own, enumerable, string keyed properties of the first argument. Symbol keyed
+/// properties are skipped.
+fn object_enumerate(
+    closure_context: &mut JsClosureContext,
+    args: &[JsValueNode],
+    to_element: impl Fn(JsValueNode, JsValueNode, &JsClosureContext) -> JsValueNode,
+) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    if matches!(target.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let elements = get_own_enumerable_entries(&target, closure_context)?
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let key = JsValue::str_rc(&closure_context.nodes_graph, key.as_str()?);
+            Some(to_element(key, value, closure_context))
+        })
+        .collect();
+    Ok(JsValue::array_rc(&closure_context.nodes_graph, elements))
+}
+
+/// Copies the own, enumerable properties of each source onto the target by assignment, so
+/// setters on the target are invoked
+fn object_assign(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let mut target = get_argument(closure_context, args, 0);
+    if matches!(target.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    for source in args.iter().skip(1) {
+        if matches!(source.get_ref(), JsValue::Undefined | JsValue::Null) {
+            continue;
+        }
+        for (key, value) in get_own_enumerable_entries(source, closure_context)? {
+            if !set_member(&mut target, &key, value, closure_context)? {
+                return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+            }
+        }
+    }
+    Ok(target)
+}
+
+/// Restricts changes to the properties of an object. Proxies are restricted through their traps,
+/// throwing a `TypeError` if a trap refuses. Other values, including arrays whose elements do not
+/// have attributes of their own, are returned unchanged.
+fn object_restrict(
+    closure_context: &mut JsClosureContext,
+    args: &[JsValueNode],
+    level: JsIntegrityLevel,
+) -> JsFunctionResult {
+    let mut target = get_argument(closure_context, args, 0);
+    if let JsValue::Proxy(_) = target.get_ref() {
+        if !set_integrity_level(&target, level, closure_context)? {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        return Ok(target);
+    }
+    if let JsValue::Object(properties) | JsValue::Function(_, properties) = target.get_mut() {
+        match level {
+            JsIntegrityLevel::Sealed => properties.seal(),
+            JsIntegrityLevel::Frozen => properties.freeze(),
+        }
+    }
+    Ok(target)
+}
+
+/// Prevents extensions through the internal methods of a proxy, redefining each of its properties
+fn set_integrity_level(
+    target: &JsValueNode,
+    level: JsIntegrityLevel,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if !internal_prevent_extensions(target, closure_context)? {
+        return Ok(false);
+    }
+    for key in internal_own_property_keys(target, closure_context)? {
+        let Some(property) = internal_get_own_property(target, &key, closure_context)? else {
+            continue;
+        };
+        let is_data = matches!(property.value, JsPropertyValue::Data { .. });
+        let descriptor = JsPropertyDescriptor {
+            configurable: Some(false),
+            writable: (level == JsIntegrityLevel::Frozen && is_data).then_some(false),
+            ..Default::default()
+        };
+        let mut target = target.clone();
+        if !internal_define_own_property(&mut target, key, descriptor, closure_context)? {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+    }
+    Ok(true)
+}
+
+/// Prevents new properties being added to an object. Values other than objects and functions
+/// are returned unchanged, while proxies whose trap refuses throw a `TypeError`.
+fn object_prevent_extensions(
+    closure_context: &mut JsClosureContext,
+    _this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    let prevented = internal_prevent_extensions(&target, closure_context)?;
+    if !prevented && matches!(target.get_ref(), JsValue::Proxy(_)) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(target)
+}
+
+/// Tests the integrity of an object, where primitives are considered frozen and sealed
+fn object_test(
+    closure_context: &mut JsClosureContext,
+    args: &[JsValueNode],
+    level: JsIntegrityLevel,
+) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    let result = match target.get_ref() {
+        JsValue::Object(properties) | JsValue::Function(_, properties) => match level {
+            JsIntegrityLevel::Sealed => properties.is_sealed(),
+            JsIntegrityLevel::Frozen => properties.is_frozen(),
+        },
+        JsValue::Proxy(_) => test_integrity_level(&target, level, closure_context)?,
+        value => !value.is_object(),
+    };
+    Ok(JsValue::bool_rc(&closure_context.nodes_graph, result))
+}
+
+/// Tests the integrity of a proxy through its internal methods
+fn test_integrity_level(
+    target: &JsValueNode,
+    level: JsIntegrityLevel,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    if internal_is_extensible(target, closure_context)? {
+        return Ok(false);
+    }
+    for key in internal_own_property_keys(target, closure_context)? {
+        let Some(property) = internal_get_own_property(target, &key, closure_context)? else {
+            continue;
+        };
+        let writable = matches!(property.value, JsPropertyValue::Data { writable: true, .. });
+        if property.configurable || (level == JsIntegrityLevel::Frozen && writable) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Reads a property descriptor object, as passed to `Object.defineProperty`. Descriptors mixing
+/// data and accessor fields, or with accessors that are not functions, throw a `TypeError`.
+pub fn to_property_descriptor(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<JsPropertyDescriptor, JsValueNode> {
+    if !value.get_ref().is_object() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let mut field = |name: &str| -> Result<Option<JsValueNode>, JsValueNode> {
+        let key = JsPropertyKey::from(name);
+        if !internal_has_property(value, &key, closure_context)? {
+            return Ok(None);
+        }
+        get_member_from_prototype_chain(value, &key, closure_context).map(Some)
+    };
+    let flag = |field: Option<JsValueNode>| field.map(|field| bool::from(field.get_ref()));
+
+    let enumerable = flag(field("enumerable")?);
+    let configurable = flag(field("configurable")?);
+    let value_field = field("value")?;
+    let writable = flag(field("writable")?);
+    let get = field("get")?;
+    let set = field("set")?;
+
+    let accessor = |function: Option<JsValueNode>| match function {
+        None => Ok(None),
+        Some(function) => match function.get_ref() {
+            JsValue::Undefined => Ok(Some(None)),
+            value if value.is_callable() => Ok(Some(Some(function.clone()))),
+            _ => Err(JsValue::type_error_rc(&function.get_node_graph())),
+        },
+    };
+    let descriptor = JsPropertyDescriptor {
+        value: value_field,
+        writable,
+        get: accessor(get)?,
+        set: accessor(set)?,
+        enumerable,
+        configurable,
+    };
+    if descriptor.is_accessor() && descriptor.is_data() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(descriptor)
+}
+
+/// Creates a descriptor object, as returned by `Object.getOwnPropertyDescriptor`, with only the
+/// fields present in the descriptor
+pub fn from_property_descriptor(
+    descriptor: &JsPropertyDescriptor,
+    nodes_graph: &JsValueGraph,
+) -> JsValueNode {
+    let function_or_undefined = |function: &Option<JsValueNode>| {
+        function
+            .clone()
+            .unwrap_or_else(|| JsValue::undefined_rc(nodes_graph))
+    };
+    let mut properties = JsProperties::new();
+    if let Some(value) = &descriptor.value {
+        properties.insert("value".into(), value.clone());
+    }
+    if let Some(writable) = descriptor.writable {
+        properties.insert("writable".into(), JsValue::bool_rc(nodes_graph, writable));
+    }
+    if let Some(get) = &descriptor.get {
+        properties.insert("get".into(), function_or_undefined(get));
+    }
+    if let Some(set) = &descriptor.set {
+        properties.insert("set".into(), function_or_undefined(set));
+    }
+    if let Some(enumerable) = descriptor.enumerable {
+        properties.insert(
+            "enumerable".into(),
+            JsValue::bool_rc(nodes_graph, enumerable),
+        );
+    }
+    if let Some(configurable) = descriptor.configurable {
+        properties.insert(
+            "configurable".into(),
+            JsValue::bool_rc(nodes_graph, configurable),
+        );
+    }
+    JsValue::object_rc(nodes_graph, properties)
+}
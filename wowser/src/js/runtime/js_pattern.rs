@@ -0,0 +1,243 @@
+use super::{
+    access_member, collect_iterable, get_own_enumerable_entries, set_member, JsClosureContext,
+    JsExpression, JsObjectMember, JsProperties, JsPropertyKey, JsPropertyName, JsValue,
+    JsValueNode,
+};
+
+/// How a declared name may be rebound after its declaration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsDeclarationKind {
+    Var,
+    Let,
+    Const,
+}
+
+/// The target of a declaration or assignment, possibly destructuring the value assigned to it.
+#[derive(Debug, PartialEq)]
+pub enum JsPattern {
+    /// Binds the whole value to a name
+    Identifier(String),
+    /// Assigns the whole value to an arbitrary expression, such as a member access. Expressions
+    /// that cannot be assigned to throw a `ReferenceError` when bound.
+    Expression(Box<JsExpression>),
+    /// (Elements where `None` is a hole, Rest element)
+    Array(Vec<Option<JsPattern>>, Option<Box<JsPattern>>),
+    /// (Property names and the patterns they are bound to, Rest element)
+    Object(Vec<(String, JsPattern)>, Option<Box<JsPattern>>),
+    /// (Pattern, Default value used when the value bound is `undefined`)
+    Default(Box<JsPattern>, Box<JsExpression>),
+}
+
+impl JsPattern {
+    /// Binds `value` to the pattern. When `declaration` is present, names are declared rather than
+    /// assigned.
+    pub fn bind(
+        &self,
+        closure_context: &mut JsClosureContext,
+        value: JsValueNode,
+        declaration: Option<JsDeclarationKind>,
+    ) -> Result<(), JsValueNode> {
+        match self {
+            Self::Identifier(name) => bind_identifier(closure_context, name, value, declaration),
+            Self::Expression(expression) => match expression.as_ref() {
+                JsExpression::Reference(name) => {
+                    bind_identifier(closure_context, name, value, declaration)
+                }
+                JsExpression::AccessMember(base, member) => {
+                    let mut base_value = base.run(closure_context)?;
+                    // Assignments to read-only properties are silently ignored
+                    set_member(
+                        &mut base_value,
+                        &member.as_str().into(),
+                        value,
+                        closure_context,
+                    )
+                    .map(|_assigned| ())
+                }
+                JsExpression::AccessComputedMember(base, member) => {
+                    let mut base_value = base.run(closure_context)?;
+                    let member = JsPropertyKey::from(member.run(closure_context)?.get_ref());
+                    set_member(&mut base_value, &member, value, closure_context).map(|_assigned| ())
+                }
+                _ => Err(JsValue::reference_error_rc(&closure_context.nodes_graph)),
+            },
+            Self::Array(elements, rest) => {
+                let mut values = collect_iterable(&value, closure_context)?.into_iter();
+                for element in elements {
+                    let element_value = values
+                        .next()
+                        .unwrap_or_else(|| JsValue::undefined_rc(&closure_context.nodes_graph));
+                    if let Some(element) = element {
+                        element.bind(closure_context, element_value, declaration)?;
+                    }
+                }
+                if let Some(rest) = rest {
+                    let rest_value =
+                        JsValue::array_rc(&closure_context.nodes_graph, values.collect());
+                    rest.bind(closure_context, rest_value, declaration)?;
+                }
+                Ok(())
+            }
+            Self::Object(properties, rest) => {
+                if matches!(value.get_ref(), JsValue::Undefined | JsValue::Null) {
+                    return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+                }
+                for (name, property) in properties {
+                    let property_value =
+                        access_member(&value, &name.as_str().into(), closure_context)?;
+                    property.bind(closure_context, property_value, declaration)?;
+                }
+                if let Some(rest) = rest {
+                    let mut remaining = JsProperties::new();
+                    for (key, member) in get_own_enumerable_entries(&value, closure_context)? {
+                        if !properties
+                            .iter()
+                            .any(|(name, _)| key.as_str() == Some(name.as_str()))
+                        {
+                            remaining.insert(key, member);
+                        }
+                    }
+                    let rest_value = JsValue::object_rc(&closure_context.nodes_graph, remaining);
+                    rest.bind(closure_context, rest_value, declaration)?;
+                }
+                Ok(())
+            }
+            Self::Default(pattern, default) => {
+                let value = if matches!(value.get_ref(), JsValue::Undefined) {
+                    default.run(closure_context)?
+                } else {
+                    value
+                };
+                pattern.bind(closure_context, value, declaration)
+            }
+        }
+    }
+
+    /// The names a declaration with the pattern binds
+    pub fn get_bound_names(&self) -> Vec<String> {
+        match self {
+            Self::Identifier(name) => vec![name.clone()],
+            Self::Expression(expression) => match expression.as_ref() {
+                JsExpression::Reference(name) => vec![name.clone()],
+                _ => vec![],
+            },
+            Self::Array(elements, rest) => elements
+                .iter()
+                .flatten()
+                .chain(rest.iter().map(AsRef::as_ref))
+                .flat_map(|pattern| pattern.get_bound_names())
+                .collect(),
+            Self::Object(properties, rest) => properties
+                .iter()
+                .map(|(_name, pattern)| pattern)
+                .chain(rest.iter().map(AsRef::as_ref))
+                .flat_map(|pattern| pattern.get_bound_names())
+                .collect(),
+            Self::Default(pattern, _default) => pattern.get_bound_names(),
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match self {
+            Self::Identifier(_) => vec![],
+            Self::Expression(expression) => expression.get_referenced_nodes(),
+            Self::Array(elements, rest) => elements
+                .iter()
+                .flatten()
+                .chain(rest.iter().map(AsRef::as_ref))
+                .flat_map(|pattern| pattern.get_referenced_nodes())
+                .collect(),
+            Self::Object(properties, rest) => properties
+                .iter()
+                .map(|(_name, pattern)| pattern)
+                .chain(rest.iter().map(AsRef::as_ref))
+                .flat_map(|pattern| pattern.get_referenced_nodes())
+                .collect(),
+            Self::Default(pattern, default) => [
+                pattern.get_referenced_nodes(),
+                default.get_referenced_nodes(),
+            ]
+            .concat(),
+        }
+    }
+}
+
+/// Reinterprets an expression as an assignment target. This is how array and object literals on
+/// the left-hand side of an assignment become destructuring patterns.
+impl From<JsExpression> for JsPattern {
+    fn from(expression: JsExpression) -> Self {
+        match expression {
+            JsExpression::Reference(name) => Self::Identifier(name),
+            JsExpression::Assign(pattern, default) => Self::Default(pattern, default),
+            JsExpression::Array(elements) => {
+                let mut patterns = Vec::with_capacity(elements.len());
+                let mut rest = None;
+                for element in elements {
+                    match element {
+                        Some(JsExpression::Spread(expression)) => {
+                            rest = Some(Box::new(Self::from(*expression)));
+                        }
+                        element => patterns.push(element.map(Self::from)),
+                    }
+                }
+                Self::Array(patterns, rest)
+            }
+            // Computed keys and methods are not supported in patterns, leaving the literal
+            // unassignable
+            JsExpression::Object(members)
+                if members.iter().all(|member| {
+                    matches!(
+                        member,
+                        JsObjectMember::Property(JsPropertyName::Static(_), _)
+                            | JsObjectMember::Spread(_)
+                    )
+                }) =>
+            {
+                let mut properties = Vec::with_capacity(members.len());
+                let mut rest = None;
+                for member in members {
+                    match member {
+                        JsObjectMember::Property(JsPropertyName::Static(name), expression) => {
+                            properties.push((name, Self::from(expression)));
+                        }
+                        JsObjectMember::Spread(expression) => {
+                            rest = Some(Box::new(Self::from(expression)));
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                Self::Object(properties, rest)
+            }
+            expression => Self::Expression(Box::new(expression)),
+        }
+    }
+}
+
+/// Binds a value to a name. `let` and `const` declarations always declare the name in the latest
+/// scope, while assignments to constants throw a `TypeError`.
+pub fn bind_identifier(
+    closure_context: &mut JsClosureContext,
+    name: &str,
+    value: JsValueNode,
+    declaration: Option<JsDeclarationKind>,
+) -> Result<(), JsValueNode> {
+    let reference = match declaration {
+        // Matches the existing behavior of `var` reusing a visible declaration
+        Some(JsDeclarationKind::Var) => closure_context.get_or_declare_reference_mut(name),
+        Some(_) => closure_context
+            .get_lastest_closure()
+            .get_or_declare_reference_mut(name),
+        None => {
+            if closure_context
+                .get_reference(name)
+                .is_some_and(|reference| reference.constant)
+            {
+                return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+            }
+            closure_context.get_or_declare_reference_mut(name)
+        }
+    };
+    reference.value = value;
+    reference.constant = declaration == Some(JsDeclarationKind::Const);
+    Ok(())
+}
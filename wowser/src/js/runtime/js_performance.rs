@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use wowser_macros::js_class;
+
+use crate::js::api::date::{Clock, Date, HighResolutionClock};
+
+use super::{JsClosureContext, JsValueNode};
+
+/// The high resolution time of the document, which `performance.now()` and the timestamps of
+/// animation frames count from when the document was created
+/// <https://w3c.github.io/hr-time/>
+#[derive(Debug)]
+pub struct JsPerformanceTimeline {
+    clock: Rc<dyn Clock>,
+    /// When the document was created, in milliseconds since the epoch
+    time_origin: Date,
+    /// The `Performance` object, created when first read so scripts always get the same one
+    performance_object: Option<JsValueNode>,
+}
+
+impl Default for JsPerformanceTimeline {
+    fn default() -> Self {
+        Self::new(Rc::new(HighResolutionClock))
+    }
+}
+
+impl JsPerformanceTimeline {
+    /// A timeline whose time origin is the current time of the clock
+    pub fn new(clock: Rc<dyn Clock>) -> Self {
+        Self {
+            time_origin: clock.now(),
+            clock,
+            performance_object: None,
+        }
+    }
+
+    /// Reads the time from the clock, which becomes the time origin
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.time_origin = clock.now();
+        self.clock = clock;
+    }
+
+    pub fn get_time_origin(&self) -> Date {
+        self.time_origin
+    }
+
+    /// The milliseconds since the time origin
+    pub fn now(&self) -> f64 {
+        self.to_relative(self.clock.now())
+    }
+
+    /// The milliseconds between the time origin and a time read from the clock
+    pub fn to_relative(&self, time: Date) -> f64 {
+        time - self.time_origin
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.performance_object.iter().cloned().collect()
+    }
+}
+
+/// Declares `performance` as a global
+pub fn add_performance_global(closure_context: &mut JsClosureContext) {
+    let performance = get_performance_object(closure_context);
+    closure_context
+        .get_lastest_closure()
+        .get_or_declare_reference_mut("performance")
+        .value = performance;
+}
+
+/// `performance`
+pub fn get_performance_object(closure_context: &mut JsClosureContext) -> JsValueNode {
+    if let Some(object) = &closure_context.performance.performance_object {
+        return object.clone();
+    }
+    let object = closure_context.create_host_object(JsPerformance);
+    closure_context.performance.performance_object = Some(object.clone());
+    object
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Performance>
+pub struct JsPerformance;
+
+#[js_class(name = "Performance")]
+impl JsPerformance {
+    /// The milliseconds since the document was created, with sub-millisecond precision
+    #[js_method]
+    fn now(&self, closure_context: &mut JsClosureContext) -> f64 {
+        closure_context.performance.now()
+    }
+
+    /// When the document was created, in milliseconds since the epoch
+    #[js_method(getter)]
+    fn time_origin(&self, closure_context: &mut JsClosureContext) -> f64 {
+        closure_context.performance.get_time_origin()
+    }
+}
@@ -0,0 +1,414 @@
+use std::{cell::Cell, collections::VecDeque, rc::Rc};
+
+use wowser_macros::js_class;
+
+use super::{
+    build_function_entry, build_native_function, build_native_function_capturing, get_argument,
+    get_member_from_prototype_chain, invoke_function, with_host_object, JsClosureContext,
+    JsFunctionResult, JsHostClass, JsHostReferences, JsProperty, JsValue, JsValueNode,
+    CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+/// Whether a promise is still waiting, along with what to do once it settles, or how it settled
+#[derive(Debug)]
+enum JsPromiseState {
+    Pending(Vec<JsPromiseReaction>),
+    Fulfilled(JsValueNode),
+    Rejected(JsValueNode),
+}
+
+/// What to do once a promise settles, which is to call the handler for how it settled and resolve
+/// the promise `then` returned with the result. Handlers that are not functions pass the value or
+/// reason on.
+/// <https://tc39.es/ecma262/#sec-promisereaction-records>
+#[derive(Debug)]
+pub struct JsPromiseReaction {
+    derived: JsValueNode,
+    on_fulfilled: JsValueNode,
+    on_rejected: JsValueNode,
+}
+
+impl JsPromiseReaction {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![
+            self.derived.clone(),
+            self.on_fulfilled.clone(),
+            self.on_rejected.clone(),
+        ]
+    }
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Promise>
+///
+/// Handlers are called by jobs, which run once the script running finishes. See [run_jobs].
+#[derive(Debug)]
+pub struct JsPromise {
+    state: JsPromiseState,
+}
+
+impl JsPromise {
+    /// Settles a pending promise, resulting in the reactions that were waiting on it. Promises
+    /// that already settled stay as they are.
+    fn settle(&mut self, state: JsPromiseState) -> Vec<JsPromiseReaction> {
+        match &mut self.state {
+            JsPromiseState::Pending(reactions) => {
+                let reactions = std::mem::take(reactions);
+                self.state = state;
+                reactions
+            }
+            JsPromiseState::Fulfilled(_) | JsPromiseState::Rejected(_) => vec![],
+        }
+    }
+}
+
+impl JsHostReferences for JsPromise {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match &self.state {
+            JsPromiseState::Pending(reactions) => reactions
+                .iter()
+                .flat_map(JsPromiseReaction::get_referenced_nodes)
+                .collect(),
+            JsPromiseState::Fulfilled(value) | JsPromiseState::Rejected(value) => {
+                vec![value.clone()]
+            }
+        }
+    }
+}
+
+#[js_class(name = "Promise")]
+impl JsPromise {
+    /// Calls `on_fulfilled` with the value or `on_rejected` with the reason once the promise
+    /// settles, resulting in a promise resolved with what the handler returns
+    #[js_method]
+    fn then(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        on_fulfilled: JsValueNode,
+        on_rejected: JsValueNode,
+    ) -> JsValueNode {
+        let derived = create_promise(closure_context);
+        let reaction = JsPromiseReaction {
+            derived: derived.clone(),
+            on_fulfilled,
+            on_rejected,
+        };
+        let (argument, rejected) = match &mut self.state {
+            JsPromiseState::Pending(reactions) => {
+                reactions.push(reaction);
+                return derived;
+            }
+            JsPromiseState::Fulfilled(value) => (value.clone(), false),
+            JsPromiseState::Rejected(reason) => (reason.clone(), true),
+        };
+        closure_context.jobs.enqueue(JsJob::Reaction {
+            reaction,
+            argument,
+            rejected,
+        });
+        derived
+    }
+
+    /// `then` with only a handler for rejection
+    #[js_method]
+    fn catch(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        on_rejected: JsValueNode,
+    ) -> JsValueNode {
+        let on_fulfilled = JsValue::undefined_rc(&closure_context.nodes_graph);
+        self.then(closure_context, on_fulfilled, on_rejected)
+    }
+}
+
+/// `new Promise(executor)` calls the executor with functions to resolve and reject the promise,
+/// rejecting it when the executor throws. `Promise.resolve` and `Promise.reject` create promises
+/// settled with their argument.
+pub fn build_promise_constructor(closure_context: &mut JsClosureContext) -> JsValueNode {
+    closure_context
+        .global_prototypes
+        .register_host_class::<JsPromise>();
+    let prototype = closure_context.global_prototypes.host_classes[JsPromise::NAME].clone();
+    let node_graph = closure_context.nodes_graph.clone();
+    let mut constructor = build_native_function(
+        &node_graph,
+        &JsPromise::NAME.into(),
+        |closure_context, this, args| {
+            let executor = get_argument(closure_context, args, 0);
+            if matches!(this.get_ref(), JsValue::Undefined) || !executor.get_ref().is_callable() {
+                return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+            }
+            let promise = create_promise(closure_context);
+            let (resolve, reject) = create_resolving_functions(closure_context, &promise);
+            let undefined = JsValue::undefined_rc(&closure_context.nodes_graph);
+            let result = invoke_function(
+                closure_context,
+                &executor,
+                undefined.clone(),
+                &[resolve, reject.clone()],
+            );
+            if let Err(error) = result {
+                if closure_context.is_terminating() {
+                    return Err(error);
+                }
+                invoke_function(closure_context, &reject, undefined, &[error])?;
+            }
+            Ok(promise)
+        },
+    );
+    let statics = [
+        build_function_entry(&node_graph, "resolve".into(), |closure_context, _, args| {
+            let value = get_argument(closure_context, args, 0);
+            Ok(promise_resolve(closure_context, value))
+        }),
+        build_function_entry(&node_graph, "reject".into(), |closure_context, _, args| {
+            let reason = get_argument(closure_context, args, 0);
+            Ok(create_settled_promise(closure_context, Err(reason)))
+        }),
+    ];
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+        for (key, function) in statics {
+            properties.insert_property(
+                key,
+                JsProperty::with_attributes(function, true, false, true),
+            );
+        }
+    }
+    constructor
+}
+
+/// Creates a pending promise
+pub fn create_promise(closure_context: &mut JsClosureContext) -> JsValueNode {
+    closure_context.create_host_object_with_references(JsPromise {
+        state: JsPromiseState::Pending(vec![]),
+    })
+}
+
+/// Creates a promise resolved with the value or rejected with the reason of a result, such as the
+/// result of a host function run before its promise is returned
+pub fn create_settled_promise(
+    closure_context: &mut JsClosureContext,
+    result: JsFunctionResult,
+) -> JsValueNode {
+    let promise = create_promise(closure_context);
+    match result {
+        Ok(value) => resolve_promise(closure_context, &promise, value),
+        Err(reason) => reject_promise(closure_context, &promise, reason),
+    }
+    promise
+}
+
+/// `Promise.resolve`, where promises are returned as they are and other values are resolved into
+/// a new promise
+/// <https://tc39.es/ecma262/#sec-promise-resolve>
+fn promise_resolve(closure_context: &mut JsClosureContext, value: JsValueNode) -> JsValueNode {
+    if matches!(value.get_ref(), JsValue::HostObject(object) if object.is::<JsPromise>()) {
+        return value;
+    }
+    create_settled_promise(closure_context, Ok(value))
+}
+
+/// Resolves a promise with a value. Objects with a `then` method, such as other promises, are
+/// followed so the promise settles as they do, while other values fulfill it.
+/// <https://tc39.es/ecma262/#sec-promise-resolve-functions>
+pub fn resolve_promise(
+    closure_context: &mut JsClosureContext,
+    promise: &JsValueNode,
+    resolution: JsValueNode,
+) {
+    if resolution.is_same_ref(promise) {
+        let error = JsValue::type_error_rc(&closure_context.nodes_graph);
+        return reject_promise(closure_context, promise, error);
+    }
+    if !resolution.get_ref().is_object() {
+        return settle_promise(closure_context, promise, resolution, false);
+    }
+    match get_member_from_prototype_chain(&resolution, &"then".into(), closure_context) {
+        Err(error) => reject_promise(closure_context, promise, error),
+        Ok(then) if then.get_ref().is_callable() => {
+            closure_context.jobs.enqueue(JsJob::ResolveThenable {
+                promise: promise.clone(),
+                thenable: resolution,
+                then,
+            })
+        }
+        Ok(_) => settle_promise(closure_context, promise, resolution, false),
+    }
+}
+
+/// Rejects a promise with the reason
+pub fn reject_promise(
+    closure_context: &mut JsClosureContext,
+    promise: &JsValueNode,
+    reason: JsValueNode,
+) {
+    settle_promise(closure_context, promise, reason, true);
+}
+
+/// Settles a pending promise, queueing a job for each reaction waiting on it
+fn settle_promise(
+    closure_context: &mut JsClosureContext,
+    promise: &JsValueNode,
+    value: JsValueNode,
+    rejected: bool,
+) {
+    let state = match rejected {
+        true => JsPromiseState::Rejected(value.clone()),
+        false => JsPromiseState::Fulfilled(value.clone()),
+    };
+    let reactions = with_host_object(closure_context, promise, |promise: &mut JsPromise, _| {
+        promise.settle(state)
+    })
+    .unwrap_or_default();
+    for reaction in reactions {
+        closure_context.jobs.enqueue(JsJob::Reaction {
+            reaction,
+            argument: value.clone(),
+            rejected,
+        });
+    }
+}
+
+/// The `resolve` and `reject` functions of a promise, of which only the first call has an effect
+/// <https://tc39.es/ecma262/#sec-createresolvingfunctions>
+fn create_resolving_functions(
+    closure_context: &JsClosureContext,
+    promise: &JsValueNode,
+) -> (JsValueNode, JsValueNode) {
+    let already_resolved = Rc::new(Cell::new(false));
+    let build = |rejects: bool| {
+        let promise = promise.clone();
+        let already_resolved = already_resolved.clone();
+        build_native_function_capturing(
+            &closure_context.nodes_graph,
+            &"".into(),
+            vec![promise.clone()],
+            move |closure_context, _this, args| {
+                if !already_resolved.replace(true) {
+                    let value = get_argument(closure_context, args, 0);
+                    match rejects {
+                        true => reject_promise(closure_context, &promise, value),
+                        false => resolve_promise(closure_context, &promise, value),
+                    }
+                }
+                Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+            },
+        )
+    };
+    (build(false), build(true))
+}
+
+/// Work queued to run once the script running finishes, such as calling the handlers of a promise
+/// that settled
+/// <https://tc39.es/ecma262/#sec-jobs>
+#[derive(Debug)]
+pub enum JsJob {
+    Reaction {
+        reaction: JsPromiseReaction,
+        argument: JsValueNode,
+        rejected: bool,
+    },
+    /// Resolves a promise with an object by calling its `then` method with the functions
+    /// resolving the promise
+    ResolveThenable {
+        promise: JsValueNode,
+        thenable: JsValueNode,
+        then: JsValueNode,
+    },
+}
+
+impl JsJob {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match self {
+            Self::Reaction {
+                reaction, argument, ..
+            } => [reaction.get_referenced_nodes(), vec![argument.clone()]].concat(),
+            Self::ResolveThenable {
+                promise,
+                thenable,
+                then,
+            } => vec![promise.clone(), thenable.clone(), then.clone()],
+        }
+    }
+}
+
+/// The jobs waiting to run, in the order they were queued
+#[derive(Debug, Default)]
+pub struct JsJobQueue {
+    jobs: VecDeque<JsJob>,
+}
+
+impl JsJobQueue {
+    pub fn enqueue(&mut self, job: JsJob) {
+        self.jobs.push_back(job);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.jobs
+            .iter()
+            .flat_map(JsJob::get_referenced_nodes)
+            .collect()
+    }
+}
+
+/// Runs the queued jobs, along with the jobs they queue, until there are none left or the run is
+/// terminated. The results the handlers record are dropped, so the last result recorded stays the
+/// one of the script that queued them.
+/// <https://html.spec.whatwg.org/multipage/webappapis.html#perform-a-microtask-checkpoint>
+pub fn run_jobs(closure_context: &mut JsClosureContext) {
+    let results = closure_context.expression_results.len();
+    while !closure_context.is_terminating() {
+        let Some(job) = closure_context.jobs.jobs.pop_front() else {
+            break;
+        };
+        run_job(closure_context, job);
+    }
+    closure_context.expression_results.truncate(results);
+}
+
+fn run_job(closure_context: &mut JsClosureContext, job: JsJob) {
+    let undefined = JsValue::undefined_rc(&closure_context.nodes_graph);
+    match job {
+        JsJob::Reaction {
+            reaction,
+            argument,
+            rejected,
+        } => {
+            let handler = match rejected {
+                true => &reaction.on_rejected,
+                false => &reaction.on_fulfilled,
+            };
+            let result = if handler.get_ref().is_callable() {
+                invoke_function(closure_context, handler, undefined, &[argument])
+            } else if rejected {
+                Err(argument)
+            } else {
+                Ok(argument)
+            };
+            match result {
+                Ok(value) => resolve_promise(closure_context, &reaction.derived, value),
+                Err(reason) => reject_promise(closure_context, &reaction.derived, reason),
+            }
+        }
+        JsJob::ResolveThenable {
+            promise,
+            thenable,
+            then,
+        } => {
+            let (resolve, reject) = create_resolving_functions(closure_context, &promise);
+            let result =
+                invoke_function(closure_context, &then, thenable, &[resolve, reject.clone()]);
+            if let Err(error) = result {
+                // The promise is only rejected if `then` has not resolved it already
+                let _ = invoke_function(closure_context, &reject, undefined, &[error]);
+            }
+        }
+    }
+}
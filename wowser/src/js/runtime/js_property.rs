@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+
+use super::{JsPropertyKey, JsValue, JsValueGraph, JsValueNode};
+
+/// Where a property's value comes from
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsPropertyValue {
+    Data {
+        value: JsValueNode,
+        writable: bool,
+    },
+    /// Accessors run their getter when read and their setter when assigned. Missing functions
+    /// read as `undefined` and ignore assignments.
+    Accessor {
+        get: Option<JsValueNode>,
+        set: Option<JsValueNode>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsProperty {
+    pub value: JsPropertyValue,
+    pub enumerable: bool,
+    /// Non-configurable properties cannot be deleted or have their attributes changed
+    pub configurable: bool,
+}
+
+impl JsProperty {
+    /// A property as created by assignment or object literals
+    pub fn data(value: JsValueNode) -> Self {
+        Self::with_attributes(value, true, true, true)
+    }
+
+    pub fn with_attributes(
+        value: JsValueNode,
+        writable: bool,
+        enumerable: bool,
+        configurable: bool,
+    ) -> Self {
+        Self {
+            value: JsPropertyValue::Data { value, writable },
+            enumerable,
+            configurable,
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match &self.value {
+            JsPropertyValue::Data { value, .. } => vec![value.clone()],
+            JsPropertyValue::Accessor { get, set } => get.iter().chain(set).cloned().collect(),
+        }
+    }
+}
+
+/// A partial description of a property, as passed to `Object.defineProperty`. Absent fields keep
+/// their current value, or take their default when defining a new property.
+#[derive(Clone, Debug, Default)]
+pub struct JsPropertyDescriptor {
+    pub value: Option<JsValueNode>,
+    pub writable: Option<bool>,
+    /// `Some(None)` removes the getter
+    pub get: Option<Option<JsValueNode>>,
+    /// `Some(None)` removes the setter
+    pub set: Option<Option<JsValueNode>>,
+    pub enumerable: Option<bool>,
+    pub configurable: Option<bool>,
+}
+
+impl JsPropertyDescriptor {
+    pub fn is_accessor(&self) -> bool {
+        self.get.is_some() || self.set.is_some()
+    }
+
+    pub fn is_data(&self) -> bool {
+        self.value.is_some() || self.writable.is_some()
+    }
+
+    /// The property described, where absent fields take their default
+    pub fn into_property(self, node_graph: &JsValueGraph) -> JsProperty {
+        let value = if self.is_accessor() {
+            JsPropertyValue::Accessor {
+                get: self.get.flatten(),
+                set: self.set.flatten(),
+            }
+        } else {
+            JsPropertyValue::Data {
+                value: self
+                    .value
+                    .unwrap_or_else(|| JsValue::undefined_rc(node_graph)),
+                writable: self.writable.unwrap_or(false),
+            }
+        };
+        JsProperty {
+            value,
+            enumerable: self.enumerable.unwrap_or(false),
+            configurable: self.configurable.unwrap_or(false),
+        }
+    }
+}
+
+/// Describes every field of a property
+impl From<JsProperty> for JsPropertyDescriptor {
+    fn from(property: JsProperty) -> Self {
+        let (value, writable, get, set) = match property.value {
+            JsPropertyValue::Data { value, writable } => (Some(value), Some(writable), None, None),
+            JsPropertyValue::Accessor { get, set } => (None, None, Some(get), Some(set)),
+        };
+        Self {
+            value,
+            writable,
+            get,
+            set,
+            enumerable: Some(property.enumerable),
+            configurable: Some(property.configurable),
+        }
+    }
+}
+
+/// The own properties of an object. Iteration follows the order of property keys used by
+/// JavaScript: array indices ascending, then other strings, then symbols, both in insertion order.
+#[derive(Debug, PartialEq)]
+pub struct JsProperties {
+    order: Vec<JsPropertyKey>,
+    properties: HashMap<JsPropertyKey, JsProperty>,
+    /// Whether new properties may be added
+    extensible: bool,
+}
+
+impl Default for JsProperties {
+    fn default() -> Self {
+        Self {
+            order: vec![],
+            properties: HashMap::new(),
+            extensible: true,
+        }
+    }
+}
+
+impl JsProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &JsPropertyKey) -> Option<&JsProperty> {
+        self.properties.get(key)
+    }
+
+    /// The value of a data property, ignoring accessors
+    pub fn get_value(&self, key: &JsPropertyKey) -> Option<&JsValueNode> {
+        match &self.properties.get(key)?.value {
+            JsPropertyValue::Data { value, .. } => Some(value),
+            JsPropertyValue::Accessor { .. } => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &JsPropertyKey) -> bool {
+        self.properties.contains_key(key)
+    }
+
+    /// Creates or overwrites a writable, enumerable, and configurable data property, regardless
+    /// of the current attributes. This is how object literals and built-ins populate objects.
+    pub fn insert(&mut self, key: JsPropertyKey, value: JsValueNode) {
+        self.insert_property(key, JsProperty::data(value));
+    }
+
+    pub fn insert_property(&mut self, key: JsPropertyKey, property: JsProperty) {
+        if self.properties.insert(key.clone(), property).is_none() {
+            self.order.push(key);
+        }
+    }
+
+    pub fn remove(&mut self, key: &JsPropertyKey) -> Option<JsProperty> {
+        let property = self.properties.remove(key)?;
+        self.order.retain(|ordered_key| ordered_key != key);
+        Some(property)
+    }
+
+    pub fn keys(&self) -> Vec<&JsPropertyKey> {
+        let mut indices: Vec<(usize, &JsPropertyKey)> = self
+            .order
+            .iter()
+            .filter_map(|key| key.as_index().map(|index| (index, key)))
+            .collect();
+        indices.sort_by_key(|(index, _key)| *index);
+
+        let strings = self
+            .order
+            .iter()
+            .filter(|key| key.as_index().is_none() && key.as_str().is_some());
+        let symbols = self.order.iter().filter(|key| key.as_str().is_none());
+        indices
+            .into_iter()
+            .map(|(_index, key)| key)
+            .chain(strings)
+            .chain(symbols)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&JsPropertyKey, &JsProperty)> {
+        self.keys()
+            .into_iter()
+            .map(|key| (key, &self.properties[key]))
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
+
+    pub fn prevent_extensions(&mut self) {
+        self.extensible = false;
+    }
+
+    /// Prevents extensions and makes every property non-configurable
+    pub fn seal(&mut self) {
+        self.extensible = false;
+        for property in self.properties.values_mut() {
+            property.configurable = false;
+        }
+    }
+
+    /// Seals the object and makes every data property read-only
+    pub fn freeze(&mut self) {
+        self.seal();
+        for property in self.properties.values_mut() {
+            if let JsPropertyValue::Data { writable, .. } = &mut property.value {
+                *writable = false;
+            }
+        }
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        !self.extensible
+            && self
+                .properties
+                .values()
+                .all(|property| !property.configurable)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.is_sealed()
+            && self.properties.values().all(|property| {
+                !matches!(property.value, JsPropertyValue::Data { writable: true, .. })
+            })
+    }
+
+    /// Defines or updates a property from a descriptor, returning whether the change was allowed
+    /// by [is_compatible_descriptor]
+    pub fn define(
+        &mut self,
+        node_graph: &JsValueGraph,
+        key: JsPropertyKey,
+        descriptor: JsPropertyDescriptor,
+    ) -> bool {
+        if !is_compatible_descriptor(self.extensible, &descriptor, self.properties.get(&key)) {
+            return false;
+        }
+        let Some(current) = self.properties.get_mut(&key) else {
+            self.insert_property(key, descriptor.into_property(node_graph));
+            return true;
+        };
+
+        let changes_kind = match current.value {
+            JsPropertyValue::Data { .. } => descriptor.is_accessor(),
+            JsPropertyValue::Accessor { .. } => descriptor.is_data(),
+        };
+        if changes_kind {
+            current.value = match current.value {
+                JsPropertyValue::Data { .. } => JsPropertyValue::Accessor {
+                    get: None,
+                    set: None,
+                },
+                JsPropertyValue::Accessor { .. } => JsPropertyValue::Data {
+                    value: JsValue::undefined_rc(node_graph),
+                    writable: false,
+                },
+            };
+        }
+        match &mut current.value {
+            JsPropertyValue::Data { value, writable } => {
+                if let Some(new_value) = descriptor.value {
+                    *value = new_value;
+                }
+                if let Some(new_writable) = descriptor.writable {
+                    *writable = new_writable;
+                }
+            }
+            JsPropertyValue::Accessor { get, set } => {
+                if let Some(new_get) = descriptor.get {
+                    *get = new_get;
+                }
+                if let Some(new_set) = descriptor.set {
+                    *set = new_set;
+                }
+            }
+        }
+        if let Some(enumerable) = descriptor.enumerable {
+            current.enumerable = enumerable;
+        }
+        if let Some(configurable) = descriptor.configurable {
+            current.configurable = configurable;
+        }
+        true
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.properties
+            .values()
+            .flat_map(JsProperty::get_referenced_nodes)
+            .collect()
+    }
+}
+
+impl<const N: usize> From<[(JsPropertyKey, JsValueNode); N]> for JsProperties {
+    fn from(entries: [(JsPropertyKey, JsValueNode); N]) -> Self {
+        let mut properties = Self::new();
+        for (key, value) in entries {
+            properties.insert(key, value);
+        }
+        properties
+    }
+}
+
+/// Whether a property may be defined from a descriptor, given the current property if any and
+/// whether the object is extensible. Non-configurable properties may only have their value changed
+/// while writable, or be made read-only.
+pub fn is_compatible_descriptor(
+    extensible: bool,
+    descriptor: &JsPropertyDescriptor,
+    current: Option<&JsProperty>,
+) -> bool {
+    let Some(current) = current else {
+        return extensible;
+    };
+    if current.configurable {
+        return true;
+    }
+    let changes_kind = match current.value {
+        JsPropertyValue::Data { .. } => descriptor.is_accessor(),
+        JsPropertyValue::Accessor { .. } => descriptor.is_data(),
+    };
+    if descriptor.configurable == Some(true)
+        || descriptor
+            .enumerable
+            .is_some_and(|enumerable| enumerable != current.enumerable)
+        || changes_kind
+    {
+        return false;
+    }
+    match &current.value {
+        JsPropertyValue::Data {
+            value,
+            writable: false,
+        } => {
+            descriptor.writable != Some(true)
+                && descriptor
+                    .value
+                    .as_ref()
+                    .map_or(true, |new_value| is_same_value(value, new_value))
+        }
+        JsPropertyValue::Data { writable: true, .. } => true,
+        JsPropertyValue::Accessor { get, set } => {
+            descriptor
+                .get
+                .as_ref()
+                .map_or(true, |new_get| is_same_function(get, new_get))
+                && descriptor
+                    .set
+                    .as_ref()
+                    .map_or(true, |new_set| is_same_function(set, new_set))
+        }
+    }
+}
+
+/// The SameValue algorithm, where `NaN` equals itself, `0` differs from `-0`, and objects are
+/// compared by reference
+pub fn is_same_value(a: &JsValueNode, b: &JsValueNode) -> bool {
+    match (a.get_ref(), b.get_ref()) {
+        (JsValue::Number(a), JsValue::Number(b)) => {
+            (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+        }
+        (a_value, b_value) if a_value.is_object() || b_value.is_object() => a.is_same_ref(b),
+        (a_value, b_value) => a_value == b_value,
+    }
+}
+
+fn is_same_function(current: &Option<JsValueNode>, new: &Option<JsValueNode>) -> bool {
+    match (current, new) {
+        (Some(current), Some(new)) => current.is_same_ref(new),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{garbage_collector::GcNodeGraph, js::JsSymbol};
+
+    use super::*;
+
+    #[test]
+    fn test_key_order() {
+        let (node_graph, _root) = GcNodeGraph::new(JsValue::Undefined);
+        let value = JsValue::undefined_rc(&node_graph);
+        let symbol = JsPropertyKey::from(JsSymbol::new(None));
+        let mut properties = JsProperties::new();
+        for key in [
+            JsPropertyKey::from("b"),
+            symbol.clone(),
+            "10".into(),
+            "a".into(),
+            "2".into(),
+            "-1".into(),
+        ] {
+            properties.insert(key, value.clone());
+        }
+        properties.remove(&"a".into());
+        properties.insert("a".into(), value);
+
+        assert_eq!(
+            properties.keys(),
+            vec![
+                &JsPropertyKey::from("2"),
+                &"10".into(),
+                &"b".into(),
+                &"-1".into(),
+                &"a".into(),
+                &symbol,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_define_non_configurable() {
+        let (node_graph, _root) = GcNodeGraph::new(JsValue::Undefined);
+        let mut properties = JsProperties::new();
+        assert!(properties.define(
+            &node_graph,
+            "a".into(),
+            JsPropertyDescriptor {
+                value: Some(JsValue::number_rc(&node_graph, 1.0)),
+                ..Default::default()
+            },
+        ));
+        let property = properties.get(&"a".into()).unwrap();
+        assert!(!property.enumerable);
+        assert!(!property.configurable);
+
+        // Redefining with the same value is allowed, but nothing else is
+        assert!(properties.define(
+            &node_graph,
+            "a".into(),
+            JsPropertyDescriptor {
+                value: Some(JsValue::number_rc(&node_graph, 1.0)),
+                writable: Some(false),
+                ..Default::default()
+            },
+        ));
+        assert!(!properties.define(
+            &node_graph,
+            "a".into(),
+            JsPropertyDescriptor {
+                value: Some(JsValue::number_rc(&node_graph, 2.0)),
+                ..Default::default()
+            },
+        ));
+        assert!(!properties.define(
+            &node_graph,
+            "a".into(),
+            JsPropertyDescriptor {
+                get: Some(None),
+                ..Default::default()
+            },
+        ));
+        assert!(!properties.define(
+            &node_graph,
+            "a".into(),
+            JsPropertyDescriptor {
+                enumerable: Some(true),
+                ..Default::default()
+            },
+        ));
+    }
+
+    #[test]
+    fn test_freeze() {
+        let (node_graph, _root) = GcNodeGraph::new(JsValue::Undefined);
+        let mut properties =
+            JsProperties::from([("a".into(), JsValue::number_rc(&node_graph, 1.0))]);
+        assert!(!properties.is_sealed());
+        properties.seal();
+        assert!(properties.is_sealed());
+        assert!(!properties.is_frozen());
+        properties.freeze();
+        assert!(properties.is_frozen());
+        assert!(!properties.define(&node_graph, "b".into(), JsPropertyDescriptor::default()));
+    }
+}
@@ -0,0 +1,70 @@
+use super::{JsSymbol, JsValue, JsValueGraph, JsValueNode};
+
+/// Properties are keyed by either strings or symbols. All other values used as keys are converted
+/// to strings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum JsPropertyKey {
+    String(String),
+    Symbol(JsSymbol),
+}
+
+impl JsPropertyKey {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            Self::Symbol(_) => None,
+        }
+    }
+
+    /// Array indices are canonical, non-negative integers
+    pub fn as_index(&self) -> Option<usize> {
+        let key = self.as_str()?;
+        key.parse::<usize>()
+            .ok()
+            .filter(|index| index.to_string() == key)
+    }
+
+    /// The string or symbol value of the key, as passed to proxy traps
+    pub fn to_value_rc(&self, node_graph: &JsValueGraph) -> JsValueNode {
+        match self {
+            Self::String(string) => JsValue::str_rc(node_graph, string),
+            Self::Symbol(symbol) => JsValue::symbol_rc(node_graph, symbol.clone()),
+        }
+    }
+}
+
+impl From<&JsValue> for JsPropertyKey {
+    fn from(value: &JsValue) -> Self {
+        match value {
+            JsValue::Symbol(symbol) => Self::Symbol(symbol.clone()),
+            value => Self::String(value.to_string()),
+        }
+    }
+}
+
+impl From<&str> for JsPropertyKey {
+    fn from(string: &str) -> Self {
+        Self::String(string.to_string())
+    }
+}
+
+impl From<String> for JsPropertyKey {
+    fn from(string: String) -> Self {
+        Self::String(string)
+    }
+}
+
+impl From<JsSymbol> for JsPropertyKey {
+    fn from(symbol: JsSymbol) -> Self {
+        Self::Symbol(symbol)
+    }
+}
+
+impl std::fmt::Display for JsPropertyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(string) => write!(f, "{string}"),
+            Self::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
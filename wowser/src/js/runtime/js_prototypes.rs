@@ -1,103 +1,707 @@
-use std::{collections::HashMap, rc::Rc};
-
-use crate::garbage_collector::GcNodeGraph;
+use std::rc::Rc;
 
 use super::{
-    JsClosureContext, JsFunction, JsFunctionResult, JsNativeFunctionImplementation, JsValue,
-    JsValueGraph, JsValueNode,
+    get_named_properties, internal_get, internal_get_own_property, internal_own_property_keys,
+    internal_set, invoke_function, iterator_result_rc, string_match, string_match_all,
+    string_replace, string_search, string_split, JsClosureContext, JsCollectionKind, JsFunction,
+    JsFunctionResult, JsIterationKind, JsIterator, JsKeyedCollection,
+    JsNativeFunctionImplementation, JsProperties, JsProperty, JsPropertyDescriptor, JsPropertyKey,
+    JsPropertyValue, JsSymbol, JsValue, JsValueGraph, JsValueNode, JsWellKnownSymbol,
 };
 
-const PROTOTYPE_MEMBER: &str = "__proto__";
+pub const PROTOTYPE_MEMBER: &str = "__proto__";
+pub const LENGTH_MEMBER: &str = "length";
+const SIZE_MEMBER: &str = "size";
+const DESCRIPTION_MEMBER: &str = "description";
+const LAST_INDEX_MEMBER: &str = "lastIndex";
+const BUFFER_MEMBER: &str = "buffer";
+const BYTE_LENGTH_MEMBER: &str = "byteLength";
+const BYTE_OFFSET_MEMBER: &str = "byteOffset";
 
-pub fn get_member_from_prototype_chain(
+/// The value a value inherits members from, or `None` at the end of the prototype chain
+pub fn get_prototype_of(
     value: &JsValue,
-    member: &str,
     closure_context: &JsClosureContext,
-) -> JsValueNode {
-    let nodes_graph = &closure_context.nodes_graph;
+) -> Option<JsValueNode> {
+    let prototypes = &closure_context.global_prototypes;
+    let prototype = match value {
+        JsValue::Boolean(_) => &prototypes.boolean,
+        JsValue::Number(_) => &prototypes.number,
+        JsValue::BigInt(_) => &prototypes.bigint,
+        JsValue::String(_) => &prototypes.string,
+        JsValue::Symbol(_) => &prototypes.symbol,
+        JsValue::Array(_, _) => &prototypes.array,
+        JsValue::Function(_, _) => &prototypes.function,
+        JsValue::Collection(collection) => match collection.kind {
+            JsCollectionKind::Map => &prototypes.map,
+            JsCollectionKind::Set => &prototypes.set,
+            JsCollectionKind::WeakMap => &prototypes.weak_map,
+            JsCollectionKind::WeakSet => &prototypes.weak_set,
+        },
+        JsValue::Iterator(_) => &prototypes.iterator,
+        JsValue::RegExp(_) => &prototypes.regexp,
+        JsValue::Date(_) => &prototypes.date,
+        JsValue::ArrayBuffer(_) => &prototypes.array_buffer,
+        JsValue::TypedArray(array) => &prototypes.typed_arrays[&array.kind],
+        JsValue::DataView(_) => &prototypes.data_view,
+        JsValue::HostObject(object) => prototypes
+            .host_classes
+            .get(object.class_name)
+            .unwrap_or(&prototypes.object),
+        JsValue::Object(properties) => {
+            return match properties.get_value(&PROTOTYPE_MEMBER.into()) {
+                Some(parent) if matches!(parent.get_ref(), JsValue::Null) => None,
+                Some(parent) => Some(parent.clone()),
+                None => Some(prototypes.object.clone()),
+            };
+        }
+        // The prototype of a proxy is only known by running its trap. See
+        // [internal_get_prototype_of].
+        JsValue::Proxy(_) | JsValue::Undefined | JsValue::Null => return None,
+    };
+    Some(prototype.clone())
+}
+
+/// An own property of a value, including the members built into arrays, strings, symbols,
+/// collections, and array buffers and their views
+pub fn get_own_property(
+    value: &JsValue,
+    member: &JsPropertyKey,
+    nodes_graph: &JsValueGraph,
+) -> Option<JsProperty> {
+    let is_member = |name| member.as_str() == Some(name);
     match value {
-        JsValue::Boolean(_) => get_member_from_prototype_chain(
-            closure_context.global_prototypes.boolean.get_ref(),
-            member,
-            closure_context,
-        ),
-        JsValue::Number(_) => get_member_from_prototype_chain(
-            closure_context.global_prototypes.number.get_ref(),
-            member,
-            closure_context,
-        ),
-        JsValue::String(_) => get_member_from_prototype_chain(
-            closure_context.global_prototypes.string.get_ref(),
-            member,
-            closure_context,
-        ),
-        JsValue::Function(_) => get_member_from_prototype_chain(
-            closure_context.global_prototypes.function.get_ref(),
-            member,
-            closure_context,
-        ),
-        o @ JsValue::Object(map) => {
-            if let Some(value) = map.get(member) {
-                return value.clone();
+        JsValue::String(string) => {
+            if is_member(LENGTH_MEMBER) {
+                let length = JsValue::number_rc(nodes_graph, string.chars().count() as f64);
+                return Some(JsProperty::with_attributes(length, false, false, false));
             }
-            if let Some(parent) = map.get(PROTOTYPE_MEMBER) {
-                return get_member_from_prototype_chain(parent.get_ref(), member, closure_context);
+            let c = member
+                .as_index()
+                .and_then(|index| string.chars().nth(index))?;
+            let c = JsValue::string_rc(nodes_graph, c.to_string());
+            Some(JsProperty::with_attributes(c, false, true, false))
+        }
+        JsValue::Symbol(symbol) if is_member(DESCRIPTION_MEMBER) => {
+            let description = match &symbol.description {
+                Some(description) => JsValue::str_rc(nodes_graph, description),
+                None => JsValue::undefined_rc(nodes_graph),
+            };
+            Some(JsProperty::with_attributes(description, false, false, true))
+        }
+        JsValue::Array(elements, properties) => {
+            if is_member(LENGTH_MEMBER) {
+                let length = JsValue::number_rc(nodes_graph, elements.len() as f64);
+                return Some(JsProperty::with_attributes(length, true, false, false));
             }
-
-            let object_prototype = closure_context.global_prototypes.object.get_ref();
-            if o as *const JsValue != object_prototype as *const JsValue {
-                return get_member_from_prototype_chain(object_prototype, member, closure_context);
+            match member.as_index() {
+                Some(index) => Some(JsProperty::data(elements.get(index)?.clone())),
+                None => properties.get(member).cloned(),
             }
-            JsValue::undefined_rc(nodes_graph)
         }
-        JsValue::Undefined => JsValue::undefined_rc(nodes_graph), // TODO: TypeError
-        JsValue::Null => JsValue::undefined_rc(nodes_graph),      // TODO: TypeError
+        JsValue::TypedArray(array) => {
+            let read_only_number = |number: usize| {
+                let number = JsValue::number_rc(nodes_graph, number as f64);
+                Some(JsProperty::with_attributes(number, false, false, true))
+            };
+            match member.as_str() {
+                Some(LENGTH_MEMBER) => read_only_number(array.length),
+                Some(BYTE_LENGTH_MEMBER) => read_only_number(array.byte_length()),
+                Some(BYTE_OFFSET_MEMBER) => read_only_number(array.byte_offset),
+                Some(BUFFER_MEMBER) => Some(JsProperty::with_attributes(
+                    array.buffer.clone(),
+                    false,
+                    false,
+                    true,
+                )),
+                _ => {
+                    let element = array.get(member.as_index()?)?;
+                    let element = element.to_value_rc(nodes_graph);
+                    Some(JsProperty::with_attributes(element, true, true, false))
+                }
+            }
+        }
+        JsValue::ArrayBuffer(bytes) if is_member(BYTE_LENGTH_MEMBER) => {
+            let byte_length = JsValue::number_rc(nodes_graph, bytes.len() as f64);
+            Some(JsProperty::with_attributes(byte_length, false, false, true))
+        }
+        JsValue::DataView(view) => {
+            let number = match member.as_str()? {
+                BYTE_LENGTH_MEMBER => view.byte_length,
+                BYTE_OFFSET_MEMBER => view.byte_offset,
+                BUFFER_MEMBER => {
+                    let buffer = view.buffer.clone();
+                    return Some(JsProperty::with_attributes(buffer, false, false, true));
+                }
+                _ => return None,
+            };
+            let number = JsValue::number_rc(nodes_graph, number as f64);
+            Some(JsProperty::with_attributes(number, false, false, true))
+        }
+        JsValue::Collection(collection) if is_member(SIZE_MEMBER) && !collection.kind.is_weak() => {
+            let size = JsValue::number_rc(nodes_graph, collection.len() as f64);
+            Some(JsProperty::with_attributes(size, false, false, true))
+        }
+        JsValue::RegExp(regexp) if is_member(LAST_INDEX_MEMBER) => {
+            let last_index = JsValue::number_rc(nodes_graph, regexp.last_index as f64);
+            Some(JsProperty::with_attributes(last_index, true, false, false))
+        }
+        JsValue::Object(properties) | JsValue::Function(_, properties) => {
+            properties.get(member).cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Reads a member of a value. Getters are invoked with the value as `this`.
+pub fn get_member_from_prototype_chain(
+    value: &JsValueNode,
+    member: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    internal_get(value, member, value, closure_context)
+}
+
+/// The keys of the own, enumerable properties of a value. Proxies and host objects with named
+/// properties are asked for their keys and then for the descriptor of each.
+pub fn get_own_enumerable_keys(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsPropertyKey>, JsValueNode> {
+    if !matches!(value.get_ref(), JsValue::Proxy(_)) && get_named_properties(value).is_none() {
+        return Ok(value.get_ref().own_enumerable_keys());
+    }
+    let mut keys = vec![];
+    for key in internal_own_property_keys(value, closure_context)? {
+        let property = internal_get_own_property(value, &key, closure_context)?;
+        if property.is_some_and(|property| property.enumerable) {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+/// The own, enumerable properties of a value in the order they are copied by object spread and
+/// rest syntax. Getters are invoked to read their values.
+pub fn get_own_enumerable_entries(
+    value: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<(JsPropertyKey, JsValueNode)>, JsValueNode> {
+    let mut entries = vec![];
+    for key in get_own_enumerable_keys(value, closure_context)? {
+        let member = get_member_from_prototype_chain(value, &key, closure_context)?;
+        entries.push((key, member));
+    }
+    Ok(entries)
+}
+
+/// Assigns a member on a value, invoking setters found on the prototype chain. Returns whether
+/// the assignment took effect, which it does not for read-only properties, non-extensible objects,
+/// or members of primitives. See [internal_set].
+pub fn set_member(
+    target: &mut JsValueNode,
+    member: &JsPropertyKey,
+    value: JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    match target.get_mut() {
+        JsValue::Undefined | JsValue::Null => return Err(JsValue::type_error_rc(&nodes_graph)),
+        JsValue::Array(elements, _) if is_array_element(member) => {
+            return set_array_member(elements, member, value, &nodes_graph)
+        }
+        // Indices out of range are ignored
+        JsValue::TypedArray(array) if member.as_index().is_some() => {
+            let index = member.as_index().unwrap_or_default();
+            array.set(index, &array.kind.to_element(&value, closure_context)?);
+            return Ok(true);
+        }
+        JsValue::RegExp(regexp) if member.as_str() == Some(LAST_INDEX_MEMBER) => {
+            let last_index = f64::from(value.get_ref());
+            regexp.last_index = if last_index > 0.0 {
+                last_index as usize
+            } else {
+                0
+            };
+            return Ok(true);
+        }
+        _ => {}
+    }
+
+    internal_set(&target.clone(), member, value, target, closure_context)
+}
+
+/// Defines an own property from a descriptor, returning whether the definition was allowed.
+/// The elements and length of arrays only support data properties with their usual attributes.
+pub fn define_own_property(
+    target: &mut JsValueNode,
+    member: JsPropertyKey,
+    descriptor: JsPropertyDescriptor,
+    closure_context: &JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let nodes_graph = &closure_context.nodes_graph;
+    let is_element = is_array_element(&member);
+    if let (JsValue::Array(_, _), true) = (target.get_ref(), is_element) {
+        let writable = |property: &JsProperty| {
+            matches!(property.value, JsPropertyValue::Data { writable: true, .. })
+        };
+        let current = get_own_property(target.get_ref(), &member, nodes_graph)
+            .unwrap_or_else(|| JsProperty::data(JsValue::undefined_rc(nodes_graph)));
+        if descriptor.is_accessor()
+            || descriptor.writable.unwrap_or(writable(&current)) != writable(&current)
+            || descriptor.enumerable.unwrap_or(current.enumerable) != current.enumerable
+            || descriptor.configurable.unwrap_or(current.configurable) != current.configurable
+        {
+            return Ok(false);
+        }
+    }
+
+    match target.get_mut() {
+        JsValue::Array(elements, _) if is_element => match descriptor.value {
+            Some(value) => set_array_member(elements, &member, value, nodes_graph),
+            None => Ok(true),
+        },
+        JsValue::Object(properties)
+        | JsValue::Function(_, properties)
+        | JsValue::Array(_, properties) => Ok(properties.define(nodes_graph, member, descriptor)),
+        _ => Ok(false),
+    }
+}
+
+/// Whether a member is stored as the elements of an array rather than as one of its properties
+fn is_array_element(member: &JsPropertyKey) -> bool {
+    member.as_index().is_some() || member.as_str() == Some(LENGTH_MEMBER)
+}
+
+/// Assigns an element or the length of an array. Other members are ignored.
+fn set_array_member(
+    elements: &mut Vec<JsValueNode>,
+    member: &JsPropertyKey,
+    value: JsValueNode,
+    nodes_graph: &JsValueGraph,
+) -> Result<bool, JsValueNode> {
+    if let Some(index) = member.as_index() {
+        if index >= elements.len() {
+            elements.resize_with(index + 1, || JsValue::undefined_rc(nodes_graph));
+        }
+        elements[index] = value;
+        Ok(true)
+    } else if member.as_str() == Some(LENGTH_MEMBER) {
+        let length = f64::from(value.get_ref());
+        if length < 0.0 || length.fract() != 0.0 {
+            return Err(JsValue::range_error_rc(nodes_graph));
+        }
+        elements.resize_with(length as usize, || JsValue::undefined_rc(nodes_graph));
+        Ok(true)
+    } else {
+        Ok(false)
     }
 }
 
 pub fn build_object_prototype(node_graph: &JsValueGraph) -> JsValueNode {
     build_prototype(
         JsValue::null_rc(node_graph),
-        [build_function_entry(
-            node_graph,
-            "toString",
-            object_to_string,
-        )],
+        [
+            build_function_entry(node_graph, "toString".into(), object_to_string),
+            build_function_entry(node_graph, "hasOwnProperty".into(), object_has_own_property),
+        ],
     )
 }
 
 pub fn build_prototype<const N: usize>(
     parent: JsValueNode,
-    members: [(String, JsValueNode); N],
+    members: [(JsPropertyKey, JsValueNode); N],
 ) -> JsValueNode {
     let node_graph = parent.get_node_graph();
-    let mut map = HashMap::from(members);
-    map.insert(PROTOTYPE_MEMBER.to_string(), parent);
-    JsValue::object_rc(&node_graph, map)
+    let mut properties = JsProperties::new();
+    // Built-in members are not enumerable so they are skipped when copying objects
+    for (key, value) in members {
+        properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+    }
+    properties.insert_property(
+        PROTOTYPE_MEMBER.into(),
+        JsProperty::with_attributes(parent, true, false, true),
+    );
+    JsValue::object_rc(&node_graph, properties)
 }
 
-fn build_function_entry(
+/// Creates a native function. Functions keyed by symbols are named after the symbol's description,
+/// such as `[Symbol.iterator]`.
+pub fn build_native_function(
     node_graph: &JsValueGraph,
-    name: &str,
-    func: impl Fn(JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
-) -> (String, JsValueNode) {
-    (
-        name.to_string(),
-        GcNodeGraph::create_node(
-            node_graph,
-            JsValue::Function(JsFunction::Native(
-                name.to_string(),
-                JsNativeFunctionImplementation {
-                    func: Rc::new(func),
-                },
-            )),
+    name: &JsPropertyKey,
+    func: impl Fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
+) -> JsValueNode {
+    build_native_function_capturing(node_graph, name, vec![], func)
+}
+
+/// Creates a native function holding on to the values it captures, so they are not collected as
+/// garbage while it can still be called
+pub fn build_native_function_capturing(
+    node_graph: &JsValueGraph,
+    name: &JsPropertyKey,
+    captures: Vec<JsValueNode>,
+    func: impl Fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
+) -> JsValueNode {
+    let name = match name {
+        JsPropertyKey::String(name) => name.clone(),
+        JsPropertyKey::Symbol(symbol) => {
+            format!("[{}]", symbol.description.as_deref().unwrap_or_default())
+        }
+    };
+    JsValue::function_rc(
+        node_graph,
+        JsFunction::Native(
+            name,
+            JsNativeFunctionImplementation {
+                func: Rc::new(func),
+                captures,
+            },
         ),
     )
 }
 
-fn object_to_string(this: JsValueNode, _args: &[JsValueNode]) -> JsFunctionResult {
+pub fn build_function_entry(
+    node_graph: &JsValueGraph,
+    key: JsPropertyKey,
+    func: impl Fn(&mut JsClosureContext, JsValueNode, &[JsValueNode]) -> JsFunctionResult + 'static,
+) -> (JsPropertyKey, JsValueNode) {
+    let function = build_native_function(node_graph, &key, func);
+    (key, function)
+}
+
+fn object_to_string(
+    _closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    _args: &[JsValueNode],
+) -> JsFunctionResult {
     Ok(JsValue::string_rc(
         &this.get_node_graph(),
         this.get_ref().to_string(),
     ))
 }
+
+fn object_has_own_property(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    if matches!(this.get_ref(), JsValue::Undefined | JsValue::Null) {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let key = JsPropertyKey::from(get_argument(closure_context, args, 0).get_ref());
+    let has_property = key.as_str() != Some(PROTOTYPE_MEMBER)
+        && internal_get_own_property(&this, &key, closure_context)?.is_some();
+    Ok(JsValue::bool_rc(&closure_context.nodes_graph, has_property))
+}
+
+pub fn build_array_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let values = build_native_function(&node_graph, &"values".into(), |_, this, _| {
+        Ok(iterate(this, JsIterationKind::Values))
+    });
+    build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "keys".into(), |_, this, _| {
+                Ok(iterate(this, JsIterationKind::Keys))
+            }),
+            build_function_entry(&node_graph, "entries".into(), |_, this, _| {
+                Ok(iterate(this, JsIterationKind::Entries))
+            }),
+            ("values".into(), values.clone()),
+            (iterator_key(), values),
+        ],
+    )
+}
+
+pub fn build_string_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "match".into(), string_match),
+            build_function_entry(&node_graph, "matchAll".into(), string_match_all),
+            build_function_entry(&node_graph, "replace".into(), string_replace),
+            build_function_entry(&node_graph, "search".into(), string_search),
+            build_function_entry(&node_graph, "split".into(), string_split),
+            build_function_entry(&node_graph, iterator_key(), |_, this, _| {
+                Ok(iterate(this, JsIterationKind::Values))
+            }),
+        ],
+    )
+}
+
+pub fn build_symbol_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [build_function_entry(
+            &node_graph,
+            "toString".into(),
+            object_to_string,
+        )],
+    )
+}
+
+/// The prototype shared by the iterators of built-in arrays, strings, and collections
+pub fn build_iterator_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [
+            build_function_entry(
+                &node_graph,
+                "next".into(),
+                |closure_context, mut this, _| {
+                    let nodes_graph = &closure_context.nodes_graph;
+                    match this.get_mut() {
+                        JsValue::Iterator(iterator) => {
+                            let value = iterator.next(nodes_graph);
+                            Ok(iterator_result_rc(nodes_graph, value))
+                        }
+                        _ => Err(JsValue::type_error_rc(nodes_graph)),
+                    }
+                },
+            ),
+            build_function_entry(&node_graph, iterator_key(), |_, this, _| Ok(this)),
+        ],
+    )
+}
+
+pub fn build_map_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let kind = JsCollectionKind::Map;
+    let entries = build_native_function(&node_graph, &"entries".into(), move |ctx, this, _| {
+        iterate_collection(ctx, this, kind, JsIterationKind::Entries)
+    });
+    build_prototype(
+        parent,
+        [
+            build_collection_get(&node_graph, kind),
+            build_collection_set(&node_graph, kind),
+            build_collection_has(&node_graph, kind),
+            build_collection_delete(&node_graph, kind),
+            build_collection_clear(&node_graph, kind),
+            build_collection_for_each(&node_graph, kind),
+            build_function_entry(&node_graph, "keys".into(), move |ctx, this, _| {
+                iterate_collection(ctx, this, kind, JsIterationKind::Keys)
+            }),
+            build_function_entry(&node_graph, "values".into(), move |ctx, this, _| {
+                iterate_collection(ctx, this, kind, JsIterationKind::Values)
+            }),
+            ("entries".into(), entries.clone()),
+            (iterator_key(), entries),
+        ],
+    )
+}
+
+pub fn build_set_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let kind = JsCollectionKind::Set;
+    let values = build_native_function(&node_graph, &"values".into(), move |ctx, this, _| {
+        iterate_collection(ctx, this, kind, JsIterationKind::Values)
+    });
+    build_prototype(
+        parent,
+        [
+            build_collection_add(&node_graph, kind),
+            build_collection_has(&node_graph, kind),
+            build_collection_delete(&node_graph, kind),
+            build_collection_clear(&node_graph, kind),
+            build_collection_for_each(&node_graph, kind),
+            build_function_entry(&node_graph, "entries".into(), move |ctx, this, _| {
+                iterate_collection(ctx, this, kind, JsIterationKind::Entries)
+            }),
+            ("values".into(), values.clone()),
+            ("keys".into(), values.clone()),
+            (iterator_key(), values),
+        ],
+    )
+}
+
+pub fn build_weak_map_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let kind = JsCollectionKind::WeakMap;
+    build_prototype(
+        parent,
+        [
+            build_collection_get(&node_graph, kind),
+            build_collection_set(&node_graph, kind),
+            build_collection_has(&node_graph, kind),
+            build_collection_delete(&node_graph, kind),
+        ],
+    )
+}
+
+pub fn build_weak_set_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let kind = JsCollectionKind::WeakSet;
+    build_prototype(
+        parent,
+        [
+            build_collection_add(&node_graph, kind),
+            build_collection_has(&node_graph, kind),
+            build_collection_delete(&node_graph, kind),
+        ],
+    )
+}
+
+pub fn iterator_key() -> JsPropertyKey {
+    JsSymbol::well_known(JsWellKnownSymbol::Iterator).into()
+}
+
+pub fn iterate(source: JsValueNode, kind: JsIterationKind) -> JsValueNode {
+    let node_graph = source.get_node_graph();
+    JsValue::iterator_rc(&node_graph, JsIterator::new(source, kind))
+}
+
+fn iterate_collection(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    collection_kind: JsCollectionKind,
+    kind: JsIterationKind,
+) -> JsFunctionResult {
+    with_collection(closure_context, this.clone(), collection_kind, |_| ())?;
+    Ok(iterate(this, kind))
+}
+
+/// Runs `func` on the collection `this`, throwing a `TypeError` if `this` is not a collection of the
+/// given kind
+fn with_collection<T>(
+    closure_context: &JsClosureContext,
+    mut this: JsValueNode,
+    kind: JsCollectionKind,
+    func: impl FnOnce(&mut JsKeyedCollection) -> T,
+) -> Result<T, JsValueNode> {
+    match this.get_mut() {
+        JsValue::Collection(collection) if collection.kind == kind => Ok(func(collection)),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// Weak collections can only hold objects, since primitives could be recreated at any time
+fn is_valid_key(kind: JsCollectionKind, key: &JsValueNode) -> bool {
+    !kind.is_weak() || key.get_ref().is_object()
+}
+
+fn build_collection_get(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "get".into(), move |ctx, this, args| {
+        let key = get_argument(ctx, args, 0);
+        let value = with_collection(ctx, this, kind, |collection| collection.get(&key).cloned())?;
+        Ok(value.unwrap_or_else(|| JsValue::undefined_rc(&ctx.nodes_graph)))
+    })
+}
+
+fn build_collection_set(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "set".into(), move |ctx, this, args| {
+        let key = get_argument(ctx, args, 0);
+        let value = get_argument(ctx, args, 1);
+        if !is_valid_key(kind, &key) {
+            return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+        }
+        with_collection(ctx, this.clone(), kind, |collection| {
+            collection.insert(key, value)
+        })?;
+        Ok(this)
+    })
+}
+
+fn build_collection_add(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "add".into(), move |ctx, this, args| {
+        let value = get_argument(ctx, args, 0);
+        if !is_valid_key(kind, &value) {
+            return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+        }
+        with_collection(ctx, this.clone(), kind, |collection| {
+            collection.insert(value.clone(), value)
+        })?;
+        Ok(this)
+    })
+}
+
+fn build_collection_has(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "has".into(), move |ctx, this, args| {
+        let key = get_argument(ctx, args, 0);
+        let has = with_collection(ctx, this, kind, |collection| collection.has(&key))?;
+        Ok(JsValue::bool_rc(&ctx.nodes_graph, has))
+    })
+}
+
+fn build_collection_delete(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "delete".into(), move |ctx, this, args| {
+        let key = get_argument(ctx, args, 0);
+        let deleted = with_collection(ctx, this, kind, |collection| collection.remove(&key))?;
+        Ok(JsValue::bool_rc(&ctx.nodes_graph, deleted))
+    })
+}
+
+fn build_collection_clear(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "clear".into(), move |ctx, this, _| {
+        with_collection(ctx, this, kind, JsKeyedCollection::clear)?;
+        Ok(JsValue::undefined_rc(&ctx.nodes_graph))
+    })
+}
+
+/// Invokes the callback with `(value, key, collection)` for each entry, including entries added
+/// during iteration
+fn build_collection_for_each(
+    node_graph: &JsValueGraph,
+    kind: JsCollectionKind,
+) -> (JsPropertyKey, JsValueNode) {
+    build_function_entry(node_graph, "forEach".into(), move |ctx, this, args| {
+        let callback = get_argument(ctx, args, 0);
+        let this_arg = get_argument(ctx, args, 1);
+        if !matches!(callback.get_ref(), JsValue::Function(_, _)) {
+            return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+        }
+
+        let mut position = 0;
+        loop {
+            let entry = with_collection(ctx, this.clone(), kind, |collection| {
+                collection
+                    .next_entry(position)
+                    .map(|(position, (key, value))| (position, key.clone(), value.clone()))
+            })?;
+            let Some((entry_position, key, value)) = entry else {
+                return Ok(JsValue::undefined_rc(&ctx.nodes_graph));
+            };
+            position = entry_position + 1;
+            invoke_function(
+                ctx,
+                &callback,
+                this_arg.clone(),
+                &[value, key, this.clone()],
+            )?;
+        }
+    })
+}
+
+/// Missing arguments are `undefined`
+pub fn get_argument(
+    closure_context: &JsClosureContext,
+    args: &[JsValueNode],
+    index: usize,
+) -> JsValueNode {
+    args.get(index)
+        .cloned()
+        .unwrap_or_else(|| JsValue::undefined_rc(&closure_context.nodes_graph))
+}
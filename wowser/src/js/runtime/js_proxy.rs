@@ -0,0 +1,568 @@
+use std::{cell::Cell, rc::Rc, slice};
+
+use super::{
+    build_function_entry, build_native_function, construct_with_new_target,
+    create_list_from_array_like, from_property_descriptor, get_argument,
+    get_member_from_prototype_chain, internal_define_own_property, internal_delete, internal_get,
+    internal_get_own_property, internal_get_prototype_of, internal_has_property,
+    internal_is_extensible, internal_own_property_keys, internal_prevent_extensions, internal_set,
+    internal_set_prototype_of, invoke_function, is_compatible_descriptor, is_same_prototype,
+    is_same_value, to_property_descriptor, JsClosureContext, JsFunctionResult, JsProperties,
+    JsProperty, JsPropertyDescriptor, JsPropertyKey, JsPropertyValue, JsValue, JsValueGraph,
+    JsValueNode,
+};
+
+/// An object whose internal methods are implemented by the traps of a handler, falling back to
+/// the target for traps the handler does not define.
+/// <https://tc39.es/ecma262/#sec-proxy-object-internal-methods-and-internal-slots>
+#[derive(Clone, Debug)]
+pub struct JsProxy {
+    pub target: JsValueNode,
+    pub handler: JsValueNode,
+    /// Whether the target could be invoked when the proxy was created
+    pub callable: bool,
+    /// Shared with the function returned by `Proxy.revocable`, so revoking does not need a
+    /// reference to the proxy
+    revoked: Rc<Cell<bool>>,
+}
+
+impl JsProxy {
+    pub fn new(target: JsValueNode, handler: JsValueNode) -> Self {
+        Self {
+            callable: target.get_ref().is_callable(),
+            target,
+            handler,
+            revoked: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![self.target.clone(), self.handler.clone()]
+    }
+}
+
+impl PartialEq for JsProxy {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.revoked, &other.revoked)
+    }
+}
+
+/// `Proxy` must be constructed with `new`, from a target and handler which are both objects.
+/// `Proxy.revocable` returns `{ proxy, revoke }`, where calling `revoke` makes every operation on
+/// the proxy throw a `TypeError`.
+pub fn build_proxy_constructor(node_graph: &JsValueGraph) -> JsValueNode {
+    let mut constructor = build_native_function(node_graph, &"Proxy".into(), |ctx, this, args| {
+        if let JsValue::Undefined = this.get_ref() {
+            return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+        }
+        let proxy = create_proxy(ctx, args)?;
+        Ok(JsValue::proxy_rc(&ctx.nodes_graph, proxy))
+    });
+    let (key, revocable) = build_function_entry(node_graph, "revocable".into(), |ctx, _, args| {
+        let proxy = create_proxy(ctx, args)?;
+        let revoked = proxy.revoked.clone();
+        let revoke = build_native_function(&ctx.nodes_graph, &"".into(), move |ctx, _, _| {
+            revoked.set(true);
+            Ok(JsValue::undefined_rc(&ctx.nodes_graph))
+        });
+        let properties = JsProperties::from([
+            ("proxy".into(), JsValue::proxy_rc(&ctx.nodes_graph, proxy)),
+            ("revoke".into(), revoke),
+        ]);
+        Ok(JsValue::object_rc(&ctx.nodes_graph, properties))
+    });
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            key,
+            JsProperty::with_attributes(revocable, true, false, true),
+        );
+    }
+    constructor
+}
+
+fn create_proxy(
+    closure_context: &JsClosureContext,
+    args: &[JsValueNode],
+) -> Result<JsProxy, JsValueNode> {
+    let target = get_argument(closure_context, args, 0);
+    let handler = get_argument(closure_context, args, 1);
+    if !target.get_ref().is_object() || !handler.get_ref().is_object() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(JsProxy::new(target, handler))
+}
+
+/// The trap of the handler with the given name, or `None` when the handler leaves it `undefined`
+/// or `null`. Revoked proxies and traps that are not functions throw a `TypeError`.
+fn get_trap(
+    proxy: &JsProxy,
+    name: &str,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsValueNode>, JsValueNode> {
+    if proxy.revoked.get() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    let trap = get_member_from_prototype_chain(&proxy.handler, &name.into(), closure_context)?;
+    match trap.get_ref() {
+        JsValue::Undefined | JsValue::Null => Ok(None),
+        value if value.is_callable() => Ok(Some(trap.clone())),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// Invokes a trap with the handler as `this`
+fn call_trap(
+    proxy: &JsProxy,
+    trap: &JsValueNode,
+    args: &[JsValueNode],
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    invoke_function(closure_context, trap, proxy.handler.clone(), args)
+}
+
+/// Invokes a trap whose result is converted to a boolean
+fn call_boolean_trap(
+    proxy: &JsProxy,
+    trap: &JsValueNode,
+    args: &[JsValueNode],
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let result = call_trap(proxy, trap, args, closure_context)?;
+    Ok(bool::from(result.get_ref()))
+}
+
+/// Throws a `TypeError` when a trap breaks an invariant of the target
+fn check_invariant(holds: bool, closure_context: &JsClosureContext) -> Result<(), JsValueNode> {
+    match holds {
+        true => Ok(()),
+        false => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// The own property of the target, if it is non-configurable, which traps must report faithfully
+fn get_non_configurable_property(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsProperty>, JsValueNode> {
+    let property = internal_get_own_property(&proxy.target, key, closure_context)?;
+    Ok(property.filter(|property| !property.configurable))
+}
+
+/// The `getPrototypeOf` trap must return an object or `null`, which is the target's prototype
+/// when the target is not extensible
+pub fn proxy_get_prototype_of(
+    proxy: &JsProxy,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsValueNode>, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "getPrototypeOf", closure_context)? else {
+        return internal_get_prototype_of(&proxy.target, closure_context);
+    };
+    let result = call_trap(
+        proxy,
+        &trap,
+        slice::from_ref(&proxy.target),
+        closure_context,
+    )?;
+    let prototype = match result.get_ref() {
+        JsValue::Null => None,
+        value if value.is_object() => Some(result.clone()),
+        _ => return Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    };
+    if !internal_is_extensible(&proxy.target, closure_context)? {
+        let target_prototype = internal_get_prototype_of(&proxy.target, closure_context)?;
+        check_invariant(
+            is_same_prototype(&prototype, &target_prototype),
+            closure_context,
+        )?;
+    }
+    Ok(prototype)
+}
+
+/// The `setPrototypeOf` trap cannot report success for a non-extensible target unless the
+/// prototype is the target's
+pub fn proxy_set_prototype_of(
+    proxy: &JsProxy,
+    prototype: Option<JsValueNode>,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "setPrototypeOf", closure_context)? else {
+        return internal_set_prototype_of(&proxy.target, prototype, closure_context);
+    };
+    let prototype_value = prototype
+        .clone()
+        .unwrap_or_else(|| JsValue::null_rc(&closure_context.nodes_graph));
+    let args = [proxy.target.clone(), prototype_value];
+    if !call_boolean_trap(proxy, &trap, &args, closure_context)? {
+        return Ok(false);
+    }
+    if !internal_is_extensible(&proxy.target, closure_context)? {
+        let target_prototype = internal_get_prototype_of(&proxy.target, closure_context)?;
+        check_invariant(
+            is_same_prototype(&prototype, &target_prototype),
+            closure_context,
+        )?;
+    }
+    Ok(true)
+}
+
+/// The `isExtensible` trap must agree with the target
+pub fn proxy_is_extensible(
+    proxy: &JsProxy,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "isExtensible", closure_context)? else {
+        return internal_is_extensible(&proxy.target, closure_context);
+    };
+    let result = call_boolean_trap(
+        proxy,
+        &trap,
+        slice::from_ref(&proxy.target),
+        closure_context,
+    )?;
+    let target_result = internal_is_extensible(&proxy.target, closure_context)?;
+    check_invariant(result == target_result, closure_context)?;
+    Ok(result)
+}
+
+/// The `preventExtensions` trap cannot report success while the target is still extensible
+pub fn proxy_prevent_extensions(
+    proxy: &JsProxy,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "preventExtensions", closure_context)? else {
+        return internal_prevent_extensions(&proxy.target, closure_context);
+    };
+    let result = call_boolean_trap(
+        proxy,
+        &trap,
+        slice::from_ref(&proxy.target),
+        closure_context,
+    )?;
+    if result {
+        let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+        check_invariant(!extensible, closure_context)?;
+    }
+    Ok(result)
+}
+
+/// The `getOwnPropertyDescriptor` trap must return a descriptor object or `undefined`. It cannot
+/// hide non-configurable properties or the properties of non-extensible targets, nor report
+/// properties as non-configurable or read-only unless they are on the target.
+pub fn proxy_get_own_property(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<Option<JsProperty>, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "getOwnPropertyDescriptor", closure_context)? else {
+        return internal_get_own_property(&proxy.target, key, closure_context);
+    };
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&closure_context.nodes_graph),
+    ];
+    let result = call_trap(proxy, &trap, &args, closure_context)?;
+    let target_property = internal_get_own_property(&proxy.target, key, closure_context)?;
+    let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+
+    match result.get_ref() {
+        JsValue::Undefined => {
+            if let Some(target_property) = target_property {
+                check_invariant(target_property.configurable && extensible, closure_context)?;
+            }
+            return Ok(None);
+        }
+        value if !value.is_object() => {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph))
+        }
+        _ => {}
+    }
+
+    let descriptor = to_property_descriptor(&result, closure_context)?;
+    check_invariant(
+        is_compatible_descriptor(extensible, &descriptor, target_property.as_ref()),
+        closure_context,
+    )?;
+    let property = descriptor.into_property(&closure_context.nodes_graph);
+    if !property.configurable {
+        let Some(target_property) = target_property.filter(|property| !property.configurable)
+        else {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        };
+        let made_read_only = matches!(
+            (&property.value, &target_property.value),
+            (
+                JsPropertyValue::Data {
+                    writable: false,
+                    ..
+                },
+                JsPropertyValue::Data { writable: true, .. }
+            )
+        );
+        check_invariant(!made_read_only, closure_context)?;
+    }
+    Ok(Some(property))
+}
+
+/// The `defineProperty` trap cannot report success for definitions the target would not allow,
+/// nor for definitions of non-configurable or read-only properties unless the target has them
+pub fn proxy_define_own_property(
+    proxy: &JsProxy,
+    key: JsPropertyKey,
+    descriptor: JsPropertyDescriptor,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "defineProperty", closure_context)? else {
+        let mut target = proxy.target.clone();
+        return internal_define_own_property(&mut target, key, descriptor, closure_context);
+    };
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&nodes_graph),
+        from_property_descriptor(&descriptor, &nodes_graph),
+    ];
+    if !call_boolean_trap(proxy, &trap, &args, closure_context)? {
+        return Ok(false);
+    }
+
+    let target_property = internal_get_own_property(&proxy.target, &key, closure_context)?;
+    let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+    let setting_non_configurable = descriptor.configurable == Some(false);
+    let Some(target_property) = target_property else {
+        check_invariant(extensible && !setting_non_configurable, closure_context)?;
+        return Ok(true);
+    };
+    check_invariant(
+        is_compatible_descriptor(extensible, &descriptor, Some(&target_property)),
+        closure_context,
+    )?;
+    check_invariant(
+        !setting_non_configurable || !target_property.configurable,
+        closure_context,
+    )?;
+    if let JsPropertyValue::Data { writable: true, .. } = target_property.value {
+        let made_read_only = !target_property.configurable && descriptor.writable == Some(false);
+        check_invariant(!made_read_only, closure_context)?;
+    }
+    Ok(true)
+}
+
+/// The `has` trap cannot hide non-configurable properties or the properties of non-extensible
+/// targets
+pub fn proxy_has(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "has", closure_context)? else {
+        return internal_has_property(&proxy.target, key, closure_context);
+    };
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&closure_context.nodes_graph),
+    ];
+    let result = call_boolean_trap(proxy, &trap, &args, closure_context)?;
+    if !result {
+        if let Some(target_property) =
+            internal_get_own_property(&proxy.target, key, closure_context)?
+        {
+            let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+            check_invariant(target_property.configurable && extensible, closure_context)?;
+        }
+    }
+    Ok(result)
+}
+
+/// The `get` trap must report the value of non-configurable, read-only data properties, and
+/// `undefined` for non-configurable accessors without a getter
+pub fn proxy_get(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    receiver: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    let Some(trap) = get_trap(proxy, "get", closure_context)? else {
+        return internal_get(&proxy.target, key, receiver, closure_context);
+    };
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&closure_context.nodes_graph),
+        receiver.clone(),
+    ];
+    let result = call_trap(proxy, &trap, &args, closure_context)?;
+    if let Some(target_property) = get_non_configurable_property(proxy, key, closure_context)? {
+        let holds = match target_property.value {
+            JsPropertyValue::Data {
+                value,
+                writable: false,
+            } => is_same_value(&value, &result),
+            JsPropertyValue::Accessor { get: None, .. } => {
+                matches!(result.get_ref(), JsValue::Undefined)
+            }
+            _ => true,
+        };
+        check_invariant(holds, closure_context)?;
+    }
+    Ok(result)
+}
+
+/// The `set` trap cannot report success in changing non-configurable, read-only data properties,
+/// or non-configurable accessors without a setter
+pub fn proxy_set(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    value: JsValueNode,
+    receiver: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "set", closure_context)? else {
+        return internal_set(&proxy.target, key, value, receiver, closure_context);
+    };
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&closure_context.nodes_graph),
+        value.clone(),
+        receiver.clone(),
+    ];
+    if !call_boolean_trap(proxy, &trap, &args, closure_context)? {
+        return Ok(false);
+    }
+    if let Some(target_property) = get_non_configurable_property(proxy, key, closure_context)? {
+        let holds = match target_property.value {
+            JsPropertyValue::Data {
+                value: target_value,
+                writable: false,
+            } => is_same_value(&value, &target_value),
+            JsPropertyValue::Accessor { set: None, .. } => false,
+            _ => true,
+        };
+        check_invariant(holds, closure_context)?;
+    }
+    Ok(true)
+}
+
+/// The `deleteProperty` trap cannot report success in deleting non-configurable properties or the
+/// properties of non-extensible targets
+pub fn proxy_delete(
+    proxy: &JsProxy,
+    key: &JsPropertyKey,
+    closure_context: &mut JsClosureContext,
+) -> Result<bool, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "deleteProperty", closure_context)? else {
+        return internal_delete(&proxy.target, key, closure_context);
+    };
+    let args = [
+        proxy.target.clone(),
+        key.to_value_rc(&closure_context.nodes_graph),
+    ];
+    if !call_boolean_trap(proxy, &trap, &args, closure_context)? {
+        return Ok(false);
+    }
+    if let Some(target_property) = internal_get_own_property(&proxy.target, key, closure_context)? {
+        let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+        check_invariant(target_property.configurable && extensible, closure_context)?;
+    }
+    Ok(true)
+}
+
+/// The `ownKeys` trap must return an array-like object of unique strings and symbols, which
+/// includes every non-configurable key of the target, and exactly the target's keys when the
+/// target is not extensible
+pub fn proxy_own_keys(
+    proxy: &JsProxy,
+    closure_context: &mut JsClosureContext,
+) -> Result<Vec<JsPropertyKey>, JsValueNode> {
+    let Some(trap) = get_trap(proxy, "ownKeys", closure_context)? else {
+        return internal_own_property_keys(&proxy.target, closure_context);
+    };
+    let result = call_trap(
+        proxy,
+        &trap,
+        slice::from_ref(&proxy.target),
+        closure_context,
+    )?;
+    let mut keys: Vec<JsPropertyKey> = vec![];
+    for element in create_list_from_array_like(&result, closure_context)? {
+        let key = match element.get_ref() {
+            JsValue::String(_) | JsValue::Symbol(_) => JsPropertyKey::from(element.get_ref()),
+            _ => return Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        };
+        check_invariant(!keys.contains(&key), closure_context)?;
+        keys.push(key);
+    }
+
+    let extensible = internal_is_extensible(&proxy.target, closure_context)?;
+    let mut configurable_keys = vec![];
+    let mut non_configurable_keys = vec![];
+    for key in internal_own_property_keys(&proxy.target, closure_context)? {
+        match get_non_configurable_property(proxy, &key, closure_context)? {
+            Some(_) => non_configurable_keys.push(key),
+            None => configurable_keys.push(key),
+        }
+    }
+    if extensible && non_configurable_keys.is_empty() {
+        return Ok(keys);
+    }
+
+    let mut unchecked_keys = keys.clone();
+    let mut check_key = |key: &JsPropertyKey| match unchecked_keys.iter().position(|k| k == key) {
+        Some(position) => {
+            unchecked_keys.remove(position);
+            true
+        }
+        None => false,
+    };
+    let reports_non_configurable = non_configurable_keys.iter().all(&mut check_key);
+    check_invariant(reports_non_configurable, closure_context)?;
+    if extensible {
+        return Ok(keys);
+    }
+    let reports_configurable = configurable_keys.iter().all(&mut check_key);
+    check_invariant(
+        reports_configurable && unchecked_keys.is_empty(),
+        closure_context,
+    )?;
+    Ok(keys)
+}
+
+/// Invokes a proxy of a function through its `apply` trap, which receives the arguments as an
+/// array
+pub fn proxy_call(
+    proxy: &JsProxy,
+    this: JsValueNode,
+    args: &[JsValueNode],
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    let Some(trap) = get_trap(proxy, "apply", closure_context)? else {
+        return invoke_function(closure_context, &proxy.target, this, args);
+    };
+    let args_array = JsValue::array_rc(&closure_context.nodes_graph, args.to_vec());
+    call_trap(
+        proxy,
+        &trap,
+        &[proxy.target.clone(), this, args_array],
+        closure_context,
+    )
+}
+
+/// Constructs a proxy of a function through its `construct` trap, which must return an object
+pub fn proxy_construct(
+    proxy: &JsProxy,
+    args: &[JsValueNode],
+    new_target: &JsValueNode,
+    closure_context: &mut JsClosureContext,
+) -> JsFunctionResult {
+    let Some(trap) = get_trap(proxy, "construct", closure_context)? else {
+        return construct_with_new_target(closure_context, &proxy.target, args, new_target);
+    };
+    let args_array = JsValue::array_rc(&closure_context.nodes_graph, args.to_vec());
+    let result = call_trap(
+        proxy,
+        &trap,
+        &[proxy.target.clone(), args_array, new_target.clone()],
+        closure_context,
+    )?;
+    check_invariant(result.get_ref().is_object(), closure_context)?;
+    Ok(result)
+}
@@ -4,6 +4,8 @@ use super::JsValueNode;
 pub struct JsReference {
     pub name: String,
     pub value: JsValueNode,
+    /// Set for `const` declarations, which cannot be reassigned
+    pub constant: bool,
 }
 
 impl JsReference {
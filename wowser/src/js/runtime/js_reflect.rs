@@ -0,0 +1,130 @@
+use super::{
+    build_function_entry, build_prototype, construct_with_new_target, create_list_from_array_like,
+    from_property_descriptor, get_argument, internal_define_own_property, internal_delete,
+    internal_get, internal_get_own_property, internal_get_prototype_of, internal_has_property,
+    internal_is_extensible, internal_own_property_keys, internal_prevent_extensions, internal_set,
+    internal_set_prototype_of, invoke_function, set_member, to_property_descriptor,
+    JsClosureContext, JsFunctionResult, JsPropertyKey, JsValue, JsValueNode,
+};
+
+/// `Reflect` exposes the internal methods of objects as functions, which mirror the traps of a
+/// proxy handler. Each throws a `TypeError` when its target is not an object.
+pub fn build_reflect_object(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "apply".into(), |ctx, _, args| {
+                let target = get_argument(ctx, args, 0);
+                if !target.get_ref().is_callable() {
+                    return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+                }
+                let arguments = create_list_from_array_like(&get_argument(ctx, args, 2), ctx)?;
+                invoke_function(ctx, &target, get_argument(ctx, args, 1), &arguments)
+            }),
+            build_function_entry(&node_graph, "construct".into(), |ctx, _, args| {
+                let target = get_argument(ctx, args, 0);
+                let new_target = args.get(2).cloned().unwrap_or_else(|| target.clone());
+                if !target.get_ref().is_callable() || !new_target.get_ref().is_callable() {
+                    return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+                }
+                let arguments = create_list_from_array_like(&get_argument(ctx, args, 1), ctx)?;
+                construct_with_new_target(ctx, &target, &arguments, &new_target)
+            }),
+            build_function_entry(&node_graph, "defineProperty".into(), |ctx, _, args| {
+                let (mut target, key) = get_target_and_key(ctx, args)?;
+                let descriptor = to_property_descriptor(&get_argument(ctx, args, 2), ctx)?;
+                let defined = internal_define_own_property(&mut target, key, descriptor, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, defined))
+            }),
+            build_function_entry(&node_graph, "deleteProperty".into(), |ctx, _, args| {
+                let (target, key) = get_target_and_key(ctx, args)?;
+                let deleted = internal_delete(&target, &key, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, deleted))
+            }),
+            build_function_entry(&node_graph, "get".into(), |ctx, _, args| {
+                let (target, key) = get_target_and_key(ctx, args)?;
+                let receiver = args.get(2).cloned().unwrap_or_else(|| target.clone());
+                internal_get(&target, &key, &receiver, ctx)
+            }),
+            build_function_entry(
+                &node_graph,
+                "getOwnPropertyDescriptor".into(),
+                |ctx, _, args| {
+                    let (target, key) = get_target_and_key(ctx, args)?;
+                    Ok(match internal_get_own_property(&target, &key, ctx)? {
+                        Some(property) => {
+                            from_property_descriptor(&property.into(), &ctx.nodes_graph)
+                        }
+                        None => JsValue::undefined_rc(&ctx.nodes_graph),
+                    })
+                },
+            ),
+            build_function_entry(&node_graph, "getPrototypeOf".into(), |ctx, _, args| {
+                let target = get_target(ctx, args)?;
+                Ok(internal_get_prototype_of(&target, ctx)?
+                    .unwrap_or_else(|| JsValue::null_rc(&ctx.nodes_graph)))
+            }),
+            build_function_entry(&node_graph, "has".into(), |ctx, _, args| {
+                let (target, key) = get_target_and_key(ctx, args)?;
+                let has = internal_has_property(&target, &key, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, has))
+            }),
+            build_function_entry(&node_graph, "isExtensible".into(), |ctx, _, args| {
+                let target = get_target(ctx, args)?;
+                let extensible = internal_is_extensible(&target, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, extensible))
+            }),
+            build_function_entry(&node_graph, "ownKeys".into(), |ctx, _, args| {
+                let target = get_target(ctx, args)?;
+                let keys = internal_own_property_keys(&target, ctx)?
+                    .iter()
+                    .map(|key| key.to_value_rc(&ctx.nodes_graph))
+                    .collect();
+                Ok(JsValue::array_rc(&ctx.nodes_graph, keys))
+            }),
+            build_function_entry(&node_graph, "preventExtensions".into(), |ctx, _, args| {
+                let target = get_target(ctx, args)?;
+                let prevented = internal_prevent_extensions(&target, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, prevented))
+            }),
+            build_function_entry(&node_graph, "set".into(), |ctx, _, args| {
+                let (mut target, key) = get_target_and_key(ctx, args)?;
+                let value = get_argument(ctx, args, 2);
+                let assigned = match args.get(3) {
+                    Some(receiver) => internal_set(&target, &key, value, receiver, ctx)?,
+                    None => set_member(&mut target, &key, value, ctx)?,
+                };
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, assigned))
+            }),
+            build_function_entry(&node_graph, "setPrototypeOf".into(), |ctx, _, args| {
+                let target = get_target(ctx, args)?;
+                let prototype = get_argument(ctx, args, 1);
+                let prototype = match prototype.get_ref() {
+                    JsValue::Null => None,
+                    value if value.is_object() => Some(prototype.clone()),
+                    _ => return Err(JsValue::type_error_rc(&ctx.nodes_graph)),
+                };
+                let set = internal_set_prototype_of(&target, prototype, ctx)?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, set))
+            }),
+        ],
+    )
+}
+
+fn get_target(closure_context: &JsClosureContext, args: &[JsValueNode]) -> JsFunctionResult {
+    let target = get_argument(closure_context, args, 0);
+    if !target.get_ref().is_object() {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(target)
+}
+
+fn get_target_and_key(
+    closure_context: &JsClosureContext,
+    args: &[JsValueNode],
+) -> Result<(JsValueNode, JsPropertyKey), JsValueNode> {
+    let target = get_target(closure_context, args)?;
+    let key = JsPropertyKey::from(get_argument(closure_context, args, 1).get_ref());
+    Ok((target, key))
+}
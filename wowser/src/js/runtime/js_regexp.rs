@@ -0,0 +1,683 @@
+use std::fmt;
+
+use fancy_regex::Regex;
+
+use super::{
+    build_function_entry, build_native_function, build_prototype, get_argument, invoke_function,
+    JsClosureContext, JsFunctionResult, JsIterationKind, JsIterator, JsProperties, JsProperty,
+    JsPropertyValue, JsValue, JsValueGraph, JsValueNode, CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+const VALID_FLAGS: &str = "gimsuy";
+
+/// A compiled regular expression. Indices into the searched strings count characters, as the
+/// lengths of strings do.
+pub struct JsRegExp {
+    pub source: String,
+    pub flags: String,
+    regex: Regex,
+    /// Where the next search of a global or sticky regular expression starts
+    pub last_index: usize,
+}
+
+/// A successful match of a regular expression. Indices count characters.
+#[derive(Debug, PartialEq)]
+pub struct JsRegExpMatch {
+    pub index: usize,
+    pub end: usize,
+    /// The whole match followed by each capture group, where `None` did not participate
+    pub captures: Vec<Option<String>>,
+    /// (Name, Capture) for each named capture group in the order they appear
+    pub named_groups: Vec<(String, Option<String>)>,
+}
+
+impl JsRegExp {
+    /// Compiles a pattern with the given flags. Returns `None` for invalid patterns, unknown
+    /// flags, or repeated flags.
+    pub fn new(source: &str, flags: &str) -> Option<Self> {
+        let mut inline_flags = String::new();
+        for (i, flag) in flags.char_indices() {
+            if !VALID_FLAGS.contains(flag) || flags[..i].contains(flag) {
+                return None;
+            }
+            match flag {
+                'i' | 'm' | 's' => inline_flags.push(flag),
+                _ => {}
+            }
+        }
+
+        let source = if source.is_empty() { "(?:)" } else { source };
+        let pattern = source.replace(r"\/", "/");
+        let pattern = if inline_flags.is_empty() {
+            pattern
+        } else {
+            format!("(?{inline_flags}){pattern}")
+        };
+        let regex = Regex::new(&pattern).ok()?;
+
+        let mut sorted_flags: Vec<char> = flags.chars().collect();
+        sorted_flags.sort_unstable();
+        Some(Self {
+            source: source.to_string(),
+            flags: sorted_flags.into_iter().collect(),
+            regex,
+            last_index: 0,
+        })
+    }
+
+    pub fn has_flag(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// Global and sticky regular expressions resume searching from `last_index`
+    fn uses_last_index(&self) -> bool {
+        self.has_flag('g') || self.has_flag('y')
+    }
+
+    /// Searches the input from `last_index` when global or sticky, or from the start otherwise.
+    /// `last_index` moves past the match, or back to the start when nothing matched.
+    pub fn exec(&mut self, input: &str) -> Option<JsRegExpMatch> {
+        if !self.uses_last_index() {
+            return self.find_at(input, 0);
+        }
+        let found = self.find_at(input, self.last_index);
+        self.last_index = found.as_ref().map(|found| found.end).unwrap_or(0);
+        found
+    }
+
+    /// Every match in the input, as found by a global search starting at the beginning. Empty
+    /// matches advance the search by one character so it always ends.
+    pub fn find_all(&self, input: &str) -> Vec<JsRegExpMatch> {
+        let mut matches = vec![];
+        let mut position = 0;
+        while let Some(found) = self.find_at(input, position) {
+            position = if found.end == found.index {
+                found.end + 1
+            } else {
+                found.end
+            };
+            matches.push(found);
+        }
+        matches
+    }
+
+    /// Finds the first match starting at or after a character position. Sticky regular
+    /// expressions only match at the position itself.
+    pub fn find_at(&self, input: &str, position: usize) -> Option<JsRegExpMatch> {
+        let start = char_to_byte_index(input, position)?;
+        let captures = self.regex.captures_from_pos(input, start).ok()??;
+        let whole = captures.get(0)?;
+        if self.has_flag('y') && whole.start() != start {
+            return None;
+        }
+
+        let capture = |index: usize| captures.get(index).map(|found| found.as_str().to_string());
+        let named_groups = self
+            .regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| Some((name?.to_string(), capture(index))))
+            .collect();
+        Some(JsRegExpMatch {
+            index: input[..whole.start()].chars().count(),
+            end: input[..whole.end()].chars().count(),
+            captures: (0..captures.len()).map(capture).collect(),
+            named_groups,
+        })
+    }
+}
+
+impl fmt::Debug for JsRegExp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsRegExp")
+            .field("source", &self.source)
+            .field("flags", &self.flags)
+            .field("last_index", &self.last_index)
+            .finish()
+    }
+}
+
+impl PartialEq for JsRegExp {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+            && self.flags == other.flags
+            && self.last_index == other.last_index
+    }
+}
+
+impl fmt::Display for JsRegExp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}/{}", self.source, self.flags)
+    }
+}
+
+impl JsRegExpMatch {
+    /// Builds a match for a plain string found at a character index, as used when a string rather
+    /// than a regular expression is passed as a pattern
+    pub fn from_str(matched: &str, index: usize) -> Self {
+        Self {
+            index,
+            end: index + matched.chars().count(),
+            captures: vec![Some(matched.to_string())],
+            named_groups: vec![],
+        }
+    }
+
+    pub fn matched(&self) -> &str {
+        self.captures[0].as_deref().unwrap_or_default()
+    }
+
+    /// The array returned by `exec` and `match`, with the `index`, `input`, and `groups` of the
+    /// match as properties
+    pub fn to_array(&self, input: &str, node_graph: &JsValueGraph) -> JsValueNode {
+        let elements = self
+            .captures
+            .iter()
+            .map(|capture| optional_string_rc(node_graph, capture.as_deref()))
+            .collect();
+        let mut array = JsValue::array_rc(node_graph, elements);
+        if let JsValue::Array(_, properties) = array.get_mut() {
+            properties.insert(
+                "index".into(),
+                JsValue::number_rc(node_graph, self.index as f64),
+            );
+            properties.insert("input".into(), JsValue::str_rc(node_graph, input));
+            properties.insert("groups".into(), self.groups_rc(node_graph));
+        }
+        array
+    }
+
+    fn groups_rc(&self, node_graph: &JsValueGraph) -> JsValueNode {
+        if self.named_groups.is_empty() {
+            return JsValue::undefined_rc(node_graph);
+        }
+        let mut groups = JsProperties::new();
+        for (name, capture) in &self.named_groups {
+            groups.insert(
+                name.as_str().into(),
+                optional_string_rc(node_graph, capture.as_deref()),
+            );
+        }
+        JsValue::object_rc(node_graph, groups)
+    }
+
+    /// Expands the `$` patterns of a replacement string: `$$`, `$&`, `` $` ``, `$'`, `$n`, `$nn`,
+    /// and `$<name>`. Patterns referring to missing groups are left as is.
+    pub fn expand_replacement(&self, replacement: &str, input: &str) -> String {
+        let mut expanded = String::new();
+        let mut rest = replacement;
+        while let Some(dollar) = rest.find('$') {
+            expanded.push_str(&rest[..dollar]);
+            rest = &rest[dollar..];
+            let next = rest[1..].chars().next();
+            let consumed = match next {
+                Some('$') => {
+                    expanded.push('$');
+                    2
+                }
+                Some('&') => {
+                    expanded.push_str(self.matched());
+                    2
+                }
+                Some('`') => {
+                    expanded.extend(input.chars().take(self.index));
+                    2
+                }
+                Some('\'') => {
+                    expanded.extend(input.chars().skip(self.end));
+                    2
+                }
+                Some('<') if !self.named_groups.is_empty() => match rest.find('>') {
+                    Some(close) => {
+                        let name = &rest[2..close];
+                        let capture = self
+                            .named_groups
+                            .iter()
+                            .find(|(group, _capture)| group == name)
+                            .and_then(|(_group, capture)| capture.as_deref());
+                        expanded.push_str(capture.unwrap_or_default());
+                        close + 1
+                    }
+                    None => {
+                        expanded.push('$');
+                        1
+                    }
+                },
+                Some(c) if c.is_ascii_digit() => {
+                    let digits: String = rest[1..]
+                        .chars()
+                        .take(2)
+                        .take_while(char::is_ascii_digit)
+                        .collect();
+                    // Prefer two digit group numbers when that group exists
+                    let group = [digits.as_str(), &digits[..1]]
+                        .into_iter()
+                        .filter_map(|digits| Some((digits.len(), digits.parse::<usize>().ok()?)))
+                        .find(|(_len, group)| *group > 0 && *group < self.captures.len());
+                    match group {
+                        Some((len, group)) => {
+                            expanded.push_str(self.captures[group].as_deref().unwrap_or_default());
+                            len + 1
+                        }
+                        None => {
+                            expanded.push('$');
+                            1
+                        }
+                    }
+                }
+                _ => {
+                    expanded.push('$');
+                    1
+                }
+            };
+            rest = &rest[consumed..];
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Invokes a replacement function with the match, its captures, its index, the input, and
+    /// its named groups when there are any
+    pub fn call_replacer(
+        &self,
+        replacer: &JsValueNode,
+        input: &str,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<String, JsValueNode> {
+        let nodes_graph = closure_context.nodes_graph.clone();
+        let mut args: Vec<JsValueNode> = self
+            .captures
+            .iter()
+            .map(|capture| optional_string_rc(&nodes_graph, capture.as_deref()))
+            .collect();
+        args.push(JsValue::number_rc(&nodes_graph, self.index as f64));
+        args.push(JsValue::str_rc(&nodes_graph, input));
+        if !self.named_groups.is_empty() {
+            args.push(self.groups_rc(&nodes_graph));
+        }
+        let undefined = JsValue::undefined_rc(&nodes_graph);
+        let result = invoke_function(closure_context, replacer, undefined, &args)?;
+        let replacement = result.get_ref().to_string();
+        Ok(replacement)
+    }
+}
+
+fn optional_string_rc(node_graph: &JsValueGraph, s: Option<&str>) -> JsValueNode {
+    match s {
+        Some(s) => JsValue::str_rc(node_graph, s),
+        None => JsValue::undefined_rc(node_graph),
+    }
+}
+
+/// The byte index of a character index, or `None` past the end of the string
+fn char_to_byte_index(s: &str, index: usize) -> Option<usize> {
+    s.char_indices()
+        .map(|(byte_index, _c)| byte_index)
+        .chain([s.len()])
+        .nth(index)
+}
+
+/// `RegExp` compiles a pattern, which may be another regular expression, with or without `new`.
+/// Invalid patterns throw a `SyntaxError`.
+pub fn build_regexp_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor = build_native_function(&node_graph, &"RegExp".into(), |ctx, _, args| {
+        let pattern = get_argument(ctx, args, 0);
+        let flags = get_argument(ctx, args, 1);
+        let (source, default_flags) = match pattern.get_ref() {
+            JsValue::RegExp(regexp) => (regexp.source.clone(), regexp.flags.clone()),
+            JsValue::Undefined => (String::new(), String::new()),
+            pattern => (pattern.to_string(), String::new()),
+        };
+        let flags = match flags.get_ref() {
+            JsValue::Undefined => default_flags,
+            flags => flags.to_string(),
+        };
+        regexp_rc(&ctx.nodes_graph, &source, &flags)
+    });
+
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+    constructor
+}
+
+/// Creates a regular expression, throwing a `SyntaxError` if it does not compile
+pub fn regexp_rc(node_graph: &JsValueGraph, source: &str, flags: &str) -> JsFunctionResult {
+    match JsRegExp::new(source, flags) {
+        Some(regexp) => Ok(JsValue::regexp_rc(node_graph, regexp)),
+        None => Err(JsValue::syntax_error_rc(node_graph)),
+    }
+}
+
+pub fn build_regexp_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let mut prototype = build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "exec".into(), |ctx, this, args| {
+                let input = get_argument(ctx, args, 0).get_ref().to_string();
+                let found = with_regexp(ctx, this, |regexp| regexp.exec(&input))?;
+                Ok(match found {
+                    Some(found) => found.to_array(&input, &ctx.nodes_graph),
+                    None => JsValue::null_rc(&ctx.nodes_graph),
+                })
+            }),
+            build_function_entry(&node_graph, "test".into(), |ctx, this, args| {
+                let input = get_argument(ctx, args, 0).get_ref().to_string();
+                let found = with_regexp(ctx, this, |regexp| regexp.exec(&input))?;
+                Ok(JsValue::bool_rc(&ctx.nodes_graph, found.is_some()))
+            }),
+            build_function_entry(&node_graph, "toString".into(), |ctx, this, _| {
+                let string = with_regexp(ctx, this, |regexp| regexp.to_string())?;
+                Ok(JsValue::string_rc(&ctx.nodes_graph, string))
+            }),
+        ],
+    );
+
+    let flag_getters = [
+        ("global", 'g'),
+        ("ignoreCase", 'i'),
+        ("multiline", 'm'),
+        ("dotAll", 's'),
+        ("unicode", 'u'),
+        ("sticky", 'y'),
+    ]
+    .map(|(name, flag)| {
+        build_function_entry(&node_graph, name.into(), move |ctx, this, _| {
+            let has_flag = with_regexp(ctx, this, |regexp| regexp.has_flag(flag))?;
+            Ok(JsValue::bool_rc(&ctx.nodes_graph, has_flag))
+        })
+    });
+    let string_getters = [
+        build_function_entry(&node_graph, "source".into(), |ctx, this, _| {
+            let source = with_regexp(ctx, this, |regexp| regexp.source.clone())?;
+            Ok(JsValue::string_rc(&ctx.nodes_graph, source))
+        }),
+        build_function_entry(&node_graph, "flags".into(), |ctx, this, _| {
+            let flags = with_regexp(ctx, this, |regexp| regexp.flags.clone())?;
+            Ok(JsValue::string_rc(&ctx.nodes_graph, flags))
+        }),
+    ];
+
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, getter) in flag_getters.into_iter().chain(string_getters) {
+            let getter = JsProperty {
+                value: JsPropertyValue::Accessor {
+                    get: Some(getter),
+                    set: None,
+                },
+                enumerable: false,
+                configurable: true,
+            };
+            properties.insert_property(key, getter);
+        }
+    }
+    prototype
+}
+
+/// Runs `func` on the regular expression `this`, throwing a `TypeError` if `this` is not one
+pub fn with_regexp<T>(
+    closure_context: &JsClosureContext,
+    mut this: JsValueNode,
+    func: impl FnOnce(&mut JsRegExp) -> T,
+) -> Result<T, JsValueNode> {
+    match this.get_mut() {
+        JsValue::RegExp(regexp) => Ok(func(regexp)),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// Converts the pattern argument of a string method to a regular expression. Other values are
+/// compiled as a pattern, so `"a.c"` matches any character between `a` and `c`.
+pub fn to_regexp(
+    closure_context: &JsClosureContext,
+    pattern: JsValueNode,
+    flags: &str,
+) -> JsFunctionResult {
+    match pattern.get_ref() {
+        JsValue::RegExp(_) => Ok(pattern.clone()),
+        JsValue::Undefined => regexp_rc(&closure_context.nodes_graph, "", flags),
+        value => regexp_rc(&closure_context.nodes_graph, &value.to_string(), flags),
+    }
+}
+
+/// `String.prototype.match` returns the first match like `exec`, or all matched strings for
+/// global patterns
+pub fn string_match(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let input = this.get_ref().to_string();
+    let regexp = to_regexp(closure_context, get_argument(closure_context, args, 0), "")?;
+    let found = with_regexp(closure_context, regexp, |regexp| {
+        if regexp.has_flag('g') {
+            regexp.last_index = 0;
+            Err(regexp.find_all(&input))
+        } else {
+            Ok(regexp.exec(&input))
+        }
+    })?;
+    Ok(match found {
+        Ok(Some(found)) => found.to_array(&input, &nodes_graph),
+        Err(matches) if !matches.is_empty() => {
+            let matched = matches
+                .iter()
+                .map(|found| JsValue::str_rc(&nodes_graph, found.matched()))
+                .collect();
+            JsValue::array_rc(&nodes_graph, matched)
+        }
+        _ => JsValue::null_rc(&nodes_graph),
+    })
+}
+
+/// `String.prototype.matchAll` iterates over the `exec` results of a global search. Regular
+/// expressions without the `g` flag throw a `TypeError`.
+pub fn string_match_all(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let input = this.get_ref().to_string();
+    let regexp = to_regexp(closure_context, get_argument(closure_context, args, 0), "g")?;
+    let matches = with_regexp(closure_context, regexp, |regexp| {
+        regexp.has_flag('g').then(|| regexp.find_all(&input))
+    })?
+    .ok_or_else(|| JsValue::type_error_rc(&nodes_graph))?;
+    let results = matches
+        .iter()
+        .map(|found| found.to_array(&input, &nodes_graph))
+        .collect();
+    let results = JsValue::array_rc(&nodes_graph, results);
+    Ok(JsValue::iterator_rc(
+        &nodes_graph,
+        JsIterator::new(results, JsIterationKind::Values),
+    ))
+}
+
+/// `String.prototype.replace` replaces the first occurrence of a string, or the matches of a
+/// regular expression, with a replacement string or the result of a replacement function
+pub fn string_replace(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let input = this.get_ref().to_string();
+    let pattern = get_argument(closure_context, args, 0);
+    let replacement = get_argument(closure_context, args, 1);
+    let matches = if let JsValue::RegExp(_) = pattern.get_ref() {
+        with_regexp(closure_context, pattern, |regexp| {
+            if regexp.has_flag('g') {
+                regexp.last_index = 0;
+                regexp.find_all(&input)
+            } else {
+                regexp.exec(&input).into_iter().collect()
+            }
+        })?
+    } else {
+        let pattern = pattern.get_ref().to_string();
+        input
+            .find(&pattern)
+            .map(|index| JsRegExpMatch::from_str(&pattern, input[..index].chars().count()))
+            .into_iter()
+            .collect()
+    };
+
+    let mut replaced = String::new();
+    let mut position = 0;
+    for found in matches {
+        replaced.extend(input.chars().skip(position).take(found.index - position));
+        let expanded = match replacement.get_ref() {
+            JsValue::Function(_, _) => {
+                found.call_replacer(&replacement, &input, closure_context)?
+            }
+            replacement => found.expand_replacement(&replacement.to_string(), &input),
+        };
+        replaced.push_str(&expanded);
+        position = found.end;
+    }
+    replaced.extend(input.chars().skip(position));
+    Ok(JsValue::string_rc(&closure_context.nodes_graph, replaced))
+}
+
+/// `String.prototype.search` returns the index of the first match, or -1. `lastIndex` is ignored.
+pub fn string_search(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let input = this.get_ref().to_string();
+    let regexp = to_regexp(closure_context, get_argument(closure_context, args, 0), "")?;
+    let index = with_regexp(closure_context, regexp, |regexp| {
+        regexp.find_at(&input, 0).map(|found| found.index)
+    })?;
+    let index = index.map(|index| index as f64).unwrap_or(-1.0);
+    Ok(JsValue::number_rc(&closure_context.nodes_graph, index))
+}
+
+/// `String.prototype.split` divides a string around a separator string or the matches of a
+/// regular expression, whose captures are included in the result. Empty separators split the
+/// string into characters.
+pub fn string_split(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let nodes_graph = closure_context.nodes_graph.clone();
+    let input = this.get_ref().to_string();
+    let separator = get_argument(closure_context, args, 0);
+    let limit = match get_argument(closure_context, args, 1).get_ref() {
+        JsValue::Undefined => usize::MAX,
+        limit => f64::from(limit) as u32 as usize,
+    };
+    let parts: Vec<Option<String>> = match separator.get_ref() {
+        JsValue::Undefined => vec![Some(input)],
+        JsValue::RegExp(regexp) => split_by_regexp(regexp, &input),
+        separator => {
+            let separator = separator.to_string();
+            if separator.is_empty() {
+                input.chars().map(|c| Some(c.to_string())).collect()
+            } else {
+                input
+                    .split(&separator)
+                    .map(|part| Some(part.to_string()))
+                    .collect()
+            }
+        }
+    };
+    let parts = parts
+        .into_iter()
+        .take(limit)
+        .map(|part| optional_string_rc(&nodes_graph, part.as_deref()))
+        .collect();
+    Ok(JsValue::array_rc(&nodes_graph, parts))
+}
+
+/// Splits around matches, skipping empty matches at the start of each remaining part and at the
+/// end of the string
+fn split_by_regexp(regexp: &JsRegExp, input: &str) -> Vec<Option<String>> {
+    let size = input.chars().count();
+    if size == 0 {
+        return match regexp.find_at(input, 0) {
+            Some(_) => vec![],
+            None => vec![Some(String::new())],
+        };
+    }
+
+    let slice = |start: usize, end: usize| input.chars().skip(start).take(end - start).collect();
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut position = 0;
+    while position < size {
+        let Some(found) = regexp.find_at(input, position) else {
+            break;
+        };
+        if found.index >= size {
+            break;
+        }
+        if found.end == start {
+            position = found.index + 1;
+            continue;
+        }
+        parts.push(Some(slice(start, found.index)));
+        parts.extend(found.captures.into_iter().skip(1));
+        start = found.end;
+        position = start;
+    }
+    parts.push(Some(slice(start, size)));
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!("gi", JsRegExp::new("a", "ig").unwrap().flags);
+        assert!(JsRegExp::new("a", "gg").is_none());
+        assert!(JsRegExp::new("a", "x").is_none());
+        assert!(JsRegExp::new("(", "").is_none());
+        assert!(JsRegExp::new("A", "i").unwrap().find_at("a", 0).is_some());
+    }
+
+    #[test]
+    fn test_find_at_counts_characters() {
+        let regexp = JsRegExp::new("b+", "").unwrap();
+        let found = regexp.find_at("ébbc", 0).unwrap();
+        assert_eq!((1, 3), (found.index, found.end));
+        assert_eq!("bb", found.matched());
+        assert!(regexp.find_at("ébbc", 5).is_none());
+    }
+
+    #[test]
+    fn test_sticky() {
+        let regexp = JsRegExp::new("b", "y").unwrap();
+        assert!(regexp.find_at("ab", 0).is_none());
+        assert!(regexp.find_at("ab", 1).is_some());
+    }
+
+    #[test]
+    fn test_find_all_empty_matches() {
+        let regexp = JsRegExp::new("x*", "g").unwrap();
+        let matches = regexp.find_all("axb");
+        let indices: Vec<_> = matches.iter().map(|found| found.index).collect();
+        assert_eq!(vec![0, 1, 2, 3], indices);
+    }
+
+    #[test]
+    fn test_expand_replacement() {
+        let regexp = JsRegExp::new("(?<first>a)(b)", "").unwrap();
+        let found = regexp.find_at("xaby", 0).unwrap();
+        assert_eq!(
+            "[ab|a|b|b|x|y|$|$3|a]",
+            found.expand_replacement("[$&|$1|$2|$02|$`|$'|$$|$3|$<first>]", "xaby")
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use super::{JsClosureContext, JsExpression, JsReference, JsStatementResult, JsValueNode};
+use super::{
+    is_strictly_equal, JsClass, JsClosureContext, JsDeclarationKind, JsExport, JsExpression,
+    JsImport, JsPattern, JsReference, JsStatementResult, JsValue, JsValueNode,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum JsStatement {
@@ -6,15 +9,45 @@ pub enum JsStatement {
     Expression(JsExpression),
     VarDeclaration(JsReference),
     VariableAssignment(JsReference, JsExpression),
+    /// (Kind, Binding, Initializer)
+    Declaration(JsDeclarationKind, JsPattern, Option<JsExpression>),
     FunctionDeclaration(JsReference),
+    ClassDeclaration(JsClass),
     Return(JsExpression),
     Throw(JsExpression),
     /// (Condition, True Statement, False Statements)
     If(JsExpression, Vec<JsStatement>, Vec<JsStatement>),
+    /// (Discriminant, Cases) where each case is (Test, Statements) and the default case has no
+    /// test
+    Switch(JsExpression, Vec<(Option<JsExpression>, Vec<JsStatement>)>),
+    Block(Vec<JsStatement>),
+    /// (Label, Statement)
+    Labeled(String, Box<JsStatement>),
+    /// (Label)
+    Break(Option<String>),
+    /// Only run at the top level of a module. See [super::JsModuleMap].
+    Import(JsImport),
+    /// Only run at the top level of a module. See [super::JsModuleMap].
+    Export(JsExport),
 }
 
 impl JsStatement {
+    /// The names a declaration binds in the scope it is run in
+    pub fn get_declared_names(&self) -> Vec<String> {
+        match self {
+            Self::VarDeclaration(reference)
+            | Self::VariableAssignment(reference, _)
+            | Self::FunctionDeclaration(reference) => vec![reference.name.clone()],
+            Self::Declaration(_kind, pattern, _expression) => pattern.get_bound_names(),
+            Self::ClassDeclaration(class) => vec![class.name.clone()],
+            _ => vec![],
+        }
+    }
+
     pub fn run(&self, closure_context: &mut JsClosureContext) -> JsStatementResult {
+        if let Err(value) = closure_context.step() {
+            return JsStatementResult::ThrowValue(value);
+        }
         match self {
             Self::Empty => JsStatementResult::Void,
             Self::Expression(expression) => match expression.run(closure_context) {
@@ -36,12 +69,29 @@ impl JsStatement {
                     Err(value) => JsStatementResult::ThrowValue(value),
                 }
             }
+            Self::Declaration(kind, pattern, expression) => {
+                let value = match expression {
+                    Some(expression) => match expression.run(closure_context) {
+                        Ok(value) => value,
+                        Err(value) => return JsStatementResult::ThrowValue(value),
+                    },
+                    None => JsValue::undefined_rc(&closure_context.nodes_graph),
+                };
+                match pattern.bind(closure_context, value.clone(), Some(*kind)) {
+                    Ok(()) => JsStatementResult::Value(value),
+                    Err(value) => JsStatementResult::ThrowValue(value),
+                }
+            }
             Self::FunctionDeclaration(reference) => {
                 let closure_reference =
                     closure_context.get_or_declare_reference_mut(&reference.name);
                 closure_reference.value = reference.value.clone();
                 JsStatementResult::Value(reference.value.clone())
             }
+            Self::ClassDeclaration(class) => match class.run(closure_context) {
+                Ok(value) => JsStatementResult::Value(value),
+                Err(value) => JsStatementResult::ThrowValue(value),
+            },
             Self::Return(expression) => match expression.run(closure_context) {
                 Ok(value) => JsStatementResult::ReturnValue(value),
                 Err(value) => JsStatementResult::ThrowValue(value),
@@ -63,18 +113,21 @@ impl JsStatement {
                     } else {
                         false_execution_statements
                     };
-
-                    for statement in statements {
-                        let result = statement.run(closure_context);
-                        match result {
-                            JsStatementResult::ReturnValue(_) => return result,
-                            JsStatementResult::ThrowValue(_) => return result,
-                            _ => closure_context.record_new_result(result),
-                        };
-                    }
+                    run_statements(statements, closure_context)
+                }
+            },
+            Self::Switch(discriminant, cases) => run_switch(discriminant, cases, closure_context),
+            Self::Block(statements) => run_statements(statements, closure_context),
+            Self::Labeled(label, statement) => match statement.run(closure_context) {
+                JsStatementResult::Break(Some(target)) if target == *label => {
                     JsStatementResult::Void
                 }
+                result => result,
             },
+            Self::Break(label) => JsStatementResult::Break(label.clone()),
+            Self::Import(_) | Self::Export(_) => JsStatementResult::ThrowValue(
+                JsValue::syntax_error_rc(&closure_context.nodes_graph),
+            ),
         }
     }
 
@@ -88,7 +141,16 @@ impl JsStatement {
                 expression.get_referenced_nodes(),
             ]
             .concat(),
+            Self::Declaration(_kind, pattern, expression) => [
+                pattern.get_referenced_nodes(),
+                expression
+                    .iter()
+                    .flat_map(|expression| expression.get_referenced_nodes())
+                    .collect(),
+            ]
+            .concat(),
             Self::FunctionDeclaration(reference) => reference.get_referenced_nodes(),
+            Self::ClassDeclaration(class) => class.get_referenced_nodes(),
             Self::Return(expression) => expression.get_referenced_nodes(),
             Self::Throw(expression) => expression.get_referenced_nodes(),
             Self::If(expression, true_statements, false_statements) => [
@@ -100,6 +162,89 @@ impl JsStatement {
                     .collect(),
             ]
             .concat(),
+            Self::Switch(discriminant, cases) => [
+                discriminant.get_referenced_nodes(),
+                cases
+                    .iter()
+                    .flat_map(|(test, statements)| {
+                        test.iter()
+                            .flat_map(|test| test.get_referenced_nodes())
+                            .chain(
+                                statements
+                                    .iter()
+                                    .flat_map(|statement| statement.get_referenced_nodes()),
+                            )
+                    })
+                    .collect(),
+            ]
+            .concat(),
+            Self::Block(statements) => statements
+                .iter()
+                .flat_map(|statement| statement.get_referenced_nodes())
+                .collect(),
+            Self::Labeled(_label, statement) => statement.get_referenced_nodes(),
+            Self::Break(_label) => vec![],
+            Self::Import(_import) => vec![],
+            Self::Export(export) => export.get_referenced_nodes(),
+        }
+    }
+}
+
+/// Runs statements in order until one returns, throws, or breaks
+fn run_statements(
+    statements: &[JsStatement],
+    closure_context: &mut JsClosureContext,
+) -> JsStatementResult {
+    for statement in statements {
+        let result = statement.run(closure_context);
+        match result {
+            JsStatementResult::ReturnValue(_)
+            | JsStatementResult::ThrowValue(_)
+            | JsStatementResult::Break(_) => return result,
+            _ => closure_context.record_new_result(result),
+        };
+    }
+    JsStatementResult::Void
+}
+
+/// Runs the statements from the first case whose test is strictly equal to the discriminant, or
+/// from the default case, falling through the later cases until a `break`. Tests are evaluated in
+/// order until one matches.
+fn run_switch(
+    discriminant: &JsExpression,
+    cases: &[(Option<JsExpression>, Vec<JsStatement>)],
+    closure_context: &mut JsClosureContext,
+) -> JsStatementResult {
+    let value = match discriminant.run(closure_context) {
+        Ok(value) => value,
+        Err(value) => return JsStatementResult::ThrowValue(value),
+    };
+
+    let mut start = None;
+    for (index, (test, _statements)) in cases.iter().enumerate() {
+        let Some(test) = test else {
+            continue;
+        };
+        match test.run(closure_context) {
+            Ok(test_value) if is_strictly_equal(&value, &test_value) => {
+                start = Some(index);
+                break;
+            }
+            Ok(_) => {}
+            Err(value) => return JsStatementResult::ThrowValue(value),
+        }
+    }
+    let start = start.or_else(|| cases.iter().position(|(test, _statements)| test.is_none()));
+    let Some(start) = start else {
+        return JsStatementResult::Void;
+    };
+
+    for (_test, statements) in &cases[start..] {
+        match run_statements(statements, closure_context) {
+            JsStatementResult::Break(None) => return JsStatementResult::Void,
+            JsStatementResult::Void => {}
+            result => return result,
         }
     }
+    JsStatementResult::Void
 }
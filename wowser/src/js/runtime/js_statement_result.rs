@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use super::{JsValue, JsValueGraph, JsValueNode};
+use super::{JsProperties, JsValue, JsValueGraph, JsValueNode};
 
 /// Represents the resulting value of evaluating a statement
 #[derive(Debug, PartialEq)]
@@ -8,6 +6,8 @@ pub enum JsStatementResult {
     Value(JsValueNode),
     ReturnValue(JsValueNode),
     ThrowValue(JsValueNode),
+    /// Exits the enclosing `switch`, or the enclosing statement with the label
+    Break(Option<String>),
     Void,
 }
 
@@ -43,13 +43,17 @@ impl JsStatementResult {
     }
 
     pub fn object(node_graph: &JsValueGraph, object: Vec<(&str, JsValueNode)>) -> Self {
-        let mut map = HashMap::with_capacity(object.len());
+        let mut map = JsProperties::new();
         for (key, value) in object {
-            map.insert(key.to_string(), value);
+            map.insert(key.into(), value);
         }
         Self::Value(JsValue::object_rc(node_graph, map))
     }
 
+    pub fn array(node_graph: &JsValueGraph, elements: Vec<JsValueNode>) -> Self {
+        Self::Value(JsValue::array_rc(node_graph, elements))
+    }
+
     pub fn is_nan(&self) -> bool {
         match self {
             JsStatementResult::Value(value) => value.map_value(|value| match value {
@@ -59,6 +63,15 @@ impl JsStatementResult {
             _ => false,
         }
     }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        match self {
+            Self::Value(value) | Self::ReturnValue(value) | Self::ThrowValue(value) => {
+                vec![value.clone()]
+            }
+            Self::Break(_) | Self::Void => vec![],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,205 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use wowser_macros::js_class;
+
+use crate::js::api::storage::{InMemoryStorage, StorageArea, StorageChange, StorageListener};
+
+use super::{JsClosureContext, JsNamedProperties, JsValue, JsValueNode};
+
+/// The storage areas of the document, which scripts use through `localStorage` and
+/// `sessionStorage`
+#[derive(Default)]
+pub struct JsStorageAreas {
+    /// Only documents with an origin have local storage
+    pub local: Option<Rc<RefCell<StorageArea>>>,
+    /// Created when first used when the document is not given the session storage of its tab
+    pub session: Option<Rc<RefCell<StorageArea>>>,
+    /// The document, which is told about changes other documents make to its local storage
+    pub listener: StorageListener,
+    /// The `Storage` objects, created when first read so scripts always get the same ones
+    local_object: Option<JsValueNode>,
+    session_object: Option<JsValueNode>,
+}
+
+impl JsStorageAreas {
+    /// The storage of a document at the URL, which has local storage when it has an origin
+    pub fn new(
+        url: &str,
+        local: Option<Rc<RefCell<StorageArea>>>,
+        session: Rc<RefCell<StorageArea>>,
+    ) -> Self {
+        Self {
+            local,
+            session: Some(session),
+            listener: StorageListener::new(url),
+            local_object: None,
+            session_object: None,
+        }
+    }
+
+    pub fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.local_object
+            .iter()
+            .chain(self.session_object.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Debug for JsStorageAreas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsStorageAreas")
+            .field("has_local", &self.local.is_some())
+            .field("has_session", &self.session.is_some())
+            .field("listener", &self.listener)
+            .finish()
+    }
+}
+
+/// Declares `sessionStorage` as a global, along with `localStorage` for documents with an origin
+pub fn add_storage_globals(closure_context: &mut JsClosureContext) {
+    let local_storage = get_local_storage_object(closure_context).ok();
+    let session_storage = get_session_storage_object(closure_context);
+    let global_closure = closure_context.get_lastest_closure();
+    if let Some(local_storage) = local_storage {
+        global_closure
+            .get_or_declare_reference_mut("localStorage")
+            .value = local_storage;
+    }
+    global_closure
+        .get_or_declare_reference_mut("sessionStorage")
+        .value = session_storage;
+}
+
+/// `localStorage`, which throws a `SecurityError` for documents without an origin
+pub fn get_local_storage_object(
+    closure_context: &mut JsClosureContext,
+) -> Result<JsValueNode, JsValueNode> {
+    if let Some(object) = &closure_context.storage.local_object {
+        return Ok(object.clone());
+    }
+    let Some(area) = closure_context.storage.local.clone() else {
+        return Err(JsValue::security_error_rc(&closure_context.nodes_graph));
+    };
+    let listener = closure_context.storage.listener.clone();
+    let object =
+        closure_context.create_host_object_with_named_properties(JsStorage::new(area, listener));
+    closure_context.storage.local_object = Some(object.clone());
+    Ok(object)
+}
+
+/// `sessionStorage`
+pub fn get_session_storage_object(closure_context: &mut JsClosureContext) -> JsValueNode {
+    if let Some(object) = &closure_context.storage.session_object {
+        return object.clone();
+    }
+    let area = closure_context
+        .storage
+        .session
+        .get_or_insert_with(|| {
+            let storage = Box::<InMemoryStorage>::default();
+            Rc::new(RefCell::new(StorageArea::new(storage)))
+        })
+        .clone();
+    let listener = closure_context.storage.listener.clone();
+    let object =
+        closure_context.create_host_object_with_named_properties(JsStorage::new(area, listener));
+    closure_context.storage.session_object = Some(object.clone());
+    object
+}
+
+/// The changes other documents made to local storage since they were last taken, to dispatch as
+/// `storage` events
+pub fn take_storage_changes(closure_context: &JsClosureContext) -> Vec<StorageChange> {
+    closure_context.storage.listener.take_changes()
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Storage>
+///
+/// Items are also properties of the storage, such as `localStorage.foo`, unless they have the name
+/// of a method.
+pub struct JsStorage {
+    area: Rc<RefCell<StorageArea>>,
+    /// The document using the storage, which is not told about the changes it makes
+    listener: StorageListener,
+}
+
+#[js_class(name = "Storage")]
+impl JsStorage {
+    pub fn new(area: Rc<RefCell<StorageArea>>, listener: StorageListener) -> Self {
+        area.borrow_mut().listen(&listener);
+        Self { area, listener }
+    }
+
+    #[js_method(getter)]
+    fn length(&self) -> u32 {
+        self.area.borrow().storage().length()
+    }
+
+    #[js_method]
+    fn key(&self, index: u32) -> Option<String> {
+        self.area.borrow().storage().key(index).map(str::to_string)
+    }
+
+    #[js_method]
+    fn get_item(&self, key: String) -> Option<String> {
+        self.area
+            .borrow()
+            .storage()
+            .get_item(&key)
+            .map(str::to_string)
+    }
+
+    /// Throws a `QuotaExceededError` when the storage has no room for the item
+    #[js_method]
+    fn set_item(
+        &mut self,
+        key: String,
+        value: String,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(), JsValueNode> {
+        // Exceeding the quota is the only way storing an item in the area fails
+        self.area
+            .borrow_mut()
+            .set_item(&key, &value, &self.listener)
+            .map_err(|_| JsValue::quota_exceeded_error_rc(&closure_context.nodes_graph))
+    }
+
+    #[js_method]
+    fn remove_item(&mut self, key: String) {
+        self.area.borrow_mut().remove_item(&key, &self.listener);
+    }
+
+    #[js_method]
+    fn clear(&mut self) {
+        self.area.borrow_mut().clear(&self.listener);
+    }
+}
+
+impl JsNamedProperties for JsStorage {
+    fn get_supported_property_names(&self) -> Vec<String> {
+        let area = self.area.borrow();
+        let storage = area.storage();
+        (0..storage.length())
+            .filter_map(|index| storage.key(index))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn get_named_property(&self, name: &str) -> Option<String> {
+        self.get_item(name.to_string())
+    }
+
+    fn set_named_property(
+        &mut self,
+        name: &str,
+        value: String,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(), JsValueNode> {
+        self.set_item(name.to_string(), value, closure_context)
+    }
+
+    fn delete_named_property(&mut self, name: &str) {
+        self.remove_item(name.to_string());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::js::api::index_db::IDBValue;
+
+use super::{
+    get_own_enumerable_entries, JsClosureContext, JsDate, JsProperties, JsValue, JsValueNode,
+};
+
+/// Copies a value out of the JS heap, as `history.pushState` does to keep its state, throwing a
+/// `DataCloneError` for values that cannot be copied. These are the primitives other than symbols
+/// and bigints, dates, array buffers, arrays, and plain objects, whose own enumerable properties
+/// are copied. Values that contain themselves cannot be copied either.
+/// <https://html.spec.whatwg.org/multipage/structured-data.html#structuredserialize>
+pub fn structured_serialize(
+    closure_context: &mut JsClosureContext,
+    value: &JsValueNode,
+) -> Result<IDBValue, JsValueNode> {
+    serialize_value(closure_context, value, &mut vec![])
+}
+
+fn serialize_value(
+    closure_context: &mut JsClosureContext,
+    value: &JsValueNode,
+    ancestors: &mut Vec<usize>,
+) -> Result<IDBValue, JsValueNode> {
+    let elements = match value.get_ref() {
+        JsValue::Undefined => return Ok(IDBValue::Undefined),
+        JsValue::Null => return Ok(IDBValue::Null),
+        JsValue::Boolean(value) => return Ok(IDBValue::Bool(*value)),
+        JsValue::Number(value) => return Ok(IDBValue::Number(*value)),
+        JsValue::String(value) => return Ok(IDBValue::String(value.clone())),
+        JsValue::Date(date) => return Ok(IDBValue::Date(date.time)),
+        JsValue::ArrayBuffer(bytes) => return Ok(IDBValue::Binary(bytes.clone())),
+        JsValue::Array(elements, _) => Some(elements.clone()),
+        JsValue::Object(_) => None,
+        _ => return Err(JsValue::data_clone_error_rc(&closure_context.nodes_graph)),
+    };
+    if ancestors.contains(&value.id()) {
+        return Err(JsValue::data_clone_error_rc(&closure_context.nodes_graph));
+    }
+    ancestors.push(value.id());
+    let result = match elements {
+        Some(elements) => {
+            let mut values = vec![];
+            for element in &elements {
+                values.push(serialize_value(closure_context, element, ancestors)?);
+            }
+            IDBValue::Array(values)
+        }
+        None => {
+            let mut properties = vec![];
+            for (key, member) in get_own_enumerable_entries(value, closure_context)? {
+                if let Some(name) = key.as_str() {
+                    let member = serialize_value(closure_context, &member, ancestors)?;
+                    properties.push((name.to_string(), member));
+                }
+            }
+            IDBValue::Object(properties)
+        }
+    };
+    ancestors.pop();
+    Ok(result)
+}
+
+/// Creates a new JS value from a copy made by [structured_serialize]
+/// <https://html.spec.whatwg.org/multipage/structured-data.html#structureddeserialize>
+pub fn structured_deserialize(closure_context: &JsClosureContext, value: &IDBValue) -> JsValueNode {
+    let node_graph = &closure_context.nodes_graph;
+    match value {
+        IDBValue::Undefined => JsValue::undefined_rc(node_graph),
+        IDBValue::Null => JsValue::null_rc(node_graph),
+        IDBValue::Bool(value) => JsValue::bool_rc(node_graph, *value),
+        IDBValue::Number(value) => JsValue::number_rc(node_graph, *value),
+        IDBValue::String(value) => JsValue::string_rc(node_graph, value.clone()),
+        IDBValue::Date(time) => {
+            let date = JsDate::new(*time, closure_context.time_zone.clone());
+            JsValue::date_rc(node_graph, date)
+        }
+        IDBValue::Binary(bytes) => JsValue::array_buffer_rc(node_graph, bytes.clone()),
+        IDBValue::Array(values) => {
+            let elements = values
+                .iter()
+                .map(|value| structured_deserialize(closure_context, value))
+                .collect();
+            JsValue::array_rc(node_graph, elements)
+        }
+        IDBValue::Object(entries) => {
+            let mut properties = JsProperties::new();
+            for (name, value) in entries {
+                let value = structured_deserialize(closure_context, value);
+                properties.insert(name.clone().into(), value);
+            }
+            JsValue::object_rc(node_graph, properties)
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Symbols that the language uses to look up customizable behavior on objects
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsWellKnownSymbol {
+    AsyncIterator,
+    HasInstance,
+    IsConcatSpreadable,
+    Iterator,
+    Match,
+    MatchAll,
+    Replace,
+    Search,
+    Species,
+    Split,
+    ToPrimitive,
+    ToStringTag,
+    Unscopables,
+}
+
+impl JsWellKnownSymbol {
+    pub const ALL: [Self; 13] = [
+        Self::AsyncIterator,
+        Self::HasInstance,
+        Self::IsConcatSpreadable,
+        Self::Iterator,
+        Self::Match,
+        Self::MatchAll,
+        Self::Replace,
+        Self::Search,
+        Self::Species,
+        Self::Split,
+        Self::ToPrimitive,
+        Self::ToStringTag,
+        Self::Unscopables,
+    ];
+
+    /// The name of the property on the `Symbol` constructor holding this symbol
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AsyncIterator => "asyncIterator",
+            Self::HasInstance => "hasInstance",
+            Self::IsConcatSpreadable => "isConcatSpreadable",
+            Self::Iterator => "iterator",
+            Self::Match => "match",
+            Self::MatchAll => "matchAll",
+            Self::Replace => "replace",
+            Self::Search => "search",
+            Self::Species => "species",
+            Self::Split => "split",
+            Self::ToPrimitive => "toPrimitive",
+            Self::ToStringTag => "toStringTag",
+            Self::Unscopables => "unscopables",
+        }
+    }
+}
+
+/// Ids below this are reserved for the well-known symbols
+const FIRST_UNIQUE_SYMBOL_ID: usize = JsWellKnownSymbol::ALL.len();
+
+static NEXT_SYMBOL_ID: AtomicUsize = AtomicUsize::new(FIRST_UNIQUE_SYMBOL_ID);
+
+/// A unique value usable as a property key. Symbols are only equal to themselves, regardless of
+/// their description.
+#[derive(Clone, Debug)]
+pub struct JsSymbol {
+    id: usize,
+    pub description: Option<String>,
+}
+
+impl JsSymbol {
+    pub fn new(description: Option<String>) -> Self {
+        Self {
+            id: NEXT_SYMBOL_ID.fetch_add(1, Ordering::Relaxed),
+            description,
+        }
+    }
+
+    pub fn well_known(symbol: JsWellKnownSymbol) -> Self {
+        Self {
+            id: symbol as usize,
+            description: Some(format!("Symbol.{}", symbol.name())),
+        }
+    }
+}
+
+impl PartialEq for JsSymbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for JsSymbol {}
+
+impl Hash for JsSymbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl std::fmt::Display for JsSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Symbol({})", self.description.as_deref().unwrap_or(""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equality() {
+        let a = JsSymbol::new(Some("a".to_string()));
+        assert_eq!(a, a.clone());
+        assert_ne!(a, JsSymbol::new(Some("a".to_string())));
+        assert_eq!(
+            JsSymbol::well_known(JsWellKnownSymbol::Iterator),
+            JsSymbol::well_known(JsWellKnownSymbol::Iterator)
+        );
+        assert_ne!(
+            JsSymbol::well_known(JsWellKnownSymbol::Iterator),
+            JsSymbol::well_known(JsWellKnownSymbol::AsyncIterator)
+        );
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(JsSymbol::new(None).to_string(), "Symbol()");
+        assert_eq!(
+            JsSymbol::well_known(JsWellKnownSymbol::Iterator).to_string(),
+            "Symbol(Symbol.iterator)"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use wowser_macros::js_class;
+
+use super::{
+    get_argument, get_member_from_prototype_chain, typed_array_rc, JsClosureContext,
+    JsTypedArrayKind, JsValue, JsValueNode,
+};
+
+/// The only encoding supported, which is the one `TextEncoder` always uses
+const UTF_8: &str = "utf-8";
+
+/// The labels of UTF-8, which are matched ignoring case and surrounding whitespace.
+/// <https://encoding.spec.whatwg.org/#names-and-labels>
+const UTF_8_LABELS: [&str; 6] = [
+    "unicode-1-1-utf-8",
+    "unicode11utf8",
+    "unicode20utf8",
+    "utf-8",
+    "utf8",
+    "x-unicode20utf8",
+];
+
+const BYTE_ORDER_MARK: &[u8] = &[0xef, 0xbb, 0xbf];
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/TextEncoder>
+pub struct JsTextEncoder;
+
+#[js_class(name = "TextEncoder")]
+impl JsTextEncoder {
+    pub fn construct(
+        _closure_context: &mut JsClosureContext,
+        _args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        Ok(Self)
+    }
+
+    #[js_method(getter)]
+    fn encoding(&self) -> &'static str {
+        UTF_8
+    }
+
+    /// The UTF-8 bytes of a string as a `Uint8Array`
+    #[js_method]
+    fn encode(&self, closure_context: &mut JsClosureContext, input: Option<String>) -> JsValueNode {
+        let bytes = input.unwrap_or_default().into_bytes();
+        typed_array_rc(&closure_context.nodes_graph, JsTypedArrayKind::Uint8, bytes)
+    }
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/TextDecoder>
+pub struct JsTextDecoder {
+    fatal: bool,
+    ignore_bom: bool,
+}
+
+#[js_class(name = "TextDecoder")]
+impl JsTextDecoder {
+    /// Decodes from an encoding label, which must be one of UTF-8 or a `RangeError` is thrown, and
+    /// an options object with `fatal` and `ignoreBOM`
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let label = match get_argument(closure_context, args, 0).get_ref() {
+            JsValue::Undefined => UTF_8.to_string(),
+            label => label.to_string(),
+        };
+        let label = label.trim().to_ascii_lowercase();
+        if !UTF_8_LABELS.contains(&label.as_str()) {
+            return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+        }
+
+        let options = get_argument(closure_context, args, 1);
+        let mut get_option = |name: &str| -> Result<bool, JsValueNode> {
+            if !options.get_ref().is_object() {
+                return Ok(false);
+            }
+            let option = get_member_from_prototype_chain(&options, &name.into(), closure_context)?;
+            Ok(option.map_value(|option| option.into()))
+        };
+        Ok(Self {
+            fatal: get_option("fatal")?,
+            ignore_bom: get_option("ignoreBOM")?,
+        })
+    }
+
+    #[js_method(getter)]
+    fn encoding(&self) -> &'static str {
+        UTF_8
+    }
+
+    #[js_method(getter)]
+    fn fatal(&self) -> bool {
+        self.fatal
+    }
+
+    #[js_method(getter, name = "ignoreBOM")]
+    fn ignore_bom(&self) -> bool {
+        self.ignore_bom
+    }
+
+    /// The string of the UTF-8 bytes of an `ArrayBuffer` or a view of one, skipping a leading
+    /// byte order mark unless `ignoreBOM` is set. Invalid bytes are replaced with U+FFFD, or throw
+    /// a `TypeError` when `fatal` is set.
+    #[js_method]
+    fn decode(
+        &self,
+        closure_context: &mut JsClosureContext,
+        input: Option<Vec<u8>>,
+    ) -> Result<String, JsValueNode> {
+        let input = input.unwrap_or_default();
+        let bytes = match self.ignore_bom {
+            true => &input[..],
+            false => input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(&input),
+        };
+        if self.fatal {
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| JsValue::type_error_rc(&closure_context.nodes_graph))
+        } else {
+            Ok(String::from_utf8_lossy(bytes).into_owned())
+        }
+    }
+}
@@ -0,0 +1,804 @@
+use super::{
+    build_function_entry, build_native_function, build_prototype, collect_iterable, get_argument,
+    iterate, iterator_key, to_bigint, to_number, FromJsValue, JsBigInt, JsClosureContext,
+    JsFunctionResult, JsIterationKind, JsNumeric, JsProperty, JsPropertyKey, JsValue, JsValueGraph,
+    JsValueNode, CONSTRUCTOR_PROTOTYPE_MEMBER,
+};
+
+pub const BYTES_PER_ELEMENT_MEMBER: &str = "BYTES_PER_ELEMENT";
+
+/// The largest `ArrayBuffer` that can be allocated, past which a `RangeError` is thrown
+const MAX_BYTE_LENGTH: usize = 1 << 30;
+
+/// Typed arrays use the byte order of the platform, while `DataView` is big-endian by default
+const NATIVE_LITTLE_ENDIAN: bool = cfg!(target_endian = "little");
+
+/// The element types of typed arrays, which are also the numbers `DataView` reads and writes.
+/// Elements of the 64 bit integer kinds are BigInts, and the others are numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JsTypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+}
+
+impl JsTypedArrayKind {
+    pub const ALL: [Self; 11] = [
+        Self::Int8,
+        Self::Uint8,
+        Self::Uint8Clamped,
+        Self::Int16,
+        Self::Uint16,
+        Self::Int32,
+        Self::Uint32,
+        Self::Float32,
+        Self::Float64,
+        Self::BigInt64,
+        Self::BigUint64,
+    ];
+
+    /// The name of the constructor, such as `Uint8Array`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Int8 => "Int8Array",
+            Self::Uint8 => "Uint8Array",
+            Self::Uint8Clamped => "Uint8ClampedArray",
+            Self::Int16 => "Int16Array",
+            Self::Uint16 => "Uint16Array",
+            Self::Int32 => "Int32Array",
+            Self::Uint32 => "Uint32Array",
+            Self::Float32 => "Float32Array",
+            Self::Float64 => "Float64Array",
+            Self::BigInt64 => "BigInt64Array",
+            Self::BigUint64 => "BigUint64Array",
+        }
+    }
+
+    /// The name used by the methods of `DataView`, such as `Uint8` in `getUint8`. There are no
+    /// methods for clamped numbers.
+    fn data_view_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Uint8Clamped => None,
+            kind => kind.name().strip_suffix("Array"),
+        }
+    }
+
+    pub fn element_size(&self) -> usize {
+        match self {
+            Self::Int8 | Self::Uint8 | Self::Uint8Clamped => 1,
+            Self::Int16 | Self::Uint16 => 2,
+            Self::Int32 | Self::Uint32 | Self::Float32 => 4,
+            Self::Float64 | Self::BigInt64 | Self::BigUint64 => 8,
+        }
+    }
+
+    pub fn is_bigint(&self) -> bool {
+        matches!(self, Self::BigInt64 | Self::BigUint64)
+    }
+
+    /// Converts a value to an element, which throws a `TypeError` when storing a number in a
+    /// BigInt array or a BigInt in any other
+    pub fn to_element(
+        &self,
+        value: &JsValueNode,
+        closure_context: &JsClosureContext,
+    ) -> Result<JsNumeric, JsValueNode> {
+        if self.is_bigint() {
+            Ok(JsNumeric::BigInt(to_bigint(value, closure_context)?))
+        } else {
+            Ok(JsNumeric::Number(to_number(value, closure_context)?))
+        }
+    }
+
+    /// Reads an element from as many bytes as its size
+    pub fn read(&self, bytes: &[u8], little_endian: bool) -> JsNumeric {
+        let mut le_bytes = [0; 8];
+        le_bytes[..bytes.len()].copy_from_slice(bytes);
+        if !little_endian {
+            le_bytes[..bytes.len()].reverse();
+        }
+        let bits = u64::from_le_bytes(le_bytes);
+        JsNumeric::Number(match self {
+            Self::Int8 => (bits as i8).into(),
+            Self::Uint8 | Self::Uint8Clamped => (bits as u8).into(),
+            Self::Int16 => (bits as i16).into(),
+            Self::Uint16 => (bits as u16).into(),
+            Self::Int32 => (bits as i32).into(),
+            Self::Uint32 => (bits as u32).into(),
+            Self::Float32 => f32::from_bits(bits as u32).into(),
+            Self::Float64 => f64::from_bits(bits),
+            Self::BigInt64 => return JsNumeric::BigInt(JsBigInt::from_i64(bits as i64)),
+            Self::BigUint64 => return JsNumeric::BigInt(JsBigInt::from_u64(bits)),
+        })
+    }
+
+    /// The bytes of an element. Integers wrap around, except for clamped numbers which are
+    /// rounded to the nearest in range.
+    pub fn write(&self, value: &JsNumeric, little_endian: bool) -> Vec<u8> {
+        let bits = match value {
+            JsNumeric::BigInt(bigint) if self.is_bigint() => bigint.to_u64_wrapping(),
+            JsNumeric::BigInt(bigint) => self.number_bits(bigint.to_f64()),
+            JsNumeric::Number(number) => self.number_bits(*number),
+        };
+        let mut bytes = bits.to_le_bytes()[..self.element_size()].to_vec();
+        if !little_endian {
+            bytes.reverse();
+        }
+        bytes
+    }
+
+    fn number_bits(&self, value: f64) -> u64 {
+        match self {
+            Self::Float32 => (value as f32).to_bits().into(),
+            Self::Float64 => value.to_bits(),
+            Self::Uint8Clamped if value.is_nan() => 0,
+            Self::Uint8Clamped => round_half_to_even(value.clamp(0.0, 255.0)) as u64,
+            _ if !value.is_finite() => 0,
+            Self::BigInt64 | Self::BigUint64 => JsBigInt::from_f64(value.trunc())
+                .unwrap_or_default()
+                .to_u64_wrapping(),
+            // The low bytes are the same whether the integer is signed or not
+            _ => value.trunc().rem_euclid(2f64.powi(32)) as u64,
+        }
+    }
+}
+
+/// Rounds to the nearest integer, and halfway cases to the even one
+fn round_half_to_even(value: f64) -> f64 {
+    let rounded = value.round();
+    if (rounded - value).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        rounded
+    }
+}
+
+/// A view of numbers in part of an `ArrayBuffer`. Views share the buffer they are created on, so
+/// writes through one view are read by the others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsTypedArray {
+    pub kind: JsTypedArrayKind,
+    pub buffer: JsValueNode,
+    pub byte_offset: usize,
+    pub length: usize,
+}
+
+impl JsTypedArray {
+    pub fn byte_length(&self) -> usize {
+        self.length * self.kind.element_size()
+    }
+
+    pub fn get(&self, index: usize) -> Option<JsNumeric> {
+        if index >= self.length {
+            return None;
+        }
+        let size = self.kind.element_size();
+        let bytes = read_buffer(&self.buffer, self.byte_offset + index * size, size);
+        Some(self.kind.read(&bytes, NATIVE_LITTLE_ENDIAN))
+    }
+
+    /// Writes an element. Indices out of range are ignored.
+    pub fn set(&self, index: usize, value: &JsNumeric) {
+        if index < self.length {
+            let size = self.kind.element_size();
+            let bytes = self.kind.write(value, NATIVE_LITTLE_ENDIAN);
+            write_buffer(&self.buffer, self.byte_offset + index * size, &bytes);
+        }
+    }
+
+    pub fn values(&self) -> Vec<JsNumeric> {
+        (0..self.length)
+            .filter_map(|index| self.get(index))
+            .collect()
+    }
+
+    /// A copy of the bytes viewed
+    pub fn bytes(&self) -> Vec<u8> {
+        read_buffer(&self.buffer, self.byte_offset, self.byte_length())
+    }
+}
+
+/// A view reading and writing numbers of any type and byte order in part of an `ArrayBuffer`
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsDataView {
+    pub buffer: JsValueNode,
+    pub byte_offset: usize,
+    pub byte_length: usize,
+}
+
+impl JsDataView {
+    /// A copy of the bytes viewed
+    pub fn bytes(&self) -> Vec<u8> {
+        read_buffer(&self.buffer, self.byte_offset, self.byte_length)
+    }
+}
+
+fn read_buffer(buffer: &JsValueNode, start: usize, length: usize) -> Vec<u8> {
+    match buffer.get_ref() {
+        JsValue::ArrayBuffer(bytes) => bytes[start..start + length].to_vec(),
+        _ => vec![],
+    }
+}
+
+fn write_buffer(buffer: &JsValueNode, start: usize, bytes: &[u8]) {
+    let mut buffer = buffer.clone();
+    if let JsValue::ArrayBuffer(buffer_bytes) = buffer.get_mut() {
+        buffer_bytes[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+}
+
+/// Creates a typed array viewing all of a new buffer of the bytes
+pub fn typed_array_rc(
+    node_graph: &JsValueGraph,
+    kind: JsTypedArrayKind,
+    bytes: Vec<u8>,
+) -> JsValueNode {
+    let length = bytes.len() / kind.element_size();
+    let buffer = JsValue::array_buffer_rc(node_graph, bytes);
+    JsValue::typed_array_rc(
+        node_graph,
+        JsTypedArray {
+            kind,
+            buffer,
+            byte_offset: 0,
+            length,
+        },
+    )
+}
+
+fn typed_array_from_values(
+    node_graph: &JsValueGraph,
+    kind: JsTypedArrayKind,
+    values: &[JsNumeric],
+) -> JsValueNode {
+    let bytes = values
+        .iter()
+        .flat_map(|value| kind.write(value, NATIVE_LITTLE_ENDIAN))
+        .collect();
+    typed_array_rc(node_graph, kind, bytes)
+}
+
+/// Buffer sources, which are array buffers and the views of them, convert to a copy of their bytes
+impl FromJsValue for Vec<u8> {
+    fn from_js_value(
+        value: &JsValueNode,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<Self, JsValueNode> {
+        match value.get_ref() {
+            JsValue::ArrayBuffer(bytes) => Ok(bytes.clone()),
+            JsValue::TypedArray(array) => Ok(array.bytes()),
+            JsValue::DataView(view) => Ok(view.bytes()),
+            _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        }
+    }
+}
+
+/// Converts a length or offset, throwing a `RangeError` when it is negative or larger than a
+/// buffer can be.
+/// `undefined` is zero.
+fn to_index(value: &JsValueNode, closure_context: &JsClosureContext) -> Result<usize, JsValueNode> {
+    let number = match value.get_ref() {
+        JsValue::Undefined => 0.0,
+        _ => to_number(value, closure_context)?,
+    };
+    let number = if number.is_nan() { 0.0 } else { number.trunc() };
+    if !(0.0..=MAX_BYTE_LENGTH as f64).contains(&number) {
+        return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+    }
+    Ok(number as usize)
+}
+
+/// Converts the start or end of a range, where negative positions count back from the length.
+/// `undefined` is the default.
+fn to_relative_index(
+    value: &JsValueNode,
+    length: usize,
+    default: usize,
+    closure_context: &JsClosureContext,
+) -> Result<usize, JsValueNode> {
+    if let JsValue::Undefined = value.get_ref() {
+        return Ok(default);
+    }
+    let number = to_number(value, closure_context)?;
+    let length = length as f64;
+    let index = match number.trunc() {
+        index if index.is_nan() => 0.0,
+        index if index < 0.0 => (length + index).max(0.0),
+        index => index.min(length),
+    };
+    Ok(index as usize)
+}
+
+fn throw_without_new(
+    closure_context: &JsClosureContext,
+    this: &JsValueNode,
+) -> Result<(), JsValueNode> {
+    match this.get_ref() {
+        JsValue::Undefined => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        _ => Ok(()),
+    }
+}
+
+fn insert_constructor_prototype(constructor: &mut JsValueNode, prototype: JsValueNode) {
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            CONSTRUCTOR_PROTOTYPE_MEMBER.into(),
+            JsProperty::with_attributes(prototype, false, false, false),
+        );
+    }
+}
+
+/// `ArrayBuffer` must be constructed with `new` from its length, and starts with all bytes zero
+pub fn build_array_buffer_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &"ArrayBuffer".into(), |ctx, this, args| {
+            throw_without_new(ctx, &this)?;
+            let length = to_index(&get_argument(ctx, args, 0), ctx)?;
+            Ok(JsValue::array_buffer_rc(&ctx.nodes_graph, vec![0; length]))
+        });
+    let (key, is_view) = build_function_entry(&node_graph, "isView".into(), |ctx, _this, args| {
+        let is_view = matches!(
+            get_argument(ctx, args, 0).get_ref(),
+            JsValue::TypedArray(_) | JsValue::DataView(_)
+        );
+        Ok(JsValue::bool_rc(&ctx.nodes_graph, is_view))
+    });
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(key, JsProperty::with_attributes(is_view, true, false, true));
+    }
+    insert_constructor_prototype(&mut constructor, prototype);
+    constructor
+}
+
+pub fn build_array_buffer_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    build_prototype(
+        parent,
+        [build_function_entry(
+            &node_graph,
+            "slice".into(),
+            |ctx, this, args| {
+                let JsValue::ArrayBuffer(bytes) = this.get_ref() else {
+                    return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+                };
+                let length = bytes.len();
+                let start = to_relative_index(&get_argument(ctx, args, 0), length, 0, ctx)?;
+                let end = to_relative_index(&get_argument(ctx, args, 1), length, length, ctx)?;
+                let slice = bytes[start..end.max(start)].to_vec();
+                Ok(JsValue::array_buffer_rc(&ctx.nodes_graph, slice))
+            },
+        )],
+    )
+}
+
+/// Typed arrays must be constructed with `new`, from a length, from the elements of another typed
+/// array of numbers or BigInts like their own or an iterable, or as a view of an `ArrayBuffer` from a byte offset aligned to the size
+/// of their elements
+pub fn build_typed_array_constructor(
+    kind: JsTypedArrayKind,
+    prototype: JsValueNode,
+) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &kind.name().into(), move |ctx, this, args| {
+            throw_without_new(ctx, &this)?;
+            let nodes_graph = ctx.nodes_graph.clone();
+            let source = get_argument(ctx, args, 0);
+            let values = match source.get_ref() {
+                JsValue::ArrayBuffer(bytes) => {
+                    let array = view_array_buffer(ctx, kind, &source, bytes.len(), args)?;
+                    return Ok(JsValue::typed_array_rc(&nodes_graph, array));
+                }
+                JsValue::TypedArray(array) if array.kind.is_bigint() != kind.is_bigint() => {
+                    return Err(JsValue::type_error_rc(&nodes_graph));
+                }
+                JsValue::TypedArray(array) => array.values(),
+                value if value.is_object() => {
+                    let mut values = vec![];
+                    for value in collect_iterable(&source, ctx)? {
+                        values.push(kind.to_element(&value, ctx)?);
+                    }
+                    values
+                }
+                _ => {
+                    let byte_length = to_index(&source, ctx)? * kind.element_size();
+                    if byte_length > MAX_BYTE_LENGTH {
+                        return Err(JsValue::range_error_rc(&nodes_graph));
+                    }
+                    return Ok(typed_array_rc(&nodes_graph, kind, vec![0; byte_length]));
+                }
+            };
+            Ok(typed_array_from_values(&nodes_graph, kind, &values))
+        });
+
+    let bytes_per_element = JsValue::number_rc(&node_graph, kind.element_size() as f64);
+    if let JsValue::Function(_, properties) = constructor.get_mut() {
+        properties.insert_property(
+            BYTES_PER_ELEMENT_MEMBER.into(),
+            JsProperty::with_attributes(bytes_per_element, false, false, false),
+        );
+    }
+    insert_constructor_prototype(&mut constructor, prototype);
+    constructor
+}
+
+/// A typed array viewing a buffer from the byte offset and length arguments after it. Without a
+/// length, it views the rest of the buffer, which must then be a whole number of elements.
+fn view_array_buffer(
+    closure_context: &JsClosureContext,
+    kind: JsTypedArrayKind,
+    buffer: &JsValueNode,
+    buffer_length: usize,
+    args: &[JsValueNode],
+) -> Result<JsTypedArray, JsValueNode> {
+    let range_error = || JsValue::range_error_rc(&closure_context.nodes_graph);
+    let size = kind.element_size();
+    let byte_offset = to_index(&get_argument(closure_context, args, 1), closure_context)?;
+    if byte_offset % size != 0 || byte_offset > buffer_length {
+        return Err(range_error());
+    }
+    let length = match get_argument(closure_context, args, 2) {
+        length if matches!(length.get_ref(), JsValue::Undefined) => {
+            if (buffer_length - byte_offset) % size != 0 {
+                return Err(range_error());
+            }
+            (buffer_length - byte_offset) / size
+        }
+        length => to_index(&length, closure_context)?,
+    };
+    if byte_offset + length * size > buffer_length {
+        return Err(range_error());
+    }
+    Ok(JsTypedArray {
+        kind,
+        buffer: buffer.clone(),
+        byte_offset,
+        length,
+    })
+}
+
+/// The prototype each kind of typed array inherits its methods from
+pub fn build_typed_array_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let values = build_native_function(&node_graph, &"values".into(), |ctx, this, _| {
+        get_typed_array(ctx, &this)?;
+        Ok(iterate(this, JsIterationKind::Values))
+    });
+    build_prototype(
+        parent,
+        [
+            build_function_entry(&node_graph, "set".into(), typed_array_set),
+            build_function_entry(&node_graph, "subarray".into(), typed_array_subarray),
+            build_function_entry(&node_graph, "slice".into(), typed_array_slice),
+            build_function_entry(&node_graph, "fill".into(), typed_array_fill),
+            build_function_entry(&node_graph, "keys".into(), |ctx, this, _| {
+                get_typed_array(ctx, &this)?;
+                Ok(iterate(this, JsIterationKind::Keys))
+            }),
+            build_function_entry(&node_graph, "entries".into(), |ctx, this, _| {
+                get_typed_array(ctx, &this)?;
+                Ok(iterate(this, JsIterationKind::Entries))
+            }),
+            ("values".into(), values.clone()),
+            (iterator_key(), values),
+        ],
+    )
+}
+
+/// The prototype of one kind of typed array, which only adds the size of its elements
+pub fn build_typed_array_kind_prototype(
+    kind: JsTypedArrayKind,
+    parent: JsValueNode,
+) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let mut prototype = build_prototype(parent, []);
+    let bytes_per_element = JsValue::number_rc(&node_graph, kind.element_size() as f64);
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        properties.insert_property(
+            BYTES_PER_ELEMENT_MEMBER.into(),
+            JsProperty::with_attributes(bytes_per_element, false, false, false),
+        );
+    }
+    prototype
+}
+
+/// The typed array `this` refers to, throwing a `TypeError` if it is not one
+fn get_typed_array(
+    closure_context: &JsClosureContext,
+    this: &JsValueNode,
+) -> Result<JsTypedArray, JsValueNode> {
+    match this.get_ref() {
+        JsValue::TypedArray(array) => Ok(array.clone()),
+        _ => Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+    }
+}
+
+/// Copies the numbers of a typed array or an iterable into the array from an offset, throwing a
+/// `RangeError` if they do not fit
+fn typed_array_set(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let array = get_typed_array(closure_context, &this)?;
+    let source = get_argument(closure_context, args, 0);
+    let values = match source.get_ref() {
+        JsValue::TypedArray(source) if source.kind.is_bigint() != array.kind.is_bigint() => {
+            return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+        }
+        JsValue::TypedArray(source) => source.values(),
+        _ => {
+            let mut values = vec![];
+            for value in collect_iterable(&source, closure_context)? {
+                values.push(array.kind.to_element(&value, closure_context)?);
+            }
+            values
+        }
+    };
+    let offset = to_index(&get_argument(closure_context, args, 1), closure_context)?;
+    if offset + values.len() > array.length {
+        return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+    }
+    for (index, value) in values.into_iter().enumerate() {
+        array.set(offset + index, &value);
+    }
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// The start and end arguments of a range of elements
+fn get_range(
+    closure_context: &JsClosureContext,
+    args: &[JsValueNode],
+    first: usize,
+    length: usize,
+) -> Result<(usize, usize), JsValueNode> {
+    let start = get_argument(closure_context, args, first);
+    let end = get_argument(closure_context, args, first + 1);
+    let start = to_relative_index(&start, length, 0, closure_context)?;
+    let end = to_relative_index(&end, length, length, closure_context)?;
+    Ok((start, end.max(start)))
+}
+
+/// A view of a range of the elements, sharing the buffer of the array
+fn typed_array_subarray(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let array = get_typed_array(closure_context, &this)?;
+    let (start, end) = get_range(closure_context, args, 0, array.length)?;
+    let subarray = JsTypedArray {
+        byte_offset: array.byte_offset + start * array.kind.element_size(),
+        length: end - start,
+        ..array
+    };
+    Ok(JsValue::typed_array_rc(
+        &closure_context.nodes_graph,
+        subarray,
+    ))
+}
+
+/// A copy of a range of the elements in a new buffer
+fn typed_array_slice(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let array = get_typed_array(closure_context, &this)?;
+    let (start, end) = get_range(closure_context, args, 0, array.length)?;
+    let size = array.kind.element_size();
+    let bytes = array.bytes()[start * size..end * size].to_vec();
+    Ok(typed_array_rc(
+        &closure_context.nodes_graph,
+        array.kind,
+        bytes,
+    ))
+}
+
+fn typed_array_fill(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let array = get_typed_array(closure_context, &this)?;
+    let value = array
+        .kind
+        .to_element(&get_argument(closure_context, args, 0), closure_context)?;
+    let (start, end) = get_range(closure_context, args, 1, array.length)?;
+    for index in start..end {
+        array.set(index, &value);
+    }
+    Ok(this)
+}
+
+/// `DataView` must be constructed with `new` on an `ArrayBuffer`, optionally from a byte offset
+/// and for a byte length
+pub fn build_data_view_constructor(prototype: JsValueNode) -> JsValueNode {
+    let node_graph = prototype.get_node_graph();
+    let mut constructor =
+        build_native_function(&node_graph, &"DataView".into(), |ctx, this, args| {
+            throw_without_new(ctx, &this)?;
+            let buffer = get_argument(ctx, args, 0);
+            let JsValue::ArrayBuffer(bytes) = buffer.get_ref() else {
+                return Err(JsValue::type_error_rc(&ctx.nodes_graph));
+            };
+            let buffer_length = bytes.len();
+            let byte_offset = to_index(&get_argument(ctx, args, 1), ctx)?;
+            if byte_offset > buffer_length {
+                return Err(JsValue::range_error_rc(&ctx.nodes_graph));
+            }
+            let byte_length = match get_argument(ctx, args, 2) {
+                length if matches!(length.get_ref(), JsValue::Undefined) => {
+                    buffer_length - byte_offset
+                }
+                length => to_index(&length, ctx)?,
+            };
+            if byte_offset + byte_length > buffer_length {
+                return Err(JsValue::range_error_rc(&ctx.nodes_graph));
+            }
+            let view = JsDataView {
+                buffer: buffer.clone(),
+                byte_offset,
+                byte_length,
+            };
+            Ok(JsValue::data_view_rc(&ctx.nodes_graph, view))
+        });
+    insert_constructor_prototype(&mut constructor, prototype);
+    constructor
+}
+
+/// Has a getter and a setter for each type of number, such as `getInt16(byteOffset,
+/// littleEndian)` and `setInt16(byteOffset, value, littleEndian)`, which are big-endian unless
+/// `littleEndian` is true
+pub fn build_data_view_prototype(parent: JsValueNode) -> JsValueNode {
+    let node_graph = parent.get_node_graph();
+    let mut prototype = build_prototype(parent, []);
+    let methods = JsTypedArrayKind::ALL
+        .into_iter()
+        .filter_map(|kind| Some((kind, kind.data_view_name()?)))
+        .flat_map(|(kind, name)| {
+            let getter = build_function_entry(
+                &node_graph,
+                JsPropertyKey::from(format!("get{name}")),
+                move |ctx, this, args| {
+                    let (view, start) = get_data_view_position(ctx, &this, args, kind)?;
+                    let little_endian = bool::from(get_argument(ctx, args, 1).get_ref());
+                    let bytes = read_buffer(&view.buffer, start, kind.element_size());
+                    Ok(kind
+                        .read(&bytes, little_endian)
+                        .to_value_rc(&ctx.nodes_graph))
+                },
+            );
+            let setter = build_function_entry(
+                &node_graph,
+                JsPropertyKey::from(format!("set{name}")),
+                move |ctx, this, args| {
+                    let (view, start) = get_data_view_position(ctx, &this, args, kind)?;
+                    let value = kind.to_element(&get_argument(ctx, args, 1), ctx)?;
+                    let little_endian = bool::from(get_argument(ctx, args, 2).get_ref());
+                    write_buffer(&view.buffer, start, &kind.write(&value, little_endian));
+                    Ok(JsValue::undefined_rc(&ctx.nodes_graph))
+                },
+            );
+            [getter, setter]
+        })
+        .collect::<Vec<_>>();
+
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, value) in methods {
+            properties.insert_property(key, JsProperty::with_attributes(value, true, false, true));
+        }
+    }
+    prototype
+}
+
+/// The data view `this` refers to and the position in its buffer of the byte offset argument,
+/// throwing a `RangeError` if a number of the kind does not fit in the view from there
+fn get_data_view_position(
+    closure_context: &JsClosureContext,
+    this: &JsValueNode,
+    args: &[JsValueNode],
+    kind: JsTypedArrayKind,
+) -> Result<(JsDataView, usize), JsValueNode> {
+    let JsValue::DataView(view) = this.get_ref() else {
+        return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+    };
+    let offset = to_index(&get_argument(closure_context, args, 0), closure_context)?;
+    if offset + kind.element_size() > view.byte_length {
+        return Err(JsValue::range_error_rc(&closure_context.nodes_graph));
+    }
+    Ok((view.clone(), view.byte_offset + offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read() {
+        let cases = [
+            (JsTypedArrayKind::Int8, 200.0, vec![200], -56.0),
+            (JsTypedArrayKind::Uint8, -1.0, vec![255], 255.0),
+            (JsTypedArrayKind::Uint8, 256.5, vec![0], 0.0),
+            (JsTypedArrayKind::Uint8Clamped, 300.0, vec![255], 255.0),
+            (JsTypedArrayKind::Uint8Clamped, 2.5, vec![2], 2.0),
+            (JsTypedArrayKind::Uint8Clamped, f64::NAN, vec![0], 0.0),
+            (JsTypedArrayKind::Int16, -2.0, vec![0xff, 0xfe], -2.0),
+            (
+                JsTypedArrayKind::Uint16,
+                0x1234 as f64,
+                vec![0x12, 0x34],
+                4660.0,
+            ),
+            (
+                JsTypedArrayKind::Int32,
+                2f64.powi(31),
+                vec![0x80, 0, 0, 0],
+                -(2f64.powi(31)),
+            ),
+            (
+                JsTypedArrayKind::Uint32,
+                f64::INFINITY,
+                vec![0, 0, 0, 0],
+                0.0,
+            ),
+            (JsTypedArrayKind::Float32, 1.5, vec![0x3f, 0xc0, 0, 0], 1.5),
+            (
+                JsTypedArrayKind::Float64,
+                -0.0,
+                vec![0x80, 0, 0, 0, 0, 0, 0, 0],
+                -0.0,
+            ),
+        ];
+        for (kind, value, big_endian, expected) in cases {
+            let (value, expected) = (JsNumeric::Number(value), JsNumeric::Number(expected));
+            let bytes = kind.write(&value, false);
+            assert_eq!(bytes, big_endian, "{kind:?} {value}");
+            assert_eq!(kind.read(&bytes, false), expected, "{kind:?} {value}");
+
+            let mut little_endian = big_endian.clone();
+            little_endian.reverse();
+            assert_eq!(kind.write(&value, true), little_endian, "{kind:?} {value}");
+            assert_eq!(
+                kind.read(&little_endian, true),
+                expected,
+                "{kind:?} {value}"
+            );
+        }
+    }
+    #[test]
+    fn test_write_and_read_bigints() {
+        let cases = [
+            (JsTypedArrayKind::BigInt64, "-2", "-2"),
+            (
+                JsTypedArrayKind::BigInt64,
+                "9223372036854775808",
+                "-9223372036854775808",
+            ),
+            (JsTypedArrayKind::BigUint64, "-1", "18446744073709551615"),
+            (JsTypedArrayKind::BigUint64, "18446744073709551617", "1"),
+        ];
+        for (kind, value, expected) in cases {
+            let value = JsNumeric::BigInt(JsBigInt::parse(value).unwrap());
+            let expected = JsNumeric::BigInt(JsBigInt::parse(expected).unwrap());
+            for little_endian in [false, true] {
+                let bytes = kind.write(&value, little_endian);
+                assert_eq!(bytes.len(), 8);
+                assert_eq!(
+                    kind.read(&bytes, little_endian),
+                    expected,
+                    "{kind:?} {value}"
+                );
+            }
+        }
+    }
+}
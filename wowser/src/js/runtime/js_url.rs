@@ -0,0 +1,381 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wowser_macros::js_class;
+
+use crate::net::{parse_form_urlencoded, serialize_form_urlencoded, UrlRecord};
+
+use super::{
+    collect_iterable, get_argument, get_own_enumerable_entries, with_host_object, FromJsValue,
+    JsClosureContext, JsHostReferences, JsValue, JsValueNode,
+};
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/URL>
+///
+/// The URL is shared with its `searchParams`, which write their changes to its query.
+pub struct JsUrl {
+    url: Rc<RefCell<UrlRecord>>,
+    search_params: JsValueNode,
+}
+
+impl JsUrl {
+    /// `new URL(url, base)`, which throws a `TypeError` when the URL or the base is not valid
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let url = String::from_js_argument(args, 0, closure_context)?;
+        let base = Option::<String>::from_js_argument(args, 1, closure_context)?;
+        let url = parse_url(&url, base.as_deref())
+            .ok_or_else(|| JsValue::type_error_rc(&closure_context.nodes_graph))?;
+        Ok(Self::new(closure_context, url))
+    }
+
+    pub fn new(closure_context: &mut JsClosureContext, url: UrlRecord) -> Self {
+        let list = parse_query(&url);
+        let url = Rc::new(RefCell::new(url));
+        let search_params = JsUrlSearchParams {
+            list,
+            url: Some(url.clone()),
+        };
+        Self {
+            url,
+            search_params: closure_context.create_host_object(search_params),
+        }
+    }
+
+    /// Parses the query into the list of the `searchParams` again after it changes
+    fn update_search_params(
+        &self,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(), JsValueNode> {
+        let list = parse_query(&self.url.borrow());
+        with_host_object(
+            closure_context,
+            &self.search_params,
+            |params: &mut JsUrlSearchParams, _| params.list = list,
+        )
+    }
+}
+
+impl JsHostReferences for JsUrl {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        vec![self.search_params.clone()]
+    }
+}
+
+#[js_class(name = "URL")]
+impl JsUrl {
+    #[js_method(getter)]
+    fn href(&self) -> String {
+        self.url.borrow().to_string()
+    }
+
+    /// Replaces the whole URL, throwing a `TypeError` when the value is not a valid URL
+    #[js_method(setter)]
+    fn set_href(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        let url = UrlRecord::parse(&value, None)
+            .ok_or_else(|| JsValue::type_error_rc(&closure_context.nodes_graph))?;
+        *self.url.borrow_mut() = url;
+        self.update_search_params(closure_context)
+    }
+
+    /// The serialized origin, which is `null` when it is opaque
+    #[js_method(getter)]
+    fn origin(&self) -> String {
+        self.url
+            .borrow()
+            .origin()
+            .unwrap_or_else(|| "null".to_string())
+    }
+
+    #[js_method(getter)]
+    fn protocol(&self) -> String {
+        self.url.borrow().protocol()
+    }
+
+    #[js_method(setter)]
+    fn set_protocol(&mut self, value: String) {
+        self.url.borrow_mut().set_protocol(&value);
+    }
+
+    #[js_method(getter)]
+    fn username(&self) -> String {
+        self.url.borrow().username.clone()
+    }
+
+    #[js_method(setter)]
+    fn set_username(&mut self, value: String) {
+        self.url.borrow_mut().set_username(&value);
+    }
+
+    #[js_method(getter)]
+    fn password(&self) -> String {
+        self.url.borrow().password.clone()
+    }
+
+    #[js_method(setter)]
+    fn set_password(&mut self, value: String) {
+        self.url.borrow_mut().set_password(&value);
+    }
+
+    #[js_method(getter)]
+    fn host(&self) -> String {
+        self.url.borrow().host()
+    }
+
+    #[js_method(setter)]
+    fn set_host(&mut self, value: String) {
+        self.url.borrow_mut().set_host(&value);
+    }
+
+    #[js_method(getter)]
+    fn hostname(&self) -> String {
+        self.url.borrow().hostname()
+    }
+
+    #[js_method(setter)]
+    fn set_hostname(&mut self, value: String) {
+        self.url.borrow_mut().set_hostname(&value);
+    }
+
+    #[js_method(getter)]
+    fn port(&self) -> String {
+        self.url.borrow().port()
+    }
+
+    #[js_method(setter)]
+    fn set_port(&mut self, value: String) {
+        self.url.borrow_mut().set_port(&value);
+    }
+
+    #[js_method(getter)]
+    fn pathname(&self) -> String {
+        self.url.borrow().pathname()
+    }
+
+    #[js_method(setter)]
+    fn set_pathname(&mut self, value: String) {
+        self.url.borrow_mut().set_pathname(&value);
+    }
+
+    #[js_method(getter)]
+    fn search(&self) -> String {
+        self.url.borrow().search()
+    }
+
+    #[js_method(setter)]
+    fn set_search(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        self.url.borrow_mut().set_search(&value);
+        self.update_search_params(closure_context)
+    }
+
+    #[js_method(getter)]
+    fn hash(&self) -> String {
+        self.url.borrow().hash()
+    }
+
+    #[js_method(setter)]
+    fn set_hash(&mut self, value: String) {
+        self.url.borrow_mut().set_hash(&value);
+    }
+
+    #[js_method(getter)]
+    fn search_params(&self) -> JsValueNode {
+        self.search_params.clone()
+    }
+
+    #[js_method(name = "toString")]
+    fn serialize(&self) -> String {
+        self.href()
+    }
+
+    #[js_method(name = "toJSON")]
+    fn to_json(&self) -> String {
+        self.href()
+    }
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/URLSearchParams>
+///
+/// The name and value pairs are kept in order, and names can repeat.
+pub struct JsUrlSearchParams {
+    list: Vec<(String, String)>,
+    /// The URL the parameters are the query of, which is updated when they change
+    url: Option<Rc<RefCell<UrlRecord>>>,
+}
+
+impl JsUrlSearchParams {
+    /// `new URLSearchParams(init)`, where the pairs are copied from another `URLSearchParams`
+    /// object, an iterable of `[name, value]` pairs, or the properties of an object. Other values
+    /// are parsed as a query, which may start with `?`.
+    pub fn construct(
+        closure_context: &mut JsClosureContext,
+        args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        let init = get_argument(closure_context, args, 0);
+        let (is_search_params, is_array, is_object) = match init.get_ref() {
+            JsValue::Undefined => {
+                return Ok(Self {
+                    list: vec![],
+                    url: None,
+                })
+            }
+            JsValue::HostObject(object) => (object.is::<JsUrlSearchParams>(), false, true),
+            value => (
+                false,
+                matches!(value, JsValue::Array(_, _)),
+                value.is_object(),
+            ),
+        };
+        let list = if is_search_params {
+            with_host_object(closure_context, &init, |params: &mut Self, _| {
+                params.list.clone()
+            })?
+        } else if is_array {
+            let mut list = vec![];
+            for pair in collect_iterable(&init, closure_context)? {
+                let pair = collect_iterable(&pair, closure_context)?;
+                let [name, value] = &pair[..] else {
+                    return Err(JsValue::type_error_rc(&closure_context.nodes_graph));
+                };
+                list.push((
+                    String::from_js_value(name, closure_context)?,
+                    String::from_js_value(value, closure_context)?,
+                ));
+            }
+            list
+        } else if is_object {
+            let mut list = vec![];
+            for (key, value) in get_own_enumerable_entries(&init, closure_context)? {
+                list.push((
+                    key.to_string(),
+                    String::from_js_value(&value, closure_context)?,
+                ));
+            }
+            list
+        } else {
+            let query = String::from_js_value(&init, closure_context)?;
+            parse_form_urlencoded(query.strip_prefix('?').unwrap_or(&query))
+        };
+        Ok(Self { list, url: None })
+    }
+
+    /// Writes the parameters to the query of the URL, leaving out the query when there are none
+    /// <https://url.spec.whatwg.org/#concept-urlsearchparams-update>
+    fn update(&self) {
+        if let Some(url) = &self.url {
+            let query = serialize_form_urlencoded(&self.list);
+            url.borrow_mut().query = (!query.is_empty()).then_some(query);
+        }
+    }
+}
+
+#[js_class(name = "URLSearchParams")]
+impl JsUrlSearchParams {
+    #[js_method(getter)]
+    fn size(&self) -> u32 {
+        self.list.len() as u32
+    }
+
+    #[js_method]
+    fn append(&mut self, name: String, value: String) {
+        self.list.push((name, value));
+        self.update();
+    }
+
+    /// Removes the pairs with the name, or only those with the value too when it is given
+    #[js_method]
+    fn delete(&mut self, name: String, value: Option<String>) {
+        self.list.retain(|(pair_name, pair_value)| {
+            *pair_name != name || value.as_ref().map_or(false, |value| pair_value != value)
+        });
+        self.update();
+    }
+
+    /// The value of the first pair with the name, or `null` when there is none
+    #[js_method]
+    fn get(&self, name: String) -> Option<String> {
+        self.list
+            .iter()
+            .find(|(pair_name, _)| *pair_name == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[js_method]
+    fn get_all(&self, closure_context: &mut JsClosureContext, name: String) -> JsValueNode {
+        let values = self
+            .list
+            .iter()
+            .filter(|(pair_name, _)| *pair_name == name)
+            .map(|(_, value)| JsValue::string_rc(&closure_context.nodes_graph, value.clone()))
+            .collect();
+        JsValue::array_rc(&closure_context.nodes_graph, values)
+    }
+
+    #[js_method]
+    fn has(&self, name: String, value: Option<String>) -> bool {
+        self.list.iter().any(|(pair_name, pair_value)| {
+            *pair_name == name && value.as_ref().map_or(true, |value| pair_value == value)
+        })
+    }
+
+    /// Sets the value of the first pair with the name, removing the others, or appends a pair
+    /// when there is none
+    #[js_method]
+    fn set(&mut self, name: String, value: String) {
+        let mut found = false;
+        self.list.retain_mut(|(pair_name, pair_value)| {
+            if *pair_name != name {
+                return true;
+            }
+            if found {
+                return false;
+            }
+            found = true;
+            *pair_value = value.clone();
+            true
+        });
+        if !found {
+            self.list.push((name, value));
+        }
+        self.update();
+    }
+
+    /// Sorts the pairs by their names in UTF-16 code unit order, keeping the order of pairs with
+    /// the same name
+    #[js_method]
+    fn sort(&mut self) {
+        self.list
+            .sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+        self.update();
+    }
+
+    #[js_method(name = "toString")]
+    fn serialize(&self) -> String {
+        serialize_form_urlencoded(&self.list)
+    }
+}
+
+/// Parses a URL against a base URL, which must be valid when given
+fn parse_url(url: &str, base: Option<&str>) -> Option<UrlRecord> {
+    let base = match base {
+        Some(base) => Some(UrlRecord::parse(base, None)?),
+        None => None,
+    };
+    UrlRecord::parse(url, base.as_ref())
+}
+
+fn parse_query(url: &UrlRecord) -> Vec<(String, String)> {
+    url.query
+        .as_deref()
+        .map(parse_form_urlencoded)
+        .unwrap_or_default()
+}
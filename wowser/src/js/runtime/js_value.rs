@@ -1,8 +1,10 @@
-use std::collections::HashMap;
-
 use crate::garbage_collector::{GarbageCollectable, GcNode, GcNodeGraph};
 
-use super::{JsFunction, JsValueGraph, JsValueNode};
+use super::{
+    JsBigInt, JsDataView, JsDate, JsFunction, JsHostClass, JsHostObject, JsIterator,
+    JsKeyedCollection, JsProperties, JsPropertyKey, JsProxy, JsRegExp, JsSymbol, JsTypedArray,
+    JsValueGraph, JsValueNode, LENGTH_MEMBER, PROTOTYPE_MEMBER,
+};
 
 pub type JsNumberPrimitive = f64;
 
@@ -11,9 +13,25 @@ pub type JsNumberPrimitive = f64;
 pub enum JsValue {
     Boolean(bool),
     Number(JsNumberPrimitive),
+    BigInt(JsBigInt),
     String(String),
-    Function(JsFunction),
-    Object(HashMap<String, JsValueNode>),
+    Symbol(JsSymbol),
+    /// (Function, Own properties)
+    Function(JsFunction, JsProperties),
+    Object(JsProperties),
+    /// (Elements, Own properties other than the elements and length)
+    Array(Vec<JsValueNode>, JsProperties),
+    Collection(JsKeyedCollection),
+    Iterator(JsIterator),
+    RegExp(JsRegExp),
+    Date(JsDate),
+    /// The bytes of an `ArrayBuffer`, shared by the views created on it
+    ArrayBuffer(Vec<u8>),
+    TypedArray(JsTypedArray),
+    DataView(JsDataView),
+    /// A value of a Rust type exposed to JS
+    HostObject(JsHostObject),
+    Proxy(JsProxy),
     Undefined,
     Null,
 }
@@ -48,6 +66,10 @@ impl JsValue {
         GcNodeGraph::create_node(node_graph, Self::Number(value.into()))
     }
 
+    pub fn bigint_rc(node_graph: &JsValueGraph, bigint: JsBigInt) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::BigInt(bigint))
+    }
+
     pub fn undefined_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
         GcNodeGraph::create_node(node_graph, Self::Undefined)
     }
@@ -56,10 +78,58 @@ impl JsValue {
         GcNodeGraph::create_node(node_graph, Self::Null)
     }
 
-    pub fn object_rc(node_graph: &JsValueGraph, map: HashMap<String, JsValueNode>) -> GcNode<Self> {
+    pub fn symbol_rc(node_graph: &JsValueGraph, symbol: JsSymbol) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Symbol(symbol))
+    }
+
+    pub fn function_rc(node_graph: &JsValueGraph, function: JsFunction) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Function(function, JsProperties::new()))
+    }
+
+    pub fn object_rc(node_graph: &JsValueGraph, map: JsProperties) -> GcNode<Self> {
         GcNodeGraph::create_node(node_graph, Self::Object(map))
     }
 
+    pub fn array_rc(node_graph: &JsValueGraph, elements: Vec<JsValueNode>) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Array(elements, JsProperties::new()))
+    }
+
+    pub fn collection_rc(node_graph: &JsValueGraph, collection: JsKeyedCollection) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Collection(collection))
+    }
+
+    pub fn iterator_rc(node_graph: &JsValueGraph, iterator: JsIterator) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Iterator(iterator))
+    }
+
+    pub fn regexp_rc(node_graph: &JsValueGraph, regexp: JsRegExp) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::RegExp(regexp))
+    }
+
+    pub fn date_rc(node_graph: &JsValueGraph, date: JsDate) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Date(date))
+    }
+
+    pub fn array_buffer_rc(node_graph: &JsValueGraph, bytes: Vec<u8>) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::ArrayBuffer(bytes))
+    }
+
+    pub fn typed_array_rc(node_graph: &JsValueGraph, array: JsTypedArray) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::TypedArray(array))
+    }
+
+    pub fn data_view_rc(node_graph: &JsValueGraph, view: JsDataView) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::DataView(view))
+    }
+
+    pub fn proxy_rc(node_graph: &JsValueGraph, proxy: JsProxy) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::Proxy(proxy))
+    }
+
+    pub fn host_object_rc<T: JsHostClass>(node_graph: &JsValueGraph, value: T) -> GcNode<Self> {
+        GcNodeGraph::create_node(node_graph, Self::HostObject(JsHostObject::new(value)))
+    }
+
     pub fn type_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
         Self::string_rc(node_graph, "TypeError".to_string()) // TODO: These should raise exceptions when supported
     }
@@ -68,13 +138,137 @@ impl JsValue {
         Self::string_rc(node_graph, "DomException".to_string()) // TODO: These should raise exceptions when supported
     }
 
+    pub fn quota_exceeded_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "QuotaExceededError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    pub fn security_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "SecurityError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    pub fn invalid_state_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "InvalidStateError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    pub fn network_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "NetworkError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    pub fn data_clone_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "DataCloneError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
     pub fn reference_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
         Self::string_rc(node_graph, "ReferenceError".to_string()) // TODO: These should raise exceptions when supported
     }
 
+    pub fn syntax_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "SyntaxError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    /// A `SyntaxError` describing why and where source text failed to parse
+    pub fn syntax_error_with_message_rc(node_graph: &JsValueGraph, message: &str) -> GcNode<Self> {
+        Self::string_rc(node_graph, format!("SyntaxError: {message}"))
+    }
+
+    pub fn range_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "RangeError".to_string()) // TODO: These should raise exceptions when supported
+    }
+
+    /// Thrown when a script is terminated, which cannot be caught
+    pub fn termination_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
+        Self::string_rc(node_graph, "Terminated".to_string())
+    }
+
     pub fn stack_overflow_error_rc(node_graph: &JsValueGraph) -> GcNode<Self> {
         Self::string_rc(node_graph, "StackOverflowError".to_string()) // TODO: This should raise RangeError: Maximum call stack size exceeded when supported
     }
+
+    /// The keys of the own, enumerable properties of the value in the order they are copied by
+    /// object spread and rest syntax.
+    pub fn own_enumerable_keys(&self) -> Vec<JsPropertyKey> {
+        let indices = |length: usize| (0..length).map(|index| index.to_string().into()).collect();
+        let enumerable_keys = |properties: &JsProperties| {
+            properties
+                .iter()
+                .filter(|(key, property)| {
+                    property.enumerable && key.as_str() != Some(PROTOTYPE_MEMBER)
+                })
+                .map(|(key, _property)| key.clone())
+                .collect::<Vec<_>>()
+        };
+        match self {
+            Self::Object(properties) | Self::Function(_, properties) => enumerable_keys(properties),
+            Self::Array(elements, properties) => {
+                let mut keys: Vec<JsPropertyKey> = indices(elements.len());
+                keys.extend(enumerable_keys(properties));
+                keys
+            }
+            Self::String(string) => indices(string.chars().count()),
+            Self::TypedArray(array) => indices(array.length),
+            _ => vec![],
+        }
+    }
+
+    /// The keys of all own properties of the value in the order of property keys, including the
+    /// elements and length of arrays and strings
+    pub fn own_keys(&self) -> Vec<JsPropertyKey> {
+        let indices = |length: usize| (0..length).map(|index| index.to_string().into());
+        let keys = |properties: &JsProperties| {
+            properties
+                .keys()
+                .into_iter()
+                .filter(|key| key.as_str() != Some(PROTOTYPE_MEMBER))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        match self {
+            Self::Object(properties) | Self::Function(_, properties) => keys(properties),
+            Self::Array(elements, properties) => indices(elements.len())
+                .chain([LENGTH_MEMBER.into()])
+                .chain(keys(properties))
+                .collect(),
+            Self::String(string) => indices(string.chars().count())
+                .chain([LENGTH_MEMBER.into()])
+                .collect(),
+            Self::TypedArray(array) => indices(array.length).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the value can be invoked, which functions and proxies of them can
+    pub fn is_callable(&self) -> bool {
+        match self {
+            Self::Function(_, _) => true,
+            Self::Proxy(proxy) => proxy.callable,
+            _ => false,
+        }
+    }
+
+    /// Whether the value is an object, rather than a primitive, and so is compared by reference
+    pub fn is_object(&self) -> bool {
+        match self {
+            Self::Function(_, _)
+            | Self::Object(_)
+            | Self::Array(_, _)
+            | Self::Collection(_)
+            | Self::Iterator(_)
+            | Self::RegExp(_)
+            | Self::Date(_)
+            | Self::ArrayBuffer(_)
+            | Self::TypedArray(_)
+            | Self::DataView(_)
+            | Self::HostObject(_)
+            | Self::Proxy(_) => true,
+            Self::Boolean(_)
+            | Self::Number(_)
+            | Self::BigInt(_)
+            | Self::String(_)
+            | Self::Symbol(_)
+            | Self::Undefined
+            | Self::Null => false,
+        }
+    }
 }
 
 impl ToString for JsValue {
@@ -82,10 +276,12 @@ impl ToString for JsValue {
         match self {
             Self::Boolean(b) => b.to_string(),
             Self::Number(v) => v.to_string(),
+            Self::BigInt(bigint) => bigint.to_string(),
             Self::String(v) => v.clone(),
             Self::Undefined => "undefined".to_string(),
             Self::Null => "null".to_string(),
-            Self::Function(function) => match function {
+            Self::Symbol(symbol) => symbol.to_string(),
+            Self::Function(function, _properties) => match function {
                 JsFunction::Native(name, _implementation) => {
                     format!("function {name}() {{ [native code] }}")
                 }
@@ -94,6 +290,29 @@ impl ToString for JsValue {
                 }
             },
             Self::Object(_) => "[object Object]".to_string(),
+            Self::Collection(collection) => format!("[object {}]", collection.kind.name()),
+            Self::Iterator(_) => "[object Iterator]".to_string(),
+            Self::RegExp(regexp) => regexp.to_string(),
+            Self::Date(date) => date.to_string(),
+            Self::ArrayBuffer(_) => "[object ArrayBuffer]".to_string(),
+            Self::TypedArray(array) => array
+                .values()
+                .into_iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            Self::DataView(_) => "[object DataView]".to_string(),
+            Self::HostObject(object) => format!("[object {}]", object.class_name),
+            Self::Proxy(proxy) if proxy.callable => "function () { [native code] }".to_string(),
+            Self::Proxy(_) => "[object Object]".to_string(),
+            Self::Array(elements, _properties) => elements
+                .iter()
+                .map(|element| match element.get_ref() {
+                    Self::Undefined | Self::Null => String::new(),
+                    value => value.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
         }
     }
 }
@@ -115,6 +334,7 @@ impl From<&JsValue> for JsNumberPrimitive {
                 }
             }
             JsValue::Number(v) => *v,
+            JsValue::BigInt(bigint) => bigint.to_f64(),
             JsValue::String(v) => {
                 let trimmed = v.trim();
                 // Strings with just whitespace convert to 0
@@ -128,8 +348,19 @@ impl From<&JsValue> for JsNumberPrimitive {
             }
             JsValue::Undefined => JsNumberPrimitive::NAN,
             JsValue::Null => 0.0,
-            JsValue::Function(_) => JsNumberPrimitive::NAN,
+            JsValue::Symbol(_) => JsNumberPrimitive::NAN,
+            JsValue::Function(_, _) => JsNumberPrimitive::NAN,
             JsValue::Object(_) => JsNumberPrimitive::NAN,
+            JsValue::Collection(_) => JsNumberPrimitive::NAN,
+            JsValue::Iterator(_) => JsNumberPrimitive::NAN,
+            JsValue::RegExp(_) => JsNumberPrimitive::NAN,
+            JsValue::Date(date) => date.time,
+            JsValue::ArrayBuffer(_) => JsNumberPrimitive::NAN,
+            JsValue::TypedArray(_) => From::from(&JsValue::String(value.to_string())),
+            JsValue::DataView(_) => JsNumberPrimitive::NAN,
+            JsValue::HostObject(_) => JsNumberPrimitive::NAN,
+            JsValue::Proxy(_) => JsNumberPrimitive::NAN,
+            JsValue::Array(_, _) => From::from(&JsValue::String(value.to_string())),
         }
     }
 }
@@ -139,11 +370,23 @@ impl From<&JsValue> for bool {
         match value {
             JsValue::Boolean(v) => *v,
             JsValue::Number(v) => !v.is_nan() && *v != 0.0,
+            JsValue::BigInt(bigint) => !bigint.is_zero(),
             JsValue::String(v) => !v.is_empty(),
             JsValue::Undefined => false,
             JsValue::Null => false,
-            JsValue::Function(_) => true,
+            JsValue::Symbol(_) => true,
+            JsValue::Function(_, _) => true,
             JsValue::Object(_) => true,
+            JsValue::Array(_, _) => true,
+            JsValue::Collection(_) => true,
+            JsValue::Iterator(_) => true,
+            JsValue::RegExp(_) => true,
+            JsValue::Date(_) => true,
+            JsValue::ArrayBuffer(_) => true,
+            JsValue::TypedArray(_) => true,
+            JsValue::DataView(_) => true,
+            JsValue::HostObject(_) => true,
+            JsValue::Proxy(_) => true,
         }
     }
 }
@@ -153,11 +396,42 @@ impl GarbageCollectable for JsValue {
         match self {
             JsValue::Boolean(_) => vec![],
             JsValue::Number(_) => vec![],
+            JsValue::BigInt(_) => vec![],
             JsValue::String(_) => vec![],
             JsValue::Undefined => vec![],
             JsValue::Null => vec![],
-            JsValue::Function(function) => function.get_referenced_nodes(),
-            JsValue::Object(map) => map.values().map(Clone::clone).collect(),
+            JsValue::Symbol(_) => vec![],
+            JsValue::Function(function, properties) => [
+                function.get_referenced_nodes(),
+                properties.get_referenced_nodes(),
+            ]
+            .concat(),
+            JsValue::Object(properties) => properties.get_referenced_nodes(),
+            JsValue::Array(elements, properties) => {
+                [elements.clone(), properties.get_referenced_nodes()].concat()
+            }
+            JsValue::Collection(collection) => collection.get_referenced_nodes(),
+            JsValue::Iterator(iterator) => iterator.get_referenced_nodes(),
+            JsValue::RegExp(_) => vec![],
+            JsValue::Date(_) => vec![],
+            JsValue::ArrayBuffer(_) => vec![],
+            JsValue::TypedArray(array) => vec![array.buffer.clone()],
+            JsValue::DataView(view) => vec![view.buffer.clone()],
+            JsValue::HostObject(object) => object.get_referenced_nodes(),
+            JsValue::Proxy(proxy) => proxy.get_referenced_nodes(),
+        }
+    }
+
+    fn get_weak_entries(&self) -> Vec<(GcNode<Self>, GcNode<Self>)> {
+        match self {
+            JsValue::Collection(collection) => collection.get_weak_entries(),
+            _ => vec![],
+        }
+    }
+
+    fn retain_weak_entries(&mut self) {
+        if let JsValue::Collection(collection) = self {
+            collection.retain_weak_entries();
         }
     }
 }
@@ -188,26 +462,29 @@ mod tests {
         assert_eq!(JsValue::Undefined.to_string(), "undefined");
         assert_eq!(JsValue::Null.to_string(), "null");
         assert_eq!(
-            JsValue::Function(JsFunction::Native(
-                "abc".to_string(),
-                JsNativeFunctionImplementation::default()
-            ))
+            JsValue::Function(
+                JsFunction::Native("abc".to_string(), JsNativeFunctionImplementation::default()),
+                Default::default()
+            )
             .to_string(),
             "function abc() { [native code] }"
         );
         assert_eq!(
-            JsValue::Function(JsFunction::UserDefined(
-                "function abc(param) {return param;}".to_string(),
-                Default::default(),
-                Default::default(),
+            JsValue::Function(
+                JsFunction::UserDefined(
+                    "function abc(param) {return param;}".to_string(),
+                    Default::default(),
+                    Default::default(),
+                    Default::default()
+                ),
                 Default::default()
-            ))
+            )
             .to_string(),
             "function abc(param) {return param;}"
         );
         assert_eq!(
-            JsValue::Object(HashMap::from([(
-                "key".to_string(),
+            JsValue::Object(JsProperties::from([(
+                "key".into(),
                 JsValue::str_rc(&node_graph, "value")
             )]))
             .to_string(),
@@ -231,11 +508,11 @@ mod tests {
         assert!(f64::from(JsValue::Undefined).is_nan());
         assert_eq!(f64::from(JsValue::Null), 0.0);
 
-        assert!(f64::from(JsValue::Function(JsFunction::Native(
-            "abc".to_string(),
-            JsNativeFunctionImplementation::default(),
-        )))
+        assert!(f64::from(JsValue::Function(
+            JsFunction::Native("abc".to_string(), JsNativeFunctionImplementation::default(),),
+            Default::default()
+        ))
         .is_nan());
-        assert!(f64::from(JsValue::Object(HashMap::new())).is_nan());
+        assert!(f64::from(JsValue::Object(JsProperties::new())).is_nan());
     }
 }
@@ -0,0 +1,645 @@
+use std::rc::Rc;
+
+use super::{
+    access_member, add, apply_binary_operator, bind_identifier, bitwise_not, collect_iterable,
+    construct, create_class_prototype, create_constructed_object, declare_function_scope,
+    enter_module_scope, get_member_from_prototype_chain, get_own_enumerable_entries, import_module,
+    invoke_function, is_loosely_equal, is_strictly_equal, leave_module_scope, method_descriptor,
+    negate, regexp_rc, run_eval, set_member, to_number, JsChunk, JsClosureContext, JsFunction,
+    JsFunctionResult, JsInstruction, JsProperties, JsPropertyKey, JsStatementResult, JsValue,
+    JsValueNode,
+};
+
+/// Selects how documents and user defined functions are run
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum JsEngine {
+    /// Runs statements and expressions by walking them
+    TreeWalker,
+    /// Compiles statements to bytecode run by a [JsVm]
+    #[default]
+    Bytecode,
+}
+
+impl JsEngine {
+    pub const ALL: [Self; 2] = [Self::TreeWalker, Self::Bytecode];
+}
+
+/// A document or function being run
+#[derive(Debug)]
+struct JsFrame {
+    chunk: Rc<JsChunk>,
+    /// The index of the next instruction
+    ip: usize,
+    /// The height of the stack when the frame was entered
+    stack_base: usize,
+    /// (Target, Stack height) of the handlers of thrown values, innermost last
+    handlers: Vec<(usize, usize)>,
+    /// The object being constructed when invoked with `new`
+    constructing: Option<JsValueNode>,
+    /// Set when the frame has a scope of its own to leave when it exits
+    has_scope: bool,
+    /// The module whose scope was entered for the frame, to leave after the frame's own scope
+    module: Option<String>,
+    /// Set for the frame of a document, between whose statements garbage can be collected
+    is_document: bool,
+}
+
+/// What happens after an instruction runs
+enum JsStep {
+    Continue,
+    /// The frame the VM was entered with exited with the value
+    Exit(JsValueNode),
+}
+
+/// A stack machine running [JsChunk]s. User defined functions invoking each other push frames
+/// rather than recursing, so only native functions invoking user defined functions nest VMs.
+#[derive(Debug, Default)]
+pub struct JsVm {
+    stack: Vec<JsValueNode>,
+    frames: Vec<JsFrame>,
+}
+
+impl JsVm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a compiled document in the global scope
+    pub fn run_document(&mut self, chunk: Rc<JsChunk>, closure_context: &mut JsClosureContext) {
+        self.frames.push(JsFrame {
+            chunk,
+            ip: 0,
+            stack_base: self.stack.len(),
+            handlers: vec![],
+            constructing: None,
+            has_scope: false,
+            module: None,
+            is_document: true,
+        });
+        // Documents handle everything thrown in them by recording it, other than terminations
+        if let Err(value) = self.execute(closure_context, 0) {
+            closure_context.record_new_result(JsStatementResult::ThrowValue(value));
+        }
+    }
+
+    /// Runs a compiled module in the scope entered for it, resulting in the value thrown out of it
+    pub fn run_module(
+        &mut self,
+        chunk: Rc<JsChunk>,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<(), JsValueNode> {
+        self.frames.push(JsFrame {
+            chunk,
+            ip: 0,
+            stack_base: self.stack.len(),
+            handlers: vec![],
+            constructing: None,
+            has_scope: false,
+            module: None,
+            is_document: false,
+        });
+        self.execute(closure_context, 0).map(|_value| ())
+    }
+
+    /// Invokes a function, running it to completion
+    pub fn call(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        function: &JsValueNode,
+        this: JsValueNode,
+        args: &[JsValueNode],
+    ) -> JsFunctionResult {
+        let base_depth = self.frames.len();
+        self.invoke(closure_context, function, this, args, None)?;
+        if self.frames.len() == base_depth {
+            Ok(self.pop())
+        } else {
+            let value = self.execute(closure_context, base_depth)?;
+            Ok(value.expect("Functions return a value"))
+        }
+    }
+
+    /// Runs until the frame above `base_depth` exits, resulting in the value it returns
+    fn execute(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        base_depth: usize,
+    ) -> Result<Option<JsValueNode>, JsValueNode> {
+        loop {
+            let frame = self.frames.last_mut().expect("A frame is being run");
+            let chunk = frame.chunk.clone();
+            let step = match chunk.instructions.get(frame.ip) {
+                Some(instruction) => {
+                    frame.ip += 1;
+                    closure_context
+                        .step()
+                        .and_then(|()| self.step(closure_context, &chunk, instruction, base_depth))
+                }
+                // Only documents end without returning, and are only run by themselves
+                None => {
+                    self.leave_frame(closure_context);
+                    return Ok(None);
+                }
+            };
+            match step {
+                Ok(JsStep::Continue) => {}
+                Ok(JsStep::Exit(value)) => return Ok(Some(value)),
+                Err(value) => self.throw(closure_context, value, base_depth)?,
+            }
+        }
+    }
+
+    fn step(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        chunk: &JsChunk,
+        instruction: &JsInstruction,
+        base_depth: usize,
+    ) -> Result<JsStep, JsValueNode> {
+        let nodes_graph = closure_context.nodes_graph.clone();
+        let name = |index: &usize| chunk.names[*index].as_str();
+        match instruction {
+            JsInstruction::Undefined => self.push(JsValue::undefined_rc(&nodes_graph)),
+            JsInstruction::Null => self.push(JsValue::null_rc(&nodes_graph)),
+            JsInstruction::Boolean(b) => self.push(JsValue::bool_rc(&nodes_graph, *b)),
+            JsInstruction::Number(num) => self.push(JsValue::number_rc(&nodes_graph, *num)),
+            JsInstruction::BigInt(bigint) => {
+                self.push(JsValue::bigint_rc(&nodes_graph, bigint.as_ref().clone()))
+            }
+            JsInstruction::String(string) => self.push(JsValue::str_rc(&nodes_graph, name(string))),
+            JsInstruction::RegExp(source, flags) => {
+                self.push(regexp_rc(&nodes_graph, name(source), name(flags))?)
+            }
+            JsInstruction::Node(node) => self.push(chunk.nodes[*node].clone()),
+
+            JsInstruction::Pop => {
+                self.pop();
+            }
+            JsInstruction::Dup => self.push(self.peek(0).clone()),
+            JsInstruction::Over => self.push(self.peek(1).clone()),
+
+            JsInstruction::GetReference(variable_name) => {
+                let value = closure_context
+                    .get_reference(name(variable_name))
+                    .map(|reference| reference.value.clone())
+                    .ok_or_else(|| JsValue::reference_error_rc(&nodes_graph))?;
+                self.push(value);
+            }
+            JsInstruction::DeclareVar(variable_name) => {
+                let reference = closure_context.get_or_declare_reference_mut(name(variable_name));
+                let value = reference.value.clone();
+                self.push(value);
+            }
+            JsInstruction::AssignVar(variable_name) => {
+                let value = self.peek(0).clone();
+                closure_context
+                    .get_or_declare_reference_mut(name(variable_name))
+                    .value = value;
+            }
+            JsInstruction::Bind(variable_name, declaration) => {
+                let value = self.pop();
+                bind_identifier(closure_context, name(variable_name), value, *declaration)?;
+            }
+
+            JsInstruction::Add => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(add(&a, &b, closure_context)?);
+            }
+            JsInstruction::Binary(operator) => {
+                let b = self.pop();
+                let a = self.pop();
+                let result = apply_binary_operator(*operator, &a, &b, closure_context)?;
+                self.push(result);
+            }
+            JsInstruction::StrictEquals(match_equality) => {
+                let b = self.pop();
+                let a = self.pop();
+                let result = *match_equality == is_strictly_equal(&a, &b);
+                self.push(JsValue::bool_rc(&nodes_graph, result));
+            }
+            JsInstruction::LooseEquals(match_equality) => {
+                let b = self.pop();
+                let a = self.pop();
+                let result = *match_equality == is_loosely_equal(&a, &b);
+                self.push(JsValue::bool_rc(&nodes_graph, result));
+            }
+            JsInstruction::ToNumber => {
+                let value = self.pop();
+                let number = to_number(&value, closure_context)?;
+                self.push(JsValue::number_rc(&nodes_graph, number));
+            }
+            JsInstruction::Negate => {
+                let value = self.pop();
+                self.push(negate(&value, closure_context)?);
+            }
+            JsInstruction::BitwiseNot => {
+                let value = self.pop();
+                self.push(bitwise_not(&value, closure_context)?);
+            }
+
+            JsInstruction::NewObject => {
+                self.push(JsValue::object_rc(&nodes_graph, JsProperties::new()))
+            }
+            JsInstruction::DefineProperty => {
+                let value = self.pop();
+                let key = JsPropertyKey::from(self.pop().get_ref());
+                if let JsValue::Object(properties) = self.peek_mut().get_mut() {
+                    properties.insert(key, value);
+                }
+            }
+            JsInstruction::CopyProperties => {
+                let source = self.pop();
+                let entries = get_own_enumerable_entries(&source, closure_context)?;
+                if let JsValue::Object(properties) = self.peek_mut().get_mut() {
+                    for (key, value) in entries {
+                        properties.insert(key, value);
+                    }
+                }
+            }
+            JsInstruction::DefineMethod(function, kind, enumerable) => {
+                let key = JsPropertyKey::from(self.pop().get_ref());
+                let mut target = self.pop();
+                let descriptor =
+                    method_descriptor(*kind, chunk.nodes[*function].clone(), *enumerable);
+                if let JsValue::Object(properties) | JsValue::Function(_, properties) =
+                    target.get_mut()
+                {
+                    properties.define(&nodes_graph, key, descriptor);
+                }
+            }
+            JsInstruction::CreateClass(constructor) => {
+                let constructor = chunk.nodes[*constructor].clone();
+                let prototype = create_class_prototype(&constructor);
+                self.push(constructor);
+                self.push(prototype);
+            }
+            JsInstruction::NewArray => self.push(JsValue::array_rc(&nodes_graph, vec![])),
+            JsInstruction::ArrayPush => {
+                let value = self.pop();
+                if let JsValue::Array(elements, _properties) = self.peek_mut().get_mut() {
+                    elements.push(value);
+                }
+            }
+            JsInstruction::ArraySpread => {
+                let iterable = self.pop();
+                let values = collect_iterable(&iterable, closure_context)?;
+                if let JsValue::Array(elements, _properties) = self.peek_mut().get_mut() {
+                    elements.extend(values);
+                }
+            }
+
+            JsInstruction::CollectIterable => {
+                let iterable = self.pop();
+                let values = collect_iterable(&iterable, closure_context)?;
+                self.push(JsValue::array_rc(&nodes_graph, values));
+            }
+            JsInstruction::GetElement(index) => {
+                let element = match self.peek(0).get_ref() {
+                    JsValue::Array(elements, _properties) => elements.get(*index).cloned(),
+                    _ => None,
+                };
+                self.push(element.unwrap_or_else(|| JsValue::undefined_rc(&nodes_graph)));
+            }
+            JsInstruction::GetRestElements(index) => {
+                let elements = match self.peek(0).get_ref() {
+                    JsValue::Array(elements, _properties) => {
+                        elements.get(*index..).unwrap_or_default().to_vec()
+                    }
+                    _ => vec![],
+                };
+                self.push(JsValue::array_rc(&nodes_graph, elements));
+            }
+            JsInstruction::RequireObjectCoercible => {
+                if matches!(self.peek(0).get_ref(), JsValue::Undefined | JsValue::Null) {
+                    return Err(JsValue::type_error_rc(&nodes_graph));
+                }
+            }
+            JsInstruction::GetRestProperties(names) => {
+                let value = self.peek(0).clone();
+                let mut remaining = JsProperties::new();
+                for (key, member) in get_own_enumerable_entries(&value, closure_context)? {
+                    if !names
+                        .iter()
+                        .any(|property_name| key.as_str() == Some(name(property_name)))
+                    {
+                        remaining.insert(key, member);
+                    }
+                }
+                self.push(JsValue::object_rc(&nodes_graph, remaining));
+            }
+
+            JsInstruction::GetMember(member) => {
+                let base = self.pop();
+                self.push(access_member(&base, &name(member).into(), closure_context)?);
+            }
+            JsInstruction::GetComputedMember => {
+                let member = JsPropertyKey::from(self.pop().get_ref());
+                let base = self.pop();
+                self.push(access_member(&base, &member, closure_context)?);
+            }
+            JsInstruction::SetMember(member) => {
+                let mut base = self.pop();
+                let value = self.pop();
+                // Assignments to read-only properties are silently ignored
+                set_member(&mut base, &name(member).into(), value, closure_context)?;
+            }
+            JsInstruction::SetComputedMember => {
+                let member = JsPropertyKey::from(self.pop().get_ref());
+                let mut base = self.pop();
+                let value = self.pop();
+                set_member(&mut base, &member, value, closure_context)?;
+            }
+            JsInstruction::GetMethod(member) => {
+                let base = self.peek(0).clone();
+                let function =
+                    get_member_from_prototype_chain(&base, &name(member).into(), closure_context)?;
+                self.push(function);
+                self.require_function()?;
+            }
+            JsInstruction::GetComputedMethod => {
+                let member = JsPropertyKey::from(self.pop().get_ref());
+                let base = self.peek(0).clone();
+                let function = get_member_from_prototype_chain(&base, &member, closure_context)?;
+                self.push(function);
+                self.require_function()?;
+            }
+            JsInstruction::RequireFunction => self.require_function()?,
+
+            JsInstruction::Call(arg_count) => {
+                let args = self.stack.split_off(self.stack.len() - arg_count);
+                let function = self.pop();
+                let this = self.pop();
+                self.invoke(closure_context, &function, this, &args, None)?;
+            }
+            JsInstruction::CallEval(arg_count) => {
+                let args = self.stack.split_off(self.stack.len() - arg_count);
+                let function = self.pop();
+                let this = self.pop();
+                if function.is_same_ref(&closure_context.global_prototypes.eval) {
+                    let result = run_eval(closure_context, &args)?;
+                    self.push(result);
+                } else {
+                    self.invoke(closure_context, &function, this, &args, None)?;
+                }
+            }
+            JsInstruction::CallSpread => {
+                let args = self.pop_arguments();
+                let function = self.pop();
+                let this = self.pop();
+                self.invoke(closure_context, &function, this, &args, None)?;
+            }
+            JsInstruction::New(arg_count) => {
+                let args = self.stack.split_off(self.stack.len() - arg_count);
+                let constructor = self.pop();
+                self.construct(closure_context, &constructor, &args)?;
+            }
+            JsInstruction::NewSpread => {
+                let args = self.pop_arguments();
+                let constructor = self.pop();
+                self.construct(closure_context, &constructor, &args)?;
+            }
+            JsInstruction::Import => {
+                let specifier = self.pop();
+                let namespace = import_module(closure_context, &specifier)?;
+                self.push(namespace);
+            }
+
+            JsInstruction::Jump(target) => self.jump(*target),
+            JsInstruction::JumpIfFalse(target) => {
+                if !bool::from(self.pop().get_ref()) {
+                    self.jump(*target);
+                }
+            }
+            JsInstruction::JumpIfTrue(target) => {
+                if bool::from(self.pop().get_ref()) {
+                    self.jump(*target);
+                }
+            }
+            JsInstruction::JumpIfNotUndefined(target) => {
+                if !matches!(self.peek(0).get_ref(), JsValue::Undefined) {
+                    self.jump(*target);
+                }
+            }
+            JsInstruction::Return => {
+                let value = self.pop();
+                return Ok(self.return_from_frame(closure_context, value, base_depth));
+            }
+            JsInstruction::Throw => return Err(self.pop()),
+            JsInstruction::ThrowReferenceError => {
+                return Err(JsValue::reference_error_rc(&nodes_graph))
+            }
+            JsInstruction::ThrowSyntaxError => return Err(JsValue::syntax_error_rc(&nodes_graph)),
+            JsInstruction::PushHandler(target) => {
+                let stack_height = self.stack.len();
+                self.frame().handlers.push((*target, stack_height));
+            }
+            JsInstruction::PopHandler => {
+                self.frame().handlers.pop();
+            }
+
+            JsInstruction::RecordValue => {
+                let value = self.pop();
+                self.record(closure_context, JsStatementResult::Value(value));
+            }
+            JsInstruction::RecordVoid => self.record(closure_context, JsStatementResult::Void),
+            JsInstruction::RecordReturn => {
+                let value = self.pop();
+                self.record(closure_context, JsStatementResult::ReturnValue(value));
+            }
+            JsInstruction::RecordThrow => {
+                let value = self.pop();
+                self.record(closure_context, JsStatementResult::ThrowValue(value));
+            }
+            JsInstruction::RecordBreak(label) => {
+                let label = label.as_ref().map(|label| name(label).to_string());
+                self.record(closure_context, JsStatementResult::Break(label));
+            }
+        }
+        Ok(JsStep::Continue)
+    }
+
+    /// Records the result of a statement. Results are recorded after each top level statement of
+    /// a document, which is where garbage can be collected.
+    fn record(&mut self, closure_context: &mut JsClosureContext, result: JsStatementResult) {
+        closure_context.record_new_result(result);
+        if let [frame] = self.frames.as_slice() {
+            if frame.is_document {
+                let roots = || [self.stack.clone(), frame.chunk.get_referenced_nodes()].concat();
+                closure_context.collect_garbage_near_limit(roots);
+            }
+        }
+    }
+
+    /// Invokes a function. User defined functions get a frame, which is left to be run, while
+    /// native functions run immediately and have their result pushed.
+    fn invoke(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        function: &JsValueNode,
+        this: JsValueNode,
+        args: &[JsValueNode],
+        constructing: Option<JsValueNode>,
+    ) -> Result<(), JsValueNode> {
+        let implementation = match function.get_ref() {
+            JsValue::Function(implementation, _properties) => implementation,
+            // Proxies run their `apply` trap, which may call back into a new VM
+            JsValue::Proxy(_) => {
+                let result = invoke_function(closure_context, function, this, args)?;
+                self.push(result);
+                return Ok(());
+            }
+            _ => return Err(JsValue::type_error_rc(&closure_context.nodes_graph)),
+        };
+        match implementation {
+            JsFunction::Native(_, _) => {
+                let result = implementation.run(closure_context, this, args)?;
+                self.push(result);
+            }
+            JsFunction::UserDefined(_source, _name, params, _implementation) => {
+                if closure_context.get_closure_depth() > closure_context.limits.max_call_depth {
+                    return Err(JsValue::stack_overflow_error_rc(
+                        &closure_context.nodes_graph,
+                    ));
+                }
+                let chunk = closure_context.get_compiled_function(function);
+                let module = enter_module_scope(closure_context, function);
+                closure_context.push_closure();
+                declare_function_scope(closure_context, this, args, params);
+                self.frames.push(JsFrame {
+                    chunk,
+                    ip: 0,
+                    stack_base: self.stack.len(),
+                    handlers: vec![],
+                    constructing,
+                    has_scope: true,
+                    module,
+                    is_document: false,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Invokes a function with `new`. User defined functions get a frame, which results in the
+    /// constructed object unless it returns some other object.
+    fn construct(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        constructor: &JsValueNode,
+        args: &[JsValueNode],
+    ) -> Result<(), JsValueNode> {
+        match constructor.get_ref() {
+            JsValue::Function(JsFunction::UserDefined(..), _properties) => {
+                let this = create_constructed_object(closure_context, constructor)?;
+                self.invoke(closure_context, constructor, this.clone(), args, Some(this))
+            }
+            _ => {
+                let result = construct(closure_context, constructor, args)?;
+                self.push(result);
+                Ok(())
+            }
+        }
+    }
+
+    /// Leaves the frame being run, passing its result to the frame below
+    fn return_from_frame(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: JsValueNode,
+        base_depth: usize,
+    ) -> JsStep {
+        let frame = self.leave_frame(closure_context);
+        let value = match frame.constructing {
+            Some(this) if !value.get_ref().is_object() => this,
+            _ => value,
+        };
+        if self.frames.len() == base_depth {
+            JsStep::Exit(value)
+        } else {
+            self.push(value);
+            JsStep::Continue
+        }
+    }
+
+    /// Passes a thrown value to the innermost handler, leaving frames without one. Values thrown
+    /// out of the frame the VM was entered with are returned, as are terminations, which are never
+    /// handled.
+    fn throw(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: JsValueNode,
+        base_depth: usize,
+    ) -> Result<(), JsValueNode> {
+        loop {
+            let is_terminating = closure_context.is_terminating();
+            let frame = self.frame();
+            if let Some((target, stack_height)) =
+                frame.handlers.pop().filter(|_handler| !is_terminating)
+            {
+                frame.ip = target;
+                self.stack.truncate(stack_height);
+                self.push(value);
+                return Ok(());
+            }
+            self.leave_frame(closure_context);
+            if self.frames.len() == base_depth {
+                return Err(value);
+            }
+        }
+    }
+
+    fn leave_frame(&mut self, closure_context: &mut JsClosureContext) -> JsFrame {
+        let frame = self.frames.pop().expect("A frame is being run");
+        self.stack.truncate(frame.stack_base);
+        if frame.has_scope {
+            closure_context.pop_closure();
+        }
+        leave_module_scope(closure_context, frame.module.clone());
+        frame
+    }
+
+    fn frame(&mut self) -> &mut JsFrame {
+        self.frames.last_mut().expect("A frame is being run")
+    }
+
+    fn jump(&mut self, target: usize) {
+        self.frame().ip = target;
+    }
+
+    fn push(&mut self, value: JsValueNode) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> JsValueNode {
+        self.stack
+            .pop()
+            .expect("Instructions do not pop more than they push")
+    }
+
+    /// Gets the value `depth` below the top of the stack
+    fn peek(&self, depth: usize) -> &JsValueNode {
+        &self.stack[self.stack.len() - 1 - depth]
+    }
+
+    fn peek_mut(&mut self) -> &mut JsValueNode {
+        self.stack
+            .last_mut()
+            .expect("Instructions do not pop more than they push")
+    }
+
+    /// Pops an array of arguments built from spread elements
+    fn pop_arguments(&mut self) -> Vec<JsValueNode> {
+        match self.pop().get_ref() {
+            JsValue::Array(elements, _properties) => elements.clone(),
+            _ => vec![],
+        }
+    }
+
+    fn require_function(&self) -> Result<(), JsValueNode> {
+        match self.peek(0).get_ref() {
+            value if value.is_callable() => Ok(()),
+            _ => Err(JsValue::type_error_rc(&self.peek(0).get_node_graph())),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use wowser_macros::js_class;
+
+use super::{
+    add_animation_frame_globals, add_navigation_globals, add_performance_global,
+    add_storage_globals, build_event_target_methods, get_history_object, get_local_storage_object,
+    get_location_object, get_performance_object, get_session_storage_object, navigate,
+    resolve_navigation_url, JsClosureContext, JsValueNode,
+};
+
+/// Exposes the window of the tab to scripts as `window`, along with its event target methods,
+/// storage, `location`, `history`, `performance` and animation frame methods as globals
+pub fn add_window_global(closure_context: &mut JsClosureContext) {
+    let event_target = closure_context.global_prototypes.event_target.clone();
+    closure_context
+        .global_prototypes
+        .register_host_class_extending::<JsWindow>(event_target);
+    let window = closure_context.create_host_object(JsWindow);
+    closure_context.event_targets.window = Some(window.clone());
+
+    let methods = build_event_target_methods(&closure_context.nodes_graph);
+    let global_closure = closure_context.get_lastest_closure();
+    global_closure.get_or_declare_reference_mut("window").value = window;
+    for (name, method) in methods {
+        global_closure
+            .get_or_declare_reference_mut(&name.to_string())
+            .value = method;
+    }
+    add_storage_globals(closure_context);
+    add_navigation_globals(closure_context);
+    add_performance_global(closure_context);
+    add_animation_frame_globals(closure_context);
+}
+
+/// The window of the tab the document is shown in
+///
+/// <https://developer.mozilla.org/en-US/docs/Web/API/Window>
+pub struct JsWindow;
+
+#[js_class(name = "Window")]
+impl JsWindow {
+    #[js_method(getter)]
+    fn window(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        closure_context.event_targets.window.clone()
+    }
+
+    #[js_method(getter)]
+    fn document(&self, closure_context: &mut JsClosureContext) -> Option<JsValueNode> {
+        closure_context.event_targets.document.clone()
+    }
+
+    #[js_method(getter)]
+    fn local_storage(
+        &self,
+        closure_context: &mut JsClosureContext,
+    ) -> Result<JsValueNode, JsValueNode> {
+        get_local_storage_object(closure_context)
+    }
+
+    #[js_method(getter)]
+    fn session_storage(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        get_session_storage_object(closure_context)
+    }
+
+    #[js_method(getter)]
+    fn location(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        get_location_object(closure_context)
+    }
+
+    /// Navigates to the URL, as setting `location.href` does
+    #[js_method(setter)]
+    fn set_location(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        let url = resolve_navigation_url(closure_context, &value)?;
+        navigate(closure_context, url, false);
+        Ok(())
+    }
+
+    #[js_method(getter)]
+    fn history(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        get_history_object(closure_context)
+    }
+
+    #[js_method(getter)]
+    fn performance(&self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        get_performance_object(closure_context)
+    }
+}
@@ -0,0 +1,774 @@
+use std::collections::HashMap;
+
+use wowser_macros::js_class;
+
+use crate::{
+    net::{HttpHeaderMap, HttpResponse, HttpVerb, Url, UrlProtocol},
+    util::parse_json,
+};
+
+use super::{
+    build_function_entry, build_host_constructor_with_references, extract_body, get_argument,
+    invoke_function, json_to_js_value, resolve_url, send_fetch_request, with_host_object,
+    FromJsValue, JsClosureContext, JsEvent, JsEventDetail, JsFetchRequest, JsFetchTarget,
+    JsFunctionResult, JsHeaders, JsHeadersGuard, JsHostClass, JsHostReferences, JsProperty,
+    JsValue, JsValueNode,
+};
+
+/// The states of a request, which are also constants of `XMLHttpRequest`
+/// <https://xhr.spec.whatwg.org/#states>
+const UNSENT: u32 = 0;
+const OPENED: u32 = 1;
+const HEADERS_RECEIVED: u32 = 2;
+const LOADING: u32 = 3;
+const DONE: u32 = 4;
+
+const STATES: [(&str, u32); 5] = [
+    ("UNSENT", UNSENT),
+    ("OPENED", OPENED),
+    ("HEADERS_RECEIVED", HEADERS_RECEIVED),
+    ("LOADING", LOADING),
+    ("DONE", DONE),
+];
+
+/// What `response` reads the body as
+/// <https://xhr.spec.whatwg.org/#the-responsetype-attribute>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum JsXmlHttpResponseType {
+    /// The default, which is text
+    #[default]
+    Empty,
+    Text,
+    Json,
+    ArrayBuffer,
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Web/API/XMLHttpRequest>
+///
+/// Requests are sent with the same origin and CORS checks as `fetch`. Events only call the handlers
+/// set with their `on` properties, such as `onload`, and `open`, `send`, and `abort` are added to
+/// the prototype by [build_xml_http_request_constructor] as they call them.
+#[derive(Debug)]
+pub struct JsXmlHttpRequest {
+    ready_state: u32,
+    method: HttpVerb,
+    url: Option<Url>,
+    /// Whether `send` returns before the response is received
+    asynchronous: bool,
+    request_headers: JsHeaders,
+    /// Whether an asynchronous request is waiting on the network
+    sent: bool,
+    /// Counts the requests opened, so the responses to requests that were aborted or replaced by
+    /// opening another are ignored
+    generation: u64,
+    response_type: JsXmlHttpResponseType,
+    /// `None` until the response is received, and when the request failed
+    response: Option<HttpResponse>,
+    /// The `response` read as JSON or an `ArrayBuffer`, created when first read so scripts always
+    /// get the same value
+    response_object: Option<JsValueNode>,
+    /// The functions set with the `on` properties by the type of event they handle
+    handlers: HashMap<&'static str, JsValueNode>,
+}
+
+impl JsXmlHttpRequest {
+    /// `new XMLHttpRequest()`
+    pub fn construct(
+        _closure_context: &mut JsClosureContext,
+        _args: &[JsValueNode],
+    ) -> Result<Self, JsValueNode> {
+        Ok(Self {
+            ready_state: UNSENT,
+            method: HttpVerb::Get,
+            url: None,
+            asynchronous: true,
+            request_headers: JsHeaders::new(HttpHeaderMap::default(), JsHeadersGuard::Request),
+            sent: false,
+            generation: 0,
+            response_type: JsXmlHttpResponseType::Empty,
+            response: None,
+            response_object: None,
+            handlers: HashMap::new(),
+        })
+    }
+
+    /// Starts a new request, forgetting the request and response before
+    fn open(&mut self, method: HttpVerb, url: Url, asynchronous: bool) {
+        self.ready_state = OPENED;
+        self.method = method;
+        self.url = Some(url);
+        self.asynchronous = asynchronous;
+        self.request_headers = JsHeaders::new(HttpHeaderMap::default(), JsHeadersGuard::Request);
+        self.sent = false;
+        self.generation += 1;
+        self.response = None;
+        self.response_object = None;
+    }
+
+    /// The request to send with the body, throwing an `InvalidStateError` when the request was not
+    /// opened or was already sent. Requests with a `GET` or `HEAD` method ignore the body.
+    fn get_request(
+        &mut self,
+        closure_context: &JsClosureContext,
+        body: Option<Vec<u8>>,
+        content_type: Option<&str>,
+    ) -> Result<JsFetchRequest, JsValueNode> {
+        let url = match &self.url {
+            Some(url) if self.ready_state == OPENED && !self.sent => url.clone(),
+            _ => {
+                return Err(JsValue::invalid_state_error_rc(
+                    &closure_context.nodes_graph,
+                ))
+            }
+        };
+        let mut headers = self.request_headers.headers.clone();
+        let body = match body {
+            Some(body) if !matches!(self.method, HttpVerb::Get | HttpVerb::Head) => {
+                if let Some(content_type) = content_type {
+                    if !headers.contains("content-type") {
+                        headers.append("content-type", content_type);
+                    }
+                }
+                body
+            }
+            _ => vec![],
+        };
+        Ok(JsFetchRequest::new(
+            &closure_context.fetches,
+            url,
+            self.method,
+            headers,
+            body,
+        ))
+    }
+
+    /// Moves on to the state while the request is still the one sent, resulting in whether it is.
+    /// Handlers can abort the request or open another between states.
+    fn advance(&mut self, generation: u64, state: u32) -> bool {
+        if !self.sent || self.generation != generation {
+            return false;
+        }
+        self.ready_state = state;
+        self.sent = state != DONE;
+        true
+    }
+
+    /// Stops the request, resulting in whether it was being sent, in which case it is done
+    fn abort(&mut self) -> bool {
+        let sending = matches!(self.ready_state, HEADERS_RECEIVED | LOADING)
+            || (self.ready_state == OPENED && self.sent);
+        if sending {
+            self.ready_state = DONE;
+        }
+        self.sent = false;
+        self.generation += 1;
+        self.response = None;
+        self.response_object = None;
+        sending
+    }
+
+    /// How much of the body was received, out of the length the response says it has
+    fn get_progress(&self) -> JsEventDetail {
+        let Some(response) = &self.response else {
+            return JsEventDetail::Progress {
+                loaded: 0,
+                total: None,
+            };
+        };
+        let total = response
+            .headers
+            .get_combined("content-length")
+            .and_then(|length| length.trim().parse().ok());
+        JsEventDetail::Progress {
+            loaded: response.body.len() as u64,
+            total,
+        }
+    }
+
+    fn get_text(&self) -> String {
+        match &self.response {
+            Some(response) if matches!(self.ready_state, LOADING | DONE) => {
+                String::from_utf8_lossy(&response.body).into_owned()
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn get_handler(&self, event_type: &str) -> Option<JsValueNode> {
+        self.handlers.get(event_type).cloned()
+    }
+
+    /// Sets the handler of the event, where values that are not objects remove it
+    fn set_handler(&mut self, event_type: &'static str, handler: JsValueNode) {
+        if handler.get_ref().is_object() {
+            self.handlers.insert(event_type, handler);
+        } else {
+            self.handlers.remove(event_type);
+        }
+    }
+}
+
+impl JsHostReferences for JsXmlHttpRequest {
+    fn get_referenced_nodes(&self) -> Vec<JsValueNode> {
+        self.handlers
+            .values()
+            .cloned()
+            .chain(self.response_object.clone())
+            .collect()
+    }
+}
+
+#[js_class(name = "XMLHttpRequest")]
+impl JsXmlHttpRequest {
+    #[js_method(getter)]
+    fn ready_state(&self) -> u32 {
+        self.ready_state
+    }
+
+    /// Adds a header to the request, throwing an `InvalidStateError` when the request was not
+    /// opened or was already sent. Forbidden request headers are ignored.
+    #[js_method]
+    fn set_request_header(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        name: String,
+        value: String,
+    ) -> Result<(), JsValueNode> {
+        if self.ready_state != OPENED || self.sent {
+            return Err(JsValue::invalid_state_error_rc(
+                &closure_context.nodes_graph,
+            ));
+        }
+        self.request_headers.append(closure_context, name, value)
+    }
+
+    /// The status of the response, which is 0 until it is received and when the request failed
+    #[js_method(getter)]
+    fn status(&self) -> u32 {
+        self.response
+            .as_ref()
+            .map_or(0, |response| response.status.status_code.into())
+    }
+
+    #[js_method(getter)]
+    fn status_text(&self) -> String {
+        self.response
+            .as_ref()
+            .map(|response| response.status.reason_phrase.clone())
+            .unwrap_or_default()
+    }
+
+    #[js_method(getter, name = "responseURL")]
+    fn response_url(&self) -> String {
+        self.response
+            .as_ref()
+            .and(self.url.as_ref())
+            .map(|url| url.to_record().to_string())
+            .unwrap_or_default()
+    }
+
+    /// The values of the response headers with the name joined by `, `, or `null` when there are
+    /// none. Cross-origin responses only have the headers CORS exposes.
+    #[js_method]
+    fn get_response_header(&self, name: String) -> Option<String> {
+        self.response
+            .as_ref()
+            .and_then(|response| response.headers.get_combined(&name.to_ascii_lowercase()))
+    }
+
+    /// The response headers as `name: value` lines sorted by name
+    #[js_method]
+    fn get_all_response_headers(&self) -> String {
+        let Some(response) = &self.response else {
+            return String::new();
+        };
+        let mut names: Vec<&str> = response
+            .headers
+            .iter()
+            .map(|header| header.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.headers.get_combined(name)?;
+                Some(format!("{name}: {value}\r\n"))
+            })
+            .collect()
+    }
+
+    #[js_method(getter)]
+    fn response_type(&self) -> &'static str {
+        match self.response_type {
+            JsXmlHttpResponseType::Empty => "",
+            JsXmlHttpResponseType::Text => "text",
+            JsXmlHttpResponseType::Json => "json",
+            JsXmlHttpResponseType::ArrayBuffer => "arraybuffer",
+        }
+    }
+
+    /// Sets what `response` reads the body as, throwing an `InvalidStateError` once the body is
+    /// being received. Types that are not supported are ignored.
+    #[js_method(setter)]
+    fn set_response_type(
+        &mut self,
+        closure_context: &mut JsClosureContext,
+        response_type: String,
+    ) -> Result<(), JsValueNode> {
+        if matches!(self.ready_state, LOADING | DONE) {
+            return Err(JsValue::invalid_state_error_rc(
+                &closure_context.nodes_graph,
+            ));
+        }
+        self.response_type = match response_type.as_str() {
+            "" => JsXmlHttpResponseType::Empty,
+            "text" => JsXmlHttpResponseType::Text,
+            "json" => JsXmlHttpResponseType::Json,
+            "arraybuffer" => JsXmlHttpResponseType::ArrayBuffer,
+            _ => return Ok(()),
+        };
+        Ok(())
+    }
+
+    /// The body received so far, throwing an `InvalidStateError` when it is not read as text
+    #[js_method(getter)]
+    fn response_text(&self, closure_context: &mut JsClosureContext) -> Result<String, JsValueNode> {
+        match self.response_type {
+            JsXmlHttpResponseType::Empty | JsXmlHttpResponseType::Text => Ok(self.get_text()),
+            _ => Err(JsValue::invalid_state_error_rc(
+                &closure_context.nodes_graph,
+            )),
+        }
+    }
+
+    /// The body read as the `responseType`. JSON and `ArrayBuffer` responses are `null` until the
+    /// request is done, and JSON that is not valid is `null`.
+    /// <https://xhr.spec.whatwg.org/#the-response-attribute>
+    #[js_method(getter)]
+    fn response(&mut self, closure_context: &mut JsClosureContext) -> JsValueNode {
+        let nodes_graph = &closure_context.nodes_graph;
+        if matches!(
+            self.response_type,
+            JsXmlHttpResponseType::Empty | JsXmlHttpResponseType::Text
+        ) {
+            return JsValue::string_rc(nodes_graph, self.get_text());
+        }
+        if let Some(object) = &self.response_object {
+            return object.clone();
+        }
+        let response = match &self.response {
+            Some(response) if self.ready_state == DONE => response,
+            _ => return JsValue::null_rc(nodes_graph),
+        };
+        let object = if self.response_type == JsXmlHttpResponseType::Json {
+            match parse_json(&String::from_utf8_lossy(&response.body)) {
+                Ok(json) => json_to_js_value(closure_context, json),
+                Err(_) => JsValue::null_rc(nodes_graph),
+            }
+        } else {
+            JsValue::array_buffer_rc(nodes_graph, response.body.clone())
+        };
+        self.response_object = Some(object.clone());
+        object
+    }
+
+    #[js_method(getter)]
+    fn onreadystatechange(&self) -> Option<JsValueNode> {
+        self.get_handler("readystatechange")
+    }
+
+    #[js_method(setter)]
+    fn set_onreadystatechange(&mut self, handler: JsValueNode) {
+        self.set_handler("readystatechange", handler);
+    }
+
+    #[js_method(getter)]
+    fn onloadstart(&self) -> Option<JsValueNode> {
+        self.get_handler("loadstart")
+    }
+
+    #[js_method(setter)]
+    fn set_onloadstart(&mut self, handler: JsValueNode) {
+        self.set_handler("loadstart", handler);
+    }
+
+    #[js_method(getter)]
+    fn onprogress(&self) -> Option<JsValueNode> {
+        self.get_handler("progress")
+    }
+
+    #[js_method(setter)]
+    fn set_onprogress(&mut self, handler: JsValueNode) {
+        self.set_handler("progress", handler);
+    }
+
+    #[js_method(getter)]
+    fn onload(&self) -> Option<JsValueNode> {
+        self.get_handler("load")
+    }
+
+    #[js_method(setter)]
+    fn set_onload(&mut self, handler: JsValueNode) {
+        self.set_handler("load", handler);
+    }
+
+    #[js_method(getter)]
+    fn onerror(&self) -> Option<JsValueNode> {
+        self.get_handler("error")
+    }
+
+    #[js_method(setter)]
+    fn set_onerror(&mut self, handler: JsValueNode) {
+        self.set_handler("error", handler);
+    }
+
+    #[js_method(getter)]
+    fn onabort(&self) -> Option<JsValueNode> {
+        self.get_handler("abort")
+    }
+
+    #[js_method(setter)]
+    fn set_onabort(&mut self, handler: JsValueNode) {
+        self.set_handler("abort", handler);
+    }
+
+    #[js_method(getter)]
+    fn onloadend(&self) -> Option<JsValueNode> {
+        self.get_handler("loadend")
+    }
+
+    #[js_method(setter)]
+    fn set_onloadend(&mut self, handler: JsValueNode) {
+        self.set_handler("loadend", handler);
+    }
+}
+
+/// `XMLHttpRequest`, whose prototype has `open`, `send`, and `abort` along with the methods of
+/// [JsXmlHttpRequest]. They are native functions rather than methods as the handlers they call
+/// use the request too. The constructor and the prototype have the states as constants.
+pub fn build_xml_http_request_constructor(closure_context: &mut JsClosureContext) -> JsValueNode {
+    let mut constructor =
+        build_host_constructor_with_references(closure_context, JsXmlHttpRequest::construct);
+    let mut prototype =
+        closure_context.global_prototypes.host_classes[JsXmlHttpRequest::NAME].clone();
+    let node_graph = closure_context.nodes_graph.clone();
+    let methods = [
+        build_function_entry(&node_graph, "open".into(), xml_http_request_open),
+        build_function_entry(&node_graph, "send".into(), xml_http_request_send),
+        build_function_entry(&node_graph, "abort".into(), xml_http_request_abort),
+    ];
+    if let JsValue::Object(properties) = prototype.get_mut() {
+        for (key, method) in methods {
+            properties.insert_property(key, JsProperty::with_attributes(method, true, false, true));
+        }
+    }
+    for object in [&mut constructor, &mut prototype] {
+        if let JsValue::Function(_, properties) | JsValue::Object(properties) = object.get_mut() {
+            for (name, state) in STATES {
+                let state = JsValue::number_rc(&node_graph, f64::from(state));
+                properties.insert_property(
+                    name.into(),
+                    JsProperty::with_attributes(state, false, true, false),
+                );
+            }
+        }
+    }
+    constructor
+}
+
+/// `open(method, url, async)` starts a new request to the URL resolved against the URL of the
+/// document, aborting the request sent before. Methods and URLs that are not valid throw a
+/// `SyntaxError`.
+fn xml_http_request_open(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let method = String::from_js_value(&get_argument(closure_context, args, 0), closure_context)?;
+    let url = String::from_js_value(&get_argument(closure_context, args, 1), closure_context)?;
+    let asynchronous = match args.get(2) {
+        Some(asynchronous) => bool::from_js_value(asynchronous, closure_context)?,
+        None => true,
+    };
+    let method = HttpVerb::parse(&method)
+        .ok_or_else(|| JsValue::syntax_error_rc(&closure_context.nodes_graph))?;
+    let url = resolve_url(closure_context, &url)
+        .map_err(|_| JsValue::syntax_error_rc(&closure_context.nodes_graph))?;
+    with_host_object(
+        closure_context,
+        &this,
+        |request: &mut JsXmlHttpRequest, _| request.open(method, url, asynchronous),
+    )?;
+    fire_event(
+        closure_context,
+        &this,
+        "readystatechange",
+        JsEventDetail::None,
+    )?;
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// `send(body)` sends the request opened, with a body that is a buffer source or a string.
+/// Asynchronous requests finish once the response is received, see [finish_xml_http_request].
+/// Synchronous requests wait for the response, throwing a `NetworkError` when the request fails.
+/// Requests can only be sent on HTTP.
+/// <https://xhr.spec.whatwg.org/#the-send()-method>
+fn xml_http_request_send(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    args: &[JsValueNode],
+) -> JsFunctionResult {
+    let body = get_argument(closure_context, args, 0);
+    let (body, content_type) = extract_body(closure_context, &body)?;
+    let (request, asynchronous, generation) = with_host_object(
+        closure_context,
+        &this,
+        |xml_http_request: &mut JsXmlHttpRequest, closure_context| -> Result<_, JsValueNode> {
+            let request = xml_http_request.get_request(closure_context, body, content_type)?;
+            xml_http_request.sent = xml_http_request.asynchronous;
+            Ok((
+                request,
+                xml_http_request.asynchronous,
+                xml_http_request.generation,
+            ))
+        },
+    )??;
+    let undefined = JsValue::undefined_rc(&closure_context.nodes_graph);
+    let http = request.url.protocol == UrlProtocol::Http;
+
+    if asynchronous {
+        fire_event(closure_context, &this, "loadstart", JsEventDetail::None)?;
+        let target = JsFetchTarget::XmlHttpRequest(this.clone(), generation);
+        if !http || send_fetch_request(closure_context, request, target).is_err() {
+            finish_xml_http_request(closure_context, &this, generation, None);
+        }
+        return Ok(undefined);
+    }
+
+    let response = if http { request.send_blocking() } else { None };
+    let received = response.is_some();
+    with_host_object(
+        closure_context,
+        &this,
+        |request: &mut JsXmlHttpRequest, _| {
+            request.ready_state = DONE;
+            request.response = response;
+        },
+    )?;
+    if !received {
+        return Err(JsValue::network_error_rc(&closure_context.nodes_graph));
+    }
+    fire_event(
+        closure_context,
+        &this,
+        "readystatechange",
+        JsEventDetail::None,
+    )?;
+    for event_type in ["load", "loadend"] {
+        let progress = with_host_object(
+            closure_context,
+            &this,
+            |request: &mut JsXmlHttpRequest, _| request.get_progress(),
+        )?;
+        fire_event(closure_context, &this, event_type, progress)?;
+    }
+    Ok(undefined)
+}
+
+/// `abort()` stops the request, which is done with an `abort` event when it was being sent
+fn xml_http_request_abort(
+    closure_context: &mut JsClosureContext,
+    this: JsValueNode,
+    _args: &[JsValueNode],
+) -> JsFunctionResult {
+    let aborted = with_host_object(
+        closure_context,
+        &this,
+        |request: &mut JsXmlHttpRequest, _| request.abort(),
+    )?;
+    if aborted {
+        fire_event(
+            closure_context,
+            &this,
+            "readystatechange",
+            JsEventDetail::None,
+        )?;
+        for event_type in ["abort", "loadend"] {
+            let progress = JsEventDetail::Progress {
+                loaded: 0,
+                total: None,
+            };
+            fire_event(closure_context, &this, event_type, progress)?;
+        }
+    }
+    with_host_object(
+        closure_context,
+        &this,
+        |request: &mut JsXmlHttpRequest, _| {
+            if request.ready_state == DONE {
+                request.ready_state = UNSENT;
+                request.response = None;
+            }
+        },
+    )?;
+    Ok(JsValue::undefined_rc(&closure_context.nodes_graph))
+}
+
+/// Finishes an asynchronous request with its response, or a network error when it is `None`.
+/// Responses go through the headers received and loading states to done, with a `load` event,
+/// and errors go straight to done with an `error` event. Responses to requests that are no longer
+/// the one sent are ignored.
+pub fn finish_xml_http_request(
+    closure_context: &mut JsClosureContext,
+    request: &JsValueNode,
+    generation: u64,
+    response: Option<HttpResponse>,
+) {
+    let result = match response {
+        Some(response) => receive_response(closure_context, request, generation, response),
+        None => fail_request(closure_context, request, generation),
+    };
+    if let Err(value) = result {
+        log!(ERROR["JS"]: "XMLHttpRequest failed to finish", value.get_ref().to_string());
+    }
+}
+
+fn receive_response(
+    closure_context: &mut JsClosureContext,
+    request: &JsValueNode,
+    generation: u64,
+    response: HttpResponse,
+) -> Result<(), JsValueNode> {
+    let received = with_host_object(
+        closure_context,
+        request,
+        |xml_http_request: &mut JsXmlHttpRequest, _| {
+            let current = xml_http_request.advance(generation, HEADERS_RECEIVED);
+            if current {
+                xml_http_request.response = Some(response);
+            }
+            current
+        },
+    )?;
+    if !received {
+        return Ok(());
+    }
+    fire_event(
+        closure_context,
+        request,
+        "readystatechange",
+        JsEventDetail::None,
+    )?;
+
+    for (state, event_types) in [
+        (LOADING, &["readystatechange", "progress"][..]),
+        (DONE, &["readystatechange", "load", "loadend"][..]),
+    ] {
+        let progress = with_host_object(
+            closure_context,
+            request,
+            |xml_http_request: &mut JsXmlHttpRequest, _| {
+                xml_http_request
+                    .advance(generation, state)
+                    .then(|| xml_http_request.get_progress())
+            },
+        )?;
+        let Some(progress) = progress else {
+            return Ok(());
+        };
+        for event_type in event_types {
+            let detail = match *event_type {
+                "readystatechange" => JsEventDetail::None,
+                _ => progress.clone(),
+            };
+            fire_event(closure_context, request, event_type, detail)?;
+        }
+    }
+    Ok(())
+}
+
+fn fail_request(
+    closure_context: &mut JsClosureContext,
+    request: &JsValueNode,
+    generation: u64,
+) -> Result<(), JsValueNode> {
+    let failed = with_host_object(
+        closure_context,
+        request,
+        |xml_http_request: &mut JsXmlHttpRequest, _| {
+            xml_http_request.response = None;
+            xml_http_request.advance(generation, DONE)
+        },
+    )?;
+    if !failed {
+        return Ok(());
+    }
+    fire_event(
+        closure_context,
+        request,
+        "readystatechange",
+        JsEventDetail::None,
+    )?;
+    for event_type in ["error", "loadend"] {
+        let progress = JsEventDetail::Progress {
+            loaded: 0,
+            total: None,
+        };
+        fire_event(closure_context, request, event_type, progress)?;
+    }
+    Ok(())
+}
+
+/// Calls the handler set for the event with an `Event` whose target is `null`. Exceptions thrown by
+/// the handler are reported, and only termination is thrown.
+fn fire_event(
+    closure_context: &mut JsClosureContext,
+    request: &JsValueNode,
+    event_type: &str,
+    detail: JsEventDetail,
+) -> Result<(), JsValueNode> {
+    let handler = with_host_object(
+        closure_context,
+        request,
+        |xml_http_request: &mut JsXmlHttpRequest, _| xml_http_request.get_handler(event_type),
+    )?;
+    let Some(handler) = handler.filter(|handler| handler.get_ref().is_callable()) else {
+        return Ok(());
+    };
+    let event = closure_context.create_host_object(JsEvent::new(event_type, false, false, detail));
+    if let Err(value) = invoke_function(closure_context, &handler, request.clone(), &[event]) {
+        if closure_context.is_terminating() {
+            return Err(value);
+        }
+        log!(ERROR["JS"]: "Uncaught exception in", event_type, "handler", value.get_ref().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{js::JsDocument, net::HttpStatus};
+
+    use super::*;
+
+    #[test]
+    fn test_response_url_without_port() {
+        let mut js_document = JsDocument::new(vec![]);
+        let mut xhr =
+            JsXmlHttpRequest::construct(&mut js_document.global_closure_context, &[]).unwrap();
+        xhr.url = Url::parse("http://a.com/x?y");
+        assert_eq!(xhr.response_url(), "");
+
+        xhr.response = Some(HttpResponse {
+            status: HttpStatus {
+                http_version: "HTTP/1.1".to_string(),
+                status_code: 200,
+                reason_phrase: "OK".to_string(),
+            },
+            headers: HttpHeaderMap::default(),
+            body: vec![],
+        });
+        assert_eq!(xhr.response_url(), "http://a.com/x?y");
+    }
+}
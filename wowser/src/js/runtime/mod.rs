@@ -1,24 +1,98 @@
 mod globals;
+mod js_animation_frame;
+mod js_bigint;
+mod js_bytecode;
+mod js_class;
 mod js_closure;
 mod js_closure_context;
+mod js_collection;
+mod js_compiler;
+mod js_date;
 mod js_document;
+mod js_dom;
+mod js_eval;
+mod js_event;
 mod js_expression;
+mod js_fetch;
 mod js_function;
+mod js_headers;
+mod js_history;
+mod js_host;
+mod js_internal_methods;
+mod js_iteration;
+mod js_limits;
+mod js_location;
+mod js_module;
+mod js_object;
+mod js_pattern;
+mod js_performance;
+mod js_promise;
+mod js_property;
+mod js_property_key;
 mod js_prototypes;
+mod js_proxy;
 mod js_reference;
+mod js_reflect;
+mod js_regexp;
 mod js_statement;
 mod js_statement_result;
+mod js_storage;
+mod js_structured_clone;
+mod js_symbol;
+mod js_text_encoding;
+mod js_typed_array;
+mod js_url;
 mod js_value;
 mod js_value_graph;
+mod js_vm;
+mod js_window;
+mod js_xml_http_request;
 
+pub use js_animation_frame::*;
+pub use js_bigint::*;
+pub use js_bytecode::*;
+pub use js_class::*;
 pub use js_closure::*;
 pub use js_closure_context::*;
+pub use js_collection::*;
+pub use js_compiler::*;
+pub use js_date::*;
 pub use js_document::*;
+pub use js_dom::*;
+pub use js_eval::*;
+pub use js_event::*;
 pub use js_expression::*;
+pub use js_fetch::*;
 pub use js_function::*;
+pub use js_headers::*;
+pub use js_history::*;
+pub use js_host::*;
+pub use js_internal_methods::*;
+pub use js_iteration::*;
+pub use js_limits::*;
+pub use js_location::*;
+pub use js_module::*;
+pub use js_object::*;
+pub use js_pattern::*;
+pub use js_performance::*;
+pub use js_promise::*;
+pub use js_property::*;
+pub use js_property_key::*;
 pub use js_prototypes::*;
+pub use js_proxy::*;
 pub use js_reference::*;
+pub use js_reflect::*;
+pub use js_regexp::*;
 pub use js_statement::*;
 pub use js_statement_result::*;
+pub use js_storage::*;
+pub use js_structured_clone::*;
+pub use js_symbol::*;
+pub use js_text_encoding::*;
+pub use js_typed_array::*;
+pub use js_url::*;
 pub use js_value::*;
 pub use js_value_graph::*;
+pub use js_vm::*;
+pub use js_window::*;
+pub use js_xml_http_request::*;
@@ -1,6 +1,8 @@
 use super::HttpHeader;
 
-#[derive(Debug, PartialEq, Default)]
+/// The headers of a request or response in order. Names are compared as they are, so they are kept
+/// in lower case, as parsed headers are.
+#[derive(Clone, Debug, PartialEq, Default)]
 pub struct HttpHeaderMap {
     headers: Vec<HttpHeader>,
 }
@@ -43,6 +45,59 @@ impl HttpHeaderMap {
                 .map(|header| header.value.clone())
         }
     }
+
+    /// Gets the values of every header with the name joined by `, `, which is how fetch combines
+    /// them
+    /// <https://fetch.spec.whatwg.org/#concept-header-list-get>
+    pub fn get_combined(&self, header_name: &str) -> Option<String> {
+        let values: Vec<&str> = self
+            .headers
+            .iter()
+            .filter(|header| header.name == header_name)
+            .map(|header| header.value.as_str())
+            .collect();
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(", "))
+        }
+    }
+
+    pub fn contains(&self, header_name: &str) -> bool {
+        self.headers.iter().any(|header| header.name == header_name)
+    }
+
+    /// Adds a header after the others, including those of the same name
+    pub fn append(&mut self, header_name: &str, value: &str) {
+        self.headers.push(HttpHeader::new(header_name, value));
+    }
+
+    /// Replaces the headers of the name with one holding the value, in place of the first of them
+    pub fn set(&mut self, header_name: &str, value: &str) {
+        let mut found = false;
+        self.headers.retain_mut(|header| {
+            if header.name != header_name {
+                return true;
+            }
+            if found {
+                return false;
+            }
+            found = true;
+            header.value = value.to_string();
+            true
+        });
+        if !found {
+            self.append(header_name, value);
+        }
+    }
+
+    pub fn remove(&mut self, header_name: &str) {
+        self.headers.retain(|header| header.name != header_name);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HttpHeader> {
+        self.headers.iter()
+    }
 }
 
 /// Determines if a header's values are in the form of a comma-separated list.
@@ -87,6 +142,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_combined() {
+        let header_map = HttpHeaderMap::from(vec![
+            HttpHeader::new("abc", "1"),
+            HttpHeader::new("foo", "bar"),
+            HttpHeader::new("abc", "2"),
+        ]);
+        assert_eq!(header_map.get_combined("abc"), Some("1, 2".into()));
+        assert_eq!(header_map.get_combined("foo"), Some("bar".into()));
+        assert_eq!(header_map.get_combined("def"), None);
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut header_map = HttpHeaderMap::default();
+        header_map.append("abc", "1");
+        header_map.append("foo", "bar");
+        header_map.append("abc", "2");
+        assert!(header_map.contains("abc"));
+
+        header_map.set("abc", "3");
+        assert_eq!(
+            header_map.headers,
+            vec![HttpHeader::new("abc", "3"), HttpHeader::new("foo", "bar")]
+        );
+        header_map.set("def", "4");
+        assert_eq!(header_map.get("def"), Some("4".into()));
+
+        header_map.remove("abc");
+        assert!(!header_map.contains("abc"));
+        assert_eq!(
+            header_map
+                .iter()
+                .map(|header| &header.name)
+                .collect::<Vec<_>>(),
+            vec!["foo", "def"]
+        );
+    }
+
     #[test]
     fn test_list_separated_values() {
         assert_eq!(HttpHeaderMap { headers: vec![] }.get("cache-control"), None);
@@ -2,7 +2,10 @@ use super::super::dns::resolve_domain_name_to_ip;
 use super::super::stream::AsyncTcpStream;
 use super::constants::{DOUBLE_NEWLINE_BYTES, SINGLE_NEWLINE_BYTES};
 use super::http_header_map::HttpHeaderMap;
-use super::{structures::HttpVerb, HttpRequestError, HttpResponse, HttpResult, HttpStatus, Result};
+use super::{
+    structures::HttpVerb, HttpHeader, HttpRequestError, HttpResponse, HttpResult, HttpStatus,
+    Result,
+};
 use crate::net::http::headers::parse_status_headers;
 use crate::util::vec_find_subslice;
 use crate::{
@@ -20,11 +23,11 @@ fn contains_end_of_headers(vec: &[u8]) -> bool {
 }
 
 fn determine_content_length(
-    verb: &HttpVerb,
+    verb: HttpVerb,
     status: &HttpStatus,
     header_map: &HttpHeaderMap,
 ) -> Result<u32> {
-    if *verb == HttpVerb::Head {
+    if verb == HttpVerb::Head {
         return Ok(0);
     }
 
@@ -116,28 +119,63 @@ async fn chunked_transfer_encoding(
 /// Holds the state of an HTTP request
 pub struct HttpRequest {
     url: Url,
+    /// Sent after the `Host` header
+    headers: Vec<HttpHeader>,
+    body: Vec<u8>,
 }
 
 impl HttpRequest {
     /// Creates a new HTTP request
     pub fn new(url: Url) -> HttpRequest {
-        HttpRequest { url }
+        HttpRequest {
+            url,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    /// Adds a header to send with the request
+    pub fn with_header(mut self, name: &str, value: &str) -> HttpRequest {
+        self.headers.push(HttpHeader::new(name, value));
+        self
+    }
+
+    /// Sets the body to send with the request, whose length is sent as `Content-Length`
+    pub fn with_body(mut self, body: Vec<u8>) -> HttpRequest {
+        self.body = body;
+        self
     }
 
     /// Performs a GET request
     pub fn get(&self) -> impl Future<Output = HttpResult> {
-        Self::make_request(self.url.clone(), HttpVerb::Get)
+        self.send(HttpVerb::Get)
     }
 
     /// Performs a HEAD request
     pub fn head(&self) -> impl Future<Output = HttpResult> {
-        Self::make_request(self.url.clone(), HttpVerb::Head)
+        self.send(HttpVerb::Head)
     }
 
-    async fn make_request(url: Url, verb: HttpVerb) -> HttpResult {
+    /// Performs a request with the method, sending the headers and body of the request
+    pub fn send(&self, verb: HttpVerb) -> impl Future<Output = HttpResult> {
+        Self::make_request(
+            self.url.clone(),
+            verb,
+            self.headers.clone(),
+            self.body.clone(),
+        )
+    }
+
+    async fn make_request(
+        url: Url,
+        verb: HttpVerb,
+        headers: Vec<HttpHeader>,
+        body: Vec<u8>,
+    ) -> HttpResult {
         let (host, ip) = Self::get_ip_address(&url).await?;
 
-        let stream = Self::get_tcp(host, &ip, url.port, url.http_request_path().as_str(), &verb)
+        let request = Self::get_request_bytes(&host, &url, verb, &headers, &body);
+        let stream = Self::get_tcp(&ip, url.port, &request)
             .map_err(|e| HttpRequestError::from(Box::new(e)))?;
 
         Self::read_full_response(verb, stream).await
@@ -154,21 +192,35 @@ impl HttpRequest {
         }
     }
 
+    /// The request line, headers, and body sent to the server. Methods that are meant to have a
+    /// body always send its length, even when it is empty.
+    fn get_request_bytes(
+        host: &str,
+        url: &Url,
+        verb: HttpVerb,
+        headers: &[HttpHeader],
+        body: &[u8],
+    ) -> Vec<u8> {
+        let verb_str = verb.as_str();
+        let path = url.http_request_path();
+        let mut request = format!("{verb_str} {path} HTTP/1.1\r\nHost: {host}\r\n");
+        for header in headers {
+            request += &format!("{}: {}\r\n", header.name, header.value);
+        }
+        if !body.is_empty() || matches!(verb, HttpVerb::Patch | HttpVerb::Post | HttpVerb::Put) {
+            request += &format!("Content-Length: {}\r\n", body.len());
+        }
+        request += "\r\n";
+        [request.as_bytes(), body].concat()
+    }
+
     fn get_tcp(
-        host: String,
         ip: &IpAddr,
         port: u16,
-        path: &str,
-        verb: &HttpVerb,
+        request: &[u8],
     ) -> result::Result<AsyncTcpStream, std::io::Error> {
-        let verb_str = match verb {
-            HttpVerb::Get => "GET",
-            HttpVerb::Head => "HEAD",
-        };
-
         let mut stream = TcpStream::connect(SocketAddr::new(*ip, port))?;
-        let request = format!("{verb_str} {path} HTTP/1.1\r\nHost: {host}\r\n\r\n");
-        stream.write_all(request.as_bytes())?;
+        stream.write_all(request)?;
         Ok(AsyncTcpStream::from_tcp_stream(stream))
     }
 
@@ -197,7 +249,7 @@ impl HttpRequest {
                             body,
                         });
                     }
-                    content_length = determine_content_length(&verb, &status, &header_map)?;
+                    content_length = determine_content_length(verb, &status, &header_map)?;
                     result = remainder.to_vec();
 
                     if content_length == 0 {
@@ -217,3 +269,32 @@ impl HttpRequest {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_request_bytes() {
+        let url = Url::parse("http://example.com/a?b").unwrap();
+        let request = HttpRequest::get_request_bytes("example.com", &url, HttpVerb::Get, &[], &[]);
+        assert_eq!(
+            std::str::from_utf8(&request).unwrap(),
+            "GET /a?b HTTP/1.1\r\nHost: example.com\r\n\r\n"
+        );
+
+        let headers = [HttpHeader::new("content-type", "text/plain")];
+        let request =
+            HttpRequest::get_request_bytes("example.com", &url, HttpVerb::Post, &headers, b"hi");
+        assert_eq!(
+            std::str::from_utf8(&request).unwrap(),
+            "POST /a?b HTTP/1.1\r\nHost: example.com\r\ncontent-type: text/plain\r\n\
+            Content-Length: 2\r\n\r\nhi"
+        );
+
+        let request = HttpRequest::get_request_bytes("example.com", &url, HttpVerb::Put, &[], &[]);
+        assert!(std::str::from_utf8(&request)
+            .unwrap()
+            .contains("Content-Length: 0\r\n"));
+    }
+}
@@ -8,4 +8,4 @@ mod structures;
 pub use error::{HttpRequestError, Result};
 pub use http_header_map::HttpHeaderMap;
 pub use http_request::HttpRequest;
-pub use structures::{HttpHeader, HttpResponse, HttpResult, HttpStatus};
+pub use structures::{HttpHeader, HttpResponse, HttpResult, HttpStatus, HttpVerb};
@@ -22,7 +22,7 @@ pub struct HttpResponse {
     pub body: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct HttpHeader {
     pub name: String,
     pub value: String,
@@ -37,10 +37,44 @@ impl HttpHeader {
     }
 }
 
-#[derive(PartialEq)]
+/// The methods requests can be made with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HttpVerb {
+    Delete,
     Get,
     Head,
+    Options,
+    Patch,
+    Post,
+    Put,
+}
+
+impl HttpVerb {
+    /// Parses a method, ignoring case
+    pub fn parse(method: &str) -> Option<Self> {
+        Some(match method.to_ascii_uppercase().as_str() {
+            "DELETE" => Self::Delete,
+            "GET" => Self::Get,
+            "HEAD" => Self::Head,
+            "OPTIONS" => Self::Options,
+            "PATCH" => Self::Patch,
+            "POST" => Self::Post,
+            "PUT" => Self::Put,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delete => "DELETE",
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+            Self::Patch => "PATCH",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+        }
+    }
 }
 
 pub type HttpResult = net::Result<HttpResponse>;
@@ -55,4 +89,14 @@ mod tests {
         assert_eq!(http_header.name, "abc");
         assert_eq!(http_header.value, "123");
     }
+
+    #[test]
+    fn test_http_verb_parse() {
+        assert_eq!(HttpVerb::parse("post"), Some(HttpVerb::Post));
+        assert_eq!(
+            HttpVerb::parse("GET").map(|verb| verb.as_str()),
+            Some("GET")
+        );
+        assert_eq!(HttpVerb::parse("CONNECT"), None);
+    }
 }
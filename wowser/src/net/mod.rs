@@ -11,11 +11,14 @@ pub use async_net::*;
 pub use dns::{build_resolve_bytes, resolve_domain_name_to_ip};
 pub use http::{
     HttpHeader, HttpHeaderMap, HttpRequest, HttpRequestError, HttpResponse, HttpResult, HttpStatus,
-    Result,
+    HttpVerb, Result,
 };
 pub use network_resource_manager::*;
 pub use stream::AsyncTcpStream;
-pub use url::{Url, UrlHost, UrlProtocol};
+pub use url::{
+    parse_form_urlencoded, percent_decode, percent_encode, serialize_form_urlencoded,
+    PercentEncodeSet, Url, UrlHost, UrlPath, UrlProtocol, UrlRecord,
+};
 
 #[cfg(test)]
 mod tests {
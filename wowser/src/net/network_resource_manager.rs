@@ -38,6 +38,13 @@ impl NetworkResourceManager {
         self.token_result_cache.get(&token)
     }
 
+    /// Forgets the response for the URL, so it is requested again the next time it is needed
+    pub fn remove(&mut self, url: &Url) {
+        if let Some(token) = self.url_token_cache.remove(url) {
+            self.token_result_cache.remove(&token);
+        }
+    }
+
     fn initiate_network_request(url: &Url) -> Option<TaskToken> {
         let mut executor = NETWORK_EXECUTOR.lock().ok()?;
         let token = executor.run(HttpRequest::new(url.clone()).get()).ok()?;
@@ -0,0 +1,418 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::{percent_decode, percent_encode, PercentEncodeSet, UrlHost};
+
+/// Code points hosts cannot contain
+/// <https://url.spec.whatwg.org/#forbidden-host-code-point>
+fn is_forbidden_host_code_point(c: char) -> bool {
+    matches!(
+        c,
+        '\0' | '\t'
+            | '\n'
+            | '\r'
+            | ' '
+            | '#'
+            | '/'
+            | ':'
+            | '<'
+            | '>'
+            | '?'
+            | '@'
+            | '['
+            | '\\'
+            | ']'
+            | '^'
+            | '|'
+    )
+}
+
+/// Code points domains cannot contain, which are the forbidden host code points along with C0
+/// controls, `%`, and U+007F DELETE
+/// <https://url.spec.whatwg.org/#forbidden-domain-code-point>
+fn is_forbidden_domain_code_point(c: char) -> bool {
+    is_forbidden_host_code_point(c) || c <= '\u{1f}' || c == '%' || c == '\u{7f}'
+}
+
+/// Parses the host of a URL. Hosts of special schemes are IPv4 addresses or domains, which are
+/// lower cased and encoded with punycode, while the hosts of other schemes are opaque and only
+/// percent-encoded. Both can be IPv6 addresses in brackets.
+/// <https://url.spec.whatwg.org/#host-parsing>
+pub fn parse_host(input: &str, is_opaque: bool) -> Option<UrlHost> {
+    if let Some(address) = input.strip_prefix('[') {
+        let address = address.strip_suffix(']')?;
+        return parse_ipv6(address).map(|address| UrlHost::IP(IpAddr::V6(address)));
+    }
+    if is_opaque {
+        if input
+            .chars()
+            .any(|c| c != '%' && is_forbidden_host_code_point(c))
+        {
+            return None;
+        }
+        let host = percent_encode(input, PercentEncodeSet::C0Control);
+        return Some(UrlHost::DomainName(host));
+    }
+
+    let domain = String::from_utf8(percent_decode(input.as_bytes())).ok()?;
+    let ascii_domain = domain_to_ascii(&domain)?;
+    if ascii_domain.chars().any(is_forbidden_domain_code_point) {
+        return None;
+    }
+    if ends_in_number(&ascii_domain) {
+        return parse_ipv4(&ascii_domain).map(|address| UrlHost::IP(IpAddr::V4(address)));
+    }
+    Some(UrlHost::DomainName(ascii_domain))
+}
+
+/// Serializes a host as it is written in URLs, where IPv6 addresses are compressed and in brackets
+/// <https://url.spec.whatwg.org/#concept-host-serializer>
+pub fn serialize_host(host: &UrlHost) -> String {
+    match host {
+        UrlHost::IP(IpAddr::V6(address)) => format!("[{}]", serialize_ipv6(address)),
+        host => host.to_string(),
+    }
+}
+
+/// A simplified version of UTS #46 processing, which lower cases the domain and encodes the labels
+/// outside ASCII with punycode. Unicode normalization and mapping are not done.
+/// <https://url.spec.whatwg.org/#concept-domain-to-ascii>
+fn domain_to_ascii(domain: &str) -> Option<String> {
+    let domain = domain
+        .replace(['\u{3002}', '\u{ff0e}', '\u{ff61}'], ".")
+        .to_lowercase();
+    if domain.is_empty() {
+        return None;
+    }
+    let labels: Option<Vec<String>> = domain
+        .split('.')
+        .map(|label| {
+            if label.is_ascii() {
+                Some(label.to_string())
+            } else {
+                punycode_encode(label).map(|encoded| format!("xn--{encoded}"))
+            }
+        })
+        .collect();
+    Some(labels?.join("."))
+}
+
+/// Encodes a label with the punycode of RFC 3492, which writes the code points outside ASCII as
+/// variable-length integers after the ASCII code points
+/// <https://www.rfc-editor.org/rfc/rfc3492#section-6.3>
+fn punycode_encode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    let code_points: Vec<u32> = input.chars().map(u32::from).collect();
+    let mut output: String = input.chars().filter(char::is_ascii).collect();
+    let basic_length = output.len() as u32;
+    if basic_length > 0 {
+        output.push('-');
+    }
+    let encode_digit = |digit: u32| match digit {
+        0..=25 => char::from(b'a' + digit as u8),
+        _ => char::from(b'0' + (digit - 26) as u8),
+    };
+
+    let mut n = 0x80;
+    let mut delta: u32 = 0;
+    let mut bias = 72;
+    let mut handled = basic_length;
+    while (handled as usize) < code_points.len() {
+        let next = *code_points.iter().filter(|&&c| c >= n).min()?;
+        delta = delta.checked_add((next - n).checked_mul(handled + 1)?)?;
+        n = next;
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c != n {
+                continue;
+            }
+            let mut q = delta;
+            let mut k = BASE;
+            loop {
+                let t = if k <= bias {
+                    T_MIN
+                } else if k >= bias + T_MAX {
+                    T_MAX
+                } else {
+                    k - bias
+                };
+                if q < t {
+                    break;
+                }
+                output.push(encode_digit(t + (q - t) % (BASE - t)));
+                q = (q - t) / (BASE - t);
+                k += BASE;
+            }
+            output.push(encode_digit(q));
+            bias = adapt_bias(delta, handled + 1, handled == basic_length);
+            delta = 0;
+            handled += 1;
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(output)
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc3492#section-6.1>
+fn adapt_bias(delta: u32, length: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / length;
+    let mut k = 0;
+    while delta > ((36 - 1) * 26) / 2 {
+        delta /= 36 - 1;
+        k += 36;
+    }
+    k + (36 * delta) / (delta + 38)
+}
+
+/// Whether the last label of the domain is a number, in which case it is an IPv4 address
+/// <https://url.spec.whatwg.org/#ends-in-a-number-checker>
+fn ends_in_number(domain: &str) -> bool {
+    let domain = domain.strip_suffix('.').unwrap_or(domain);
+    let last = domain.rsplit('.').next().unwrap_or_default();
+    if !last.is_empty() && last.bytes().all(|byte| byte.is_ascii_digit()) {
+        return true;
+    }
+    parse_ipv4_number(last).is_some()
+}
+
+/// Parses an IPv4 address of up to four decimal, octal, or hexadecimal numbers, where the last
+/// number fills the bytes left
+/// <https://url.spec.whatwg.org/#concept-ipv4-parser>
+fn parse_ipv4(input: &str) -> Option<Ipv4Addr> {
+    let input = input.strip_suffix('.').unwrap_or(input);
+    let parts: Vec<&str> = input.split('.').collect();
+    if parts.len() > 4 {
+        return None;
+    }
+    let numbers: Vec<u64> = parts
+        .iter()
+        .map(|part| parse_ipv4_number(part))
+        .collect::<Option<_>>()?;
+    let (last, rest) = numbers.split_last()?;
+    if rest.iter().any(|&number| number > 255) || *last >= 256u64.pow(5 - numbers.len() as u32) {
+        return None;
+    }
+    let mut address = *last;
+    for (index, number) in rest.iter().enumerate() {
+        address += number * 256u64.pow(3 - index as u32);
+    }
+    Some(Ipv4Addr::from(address as u32))
+}
+
+/// Parses a number of an IPv4 address, which is hexadecimal after `0x` and octal after `0`
+/// <https://url.spec.whatwg.org/#ipv4-number-parser>
+fn parse_ipv4_number(input: &str) -> Option<u64> {
+    if input.is_empty() {
+        return None;
+    }
+    let (digits, radix) = if let Some(digits) = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        (digits, 16)
+    } else if input.len() > 1 && input.starts_with('0') {
+        (&input[1..], 8)
+    } else {
+        (input, 10)
+    };
+    if digits.is_empty() {
+        return Some(0);
+    }
+    if !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Numbers too large for any part of an address are only rejected by the IPv4 parser
+    Some(u64::from_str_radix(digits, radix).unwrap_or(u64::MAX))
+}
+
+/// Parses the eight pieces of an IPv6 address, where `::` compresses a run of zero pieces and the
+/// last two pieces can be written as an IPv4 address
+/// <https://url.spec.whatwg.org/#concept-ipv6-parser>
+fn parse_ipv6(input: &str) -> Option<Ipv6Addr> {
+    let (head, tail) = match input.split_once("::") {
+        Some((head, tail)) => (head, Some(tail)),
+        None => (input, None),
+    };
+    let parse_pieces = |part: &str| -> Option<Vec<u16>> {
+        if part.is_empty() {
+            return Some(vec![]);
+        }
+        let groups: Vec<&str> = part.split(':').collect();
+        let mut pieces = vec![];
+        for (index, group) in groups.iter().enumerate() {
+            if index == groups.len() - 1 && group.contains('.') {
+                let address = parse_ipv6_ipv4(group)?.octets();
+                pieces.push(u16::from_be_bytes([address[0], address[1]]));
+                pieces.push(u16::from_be_bytes([address[2], address[3]]));
+            } else if (1..=4).contains(&group.len()) && group.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                pieces.push(u16::from_str_radix(group, 16).ok()?);
+            } else {
+                return None;
+            }
+        }
+        Some(pieces)
+    };
+
+    let head_pieces = parse_pieces(head)?;
+    let mut pieces = [0u16; 8];
+    match tail {
+        Some(tail) => {
+            if head.contains('.') {
+                return None;
+            }
+            let tail_pieces = parse_pieces(tail)?;
+            if head_pieces.len() + tail_pieces.len() > 7 {
+                return None;
+            }
+            pieces[..head_pieces.len()].copy_from_slice(&head_pieces);
+            pieces[8 - tail_pieces.len()..].copy_from_slice(&tail_pieces);
+        }
+        None => {
+            if head_pieces.len() != 8 {
+                return None;
+            }
+            pieces.copy_from_slice(&head_pieces);
+        }
+    }
+    Some(Ipv6Addr::from(pieces))
+}
+
+/// The IPv4 address ending an IPv6 address, whose numbers are strictly decimal
+fn parse_ipv6_ipv4(input: &str) -> Option<Ipv4Addr> {
+    let numbers: Vec<u8> = input
+        .split('.')
+        .map(|number| {
+            let leading_zero = number.len() > 1 && number.starts_with('0');
+            if leading_zero || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            number.parse().ok()
+        })
+        .collect::<Option<_>>()?;
+    let octets: [u8; 4] = numbers.try_into().ok()?;
+    Some(Ipv4Addr::from(octets))
+}
+
+/// Writes the pieces in lower case hexadecimal, compressing the first longest run of two or more
+/// zero pieces as `::`
+/// <https://url.spec.whatwg.org/#concept-ipv6-serializer>
+fn serialize_ipv6(address: &Ipv6Addr) -> String {
+    let pieces = address.segments();
+    let mut compress: Option<(usize, usize)> = None;
+    let mut index = 0;
+    while index < pieces.len() {
+        let length = pieces[index..]
+            .iter()
+            .take_while(|&&piece| piece == 0)
+            .count();
+        if length > 1 && compress.map_or(0, |(_, longest)| longest) < length {
+            compress = Some((index, length));
+        }
+        index += length.max(1);
+    }
+    let write = |pieces: &[u16]| {
+        pieces
+            .iter()
+            .map(|piece| format!("{piece:x}"))
+            .collect::<Vec<_>>()
+            .join(":")
+    };
+    match compress {
+        Some((start, length)) => format!(
+            "{}::{}",
+            write(&pieces[..start]),
+            write(&pieces[start + length..])
+        ),
+        None => write(&pieces),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domain(name: &str) -> Option<UrlHost> {
+        Some(UrlHost::DomainName(name.to_string()))
+    }
+
+    fn ipv4(a: u8, b: u8, c: u8, d: u8) -> Option<UrlHost> {
+        Some(UrlHost::IP(IpAddr::V4(Ipv4Addr::new(a, b, c, d))))
+    }
+
+    #[test]
+    pub fn test_parse_domain() {
+        assert_eq!(parse_host("ExAmPle.COM", false), domain("example.com"));
+        assert_eq!(parse_host("%65xample.com", false), domain("example.com"));
+        assert_eq!(parse_host("münchen.de", false), domain("xn--mnchen-3ya.de"));
+        assert_eq!(parse_host("MÜNCHEN.de", false), domain("xn--mnchen-3ya.de"));
+        assert_eq!(
+            parse_host("例え.テスト", false),
+            domain("xn--r8jz45g.xn--zckzah")
+        );
+        assert_eq!(parse_host("a\u{3002}b", false), domain("a.b"));
+        assert_eq!(parse_host("", false), None);
+        assert_eq!(parse_host("a b", false), None);
+        assert_eq!(parse_host("a%25b", false), None);
+        assert_eq!(parse_host("a<b", false), None);
+    }
+
+    #[test]
+    pub fn test_parse_opaque_host() {
+        assert_eq!(parse_host("ExAmPle", true), domain("ExAmPle"));
+        assert_eq!(parse_host("a%20é", true), domain("a%20%C3%A9"));
+        assert_eq!(parse_host("a b", true), None);
+    }
+
+    #[test]
+    pub fn test_parse_ipv4() {
+        assert_eq!(parse_host("1.2.3.4", false), ipv4(1, 2, 3, 4));
+        assert_eq!(parse_host("1.2.3.4.", false), ipv4(1, 2, 3, 4));
+        assert_eq!(parse_host("0x7f.1", false), ipv4(127, 0, 0, 1));
+        assert_eq!(parse_host("0300.0250.0.1", false), ipv4(192, 168, 0, 1));
+        assert_eq!(parse_host("3232235521", false), ipv4(192, 168, 0, 1));
+        assert_eq!(parse_host("1.2.65535", false), ipv4(1, 2, 255, 255));
+        assert_eq!(parse_host("0x", false), ipv4(0, 0, 0, 0));
+        assert_eq!(parse_host("1.256.3.4", false), None);
+        assert_eq!(parse_host("4294967296", false), None);
+        assert_eq!(parse_host("1.2.3.4.5", false), None);
+        assert_eq!(parse_host("1.2.3.09", false), None);
+        assert_eq!(parse_host("a.1", false), None);
+        assert_eq!(parse_host("1.a", false), domain("1.a"));
+        assert_eq!(parse_host("1.2.3.4", true), domain("1.2.3.4"));
+    }
+
+    #[test]
+    pub fn test_parse_ipv6() {
+        let parse = |input| match parse_host(input, false) {
+            Some(host) => serialize_host(&host),
+            None => "failure".to_string(),
+        };
+        assert_eq!(parse("[1:2:3:4:5:6:7:8]"), "[1:2:3:4:5:6:7:8]");
+        assert_eq!(parse("[0:0:0:0:0:0:0:1]"), "[::1]");
+        assert_eq!(parse("[::]"), "[::]");
+        assert_eq!(parse("[1:0:0:2:0:0:0:3]"), "[1:0:0:2::3]");
+        assert_eq!(parse("[1:0:2:0:3:0:4:0]"), "[1:0:2:0:3:0:4:0]");
+        assert_eq!(parse("[ABCD::1.2.3.4]"), "[abcd::102:304]");
+        assert_eq!(parse("[::ffff:127.0.0.1]"), "[::ffff:7f00:1]");
+        assert_eq!(parse("[1:2:3:4:5:6:7]"), "failure");
+        assert_eq!(parse("[1::2::3]"), "failure");
+        assert_eq!(parse("[1:2:3:4:5:6:7:8:9]"), "failure");
+        assert_eq!(parse("[12345::]"), "failure");
+        assert_eq!(parse("[::1.2.3.04]"), "failure");
+        assert_eq!(parse("[::1"), "failure");
+    }
+
+    #[test]
+    pub fn test_punycode_encode() {
+        assert_eq!(punycode_encode("bücher").as_deref(), Some("bcher-kva"));
+        assert_eq!(punycode_encode("ü").as_deref(), Some("tda"));
+        assert_eq!(punycode_encode("中文").as_deref(), Some("fiq228c"));
+    }
+}
@@ -1,7 +1,13 @@
+mod host;
+mod percent_encoding;
+mod url_record;
+
 use std::fmt;
 use std::net::IpAddr;
 
-use fancy_regex::Regex;
+pub use host::{parse_host, serialize_host};
+pub use percent_encoding::*;
+pub use url_record::{UrlPath, UrlRecord};
 
 /// Represents protocols supported by wowser. Future additions to include HTTPS, FTP, FILE, etc.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
@@ -71,7 +77,7 @@ pub struct Url {
 
 impl fmt::Display for Url {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}://{}", self.protocol, self.host)?;
+        write!(f, "{}://{}", self.protocol, serialize_host(&self.host))?;
         match self.protocol {
             UrlProtocol::File => (),
             UrlProtocol::Http => write!(f, ":{}", self.port)?,
@@ -122,49 +128,68 @@ impl Url {
         }
     }
 
+    /// Parses a URL with the [UrlRecord] parser, failing for the schemes wowser cannot load
     pub fn parse(url: &str) -> Option<Self> {
-        let url_regex = Regex::new(
-            r"(\w+)://([a-zA-Z\d]+([\w\d\-\.]*[a-zA-Z\d]+)?)?(:(\d+))?(/[^\?]*)?(\?([^#]*))?(#(.*))?",
-        )
-        .unwrap();
-        let captures = url_regex.captures(url).ok()??;
-        // If it failed to match the full string, bail out early
-        if captures.get(0)?.range().len() != url.len() {
-            return None;
-        }
-        let protocol = captures.get(1)?.as_str();
-        let parsed_protocol = UrlProtocol::parse(protocol)?;
-        let host = captures.get(2)?.as_str();
-        let port = captures
-            .get(5)
-            .map(|capture| capture.as_str())
-            .unwrap_or_else(|| match parsed_protocol {
-                UrlProtocol::File => "0",
-                UrlProtocol::Http => "80",
-            });
-        let path = captures
-            .get(6)
-            .map(|capture| capture.as_str())
-            .unwrap_or("/");
-        let query_string = captures
-            .get(8)
-            .map(|capture| capture.as_str())
-            .unwrap_or("");
-        let fragment = captures
-            .get(10)
-            .map(|capture| capture.as_str())
-            .unwrap_or("");
+        Self::from_record(&UrlRecord::parse(url, None)?)
+    }
 
+    /// Converts a URL record of a scheme wowser can load, filling in the default port
+    pub fn from_record(record: &UrlRecord) -> Option<Self> {
+        let protocol = UrlProtocol::parse(&record.scheme)?;
+        let default_port = match protocol {
+            UrlProtocol::File => 0,
+            UrlProtocol::Http => 80,
+        };
         Some(Url {
-            protocol: parsed_protocol,
-            host: UrlHost::parse(host)?,
-            port: port.parse::<u16>().ok()?,
-            path: path.to_string(),
-            query_string: query_string.to_string(),
-            fragment: fragment.to_string(),
+            protocol,
+            host: record.host.clone()?,
+            port: record.port.unwrap_or(default_port),
+            path: record.pathname(),
+            query_string: record.query.clone().unwrap_or_default(),
+            fragment: record.fragment.clone().unwrap_or_default(),
         })
     }
 
+    /// The URL as a record of the URL Standard, where the default port is left out
+    pub fn to_record(&self) -> UrlRecord {
+        let (scheme, port) = match self.protocol {
+            UrlProtocol::File => ("file", None),
+            UrlProtocol::Http => ("http", Some(self.port).filter(|&port| port != 80)),
+        };
+        let segments = match self.path.strip_prefix('/') {
+            Some(path) => path.split('/').map(String::from).collect(),
+            None => vec![String::new()],
+        };
+        UrlRecord {
+            scheme: scheme.to_string(),
+            username: String::new(),
+            password: String::new(),
+            host: Some(self.host.clone()),
+            port,
+            path: UrlPath::Segments(segments),
+            query: Some(self.query_string.clone()).filter(|query| !query.is_empty()),
+            fragment: Some(self.fragment.clone()).filter(|fragment| !fragment.is_empty()),
+        }
+    }
+
+    /// Serializes the scheme, host, and port documents of the URL share their storage and other
+    /// state by. File URLs have an opaque origin, which is `None`.
+    /// <https://html.spec.whatwg.org/multipage/browsers.html#ascii-serialisation-of-an-origin>
+    pub fn origin(&self) -> Option<String> {
+        match self.protocol {
+            UrlProtocol::File => None,
+            UrlProtocol::Http => self.to_record().origin(),
+        }
+    }
+
+    /// Resolves a reference to a URL against the URL, such as the URL of a request against the URL
+    /// of its document. Relative paths are resolved against the directory of the path, along with
+    /// their `.` and `..` segments.
+    /// <https://url.spec.whatwg.org/#concept-url-parser>
+    pub fn join(&self, reference: &str) -> Option<Url> {
+        Self::from_record(&UrlRecord::parse(reference, Some(&self.to_record()))?)
+    }
+
     /// The representation of the path to send in HTTP requests
     pub fn http_request_path(&self) -> String {
         let mut ret = String::new();
@@ -283,21 +308,69 @@ mod tests {
         assert_eq!("/?query", url.http_request_path());
     }
 
+    #[test]
+    pub fn test_url_origin() {
+        let origin = |url| Url::parse(url).unwrap().origin();
+        assert_eq!(
+            origin("http://example.com/a?b#c"),
+            Some("http://example.com".to_string())
+        );
+        assert_eq!(
+            origin("http://127.0.0.1:8080/"),
+            Some("http://127.0.0.1:8080".to_string())
+        );
+        assert_eq!(origin("file://foo/bar.html"), None);
+    }
+
+    #[test]
+    pub fn test_url_join() {
+        let base = Url::parse("http://example.com/a/b/c.html?q=1#top").unwrap();
+        let join = |reference| base.join(reference).unwrap().to_string();
+        assert_eq!(join("d.json"), "http://example.com:80/a/b/d.json");
+        assert_eq!(join("./d/../e?x=2"), "http://example.com:80/a/b/e?x=2");
+        assert_eq!(join("../.."), "http://example.com:80/");
+        assert_eq!(join("/api"), "http://example.com:80/api");
+        assert_eq!(join("?q=2"), "http://example.com:80/a/b/c.html?q=2");
+        assert_eq!(join("#end"), "http://example.com:80/a/b/c.html?q=1#end");
+        assert_eq!(join("//other.com/x"), "http://other.com:80/x");
+        assert_eq!(join("http://127.0.0.1:8080/"), "http://127.0.0.1:8080/");
+        assert_eq!(join("d%2e/../%2E%2e/f"), "http://example.com:80/a/f");
+        assert_eq!(join("\\\\other.com\\x"), "http://other.com:80/x");
+        assert_eq!(join("http:g"), "http://example.com:80/a/b/g");
+        assert_eq!(base.join("foo:bar"), None);
+    }
+
     #[test]
     pub fn test_url_parse_invalid() {
         assert_eq!(Url::parse("foo://example.com"), None);
         assert_eq!(Url::parse("http//example.com"), None);
-        assert_eq!(Url::parse("http:/example.com/"), None);
-        assert_eq!(Url::parse("http:/e/"), None);
-        assert_eq!(Url::parse("http://example-"), None);
-        assert_eq!(Url::parse("http://-example"), None);
-        assert_eq!(Url::parse("http://example."), None);
-        assert_eq!(Url::parse("http://.example"), None);
-        assert_eq!(Url::parse("http://.example"), None);
-        assert_eq!(Url::parse("http://example:"), None);
-        assert_eq!(Url::parse("http://example:/"), None);
         assert_eq!(Url::parse("http://exam:ple"), None);
         assert_eq!(Url::parse("http://example::3"), None);
+        assert_eq!(Url::parse("http://example:65536"), None);
+        assert_eq!(Url::parse("http://"), None);
+        assert_eq!(Url::parse("http://a b"), None);
+        assert_eq!(Url::parse("http://1.2.3.256"), None);
+        assert_eq!(Url::parse("http://[::1"), None);
+        assert_eq!(Url::parse("data:text/plain,hi"), None);
+    }
+
+    #[test]
+    pub fn test_url_parse_lenient() {
+        let parse = |url| Url::parse(url).map(|url| url.to_string());
+        let expected = |url: &str| Some(url.to_string());
+        assert_eq!(
+            parse("http:/example.com/"),
+            expected("http://example.com:80/")
+        );
+        assert_eq!(parse("http:/e/"), expected("http://e:80/"));
+        assert_eq!(parse("HTTP://EXAMPLE-"), expected("http://example-:80/"));
+        assert_eq!(parse("http://-example"), expected("http://-example:80/"));
+        assert_eq!(parse("http://example."), expected("http://example.:80/"));
+        assert_eq!(parse("http://.example"), expected("http://.example:80/"));
+        assert_eq!(parse("http://example:"), expected("http://example:80/"));
+        assert_eq!(parse("http://example:/"), expected("http://example:80/"));
+        assert_eq!(parse(" http://a/b c "), expected("http://a:80/b%20c"));
+        assert_eq!(parse("http://0x7f.1/"), expected("http://127.0.0.1:80/"));
     }
 
     #[test]
@@ -410,18 +483,25 @@ mod tests {
             ))
         );
 
-        // TODO: Support IPv6 URLs
-        // assert_eq!(
-        //     Url::parse("http://[1:2:3:4:5:6:7:8]:9/[11:12:13:14:15:16:17:18]?[21:22:23:24:25:26:27:28]#[31:32:33:34:35:36:37:38]"),
-        //     Some(Url::new(
-        //         UrlProtocol::Http,
-        //         UrlHost::IP(IpAddr::V6(Ipv6Addr::new(1,2,3,4,5,6,7,8))),
-        //         9,
-        //         "/[11:12:13:14:15:16:17:18]",
-        //         "[21:22:23:24:25:26:27:28]",
-        //         "[31:32:33:34:35:36:37:38]",
-        //     ))
-        // );
+        let url = concat!(
+            "http://[1:2:3:4:5:6:7:8]:9/[11:12:13:14:15:16:17:18]",
+            "?[21:22:23:24:25:26:27:28]#[31:32:33:34:35:36:37:38]"
+        );
+        assert_eq!(
+            Url::parse(url),
+            Some(Url::new(
+                UrlProtocol::Http,
+                UrlHost::IP(IpAddr::V6(Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8))),
+                9,
+                "/[11:12:13:14:15:16:17:18]",
+                "[21:22:23:24:25:26:27:28]",
+                "[31:32:33:34:35:36:37:38]",
+            ))
+        );
+        assert_eq!(
+            Url::parse("http://[::1]:8080/").unwrap().to_string(),
+            "http://[::1]:8080/"
+        );
     }
 
     #[test]
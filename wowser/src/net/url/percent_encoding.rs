@@ -0,0 +1,195 @@
+/// The bytes percent-encoded in each part of a URL. Each set adds bytes to a smaller set, see
+/// [Self::parent].
+/// <https://url.spec.whatwg.org/#percent-encoded-bytes>
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum PercentEncodeSet {
+    /// C0 control bytes and bytes outside ASCII, encoded in opaque paths and hosts
+    C0Control,
+    Fragment,
+    Query,
+    /// The query of URLs with special schemes, which also encodes `'`
+    SpecialQuery,
+    Path,
+    Userinfo,
+    /// A component encoded by `encodeURIComponent`
+    Component,
+    /// The names and values of `application/x-www-form-urlencoded` data
+    FormUrlencoded,
+}
+
+impl PercentEncodeSet {
+    pub fn contains(self, byte: u8) -> bool {
+        let contained = match self {
+            Self::C0Control => return !(0x20..=0x7e).contains(&byte),
+            Self::Fragment => matches!(byte, b' ' | b'"' | b'<' | b'>' | b'`'),
+            Self::Query => matches!(byte, b' ' | b'"' | b'#' | b'<' | b'>'),
+            Self::SpecialQuery => byte == b'\'',
+            Self::Path => matches!(byte, b'?' | b'`' | b'{' | b'}'),
+            Self::Userinfo => matches!(
+                byte,
+                b'/' | b':' | b';' | b'=' | b'@' | b'[' | b'\\' | b']' | b'^' | b'|'
+            ),
+            Self::Component => matches!(byte, b'$' | b'%' | b'&' | b'+' | b','),
+            Self::FormUrlencoded => matches!(byte, b'!' | b'\'' | b'(' | b')' | b'~'),
+        };
+        contained || self.parent().is_some_and(|parent| parent.contains(byte))
+    }
+
+    /// The set this set adds bytes to. The special query set adds to the query set, and the path
+    /// set does not include `'`.
+    fn parent(self) -> Option<Self> {
+        match self {
+            Self::C0Control => None,
+            Self::Fragment | Self::Query => Some(Self::C0Control),
+            Self::SpecialQuery | Self::Path => Some(Self::Query),
+            Self::Userinfo => Some(Self::Path),
+            Self::Component => Some(Self::Userinfo),
+            Self::FormUrlencoded => Some(Self::Component),
+        }
+    }
+}
+
+/// Encodes the text as UTF-8, writing the bytes in the set as `%` followed by their hexadecimal
+/// value
+/// <https://url.spec.whatwg.org/#string-utf-8-percent-encode>
+pub fn percent_encode(input: &str, set: PercentEncodeSet) -> String {
+    let mut output = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if set.contains(byte) {
+            output += &format!("%{byte:02X}");
+        } else {
+            output.push(byte as char);
+        }
+    }
+    output
+}
+
+/// Decodes each `%` followed by two hexadecimal digits into the byte they describe, leaving other
+/// bytes as they are
+/// <https://url.spec.whatwg.org/#percent-decode>
+pub fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        let byte = input[index];
+        let decoded = input
+            .get(index + 1..index + 3)
+            .filter(|_| byte == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .filter(|hex| hex.bytes().all(|digit| digit.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                output.push(decoded);
+                index += 3;
+            }
+            None => {
+                output.push(byte);
+                index += 1;
+            }
+        }
+    }
+    output
+}
+
+/// Parses the name and value pairs of `application/x-www-form-urlencoded` data, such as a query,
+/// where `+` is a space
+/// <https://url.spec.whatwg.org/#concept-urlencoded-parser>
+pub fn parse_form_urlencoded(input: &str) -> Vec<(String, String)> {
+    let decode = |text: &str| {
+        let bytes = percent_decode(text.replace('+', " ").as_bytes());
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    input
+        .split('&')
+        .filter(|sequence| !sequence.is_empty())
+        .map(|sequence| {
+            let (name, value) = sequence.split_once('=').unwrap_or((sequence, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// Serializes name and value pairs as `application/x-www-form-urlencoded` data, where spaces are
+/// written as `+`
+/// <https://url.spec.whatwg.org/#concept-urlencoded-serializer>
+pub fn serialize_form_urlencoded(list: &[(String, String)]) -> String {
+    let encode = |text: &str| {
+        text.split(' ')
+            .map(|part| percent_encode(part, PercentEncodeSet::FormUrlencoded))
+            .collect::<Vec<_>>()
+            .join("+")
+    };
+    list.iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_percent_encode() {
+        let input = "a b\"#<>?`{}'/:@|\u{7f}é%+&!~";
+        let encode = |set| percent_encode(input, set);
+        assert_eq!(
+            encode(PercentEncodeSet::C0Control),
+            "a b\"#<>?`{}'/:@|%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::Fragment),
+            "a%20b%22#%3C%3E?%60{}'/:@|%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::Query),
+            "a%20b%22%23%3C%3E?`{}'/:@|%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::SpecialQuery),
+            "a%20b%22%23%3C%3E?`{}%27/:@|%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::Path),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D'/:@|%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::Userinfo),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D'%2F%3A%40%7C%7F%C3%A9%+&!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::Component),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D'%2F%3A%40%7C%7F%C3%A9%25%2B%26!~"
+        );
+        assert_eq!(
+            encode(PercentEncodeSet::FormUrlencoded),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D%27%2F%3A%40%7C%7F%C3%A9%25%2B%26%21%7E"
+        );
+    }
+
+    #[test]
+    pub fn test_percent_decode() {
+        assert_eq!(percent_decode(b"%41%c3%A9%"), "Aé%".as_bytes());
+        assert_eq!(percent_decode(b"%4g%%41%4"), b"%4g%A%4");
+    }
+
+    #[test]
+    pub fn test_form_urlencoded() {
+        let list = parse_form_urlencoded("a=1&&b=x+y%20z&c&=d&a=%E2%9C%93");
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            list,
+            pairs(&[("a", "1"), ("b", "x y z"), ("c", ""), ("", "d"), ("a", "✓")])
+        );
+        assert_eq!(
+            serialize_form_urlencoded(&pairs(&[("a b", "1+2"), ("c", "~✓")])),
+            "a+b=1%2B2&c=%7E%E2%9C%93"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use super::token::Token;
+use super::{token::Token, ParseError};
 
 /// Converts text into tokens
 pub struct Lexer<T: Token> {
@@ -16,6 +16,13 @@ pub struct ParsedToken<'a, T: Token> {
     pub full_match: &'a str,
 }
 
+impl<T: Token> ParsedToken<'_, T> {
+    /// The offset of the literal, after any whitespace or other text matched before it
+    pub fn literal_offset(&self) -> ParsedTokenOffset {
+        self.offset + (self.literal.as_ptr() as usize - self.full_match.as_ptr() as usize)
+    }
+}
+
 impl<T: Token> fmt::Debug for ParsedToken<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}({}):{}", self.token, self.literal, self.offset)
@@ -32,19 +39,37 @@ impl<T: Token> Lexer<T> {
 
     /// Parses a source string into a series of tokens
     pub fn parse<'a>(&self, source: &'a str) -> Option<ParsedTokens<'a, T>> {
-        Self::recursive_parse(0, source, &self.root_token).map(|v| {
-            v.into_iter()
+        self.lex(source).ok()
+    }
+
+    /// Parses a source string into a series of tokens, reporting the furthest offset reached
+    /// when it cannot be
+    pub fn lex<'a>(&self, source: &'a str) -> Result<ParsedTokens<'a, T>, ParseError> {
+        let mut furthest_offset = 0;
+        match Self::recursive_parse(0, source, &self.root_token, &mut furthest_offset) {
+            Some(tokens) => Ok(tokens
+                .into_iter()
                 .rev()
                 .filter(|token| !token.token.is_comment())
-                .collect()
-        })
+                .collect()),
+            None => {
+                let remaining = &source[furthest_offset..];
+                let whitespace = remaining.len() - remaining.trim_start().len();
+                Err(ParseError {
+                    message: "Invalid or unexpected token",
+                    offset: furthest_offset + whitespace,
+                })
+            }
+        }
     }
 
     fn recursive_parse<'a>(
         cur_source_offset: ParsedTokenOffset,
         source: &'a str,
         root_token: &T,
+        furthest_offset: &mut ParsedTokenOffset,
     ) -> Option<ParsedTokens<'a, T>> {
+        *furthest_offset = (*furthest_offset).max(cur_source_offset);
         if root_token.is_terminator() {
             return Some(vec![]);
         }
@@ -78,6 +103,7 @@ impl<T: Token> Lexer<T> {
                     cur_source_offset + capture_offset,
                     &source[capture_offset..],
                     new_root_token,
+                    furthest_offset,
                 ) {
                     subpath.push(ParsedToken {
                         token: *token,
@@ -8,12 +8,14 @@
 
 mod interpreter;
 mod lexer;
+mod parse_error;
 mod parser;
 mod rule;
 mod token;
 
 pub use interpreter::*;
 pub use lexer::{Lexer, ParsedToken, ParsedTokenOffset, ParsedTokens};
+pub use parse_error::ParseError;
 pub use parser::{ASTNode, Parser, ParserResult};
 pub use rule::{Rule, RuleType};
 pub use token::Token;
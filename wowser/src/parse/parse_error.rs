@@ -0,0 +1,40 @@
+use std::fmt;
+
+use super::ParsedTokenOffset;
+
+/// Why a source failed to lex or parse, and where
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub message: &'static str,
+    /// The byte offset in the source of the furthest point lexing or parsing reached before
+    /// failing, which is usually where the error is
+    pub offset: ParsedTokenOffset,
+}
+
+impl ParseError {
+    /// The line and column of the error in the source, both starting at 1
+    pub fn line_and_column(&self, source: &str) -> (usize, usize) {
+        let before = source.get(..self.offset).unwrap_or(source);
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        (line, column)
+    }
+
+    /// Describes the error with its line and column in the source
+    pub fn describe(&self, source: &str) -> String {
+        let (line, column) = self.line_and_column(source);
+        format!("{} at line {line}, column {column}", self.message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
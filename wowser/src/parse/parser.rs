@@ -1,5 +1,6 @@
 use super::lexer::ParsedToken;
 use super::rule::{Rule, RuleType};
+use super::ParseError;
 
 /// Represents a node in an AST representation of a language
 #[derive(Debug)]
@@ -59,14 +60,46 @@ impl Parser {
         tokens: &'a [ParsedToken<'a, R::Token>],
         rule: &R,
     ) -> Result<ParserResult<'a, R>, &str> {
-        Self::_parse(tokens, rule)
+        let mut fewest_remaining = tokens.len();
+        Self::_parse(tokens, rule, &mut fewest_remaining)
     }
 
+    /// Performs the parsing, reporting the furthest token that could not be matched when the
+    /// tokens cannot be parsed
+    pub fn parse_with_location<'a, R: Rule>(
+        &self,
+        tokens: &'a [ParsedToken<'a, R::Token>],
+        rule: &R,
+    ) -> Result<ParserResult<'a, R>, ParseError> {
+        let mut fewest_remaining = tokens.len();
+        Self::_parse(tokens, rule, &mut fewest_remaining).map_err(|_message| {
+            match tokens.get(tokens.len() - fewest_remaining) {
+                Some(token) => ParseError {
+                    message: "Unexpected token",
+                    offset: token.literal_offset(),
+                },
+                None => ParseError {
+                    message: "Unexpected end of input",
+                    offset: tokens
+                        .last()
+                        .map(|token| token.offset + token.full_match.len())
+                        .unwrap_or_default(),
+                },
+            }
+        })
+    }
+
+    /// Tracks the fewest tokens remaining when a token failed to match, which is the furthest
+    /// the parser reached
     fn _parse<'a, 'b, R: Rule>(
         tokens: &'a [ParsedToken<'a, R::Token>],
         root_rule: &'b R,
+        fewest_remaining: &mut usize,
     ) -> Result<ParserResult<'a, R>, &'static str> {
-        let first_token = tokens.get(0).ok_or("No tokens left")?;
+        let Some(first_token) = tokens.first() else {
+            *fewest_remaining = 0;
+            return Err("No tokens left");
+        };
 
         for child_rule_type in root_rule.children().iter() {
             let result = match child_rule_type {
@@ -81,10 +114,11 @@ impl Parser {
                             remaining_tokens: &tokens[1..],
                         })
                     } else {
+                        *fewest_remaining = (*fewest_remaining).min(tokens.len());
                         Err("Not a match")
                     }
                 }
-                RuleType::Rule(rule) => match Self::_parse(tokens, rule) {
+                RuleType::Rule(rule) => match Self::_parse(tokens, rule, fewest_remaining) {
                     Ok(result) => Ok(ParserResult {
                         node: ASTNode {
                             rule: *root_rule,
@@ -98,7 +132,7 @@ impl Parser {
                 RuleType::RepeatableRule(rule) => {
                     let mut children = vec![];
                     let mut cur_tokens = tokens;
-                    while let Ok(result) = Self::_parse(cur_tokens, rule) {
+                    while let Ok(result) = Self::_parse(cur_tokens, rule, fewest_remaining) {
                         children.push(result.node);
                         cur_tokens = result.remaining_tokens;
                     }
@@ -118,7 +152,7 @@ impl Parser {
                     let mut failed = false;
 
                     for rule in rules {
-                        if let Ok(child) = Self::_parse(cur_tokens, rule) {
+                        if let Ok(child) = Self::_parse(cur_tokens, rule, fewest_remaining) {
                             children.push(child.node);
                             cur_tokens = child.remaining_tokens;
                         } else {
@@ -6,19 +6,66 @@ use crate::{
     parse::ParsedTokenOffset,
 };
 
+use super::ScriptQueue;
+
 /// Abstraction for managing async tasks when rendering
 #[derive(Default)]
 pub struct AsyncRenderContext {
     pub css_documents: HashMap<String, (ParsedTokenOffset, CssDocument)>,
+    /// The scripts of the document, which are run by the tab once loaded
+    pub scripts: ScriptQueue,
+    /// Set when a render adds or changes a stylesheet, which is only found after the document was
+    /// styled, so it has to be rendered again to show it
+    pub styles_changed: bool,
+    /// Set when a render needs a resource that is still loading, so it has to be rendered again
+    /// once it loads
+    pub loading_resources: bool,
+    /// The URL of the document, which the URLs of its resources are relative to
+    pub base_url: Option<Url>,
 }
 
 impl AsyncRenderContext {
+    /// The response for the URL, which is requested when it was not yet. Results in `None` while
+    /// it is loading.
     pub fn get_resource(&mut self, url: &Url) -> Option<Rc<HttpResult>> {
-        NETWORK_RESOURCE_MANAGER
+        let resource = NETWORK_RESOURCE_MANAGER
             .lock()
             .map(|mut network_resource_manager| network_resource_manager.get_or_request(url))
             .ok()
             .flatten()
-            .and_then(|result_weak| result_weak.upgrade())
+            .and_then(|result_weak| result_weak.upgrade());
+        if resource.is_none() {
+            self.loading_resources = true;
+        }
+        resource
+    }
+
+    /// Resolves the URL of a resource against the URL of the document. Documents without one, such
+    /// as those loaded from a string, only load resources by absolute URL.
+    pub fn resolve_url(&self, reference: &str) -> Option<Url> {
+        match &self.base_url {
+            Some(base_url) => base_url.join(reference),
+            None => Url::parse(reference),
+        }
+    }
+
+    /// Adds the stylesheet or replaces the one with the same key, noting when it changed
+    pub fn insert_css_document(
+        &mut self,
+        key: String,
+        offset: ParsedTokenOffset,
+        css_document: CssDocument,
+    ) {
+        let entry = (offset, css_document);
+        if self.css_documents.get(&key) != Some(&entry) {
+            self.css_documents.insert(key, entry);
+            self.styles_changed = true;
+        }
+    }
+
+    /// Forgets what the last render noted before rendering again
+    pub fn reset_changes(&mut self) {
+        self.styles_changed = false;
+        self.loading_resources = false;
     }
 }
@@ -5,4 +5,8 @@ mod to_styles;
 
 pub use apply_css::*;
 pub use async_render_context::*;
+pub use special_elements::{
+    load_scripts, queue_scripts, DocumentScript, ReadyScript, ScriptQueue, ScriptSource,
+    ScriptTiming,
+};
 pub use to_styles::*;
@@ -25,9 +25,11 @@ pub fn on_link_node(
         {
             let body_string = from_utf8(&response.body).ok()?;
             let css_document = css::parse_css(body_string).ok()?;
-            async_render_context
-                .css_documents
-                .insert(href.to_string(), (element.document_offset, css_document));
+            async_render_context.insert_css_document(
+                href.to_string(),
+                element.document_offset,
+                css_document,
+            );
         }
         None
     })();
@@ -1,7 +1,9 @@
 mod img_node;
 mod link_node;
+mod script_node;
 mod style_node;
 
 pub use img_node::*;
 pub use link_node::*;
+pub use script_node::*;
 pub use style_node::*;
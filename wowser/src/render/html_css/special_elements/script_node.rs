@@ -0,0 +1,316 @@
+use std::str::from_utf8;
+
+use crate::{
+    html::{ElementContents, ElementContentsId, ElementHtmlNode},
+    net::Url,
+    render::AsyncRenderContext,
+};
+
+/// Queues the scripts among the node and its descendants to be run by the tab once they load, in
+/// document order. Scripts of the document honor `async` and `defer`, while those inserted by
+/// other scripts run as soon as they load. Each element is only queued once.
+pub fn queue_scripts(
+    node: &ElementContents,
+    inserted: bool,
+    async_render_context: &mut AsyncRenderContext,
+) {
+    let is_script = |node: &ElementContents| matches!(node, ElementContents::Element(element) if element.tag_name == "script");
+    let scripts = std::iter::once(node)
+        .filter(|node| is_script(node))
+        .chain(node.find_descendants(is_script));
+    for script in scripts {
+        if let ElementContents::Element(element) = script {
+            queue_script(element, inserted, async_render_context);
+        }
+    }
+}
+
+fn queue_script(
+    element: &ElementHtmlNode,
+    inserted: bool,
+    async_render_context: &mut AsyncRenderContext,
+) {
+    let id = element.get_id();
+    if async_render_context.scripts.find(id).is_some() {
+        return;
+    }
+    let Some(script_type) = get_script_type(element) else {
+        return;
+    };
+    let src = element.get_attribute("src");
+    let (name, source) = match (script_type, src) {
+        (ScriptType::Classic, Some(src)) => match async_render_context.resolve_url(src) {
+            Some(url) => (src.to_string(), fetch_script(&url, async_render_context)),
+            None => (src.to_string(), ScriptSource::Failed),
+        },
+        (ScriptType::Classic, None) => (
+            format!("inline script {id}"),
+            ScriptSource::Loaded(ReadyScript::Classic(
+                element
+                    .children
+                    .iter()
+                    .map(|child| child.text_content())
+                    .collect(),
+            )),
+        ),
+        (ScriptType::Module, Some(src)) => match async_render_context.resolve_url(src) {
+            Some(url) => (
+                src.to_string(),
+                ScriptSource::Loaded(ReadyScript::Module(url)),
+            ),
+            None => (src.to_string(), ScriptSource::Failed),
+        },
+        (ScriptType::Module, None) => {
+            log!(WARN["JS"]: "Inline module scripts are not supported, skipping script", id);
+            return;
+        }
+    };
+    // `async` and `defer` only apply to scripts loaded from a URL, and modules are deferred
+    let timing = if src.is_none() {
+        ScriptTiming::Blocking
+    } else if inserted || element.has_attribute("async") {
+        ScriptTiming::Async
+    } else if script_type == ScriptType::Module || element.has_attribute("defer") {
+        ScriptTiming::Defer
+    } else {
+        ScriptTiming::Blocking
+    };
+    async_render_context.scripts.scripts.push(DocumentScript {
+        id,
+        name,
+        timing,
+        source,
+        has_run: false,
+    });
+}
+
+/// Fetches the sources of the queued scripts that were still loading when they were queued
+pub fn load_scripts(async_render_context: &mut AsyncRenderContext) {
+    for index in 0..async_render_context.scripts.scripts.len() {
+        if let ScriptSource::Pending(url) = &async_render_context.scripts.scripts[index].source {
+            let url = url.clone();
+            let source = fetch_script(&url, async_render_context);
+            async_render_context.scripts.scripts[index].source = source;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScriptType {
+    Classic,
+    Module,
+}
+
+/// Classic scripts and module scripts are run, while data blocks are left alone
+fn get_script_type(element: &ElementHtmlNode) -> Option<ScriptType> {
+    let type_attribute = element
+        .get_attribute("type")
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match type_attribute.as_str() {
+        "" | "text/javascript" | "application/javascript" => Some(ScriptType::Classic),
+        "module" => Some(ScriptType::Module),
+        _ => None,
+    }
+}
+
+fn fetch_script(url: &Url, async_render_context: &mut AsyncRenderContext) -> ScriptSource {
+    let Some(http_result) = async_render_context.get_resource(url) else {
+        return ScriptSource::Pending(url.clone());
+    };
+    match http_result.as_ref() {
+        Ok(response) if response.status.contains_success_content() => {
+            match from_utf8(&response.body) {
+                Ok(body) => ScriptSource::Loaded(ReadyScript::Classic(body.to_string())),
+                Err(_) => ScriptSource::Failed,
+            }
+        }
+        _ => ScriptSource::Failed,
+    }
+}
+
+/// When a script runs relative to the other scripts of the document
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptTiming {
+    /// Runs in document order before any later scripts
+    Blocking,
+    /// Runs in document order once the blocking scripts have run
+    Defer,
+    /// Runs as soon as it is loaded
+    Async,
+}
+
+/// What is run for a script once it has loaded
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadyScript {
+    /// The source text of a classic script
+    Classic(String),
+    /// The URL of a module script, which is loaded along with the modules it imports through the
+    /// module loader of the tab when it runs
+    Module(Url),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScriptSource {
+    Pending(Url),
+    Loaded(ReadyScript),
+    /// The source could not be loaded, so the script is skipped
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct DocumentScript {
+    pub id: ElementContentsId,
+    /// Describes the script when reporting its errors
+    pub name: String,
+    pub timing: ScriptTiming,
+    pub source: ScriptSource,
+    pub has_run: bool,
+}
+
+/// The scripts of a document in the order they were found
+#[derive(Debug, Default)]
+pub struct ScriptQueue {
+    pub scripts: Vec<DocumentScript>,
+}
+
+impl ScriptQueue {
+    fn find(&self, id: ElementContentsId) -> Option<usize> {
+        self.scripts.iter().position(|script| script.id == id)
+    }
+
+    /// Whether the scripts that run before the document is done loading have run, which are all
+    /// but the async ones
+    pub fn has_run_document_scripts(&self) -> bool {
+        self.scripts
+            .iter()
+            .all(|script| script.has_run || script.timing == ScriptTiming::Async)
+    }
+
+    pub fn has_run_all(&self) -> bool {
+        self.scripts.iter().all(|script| script.has_run)
+    }
+
+    /// Marks the scripts that can run now as run, resulting in their names and what to run in the
+    /// order to run them. Scripts that failed to load are marked as run without being returned.
+    pub fn take_ready(&mut self) -> Vec<(String, ReadyScript)> {
+        let mut ready = vec![];
+        let mut blocked = false;
+        for timing in [ScriptTiming::Blocking, ScriptTiming::Async] {
+            for script in self.scripts.iter_mut() {
+                if script.has_run || script.timing != timing {
+                    continue;
+                }
+                match &script.source {
+                    ScriptSource::Pending(_) if timing == ScriptTiming::Blocking => {
+                        blocked = true;
+                        break;
+                    }
+                    ScriptSource::Pending(_) => {}
+                    ScriptSource::Loaded(source) => {
+                        script.has_run = true;
+                        ready.push((script.name.clone(), source.clone()));
+                    }
+                    ScriptSource::Failed => script.has_run = true,
+                }
+            }
+        }
+        if blocked {
+            return ready;
+        }
+        for script in self.scripts.iter_mut() {
+            if script.has_run || script.timing != ScriptTiming::Defer {
+                continue;
+            }
+            match &script.source {
+                ScriptSource::Pending(_) => break,
+                ScriptSource::Loaded(source) => {
+                    script.has_run = true;
+                    ready.push((script.name.clone(), source.clone()));
+                }
+                ScriptSource::Failed => script.has_run = true,
+            }
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(id: ElementContentsId, timing: ScriptTiming, source: ScriptSource) -> DocumentScript {
+        DocumentScript {
+            id,
+            name: id.to_string(),
+            timing,
+            source,
+            has_run: false,
+        }
+    }
+
+    fn loaded(source: &str) -> ScriptSource {
+        ScriptSource::Loaded(ReadyScript::Classic(source.to_string()))
+    }
+
+    fn pending() -> ScriptSource {
+        ScriptSource::Pending(Url::parse("http://example.com/script.js").unwrap())
+    }
+
+    #[test]
+    fn relative_src() {
+        let mut async_render_context = AsyncRenderContext::default();
+        assert_eq!(async_render_context.resolve_url("app.js"), None);
+
+        async_render_context.base_url = Url::parse("http://example.com/dir/page.html?a#b");
+        assert_eq!(
+            async_render_context.resolve_url("app.js"),
+            Url::parse("http://example.com/dir/app.js")
+        );
+        assert_eq!(
+            async_render_context.resolve_url("../lib/app.js"),
+            Url::parse("http://example.com/lib/app.js")
+        );
+        assert_eq!(
+            async_render_context.resolve_url("/app.js"),
+            Url::parse("http://example.com/app.js")
+        );
+        assert_eq!(
+            async_render_context.resolve_url("http://cdn.example.com/app.js"),
+            Url::parse("http://cdn.example.com/app.js")
+        );
+    }
+
+    fn names(ready: Vec<(String, ReadyScript)>) -> Vec<String> {
+        ready.into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn take_ready_order() {
+        let mut queue = ScriptQueue {
+            scripts: vec![
+                script(1, ScriptTiming::Defer, loaded("a")),
+                script(2, ScriptTiming::Blocking, loaded("b")),
+                script(3, ScriptTiming::Async, pending()),
+                script(4, ScriptTiming::Blocking, pending()),
+                script(5, ScriptTiming::Blocking, loaded("c")),
+                script(6, ScriptTiming::Async, loaded("d")),
+                script(7, ScriptTiming::Defer, ScriptSource::Failed),
+                script(8, ScriptTiming::Defer, loaded("e")),
+            ],
+        };
+        assert_eq!(names(queue.take_ready()), vec!["2", "6"]);
+        assert!(queue.take_ready().is_empty());
+
+        queue.scripts[3].source = loaded("f");
+        assert_eq!(names(queue.take_ready()), vec!["4", "5", "1", "8"]);
+
+        assert!(queue.has_run_document_scripts());
+        assert!(!queue.has_run_all());
+
+        queue.scripts[2].source = ScriptSource::Failed;
+        assert!(queue.take_ready().is_empty());
+        assert!(queue.has_run_all());
+    }
+}
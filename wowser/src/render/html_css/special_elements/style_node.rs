@@ -21,9 +21,10 @@ pub fn on_style_node(
 
         if let ElementContents::Text(TextHtmlNode { text, .. }) = &element.children[0] {
             let css_document = css::parse_css(text).ok()?;
-            async_render_context.css_documents.insert(
+            async_render_context.insert_css_document(
                 element.get_id().to_string(),
-                (element.document_offset, css_document),
+                element.document_offset,
+                css_document,
             );
         }
         None
@@ -122,11 +122,14 @@ fn render(
     } else {
         StyleNode::new_default(StyleNodeDisplay::Inline)
     };
+    style_node.element_id = Some(element.get_id());
 
     style_node.child = match element {
         ElementContents::Element(element_node) => match element_node.tag_name.as_str() {
             "img" => special_elements::on_img_node(element_node, async_render_context),
             "link" => special_elements::on_link_node(element_node, async_render_context),
+            // Scripts are queued when the document loads or they are inserted, and are not drawn
+            "script" => StyleNodeChild::Nodes(vec![]),
             "style" => special_elements::on_style_node(element_node, async_render_context),
             _ => StyleNodeChild::Nodes(
                 element_node
@@ -26,6 +26,11 @@ impl Rect {
     pub fn area(&self) -> f32 {
         self.width * self.height
     }
+
+    /// Whether the point is within the rect, including its top and left edges
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.left() && x < self.right() && y >= self.top() && y < self.bottom()
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +65,15 @@ mod tests {
         assert_eq!(1_f32, RECT.left());
     }
 
+    #[test]
+    fn rect_contains() {
+        assert!(RECT.contains(1_f32, 2_f32));
+        assert!(RECT.contains(3.5_f32, 5.5_f32));
+        assert!(!RECT.contains(4_f32, 3_f32));
+        assert!(!RECT.contains(2_f32, 6_f32));
+        assert!(!RECT.contains(0_f32, 3_f32));
+    }
+
     #[test]
     fn rect_area() {
         assert_eq!(12_f32, RECT.area());
@@ -8,11 +8,21 @@ use super::{
 /// This is the default font used for rendering
 const DEFAULT_FONT_BYTES: &[u8] = include_bytes!("../../data/gohufont-11.bdf");
 
+/// Lays out the style nodes as a scene, leaving out the nodes that would draw nothing
 pub fn style_to_scene(
     style_node: &StyleNode,
     parent_left: f32,
     parent_width: f32,
 ) -> Vec<SceneNode> {
+    layout_scene(style_node, parent_left, parent_width)
+        .into_iter()
+        .filter(SceneNode::is_visible)
+        .collect()
+}
+
+/// Lays out the style nodes as a scene, including the nodes that draw nothing. Finding what is
+/// under a point needs these, such as an element without a background.
+pub fn layout_scene(style_node: &StyleNode, parent_left: f32, parent_width: f32) -> Vec<SceneNode> {
     let mut font: CachingFont = CachingFont::wrap(Box::new(
         BDFFont::load(DEFAULT_FONT_BYTES).expect("Unable to load default font"),
     ));
@@ -24,20 +34,6 @@ pub fn style_to_scene(
         0_f32,
         &mut font,
     )
-    .into_iter()
-    .filter(|node| match node {
-        SceneNode::RectangleSceneNode(rect_node) => {
-            !rect_node.fill.is_transparent()
-                || !rect_node.fill_pixels.is_empty()
-                || !rect_node.border_color.is_transparent()
-                || rect_node.bounds.width == 0_f32
-                || rect_node.bounds.height == 0_f32
-        }
-        SceneNode::TextSceneNode(text_node) => {
-            !text_node.text_color.is_transparent() || text_node.text.is_empty()
-        }
-    })
-    .collect()
 }
 
 // Notes:
@@ -97,6 +93,7 @@ fn style_to_scene_r(
         fill_pixels: vec![],
         border_color: style_node.border_color,
         border_width: style_node.border_width,
+        element_id: style_node.element_id,
     });
 
     let base_child_offset = &root_offset
@@ -109,7 +106,8 @@ fn style_to_scene_r(
     match &style_node.child {
         StyleNodeChild::Text(text) => {
             if text.affects_layout() {
-                let mut child_text: SceneNode = text_style_to_scene(text, &child_offset, font);
+                let mut child_text: SceneNode =
+                    text_style_to_scene(text, &child_offset, style_node, font);
                 if let SceneNode::TextSceneNode(text_node) = &mut child_text {
                     if text_node.bounds.right() > parent_left + parent_width
                         && text_node.bounds.x != 0_f32
@@ -146,6 +144,7 @@ fn style_to_scene_r(
                 border_width: 0_f32,
                 fill: Color::TRANSPARENT,
                 fill_pixels: native.pixels.clone(),
+                element_id: style_node.element_id,
             });
 
             root.mut_bounds().height += native.height as f32;
@@ -219,6 +218,7 @@ fn style_to_scene_r(
 fn text_style_to_scene(
     node: &TextStyleNode,
     offset: &Point<f32>,
+    parent: &StyleNode,
     font: &mut CachingFont,
 ) -> SceneNode {
     SceneNode::TextSceneNode(TextSceneNode {
@@ -231,6 +231,7 @@ fn text_style_to_scene(
         text: node.text.clone(),
         font_size: node.font_size,
         text_color: node.text_color,
+        element_id: parent.element_id,
     })
 }
 
@@ -1,7 +1,9 @@
+use crate::html::ElementContentsId;
+
 use super::{color::Color, rect::Rect};
 
 /// An abstract representation of a scene to draw. This should be renderable onto a 2D canvas.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum SceneNode {
     TextSceneNode(TextSceneNode),
     RectangleSceneNode(RectangleSceneNode),
@@ -21,21 +23,58 @@ impl SceneNode {
             Self::RectangleSceneNode(RectangleSceneNode { bounds, .. }) => bounds,
         }
     }
+
+    /// Whether drawing the node changes anything. Empty nodes are kept since they may still affect
+    /// layout.
+    pub fn is_visible(&self) -> bool {
+        match self {
+            Self::RectangleSceneNode(rect_node) => {
+                !rect_node.fill.is_transparent()
+                    || !rect_node.fill_pixels.is_empty()
+                    || !rect_node.border_color.is_transparent()
+                    || rect_node.bounds.width == 0_f32
+                    || rect_node.bounds.height == 0_f32
+            }
+            Self::TextSceneNode(text_node) => {
+                !text_node.text_color.is_transparent() || text_node.text.is_empty()
+            }
+        }
+    }
+
+    /// The node of the document the scene node was drawn for, if any
+    pub fn element_id(&self) -> Option<ElementContentsId> {
+        match self {
+            Self::TextSceneNode(TextSceneNode { element_id, .. }) => *element_id,
+            Self::RectangleSceneNode(RectangleSceneNode { element_id, .. }) => *element_id,
+        }
+    }
+}
+
+/// Finds the node of the document drawn on top at the point, which is the last scene node drawn
+/// with bounds containing it
+pub fn hit_test(scene_nodes: &[SceneNode], x: f32, y: f32) -> Option<ElementContentsId> {
+    scene_nodes
+        .iter()
+        .rev()
+        .filter(|node| node.bounds().contains(x, y))
+        .find_map(SceneNode::element_id)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TextSceneNode {
     pub bounds: Rect,
     pub text: String,
     pub font_size: f32,
     pub text_color: Color,
+    pub element_id: Option<ElementContentsId>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RectangleSceneNode {
     pub bounds: Rect,
     pub fill: Color,
     pub fill_pixels: Vec<Color>,
     pub border_color: Color,
     pub border_width: f32,
+    pub element_id: Option<ElementContentsId>,
 }
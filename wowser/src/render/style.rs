@@ -1,11 +1,15 @@
 use std::fmt::Debug;
 
+use crate::html::ElementContentsId;
+
 use super::color::Color;
 
 /// Represents a DOM node after attaching all applicable CSS styles.
 #[derive(Debug, PartialEq)]
 pub struct StyleNode {
     pub display: StyleNodeDisplay,
+    /// The node of the document the style node was created for, if any
+    pub element_id: Option<ElementContentsId>,
     pub border_width: f32,
     pub border_color: Color,
     pub background_color: Color,
@@ -19,6 +23,7 @@ impl StyleNode {
     pub fn new_default(display: StyleNodeDisplay) -> Self {
         StyleNode {
             display,
+            element_id: None,
             border_width: 0_f32,
             border_color: Color::TRANSPARENT,
             background_color: Color::TRANSPARENT,
@@ -114,6 +119,7 @@ pub enum StyleNodeChild {
 /// following html layout rules suitable for simple rendering.
 pub fn normalize_style_nodes(style_node: &mut StyleNode) -> &mut StyleNode {
     let background_color = style_node.background_color;
+    let element_id = style_node.element_id;
     match &mut style_node.child {
         StyleNodeChild::Text(text_style_node) => {
             // Split text on whitespace to enable word wrapping
@@ -126,6 +132,7 @@ pub fn normalize_style_nodes(style_node: &mut StyleNode) -> &mut StyleNode {
                             node.text = chunk.to_string() + " ";
                             StyleNode {
                                 display: StyleNodeDisplay::Inline,
+                                element_id,
                                 border_width: 0_f32,
                                 border_color: Color::TRANSPARENT,
                                 background_color,
@@ -197,6 +204,7 @@ mod tests {
         fn inline_children_all_inline() {
             let mut style_node = StyleNode {
                 display: StyleNodeDisplay::Inline,
+                element_id: None,
                 border_width: 1_f32,
                 border_color: Color::RED,
                 background_color: Color::BLUE,
@@ -214,6 +222,7 @@ mod tests {
             assert_eq!(
                 StyleNode {
                     display: StyleNodeDisplay::Inline,
+                    element_id: None,
                     border_width: 1_f32,
                     border_color: Color::RED,
                     background_color: Color::BLUE,
@@ -233,6 +242,7 @@ mod tests {
         fn inline_children_all_block() {
             let mut style_node = StyleNode {
                 display: StyleNodeDisplay::Inline,
+                element_id: None,
                 border_width: 1_f32,
                 border_color: Color::RED,
                 background_color: Color::BLUE,
@@ -250,6 +260,7 @@ mod tests {
             assert_eq!(
                 StyleNode {
                     display: StyleNodeDisplay::Inline,
+                    element_id: None,
                     border_width: 1_f32,
                     border_color: Color::RED,
                     background_color: Color::BLUE,
@@ -269,6 +280,7 @@ mod tests {
         fn inline_children_mixed() {
             let mut style_node = StyleNode {
                 display: StyleNodeDisplay::Inline,
+                element_id: None,
                 border_width: 1_f32,
                 border_color: Color::RED,
                 background_color: Color::BLUE,
@@ -287,6 +299,7 @@ mod tests {
             assert_eq!(
                 StyleNode {
                     display: StyleNodeDisplay::Inline,
+                    element_id: None,
                     border_width: 1_f32,
                     border_color: Color::RED,
                     background_color: Color::BLUE,
@@ -296,6 +309,7 @@ mod tests {
                     child: StyleNodeChild::Nodes(vec!(
                         StyleNode {
                             display: StyleNodeDisplay::Block,
+                            element_id: None,
                             border_width: 0_f32,
                             border_color: Color::TRANSPARENT,
                             background_color: Color::TRANSPARENT,
@@ -309,6 +323,7 @@ mod tests {
                         StyleNode::new_default(StyleNodeDisplay::Block),
                         StyleNode {
                             display: StyleNodeDisplay::Block,
+                            element_id: None,
                             border_width: 0_f32,
                             border_color: Color::TRANSPARENT,
                             background_color: Color::TRANSPARENT,
@@ -329,6 +344,7 @@ mod tests {
         fn splits_strings() {
             let mut style_node = StyleNode {
                 display: StyleNodeDisplay::Inline,
+                element_id: None,
                 border_width: 1_f32,
                 border_color: Color::RED,
                 background_color: Color::BLUE,
@@ -348,6 +364,7 @@ mod tests {
                 style_node,
                 StyleNode {
                     display: StyleNodeDisplay::Inline,
+                    element_id: None,
                     border_width: 1_f32,
                     border_color: Color::RED,
                     background_color: Color::BLUE,
@@ -357,6 +374,7 @@ mod tests {
                     child: StyleNodeChild::Nodes(vec!(
                         StyleNode {
                             display: StyleNodeDisplay::Inline,
+                            element_id: None,
                             border_width: 0_f32,
                             border_color: Color::TRANSPARENT,
                             background_color: Color::BLUE,
@@ -371,6 +389,7 @@ mod tests {
                         },
                         StyleNode {
                             display: StyleNodeDisplay::Inline,
+                            element_id: None,
                             border_width: 0_f32,
                             border_color: Color::TRANSPARENT,
                             background_color: Color::BLUE,
@@ -385,6 +404,7 @@ mod tests {
                         },
                         StyleNode {
                             display: StyleNodeDisplay::Inline,
+                            element_id: None,
                             border_width: 0_f32,
                             border_color: Color::TRANSPARENT,
                             background_color: Color::BLUE,
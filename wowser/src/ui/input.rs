@@ -0,0 +1,204 @@
+use std::os::raw::c_int;
+
+use wowser_glfw as glfw;
+use wowser_glfw_sys::*;
+
+pub use glfw::Modifiers;
+
+/// Mouse and keyboard input to a window. Positions are in pixels from the top left of the window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiInputEvent {
+    MouseMove {
+        x: f32,
+        y: f32,
+    },
+    /// Buttons are numbered as they are for DOM mouse events, where the main button is 0 and the
+    /// secondary button is 2
+    MouseButton {
+        button: u16,
+        pressed: bool,
+        x: f32,
+        y: f32,
+        modifiers: Modifiers,
+    },
+    /// Keys are described by the value they produce, such as `a` or `Enter`, and the physical key
+    /// they are, such as `KeyA`, as they are for DOM keyboard events
+    Key {
+        key: String,
+        code: String,
+        pressed: bool,
+        repeat: bool,
+        modifiers: Modifiers,
+    },
+}
+
+impl UiInputEvent {
+    pub fn from_glfw(event: glfw::InputEvent) -> Option<Self> {
+        Some(match event {
+            glfw::InputEvent::CursorMove { x, y } => UiInputEvent::MouseMove {
+                x: x as f32,
+                y: y as f32,
+            },
+            glfw::InputEvent::MouseButton {
+                button,
+                action,
+                modifiers,
+                x,
+                y,
+            } => UiInputEvent::MouseButton {
+                button: get_mouse_button(button)?,
+                pressed: action != glfw::InputAction::Release,
+                x: x as f32,
+                y: y as f32,
+                modifiers,
+            },
+            glfw::InputEvent::Key {
+                key,
+                scancode,
+                action,
+                modifiers,
+            } => {
+                let code = get_key_code(key)?;
+                let key = match glfw::get_key_name(key, scancode) {
+                    Some(name) if modifiers.shift => name.to_uppercase(),
+                    Some(name) => name,
+                    None => get_named_key(key).unwrap_or(code).to_string(),
+                };
+                UiInputEvent::Key {
+                    key,
+                    code: code.to_string(),
+                    pressed: action != glfw::InputAction::Release,
+                    repeat: action == glfw::InputAction::Repeat,
+                    modifiers,
+                }
+            }
+        })
+    }
+}
+
+fn get_mouse_button(button: c_int) -> Option<u16> {
+    match button as u32 {
+        GLFW_MOUSE_BUTTON_LEFT => Some(0),
+        GLFW_MOUSE_BUTTON_MIDDLE => Some(1),
+        GLFW_MOUSE_BUTTON_RIGHT => Some(2),
+        GLFW_MOUSE_BUTTON_4 => Some(3),
+        GLFW_MOUSE_BUTTON_5 => Some(4),
+        _ => None,
+    }
+}
+
+/// The value of keys that are not printable
+fn get_named_key(key: c_int) -> Option<&'static str> {
+    Some(match key as u32 {
+        GLFW_KEY_SPACE => " ",
+        GLFW_KEY_LEFT_SHIFT | GLFW_KEY_RIGHT_SHIFT => "Shift",
+        GLFW_KEY_LEFT_CONTROL | GLFW_KEY_RIGHT_CONTROL => "Control",
+        GLFW_KEY_LEFT_ALT | GLFW_KEY_RIGHT_ALT => "Alt",
+        GLFW_KEY_LEFT_SUPER | GLFW_KEY_RIGHT_SUPER => "Meta",
+        GLFW_KEY_KP_ENTER => "Enter",
+        _ => return None,
+    })
+}
+
+/// The physical key, which does not depend on the keyboard layout or modifiers. Keys without a
+/// code are not reported.
+fn get_key_code(key: c_int) -> Option<&'static str> {
+    const LETTERS: [&str; 26] = [
+        "KeyA", "KeyB", "KeyC", "KeyD", "KeyE", "KeyF", "KeyG", "KeyH", "KeyI", "KeyJ", "KeyK",
+        "KeyL", "KeyM", "KeyN", "KeyO", "KeyP", "KeyQ", "KeyR", "KeyS", "KeyT", "KeyU", "KeyV",
+        "KeyW", "KeyX", "KeyY", "KeyZ",
+    ];
+    const DIGITS: [&str; 10] = [
+        "Digit0", "Digit1", "Digit2", "Digit3", "Digit4", "Digit5", "Digit6", "Digit7", "Digit8",
+        "Digit9",
+    ];
+    const FUNCTION_KEYS: [&str; 12] = [
+        "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+    ];
+    let key = key as u32;
+    Some(match key {
+        GLFW_KEY_A..=GLFW_KEY_Z => LETTERS[(key - GLFW_KEY_A) as usize],
+        GLFW_KEY_0..=GLFW_KEY_9 => DIGITS[(key - GLFW_KEY_0) as usize],
+        GLFW_KEY_F1..=GLFW_KEY_F12 => FUNCTION_KEYS[(key - GLFW_KEY_F1) as usize],
+        GLFW_KEY_SPACE => "Space",
+        GLFW_KEY_APOSTROPHE => "Quote",
+        GLFW_KEY_COMMA => "Comma",
+        GLFW_KEY_MINUS => "Minus",
+        GLFW_KEY_PERIOD => "Period",
+        GLFW_KEY_SLASH => "Slash",
+        GLFW_KEY_SEMICOLON => "Semicolon",
+        GLFW_KEY_EQUAL => "Equal",
+        GLFW_KEY_LEFT_BRACKET => "BracketLeft",
+        GLFW_KEY_BACKSLASH => "Backslash",
+        GLFW_KEY_RIGHT_BRACKET => "BracketRight",
+        GLFW_KEY_GRAVE_ACCENT => "Backquote",
+        GLFW_KEY_ESCAPE => "Escape",
+        GLFW_KEY_ENTER => "Enter",
+        GLFW_KEY_KP_ENTER => "NumpadEnter",
+        GLFW_KEY_TAB => "Tab",
+        GLFW_KEY_BACKSPACE => "Backspace",
+        GLFW_KEY_INSERT => "Insert",
+        GLFW_KEY_DELETE => "Delete",
+        GLFW_KEY_RIGHT => "ArrowRight",
+        GLFW_KEY_LEFT => "ArrowLeft",
+        GLFW_KEY_DOWN => "ArrowDown",
+        GLFW_KEY_UP => "ArrowUp",
+        GLFW_KEY_PAGE_UP => "PageUp",
+        GLFW_KEY_PAGE_DOWN => "PageDown",
+        GLFW_KEY_HOME => "Home",
+        GLFW_KEY_END => "End",
+        GLFW_KEY_CAPS_LOCK => "CapsLock",
+        GLFW_KEY_LEFT_SHIFT => "ShiftLeft",
+        GLFW_KEY_RIGHT_SHIFT => "ShiftRight",
+        GLFW_KEY_LEFT_CONTROL => "ControlLeft",
+        GLFW_KEY_RIGHT_CONTROL => "ControlRight",
+        GLFW_KEY_LEFT_ALT => "AltLeft",
+        GLFW_KEY_RIGHT_ALT => "AltRight",
+        GLFW_KEY_LEFT_SUPER => "MetaLeft",
+        GLFW_KEY_RIGHT_SUPER => "MetaRight",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(get_key_code(GLFW_KEY_A as c_int), Some("KeyA"));
+        assert_eq!(get_key_code(GLFW_KEY_Z as c_int), Some("KeyZ"));
+        assert_eq!(get_key_code(GLFW_KEY_7 as c_int), Some("Digit7"));
+        assert_eq!(get_key_code(GLFW_KEY_F12 as c_int), Some("F12"));
+        assert_eq!(get_key_code(GLFW_KEY_LEFT as c_int), Some("ArrowLeft"));
+        assert_eq!(get_key_code(GLFW_KEY_UNKNOWN), None);
+    }
+
+    #[test]
+    fn test_named_keys() {
+        assert_eq!(get_named_key(GLFW_KEY_KP_ENTER as c_int), Some("Enter"));
+        assert_eq!(get_named_key(GLFW_KEY_RIGHT_SHIFT as c_int), Some("Shift"));
+        assert_eq!(get_named_key(GLFW_KEY_A as c_int), None);
+    }
+
+    #[test]
+    fn test_mouse_button() {
+        let event = UiInputEvent::from_glfw(glfw::InputEvent::MouseButton {
+            button: GLFW_MOUSE_BUTTON_RIGHT as c_int,
+            action: glfw::InputAction::Press,
+            modifiers: Modifiers::default(),
+            x: 1.0,
+            y: 2.0,
+        });
+        assert_eq!(
+            event,
+            Some(UiInputEvent::MouseButton {
+                button: 2,
+                pressed: true,
+                x: 1.0,
+                y: 2.0,
+                modifiers: Modifiers::default(),
+            })
+        );
+    }
+}
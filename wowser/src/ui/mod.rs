@@ -1,8 +1,10 @@
 mod error;
+mod input;
 mod ui_event_processor;
 mod window;
 
 pub use error::{UiError, UiResult};
+pub use input::{Modifiers, UiInputEvent};
 pub use ui_event_processor::UiEventProcessor;
 pub use window::Window;
 
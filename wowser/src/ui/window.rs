@@ -1,6 +1,6 @@
 use std::{cell::RefCell, cmp, rc::Rc};
 
-use super::{ui_event_processor::UiEventProcessor, UiError, UiResult};
+use super::{ui_event_processor::UiEventProcessor, UiError, UiInputEvent, UiResult};
 use crate::{
     render::Color,
     util::{Point, Rect},
@@ -11,6 +11,8 @@ use wowser_glfw as glfw;
 pub struct Window {
     window: Box<glfw::Window>,
     bounds: Rect<i32>,
+    /// The input received since it was last taken
+    input_events: Vec<UiInputEvent>,
 }
 
 impl Window {
@@ -31,6 +33,7 @@ impl Window {
         let window = Rc::new(RefCell::new(Window {
             window: glfw_window,
             bounds: Rect::default(),
+            input_events: vec![],
         }));
         let mut window_ref = (*window).borrow_mut();
 
@@ -138,6 +141,11 @@ impl Window {
     pub fn is_alive(&self) -> bool {
         self.window.is_alive()
     }
+
+    /// The mouse and keyboard input processed since the last call, in the order received
+    pub fn take_input_events(&mut self) -> Vec<UiInputEvent> {
+        std::mem::take(&mut self.input_events)
+    }
 }
 
 impl UiEventProcessor for Window {
@@ -152,6 +160,12 @@ impl UiEventProcessor for Window {
             self.bounds.x = x;
             self.bounds.y = y;
         }
+        self.input_events.extend(
+            self.window
+                .take_input_events()
+                .into_iter()
+                .filter_map(UiInputEvent::from_glfw),
+        );
         Ok(())
     }
 }
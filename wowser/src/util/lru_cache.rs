@@ -73,6 +73,15 @@ where
         Some(weak_value)
     }
 
+    /// Removes the item from the cache, so it is computed again the next time it is gotten
+    pub fn remove(&mut self, key: &K) -> Option<Rc<V>> {
+        let value = self.map.remove(key)?;
+        if let Some(index) = self.accessed.get_index(key) {
+            self.accessed.remove(index);
+        }
+        Some(value)
+    }
+
     fn record_access(&mut self, key: &K) {
         let accessed = &mut self.accessed;
 
@@ -101,6 +110,18 @@ mod tests {
         assert!(!cache.is_empty());
     }
 
+    #[test]
+    fn test_remove() {
+        let mut cache = LRUCache::new(NonZeroUsize::new(2).unwrap(), |v| Some(-v));
+        cache.get(&1);
+        cache.get(&2);
+        assert_eq!(cache.remove(&1).as_deref(), Some(&-1));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(-1, *cache.get(&1).unwrap().upgrade().unwrap());
+        assert_eq!(cache.len(), 2);
+    }
+
     #[test]
     fn test_failed_to_compute() {
         static mut COUNT: i32 = 0;